@@ -0,0 +1,14 @@
+<html>
+  <head>
+    <title>CCH23 Day 11 Assets</title>
+  </head>
+  <body>
+    <h1>Assets</h1>
+    <table>
+      <tr><th>Name</th><th>Size</th></tr>
+      {{#each assets}}
+      <tr><td><a href="{{href}}">{{name}}</a></td><td>{{size}} {{pluralize size "byte"}}</td></tr>
+      {{/each}}
+    </table>
+  </body>
+</html>
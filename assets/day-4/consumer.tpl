@@ -0,0 +1 @@
+{{{name}}} ate lots of candies, but also some {{{favorite_food}}}
\ No newline at end of file
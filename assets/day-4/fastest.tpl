@@ -0,0 +1 @@
+Speeding past the finish line with a strength of {{strength}} is {{{name}}}
\ No newline at end of file
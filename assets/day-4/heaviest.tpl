@@ -0,0 +1 @@
+{{{name}}} is the heaviest of the herd, tipping the scales at {{weight}}
\ No newline at end of file
@@ -0,0 +1 @@
+{{{name}}} could blast you away with a snow magic power of {{snow_magic_power}}
\ No newline at end of file
@@ -0,0 +1 @@
+{{{name}}} is standing tall with his {{antler_width}} wide antlers
\ No newline at end of file
@@ -0,0 +1,15 @@
+<html>
+  <head>
+    <title>{{status}} {{reason}}</title>
+  </head>
+  <body>
+    <h1>{{status}} {{reason}}</h1>
+    <p>{{message}}</p>
+    {{#if details}}
+    <pre>{{details}}</pre>
+    {{/if}}
+    {{#if request_id}}
+    <p><small>Request id: <code>{{request_id}}</code></small></p>
+    {{/if}}
+  </body>
+</html>
@@ -0,0 +1,25 @@
+//! ## Build Script
+//!
+
+/// Compile the project's protobuf definitions (if enabled)
+/// and emit the build info reported by `/admin/runtime`
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto");
+
+    vergen::EmitBuilder::builder()
+        .build_timestamp()
+        .git_sha(true)
+        .rustc_semver()
+        .emit()?;
+
+    #[cfg(feature = "grpc")]
+    {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+
+        tonic_build::configure()
+            .build_client(false)
+            .compile(&["proto/orders.proto"], &["proto"])?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,685 @@
+//! ## Abuse Protection
+//!
+
+// Standard Library Imports
+use core::{net::IpAddr, str::FromStr};
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+};
+
+// Third-Party Imports
+use axum::{
+    extract::{ConnectInfo, Json, Path, State},
+    http::{header, HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use shuttle_secrets::SecretStore;
+
+// Crate-Level Imports
+use crate::{
+    clock::Clock,
+    errors::{AppError, ErrorEnvelope},
+    shared::SharedState,
+    state::ShuttleAppState,
+    validation::{Validate, ValidationErrors},
+};
+
+/// The default number of strikes that gets an address banned
+const DEFAULT_STRIKE_LIMIT: u32 = 50;
+
+/// The default window (in seconds) in which strikes are counted
+const DEFAULT_WINDOW_SECS: i64 = 60;
+
+/// The default duration (in seconds) of an automatic ban
+const DEFAULT_BAN_SECS: i64 = 600;
+
+/// The strikes incurred by a request lacking valid credentials
+const AUTH_FAILURE_STRIKES: u32 = 1;
+
+/// The strikes incurred by an oversized upload
+const OVERSIZED_UPLOAD_STRIKES: u32 = 5;
+
+/// The strikes incurred by a request flagged as [`Suspicious`]
+const SUSPICIOUS_REQUEST_STRIKES: u32 = 5;
+
+/// The number of tracked addresses beyond which
+/// addresses without recent strikes are forgotten
+const MAX_TRACKED_ADDRESSES: usize = 10_000;
+
+/// The longest manual ban (in seconds), i.e. one week
+const MAX_BAN_SECS: i64 = 7 * 24 * 60 * 60;
+
+// <editor-fold desc="// Ban ...">
+
+/// A temporary ban on an address
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Ban {
+    /// the banned address
+    pub ip: IpAddr,
+    /// why the address was banned
+    pub reason: String,
+    /// when the ban took effect
+    pub since: DateTime<Utc>,
+    /// when the ban expires
+    pub until: DateTime<Utc>,
+}
+
+/// A request to (manually) ban an address
+#[derive(Debug, Deserialize)]
+pub struct BanRequest {
+    /// the address to ban
+    pub ip: IpAddr,
+    /// how long (in seconds) to ban the address for
+    #[serde(default)]
+    pub duration_secs: Option<i64>,
+    /// why the address is being banned
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+impl Validate for BanRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if self
+            .duration_secs
+            .is_some_and(|duration| !(1..=MAX_BAN_SECS).contains(&duration))
+        {
+            errors.add("duration_secs", format!("must be in 1..={MAX_BAN_SECS}"));
+        }
+
+        errors.into_result()
+    }
+}
+
+// </editor-fold desc="// Ban ...">
+
+// <editor-fold desc="// Suspicious ...">
+
+/// A response extension marking the request that produced
+/// the response as an abuse attempt (e.g. a path traversal),
+/// whatever the response's status
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Suspicious;
+
+impl Suspicious {
+    /// Mark the supplied response's request as suspicious
+    pub fn flag(response: impl IntoResponse) -> Response {
+        let mut response = response.into_response();
+
+        response.extensions_mut().insert(Self);
+        response
+    }
+}
+
+// </editor-fold desc="// Suspicious ...">
+
+// <editor-fold desc="// AbuseGuard ...">
+
+/// An address's recent strikes (and their weights), oldest first
+type Strikes = VecDeque<(DateTime<Utc>, u32)>;
+
+/// Tracks per-address abuse signals, temporarily
+/// banning addresses that produce bursts of them
+#[derive(Debug)]
+pub struct AbuseGuard {
+    /// the number of strikes that gets an address banned
+    strike_limit: u32,
+    /// the window in which strikes are counted
+    window: Duration,
+    /// the duration of an automatic ban
+    ban_duration: Duration,
+    /// recent strikes (and their weights), by address
+    strikes: RwLock<HashMap<IpAddr, Strikes>>,
+    /// active (and possibly expired) bans, by address
+    bans: RwLock<HashMap<IpAddr, Ban>>,
+    /// the guard's source of the current time
+    clock: Arc<dyn Clock>,
+}
+
+impl AbuseGuard {
+    /// Create a guard using the default limits
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            strike_limit: DEFAULT_STRIKE_LIMIT,
+            window: Duration::seconds(DEFAULT_WINDOW_SECS),
+            ban_duration: Duration::seconds(DEFAULT_BAN_SECS),
+            strikes: RwLock::default(),
+            bans: RwLock::default(),
+            clock,
+        }
+    }
+
+    /// Create a guard whose limits are read from the
+    /// service's secrets (falling back to the defaults)
+    pub fn from_secrets(secrets: &SecretStore, clock: Arc<dyn Clock>) -> Self {
+        let defaults = Self::new(clock);
+
+        Self {
+            strike_limit: Self::_secret(secrets, "ABUSE_STRIKE_LIMIT")
+                .unwrap_or(defaults.strike_limit),
+            window: Self::_secret(secrets, "ABUSE_WINDOW_SECS")
+                .map_or(defaults.window, Duration::seconds),
+            ban_duration: Self::_secret(secrets, "ABUSE_BAN_SECS")
+                .map_or(defaults.ban_duration, Duration::seconds),
+            ..defaults
+        }
+    }
+
+    fn _secret<T: FromStr + PartialOrd + Default>(secrets: &SecretStore, key: &str) -> Option<T>
+    where
+        T::Err: core::fmt::Display,
+    {
+        secrets.get(key).and_then(|value| {
+            value
+                .parse::<T>()
+                .map_err(|error| tracing::error!("ignoring {key} secret: {error}"))
+                .ok()
+                .filter(|value| {
+                    let positive = *value > T::default();
+
+                    if !positive {
+                        tracing::error!("ignoring {key} secret: must be positive");
+                    }
+
+                    positive
+                })
+        })
+    }
+
+    /// Get the active ban on the supplied address (if any)
+    pub fn ban_on(&self, ip: &IpAddr) -> Option<Ban> {
+        let now = self.clock.now();
+
+        self.bans
+            .read()
+            .unwrap()
+            .get(ip)
+            .filter(|ban| now < ban.until)
+            .cloned()
+    }
+
+    /// Get every active ban
+    pub fn bans(&self) -> Vec<Ban> {
+        let now = self.clock.now();
+        let mut bans = self.bans.write().unwrap();
+
+        bans.retain(|_, ban| now < ban.until);
+
+        let mut bans = bans.values().cloned().collect::<Vec<Ban>>();
+
+        bans.sort_by_key(|ban| ban.since);
+
+        bans
+    }
+
+    /// Ban the supplied address for the supplied duration
+    pub fn ban(&self, ip: IpAddr, duration: Duration, reason: impl ToString) -> Ban {
+        let since = self.clock.now();
+        let ban = Ban {
+            ip,
+            reason: reason.to_string(),
+            since,
+            until: since + duration,
+        };
+
+        self.strikes.write().unwrap().remove(&ip);
+        self.bans.write().unwrap().insert(ip, ban.clone());
+
+        tracing::warn!(%ip, until = %ban.until, "banned address: {}", ban.reason);
+
+        ban
+    }
+
+    /// Lift the ban on the supplied address (if any)
+    pub fn unban(&self, ip: &IpAddr) -> Option<Ban> {
+        self.bans.write().unwrap().remove(ip)
+    }
+
+    /// Forget expired bans and addresses without recent
+    /// strikes, returning the number of entries forgotten
+    pub fn prune(&self) -> usize {
+        let now = self.clock.now();
+        let cutoff = now - self.window;
+
+        let mut bans = self.bans.write().unwrap();
+        let mut strikes = self.strikes.write().unwrap();
+        let before = bans.len() + strikes.len();
+
+        bans.retain(|_, ban| now < ban.until);
+        strikes.retain(|_, strikes| strikes.back().is_some_and(|(at, _)| *at > cutoff));
+
+        before - (bans.len() + strikes.len())
+    }
+
+    /// The strikes incurred by a response with the supplied status
+    /// (or by a [`Suspicious`] request)
+    ///
+    /// Only genuine abuse signals count: the handlers' expected
+    /// rejections (e.g. Day 15's verdicts, a `404`, or a failed
+    /// validation) are ordinary answers, and server-side failures
+    /// are the service's fault rather than the requesting address'.
+    fn strike_weight(status: StatusCode, suspicious: bool) -> Option<u32> {
+        match status {
+            _ if suspicious => Some(SUSPICIOUS_REQUEST_STRIKES),
+            StatusCode::PAYLOAD_TOO_LARGE => Some(OVERSIZED_UPLOAD_STRIKES),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Some(AUTH_FAILURE_STRIKES),
+            _ => None,
+        }
+    }
+
+    /// Record the supplied address's response status (and whether
+    /// its request was [`Suspicious`]), banning the address if it
+    /// has produced too many abuse signals of late
+    pub fn record(&self, ip: IpAddr, status: StatusCode, suspicious: bool) -> Option<Ban> {
+        let weight = Self::strike_weight(status, suspicious)?;
+        let now = self.clock.now();
+        let cutoff = now - self.window;
+        let total = {
+            let mut tracked = self.strikes.write().unwrap();
+
+            if tracked.len() >= MAX_TRACKED_ADDRESSES {
+                tracked.retain(|_, strikes| strikes.back().is_some_and(|(at, _)| *at > cutoff));
+            }
+
+            let strikes = tracked.entry(ip).or_default();
+
+            while strikes.front().is_some_and(|(at, _)| *at <= cutoff) {
+                strikes.pop_front();
+            }
+
+            strikes.push_back((now, weight));
+            strikes.iter().map(|(_, weight)| weight).sum::<u32>()
+        };
+
+        (total >= self.strike_limit).then(|| {
+            self.ban(
+                ip,
+                self.ban_duration,
+                format!("{total} strikes within {}s", self.window.num_seconds()),
+            )
+        })
+    }
+
+    /// Record the supplied address's response status against both
+    /// this instance's strikes and (if the service's state is
+    /// distributed) the strikes counted by every instance,
+    /// banning the address if either total is too high
+    pub async fn record_shared(
+        &self,
+        ip: IpAddr,
+        status: StatusCode,
+        suspicious: bool,
+        shared: &SharedState,
+    ) -> Option<Ban> {
+        let ban = self.record(ip, status, suspicious);
+
+        if ban.is_some() || !shared.is_distributed() {
+            return ban;
+        }
+
+        let total = shared
+            .cache
+            .increment(
+                &format!("abuse:strikes:{ip}"),
+                Self::strike_weight(status, suspicious)?.into(),
+                self.window.to_std().unwrap_or_default(),
+            )
+            .await;
+
+        (total >= u64::from(self.strike_limit)).then(|| {
+            self.ban(
+                ip,
+                self.ban_duration,
+                format!(
+                    "{total} strikes (across instances) within {}s",
+                    self.window.num_seconds()
+                ),
+            )
+        })
+    }
+}
+
+// </editor-fold desc="// AbuseGuard ...">
+
+// <editor-fold desc="// TrustedProxies ...">
+
+/// The number of reverse proxies in front of the service, each of
+/// which appends the address it received a request from to the
+/// request's `X-Forwarded-For` header
+///
+/// Only the header's last `hops` entries can be trusted (any earlier
+/// ones are supplied by the client), so the client's address is the
+/// `hops`-th entry from the end. Without any trusted proxies (e.g.
+/// when the service is served directly), the header is ignored.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TrustedProxies {
+    /// the number of trusted proxies
+    pub hops: usize,
+}
+
+impl Default for TrustedProxies {
+    /// Trust Shuttle's proxy (and nothing else)
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl TrustedProxies {
+    /// Trust the specified number of proxies
+    pub const fn new(hops: usize) -> Self {
+        Self { hops }
+    }
+
+    /// Trust the number of proxies in the `TRUSTED_PROXY_HOPS`
+    /// secret, or the supplied fallback if it's unset (or invalid)
+    pub fn from_secrets(secrets: &SecretStore, fallback: Self) -> Self {
+        secrets
+            .get("TRUSTED_PROXY_HOPS")
+            .and_then(|value| {
+                value
+                    .parse::<usize>()
+                    .map_err(|error| tracing::error!("ignoring TRUSTED_PROXY_HOPS secret: {error}"))
+                    .ok()
+            })
+            .map_or(fallback, Self::new)
+    }
+
+    /// Determine the (originating) address of the supplied request,
+    /// falling back to the connection's peer address when there are
+    /// no trusted proxies or the header has too few entries
+    pub fn client_ip(&self, headers: &HeaderMap, peer: Option<&SocketAddr>) -> Option<IpAddr> {
+        let Some(nth) = self.hops.checked_sub(1) else {
+            return peer.map(SocketAddr::ip);
+        };
+
+        headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect::<Vec<&str>>()
+            .into_iter()
+            .rev()
+            .nth(nth)
+            .map_or_else(
+                || peer.map(SocketAddr::ip),
+                |value| value.trim().parse::<IpAddr>().ok(),
+            )
+    }
+}
+
+// </editor-fold desc="// TrustedProxies ...">
+
+/// Middleware that rejects requests from banned addresses
+/// and records the outcome of everyone else's
+pub async fn guard_against_abuse<B: Send + 'static>(
+    State(state): State<ShuttleAppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(peer)| peer);
+
+    let Some(ip) = state.proxies.client_ip(request.headers(), peer) else {
+        return next.run(request).await;
+    };
+
+    if let Some(ban) = state.abuse.ban_on(&ip) {
+        let retry_after = ((ban.until - state.clock.now()).num_milliseconds() + 999) / 1000;
+
+        return (
+            [(header::RETRY_AFTER, retry_after.max(1).to_string())],
+            ErrorEnvelope::new(StatusCode::FORBIDDEN, "address temporarily banned"),
+        )
+            .into_response();
+    }
+
+    let response = next.run(request).await;
+    let suspicious = response.extensions().get::<Suspicious>().is_some();
+
+    state
+        .abuse
+        .record_shared(ip, response.status(), suspicious, &state.shared)
+        .await;
+
+    response
+}
+
+/// List the active address bans
+#[tracing::instrument(skip_all)]
+pub async fn list_bans(State(state): State<ShuttleAppState>) -> Json<Vec<Ban>> {
+    Json(state.abuse.bans())
+}
+
+/// Manually ban an address
+#[tracing::instrument(skip(state))]
+pub async fn create_ban(
+    State(state): State<ShuttleAppState>,
+    Json(request): Json<BanRequest>,
+) -> Result<(StatusCode, Json<Ban>), AppError> {
+    request.validate()?;
+
+    let ban = state.abuse.ban(
+        request.ip,
+        request
+            .duration_secs
+            .map_or(state.abuse.ban_duration, Duration::seconds),
+        request
+            .reason
+            .as_deref()
+            .unwrap_or("banned by an administrator"),
+    );
+
+    Ok((StatusCode::CREATED, Json(ban)))
+}
+
+/// Lift the ban on an address
+#[tracing::instrument(skip(state))]
+pub async fn delete_ban(
+    State(state): State<ShuttleAppState>,
+    Path(ip): Path<IpAddr>,
+) -> Result<Json<Ban>, AppError> {
+    state
+        .abuse
+        .unban(&ip)
+        .map(Json)
+        .ok_or_else(|| ErrorEnvelope::new(StatusCode::NOT_FOUND, format!("no ban on: {ip}")).into())
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use core::net::IpAddr;
+    use std::{collections::BTreeMap, sync::Arc};
+
+    // Third-Party Imports
+    use axum::{
+        body::Body,
+        http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+        middleware::from_fn_with_state,
+        routing::{self, Router},
+    };
+    use chrono::Duration;
+    use pretty_assertions::{assert_eq, assert_ne};
+    use rstest::rstest;
+    use shuttle_secrets::SecretStore;
+
+    // Crate-Level Imports
+    use super::{AbuseGuard, TrustedProxies};
+    use crate::clock::FakeClock;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{state, TestService};
+    use crate::{router, routes};
+
+    fn guard(clock: &Arc<FakeClock>, limit: &str) -> AbuseGuard {
+        AbuseGuard::from_secrets(
+            &SecretStore::new(BTreeMap::from([
+                (
+                    String::from("ABUSE_STRIKE_LIMIT"),
+                    String::from(limit).into(),
+                ),
+                (String::from("ABUSE_WINDOW_SECS"), String::from("60").into()),
+                (String::from("ABUSE_BAN_SECS"), String::from("300").into()),
+            ])),
+            clock.clone(),
+        )
+    }
+
+    /// Test that `client_ip` trusts only the `X-Forwarded-For`
+    /// entries appended by trusted proxies (or the peer address)
+    #[rstest]
+    #[case::proxied(1, Some("203.0.113.7"), None, Some("203.0.113.7"))]
+    #[case::spoofed(1, Some("10.0.0.1, 203.0.113.7"), None, Some("203.0.113.7"))]
+    #[case::two_hops(2, Some("10.0.0.1, 203.0.113.7, 10.1.0.2"), None, Some("203.0.113.7"))]
+    #[case::too_few_entries(2, Some("203.0.113.7"), Some("198.51.100.2:443"), Some("198.51.100.2"))]
+    #[case::unproxied(0, Some("203.0.113.7"), Some("198.51.100.2:443"), Some("198.51.100.2"))]
+    #[case::ipv6(1, Some("2001:db8::1"), None, Some("2001:db8::1"))]
+    #[case::garbage(1, Some("sleigh"), None, None)]
+    #[case::direct(1, None, Some("198.51.100.2:443"), Some("198.51.100.2"))]
+    #[case::unknown(1, None, None, None)]
+    #[test_log::test]
+    fn test_client_ip(
+        #[case] hops: usize,
+        #[case] forwarded: Option<&str>,
+        #[case] peer: Option<&str>,
+        #[case] expected: Option<&str>,
+    ) {
+        let mut headers = HeaderMap::new();
+
+        if let Some(forwarded) = forwarded {
+            headers.insert("x-forwarded-for", HeaderValue::from_str(forwarded).unwrap());
+        }
+
+        assert_eq!(
+            expected.map(|ip| ip.parse::<IpAddr>().unwrap()),
+            TrustedProxies::new(hops)
+                .client_ip(&headers, peer.map(|peer| peer.parse().unwrap()).as_ref())
+        );
+    }
+
+    /// Test that bursts of abuse signals (but not successes,
+    /// ordinary rejections, server-side failures, or stale
+    /// signals) get an address banned until the ban expires
+    #[rstest]
+    #[test_log::test]
+    fn test_abuse_bursts_are_banned() {
+        let clock = Arc::new(FakeClock::new("2023-12-25T00:00:00Z".parse().unwrap()));
+        let guard = guard(&clock, "6");
+        let ip = "203.0.113.7".parse().unwrap();
+
+        for status in [
+            StatusCode::OK,
+            StatusCode::BAD_REQUEST,
+            StatusCode::NOT_FOUND,
+            StatusCode::UNPROCESSABLE_ENTITY,
+            StatusCode::FAILED_DEPENDENCY,
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::BAD_GATEWAY,
+        ] {
+            for _ in 0..10 {
+                assert_eq!(None, guard.record(ip, status, false));
+            }
+        }
+
+        for _ in 0..5 {
+            assert_eq!(None, guard.record(ip, StatusCode::UNAUTHORIZED, false));
+        }
+
+        clock.advance(Duration::seconds(61));
+
+        assert_eq!(None, guard.record(ip, StatusCode::FORBIDDEN, false));
+        assert_eq!(None, guard.ban_on(&ip));
+
+        let ban = guard
+            .record(ip, StatusCode::PAYLOAD_TOO_LARGE, false)
+            .unwrap();
+
+        assert_eq!(Some(&ban), guard.ban_on(&ip).as_ref());
+        assert_eq!(vec![ban], guard.bans());
+
+        clock.advance(Duration::seconds(300));
+
+        assert_eq!(None, guard.ban_on(&ip));
+        assert!(guard.bans().is_empty());
+
+        assert_eq!(None, guard.record(ip, StatusCode::NOT_FOUND, true));
+        assert!(guard.record(ip, StatusCode::NOT_FOUND, true).is_some());
+    }
+
+    /// Test that a run of Day 15's (4xx) password
+    /// verdicts doesn't get the client banned
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_day_15_verdicts_are_not_abuse(state: ShuttleAppState) -> anyhow::Result<()> {
+        let ip = "203.0.113.7".parse().unwrap();
+
+        for attempt in 0..(2 * super::DEFAULT_STRIKE_LIMIT) {
+            let response = TestService::from(router(state.clone()))
+                .resolve(
+                    Request::post(routes::day15::game())
+                        .header("x-forwarded-for", "203.0.113.7")
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from(format!(r#"{{"input": "naughty {attempt}"}}"#)))?,
+                )
+                .await?;
+
+            assert!(response.status().is_client_error());
+        }
+
+        assert_eq!(None, state.abuse.ban_on(&ip));
+
+        Ok(())
+    }
+
+    /// Test that `guard_against_abuse` rejects banned addresses
+    /// and that bans can be lifted
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_guard_against_abuse(state: ShuttleAppState) -> anyhow::Result<()> {
+        let router = Router::new()
+            .route("/", routing::get(|| async { StatusCode::IM_A_TEAPOT }))
+            .layer(from_fn_with_state(
+                state.clone(),
+                super::guard_against_abuse,
+            ))
+            .with_state(state.clone());
+        let ip = "203.0.113.7".parse().unwrap();
+        let request = || {
+            Request::get("/")
+                .header("x-forwarded-for", "203.0.113.7")
+                .body(Body::empty())
+        };
+
+        state.abuse.ban(ip, Duration::seconds(30), "testing");
+
+        let response = TestService::from(router.clone())
+            .resolve(request()?)
+            .await?;
+
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        assert_eq!("30", response.headers()["retry-after"]);
+
+        assert!(state.abuse.unban(&ip).is_some());
+
+        let response = TestService::from(router.clone())
+            .resolve(request()?)
+            .await?;
+
+        assert_eq!(StatusCode::IM_A_TEAPOT, response.status());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,764 @@
+//! ## Administrative Endpoints
+//!
+
+// Standard Library Imports
+use core::fmt::{Debug, Formatter, Result as FmtResult};
+use std::{
+    collections::BTreeMap,
+    sync::{atomic::Ordering, Arc},
+};
+
+// Third-Party Imports
+use axum::extract::{Json, State};
+use b64::{engine::general_purpose::STANDARD as base64, Engine};
+use futures::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use shuttle_secrets::SecretStore;
+use sqlx::{
+    postgres::{PgColumn, PgRow},
+    Column, FromRow, Row, TypeInfo,
+};
+
+// Crate-Level Imports
+use crate::{
+    errors::AppError,
+    state::{InitReport, ShuttleAppState},
+    telemetry::TracingSettings,
+    utils::pg_json_value,
+    validation::{Validate, ValidationErrors},
+    version::BuildInfo,
+};
+
+/// The statement types an ad-hoc [`SqlQuery`] may begin with
+const READ_ONLY_STATEMENTS: [&str; 6] = ["select", "with", "values", "table", "explain", "show"];
+
+/// The number of rows an ad-hoc [`SqlQuery`]
+/// returns when it doesn't specify a cap
+const DEFAULT_SQL_ROW_CAP: u32 = 100;
+
+/// The largest row cap an ad-hoc [`SqlQuery`] may specify
+const MAX_SQL_ROW_CAP: u32 = 1000;
+
+/// The time (in milliseconds) after which
+/// an ad-hoc [`SqlQuery`] is cancelled
+const SQL_STATEMENT_TIMEOUT_MS: u32 = 5000;
+
+// <editor-fold desc="// AdminAuth ...">
+
+/// The (bearer) token guarding the service's administrative endpoints
+#[derive(Clone, Default)]
+pub struct AdminAuth(Option<Arc<str>>);
+
+impl Debug for AdminAuth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("AdminAuth")
+            .field(&self.0.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl AdminAuth {
+    /// Require the supplied token for access to the administrative
+    /// endpoints, or disable them entirely if there is none
+    pub fn new(token: Option<&str>) -> Self {
+        Self(token.map(Arc::from))
+    }
+
+    /// Require the `ADMIN_TOKEN` secret for access to the
+    /// administrative endpoints, or disable them if it's unset
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        let token = secrets.get("ADMIN_TOKEN").and_then(|token| {
+            if token.trim().is_empty() {
+                tracing::error!("ignoring ADMIN_TOKEN secret: token is blank");
+                return None;
+            }
+
+            Some(token)
+        });
+
+        if token.is_none() {
+            tracing::warn!("ADMIN_TOKEN secret unset, admin endpoints are disabled");
+        }
+
+        Self::new(token.as_deref())
+    }
+
+    /// Check whether an admin token is configured
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Check whether the supplied `Authorization`
+    /// header value grants administrative access
+    pub fn permits(&self, authorization: Option<&str>) -> bool {
+        let Some(expected) = self.0.as_deref() else {
+            return false;
+        };
+
+        let Some(supplied) = authorization.and_then(|value| value.strip_prefix("Bearer ")) else {
+            return false;
+        };
+
+        _constant_time_eq(supplied.as_bytes(), expected.as_bytes())
+    }
+}
+
+// </editor-fold desc="// AdminAuth ...">
+
+// <editor-fold desc="// BasicAuth ...">
+
+/// The (`username:password`) credentials guarding the
+/// service's browsable [static assets](crate::assets)
+#[derive(Clone, Default)]
+pub struct BasicAuth(Option<Arc<str>>);
+
+impl Debug for BasicAuth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("BasicAuth")
+            .field(&self.0.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl BasicAuth {
+    /// Require the supplied (`username:password`) credentials,
+    /// or disable whatever they guard if there are none
+    pub fn new(credentials: Option<&str>) -> Self {
+        Self(credentials.map(Arc::from))
+    }
+
+    /// Require the `ASSETS_CREDENTIALS` secret (if
+    /// set) for access to the static asset browser
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        let credentials = secrets.get("ASSETS_CREDENTIALS").and_then(|credentials| {
+            if !credentials.contains(':') {
+                tracing::error!("ignoring ASSETS_CREDENTIALS secret: expected `username:password`");
+                return None;
+            }
+
+            Some(credentials)
+        });
+
+        Self::new(credentials.as_deref())
+    }
+
+    /// Check whether any credentials are configured
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Check whether the supplied `Authorization`
+    /// header value carries the configured credentials
+    pub fn permits(&self, authorization: Option<&str>) -> bool {
+        let Some(expected) = self.0.as_deref() else {
+            return false;
+        };
+
+        let Some(supplied) = authorization
+            .and_then(|value| value.strip_prefix("Basic "))
+            .and_then(|encoded| base64.decode(encoded.trim()).ok())
+        else {
+            return false;
+        };
+
+        _constant_time_eq(&supplied, expected.as_bytes())
+    }
+}
+
+// </editor-fold desc="// BasicAuth ...">
+
+/// Compare every byte of the supplied values so the response
+/// time doesn't reveal the length of a matching prefix
+fn _constant_time_eq(supplied: &[u8], expected: &[u8]) -> bool {
+    supplied.len() == expected.len()
+        && supplied
+            .iter()
+            .zip(expected)
+            .fold(0u8, |diff, (left, right)| diff | (left ^ right))
+            == 0
+}
+
+// <editor-fold desc="// TableOverview ...">
+
+/// A summary of a single database table
+#[cfg_attr(test, derive(Eq, PartialEq))]
+#[derive(Debug, Default, FromRow, Serialize, Deserialize)]
+pub struct TableOverview {
+    /// the table's (schema-qualified) name
+    pub name: String,
+    /// the (statistics-based) approximate
+    /// number of live rows in the table
+    pub approximate_rows: i64,
+    /// the table's on-disk size (in bytes)
+    pub size_bytes: i64,
+}
+
+impl TableOverview {
+    /// Summarize every user table in the database
+    pub async fn fetch_all(db: &sqlx::PgPool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as(
+            r#"SELECT
+                schemaname || '.' || relname AS name,
+                n_live_tup AS approximate_rows,
+                pg_relation_size(relid) AS size_bytes
+              FROM pg_stat_user_tables
+              ORDER BY schemaname, relname;
+            "#,
+        )
+        .fetch_all(db)
+        .await
+    }
+}
+
+// </editor-fold desc="// TableOverview ...">
+
+// <editor-fold desc="// SqlQuery ...">
+
+/// An ad-hoc, read-only SQL query
+#[derive(Debug, Deserialize)]
+pub struct SqlQuery {
+    /// the (single) statement to execute
+    pub query: String,
+    /// the maximum number of rows to return
+    #[serde(default)]
+    pub max_rows: Option<u32>,
+}
+
+impl Validate for SqlQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+        let statement = self.statement();
+
+        // naively rejects semicolons in string literals
+        // too, which is fine for an admin console
+        if statement.contains(';') {
+            errors.add("query", "must contain exactly one statement");
+        }
+
+        let keyword = statement
+            .split(|char: char| char.is_whitespace() || char == '(')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        if !READ_ONLY_STATEMENTS.contains(&keyword.as_str()) {
+            errors.add(
+                "query",
+                format!(
+                    "must be one of: {}",
+                    READ_ONLY_STATEMENTS.join(", ").to_uppercase()
+                ),
+            );
+        }
+
+        if self
+            .max_rows
+            .is_some_and(|max_rows| !(1..=MAX_SQL_ROW_CAP).contains(&max_rows))
+        {
+            errors.add("max_rows", format!("must be in 1..={MAX_SQL_ROW_CAP}"));
+        }
+
+        errors.into_result()
+    }
+}
+
+impl SqlQuery {
+    /// The query's statement, sans surrounding
+    /// whitespace and any trailing semicolon
+    fn statement(&self) -> &str {
+        let statement = self.query.trim();
+
+        statement.strip_suffix(';').unwrap_or(statement).trim_end()
+    }
+
+    /// Execute the query inside a read-only transaction
+    /// (which is always rolled back), returning at most
+    /// the query's row cap
+    pub async fn execute(&self, db: &sqlx::PgPool) -> Result<SqlQueryResult, sqlx::Error> {
+        let max_rows = self.max_rows.unwrap_or(DEFAULT_SQL_ROW_CAP) as usize;
+        let mut transaction = db.begin().await?;
+
+        sqlx::query("SET TRANSACTION READ ONLY;")
+            .execute(&mut *transaction)
+            .await?;
+        sqlx::query(&format!(
+            "SET LOCAL statement_timeout = {SQL_STATEMENT_TIMEOUT_MS};"
+        ))
+        .execute(&mut *transaction)
+        .await?;
+
+        let rows: Vec<PgRow> = sqlx::query(self.statement())
+            .fetch(&mut *transaction)
+            .take(max_rows + 1)
+            .try_collect()
+            .await?;
+
+        transaction.rollback().await?;
+
+        Ok(SqlQueryResult::new(rows, max_rows))
+    }
+}
+
+/// A result column's name and (PostgreSQL) type
+#[cfg_attr(test, derive(Eq, PartialEq))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SqlColumn {
+    /// the column's name
+    pub name: String,
+    /// the column's type name
+    #[serde(rename = "type")]
+    pub type_name: String,
+}
+
+impl From<&PgColumn> for SqlColumn {
+    fn from(column: &PgColumn) -> Self {
+        Self {
+            name: column.name().to_string(),
+            type_name: column.type_info().name().to_string(),
+        }
+    }
+}
+
+/// The (capped) rows returned by an ad-hoc [`SqlQuery`]
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SqlQueryResult {
+    /// the result's columns
+    pub columns: Vec<SqlColumn>,
+    /// the result's rows, with values that can't
+    /// be represented as JSON reported as `null`
+    pub rows: Vec<Vec<Value>>,
+    /// whether rows beyond the cap were omitted
+    pub truncated: bool,
+}
+
+impl SqlQueryResult {
+    fn new(mut rows: Vec<PgRow>, max_rows: usize) -> Self {
+        let truncated = max_rows < rows.len();
+
+        rows.truncate(max_rows);
+
+        Self {
+            columns: rows
+                .first()
+                .map(|row| row.columns().iter().map(SqlColumn::from).collect())
+                .unwrap_or_default(),
+            rows: rows
+                .iter()
+                .map(|row| {
+                    row.columns()
+                        .iter()
+                        .map(|column| pg_json_value(row, column))
+                        .collect()
+                })
+                .collect(),
+            truncated,
+        }
+    }
+}
+
+// </editor-fold desc="// SqlQuery ...">
+
+// <editor-fold desc="// RuntimeReport ...">
+
+/// The Tokio runtime's task and queue metrics
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskMetrics {
+    /// the number of worker threads
+    pub workers: usize,
+    /// the number of tasks that are alive (spawned
+    /// but not yet completed) in the runtime
+    pub alive_tasks: usize,
+    /// the number of tasks waiting in
+    /// the runtime's global queue
+    pub global_queue_depth: usize,
+}
+
+impl TaskMetrics {
+    /// Get the current runtime's metrics
+    pub fn current() -> Self {
+        let metrics = tokio::runtime::Handle::current().metrics();
+
+        Self {
+            workers: metrics.num_workers(),
+            alive_tasks: metrics.num_alive_tasks(),
+            global_queue_depth: metrics.global_queue_depth(),
+        }
+    }
+}
+
+/// The process's memory usage
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    /// the process's resident set size (in bytes)
+    pub physical_bytes: usize,
+    /// the process's virtual memory size (in bytes)
+    pub virtual_bytes: usize,
+}
+
+impl MemoryUsage {
+    /// Get the process's current memory usage
+    /// (if the platform reports it)
+    pub fn current() -> Option<Self> {
+        memory_stats::memory_stats().map(|stats| Self {
+            physical_bytes: stats.physical_mem,
+            virtual_bytes: stats.virtual_mem,
+        })
+    }
+}
+
+/// A point-in-time snapshot of the service's process and runtime
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RuntimeReport {
+    /// the time (in seconds) since the service started
+    pub uptime_secs: u64,
+    /// the Tokio runtime's metrics
+    pub tasks: TaskMetrics,
+    /// the process's memory usage (if available)
+    pub memory: Option<MemoryUsage>,
+    /// the number of currently open websocket connections
+    pub live_sockets: u64,
+    /// the number of requests currently being handled
+    pub in_flight_requests: usize,
+    /// the number of requests shed while the service was overloaded
+    pub shed_requests: u64,
+    /// the service's build provenance
+    pub build: BuildInfo,
+}
+
+// </editor-fold desc="// RuntimeReport ...">
+
+/// Retrieve a snapshot of the service's metrics
+#[tracing::instrument(ret, skip(state))]
+pub async fn get_service_metrics(
+    State(state): State<ShuttleAppState>,
+) -> Json<BTreeMap<String, u64>> {
+    Json(state.metrics.snapshot())
+}
+
+/// Retrieve a snapshot of the service's process and runtime
+#[tracing::instrument(ret, skip(state))]
+pub async fn get_runtime_report(State(state): State<ShuttleAppState>) -> Json<RuntimeReport> {
+    Json(RuntimeReport {
+        uptime_secs: state.metrics.uptime().as_secs(),
+        tasks: TaskMetrics::current(),
+        memory: MemoryUsage::current(),
+        live_sockets: state.metrics.live_sockets.load(Ordering::Relaxed),
+        in_flight_requests: state.shedder.in_flight(),
+        shed_requests: state.shedder.shed(),
+        build: BuildInfo::default(),
+    })
+}
+
+/// Retrieve the report on what became of the
+/// service's subsystems as it was initialized
+#[tracing::instrument(ret, skip(state))]
+pub async fn get_init_report(State(state): State<ShuttleAppState>) -> Json<InitReport> {
+    Json(InitReport::clone(&state.init))
+}
+
+/// Retrieve the service's current tracing settings
+#[tracing::instrument(ret, skip(state))]
+pub async fn get_tracing_settings(State(state): State<ShuttleAppState>) -> Json<TracingSettings> {
+    Json(state.tracing.settings())
+}
+
+/// Adjust the service's tracing filter
+/// and/or span sampling rate at runtime
+#[tracing::instrument(ret, err(Debug), skip(state))]
+pub async fn update_tracing_settings(
+    State(state): State<ShuttleAppState>,
+    Json(settings): Json<TracingSettings>,
+) -> Result<Json<TracingSettings>, AppError> {
+    Ok(Json(state.tracing.apply(settings)?))
+}
+
+/// Retrieve the name, approximate row count,
+/// and on-disk size of every database table
+#[tracing::instrument(ret, err(Debug), skip(state))]
+pub async fn get_db_overview(
+    State(state): State<ShuttleAppState>,
+) -> Result<Json<Vec<TableOverview>>, AppError> {
+    Ok(Json(TableOverview::fetch_all(&state.db).await?))
+}
+
+/// Execute an ad-hoc, read-only SQL query
+#[tracing::instrument(skip(state))]
+pub async fn execute_sql_query(
+    State(state): State<ShuttleAppState>,
+    Json(query): Json<SqlQuery>,
+) -> Result<Json<SqlQueryResult>, AppError> {
+    query.validate()?;
+
+    Ok(Json(query.execute(&state.db).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use core::{cmp::PartialEq, fmt::Debug, ops::BitOr, str::FromStr};
+    use std::collections::HashMap;
+
+    // Third-Party Imports
+    use axum::{
+        body::{Body, BoxBody, HttpBody},
+        http::{
+            header as headers,
+            request::{Builder, Parts},
+            Method, Request, Response, StatusCode,
+        },
+        routing::Router,
+    };
+    use once_cell::sync::Lazy;
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use rstest::{fixture, rstest};
+    use serde_json::{error::Error as SerdeJsonError, Value};
+    use shuttle_shared_db::Postgres as ShuttleDB;
+    use tower::{MakeService, ServiceExt};
+
+    // Crate-Level Imports
+    use super::{InitReport, RuntimeReport, SqlColumn, SqlQuery, SqlQueryResult, TableOverview};
+    use crate::errors::ErrorDetail;
+    use crate::routes;
+    use crate::solutions::day_18::GiftOrderRegion;
+    use crate::state::ShuttleAppState;
+    use crate::telemetry::TracingSettings;
+    use crate::validation::Validate;
+    use crate::{
+        router,
+        utils::{admin_request, isolated_state, service, state, IsolatedState, TestService},
+    };
+
+    /// Test that `update_tracing_settings` validates and
+    /// applies the supplied tracing settings
+    #[rstest]
+    #[case::sampling_rate(r#"{"sampling_rate": 0.25}"#, StatusCode::OK, Some(0.25f64))]
+    #[case::sampling_rate_out_of_range(
+        r#"{"sampling_rate": 1.5}"#,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        None
+    )]
+    #[case::invalid_filter(r#"{"filter": "info,[{"}"#, StatusCode::UNPROCESSABLE_ENTITY, None)]
+    #[test_log::test(tokio::test)]
+    async fn test_update_tracing_settings(
+        service: TestService,
+        #[case] settings: &str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_rate: Option<f64>,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                admin_request(Method::PUT, routes::admin::tracing())
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(settings.to_string()))?,
+            )
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if let Some(expected_rate) = expected_rate {
+            let actual: TracingSettings =
+                serde_json::from_slice(response.into_body().data().await.unwrap()?.as_ref())?;
+
+            assert_eq!(Some(expected_rate), actual.sampling_rate);
+            assert!(actual.filter.is_some());
+        }
+
+        Ok(())
+    }
+
+    /// Test that `get_runtime_report` reports the
+    /// runtime's metrics and the service's build info
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_get_runtime_report(service: TestService) -> anyhow::Result<()> {
+        let response = service
+            .resolve(admin_request(Method::GET, routes::admin::runtime()).body(Body::empty())?)
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let report: RuntimeReport =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert!(0 < report.tasks.workers);
+        assert_eq!(0, report.live_sockets);
+        assert_str_eq!(env!("CARGO_PKG_VERSION"), report.build.version);
+        assert!(!report.build.rustc_version.is_empty());
+
+        Ok(())
+    }
+
+    /// Test that `get_init_report` reports the test service's
+    /// compiled templates and unset secrets (and no default
+    /// persistence path, as the fixture supplies its store)
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_get_init_report(service: TestService) -> anyhow::Result<()> {
+        let response = service
+            .resolve(admin_request(Method::GET, routes::admin::init()).body(Body::empty())?)
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let report: InitReport =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert!(!report.allow_degraded);
+        assert!(!report.is_degraded());
+        assert_eq!(Some(9), report.template_count);
+        assert_eq!(None, report.persistence_path);
+        assert!(report.missing_secrets.contains(&"ADMIN_TOKEN".to_string()));
+        assert!(report.defaulted.contains(&"admin".to_string()));
+
+        Ok(())
+    }
+
+    /// Test that `get_db_overview` lists the
+    /// tables (re)created by the schema resets
+    #[rstest]
+    #[cfg_attr(not(feature = "ci"), ignore = "requires a PostgreSQL instance")]
+    #[test_log::test(tokio::test)]
+    async fn test_get_db_overview(
+        #[future(awt)]
+        #[from(isolated_state)]
+        state: IsolatedState,
+    ) -> anyhow::Result<()> {
+        GiftOrderRegion::reset_schema(&state.db).await?;
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(admin_request(Method::GET, routes::admin::db_overview()).body(Body::empty())?)
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let tables: Vec<TableOverview> =
+            serde_json::from_slice(hyper::body::to_bytes(response.into_body()).await?.as_ref())?;
+
+        assert!(tables
+            .iter()
+            .any(|table| table.name == "cch18.regions" && table.approximate_rows == 0));
+
+        Ok(())
+    }
+
+    /// Test that `SqlQuery` only accepts single,
+    /// read-only statements and sane row caps
+    #[rstest]
+    #[case::select("SELECT 1;", None, true)]
+    #[case::cte(" with one AS (SELECT 1) SELECT * FROM one ", Some(5), true)]
+    #[case::explain("EXPLAIN SELECT * FROM orders", None, true)]
+    #[case::delete("DELETE FROM orders", None, false)]
+    #[case::stacked("SELECT 1; DROP TABLE orders", None, false)]
+    #[case::empty("  ", None, false)]
+    #[case::zero_cap("SELECT 1", Some(0), false)]
+    #[case::huge_cap("SELECT 1", Some(1_000_000), false)]
+    #[test_log::test]
+    fn test_sql_query_validation(
+        #[case] query: &str,
+        #[case] max_rows: Option<u32>,
+        #[case] expected: bool,
+    ) {
+        let query = SqlQuery {
+            query: query.to_string(),
+            max_rows,
+        };
+
+        assert_eq!(expected, query.validate().is_ok());
+    }
+
+    /// Test that `execute_sql_query` rejects non-read-only
+    /// statements without touching the database
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_execute_sql_query_rejects_writes(service: TestService) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                admin_request(Method::POST, routes::admin::sql())
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(r#"{"query": "DROP TABLE orders"}"#))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+        Ok(())
+    }
+
+    /// Test that `execute_sql_query` returns typed, capped
+    /// rows and refuses writes disguised as reads
+    #[rstest]
+    #[cfg_attr(not(feature = "ci"), ignore = "requires a PostgreSQL instance")]
+    #[test_log::test(tokio::test)]
+    async fn test_execute_sql_query(state: ShuttleAppState) -> anyhow::Result<()> {
+        let response = TestService::from(router(state.clone()))
+            .resolve(
+                admin_request(Method::POST, routes::admin::sql())
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        r#"{
+                          "query": "SELECT n, n % 2 = 0 AS even, 'gift ' || n AS label FROM generate_series(1, 5) AS n;",
+                          "max_rows": 2
+                        }"#,
+                    ))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let result: SqlQueryResult =
+            serde_json::from_slice(hyper::body::to_bytes(response.into_body()).await?.as_ref())?;
+
+        assert_eq!(
+            SqlQueryResult {
+                columns: vec![
+                    SqlColumn {
+                        name: String::from("n"),
+                        type_name: String::from("INT4"),
+                    },
+                    SqlColumn {
+                        name: String::from("even"),
+                        type_name: String::from("BOOL"),
+                    },
+                    SqlColumn {
+                        name: String::from("label"),
+                        type_name: String::from("TEXT"),
+                    },
+                ],
+                rows: vec![
+                    vec![Value::from(1), Value::from(false), Value::from("gift 1")],
+                    vec![Value::from(2), Value::from(true), Value::from("gift 2")],
+                ],
+                truncated: true,
+            },
+            result,
+        );
+
+        let response = TestService::from(router(state))
+            .resolve(
+                admin_request(Method::POST, routes::admin::sql())
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        r#"{"query": "WITH doomed AS (DELETE FROM pg_description WHERE false RETURNING 1) SELECT count(*) FROM doomed"}"#,
+                    ))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::FAILED_DEPENDENCY, response.status());
+        assert!(response
+            .extensions()
+            .get::<ErrorDetail>()
+            .is_some_and(|ErrorDetail(detail)| detail.contains("read-only transaction")));
+
+        let message = hyper::body::to_bytes(response.into_body()).await?;
+
+        assert!(!String::from_utf8_lossy(message.as_ref()).contains("read-only transaction"));
+
+        Ok(())
+    }
+}
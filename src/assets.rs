@@ -0,0 +1,372 @@
+//! ## Static Asset Manifest
+//!
+//! Every file under `assets/` is hashed (once per process) into an
+//! in-memory [`AssetManifest`], which backs the static asset handler's
+//! `ETag`s and is checked against the committed `assets/manifest.json`
+//! at startup. The `.tpl` files among them are compiled when the
+//! service's template engine is [initialized](crate::state::ShuttleAppState::initialize).
+
+// Standard Library Imports
+use core::fmt::Write;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::{Path as FilePath, PathBuf as FilePathBuf},
+    sync::{Arc, Mutex},
+};
+
+// Third-Party Imports
+use anyhow::Context;
+use axum::{body::Bytes, extract::State, http::HeaderValue, Json};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// Crate-Level Imports
+use crate::state::ShuttleAppState;
+
+/// The directory from which the service's static assets are served
+pub const ASSETS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets");
+
+/// The name (relative to [`ASSETS_DIR`]) of the committed manifest
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// The manifest of [`ASSETS_DIR`], built on first use
+static STARTUP_MANIFEST: OnceCell<Arc<AssetManifest>> = OnceCell::new();
+
+// <editor-fold desc="// AssetManifest ...">
+
+/// A single static asset's manifest entry
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct AssetEntry {
+    /// the asset's path, relative to the assets directory
+    pub name: String,
+    /// the asset's size (in bytes)
+    pub size: u64,
+    /// the (hex-encoded) SHA-256 digest of the asset's content
+    pub sha256: String,
+}
+
+impl AssetEntry {
+    /// The asset's (strong) `ETag`
+    pub fn etag(&self) -> HeaderValue {
+        HeaderValue::from_str(&format!("\"{}\"", self.sha256))
+            .expect("hex digests are valid header values")
+    }
+}
+
+/// The name, size, and digest of every static asset (ordered by name)
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct AssetManifest(Vec<AssetEntry>);
+
+impl AssetManifest {
+    /// Hash every file under the supplied directory
+    /// (save the committed manifest itself)
+    pub fn build(dir: impl AsRef<FilePath>) -> anyhow::Result<Self> {
+        let mut entries = Vec::new();
+        let mut pending = vec![dir.as_ref().to_path_buf()];
+
+        while let Some(current) = pending.pop() {
+            for entry in
+                fs::read_dir(&current).with_context(|| format!("reading {}", current.display()))?
+            {
+                let path = entry?.path();
+
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+
+                let name = _relative_name(dir.as_ref(), &path);
+
+                if name == MANIFEST_NAME {
+                    continue;
+                }
+
+                let content =
+                    fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+
+                entries.push(AssetEntry {
+                    name,
+                    size: content.len() as u64,
+                    sha256: Sha256::digest(&content)
+                        .iter()
+                        .fold(String::new(), |mut hex, byte| {
+                            let _ = write!(hex, "{byte:02x}");
+                            hex
+                        }),
+                });
+            }
+        }
+
+        entries.sort_by(|left, right| left.name.cmp(&right.name));
+
+        Ok(Self(entries))
+    }
+
+    /// Load the manifest committed alongside the supplied directory's assets
+    pub fn committed(dir: impl AsRef<FilePath>) -> anyhow::Result<Self> {
+        let path = dir.as_ref().join(MANIFEST_NAME);
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+
+        serde_json::from_str(&content).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// The manifest of [`ASSETS_DIR`], built (and
+    /// [preflight](Self::preflight) checked) on first use
+    pub fn startup() -> anyhow::Result<Arc<Self>> {
+        STARTUP_MANIFEST
+            .get_or_try_init(|| {
+                let manifest = Self::build(ASSETS_DIR)?;
+
+                match Self::committed(ASSETS_DIR) {
+                    Ok(committed) => {
+                        if let Err(differences) = manifest.preflight(&committed) {
+                            for difference in differences {
+                                tracing::warn!("asset manifest mismatch: {difference}");
+                            }
+                        }
+                    }
+                    Err(error) => tracing::warn!("no committed asset manifest: {error:?}"),
+                }
+
+                Ok(Arc::new(manifest))
+            })
+            .cloned()
+    }
+
+    /// Get the entry of the named asset (if any)
+    pub fn get(&self, name: &str) -> Option<&AssetEntry> {
+        self.0
+            .binary_search_by(|entry| entry.name.as_str().cmp(name))
+            .ok()
+            .map(|index| &self.0[index])
+    }
+
+    /// Get every entry in the manifest
+    pub fn entries(&self) -> &[AssetEntry] {
+        &self.0
+    }
+
+    /// Check the manifest against the supplied (committed) one,
+    /// describing every missing, unexpected, or altered asset
+    pub fn preflight(&self, committed: &Self) -> Result<(), Vec<String>> {
+        let mut differences = Vec::new();
+
+        for expected in committed.entries() {
+            match self.get(&expected.name) {
+                None => differences.push(format!("missing asset: {}", expected.name)),
+                Some(actual) if actual != expected => {
+                    differences.push(format!("altered asset: {}", expected.name));
+                }
+                Some(_) => {}
+            }
+        }
+
+        for actual in self.entries() {
+            if committed.get(&actual.name).is_none() {
+                differences.push(format!("unexpected asset: {}", actual.name));
+            }
+        }
+
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(differences)
+        }
+    }
+}
+
+/// The `/`-separated path of `path`, relative to `dir`
+fn _relative_name(dir: &FilePath, path: &FilePath) -> String {
+    path.strip_prefix(dir)
+        .map_or_else(|_| FilePathBuf::from(path), FilePathBuf::from)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// </editor-fold desc="// AssetManifest ...">
+
+// <editor-fold desc="// AssetCache ...">
+
+/// Retained content (by key), and their
+/// keys' recency order (least recent first)
+type CachedContent = (HashMap<String, Bytes>, VecDeque<String>);
+
+/// A bounded, least-recently-used "hot" cache of
+/// content derived from the static assets (e.g.
+/// [thumbnails](crate::solutions::day_11::serve_asset_thumbnail))
+#[derive(Debug)]
+pub struct AssetCache {
+    /// the maximum number of retained entries
+    capacity: usize,
+    /// the retained entries
+    entries: Mutex<CachedContent>,
+}
+
+impl Default for AssetCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl AssetCache {
+    /// The default maximum number of retained entries
+    const DEFAULT_CAPACITY: usize = 64;
+
+    /// Create an empty cache retaining at
+    /// most `capacity` entries
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new((HashMap::new(), VecDeque::new())),
+        }
+    }
+
+    /// Get the content cached under `key` (if any),
+    /// marking it as the most recently used
+    pub fn get(&self, key: &str) -> Option<Bytes> {
+        let (contents, recency) = &mut *self.entries.lock().unwrap();
+        let content = contents.get(key)?.clone();
+
+        if let Some(position) = recency.iter().position(|cached| cached == key) {
+            if let Some(key) = recency.remove(position) {
+                recency.push_back(key);
+            }
+        }
+
+        Some(content)
+    }
+
+    /// Retain the supplied content under `key`, evicting
+    /// the least recently used entries beyond capacity
+    pub fn insert(&self, key: String, content: Bytes) {
+        let (contents, recency) = &mut *self.entries.lock().unwrap();
+
+        if contents.insert(key.clone(), content).is_none() {
+            recency.push_back(key);
+        }
+
+        while self.capacity < recency.len() {
+            if let Some(evicted) = recency.pop_front() {
+                contents.remove(&evicted);
+            }
+        }
+    }
+
+    /// Evict every retained entry, returning the number evicted
+    pub fn clear(&self) -> usize {
+        let (contents, recency) = &mut *self.entries.lock().unwrap();
+        let evicted = contents.len();
+
+        contents.clear();
+        recency.clear();
+
+        evicted
+    }
+}
+
+// </editor-fold desc="// AssetCache ...">
+
+/// Report the name, size, and digest of every static asset
+#[tracing::instrument(skip_all)]
+pub async fn get_asset_manifest(State(state): State<ShuttleAppState>) -> Json<AssetManifest> {
+    Json(state.assets.as_ref().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Third-Party Imports
+    use axum::{body::Bytes, http::StatusCode};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use super::{AssetCache, AssetEntry, AssetManifest, ASSETS_DIR};
+    use crate::routes;
+    use crate::utils::{service, TestService};
+
+    /// Test that the committed manifest matches the shipped assets
+    #[rstest]
+    #[test_log::test]
+    fn test_committed_manifest_is_current() -> anyhow::Result<()> {
+        let manifest = AssetManifest::build(ASSETS_DIR)?;
+
+        assert_eq!(
+            Ok(()),
+            manifest.preflight(&AssetManifest::committed(ASSETS_DIR)?),
+            "regenerate assets/manifest.json from:\n{}",
+            serde_json::to_string_pretty(&manifest)?
+        );
+
+        Ok(())
+    }
+
+    /// Test that `preflight` reports every missing,
+    /// unexpected, and altered asset
+    #[rstest]
+    #[test_log::test]
+    fn test_manifest_preflight() {
+        let entry = |name: &str, sha256: &str| AssetEntry {
+            name: name.to_string(),
+            size: 1,
+            sha256: sha256.to_string(),
+        };
+
+        let committed = AssetManifest(vec![entry("a", "00"), entry("b", "00"), entry("c", "00")]);
+        let actual = AssetManifest(vec![entry("a", "00"), entry("c", "ff"), entry("d", "00")]);
+
+        assert_eq!(Ok(()), committed.preflight(&committed));
+        assert_eq!(
+            Err(vec![
+                String::from("missing asset: b"),
+                String::from("altered asset: c"),
+                String::from("unexpected asset: d"),
+            ]),
+            actual.preflight(&committed)
+        );
+    }
+
+    /// Test that the asset cache evicts its least recently used entries
+    #[rstest]
+    #[test_log::test]
+    fn test_asset_cache_eviction() {
+        let cache = AssetCache::new(2);
+
+        cache.insert(String::from("a"), Bytes::from_static(b"a"));
+        cache.insert(String::from("b"), Bytes::from_static(b"b"));
+
+        assert_eq!(Some(Bytes::from_static(b"a")), cache.get("a"));
+
+        cache.insert(String::from("c"), Bytes::from_static(b"c"));
+
+        assert_eq!(None, cache.get("b"));
+        assert!(cache.get("a").is_some() && cache.get("c").is_some());
+        assert_eq!(2, cache.clear());
+    }
+
+    /// Test that the manifest is served as-built
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_get_asset_manifest(service: TestService) -> anyhow::Result<()> {
+        let response = service.resolve(routes::asset_manifest()).await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let served: AssetManifest =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(AssetManifest::build(ASSETS_DIR)?, served);
+        assert!(served.get("decoration.png").is_some());
+
+        Ok(())
+    }
+}
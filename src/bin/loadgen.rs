@@ -0,0 +1,448 @@
+#![forbid(unsafe_code)]
+#![deny(missing_debug_implementations)]
+
+//! # [`shuttle.rs`](https://shuttle.rs/) Christmas Code Hunt 2023
+//!
+//! A binary generating synthetic traffic against a deployed instance
+//! of the service (to capacity-test it ahead of grading), replaying
+//! each day's [official examples](cch23_thewondersmith::grader) at a
+//! fixed rate while websocket chat clients join, chat in, and leave
+//! day 19's rooms. It's configured entirely by environment variables:
+//!
+//! - `LOADGEN_TARGET`: the instance's base URL (`http://127.0.0.1:8000` if unset)
+//! - `LOADGEN_DURATION`: how long to generate traffic for, in seconds (`60` if unset)
+//! - `LOADGEN_RPS`: the requests per second sent to each day, as a comma-separated
+//!   list of `day=rate` pairs (e.g. `1=5,15=2.5`), or a single rate for every day
+//!   (`1` if unset)
+//! - `LOADGEN_PAYLOAD_SCALE`: how many times over array and text payloads are
+//!   repeated, to send larger bodies than the examples' own (`1` if unset)
+//! - `LOADGEN_CHAT_CLIENTS`: the number of concurrent chat clients (`0` if unset)
+//! - `LOADGEN_CHAT_ROOMS`: the number of rooms chat clients spread across (`4` if unset)
+//!
+//! Once the run is over, each day's request count, failure count,
+//! and latency percentiles (and the chat clients' totals) are printed.
+
+// Standard Library Imports
+use std::{
+    collections::BTreeMap,
+    env,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+// Third-Party Imports
+use anyhow::Context;
+use cch23_thewondersmith::{
+    examples::Example, grader::GraderCase, routes, solutions::DAYS, telemetry::TracingControl,
+};
+use futures::{SinkExt, StreamExt};
+use rand::Rng;
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::Message;
+
+/// The instance targeted if `LOADGEN_TARGET` is unset
+const DEFAULT_TARGET: &str = "http://127.0.0.1:8000";
+
+/// The number of seconds traffic is generated for if `LOADGEN_DURATION` is unset
+const DEFAULT_DURATION: u64 = 60;
+
+/// The number of rooms chat clients spread across if `LOADGEN_CHAT_ROOMS` is unset
+const DEFAULT_CHAT_ROOMS: u64 = 4;
+
+/// The most messages a chat client sends before leaving its room
+const MAX_CHAT_MESSAGES: usize = 20;
+
+/// The longest a chat client waits between messages
+const MAX_CHAT_PAUSE: Duration = Duration::from_millis(500);
+
+// <editor-fold desc="// LoadConfig ...">
+
+/// The shape of the generated traffic
+#[derive(Debug)]
+struct LoadConfig {
+    /// the instance's base URL (sans trailing slash)
+    target: String,
+    /// how long to generate traffic for
+    duration: Duration,
+    /// the requests per second sent to each day
+    rates: BTreeMap<i8, f64>,
+    /// how many times over array and text payloads are repeated
+    payload_scale: usize,
+    /// the number of concurrent chat clients
+    chat_clients: usize,
+    /// the number of rooms chat clients spread across
+    chat_rooms: u64,
+}
+
+impl LoadConfig {
+    /// Read the configuration from the environment
+    fn from_env() -> anyhow::Result<Self> {
+        let target = env::var("LOADGEN_TARGET")
+            .unwrap_or_else(|_| DEFAULT_TARGET.to_string())
+            .trim_end_matches('/')
+            .to_string();
+
+        Ok(Self {
+            target,
+            duration: Duration::from_secs(_var("LOADGEN_DURATION")?.unwrap_or(DEFAULT_DURATION)),
+            rates: _rates(env::var("LOADGEN_RPS").ok().as_deref())?,
+            payload_scale: _var("LOADGEN_PAYLOAD_SCALE")?.unwrap_or(1).max(1),
+            chat_clients: _var("LOADGEN_CHAT_CLIENTS")?.unwrap_or(0),
+            chat_rooms: _var("LOADGEN_CHAT_ROOMS")?
+                .unwrap_or(DEFAULT_CHAT_ROOMS)
+                .max(1),
+        })
+    }
+}
+
+/// Parse the named environment variable (if it's set)
+fn _var<T: FromStr>(name: &str) -> anyhow::Result<Option<T>>
+where
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    env::var(name)
+        .ok()
+        .map(|value| value.trim().parse::<T>())
+        .transpose()
+        .with_context(|| format!("parsing {name}"))
+}
+
+/// Parse the per-day request rates (every registered day
+/// with official examples is sent `1` request per second
+/// if unset, or the single rate if only one is supplied)
+fn _rates(value: Option<&str>) -> anyhow::Result<BTreeMap<i8, f64>> {
+    let replayable = DAYS
+        .iter()
+        .map(|registration| (registration.day)())
+        .filter(|day| GraderCase::official(*day).is_some_and(|cases| !cases.is_empty()));
+
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(replayable.map(|day| (day, 1.0)).collect());
+    };
+
+    if let Ok(rate) = value.parse::<f64>() {
+        return Ok(replayable.map(|day| (day, rate)).collect());
+    }
+
+    value
+        .split(',')
+        .map(|pair| {
+            let (day, rate) = pair
+                .split_once('=')
+                .with_context(|| format!("expected `day=rate`, got {pair:?}"))?;
+
+            let day = day
+                .trim()
+                .parse::<i8>()
+                .context("parsing LOADGEN_RPS day")?;
+            let rate = rate
+                .trim()
+                .parse::<f64>()
+                .context("parsing LOADGEN_RPS rate")?;
+
+            if GraderCase::official(day).is_none() {
+                anyhow::bail!("day {day} has no official examples to replay");
+            }
+
+            Ok((day, rate))
+        })
+        .collect()
+}
+
+// </editor-fold desc="// LoadConfig ...">
+
+// <editor-fold desc="// LoadStats ...">
+
+/// The outcome of the requests sent to a day
+#[derive(Debug, Default)]
+struct DayStats {
+    /// the number of requests sent
+    sent: usize,
+    /// the number of requests that failed (or got an unexpected status)
+    failed: usize,
+    /// each request's latency (in milliseconds)
+    latencies: Vec<u64>,
+}
+
+impl DayStats {
+    /// The specified percentile of the day's latencies
+    fn percentile(&self, percentile: f64) -> u64 {
+        let mut latencies = self.latencies.clone();
+        latencies.sort_unstable();
+
+        let index = ((latencies.len() as f64 - 1.0) * percentile).round() as usize;
+
+        latencies.get(index).copied().unwrap_or_default()
+    }
+}
+
+/// The outcome of the chat clients' sessions
+#[derive(Debug, Default)]
+struct ChatStats {
+    /// the number of times a client joined a room
+    joins: usize,
+    /// the number of messages sent
+    messages: usize,
+    /// the number of sessions that failed
+    failed: usize,
+}
+
+/// The outcome of the whole run
+#[derive(Debug, Default)]
+struct LoadStats {
+    /// the outcome of each day's requests
+    days: BTreeMap<i8, DayStats>,
+    /// the outcome of the chat clients' sessions
+    chat: ChatStats,
+}
+
+impl LoadStats {
+    /// Print the run's outcome
+    fn print(&self, elapsed: Duration) {
+        println!(
+            "{:>4} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            "day", "sent", "failed", "rps", "p50 ms", "p99 ms"
+        );
+
+        for (day, stats) in &self.days {
+            println!(
+                "{day:>4} {:>8} {:>8} {:>8.1} {:>8} {:>8}",
+                stats.sent,
+                stats.failed,
+                stats.sent as f64 / elapsed.as_secs_f64(),
+                stats.percentile(0.5),
+                stats.percentile(0.99),
+            );
+        }
+
+        println!(
+            "chat: {} joins, {} messages, {} failed sessions",
+            self.chat.joins, self.chat.messages, self.chat.failed
+        );
+    }
+}
+
+// </editor-fold desc="// LoadStats ...">
+
+/// Repeat an array or text payload the specified number of times over
+fn _scaled(body: &Value, scale: usize) -> Value {
+    match body {
+        Value::Array(items) if 1 < scale => Value::Array(
+            core::iter::repeat_n(items, scale)
+                .flatten()
+                .cloned()
+                .collect(),
+        ),
+        Value::String(text) if 1 < scale => Value::String(text.repeat(scale)),
+        other => other.clone(),
+    }
+}
+
+/// Send the supplied example's request, checking only its status
+/// (as payload scaling changes what the response's body should be)
+async fn _send(
+    client: &reqwest::Client,
+    config: &LoadConfig,
+    example: &Example,
+) -> anyhow::Result<()> {
+    let method = reqwest::Method::from_bytes(example.request.method.as_bytes())?;
+    let mut request = client.request(method, format!("{}{}", config.target, example.request.path));
+
+    for (name, value) in &example.request.headers {
+        request = request.header(name, value);
+    }
+
+    request = match example
+        .request
+        .body
+        .as_ref()
+        .map(|body| _scaled(body, config.payload_scale))
+    {
+        None => request,
+        Some(Value::String(text)) => request.body(text),
+        Some(json) => request.json(&json),
+    };
+
+    let status = request.send().await?.status().as_u16();
+
+    if status == example.response.status {
+        Ok(())
+    } else {
+        anyhow::bail!("expected status {}, got {status}", example.response.status)
+    }
+}
+
+/// Replay the specified day's examples at its configured rate until the deadline
+async fn _drive_day(
+    day: i8,
+    rate: f64,
+    client: reqwest::Client,
+    config: Arc<LoadConfig>,
+    stats: Arc<Mutex<LoadStats>>,
+    deadline: tokio::time::Instant,
+) {
+    let examples = GraderCase::official(day)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|case| Arc::new(case.example))
+        .collect::<Vec<Arc<Example>>>();
+
+    if examples.is_empty() || rate <= 0.0 {
+        return;
+    }
+
+    let mut ticks = tokio::time::interval(Duration::from_secs_f64(1.0 / rate));
+    let mut requests = tokio::task::JoinSet::new();
+
+    for example in examples.iter().cycle() {
+        if deadline <= ticks.tick().await {
+            break;
+        }
+
+        let (client, config, stats, example) = (
+            client.clone(),
+            config.clone(),
+            stats.clone(),
+            example.clone(),
+        );
+
+        requests.spawn(async move {
+            let started = Instant::now();
+            let outcome = _send(&client, &config, &example).await;
+            let latency = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+
+            if let Err(error) = &outcome {
+                tracing::debug!("day {day} request {:?} failed: {error:?}", example.name);
+            }
+
+            let mut stats = stats.lock().expect("poisoned load stats");
+            let day = stats.days.entry(day).or_default();
+
+            day.sent += 1;
+            day.failed += usize::from(outcome.is_err());
+            day.latencies.push(latency);
+        });
+    }
+
+    while requests.join_next().await.is_some() {}
+}
+
+/// Join a random room, send a random number of messages, and leave
+/// (over and over, to churn the service's connections) until the deadline
+async fn _drive_chat_client(
+    client: usize,
+    config: Arc<LoadConfig>,
+    stats: Arc<Mutex<LoadStats>>,
+    deadline: tokio::time::Instant,
+) {
+    let base = config
+        .target
+        .replacen("https://", "wss://", 1)
+        .replacen("http://", "ws://", 1);
+
+    while tokio::time::Instant::now() < deadline {
+        let (room, messages, pause) = {
+            let mut rng = rand::thread_rng();
+            (
+                rng.gen_range(1..=config.chat_rooms),
+                rng.gen_range(1..=MAX_CHAT_MESSAGES),
+                rng.gen_range(Duration::ZERO..=MAX_CHAT_PAUSE),
+            )
+        };
+
+        let url = format!(
+            "{base}{}",
+            routes::day19::room(room, format!("loadgen-{client}"))
+        );
+
+        let outcome = async {
+            let (mut socket, _) = tokio_tungstenite::connect_async(url.as_str()).await?;
+
+            stats.lock().expect("poisoned load stats").chat.joins += 1;
+
+            for index in 0..messages {
+                if deadline <= tokio::time::Instant::now() {
+                    break;
+                }
+
+                let message = json!({"message": format!("loadgen message {index}")});
+
+                socket.send(Message::Text(message.to_string())).await?;
+                stats.lock().expect("poisoned load stats").chat.messages += 1;
+
+                tokio::time::sleep(pause).await;
+            }
+
+            socket.close(None).await?;
+
+            // drain anything broadcast to the client before it left
+            while let Some(Ok(_)) = socket.next().await {}
+
+            anyhow::Ok(())
+        }
+        .await;
+
+        if let Err(error) = outcome {
+            tracing::debug!("chat client {client} failed: {error:?}");
+            stats.lock().expect("poisoned load stats").chat.failed += 1;
+            tokio::time::sleep(MAX_CHAT_PAUSE).await;
+        }
+    }
+}
+
+/// Run the load generator
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let _tracing = TracingControl::install()?;
+
+    let config = Arc::new(LoadConfig::from_env()?);
+    let stats = Arc::new(Mutex::new(LoadStats::default()));
+    let client = reqwest::Client::new();
+
+    tracing::info!(
+        "generating load against {} for {:?}: {:?} requests per second by day, \
+         payloads scaled {}x, {} chat clients across {} rooms",
+        config.target,
+        config.duration,
+        config.rates,
+        config.payload_scale,
+        config.chat_clients,
+        config.chat_rooms,
+    );
+
+    let started = Instant::now();
+    let deadline = tokio::time::Instant::now() + config.duration;
+    let mut drivers = tokio::task::JoinSet::new();
+
+    for (day, rate) in &config.rates {
+        drivers.spawn(_drive_day(
+            *day,
+            *rate,
+            client.clone(),
+            config.clone(),
+            stats.clone(),
+            deadline,
+        ));
+    }
+
+    for client in 0..config.chat_clients {
+        drivers.spawn(_drive_chat_client(
+            client,
+            config.clone(),
+            stats.clone(),
+            deadline,
+        ));
+    }
+
+    while let Some(outcome) = drivers.join_next().await {
+        outcome.context("joining load driver")?;
+    }
+
+    stats
+        .lock()
+        .expect("poisoned load stats")
+        .print(started.elapsed());
+
+    Ok(())
+}
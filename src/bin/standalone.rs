@@ -0,0 +1,99 @@
+#![forbid(unsafe_code)]
+#![deny(missing_debug_implementations)]
+
+//! # [`shuttle.rs`](https://shuttle.rs/) Christmas Code Hunt 2023
+//!
+//! A binary serving the [`cch23_thewondersmith`] library's router
+//! without the Shuttle runtime (e.g. locally, or in non-Shuttle
+//! deployments), configured entirely by environment variables:
+//!
+//! - `DATABASE_URL`: the service's PostgreSQL database (required)
+//! - `BIND_ADDRESS`: the address to serve on (`127.0.0.1:8000` if unset)
+//! - `CCH23_*`: the service's secrets (sans prefix, e.g.
+//!   `CCH23_ADMIN_TOKEN` for `ADMIN_TOKEN`), including the
+//!   persistence directory (`CCH23_PERSISTENCE_DIR`) and
+//!   the number of reverse proxies in front of the binary
+//!   (`CCH23_TRUSTED_PROXY_HOPS`, none if unset)
+
+// Standard Library Imports
+use std::{collections::BTreeMap, env, net::SocketAddr};
+
+// Third-Party Imports
+use anyhow::Context;
+use cch23_thewondersmith::{
+    abuse::TrustedProxies, router, start, state::ShuttleAppState, telemetry::TracingControl,
+};
+use shuttle_secrets::SecretStore;
+use sqlx::postgres::PgPoolOptions;
+
+/// The address served on if `BIND_ADDRESS` is unset
+const DEFAULT_BIND_ADDRESS: &str = "127.0.0.1:8000";
+
+/// The prefix of the environment variables read as secrets
+const SECRET_PREFIX: &str = "CCH23_";
+
+/// Read every `CCH23_`-prefixed environment variable as a secret
+fn secrets_from_env() -> SecretStore {
+    SecretStore::new(BTreeMap::from_iter(env::vars().filter_map(
+        |(name, value)| {
+            name.strip_prefix(SECRET_PREFIX)
+                .map(|name| (name.to_string(), value.into()))
+        },
+    )))
+}
+
+/// Run the project
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let tracing = TracingControl::install()?;
+
+    let address = env::var("BIND_ADDRESS")
+        .unwrap_or_else(|_| DEFAULT_BIND_ADDRESS.to_string())
+        .parse::<SocketAddr>()
+        .context("parsing BIND_ADDRESS")?;
+
+    let pool = PgPoolOptions::new()
+        .connect(&env::var("DATABASE_URL").context("reading DATABASE_URL")?)
+        .await
+        .context("connecting to DATABASE_URL")?;
+
+    let secrets = secrets_from_env();
+
+    // served directly (i.e. without Shuttle's proxy), so
+    // `X-Forwarded-For` is only trusted if configured to be
+    let proxies = TrustedProxies::from_secrets(&secrets, TrustedProxies::new(0));
+
+    #[cfg(feature = "grpc")]
+    let grpc_secrets = secrets.clone();
+
+    let state = ShuttleAppState {
+        tracing,
+        proxies,
+        ..ShuttleAppState::initialize(pool, Some(secrets), None, None)?
+    };
+
+    start(&state).await;
+
+    #[cfg(feature = "grpc")]
+    {
+        let orders =
+            cch23_thewondersmith::grpc::OrdersService::new(state.db.clone(), state.metrics.clone());
+
+        tokio::spawn(async move {
+            if let Err(error) = orders.serve(&grpc_secrets).await {
+                tracing::error!("gRPC server exited: {error:?}");
+            }
+        });
+    }
+
+    tracing::info!("serving on {address}");
+
+    axum::Server::try_bind(&address)?
+        .serve(router(state).into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await?;
+
+    Ok(())
+}
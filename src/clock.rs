@@ -0,0 +1,63 @@
+//! ## Time Sources
+//!
+
+// Standard Library Imports
+use core::fmt::Debug;
+#[cfg(test)]
+use std::sync::RwLock;
+
+// Third-Party Imports
+use chrono::{DateTime, Utc};
+
+// <editor-fold desc="// Clock ...">
+
+/// A source of the current time
+pub trait Clock: Debug + Send + Sync {
+    /// Get the current time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+// </editor-fold desc="// Clock ...">
+
+// <editor-fold desc="// SystemClock ...">
+
+/// The system's wall clock
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// </editor-fold desc="// SystemClock ...">
+
+// <editor-fold desc="// FakeClock ...">
+
+/// A manually controlled clock for deterministic tests
+#[cfg(test)]
+#[derive(Debug)]
+pub struct FakeClock(RwLock<DateTime<Utc>>);
+
+#[cfg(test)]
+impl FakeClock {
+    /// Create a clock stopped at the supplied time
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self(RwLock::new(now))
+    }
+
+    /// Move the clock forward by the supplied duration
+    pub fn advance(&self, duration: chrono::Duration) {
+        *self.0.write().unwrap() += duration;
+    }
+}
+
+#[cfg(test)]
+impl Clock for FakeClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.read().unwrap()
+    }
+}
+
+// </editor-fold desc="// FakeClock ...">
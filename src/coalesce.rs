@@ -0,0 +1,140 @@
+//! ## Request Coalescing
+//!
+
+// Standard Library Imports
+use core::future::Future;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+// Third-Party Imports
+use futures::future::{BoxFuture, FutureExt, Shared};
+
+// Crate-Level Imports
+use crate::metrics::CoalescingStats;
+
+/// The calls currently in flight, by key
+type InFlight<Output> = Arc<Mutex<HashMap<String, Shared<BoxFuture<'static, Output>>>>>;
+
+/// A "single-flight" group, in which concurrent calls sharing
+/// a key share the outcome of a single in-flight call
+#[derive(Debug)]
+pub struct SingleFlight<Output> {
+    in_flight: InFlight<Output>,
+}
+
+impl<Output> Default for SingleFlight<Output> {
+    fn default() -> Self {
+        Self {
+            in_flight: Arc::default(),
+        }
+    }
+}
+
+impl<Output: Clone + Send + Sync + 'static> SingleFlight<Output> {
+    /// Await the in-flight call with the supplied key, or
+    /// make (and await) it if there isn't one, recording
+    /// which of the two happened in the supplied stats
+    pub async fn run<Call>(
+        &self,
+        key: impl Into<String>,
+        stats: &CoalescingStats,
+        call: impl FnOnce() -> Call,
+    ) -> Output
+    where
+        Call: Future<Output = Output> + Send + 'static,
+    {
+        let key = key.into();
+
+        let flight = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+
+            if let Some(flight) = in_flight.get(&key) {
+                stats.record(true);
+                flight.clone()
+            } else {
+                stats.record(false);
+
+                // the call forgets itself once it completes (rather
+                // than relying on the caller that made it to do so)
+                // so followers aren't stranded if the leader is dropped
+                let registry = Arc::downgrade(&self.in_flight);
+                let completed = key.clone();
+                let call = call();
+                let flight = async move {
+                    let output = call.await;
+
+                    if let Some(registry) = registry.upgrade() {
+                        registry.lock().unwrap().remove(&completed);
+                    }
+
+                    output
+                }
+                .boxed()
+                .shared();
+
+                in_flight.insert(key, flight.clone());
+                flight
+            }
+        };
+
+        flight.await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use core::time::Duration;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    // Third-Party Imports
+    use futures::future::join_all;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use super::SingleFlight;
+    use crate::metrics::ServiceMetrics;
+
+    /// Test that concurrent calls sharing a key are coalesced,
+    /// and that completed calls are made afresh
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_single_flight() {
+        let metrics = ServiceMetrics::default();
+        let group = SingleFlight::<usize>::default();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let call = |key: &'static str| {
+            let calls = calls.clone();
+
+            group.run(key, &metrics.upstream, move || async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                calls.fetch_add(1, Ordering::SeqCst) + 1
+            })
+        };
+
+        let outcomes = join_all((0..10).map(|_| call("/8/weight/25"))).await;
+
+        assert_eq!(vec![1; 10], outcomes);
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+
+        assert_eq!(2, call("/8/weight/25").await);
+        assert_eq!(3, call("/8/weight/1").await);
+
+        let snapshot = metrics.snapshot();
+
+        assert_eq!(Some(&3), snapshot.get("upstream.calls"));
+        assert_eq!(Some(&9), snapshot.get("upstream.coalesced"));
+        assert!(group.in_flight.lock().unwrap().is_empty());
+    }
+}
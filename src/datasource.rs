@@ -0,0 +1,485 @@
+//! ## Grafana Datasource
+//!
+//! The endpoints of Grafana's "simple JSON" datasource protocol,
+//! charting gift orders (straight from the database) and the
+//! service's periodically [sampled](MetricsHistory::sample) metrics
+
+// Standard Library Imports
+use core::time::Duration;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Mutex,
+};
+
+// Third-Party Imports
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Crate-Level Imports
+use crate::{
+    responses::{ApiOk, ApiResult},
+    solutions::{
+        counters::VIEWS_COUNTER,
+        day_13::{GiftOrder, OrderSchema},
+    },
+    state::ShuttleAppState,
+    validation::{Validate, ValidationErrors},
+};
+
+/// The number of samples retained (a day's
+/// worth, at the default sampling interval)
+const HISTORY_CAPACITY: usize = 8_640;
+
+/// The suffix of the targets charting a
+/// sampled metric's per-second rate of change
+const RATE_SUFFIX: &str = ".rate";
+
+/// The targets charting the quantity
+/// of gifts ordered, by order schema
+const ORDER_TARGETS: [(&str, OrderSchema); 2] = [
+    ("orders.cch13.quantity", OrderSchema::Day13),
+    ("orders.cch18.quantity", OrderSchema::Day18),
+];
+
+/// The maximum number of targets per query
+const MAX_QUERY_TARGETS: usize = 32;
+
+/// The number of data points charted per target
+/// if the query doesn't specify a maximum
+const DEFAULT_MAX_DATA_POINTS: usize = 1_000;
+
+// <editor-fold desc="// MetricsHistory ...">
+
+/// A point-in-time sample of the service's metrics
+#[derive(Clone, Debug)]
+pub struct MetricSample {
+    /// when the sample was taken
+    pub at: DateTime<Utc>,
+    /// the sampled metrics' values, by name
+    pub values: BTreeMap<String, u64>,
+}
+
+/// The service's most recent [`MetricSample`]s
+#[derive(Debug, Default)]
+pub struct MetricsHistory {
+    /// the retained samples, oldest first
+    samples: Mutex<VecDeque<MetricSample>>,
+}
+
+impl MetricsHistory {
+    /// Record the supplied sample, forgetting
+    /// the oldest sample if the history is full
+    pub fn record(&self, at: DateTime<Utc>, values: BTreeMap<String, u64>) {
+        let mut samples = self.samples.lock().unwrap();
+
+        if HISTORY_CAPACITY <= samples.len() {
+            samples.pop_front();
+        }
+
+        samples.push_back(MetricSample { at, values });
+    }
+
+    /// Sample the supplied state's metrics (and chat view
+    /// count), returning the number of metrics sampled
+    pub fn sample(&self, state: &ShuttleAppState) -> usize {
+        let mut values = state.metrics.snapshot();
+
+        values.insert(
+            String::from("chat.views"),
+            state.counters.named(VIEWS_COUNTER).value(),
+        );
+
+        let sampled = values.len();
+
+        self.record(state.clock.now(), values);
+
+        sampled
+    }
+
+    /// The names of the most recently sampled metrics
+    pub fn names(&self) -> Vec<String> {
+        self.samples
+            .lock()
+            .unwrap()
+            .back()
+            .map(|sample| sample.values.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// The named metric's values (or, for `{name}.rate`, its
+    /// per-second rate of change) between `from` and `to`
+    pub fn series(&self, name: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DataPoint> {
+        let samples = self.samples.lock().unwrap();
+        let in_range = samples
+            .iter()
+            .filter(|sample| from <= sample.at && sample.at <= to);
+
+        match name.strip_suffix(RATE_SUFFIX) {
+            Some(base) => in_range
+                .filter_map(|sample| Some((sample.at, *sample.values.get(base)?)))
+                .collect::<Vec<(DateTime<Utc>, u64)>>()
+                .windows(2)
+                .filter_map(|pair| {
+                    let [(before, previous), (at, value)] = pair else {
+                        return None;
+                    };
+                    let elapsed = (*at - *before).num_milliseconds();
+
+                    // a counter that went backwards was reset,
+                    // which isn't a (negative) rate of change
+                    (0 < elapsed).then(|| {
+                        DataPoint::new(
+                            value.saturating_sub(*previous) as f64 * 1000.0 / elapsed as f64,
+                            *at,
+                        )
+                    })
+                })
+                .collect(),
+            None => in_range
+                .filter_map(|sample| {
+                    Some(DataPoint::new(*sample.values.get(name)? as f64, sample.at))
+                })
+                .collect(),
+        }
+    }
+}
+
+// </editor-fold desc="// MetricsHistory ...">
+
+// <editor-fold desc="// Protocol ...">
+
+/// A charted value and when it was
+/// recorded (in Unix milliseconds)
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DataPoint(pub f64, pub i64);
+
+impl DataPoint {
+    /// Create a data point for the supplied value and time
+    pub fn new(value: f64, at: DateTime<Utc>) -> Self {
+        Self(value, at.timestamp_millis())
+    }
+}
+
+/// A request for the targets matching some text
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchRequest {
+    /// the text the targets must contain
+    #[serde(default)]
+    pub target: String,
+}
+
+/// The time range a query charts
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct QueryRange {
+    /// the start of the range
+    pub from: DateTime<Utc>,
+    /// the end of the range
+    pub to: DateTime<Utc>,
+}
+
+/// A charted target
+#[derive(Debug, Deserialize)]
+pub struct QueryTarget {
+    /// the target's name
+    pub target: String,
+    /// whether the target is hidden (and needn't be charted)
+    #[serde(default)]
+    pub hide: bool,
+}
+
+/// A request for the data points of some targets
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryRequest {
+    /// the time range to chart
+    pub range: QueryRange,
+    /// the (suggested) interval between data points
+    #[serde(default)]
+    pub interval_ms: Option<u64>,
+    /// the maximum number of data points per target
+    #[serde(default)]
+    pub max_data_points: Option<usize>,
+    /// the targets to chart
+    pub targets: Vec<QueryTarget>,
+}
+
+impl QueryRequest {
+    /// The maximum number of data points per target
+    fn max_data_points(&self) -> usize {
+        self.max_data_points
+            .unwrap_or(DEFAULT_MAX_DATA_POINTS)
+            .max(1)
+    }
+
+    /// The interval into which orders are bucketed
+    fn interval(&self) -> Duration {
+        let span = (self.range.to - self.range.from)
+            .to_std()
+            .unwrap_or_default();
+
+        self.interval_ms
+            .map(Duration::from_millis)
+            .unwrap_or_default()
+            .max(span / u32::try_from(self.max_data_points()).unwrap_or(u32::MAX))
+            .max(Duration::from_secs(1))
+    }
+}
+
+impl Validate for QueryRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if self.range.to <= self.range.from {
+            errors.add("range", "`from` must precede `to`");
+        }
+
+        if MAX_QUERY_TARGETS < self.targets.len() {
+            errors.add(
+                "targets",
+                format!("must contain at most {MAX_QUERY_TARGETS} targets"),
+            );
+        }
+
+        errors.into_result()
+    }
+}
+
+/// A charted target's data points
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TimeSeries {
+    /// the target's name
+    pub target: String,
+    /// the target's data points, oldest first
+    pub datapoints: Vec<DataPoint>,
+}
+
+// </editor-fold desc="// Protocol ...">
+
+/// Respond to Grafana's datasource connection test
+#[tracing::instrument]
+pub async fn check_datasource() -> StatusCode {
+    StatusCode::OK
+}
+
+/// List the chartable targets containing the requested text
+#[tracing::instrument(skip(state))]
+pub async fn search_metrics(
+    State(state): State<ShuttleAppState>,
+    request: Option<Json<SearchRequest>>,
+) -> Json<Vec<String>> {
+    let Json(request) = request.unwrap_or_default();
+    let sampled = state.history.names();
+
+    Json(
+        ORDER_TARGETS
+            .iter()
+            .map(|(target, _)| target.to_string())
+            .chain(sampled.iter().cloned())
+            .chain(sampled.iter().map(|name| format!("{name}{RATE_SUFFIX}")))
+            .filter(|target| target.contains(&request.target))
+            .collect(),
+    )
+}
+
+/// Chart the requested targets over the requested time range
+#[tracing::instrument(skip_all)]
+pub async fn query_metrics(
+    State(state): State<ShuttleAppState>,
+    Json(request): Json<QueryRequest>,
+) -> ApiResult<Vec<TimeSeries>> {
+    request.validate()?;
+
+    let QueryRange { from, to } = request.range;
+    let mut charted = Vec::<TimeSeries>::new();
+
+    for target in request.targets.iter().filter(|target| !target.hide) {
+        let datapoints = match ORDER_TARGETS
+            .iter()
+            .find(|(name, _)| *name == target.target)
+        {
+            Some((_, schema)) => {
+                GiftOrder::quantity_over_time(*schema, &state.db, from, to, request.interval())
+                    .await
+                    .map_err(|error| tracing::warn!("error charting {}: {error}", target.target))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(at, quantity)| DataPoint::new(quantity as f64, at))
+                    .collect()
+            }
+            None => state.history.series(&target.target, from, to),
+        };
+
+        charted.push(TimeSeries {
+            target: target.target.clone(),
+            datapoints: _downsample(datapoints, request.max_data_points()),
+        });
+    }
+
+    Ok(ApiOk(charted))
+}
+
+/// Thin the supplied data points to (at most) the
+/// supplied number, always keeping the most recent
+fn _downsample(datapoints: Vec<DataPoint>, max: usize) -> Vec<DataPoint> {
+    if datapoints.len() <= max {
+        return datapoints;
+    }
+
+    let stride = datapoints.len().div_ceil(max);
+
+    datapoints
+        .iter()
+        .rev()
+        .step_by(stride)
+        .rev()
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::collections::BTreeMap;
+
+    // Third-Party Imports
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+    };
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use serde_json::json;
+
+    // Crate-Level Imports
+    use super::{DataPoint, MetricsHistory, TimeSeries};
+    use crate::router;
+    use crate::routes;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{admin_request, state, TestService};
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2023, 12, 24, 0, 0, 0).unwrap()
+    }
+
+    fn history(requests: &[u64]) -> MetricsHistory {
+        let history = MetricsHistory::default();
+
+        for (offset, requests) in requests.iter().enumerate() {
+            history.record(
+                start() + Duration::seconds(10 * offset as i64),
+                BTreeMap::from([(String::from("requests"), *requests)]),
+            );
+        }
+
+        history
+    }
+
+    /// Test that sampled metrics (and their rates
+    /// of change) are charted within the range
+    #[rstest]
+    #[case::values("requests", 0, 30, vec![(10.0, 0), (30.0, 10), (30.0, 20), (80.0, 30)])]
+    #[case::windowed("requests", 10, 20, vec![(30.0, 10), (30.0, 20)])]
+    #[case::rates("requests.rate", 0, 40, vec![(2.0, 10), (0.0, 20), (5.0, 30), (0.0, 40)])]
+    #[case::unknown("responses", 0, 40, vec![])]
+    #[test_log::test]
+    fn test_history_series(
+        #[case] name: &str,
+        #[case] from: i64,
+        #[case] to: i64,
+        #[case] expected: Vec<(f64, i64)>,
+    ) {
+        let history = history(&[10, 30, 30, 80, 5]);
+
+        assert_eq!(
+            expected
+                .into_iter()
+                .map(|(value, offset)| DataPoint::new(value, start() + Duration::seconds(offset)))
+                .collect::<Vec<DataPoint>>(),
+            history.series(
+                name,
+                start() + Duration::seconds(from),
+                start() + Duration::seconds(to)
+            )
+        );
+    }
+
+    /// Test that the datasource endpoints list
+    /// and chart (downsampled) sampled metrics
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_datasource_endpoints(state: ShuttleAppState) -> anyhow::Result<()> {
+        for _ in 0..5 {
+            state.history.sample(&state);
+        }
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(admin_request(Method::GET, routes::datasource::root()).body(Body::empty())?)
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(
+                admin_request(Method::POST, routes::datasource::search())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(json!({"target": "views"}).to_string()))?,
+            )
+            .await?;
+        let targets: Vec<String> =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(vec!["chat.views", "chat.views.rate"], targets);
+
+        let now = state.clock.now();
+        let response = TestService::from(router(state.clone()))
+            .resolve(
+                admin_request(Method::POST, routes::datasource::query())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        json!({
+                            "range": {"from": now - Duration::hours(1), "to": now + Duration::hours(1)},
+                            "maxDataPoints": 2,
+                            "targets": [
+                                {"target": "chat.views"},
+                                {"target": "panics", "hide": true},
+                            ],
+                        })
+                        .to_string(),
+                    ))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let charted: Vec<TimeSeries> =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(1, charted.len());
+        assert_eq!("chat.views", charted[0].target);
+        assert_eq!(2, charted[0].datapoints.len());
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(
+                admin_request(Method::POST, routes::datasource::query())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        json!({"range": {"from": now, "to": now}, "targets": []}).to_string(),
+                    ))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+        assert_eq!(Some(&4), state.metrics.snapshot().get("requests"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,477 @@
+//! ## Request Echo
+//!
+//! Reports how the service would parse a request to any of the
+//! solutions' [declared](crate::solutions::RouteDoc) routes (without
+//! handling it): the route it matches, its path parameters, query,
+//! content type, and body, and the first of the checks the route's
+//! extractors make (in the order they'd make them) that it would
+//! fail. Requests to `/debug/echo/<path>` are reported as though
+//! they'd been sent to `/<path>`, with the same method, headers,
+//! query, and body.
+
+// Third-Party Imports
+use axum::{
+    body::Bytes,
+    extract::Json,
+    http::{header, HeaderMap, Method, StatusCode, Uri},
+};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::form_urlencoded;
+
+// Crate-Level Imports
+use crate::{
+    routes,
+    solutions::{self, RouteDoc},
+};
+
+// <editor-fold desc="// EchoReport ...">
+
+/// The outcome of one of the checks a request is subjected to
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct StageReport {
+    /// the part of the request checked (e.g. `"route"` or `"body"`)
+    pub stage: String,
+    /// the extractor (or router) that makes the check
+    pub extractor: String,
+    /// whether the request passed the check
+    pub passed: bool,
+    /// what the check found (or why the request would be rejected)
+    pub detail: String,
+    /// the status the request would be rejected with (if it would be)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+}
+
+impl StageReport {
+    fn passed(stage: &str, extractor: &str, detail: impl ToString) -> Self {
+        Self {
+            stage: stage.to_string(),
+            extractor: extractor.to_string(),
+            passed: true,
+            detail: detail.to_string(),
+            status: None,
+        }
+    }
+
+    fn failed(stage: &str, extractor: &str, status: StatusCode, detail: impl ToString) -> Self {
+        Self {
+            status: Some(status.as_u16()),
+            passed: false,
+            ..Self::passed(stage, extractor, detail)
+        }
+    }
+}
+
+/// The route an echoed request matches
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct MatchedRoute {
+    /// the challenge day the route belongs to
+    pub day: i8,
+    /// the route's pattern (see [`crate::routes`])
+    pub pattern: String,
+    /// the methods the route accepts
+    pub methods: Vec<String>,
+}
+
+/// How the service would parse an echoed request
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct EchoReport {
+    /// the request's HTTP method
+    pub method: String,
+    /// the (echoed) request's path
+    pub path: String,
+    /// the route the request matches (if any)
+    pub route: Option<MatchedRoute>,
+    /// the request's (decoded) path parameters
+    pub path_params: Vec<(String, String)>,
+    /// the request's (decoded) query parameters
+    pub query: Vec<(String, String)>,
+    /// the media type the request's `Content-Type` header declares
+    pub content_type: Option<String>,
+    /// the media type the request's body appears to be
+    pub detected_content_type: Option<String>,
+    /// the size of the request's (decoded) body, in bytes
+    pub body_size: usize,
+    /// the outcome of each check (up to the first that failed)
+    pub stages: Vec<StageReport>,
+    /// the first check the request would fail (if any)
+    pub rejection: Option<StageReport>,
+}
+
+// </editor-fold desc="// EchoReport ...">
+
+/// Match the supplied path against a route pattern,
+/// returning the path's (still encoded) parameters
+fn _match_pattern<'path>(pattern: &str, path: &'path str) -> Option<Vec<(String, &'path str)>> {
+    let mut params = Vec::new();
+    let mut segments = path.trim_start_matches('/').split('/');
+
+    for expected in pattern.trim_start_matches('/').split('/') {
+        if let Some(name) = expected.strip_prefix('*') {
+            let rest = segments.by_ref().collect::<Vec<&str>>().join("/");
+            let offset = path.len() - rest.len();
+
+            params.push((name.to_string(), &path[offset..]));
+            return Some(params);
+        }
+
+        let segment = segments.next()?;
+
+        match expected.strip_prefix(':') {
+            Some(name) => params.push((name.to_string(), segment)),
+            None if expected == segment => {}
+            None => return None,
+        }
+    }
+
+    segments.next().is_none().then_some(params)
+}
+
+/// Find the declared route the supplied path matches (preferring,
+/// segment by segment, static segments to parameters, and parameters
+/// to wildcards, as the router does), along with the path's (still
+/// encoded) parameters
+fn _match_route<'path>(
+    docs: &[RouteDoc],
+    path: &'path str,
+) -> Option<(MatchedRoute, Vec<(String, &'path str)>)> {
+    let (pattern, params) = docs
+        .iter()
+        .filter_map(|doc| _match_pattern(doc.path, path).map(|params| (doc.path, params)))
+        .max_by_key(|(pattern, _)| {
+            pattern
+                .split('/')
+                .map(|segment| match segment.chars().next() {
+                    Some('*') => 0u8,
+                    Some(':') => 1u8,
+                    _ => 2u8,
+                })
+                .collect::<Vec<u8>>()
+        })?;
+
+    let matching = docs.iter().filter(|doc| doc.path == pattern);
+
+    Some((
+        MatchedRoute {
+            day: matching
+                .clone()
+                .map(|doc| doc.day)
+                .next()
+                .unwrap_or_default(),
+            pattern: pattern.to_string(),
+            methods: matching.map(|doc| doc.method.to_string()).collect(),
+        },
+        params,
+    ))
+}
+
+/// Guess the media type of the supplied body
+fn _sniff(body: &[u8]) -> Option<&'static str> {
+    if body.is_empty() {
+        None
+    } else if serde_json::from_slice::<Value>(body).is_ok() {
+        Some("application/json")
+    } else if core::str::from_utf8(body).is_ok() {
+        Some("text/plain")
+    } else {
+        Some("application/octet-stream")
+    }
+}
+
+impl EchoReport {
+    /// Report how the service would parse the described request
+    pub fn of(method: &Method, uri: &Uri, headers: &HeaderMap, body: &[u8]) -> Self {
+        let path = match uri.path().strip_prefix(routes::debug::ECHO_ROOT) {
+            Some("") | None => "/",
+            Some(path) => path,
+        };
+
+        let content_type = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|media| media.trim().to_ascii_lowercase());
+
+        let mut report = Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            route: None,
+            path_params: Vec::new(),
+            query: form_urlencoded::parse(uri.query().unwrap_or_default().as_bytes())
+                .into_owned()
+                .collect(),
+            detected_content_type: _sniff(body).map(String::from),
+            content_type,
+            body_size: body.len(),
+            stages: Vec::new(),
+            rejection: None,
+        };
+
+        let checks: [&dyn Fn(&mut Self) -> StageReport; 5] = [
+            &|report| report._check_route(),
+            &|report| report._check_path(path),
+            &|report| report._check_query(),
+            &|report| report._check_content_type(),
+            &|report| report._check_body(body),
+        ];
+
+        for check in checks {
+            let stage = check(&mut report);
+            let passed = stage.passed;
+
+            report.stages.push(stage);
+
+            if !passed {
+                report.rejection = report.stages.last().cloned();
+                break;
+            }
+        }
+
+        report
+    }
+
+    fn _check_route(&mut self) -> StageReport {
+        let docs = solutions::route_docs();
+
+        let Some((route, _)) = _match_route(&docs, &self.path) else {
+            return StageReport::failed(
+                "route",
+                "Router",
+                StatusCode::NOT_FOUND,
+                format!("no route matches {}", self.path),
+            );
+        };
+
+        let allowed = route.methods.contains(&self.method)
+            || (self.method == Method::HEAD.as_str() && route.methods.iter().any(|m| m == "GET"));
+
+        let stage = if allowed {
+            StageReport::passed("route", "Router", format!("matched {}", route.pattern))
+        } else {
+            StageReport::failed(
+                "route",
+                "Router",
+                StatusCode::METHOD_NOT_ALLOWED,
+                format!(
+                    "{} doesn't accept {} (only {})",
+                    route.pattern,
+                    self.method,
+                    route.methods.join(", ")
+                ),
+            )
+        };
+
+        self.route = Some(route);
+
+        stage
+    }
+
+    fn _check_path(&mut self, path: &str) -> StageReport {
+        let docs = solutions::route_docs();
+        let params = _match_route(&docs, path)
+            .map(|(_, params)| params)
+            .unwrap_or_default();
+
+        for (name, raw) in params {
+            match percent_decode_str(raw).decode_utf8() {
+                Ok(value) => self.path_params.push((name, value.into_owned())),
+                Err(_) => {
+                    return StageReport::failed(
+                        "path",
+                        "Path",
+                        StatusCode::BAD_REQUEST,
+                        format!("path parameter `{name}` isn't valid (percent-encoded) UTF-8"),
+                    )
+                }
+            }
+        }
+
+        StageReport::passed(
+            "path",
+            "Path",
+            format!("{} parameter(s) decoded", self.path_params.len()),
+        )
+    }
+
+    fn _check_query(&mut self) -> StageReport {
+        StageReport::passed(
+            "query",
+            "Query",
+            format!("{} parameter(s) decoded", self.query.len()),
+        )
+    }
+
+    fn _check_content_type(&mut self) -> StageReport {
+        match (
+            self.content_type.as_deref(),
+            self.detected_content_type.as_deref(),
+        ) {
+            (None, Some("application/json")) => StageReport::failed(
+                "content_type",
+                "Json",
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Expected request with `Content-Type: application/json`",
+            ),
+            (declared, detected) => StageReport::passed(
+                "content_type",
+                "Json",
+                format!(
+                    "declared {}, detected {}",
+                    declared.unwrap_or("nothing"),
+                    detected.unwrap_or("nothing"),
+                ),
+            ),
+        }
+    }
+
+    fn _check_body(&mut self, body: &[u8]) -> StageReport {
+        match self.content_type.as_deref() {
+            Some(media) if media == "application/json" || media.ends_with("+json") => {
+                match serde_json::from_slice::<Value>(body) {
+                    Ok(_) => StageReport::passed("body", "Json", "valid JSON"),
+                    Err(error) => StageReport::failed(
+                        "body",
+                        "Json",
+                        StatusCode::BAD_REQUEST,
+                        format!("Failed to parse the request body as JSON: {error}"),
+                    ),
+                }
+            }
+            Some(media) if media.starts_with("text/") => match core::str::from_utf8(body) {
+                Ok(_) => StageReport::passed("body", "String", "valid UTF-8"),
+                Err(_) => StageReport::failed(
+                    "body",
+                    "String",
+                    StatusCode::BAD_REQUEST,
+                    "Request body didn't contain valid UTF-8",
+                ),
+            },
+            _ => StageReport::passed("body", "Bytes", format!("{} byte(s)", body.len())),
+        }
+    }
+}
+
+/// Report how the service would parse the
+/// (echoed) request, without handling it
+#[tracing::instrument(skip(headers, body))]
+pub async fn echo_request(
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Json<EchoReport> {
+    Json(EchoReport::of(&method, &uri, &headers, &body))
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Third-Party Imports
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use super::EchoReport;
+    use crate::routes;
+    use crate::utils::{service, TestService};
+
+    /// Test that echoed requests are matched to their
+    /// route and checked as their extractors would be
+    #[rstest]
+    #[case::path_params(
+        Request::get(routes::debug::echo(routes::day8::weight(25))).body(Body::empty()).unwrap(),
+        Some(routes::day8::WEIGHT),
+        vec![("pokedex_id", "25")],
+        None
+    )]
+    #[case::wildcard(
+        Request::get(routes::debug::echo(routes::day1::packets([4, 8, 15]))).body(Body::empty()).unwrap(),
+        Some(routes::day1::PACKETS),
+        vec![("packets", "4/8/15")],
+        None
+    )]
+    #[case::static_over_param(
+        Request::post(routes::debug::echo(routes::day8::weight("batch")))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from("[25]"))
+            .unwrap(),
+        Some(routes::day8::WEIGHT_BATCH),
+        vec![],
+        None
+    )]
+    #[case::unknown_route(
+        Request::get(routes::debug::echo("/no/such/route")).body(Body::empty()).unwrap(),
+        None,
+        vec![],
+        Some(("route", StatusCode::NOT_FOUND))
+    )]
+    #[case::wrong_method(
+        Request::get(routes::debug::echo(routes::day4::strength())).body(Body::empty()).unwrap(),
+        Some(routes::day4::STRENGTH),
+        vec![],
+        Some(("route", StatusCode::METHOD_NOT_ALLOWED))
+    )]
+    #[case::missing_content_type(
+        Request::post(routes::debug::echo(routes::day4::strength()))
+            .body(Body::from("[]"))
+            .unwrap(),
+        Some(routes::day4::STRENGTH),
+        vec![],
+        Some(("content_type", StatusCode::UNSUPPORTED_MEDIA_TYPE))
+    )]
+    #[case::malformed_json(
+        Request::post(routes::debug::echo(routes::day4::strength()))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from("[{"))
+            .unwrap(),
+        Some(routes::day4::STRENGTH),
+        vec![],
+        Some(("body", StatusCode::BAD_REQUEST))
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_echo_request(
+        service: TestService,
+        #[case] request: Request<Body>,
+        #[case] expected_route: Option<&str>,
+        #[case] expected_params: Vec<(&str, &str)>,
+        #[case] expected_rejection: Option<(&str, StatusCode)>,
+    ) -> anyhow::Result<()> {
+        let response = service.resolve(request).await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let report: EchoReport =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(
+            expected_route,
+            report.route.as_ref().map(|route| route.pattern.as_str())
+        );
+        assert_eq!(
+            expected_params,
+            report
+                .path_params
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            expected_rejection,
+            report.rejection.as_ref().map(|stage| (
+                stage.stage.as_str(),
+                StatusCode::from_u16(stage.status.unwrap()).unwrap()
+            ))
+        );
+
+        Ok(())
+    }
+}
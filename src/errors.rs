@@ -0,0 +1,588 @@
+//! ## Errors
+//!
+//! Every error response's body is an [`ErrorEnvelope`], serialized as:
+//!
+//! ```json
+//! {"error": {"code": "not_found", "status": 404, "message": "...", "details": null}}
+//! ```
+//!
+//! where `code` is the machine-readable form of the response's status
+//! (and `request_id` is included, if known). Handlers that fail with
+//! ad hoc (e.g. plain-text) bodies, as axum's extractor rejections do,
+//! are re-typed by the [`envelope_responses`](crate::responses::envelope_responses)
+//! middleware.
+
+// Standard Library Imports
+use core::fmt::{Display, Formatter, Result as FormatResult};
+
+// Third-Party Imports
+use axum::{
+    extract::Json,
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use shuttle_persist::PersistError;
+
+// Crate-Level Imports
+use crate::{abuse::Suspicious, persistence::EncryptedPersistError, validation::ValidationErrors};
+
+// <editor-fold desc="// ErrorEnvelope ...">
+
+/// The service's standard JSON error response body
+#[cfg_attr(test, derive(Eq, PartialEq))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(into = "WireEnvelope", from = "WireEnvelope")]
+pub struct ErrorEnvelope {
+    /// The response's HTTP status code
+    pub status: u16,
+    /// A human-readable description of the error
+    pub error: String,
+    /// The id of the request that produced the error (if known)
+    pub request_id: Option<String>,
+    /// Additional (error-specific) details
+    pub details: Option<Value>,
+    /// The internal description of the error, [reported](ErrorDetail)
+    /// alongside the response but never sent to the client
+    pub detail: Option<String>,
+}
+
+/// The serialized form of an [`ErrorEnvelope`]
+#[derive(Serialize, Deserialize)]
+struct WireEnvelope {
+    /// the enveloped error
+    error: WireError,
+}
+
+/// The serialized form of an [`ErrorEnvelope`]'s error
+#[derive(Serialize, Deserialize)]
+struct WireError {
+    /// the machine-readable form of the error's status
+    code: String,
+    /// the error's HTTP status code
+    status: u16,
+    /// a human-readable description of the error
+    message: String,
+    /// additional (error-specific) details
+    #[serde(default)]
+    details: Option<Value>,
+    /// the id of the request that produced the error (if known)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+}
+
+impl From<ErrorEnvelope> for WireEnvelope {
+    fn from(envelope: ErrorEnvelope) -> Self {
+        Self {
+            error: WireError {
+                code: ErrorEnvelope::code(envelope.status),
+                status: envelope.status,
+                message: envelope.error,
+                details: envelope.details,
+                request_id: envelope.request_id,
+            },
+        }
+    }
+}
+
+impl From<WireEnvelope> for ErrorEnvelope {
+    fn from(WireEnvelope { error }: WireEnvelope) -> Self {
+        Self {
+            status: error.status,
+            error: error.message,
+            request_id: error.request_id,
+            details: error.details,
+            detail: None,
+        }
+    }
+}
+
+impl ErrorEnvelope {
+    /// The machine-readable code of the supplied status
+    /// (its canonical reason, in `snake_case`)
+    pub fn code(status: u16) -> String {
+        StatusCode::from_u16(status)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .map_or_else(
+                || String::from("error"),
+                |reason| {
+                    reason
+                        .chars()
+                        .filter(|character| character.is_ascii_alphanumeric() || *character == ' ')
+                        .map(|character| match character {
+                            ' ' => '_',
+                            character => character.to_ascii_lowercase(),
+                        })
+                        .collect()
+                },
+            )
+    }
+
+    /// Create a new envelope with the supplied status and message
+    pub fn new<Message: ToString>(status: StatusCode, error: Message) -> Self {
+        Self {
+            status: status.as_u16(),
+            error: error.to_string(),
+            request_id: None,
+            details: None,
+            detail: None,
+        }
+    }
+
+    /// Attach the supplied request id to the envelope
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// Attach the supplied details to the envelope
+    pub fn with_details(mut self, details: Option<Value>) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+impl IntoResponse for ErrorEnvelope {
+    fn into_response(mut self) -> Response {
+        let detail = self.detail.take();
+        let mut response = (
+            StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Json(self),
+        )
+            .into_response();
+
+        if let Some(detail) = detail {
+            response.extensions_mut().insert(ErrorDetail(detail));
+        }
+
+        response
+    }
+}
+
+// </editor-fold desc="// ErrorEnvelope ...">
+
+// <editor-fold desc="// AppError ...">
+
+/// A response extension carrying the (internal) description of
+/// the failed dependency an [`AppError`] response was produced
+/// from, such that it's [reported](crate::reporting::report_errors)
+/// whatever the response's status
+#[derive(Clone, Debug)]
+pub struct ErrorDetail(pub String);
+
+/// The error half of an [`ApiResult`](crate::responses::ApiResult),
+/// and of every handler that can fail (all of which are reported
+/// as an [`ErrorEnvelope`])
+#[derive(Debug)]
+pub enum AppError {
+    /// A general failure, reported as-is
+    Envelope(ErrorEnvelope),
+    /// A request that failed [validation](crate::validation::Validate)
+    Validation(ValidationErrors),
+    /// A failed call to an external service
+    Upstream(UpstreamError),
+    /// A failed database query (`424 Failed Dependency`)
+    Database(sqlx::Error),
+    /// A failed read from (or write to) the service's
+    /// persistent store (`424 Failed Dependency`)
+    Persistence(EncryptedPersistError),
+    /// A request rejected as an abuse attempt, reported as
+    /// the wrapped error and flagged as [`Suspicious`]
+    Suspicious(Box<AppError>),
+}
+
+impl AppError {
+    /// The status with which the error is reported
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Envelope(envelope) => {
+                StatusCode::from_u16(envelope.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+            Self::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Upstream(error) => error.status(),
+            Self::Database(_) | Self::Persistence(_) => StatusCode::FAILED_DEPENDENCY,
+            Self::Suspicious(error) => error.status(),
+        }
+    }
+
+    /// Flag the error's request as an abuse attempt
+    pub fn suspicious(self) -> Self {
+        match self {
+            error @ Self::Suspicious(_) => error,
+            error => Self::Suspicious(Box::new(error)),
+        }
+    }
+
+    /// Attach the supplied details to the error (converting
+    /// it into a general failure with the same status)
+    pub fn with_details(self, details: Value) -> Self {
+        match self {
+            Self::Envelope(envelope) => Self::Envelope(envelope.with_details(Some(details))),
+            Self::Suspicious(error) => Self::Suspicious(Box::new(error.with_details(details))),
+            error @ (Self::Database(_) | Self::Persistence(_)) => {
+                Self::Envelope(error._dependency_failure().with_details(Some(details)))
+            }
+            error => Self::Envelope(
+                ErrorEnvelope::new(error.status(), &error).with_details(Some(details)),
+            ),
+        }
+    }
+
+    /// Log the supplied dependency failure, and report it to the client
+    /// as a generic message (keeping the underlying error's description
+    /// out of the response body, but attached to it as an [`ErrorDetail`])
+    fn _dependency_failure(&self) -> ErrorEnvelope {
+        tracing::warn!("{self}");
+
+        let summary = match self {
+            Self::Database(_) => "database query failed",
+            _ => "persistent store failed",
+        };
+
+        ErrorEnvelope {
+            detail: Some(self.to_string()),
+            ..ErrorEnvelope::new(self.status(), summary)
+        }
+    }
+}
+
+impl Display for AppError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match self {
+            Self::Envelope(envelope) => formatter.write_str(&envelope.error),
+            Self::Validation(_) => formatter.write_str("request failed validation"),
+            Self::Upstream(error) => Display::fmt(error, formatter),
+            Self::Database(error) => write!(formatter, "database query failed: {error}"),
+            Self::Persistence(error) => write!(formatter, "persistent store failed: {error}"),
+            Self::Suspicious(error) => Display::fmt(error, formatter),
+        }
+    }
+}
+
+impl From<ErrorEnvelope> for AppError {
+    fn from(envelope: ErrorEnvelope) -> Self {
+        Self::Envelope(envelope)
+    }
+}
+
+impl From<(StatusCode, String)> for AppError {
+    fn from((status, error): (StatusCode, String)) -> Self {
+        Self::Envelope(ErrorEnvelope::new(status, error))
+    }
+}
+
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        Self::Validation(errors)
+    }
+}
+
+impl From<UpstreamError> for AppError {
+    fn from(error: UpstreamError) -> Self {
+        Self::Upstream(error)
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Upstream(UpstreamError::from(error))
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Database(error)
+    }
+}
+
+impl From<EncryptedPersistError> for AppError {
+    fn from(error: EncryptedPersistError) -> Self {
+        Self::Persistence(error)
+    }
+}
+
+impl From<PersistError> for AppError {
+    fn from(error: PersistError) -> Self {
+        Self::Persistence(EncryptedPersistError::from(error))
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Envelope(envelope) => envelope.into_response(),
+            Self::Validation(errors) => errors.into_response(),
+            Self::Upstream(error) => error.into_response(),
+            error @ (Self::Database(_) | Self::Persistence(_)) => {
+                error._dependency_failure().into_response()
+            }
+            Self::Suspicious(error) => Suspicious::flag(*error),
+        }
+    }
+}
+
+// </editor-fold desc="// AppError ...">
+
+// <editor-fold desc="// UpstreamError ...">
+
+/// A failed call to an external service, reported per a single
+/// policy (rather than passing the service's own status through)
+#[derive(Clone, Debug)]
+pub enum UpstreamError {
+    /// The call timed out (`504 Gateway Timeout`)
+    Timeout(String),
+    /// The service is rate limiting our calls (`429 Too Many
+    /// Requests`, with its `Retry-After` header passed through)
+    RateLimited(Option<HeaderValue>),
+    /// The call failed, or the service responded
+    /// with an error (`502 Bad Gateway`)
+    Unavailable(String),
+    /// The service's response couldn't be decoded (`502 Bad Gateway`)
+    Decode(String),
+}
+
+impl UpstreamError {
+    /// The status with which the error is reported
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            Self::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            Self::Unavailable(_) | Self::Decode(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    /// Pass the supplied (upstream) response through if
+    /// successful, or describe its failure otherwise
+    pub fn check(response: reqwest::Response) -> Result<reqwest::Response, Self> {
+        match response.status() {
+            status if status.is_success() => Ok(response),
+            StatusCode::TOO_MANY_REQUESTS => Err(Self::RateLimited(
+                response.headers().get(header::RETRY_AFTER).cloned(),
+            )),
+            status => Err(Self::Unavailable(format!(
+                "{} responded with {status}",
+                response.url().host_str().unwrap_or("upstream service")
+            ))),
+        }
+    }
+}
+
+impl From<reqwest::Error> for UpstreamError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Self::Timeout(error.to_string())
+        } else if error.is_decode() {
+            Self::Decode(error.to_string())
+        } else if error.status() == Some(StatusCode::TOO_MANY_REQUESTS) {
+            Self::RateLimited(None)
+        } else {
+            Self::Unavailable(error.to_string())
+        }
+    }
+}
+
+impl Display for UpstreamError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match self {
+            Self::Timeout(error) => write!(formatter, "upstream call timed out: {error}"),
+            Self::RateLimited(_) => formatter.write_str("upstream service is rate limiting calls"),
+            Self::Unavailable(error) => write!(formatter, "upstream call failed: {error}"),
+            Self::Decode(error) => write!(formatter, "malformed upstream response: {error}"),
+        }
+    }
+}
+
+impl IntoResponse for UpstreamError {
+    fn into_response(self) -> Response {
+        tracing::warn!("{self}");
+
+        let mut response = ErrorEnvelope::new(self.status(), &self).into_response();
+
+        if let Self::RateLimited(Some(retry_after)) = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, retry_after);
+        }
+
+        response
+    }
+}
+
+// </editor-fold desc="// UpstreamError ...">
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Third-Party Imports
+    use axum::{
+        http::{header, HeaderValue, StatusCode},
+        response::IntoResponse,
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use serde_json::json;
+    use shuttle_persist::PersistError;
+
+    // Crate-Level Imports
+    use super::{AppError, ErrorEnvelope, UpstreamError};
+
+    /// Test that upstream responses are mapped per the service's policy
+    #[rstest]
+    #[case::success(StatusCode::OK, None, StatusCode::OK)]
+    #[case::rate_limited(
+        StatusCode::TOO_MANY_REQUESTS,
+        Some("30"),
+        StatusCode::TOO_MANY_REQUESTS
+    )]
+    #[case::server_error(StatusCode::INTERNAL_SERVER_ERROR, None, StatusCode::BAD_GATEWAY)]
+    #[case::unavailable(StatusCode::SERVICE_UNAVAILABLE, None, StatusCode::BAD_GATEWAY)]
+    #[case::client_error(StatusCode::BAD_REQUEST, None, StatusCode::BAD_GATEWAY)]
+    #[test_log::test(tokio::test)]
+    async fn test_upstream_error_mapping(
+        #[case] upstream_status: StatusCode,
+        #[case] retry_after: Option<&'static str>,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let mut upstream = axum::http::Response::builder().status(upstream_status);
+
+        if let Some(retry_after) = retry_after {
+            upstream = upstream.header(header::RETRY_AFTER, retry_after);
+        }
+
+        let response = match UpstreamError::check(reqwest::Response::from(upstream.body("")?)) {
+            Ok(response) => {
+                assert_eq!(StatusCode::OK, expected_status);
+                assert_eq!(upstream_status, response.status());
+                return Ok(());
+            }
+            Err(error) => {
+                assert_eq!(expected_status, error.status());
+                error.into_response()
+            }
+        };
+
+        assert_eq!(expected_status, response.status());
+        assert_eq!(
+            retry_after.map(HeaderValue::from_static),
+            response.headers().get(header::RETRY_AFTER).cloned()
+        );
+
+        let envelope: ErrorEnvelope =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(expected_status.as_u16(), envelope.status);
+
+        Ok(())
+    }
+
+    /// Test that undecodable upstream responses
+    /// are reported as `502 Bad Gateway`
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_upstream_decode_error() -> anyhow::Result<()> {
+        let upstream = reqwest::Response::from(axum::http::Response::new("not json"));
+        let error = UpstreamError::from(
+            upstream
+                .json::<serde_json::Value>()
+                .await
+                .expect_err("body isn't json"),
+        );
+
+        assert!(matches!(error, UpstreamError::Decode(_)));
+        assert_eq!(StatusCode::BAD_GATEWAY, error.into_response().status());
+
+        Ok(())
+    }
+
+    /// Test that each kind of failure is reported
+    /// with its status, as an `ErrorEnvelope`
+    #[rstest]
+    #[case::database(
+        AppError::from(sqlx::Error::RowNotFound),
+        StatusCode::FAILED_DEPENDENCY,
+        "database query failed"
+    )]
+    #[case::persistence(
+        AppError::from(PersistError::Open(std::io::ErrorKind::NotFound.into())),
+        StatusCode::FAILED_DEPENDENCY,
+        "persistent store failed"
+    )]
+    #[case::tuple(
+        AppError::from((StatusCode::UNPROCESSABLE_ENTITY, String::from("nope"))),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "nope"
+    )]
+    #[case::upstream(
+        AppError::from(UpstreamError::Timeout(String::from("operation timed out"))),
+        StatusCode::GATEWAY_TIMEOUT,
+        "upstream call timed out: operation timed out"
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_app_error_responses(
+        #[case] error: AppError,
+        #[case] expected_status: StatusCode,
+        #[case] message: &str,
+    ) -> anyhow::Result<()> {
+        assert_eq!(expected_status, error.status());
+
+        let response = error.with_details(json!({ "request": [] })).into_response();
+
+        assert_eq!(expected_status, response.status());
+
+        let envelope: ErrorEnvelope =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(expected_status.as_u16(), envelope.status);
+        assert_eq!(message, envelope.error);
+        assert_eq!(Some(json!({ "request": [] })), envelope.details);
+
+        Ok(())
+    }
+
+    /// Test that envelopes are serialized with
+    /// a machine-readable code for their status
+    #[rstest]
+    #[case::not_found(StatusCode::NOT_FOUND, "not_found")]
+    #[case::teapot(StatusCode::IM_A_TEAPOT, "im_a_teapot")]
+    #[case::unknown(StatusCode::from_u16(599).unwrap(), "error")]
+    #[test_log::test]
+    fn test_envelope_wire_format(
+        #[case] status: StatusCode,
+        #[case] expected_code: &str,
+    ) -> anyhow::Result<()> {
+        let envelope =
+            ErrorEnvelope::new(status, "oops").with_request_id(Some(String::from("abc")));
+        let serialized = serde_json::to_value(&envelope)?;
+
+        assert_eq!(
+            json!({"error": {
+                "code": expected_code,
+                "status": status.as_u16(),
+                "message": "oops",
+                "details": null,
+                "request_id": "abc",
+            }}),
+            serialized
+        );
+        assert_eq!(envelope, serde_json::from_value(serialized)?);
+
+        Ok(())
+    }
+
+    /// Test that timed out upstream calls are
+    /// reported as `504 Gateway Timeout`
+    #[rstest]
+    #[test_log::test]
+    fn test_upstream_timeout() {
+        let error = UpstreamError::Timeout(String::from("operation timed out"));
+
+        assert_eq!(StatusCode::GATEWAY_TIMEOUT, error.status());
+        assert_eq!(StatusCode::GATEWAY_TIMEOUT, error.into_response().status());
+    }
+}
@@ -0,0 +1,506 @@
+//! ## Request/Response Examples
+//!
+
+// Standard Library Imports
+use core::str::FromStr;
+use std::{collections::BTreeMap, fs, path::Path as FilePath, sync::Arc};
+
+// Third-Party Imports
+use anyhow::Context;
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderName, HeaderValue, Method, Request, StatusCode},
+    routing::Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tower::ServiceExt;
+
+// Crate-Level Imports
+use crate::{
+    errors::ErrorEnvelope,
+    responses::{ApiOk, ApiResult},
+    solutions::DAYS,
+    state::ShuttleAppState,
+};
+
+/// The directory the service's examples are loaded from
+pub const EXAMPLES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/examples");
+
+// <editor-fold desc="// Example ...">
+
+/// A curated request/response pair for one of the service's endpoints
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Example {
+    /// a short, elf-readable title
+    pub name: String,
+    /// what the example demonstrates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// the request to send
+    pub request: ExampleRequest,
+    /// the response to expect
+    pub response: ExampleResponse,
+}
+
+/// An [`Example`]'s request
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExampleRequest {
+    /// the request's HTTP method
+    pub method: String,
+    /// the request's path (and query)
+    pub path: String,
+    /// the request's headers
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// the request's body (sent verbatim if a
+    /// string, or as `application/json` otherwise)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+/// An [`Example`]'s (expected) response
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExampleResponse {
+    /// the response's HTTP status code
+    pub status: u16,
+    /// the response's body (verbatim if a string,
+    /// or as parsed JSON otherwise)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+/// The outcome of [replaying](Example::replay) an [`Example`]
+#[cfg_attr(test, derive(Deserialize))]
+#[derive(Debug, Serialize)]
+pub struct ExampleReplay {
+    /// the replayed example's name
+    pub example: String,
+    /// whether the actual response matched the expected one
+    pub passed: bool,
+    /// the documented response
+    pub expected: ExampleResponse,
+    /// the response the service actually produced
+    pub actual: ExampleResponse,
+    /// how the actual response differs from the expected one
+    pub differences: Vec<String>,
+}
+
+impl Example {
+    /// The example's name, as used in paths (lowercase
+    /// alphanumerics, separated by hyphens)
+    pub fn slug(&self) -> String {
+        self.name
+            .to_lowercase()
+            .split(|char: char| !char.is_ascii_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<&str>>()
+            .join("-")
+    }
+
+    /// Build the example's request
+    pub fn to_request(&self) -> anyhow::Result<Request<Body>> {
+        let mut builder = Request::builder()
+            .method(self.request.method.as_str())
+            .uri(self.request.path.as_str());
+
+        for (name, value) in &self.request.headers {
+            builder = builder.header(name, value);
+        }
+
+        let body = match &self.request.body {
+            None => Body::empty(),
+            Some(Value::String(text)) => Body::from(text.clone()),
+            Some(json) => {
+                builder = builder.header(header::CONTENT_TYPE, "application/json");
+                Body::from(json.to_string())
+            }
+        };
+
+        Ok(builder.body(body)?)
+    }
+
+    /// Feed the example's request through the supplied
+    /// router, comparing the response to the expected one
+    pub async fn replay(&self, router: Router) -> anyhow::Result<ExampleReplay> {
+        let response = router.oneshot(self.to_request()?).await?;
+        let status = response.status().as_u16();
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+        let text = String::from_utf8_lossy(&content).into_owned();
+
+        let body = match &self.response.body {
+            Some(Value::String(_)) => Value::String(text),
+            _ => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+        };
+
+        let mut differences = Vec::new();
+
+        if status != self.response.status {
+            differences.push(format!(
+                "status: expected {}, got {status}",
+                self.response.status
+            ));
+        }
+
+        if let Some(expected) = &self.response.body {
+            Self::_diff("body", expected, &body, &mut differences);
+        }
+
+        Ok(ExampleReplay {
+            example: self.name.clone(),
+            passed: differences.is_empty(),
+            expected: self.response.clone(),
+            actual: ExampleResponse {
+                status,
+                body: Some(body),
+            },
+            differences,
+        })
+    }
+
+    /// Describe every difference between the supplied values
+    fn _diff(path: &str, expected: &Value, actual: &Value, differences: &mut Vec<String>) {
+        match (expected, actual) {
+            (Value::Object(expected), Value::Object(actual)) => {
+                for (key, value) in expected {
+                    match actual.get(key) {
+                        Some(other) => {
+                            Self::_diff(&format!("{path}.{key}"), value, other, differences);
+                        }
+                        None => differences.push(format!("{path}.{key}: missing")),
+                    }
+                }
+
+                for key in actual.keys().filter(|key| !expected.contains_key(*key)) {
+                    differences.push(format!("{path}.{key}: unexpected"));
+                }
+            }
+            (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+                for (index, (value, other)) in expected.iter().zip(actual).enumerate() {
+                    Self::_diff(&format!("{path}[{index}]"), value, other, differences);
+                }
+            }
+            (Value::Array(expected), Value::Array(actual)) => differences.push(format!(
+                "{path}: expected {} items, got {}",
+                expected.len(),
+                actual.len()
+            )),
+            (expected, actual) if expected != actual => {
+                differences.push(format!("{path}: expected {expected}, got {actual}"));
+            }
+            _ => {}
+        }
+    }
+
+    /// Check that the example describes a
+    /// plausible request to the specified day
+    fn validate(&self, day: i8) -> Result<(), String> {
+        Method::from_str(&self.request.method)
+            .map_err(|_| format!("invalid method: {}", self.request.method))?;
+
+        let prefix = format!("/{day}");
+        let path = self.request.path.split('?').next().unwrap_or_default();
+
+        if path != prefix && !path.starts_with(&format!("{prefix}/")) {
+            return Err(format!("path must start with {prefix}: {path}"));
+        }
+
+        for (name, value) in &self.request.headers {
+            HeaderName::from_str(name).map_err(|_| format!("invalid header name: {name}"))?;
+            HeaderValue::from_str(value).map_err(|_| format!("invalid {name} header value"))?;
+        }
+
+        StatusCode::from_u16(self.response.status)
+            .map_err(|_| format!("invalid status: {}", self.response.status))?;
+
+        Ok(())
+    }
+}
+
+// </editor-fold desc="// Example ...">
+
+// <editor-fold desc="// ExampleCatalog ...">
+
+/// The contents of an `assets/examples/day-:n.json` file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DayExamples {
+    /// the day the examples belong to
+    day: i8,
+    /// the day's examples
+    examples: Vec<Example>,
+}
+
+/// Every day's [examples](Example), validated at startup
+#[derive(Clone, Debug, Default)]
+pub struct ExampleCatalog(Arc<BTreeMap<i8, Vec<Example>>>);
+
+impl ExampleCatalog {
+    /// Load (and validate) every `day-:n.json`
+    /// file in the specified directory
+    pub fn load(dir: impl AsRef<FilePath>) -> anyhow::Result<Self> {
+        let mut catalog = BTreeMap::new();
+
+        for entry in fs::read_dir(dir.as_ref())
+            .with_context(|| format!("reading {}", dir.as_ref().display()))?
+        {
+            let path = entry?.path();
+
+            let Some(day) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("day-")?.strip_suffix(".json"))
+            else {
+                continue;
+            };
+
+            let content =
+                fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+
+            Self::_parse(day, &content)
+                .with_context(|| format!("invalid examples in {}", path.display()))
+                .map(|(day, examples)| catalog.insert(day, examples))?;
+        }
+
+        Ok(Self(Arc::new(catalog)))
+    }
+
+    fn _parse(file_day: &str, content: &str) -> anyhow::Result<(i8, Vec<Example>)> {
+        let DayExamples { day, examples } = serde_json::from_str(content)?;
+
+        if file_day.parse::<i8>().ok() != Some(day) {
+            anyhow::bail!("file is named for day {file_day} but contains day {day}");
+        }
+
+        if !DAYS.iter().any(|registration| (registration.day)() == day) {
+            anyhow::bail!("day {day} isn't a registered solutions module");
+        }
+
+        for example in &examples {
+            example
+                .validate(day)
+                .map_err(|error| anyhow::anyhow!("{}: {error}", example.name))?;
+        }
+
+        Ok((day, examples))
+    }
+
+    /// Get the specified day's examples (if any)
+    pub fn get(&self, day: i8) -> Option<&[Example]> {
+        self.0.get(&day).map(Vec::as_slice)
+    }
+
+    /// Find the specified day's example by name (or slug)
+    pub fn find(&self, day: i8, name: &str) -> Option<&Example> {
+        self.get(day)?
+            .iter()
+            .find(|example| example.name == name || example.slug() == name)
+    }
+}
+
+// </editor-fold desc="// ExampleCatalog ...">
+
+/// List the specified day's request/response examples
+#[tracing::instrument(skip(state))]
+pub async fn get_day_examples(
+    State(state): State<ShuttleAppState>,
+    Path(day): Path<i8>,
+) -> ApiResult<Vec<Example>> {
+    if !DAYS.iter().any(|registration| (registration.day)() == day) {
+        return Err(
+            ErrorEnvelope::new(StatusCode::NOT_FOUND, format!("no such day: {day}")).into(),
+        );
+    }
+
+    Ok(ApiOk(
+        state
+            .examples
+            .get(day)
+            .map(<[Example]>::to_vec)
+            .unwrap_or_default(),
+    ))
+}
+
+/// Replay the specified example against the live router
+#[tracing::instrument(skip(state))]
+pub async fn replay_day_example(
+    State(state): State<ShuttleAppState>,
+    Path((day, name)): Path<(i8, String)>,
+) -> ApiResult<ExampleReplay> {
+    let example = state.examples.find(day, &name).ok_or_else(|| {
+        ErrorEnvelope::new(
+            StatusCode::NOT_FOUND,
+            format!("no such example for day {day}: {name}"),
+        )
+    })?;
+
+    example
+        .replay(crate::router(state.clone()))
+        .await
+        .map(ApiOk)
+        .map_err(|error| ErrorEnvelope::new(StatusCode::INTERNAL_SERVER_ERROR, error).into())
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Third-Party Imports
+    use axum::{
+        body::Body,
+        http::{header as headers, Request, StatusCode},
+    };
+    use pretty_assertions::{assert_eq, assert_str_eq};
+    use rstest::rstest;
+    use serde_json::Value;
+
+    // Crate-Level Imports
+    use super::{Example, ExampleCatalog, ExampleReplay, ExampleResponse, EXAMPLES_DIR};
+    use crate::router;
+    use crate::routes;
+    use crate::utils::{service, state, TestService};
+
+    /// Test that every shipped example replays against the
+    /// service with exactly the documented response
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_shipped_examples_replay() -> anyhow::Result<()> {
+        let catalog = ExampleCatalog::load(EXAMPLES_DIR)?;
+
+        for (day, examples) in catalog.0.iter() {
+            assert!(!examples.is_empty(), "day {day} has no examples");
+
+            for example in examples {
+                let replay = example.replay(router(state())).await?;
+
+                assert!(replay.passed, "{}: {:?}", example.name, replay.differences);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Test that malformed example files are rejected
+    #[rstest]
+    #[case::misnamed("6", r#"{"day": 7, "examples": []}"#)]
+    #[case::unregistered_day("2", r#"{"day": 2, "examples": []}"#)]
+    #[case::foreign_path(
+        "6",
+        r#"{"day": 6, "examples": [{"name": "x", "request": {"method": "POST", "path": "/7/bake"}, "response": {"status": 200}}]}"#
+    )]
+    #[case::lookalike_path(
+        "1",
+        r#"{"day": 1, "examples": [{"name": "x", "request": {"method": "GET", "path": "/12/ulids"}, "response": {"status": 200}}]}"#
+    )]
+    #[case::bad_method(
+        "6",
+        r#"{"day": 6, "examples": [{"name": "x", "request": {"method": "P OST", "path": "/6"}, "response": {"status": 200}}]}"#
+    )]
+    #[case::bad_status(
+        "6",
+        r#"{"day": 6, "examples": [{"name": "x", "request": {"method": "POST", "path": "/6"}, "response": {"status": 42}}]}"#
+    )]
+    #[case::unknown_field(
+        "6",
+        r#"{"day": 6, "examples": [{"name": "x", "request": {"method": "POST", "path": "/6", "bdy": "elf"}, "response": {"status": 200}}]}"#
+    )]
+    #[test_log::test]
+    fn test_invalid_examples(#[case] file_day: &str, #[case] content: &str) {
+        assert!(ExampleCatalog::_parse(file_day, content).is_err());
+    }
+
+    /// Test that `get_day_examples` serves registered
+    /// days' examples and rejects unknown days
+    #[rstest]
+    #[case::with_examples(7, StatusCode::OK, true)]
+    #[case::without_examples(-1, StatusCode::OK, false)]
+    #[case::unknown_day(2, StatusCode::NOT_FOUND, false)]
+    #[test_log::test(tokio::test)]
+    async fn test_get_day_examples(
+        service: TestService,
+        #[case] day: i8,
+        #[case] expected_status: StatusCode,
+        #[case] expected_examples: bool,
+    ) -> anyhow::Result<()> {
+        let response = service.resolve(routes::day_examples(day).as_str()).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status.is_success() {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+            let examples = serde_json::from_slice::<Vec<Value>>(&content)?;
+
+            assert_eq!(expected_examples, !examples.is_empty());
+        }
+
+        Ok(())
+    }
+
+    /// Test that `Example::replay` summarizes how
+    /// the actual response differs from the expected one
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_replay_differences() -> anyhow::Result<()> {
+        let example = serde_json::from_value::<Example>(serde_json::json!({
+            "name": "Miscounted elves",
+            "request": {"method": "POST", "path": "/6", "body": "elf on a shelf"},
+            "response": {
+                "status": 201,
+                "body": {"elf": 3, "elf on a shelf": 1, "shelf with elves on it": 0}
+            }
+        }))?;
+
+        let replay = example.replay(router(state())).await?;
+
+        assert!(!replay.passed);
+        assert_eq!(
+            vec![
+                "status: expected 201, got 200",
+                "body.elf: expected 3, got 2",
+                "body.shelf with elves on it: missing",
+                "body.shelf with no elf on it: unexpected",
+            ],
+            replay.differences
+        );
+
+        Ok(())
+    }
+
+    /// Test that `replay_day_example` finds examples
+    /// by name or slug and replays them
+    #[rstest]
+    #[case::by_slug(7, "decode-a-recipe-cookie", StatusCode::OK)]
+    #[case::by_name(7, "Decode%20a%20recipe%20cookie", StatusCode::OK)]
+    #[case::unknown_example(7, "eat-the-cookie", StatusCode::NOT_FOUND)]
+    #[case::wrong_day(6, "decode-a-recipe-cookie", StatusCode::NOT_FOUND)]
+    #[test_log::test(tokio::test)]
+    async fn test_replay_day_example(
+        service: TestService,
+        #[case] day: i8,
+        #[case] name: &str,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(Request::post(routes::replay_day_example(day, name)).body(Body::empty())?)
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status.is_success() {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+            let replay = serde_json::from_slice::<ExampleReplay>(&content)?;
+
+            assert!(replay.passed, "{:?}", replay.differences);
+            assert_eq!(replay.expected, replay.actual);
+        }
+
+        Ok(())
+    }
+}
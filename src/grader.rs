@@ -0,0 +1,612 @@
+//! ## Self-Validation
+//!
+//! The official task and bonus examples of each day's
+//! challenge, replayed against the service's own router
+//! (much like the CCH validator would replay them)
+
+// Third-Party Imports
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// Crate-Level Imports
+use crate::{
+    errors::ErrorEnvelope,
+    examples::Example,
+    responses::{ApiOk, ApiResult},
+    state::ShuttleAppState,
+};
+
+// <editor-fold desc="// GraderCase ...">
+
+/// The part of a day's challenge a [`GraderCase`] checks
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Part {
+    /// the day's main task
+    Task,
+    /// the day's bonus task(s)
+    Bonus,
+}
+
+/// One of a day's official examples
+#[derive(Clone, Debug)]
+pub struct GraderCase {
+    /// the part of the challenge the case checks
+    pub part: Part,
+    /// the request to send and the response to expect
+    pub example: Example,
+}
+
+impl GraderCase {
+    fn new(part: Part, example: Value) -> Self {
+        Self {
+            part,
+            example: serde_json::from_value(example).expect("malformed grader case"),
+        }
+    }
+
+    /// Get the official examples of the specified day
+    /// (or `None` if the day can't be self-validated)
+    ///
+    /// Days whose examples are multi-request sequences
+    /// (12's save/load, 13 and 18's orders, 19's sockets)
+    /// or binary uploads (11's red pixels, 20's archives)
+    /// only have their single-request examples encoded
+    /// here, if they have any at all.
+    pub fn official(day: i8) -> Option<Vec<Self>> {
+        let cases = match day {
+            -1 => vec![
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "Hello, world",
+                        "request": {"method": "GET", "path": "/"},
+                        "response": {"status": 200, "body": "Hello Shuttle CCH 2023!"}
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "Fake error",
+                        "request": {"method": "GET", "path": "/-1/error"},
+                        "response": {"status": 500}
+                    }),
+                ),
+            ],
+            1 => vec![
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "Cube the bits",
+                        "request": {"method": "GET", "path": "/1/4/8"},
+                        "response": {"status": 200, "body": "1728"}
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "The sled ID system",
+                        "request": {"method": "GET", "path": "/1/4/5/8/10"},
+                        "response": {"status": 200, "body": "27"}
+                    }),
+                ),
+            ],
+            4 => vec![
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "Reindeer cheer",
+                        "request": {
+                            "method": "POST",
+                            "path": "/4/strength",
+                            "body": [
+                                {"name": "Dasher", "strength": 5},
+                                {"name": "Dancer", "strength": 6},
+                                {"name": "Donner", "strength": 4},
+                                {"name": "Prancer", "strength": 7}
+                            ]
+                        },
+                        "response": {"status": 200, "body": "22"}
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "Cursed candy eating contest",
+                        "request": {
+                            "method": "POST",
+                            "path": "/4/contest",
+                            "body": [
+                                {
+                                    "name": "Dasher",
+                                    "strength": 5,
+                                    "speed": 50.4,
+                                    "height": 80,
+                                    "antler_width": 36,
+                                    "snow_magic_power": 9001,
+                                    "favorite_food": "hay",
+                                    "cAnD13s_3ATeN-yesT3rdAy": 2
+                                },
+                                {
+                                    "name": "Dancer",
+                                    "strength": 6,
+                                    "speed": 48.2,
+                                    "height": 65,
+                                    "antler_width": 37,
+                                    "snow_magic_power": 4004,
+                                    "favorite_food": "grass",
+                                    "cAnD13s_3ATeN-yesT3rdAy": 5
+                                }
+                            ]
+                        },
+                        "response": {
+                            "status": 200,
+                            "body": {
+                                "fastest": "Speeding past the finish line with a strength of 5 is Dasher",
+                                "tallest": "Dasher is standing tall with his 36 cm wide antlers",
+                                "magician": "Dasher could blast you away with a snow magic power of 9001",
+                                "consumer": "Dancer ate lots of candies, but also some grass"
+                            }
+                        }
+                    }),
+                ),
+            ],
+            5 => vec![
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "Slicing the loop",
+                        "request": {
+                            "method": "POST",
+                            "path": "/5?offset=3&limit=5",
+                            "body": [
+                                "Alice", "Bob", "Charlie", "David", "Eve", "Frank", "Grace",
+                                "Hank", "Ingrid", "Jack", "Kyle", "Leo", "Michael", "Nathan",
+                                "Olivia"
+                            ]
+                        },
+                        "response": {
+                            "status": 200,
+                            "body": ["David", "Eve", "Frank", "Grace", "Hank"]
+                        }
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "Time to page some names",
+                        "request": {
+                            "method": "POST",
+                            "path": "/5?offset=10&limit=4&split=4",
+                            "body": [
+                                "Alice", "Bob", "Charlie", "David", "Eve", "Frank", "Grace",
+                                "Hank", "Ingrid", "Jack", "Kyle", "Leo", "Michael", "Nathan",
+                                "Olivia"
+                            ]
+                        },
+                        "response": {
+                            "status": 200,
+                            "body": [["Kyle", "Leo", "Michael", "Nathan"]]
+                        }
+                    }),
+                ),
+            ],
+            6 => vec![
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "Never count on an elf",
+                        "request": {
+                            "method": "POST",
+                            "path": "/6",
+                            "body": "The mischievous elf peeked out from behind the toy workshop, and another elf joined in the festive dance. Look, there is also an elf on that shelf!"
+                        },
+                        "response": {
+                            "status": 200,
+                            "body": {"elf": 4, "elf on a shelf": 0, "shelf with no elf on it": 1}
+                        }
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "Shelf under an elf?",
+                        "request": {
+                            "method": "POST",
+                            "path": "/6",
+                            "body": "there is an elf on a shelf on an elf. there is also another shelf in Belfast."
+                        },
+                        "response": {
+                            "status": 200,
+                            "body": {"elf": 5, "elf on a shelf": 1, "shelf with no elf on it": 1}
+                        }
+                    }),
+                ),
+            ],
+            7 => vec![
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "Based encoding, 64th edition",
+                        "request": {
+                            "method": "GET",
+                            "path": "/7/decode",
+                            "headers": {"cookie": "recipe=eyJmbG91ciI6MTAwLCJjaG9jb2xhdGUgY2hpcHMiOjIwfQ=="}
+                        },
+                        "response": {"status": 200, "body": {"flour": 100, "chocolate chips": 20}}
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "The secret cookie recipe",
+                        "request": {
+                            "method": "GET",
+                            "path": "/7/bake",
+                            "headers": {
+                                "cookie": "recipe=eyJyZWNpcGUiOnsiZmxvdXIiOjk1LCJzdWdhciI6NTAsImJ1dHRlciI6MzAsImJha2luZyBwb3dkZXIiOjEwLCJjaG9jb2xhdGUgY2hpcHMiOjUwfSwicGFudHJ5Ijp7ImZsb3VyIjozODUsInN1Z2FyIjo1MDcsImJ1dHRlciI6MjEyMiwiYmFraW5nIHBvd2RlciI6ODY1LCJjaG9jb2xhdGUgY2hpcHMiOjQ1N319"
+                            }
+                        },
+                        "response": {
+                            "status": 200,
+                            "body": {
+                                "cookies": 4,
+                                "pantry": {
+                                    "flour": 5,
+                                    "sugar": 307,
+                                    "butter": 2002,
+                                    "baking powder": 825,
+                                    "chocolate chips": 257
+                                }
+                            }
+                        }
+                    }),
+                ),
+            ],
+            8 => vec![
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "IT'S PIKACHU!",
+                        "request": {"method": "GET", "path": "/8/weight/25"},
+                        "response": {"status": 200, "body": 6.0}
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "That's gonna leave a dent",
+                        "request": {"method": "GET", "path": "/8/drop/25"},
+                        "response": {"status": 200, "body": 84.10707461325713}
+                    }),
+                ),
+            ],
+            11 => vec![Self::new(
+                Part::Task,
+                json!({
+                    "name": "Served on a silver platter",
+                    "request": {"method": "GET", "path": "/11/assets/decoration.png"},
+                    "response": {"status": 200}
+                }),
+            )],
+            12 => vec![
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "Unanimously legendary IDentifier (ULID)",
+                        "request": {
+                            "method": "POST",
+                            "path": "/12/ulids",
+                            "body": [
+                                "01BJQ0E1C3Z56ABCD0E11HYX4M",
+                                "01BJQ0E1C3Z56ABCD0E11HYX5N",
+                                "01BJQ0E1C3Z56ABCD0E11HYX6Q",
+                                "01BJQ0E1C3Z56ABCD0E11HYX7R",
+                                "01BJQ0E1C3Z56ABCD0E11HYX8P"
+                            ]
+                        },
+                        "response": {
+                            "status": 200,
+                            "body": [
+                                "015cae07-0583-f94c-a5b1-a070431f7516",
+                                "015cae07-0583-f94c-a5b1-a070431f74f8",
+                                "015cae07-0583-f94c-a5b1-a070431f74d7",
+                                "015cae07-0583-f94c-a5b1-a070431f74b5",
+                                "015cae07-0583-f94c-a5b1-a070431f7494"
+                            ]
+                        }
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "Let's be merry!",
+                        "request": {
+                            "method": "POST",
+                            "path": "/12/ulids/5",
+                            "body": [
+                                "00WEGGF0G0J5HEYXS3D7RWZGV8",
+                                "76EP4G39R8JD1N8AQNYDVJBRCF",
+                                "018CJ7KMG0051CDCS3B7BFJ3AK",
+                                "00Y986KPG0AMGB78RD45E9109K",
+                                "010451HTG0NQ7H2AP28T3K1VW5",
+                                "01HH9SJEG0KY16H81S3N1BMXM4",
+                                "01HH9SJEG0P9M22Z9VGHH9C8CX",
+                                "017F8YY0G0NQA16HHC2QT5JD6X",
+                                "03QCPC7P003V1NND3B3QJW72QJ"
+                            ]
+                        },
+                        "response": {
+                            "status": 200,
+                            "body": {"christmas eve": 3, "weekday": 1, "in the future": 2, "LSB is 1": 6}
+                        }
+                    }),
+                ),
+            ],
+            13 => vec![Self::new(
+                Part::Task,
+                json!({
+                    "name": "SQL? Sequel? Squeel??",
+                    "request": {"method": "GET", "path": "/13/sql"},
+                    "response": {"status": 200, "body": "20231213"}
+                }),
+            )],
+            14 => vec![
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "Ho-ho, Toymaking Magic Land! (HTML)",
+                        "request": {
+                            "method": "POST",
+                            "path": "/14/unsafe",
+                            "body": {"content": "<h1>Welcome to the North Pole!</h1>"}
+                        },
+                        "response": {"status": 200}
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "Safety 2nd",
+                        "request": {
+                            "method": "POST",
+                            "path": "/14/safe",
+                            "body": {"content": "<script>alert(\"XSS Attack!\")</script>"}
+                        },
+                        "response": {"status": 200}
+                    }),
+                ),
+            ],
+            15 => vec![
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "Naughty or nice strings (nice)",
+                        "request": {"method": "POST", "path": "/15/nice", "body": {"input": "hello there"}},
+                        "response": {"status": 200, "body": {"result": "nice"}}
+                    }),
+                ),
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "Naughty or nice strings (naughty)",
+                        "request": {"method": "POST", "path": "/15/nice", "body": {"input": "abcd"}},
+                        "response": {"status": 400, "body": {"result": "naughty"}}
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "Game of the year",
+                        "request": {"method": "POST", "path": "/15/game", "body": {"input": "mario"}},
+                        "response": {"status": 400, "body": {"result": "naughty", "reason": "8 chars"}}
+                    }),
+                ),
+            ],
+            21 => vec![
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "Flat squares on a round sphere?",
+                        "request": {
+                            "method": "GET",
+                            "path": "/21/coords/0100111110010011000110011001010101011111000010100011110001011011"
+                        },
+                        "response": {"status": 200, "body": "83°39'54.324''N 30°37'40.584''W"}
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "Turbo-fast country lookup",
+                        "request": {
+                            "method": "GET",
+                            "path": "/21/country/0010000111110000011111100000111010111100000100111101111011000101"
+                        },
+                        "response": {"status": 200, "body": "Brazil"}
+                    }),
+                ),
+            ],
+            22 => vec![
+                Self::new(
+                    Part::Task,
+                    json!({
+                        "name": "Leave no gift behind!",
+                        "request": {"method": "POST", "path": "/22/integers", "body": "888\n77\n888\n22\n77\n"},
+                        "response": {"status": 200, "body": "🎁".repeat(22)}
+                    }),
+                ),
+                Self::new(
+                    Part::Bonus,
+                    json!({
+                        "name": "The rocket's path",
+                        "request": {
+                            "method": "POST",
+                            "path": "/22/rocket",
+                            "body": "5\n0 1 0\n-2 2 3\n3 -3 -5\n1 1 5\n4 3 5\n4\n0 1\n2 4\n3 4\n1 2\n"
+                        },
+                        "response": {"status": 200, "body": "3 26.123"}
+                    }),
+                ),
+            ],
+            _ => return None,
+        };
+
+        Some(cases)
+    }
+}
+
+// </editor-fold desc="// GraderCase ...">
+
+// <editor-fold desc="// GraderReport ...">
+
+/// The outcome of replaying one [`GraderCase`]
+#[cfg_attr(test, derive(Deserialize))]
+#[derive(Debug, Serialize)]
+pub struct GraderResult {
+    /// the part of the challenge the case checks
+    pub part: Part,
+    /// the case's name
+    pub name: String,
+    /// whether the service responded as expected
+    pub passed: bool,
+    /// how the service's response differed from the expected one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub differences: Vec<String>,
+}
+
+/// The outcome of validating a day
+#[cfg_attr(test, derive(Deserialize))]
+#[derive(Debug, Serialize)]
+pub struct GraderReport {
+    /// the validated day
+    pub day: i8,
+    /// whether every case passed
+    pub passed: bool,
+    /// the outcome of each case
+    pub cases: Vec<GraderResult>,
+}
+
+// </editor-fold desc="// GraderReport ...">
+
+/// Replay the specified day's official examples against the live router
+#[tracing::instrument(skip(state))]
+pub async fn validate_day(
+    State(state): State<ShuttleAppState>,
+    Path(day): Path<i8>,
+) -> ApiResult<GraderReport> {
+    let cases = GraderCase::official(day).ok_or_else(|| {
+        ErrorEnvelope::new(
+            StatusCode::NOT_FOUND,
+            format!("no official examples for day {day}"),
+        )
+    })?;
+
+    let mut results = Vec::with_capacity(cases.len());
+
+    for GraderCase { part, example } in cases {
+        let result = match example.replay(crate::router(state.clone())).await {
+            Ok(replay) => GraderResult {
+                part,
+                name: replay.example,
+                passed: replay.passed,
+                differences: replay.differences,
+            },
+            Err(error) => GraderResult {
+                part,
+                name: example.name,
+                passed: false,
+                differences: vec![format!("replay failed: {error}")],
+            },
+        };
+
+        results.push(result);
+    }
+
+    Ok(ApiOk(GraderReport {
+        day,
+        passed: results.iter().all(|result| result.passed),
+        cases: results,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Third-Party Imports
+    use axum::http::StatusCode;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use super::{GraderCase, GraderReport, Part};
+    use crate::routes;
+    use crate::solutions::DAYS;
+    use crate::utils::{service, TestService};
+
+    /// Test that every day's official examples pass (for
+    /// the days whose handlers need no network or database)
+    #[rstest]
+    #[case::day_minus_one(-1)]
+    #[case::day_one(1)]
+    #[case::day_four(4)]
+    #[case::day_five(5)]
+    #[case::day_six(6)]
+    #[case::day_seven(7)]
+    #[case::day_eleven(11)]
+    #[case::day_twelve(12)]
+    #[case::day_fourteen(14)]
+    #[case::day_fifteen(15)]
+    #[case::day_twenty_two(22)]
+    #[test_log::test(tokio::test)]
+    async fn test_validate_day(service: TestService, #[case] day: i8) -> anyhow::Result<()> {
+        let response = service.resolve(routes::validate(day).as_str()).await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+        let report = serde_json::from_slice::<GraderReport>(&content)?;
+
+        assert!(report.passed, "{report:#?}");
+        assert_eq!(day, report.day);
+
+        Ok(())
+    }
+
+    /// Test that every official example belongs to (and
+    /// is correctly routed by) a registered day
+    #[rstest]
+    #[test_log::test]
+    fn test_official_examples_are_well_formed() {
+        for registration in DAYS.iter() {
+            let day = (registration.day)();
+
+            for case in GraderCase::official(day).unwrap_or_default() {
+                let path = case.example.request.path;
+
+                assert!(
+                    (day == -1 && path == "/") || path.starts_with(&format!("/{day}")),
+                    "{path}"
+                );
+            }
+        }
+    }
+
+    /// Test that days without official examples are rejected
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_validate_unknown_day(service: TestService) -> anyhow::Result<()> {
+        let response = service.resolve(routes::validate(2).as_str()).await?;
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+//! ## gRPC Endpoints
+//!
+
+// Standard Library Imports
+use std::{net::SocketAddr, sync::Arc};
+
+// Third-Party Imports
+use shuttle_secrets::SecretStore;
+use sqlx::error::Error as DbError;
+use tonic::{Request, Response, Status};
+
+// Crate-Level Imports
+use crate::{
+    metrics::{ServiceMetrics, TimedQuery},
+    solutions::{
+        day_13::{GiftOrder, OrderSchema},
+        day_18::{GiftOrderRegion, RegionalOrderTotal, RegionalTopGifts},
+    },
+};
+
+/// Protobuf types and service definitions
+/// compiled from `proto/orders.proto`
+#[allow(clippy::all, missing_debug_implementations)]
+pub mod proto {
+    tonic::include_proto!("orders");
+}
+
+use self::proto::orders_server::{Orders, OrdersServer};
+
+/// The address the gRPC server binds to when
+/// the `GRPC_ADDRESS` secret is unset (or invalid)
+const DEFAULT_ADDRESS: &str = "0.0.0.0:50051";
+
+// <editor-fold desc="// Conversions ...">
+
+impl From<proto::GiftOrder> for GiftOrder {
+    fn from(order: proto::GiftOrder) -> Self {
+        Self {
+            id: order.id,
+            quantity: order.quantity,
+            gift_name: order.gift_name,
+            region_id: order.region_id,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<RegionalOrderTotal> for proto::RegionalOrderTotal {
+    fn from(total: RegionalOrderTotal) -> Self {
+        Self {
+            region: total.region,
+            total: total.total_orders,
+        }
+    }
+}
+
+impl From<RegionalTopGifts> for proto::RegionalTopGifts {
+    fn from(gifts: RegionalTopGifts) -> Self {
+        Self {
+            region: gifts.region,
+            top_gifts: gifts.top_gifts,
+        }
+    }
+}
+
+fn _db_error_status(error: DbError) -> Status {
+    Status::failed_precondition(format!("{error}"))
+}
+
+// </editor-fold desc="// Conversions ...">
+
+// <editor-fold desc="// OrdersService ...">
+
+/// The gRPC surface of the gift orders subsystem
+#[derive(Clone, Debug)]
+pub struct OrdersService {
+    /// A pool of connections to the
+    /// service's PostgreSQL database
+    db: sqlx::PgPool,
+    /// Running totals of noteworthy
+    /// service events
+    metrics: Arc<ServiceMetrics>,
+}
+
+impl OrdersService {
+    /// Create a new service backed by the supplied
+    /// database, recording its queries' timings
+    pub fn new(db: sqlx::PgPool, metrics: Arc<ServiceMetrics>) -> Self {
+        Self { db, metrics }
+    }
+
+    /// Serve the gRPC surface on the address specified
+    /// by the `GRPC_ADDRESS` secret (or the default)
+    #[cfg_attr(tarpaulin, coverage(off))]
+    #[cfg_attr(tarpaulin, tarpaulin::skip)]
+    pub async fn serve(self, secrets: &SecretStore) -> anyhow::Result<()> {
+        let address = secrets
+            .get("GRPC_ADDRESS")
+            .and_then(|address| {
+                address
+                    .parse::<SocketAddr>()
+                    .map_err(|error| tracing::error!("ignoring GRPC_ADDRESS secret: {error}"))
+                    .ok()
+            })
+            .unwrap_or_else(|| DEFAULT_ADDRESS.parse().unwrap());
+
+        tracing::info!("serving gRPC endpoints on: {address}");
+
+        tonic::transport::Server::builder()
+            .add_service(OrdersServer::new(self))
+            .serve(address)
+            .await
+            .map_err(anyhow::Error::from)
+    }
+}
+
+#[tonic::async_trait]
+impl Orders for OrdersService {
+    #[tracing::instrument(ret, err(Debug), skip_all)]
+    async fn create_orders(
+        &self,
+        request: Request<proto::CreateOrdersRequest>,
+    ) -> Result<Response<proto::CreateOrdersResponse>, Status> {
+        let orders = request
+            .into_inner()
+            .orders
+            .into_iter()
+            .map(GiftOrder::from)
+            .collect::<Vec<GiftOrder>>();
+
+        if orders.is_empty() {
+            return Ok(Response::new(proto::CreateOrdersResponse::default()));
+        }
+
+        self.metrics
+            .time_query(
+                TimedQuery::OrderInserts,
+                GiftOrder::insert_many(orders.iter(), OrderSchema::Day18, &self.db),
+            )
+            .await
+            .map(|result| {
+                Response::new(proto::CreateOrdersResponse {
+                    inserted: result.rows_affected(),
+                })
+            })
+            .map_err(_db_error_status)
+    }
+
+    #[tracing::instrument(ret, err(Debug), skip_all)]
+    async fn get_totals(
+        &self,
+        _request: Request<proto::GetTotalsRequest>,
+    ) -> Result<Response<proto::GetTotalsResponse>, Status> {
+        let total = GiftOrder::total_ordered(OrderSchema::Day18, &self.db)
+            .await
+            .map_err(_db_error_status)?;
+
+        let regions = self
+            .metrics
+            .time_query(
+                TimedQuery::RegionalTotals,
+                GiftOrderRegion::total_orders_by_region(&self.db),
+            )
+            .await
+            .map_err(_db_error_status)?;
+
+        Ok(Response::new(proto::GetTotalsResponse {
+            total,
+            regions: regions.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    #[tracing::instrument(ret, err(Debug), skip_all)]
+    async fn top_gifts(
+        &self,
+        request: Request<proto::TopGiftsRequest>,
+    ) -> Result<Response<proto::TopGiftsResponse>, Status> {
+        self.metrics
+            .time_query(
+                TimedQuery::RegionalTopGifts,
+                GiftOrderRegion::top_n_most_popular(request.into_inner().number, &self.db),
+            )
+            .await
+            .map(|regions| {
+                Response::new(proto::TopGiftsResponse {
+                    regions: regions.into_iter().map(Into::into).collect(),
+                })
+            })
+            .map_err(_db_error_status)
+    }
+}
+
+// </editor-fold desc="// OrdersService ...">
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use core::{cmp::PartialEq, fmt::Debug, ops::BitOr, str::FromStr};
+
+    // Third-Party Imports
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use super::proto;
+    use crate::solutions::{
+        day_13::GiftOrder,
+        day_18::{RegionalOrderTotal, RegionalTopGifts},
+    };
+
+    /// Test that the protobuf messages convert
+    /// losslessly to and from the existing structs
+    #[rstest]
+    #[test_log::test]
+    fn test_proto_conversions() {
+        assert_eq!(
+            GiftOrder {
+                id: 1,
+                quantity: 5,
+                gift_name: String::from("Toy Train"),
+                region_id: 2,
+                ..GiftOrder::default()
+            },
+            GiftOrder::from(proto::GiftOrder {
+                id: 1,
+                quantity: 5,
+                gift_name: String::from("Toy Train"),
+                region_id: 2,
+            }),
+        );
+
+        assert_eq!(
+            proto::RegionalOrderTotal {
+                region: String::from("Pacific"),
+                total: 12,
+            },
+            proto::RegionalOrderTotal::from(RegionalOrderTotal {
+                region: String::from("Pacific"),
+                total_orders: 12,
+            }),
+        );
+
+        assert_eq!(
+            proto::RegionalTopGifts {
+                region: String::from("Pacific"),
+                top_gifts: vec![String::from("Toy Train"), String::from("Doll")],
+            },
+            proto::RegionalTopGifts::from(RegionalTopGifts {
+                region: String::from("Pacific"),
+                top_gifts: vec![String::from("Toy Train"), String::from("Doll")],
+            }),
+        );
+    }
+}
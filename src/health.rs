@@ -0,0 +1,306 @@
+//! ## Health & Readiness Reports
+//!
+//! The readiness report (`/readyz`) checks each of the service's
+//! dependencies (its database, its template engine, and its
+//! persistence directory), answering `503 Service Unavailable`
+//! unless every check passed. The liveness report (`/healthz`)
+//! checks only the process' own dependencies (skipping the
+//! database, whose outages restarting the process won't fix)
+//! and always answers `200 OK`, as the process is evidently up.
+
+// Standard Library Imports
+use std::time::{Duration, Instant};
+
+// Third-Party Imports
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+};
+use axum_template::TemplateEngine as _;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+// Crate-Level Imports
+use crate::state::ShuttleAppState;
+
+/// How long the database ping may take before it's abandoned
+pub const DB_PING_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The name under which the persistence probe value is written
+pub const PERSISTENCE_PROBE: &str = "healthz-probe";
+
+/// The file extension of the service's (compiled) templates
+const TEMPLATE_EXTENSION: &str = ".tpl";
+
+// <editor-fold desc="// DependencyStatus ...">
+
+/// The outcome of checking a single dependency
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct DependencyStatus {
+    /// the dependency checked
+    pub name: String,
+    /// whether the dependency is usable
+    pub healthy: bool,
+    /// how long the check took (in milliseconds)
+    pub latency_ms: u64,
+    /// why the dependency is unusable (if it is)
+    pub error: Option<String>,
+}
+
+impl DependencyStatus {
+    /// Time the supplied check of the named dependency
+    async fn check<Check>(name: &str, check: Check) -> Self
+    where
+        Check: core::future::Future<Output = Result<(), String>>,
+    {
+        let started = Instant::now();
+        let outcome = check.await;
+
+        if let Err(error) = &outcome {
+            tracing::warn!("{name} health check failed: {error}");
+        }
+
+        Self {
+            name: name.to_string(),
+            healthy: outcome.is_ok(),
+            latency_ms: started.elapsed().as_millis() as u64,
+            error: outcome.err(),
+        }
+    }
+}
+
+// </editor-fold desc="// DependencyStatus ...">
+
+// <editor-fold desc="// HealthReport ...">
+
+/// The status of every dependency of the service
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct HealthReport {
+    /// whether every dependency is usable
+    pub healthy: bool,
+    /// the deployment's version (per [`VERSION`](crate::version::VERSION))
+    pub version: String,
+    /// the status of each dependency
+    pub dependencies: Vec<DependencyStatus>,
+}
+
+impl HealthReport {
+    /// Check each of the supplied state's dependencies
+    #[tracing::instrument(skip_all)]
+    pub async fn check(state: &ShuttleAppState) -> Self {
+        let (database, templates, persistence) = tokio::join!(
+            DependencyStatus::check("database", _ping_database(state)),
+            DependencyStatus::check("templates", async { _check_templates(state) }),
+            DependencyStatus::check("persistence", async { _probe_persistence(state) }),
+        );
+
+        Self::from(vec![database, templates, persistence])
+    }
+
+    /// Check the supplied state's in-process
+    /// dependencies (i.e. all but its database)
+    #[tracing::instrument(skip_all)]
+    pub async fn check_local(state: &ShuttleAppState) -> Self {
+        let (templates, persistence) = tokio::join!(
+            DependencyStatus::check("templates", async { _check_templates(state) }),
+            DependencyStatus::check("persistence", async { _probe_persistence(state) }),
+        );
+
+        Self::from(vec![templates, persistence])
+    }
+}
+
+impl From<Vec<DependencyStatus>> for HealthReport {
+    fn from(dependencies: Vec<DependencyStatus>) -> Self {
+        Self {
+            healthy: dependencies.iter().all(|status| status.healthy),
+            version: crate::version::VERSION.to_string(),
+            dependencies,
+        }
+    }
+}
+
+// </editor-fold desc="// HealthReport ...">
+
+/// Ping the service's database
+async fn _ping_database(state: &ShuttleAppState) -> Result<(), String> {
+    tokio::time::timeout(DB_PING_TIMEOUT, sqlx::query("SELECT 1").execute(&state.db))
+        .await
+        .map_err(|_| format!("no response within {DB_PING_TIMEOUT:?}"))?
+        .map(|_| ())
+        .map_err(|error| error.to_string())
+}
+
+/// Check that every template among the service's assets was
+/// compiled (rendering each with no data, such that only
+/// missing templates fail for want of being registered)
+fn _check_templates(state: &ShuttleAppState) -> Result<(), String> {
+    let missing = state
+        .assets
+        .entries()
+        .iter()
+        .filter_map(|entry| entry.name.strip_suffix(TEMPLATE_EXTENSION))
+        .filter(|name| {
+            state.templates.render(name, json!({})).is_err_and(|error| {
+                let axum_template::engine::HandlebarsError::RenderError(error) = error;
+                error.desc.starts_with("Template not found")
+            })
+        })
+        .collect::<Vec<&str>>();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("templates not loaded: {}", missing.join(", ")))
+    }
+}
+
+/// Check that the service's persistence directory is writable
+fn _probe_persistence(state: &ShuttleAppState) -> Result<(), String> {
+    state
+        .persistence
+        .probe(PERSISTENCE_PROBE)
+        .map_err(|error| error.to_string())
+}
+
+/// Report the status of the service's in-process dependencies
+/// (answering `200 OK` regardless, as the service is evidently alive)
+#[tracing::instrument(skip(state))]
+pub async fn get_liveness(State(state): State<ShuttleAppState>) -> Json<HealthReport> {
+    Json(HealthReport::check_local(&state).await)
+}
+
+/// Report the status of the service's dependencies, answering
+/// `503 Service Unavailable` unless every one of them is usable
+#[tracing::instrument(skip(state))]
+pub async fn get_readiness(
+    State(state): State<ShuttleAppState>,
+) -> (StatusCode, Json<HealthReport>) {
+    let report = HealthReport::check(&state).await;
+
+    let status = if report.healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Third-Party Imports
+    use axum::http::StatusCode;
+    use handlebars::Handlebars;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use super::{HealthReport, PERSISTENCE_PROBE};
+    use crate::routes;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{service, state, TestService};
+
+    /// Test that the liveness report checks (and finds usable)
+    /// the test service's in-process dependencies only
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_healthy_liveness_report(service: TestService) -> anyhow::Result<()> {
+        _assert_healthy(service, routes::healthz(), &["templates", "persistence"]).await
+    }
+
+    /// Test that the readiness report finds every
+    /// dependency of the test service usable
+    #[rstest]
+    #[cfg_attr(not(feature = "ci"), ignore = "requires a PostgreSQL instance")]
+    #[test_log::test(tokio::test)]
+    async fn test_healthy_readiness_report(service: TestService) -> anyhow::Result<()> {
+        _assert_healthy(
+            service,
+            routes::readyz(),
+            &["database", "templates", "persistence"],
+        )
+        .await
+    }
+
+    async fn _assert_healthy(
+        service: TestService,
+        url: &str,
+        expected_dependencies: &[&str],
+    ) -> anyhow::Result<()> {
+        let response = service.resolve(url).await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+        let report = serde_json::from_slice::<HealthReport>(&content)?;
+
+        assert!(report.healthy, "{report:?}");
+        assert_eq!(
+            expected_dependencies,
+            report
+                .dependencies
+                .iter()
+                .map(|status| status.name.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    /// Test that a state whose template engine never loaded the
+    /// service's templates is alive, but reported as not ready
+    #[rstest]
+    #[case::liveness(routes::healthz(), StatusCode::OK)]
+    #[case::readiness(routes::readyz(), StatusCode::SERVICE_UNAVAILABLE)]
+    #[test_log::test(tokio::test)]
+    async fn test_missing_templates(
+        state: ShuttleAppState,
+        #[case] url: &str,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let state = ShuttleAppState {
+            templates: Handlebars::new().into(),
+            ..state
+        };
+
+        let response = TestService::from(crate::router(state)).resolve(url).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+        let report = serde_json::from_slice::<HealthReport>(&content)?;
+        let templates = report
+            .dependencies
+            .iter()
+            .find(|status| status.name == "templates")
+            .unwrap();
+
+        assert!(!report.healthy);
+        assert!(!templates.healthy);
+        assert!(templates
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains("day-4/magician")));
+
+        Ok(())
+    }
+
+    /// Test that the persistence probe leaves nothing behind
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_persistence_probe(state: ShuttleAppState) -> anyhow::Result<()> {
+        state.persistence.probe(PERSISTENCE_PROBE)?;
+
+        assert!(!state
+            .persistence
+            .list()?
+            .contains(&PERSISTENCE_PROBE.to_string()));
+
+        Ok(())
+    }
+}
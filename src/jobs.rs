@@ -0,0 +1,634 @@
+//! ## Background Jobs
+//!
+
+// Standard Library Imports
+use core::{
+    convert::Infallible,
+    fmt::Debug,
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+// Third-Party Imports
+use chrono::{DateTime, Duration, Utc};
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Semaphore;
+
+// Crate-Level Imports
+use crate::{clock::Clock, persistence::EncryptedPersistence};
+
+/// The default maximum number of
+/// concurrently running jobs
+const DEFAULT_CONCURRENCY: usize = 8;
+
+/// The default maximum number of jobs
+/// queued (or running) at any one time
+const DEFAULT_CAPACITY: usize = 256;
+
+/// The default maximum number of unfinished tracked jobs
+const DEFAULT_MAX_PENDING: usize = 32;
+
+/// The default number of seconds finished tracked
+/// jobs' statuses are kept around to be polled
+const DEFAULT_FINISHED_TTL_SECS: i64 = 60 * 60;
+
+/// The persistent store key prefix of tracked jobs' statuses
+const STATUS_KEY_PREFIX: &str = "job-";
+
+// <editor-fold desc="// JobQueue ...">
+
+/// A bounded queue of fire-and-forget background jobs
+#[derive(Clone, Debug)]
+pub struct JobQueue {
+    /// Permits limiting the number of
+    /// concurrently running jobs
+    permits: Arc<Semaphore>,
+    /// Slots limiting the number of jobs queued (or running),
+    /// and so the number of tasks (and whatever their jobs
+    /// hold) parked waiting for a permit
+    slots: Arc<Semaphore>,
+    /// The number of jobs dropped because the queue was full
+    dropped: Arc<AtomicU64>,
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_CONCURRENCY)
+    }
+}
+
+impl JobQueue {
+    /// Create a queue running at most
+    /// `concurrency` jobs at a time
+    pub fn new(concurrency: usize) -> Self {
+        Self::with_capacity(concurrency, DEFAULT_CAPACITY)
+    }
+
+    /// Create a queue running at most `concurrency` jobs at
+    /// a time, and holding at most `capacity` jobs in total
+    pub fn with_capacity(concurrency: usize, capacity: usize) -> Self {
+        let concurrency = concurrency.max(1);
+
+        Self {
+            permits: Arc::new(Semaphore::new(concurrency)),
+            slots: Arc::new(Semaphore::new(capacity.max(concurrency))),
+            dropped: Arc::default(),
+        }
+    }
+
+    /// The number of jobs dropped because the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Queue the supplied job, logging its failure (if it
+    /// fails), or drop it if the queue is full, reporting
+    /// whether it was queued
+    pub fn enqueue<Job, Error>(&self, name: &'static str, job: Job) -> bool
+    where
+        Job: Future<Output = Result<(), Error>> + Send + 'static,
+        Error: Debug + Send + 'static,
+    {
+        let Ok(slot) = self.slots.clone().try_acquire_owned() else {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!("job queue full, dropping job: {name}");
+            return false;
+        };
+
+        let permits = self.permits.clone();
+
+        tokio::spawn(async move {
+            let _slot = slot;
+
+            let Ok(_permit) = permits.acquire_owned().await else {
+                return tracing::warn!("job queue closed, dropping job: {name}");
+            };
+
+            if let Err(error) = job.await {
+                tracing::error!("background job {name} failed: {error:?}");
+            }
+        });
+
+        true
+    }
+}
+
+// </editor-fold desc="// JobQueue ...">
+
+// <editor-fold desc="// JobTracker ...">
+
+/// The lifecycle stage of a tracked job
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// Waiting for a free slot in the job queue
+    Queued,
+    /// Running
+    Running,
+    /// Finished with a result
+    Succeeded,
+    /// Finished with an error
+    Failed,
+}
+
+impl JobState {
+    /// Whether the job has finished (successfully or otherwise)
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed)
+    }
+}
+
+/// A tracked job's status (and, once finished, its outcome)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct JobStatus {
+    /// the job's id
+    pub id: String,
+    /// the job's name
+    pub name: String,
+    /// the job's lifecycle stage
+    pub state: JobState,
+    /// the units of work (e.g. commits scanned) completed so far
+    pub progress: u64,
+    /// the job's result (once it's succeeded)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// why the job failed (if it has)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// when the job was submitted
+    pub created_at: DateTime<Utc>,
+    /// when the job's status last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A tracked job's handle for reporting its progress
+#[derive(Clone, Debug, Default)]
+pub struct JobProgress(Arc<AtomicU64>);
+
+impl JobProgress {
+    /// Record another `units` of completed work
+    pub fn advance(&self, units: u64) {
+        self.0.fetch_add(units, Ordering::Relaxed);
+    }
+
+    /// The units of work completed so far
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Background jobs whose status (and outcome) can be polled,
+/// persisted to the supplied store (if any) so that finished
+/// jobs' outcomes outlive the instance that ran them
+#[derive(Clone, Debug)]
+pub struct JobTracker {
+    /// the queue tracked jobs are run on
+    queue: JobQueue,
+    /// the source of status timestamps
+    clock: Arc<dyn Clock>,
+    /// the statuses (and progress handles)
+    /// of the jobs submitted to this instance
+    statuses: Arc<Mutex<HashMap<String, (JobStatus, JobProgress)>>>,
+    /// where statuses are persisted (if anywhere)
+    persistence: Option<EncryptedPersistence>,
+    /// the maximum number of unfinished jobs
+    max_pending: usize,
+    /// how long finished jobs' statuses are kept
+    finished_ttl: Duration,
+}
+
+impl JobTracker {
+    /// Create a tracker running jobs on the supplied queue,
+    /// persisting their statuses to the supplied store (if any)
+    pub fn new(
+        queue: JobQueue,
+        clock: Arc<dyn Clock>,
+        persistence: Option<EncryptedPersistence>,
+    ) -> Self {
+        Self {
+            queue,
+            clock,
+            statuses: Arc::new(Mutex::new(HashMap::new())),
+            persistence,
+            max_pending: DEFAULT_MAX_PENDING,
+            finished_ttl: Duration::seconds(DEFAULT_FINISHED_TTL_SECS),
+        }
+    }
+
+    /// Limit the tracker to `max_pending` unfinished jobs,
+    /// forgetting finished ones after `finished_ttl`
+    pub fn with_limits(self, max_pending: usize, finished_ttl: Duration) -> Self {
+        Self {
+            max_pending: max_pending.max(1),
+            finished_ttl,
+            ..self
+        }
+    }
+
+    /// Queue the job `work` creates under the supplied id,
+    /// tracking its progress and outcome, and reporting its
+    /// initial (queued) status, or `None` if the tracker
+    /// (or its queue) is already full
+    pub fn submit<Work, Job>(&self, id: String, name: &'static str, work: Work) -> Option<JobStatus>
+    where
+        Work: FnOnce(JobProgress) -> Job,
+        Job: Future<Output = Result<Value, String>> + Send + 'static,
+    {
+        self._expire();
+
+        let progress = JobProgress::default();
+        let now = self.clock.now();
+
+        let status = JobStatus {
+            id: id.clone(),
+            name: name.to_string(),
+            state: JobState::Queued,
+            progress: 0,
+            result: None,
+            error: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        {
+            let mut statuses = self.statuses.lock().unwrap();
+
+            let pending = statuses
+                .values()
+                .filter(|(status, _)| !status.state.is_finished())
+                .count();
+
+            if pending >= self.max_pending {
+                tracing::warn!("too many unfinished jobs, rejecting job: {name}");
+                return None;
+            }
+
+            statuses.insert(id.clone(), (status.clone(), progress.clone()));
+        }
+
+        self._save(&status);
+
+        let job = work(progress);
+        let tracker = self.clone();
+
+        let queued = self.queue.enqueue(name, async move {
+            tracker._update(&id, |status| status.state = JobState::Running);
+
+            let outcome = AssertUnwindSafe(job)
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|_| Err(String::from("job panicked")));
+
+            tracker._update(&id, |status| match outcome {
+                Ok(result) => {
+                    status.state = JobState::Succeeded;
+                    status.result = Some(result);
+                }
+                Err(error) => {
+                    status.state = JobState::Failed;
+                    status.error = Some(error);
+                }
+            });
+
+            Ok::<(), Infallible>(())
+        });
+
+        if !queued {
+            self.statuses.lock().unwrap().remove(&status.id);
+            self._forget(&status.id);
+            return None;
+        }
+
+        Some(status)
+    }
+
+    /// Get the identified job's current status (if it's known)
+    pub fn get(&self, id: &str) -> Option<JobStatus> {
+        if let Some((status, progress)) = self.statuses.lock().unwrap().get(id) {
+            return Some(JobStatus {
+                progress: progress.get(),
+                ..status.clone()
+            });
+        }
+
+        // ids end up in the store's file names
+        if !id
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric())
+        {
+            return None;
+        }
+
+        let status = self
+            .persistence
+            .as_ref()?
+            .load::<String>(&format!("{STATUS_KEY_PREFIX}{id}"))
+            .ok()
+            .and_then(|status| serde_json::from_str::<JobStatus>(&status).ok())?;
+
+        if self._is_expired(&status) {
+            self._forget(id);
+            return None;
+        }
+
+        // an unfinished job unknown to this instance
+        // was running on one that's since stopped
+        Some(match status.state.is_finished() {
+            true => status,
+            false => JobStatus {
+                state: JobState::Failed,
+                error: Some(String::from("job interrupted by a restart")),
+                ..status
+            },
+        })
+    }
+
+    /// Whether the supplied status is old enough to be forgotten
+    /// (unfinished statuses are only ever expired once persisted,
+    /// as those unknown to this instance will never finish)
+    fn _is_expired(&self, status: &JobStatus) -> bool {
+        status.updated_at + self.finished_ttl <= self.clock.now()
+    }
+
+    /// Forget every finished job whose status has expired,
+    /// both here and in the store (if there is one), including
+    /// those persisted by other (possibly stopped) instances
+    fn _expire(&self) {
+        let expired = {
+            let mut statuses = self.statuses.lock().unwrap();
+            let mut expired = Vec::new();
+
+            statuses.retain(|id, (status, _)| {
+                let keep = !status.state.is_finished() || !self._is_expired(status);
+
+                if !keep {
+                    expired.push(id.clone());
+                }
+
+                keep
+            });
+
+            expired
+        };
+
+        expired.iter().for_each(|id| self._forget(id));
+
+        let Some(store) = self.persistence.as_ref() else {
+            return;
+        };
+
+        let Ok(names) = store.list() else {
+            return;
+        };
+
+        for name in names {
+            let Some(id) = name.strip_prefix(STATUS_KEY_PREFIX) else {
+                continue;
+            };
+
+            if self.statuses.lock().unwrap().contains_key(id) {
+                continue;
+            }
+
+            if store
+                .load::<String>(&name)
+                .ok()
+                .and_then(|status| serde_json::from_str::<JobStatus>(&status).ok())
+                .is_some_and(|status| self._is_expired(&status))
+            {
+                self._forget(id);
+            }
+        }
+    }
+
+    /// Remove the identified job's persisted
+    /// status (if there's a store to remove it from)
+    fn _forget(&self, id: &str) {
+        let Some(store) = self.persistence.as_ref() else {
+            return;
+        };
+
+        if let Err(error) = store.remove(&format!("{STATUS_KEY_PREFIX}{id}")) {
+            tracing::warn!("unable to remove status of job {id}: {error:?}");
+        }
+    }
+
+    /// Apply the supplied change to the identified job's
+    /// status, recording its progress and persisting it
+    fn _update(&self, id: &str, change: impl FnOnce(&mut JobStatus)) {
+        let updated = {
+            let mut statuses = self.statuses.lock().unwrap();
+
+            let Some((status, progress)) = statuses.get_mut(id) else {
+                return;
+            };
+
+            change(status);
+            status.progress = progress.get();
+            status.updated_at = self.clock.now();
+
+            status.clone()
+        };
+
+        self._save(&updated);
+    }
+
+    /// Persist the supplied status (if there's a store to persist it to)
+    /// as JSON, as its (arbitrary) result isn't representable in the
+    /// store's own (non-self-describing) format
+    fn _save(&self, status: &JobStatus) {
+        let Some(store) = self.persistence.as_ref() else {
+            return;
+        };
+
+        let saved = serde_json::to_string(status)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| {
+                store
+                    .save(&format!("{STATUS_KEY_PREFIX}{}", status.id), content)
+                    .map_err(anyhow::Error::from)
+            });
+
+        if let Err(error) = saved {
+            tracing::warn!("unable to persist status of job {}: {error:?}", status.id);
+        }
+    }
+}
+
+// </editor-fold desc="// JobTracker ...">
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use core::time::Duration;
+    use std::sync::Arc;
+
+    // Third-Party Imports
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use serde_json::Value;
+    use shuttle_persist::PersistInstance;
+
+    // Crate-Level Imports
+    use super::{JobQueue, JobState, JobStatus, JobTracker};
+    use crate::clock::FakeClock;
+    use crate::persistence::EncryptedPersistence;
+
+    /// Test that jobs beyond the queue's capacity are
+    /// dropped (and counted) rather than parked
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_job_queue_capacity() {
+        let queue = JobQueue::with_capacity(1, 2);
+        let stalled = || async move {
+            futures::future::pending::<()>().await;
+            Ok::<(), ()>(())
+        };
+
+        assert!(queue.enqueue("running", stalled()));
+        assert!(queue.enqueue("queued", stalled()));
+        assert!(!queue.enqueue("dropped", stalled()));
+        assert_eq!(1, queue.dropped());
+    }
+
+    /// Test that finished jobs' outcomes outlive the tracker that
+    /// ran them, and that unfinished ones are reported as interrupted
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_persisted_job_statuses() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let store =
+            EncryptedPersistence::new(PersistInstance::new(directory.path().to_path_buf())?, None);
+        let clock = Arc::new(FakeClock::new("2023-12-20T12:00:00Z".parse()?));
+        let tracker = JobTracker::new(JobQueue::default(), clock.clone(), Some(store.clone()));
+
+        tracker.submit(
+            String::from("finished"),
+            "finished",
+            |progress| async move {
+                progress.advance(3);
+                Ok(Value::from(12))
+            },
+        );
+
+        let queued = tracker
+            .submit(String::from("stalled"), "stalled", |_| async move {
+                futures::future::pending::<()>().await;
+                Ok(Value::Null)
+            })
+            .expect("the tracker has room");
+
+        for _ in 0..100 {
+            if tracker
+                .get("finished")
+                .is_some_and(|status| status.state.is_finished())
+            {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // a "restarted" instance, sharing the same store
+        let restarted = JobTracker::new(JobQueue::default(), clock, Some(store));
+
+        let finished = restarted.get("finished").expect("the status is persisted");
+
+        assert_eq!(JobState::Succeeded, finished.state);
+        assert_eq!(
+            (3, Some(Value::from(12))),
+            (finished.progress, finished.result)
+        );
+
+        assert!(!tracker.get("stalled").unwrap().state.is_finished());
+        assert_eq!(
+            JobStatus {
+                state: JobState::Failed,
+                error: Some(String::from("job interrupted by a restart")),
+                ..restarted.get("stalled").unwrap()
+            },
+            restarted.get("stalled").unwrap()
+        );
+        assert_eq!(
+            queued.created_at,
+            restarted.get("stalled").unwrap().created_at
+        );
+
+        assert_eq!(None, restarted.get("unknown"));
+        assert_eq!(None, restarted.get("../finished"));
+
+        Ok(())
+    }
+
+    /// Test that submissions beyond the tracker's limit are rejected,
+    /// and that finished jobs' statuses are forgotten once expired
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_tracked_job_limits() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let store =
+            EncryptedPersistence::new(PersistInstance::new(directory.path().to_path_buf())?, None);
+        let clock = Arc::new(FakeClock::new("2023-12-20T12:00:00Z".parse()?));
+        let tracker = JobTracker::new(JobQueue::default(), clock.clone(), Some(store.clone()))
+            .with_limits(1, chrono::Duration::minutes(5));
+
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+
+        assert!(tracker
+            .submit(String::from("first"), "first", |_| async move {
+                let _ = released.await;
+                Ok(Value::Null)
+            })
+            .is_some());
+        assert!(tracker
+            .submit(String::from("second"), "second", |_| async move {
+                Ok(Value::Null)
+            })
+            .is_none());
+        assert_eq!(None, tracker.get("second"));
+
+        release.send(()).unwrap();
+
+        for _ in 0..100 {
+            if tracker
+                .get("first")
+                .is_some_and(|status| status.state.is_finished())
+            {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(JobState::Succeeded, tracker.get("first").unwrap().state);
+
+        // once the first job's status expires, it's forgotten
+        // (here and in the store) when the next job's submitted
+        clock.advance(chrono::Duration::minutes(5));
+
+        let restarted = JobTracker::new(JobQueue::default(), clock.clone(), Some(store.clone()))
+            .with_limits(1, chrono::Duration::minutes(5));
+
+        assert_eq!(None, restarted.get("first"));
+        assert!(tracker
+            .submit(String::from("third"), "third", |_| async move {
+                Ok(Value::Null)
+            })
+            .is_some());
+        assert_eq!(None, tracker.get("first"));
+        assert!(!store.list()?.contains(&String::from("job-first")));
+
+        Ok(())
+    }
+}
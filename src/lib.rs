@@ -0,0 +1,242 @@
+#![forbid(unsafe_code)]
+#![deny(missing_debug_implementations)]
+#![cfg_attr(tarpaulin, feature(register_tool))]
+#![cfg_attr(tarpaulin, register_tool(tarpaulin))]
+#![cfg_attr(tarpaulin, feature(coverage_attribute))]
+#![feature(entry_insert, error_in_core, const_trait_impl, try_trait_v2)]
+#![cfg_attr(test, feature(test))]
+
+//! # [`shuttle.rs`](https://shuttle.rs/) Christmas Code Hunt 2023
+//!
+//! The service's solutions, state, and [`router`], usable
+//! without the Shuttle runtime (e.g. embedded in another
+//! `axum` app, or served by a plain `tokio` binary) given
+//! an initialized [`ShuttleAppState`]
+
+// Module Declarations
+pub mod abuse;
+pub mod admin;
+pub mod assets;
+pub mod clock;
+pub mod coalesce;
+pub mod datasource;
+pub mod debug;
+pub mod errors;
+pub mod examples;
+pub mod grader;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod health;
+pub mod jobs;
+pub mod metrics;
+pub mod middleware;
+pub mod openapi;
+pub mod outbound;
+pub mod outbox;
+pub mod persistence;
+pub mod random;
+#[cfg(feature = "recording")]
+pub mod recording;
+pub mod redaction;
+pub mod reporting;
+pub mod responses;
+pub mod routes;
+pub mod scheduler;
+pub mod sessions;
+pub mod shared;
+pub mod shedding;
+pub mod solutions;
+pub mod state;
+pub mod telemetry;
+pub mod templating;
+pub mod utils;
+pub mod validation;
+pub mod version;
+
+// Third-Party Imports
+use axum::{
+    middleware::{from_fn, from_fn_with_state},
+    routing::{self, Router as AxumRouter},
+};
+
+// Crate-Level Imports
+use crate::state::ShuttleAppState;
+
+/// Start the supplied state's background work (its scheduled
+/// jobs, upstream preflight calls, pokedex preload, and chat
+/// room bridge) ahead of serving its [`router`]
+#[tracing::instrument(skip_all)]
+pub async fn start(state: &ShuttleAppState) {
+    state.scheduler.start(state);
+
+    // don't make the first requests to upstream
+    // providers pay to resolve and connect to them
+    tokio::spawn({
+        let state = state.clone();
+        async move { state.outbound.preflight(&state.metrics).await }
+    });
+
+    state.pokedex.on_startup();
+
+    state.chat.clone().bridge(state.shared.clone()).await;
+}
+
+/// Create the project's main `Router` instance
+#[tracing::instrument(skip(state))]
+pub fn router(state: ShuttleAppState) -> AxumRouter {
+    let router = solutions::routes()
+        .route(routes::DAYS, routing::get(solutions::list_days))
+        .route(routes::OPENAPI, routing::get(openapi::get_openapi_document))
+        .route(routes::VERSION, routing::get(version::get_version))
+        .route(routes::HEALTHZ, routing::get(health::get_liveness))
+        .route(routes::READYZ, routing::get(health::get_readiness))
+        .route(
+            routes::DAY_EXAMPLES,
+            routing::get(examples::get_day_examples),
+        )
+        .route(
+            routes::REPLAY_DAY_EXAMPLE,
+            routing::post(examples::replay_day_example),
+        )
+        .route(routes::VALIDATE_DAY, routing::get(grader::validate_day))
+        .route(routes::debug::ECHO_ROOT, routing::any(debug::echo_request))
+        .route(routes::debug::ECHO, routing::any(debug::echo_request))
+        .route(
+            routes::ASSET_MANIFEST,
+            routing::get(assets::get_asset_manifest),
+        )
+        .route(
+            routes::day11::LISTING,
+            routing::get(solutions::day_11::list_static_assets).route_layer(from_fn_with_state(
+                state.clone(),
+                middleware::require_asset_credentials,
+            )),
+        )
+        .route(
+            routes::counters::COUNTER,
+            routing::get(solutions::counters::get_counter),
+        )
+        .route(
+            routes::counters::INCR,
+            routing::post(solutions::counters::increment_counter),
+        )
+        .route(
+            routes::counters::RESET,
+            routing::post(solutions::counters::reset_counter),
+        )
+        .merge(admin_router(&state))
+        .layer(from_fn_with_state(state.clone(), sessions::attach_session));
+
+    #[cfg(feature = "recording")]
+    let router = router.layer(from_fn_with_state(
+        state.clone(),
+        recording::record_exchanges,
+    ));
+
+    router
+        .layer(from_fn_with_state(
+            state.clone(),
+            shedding::limit_concurrency,
+        ))
+        .layer(from_fn_with_state(state.clone(), shedding::shed_load))
+        .layer(from_fn_with_state(state.clone(), shedding::limit_rates))
+        .layer(from_fn_with_state(
+            state.clone(),
+            middleware::set_security_headers,
+        ))
+        .layer(from_fn_with_state(
+            state.clone(),
+            abuse::guard_against_abuse,
+        ))
+        .layer(from_fn_with_state(state.clone(), reporting::report_errors))
+        .layer(from_fn_with_state(state.clone(), middleware::catch_panics))
+        .layer(from_fn_with_state(
+            state.clone(),
+            responses::envelope_responses,
+        ))
+        .layer(from_fn_with_state(state.clone(), metrics::count_requests))
+        .layer(from_fn(version::set_version_headers))
+        .layer(from_fn(middleware::assign_request_ids))
+        .with_state(state)
+}
+
+/// Create the `Router` of the (administrative) request
+/// recording endpoints, if the `recording` feature is on
+fn recordings_router() -> AxumRouter<ShuttleAppState> {
+    #[cfg(feature = "recording")]
+    return routing::Router::new()
+        .route(
+            routes::admin::RECORDINGS,
+            routing::get(recording::list_recordings),
+        )
+        .route(
+            routes::admin::RECORDING,
+            routing::get(recording::get_recording),
+        )
+        .route(
+            routes::admin::REPLAY_RECORDING,
+            routing::post(recording::replay_recording),
+        );
+
+    #[cfg(not(feature = "recording"))]
+    routing::Router::new()
+}
+
+/// Create the `Router` of the project's administrative
+/// endpoints (all of which require admin credentials)
+fn admin_router(state: &ShuttleAppState) -> AxumRouter<ShuttleAppState> {
+    routing::Router::new()
+        .route(
+            routes::admin::METRICS,
+            routing::get(admin::get_service_metrics),
+        )
+        .route(
+            routes::admin::RUNTIME,
+            routing::get(admin::get_runtime_report),
+        )
+        .route(routes::admin::INIT, routing::get(admin::get_init_report))
+        .route(
+            routes::admin::SCHEDULE,
+            routing::get(scheduler::get_schedule),
+        )
+        .route(
+            routes::admin::TRACING,
+            routing::get(admin::get_tracing_settings).put(admin::update_tracing_settings),
+        )
+        .route(
+            routes::admin::DB_OVERVIEW,
+            routing::get(admin::get_db_overview),
+        )
+        .route(routes::admin::SQL, routing::post(admin::execute_sql_query))
+        .route(
+            routes::admin::BANS,
+            routing::get(abuse::list_bans).post(abuse::create_ban),
+        )
+        .route(routes::admin::BAN, routing::delete(abuse::delete_ban))
+        .route(
+            routes::admin::ROOM_PROTECTION,
+            routing::put(solutions::day_19::update_chat_room_protection),
+        )
+        .route(
+            routes::admin::ROOM_TOKEN,
+            routing::post(solutions::day_19::issue_chat_room_token),
+        )
+        .merge(recordings_router())
+        .route(
+            routes::datasource::ROOT,
+            routing::get(datasource::check_datasource),
+        )
+        .route(
+            routes::datasource::ROOT_SLASH,
+            routing::get(datasource::check_datasource),
+        )
+        .route(
+            routes::datasource::SEARCH,
+            routing::post(datasource::search_metrics),
+        )
+        .route(
+            routes::datasource::QUERY,
+            routing::post(datasource::query_metrics),
+        )
+        .route_layer(from_fn_with_state(state.clone(), middleware::require_admin))
+}
@@ -0,0 +1,738 @@
+//! ## Metrics
+//!
+
+// Standard Library Imports
+use core::{fmt::Display, future::Future, net::IpAddr, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+// Third-Party Imports
+use axum::{
+    extract::State,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use shuttle_secrets::SecretStore;
+use sqlx::postgres::PgQueryResult;
+
+// Crate-Level Imports
+use crate::{
+    errors::{AppError, ErrorEnvelope},
+    state::ShuttleAppState,
+    utils,
+};
+
+/// The upper bounds (in milliseconds) of the
+/// buckets of each [`QueryTimings`] histogram
+const QUERY_TIMING_BUCKETS: [u64; 10] = [1, 5, 10, 25, 50, 100, 250, 500, 1000, 5000];
+
+/// The elapsed time beyond which a query is logged as slow
+/// when the `SLOW_QUERY_THRESHOLD_MS` secret is unset
+const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(250);
+
+// <editor-fold desc="// RowCount ...">
+
+/// The number of rows touched or returned by a query
+pub trait RowCount {
+    /// Get the number of rows touched or returned
+    fn row_count(&self) -> u64;
+}
+
+impl RowCount for PgQueryResult {
+    fn row_count(&self) -> u64 {
+        self.rows_affected()
+    }
+}
+
+impl<Row> RowCount for Vec<Row> {
+    fn row_count(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+// </editor-fold desc="// RowCount ...">
+
+// <editor-fold desc="// QueryTimings ...">
+
+/// The (timed) queries whose performance is tracked
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimedQuery {
+    /// [`GiftOrder::insert_many`](crate::solutions::day_13::GiftOrder::insert_many)
+    OrderInserts,
+    /// [`GiftOrderRegion::total_orders_by_region`](crate::solutions::day_18::GiftOrderRegion::total_orders_by_region)
+    RegionalTotals,
+    /// [`GiftOrderRegion::top_n_most_popular`](crate::solutions::day_18::GiftOrderRegion::top_n_most_popular)
+    RegionalTopGifts,
+}
+
+impl TimedQuery {
+    /// The query's metric name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::OrderInserts => "order_inserts",
+            Self::RegionalTotals => "regional_totals",
+            Self::RegionalTopGifts => "regional_top_gifts",
+        }
+    }
+}
+
+/// A histogram of a query's elapsed times
+#[derive(Debug, Default)]
+pub struct QueryTimings {
+    /// The number of queries completing within each
+    /// of [`QUERY_TIMING_BUCKETS`] (plus an overflow)
+    buckets: [AtomicU64; QUERY_TIMING_BUCKETS.len() + 1],
+    /// The number of queries that failed
+    failures: AtomicU64,
+    /// The total number of rows touched or returned
+    rows: AtomicU64,
+    /// The total elapsed time (in microseconds)
+    total_micros: AtomicU64,
+}
+
+impl QueryTimings {
+    /// Record a single query's outcome
+    pub fn record(&self, elapsed: Duration, rows: Option<u64>) {
+        let millis = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        let bucket = QUERY_TIMING_BUCKETS
+            .iter()
+            .position(|bound| millis <= *bound)
+            .unwrap_or(QUERY_TIMING_BUCKETS.len());
+
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.total_micros.fetch_add(
+            u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+
+        match rows {
+            Some(rows) => self.rows.fetch_add(rows, Ordering::Relaxed),
+            None => self.failures.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    /// Add a point-in-time snapshot of the histogram's
+    /// values to the supplied snapshot, prefixed by `name`
+    fn snapshot_into(&self, name: &str, snapshot: &mut BTreeMap<String, u64>) {
+        let mut count = 0u64;
+
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            let value = bucket.load(Ordering::Relaxed);
+
+            count += value;
+
+            let label = QUERY_TIMING_BUCKETS
+                .get(index)
+                .map_or_else(|| String::from("le_inf"), |bound| format!("le_{bound}ms"));
+
+            snapshot.insert(format!("{name}.{label}"), value);
+        }
+
+        snapshot.insert(format!("{name}.count"), count);
+        snapshot.extend([
+            (
+                format!("{name}.failures"),
+                self.failures.load(Ordering::Relaxed),
+            ),
+            (format!("{name}.rows"), self.rows.load(Ordering::Relaxed)),
+            (
+                format!("{name}.total_micros"),
+                self.total_micros.load(Ordering::Relaxed),
+            ),
+        ]);
+    }
+}
+
+// </editor-fold desc="// QueryTimings ...">
+
+// <editor-fold desc="// CoalescingStats ...">
+
+/// Counts of the upstream calls made or
+/// shared by a [`SingleFlight`](crate::coalesce::SingleFlight)
+#[derive(Debug, Default)]
+pub struct CoalescingStats {
+    /// The number of calls actually made
+    calls: AtomicU64,
+    /// The number of calls that shared an in-flight call's outcome
+    coalesced: AtomicU64,
+}
+
+impl CoalescingStats {
+    /// Record a single call, and whether it was coalesced
+    pub fn record(&self, coalesced: bool) {
+        if coalesced {
+            self.coalesced.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Add a point-in-time snapshot of the counts
+    /// to the supplied snapshot, prefixed by `name`
+    fn snapshot_into(&self, name: &str, snapshot: &mut BTreeMap<String, u64>) {
+        snapshot.extend([
+            (format!("{name}.calls"), self.calls.load(Ordering::Relaxed)),
+            (
+                format!("{name}.coalesced"),
+                self.coalesced.load(Ordering::Relaxed),
+            ),
+        ]);
+    }
+}
+
+// </editor-fold desc="// CoalescingStats ...">
+
+// <editor-fold desc="// PreflightStats ...">
+
+/// The outcomes of the (connection warming) preflight
+/// calls made to [upstream providers](crate::outbound::Provider)
+#[derive(Debug, Default)]
+pub struct PreflightStats {
+    /// The latest successful call's latency (in milliseconds), by provider
+    latencies: Mutex<BTreeMap<&'static str, u64>>,
+    /// The number of failed calls
+    failures: AtomicU64,
+}
+
+impl PreflightStats {
+    /// Record a single call to the named provider,
+    /// and its latency (or `None` if it failed)
+    pub fn record(&self, provider: &'static str, latency: Option<Duration>) {
+        match latency {
+            Some(latency) => {
+                self.latencies.lock().unwrap().insert(
+                    provider,
+                    u64::try_from(latency.as_millis()).unwrap_or(u64::MAX),
+                );
+            }
+            None => {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Add a point-in-time snapshot of the outcomes
+    /// to the supplied snapshot, prefixed by `name`
+    fn snapshot_into(&self, name: &str, snapshot: &mut BTreeMap<String, u64>) {
+        snapshot.extend(
+            self.latencies
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(provider, latency)| (format!("{name}.{provider}.latency_ms"), *latency)),
+        );
+        snapshot.insert(
+            format!("{name}.failures"),
+            self.failures.load(Ordering::Relaxed),
+        );
+    }
+}
+
+// </editor-fold desc="// PreflightStats ...">
+
+// <editor-fold desc="// ServiceMetrics ...">
+
+/// Running totals of noteworthy service events
+#[derive(Debug)]
+pub struct ServiceMetrics {
+    /// The number of requests received
+    pub requests: AtomicU64,
+    /// The number of panics caught while handling requests
+    pub panics: AtomicU64,
+    /// The number of currently open websocket connections
+    pub live_sockets: AtomicU64,
+    /// The number of websocket connections refused by [`Self::sockets`]
+    pub rejected_sockets: AtomicU64,
+    /// The limits on concurrently open websocket connections
+    pub sockets: SocketQuota,
+    /// The moment the service started
+    pub started: Instant,
+    /// Timings of gift order insertions
+    pub order_inserts: QueryTimings,
+    /// Timings of regional order total aggregations
+    pub regional_totals: QueryTimings,
+    /// Timings of regional top gift aggregations
+    pub regional_top_gifts: QueryTimings,
+    /// Counts of the (coalesced) calls made to upstream APIs
+    pub upstream: CoalescingStats,
+    /// Latencies of the preflight calls made to upstream APIs
+    pub preflight: PreflightStats,
+    /// The elapsed time beyond which
+    /// a query is logged as slow
+    pub slow_query_threshold: Duration,
+}
+
+impl Default for ServiceMetrics {
+    fn default() -> Self {
+        Self {
+            requests: AtomicU64::default(),
+            panics: AtomicU64::default(),
+            live_sockets: AtomicU64::default(),
+            rejected_sockets: AtomicU64::default(),
+            sockets: SocketQuota::default(),
+            started: Instant::now(),
+            order_inserts: QueryTimings::default(),
+            regional_totals: QueryTimings::default(),
+            regional_top_gifts: QueryTimings::default(),
+            upstream: CoalescingStats::default(),
+            preflight: PreflightStats::default(),
+            slow_query_threshold: DEFAULT_SLOW_QUERY_THRESHOLD,
+        }
+    }
+}
+
+impl ServiceMetrics {
+    /// Create a new (empty) set of metrics, using the
+    /// `SLOW_QUERY_THRESHOLD_MS` secret (if set), and
+    /// the [socket quota's](SocketQuota::from_secrets)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        let slow_query_threshold = secrets
+            .get("SLOW_QUERY_THRESHOLD_MS")
+            .and_then(|threshold| {
+                threshold
+                    .parse::<u64>()
+                    .map_err(|error| {
+                        tracing::error!("ignoring SLOW_QUERY_THRESHOLD_MS secret: {error}")
+                    })
+                    .ok()
+            })
+            .map_or(DEFAULT_SLOW_QUERY_THRESHOLD, Duration::from_millis);
+
+        Self {
+            slow_query_threshold,
+            sockets: SocketQuota::from_secrets(secrets),
+            ..Self::default()
+        }
+    }
+
+    /// Get the timings of the specified query
+    pub fn timings(&self, query: TimedQuery) -> &QueryTimings {
+        match query {
+            TimedQuery::OrderInserts => &self.order_inserts,
+            TimedQuery::RegionalTotals => &self.regional_totals,
+            TimedQuery::RegionalTopGifts => &self.regional_top_gifts,
+        }
+    }
+
+    /// Run the supplied query, recording its elapsed time
+    /// and logging it if it's slow (or if it fails)
+    pub async fn time_query<Rows, Error, Query>(
+        &self,
+        query: TimedQuery,
+        future: Query,
+    ) -> Result<Rows, Error>
+    where
+        Rows: RowCount,
+        Error: Display,
+        Query: Future<Output = Result<Rows, Error>>,
+    {
+        let started = Instant::now();
+        let result = future.await;
+        let elapsed = started.elapsed();
+        let rows = result.as_ref().ok().map(RowCount::row_count);
+
+        self.timings(query).record(elapsed, rows);
+
+        let elapsed_ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+
+        match (&result, rows) {
+            (Err(error), _) => {
+                tracing::warn!(query = query.name(), elapsed_ms, "query failed: {error}")
+            }
+            (Ok(_), Some(rows)) if self.slow_query_threshold <= elapsed => {
+                tracing::warn!(query = query.name(), rows, elapsed_ms, "slow query")
+            }
+            _ => {}
+        }
+
+        result
+    }
+
+    /// Get the time elapsed since the service started
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Get a point-in-time snapshot of the service's metrics
+    pub fn snapshot(&self) -> BTreeMap<String, u64> {
+        let mut snapshot = BTreeMap::from([
+            (
+                String::from("requests"),
+                self.requests.load(Ordering::Relaxed),
+            ),
+            (String::from("panics"), self.panics.load(Ordering::Relaxed)),
+            (
+                String::from("live_sockets"),
+                self.live_sockets.load(Ordering::Relaxed),
+            ),
+            (
+                String::from("rejected_sockets"),
+                self.rejected_sockets.load(Ordering::Relaxed),
+            ),
+            (
+                String::from("socket_clients"),
+                self.sockets.clients() as u64,
+            ),
+        ]);
+
+        for query in [
+            TimedQuery::OrderInserts,
+            TimedQuery::RegionalTotals,
+            TimedQuery::RegionalTopGifts,
+        ] {
+            self.timings(query)
+                .snapshot_into(query.name(), &mut snapshot);
+        }
+
+        self.upstream.snapshot_into("upstream", &mut snapshot);
+        self.preflight.snapshot_into("preflight", &mut snapshot);
+
+        snapshot
+    }
+}
+
+// </editor-fold desc="// ServiceMetrics ...">
+
+// <editor-fold desc="// LiveSocket ...">
+
+/// Limits on the number of concurrently open websocket
+/// connections, both overall and per client address
+#[derive(Debug)]
+pub struct SocketQuota {
+    /// the maximum number of open connections
+    global: u64,
+    /// the maximum number of open connections per client address
+    per_client: u64,
+    /// the number of open connections, by client address
+    clients: Mutex<HashMap<IpAddr, u64>>,
+}
+
+impl Default for SocketQuota {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_GLOBAL, Self::DEFAULT_PER_CLIENT)
+    }
+}
+
+impl SocketQuota {
+    /// The default maximum number of open connections
+    const DEFAULT_GLOBAL: u64 = 1024;
+
+    /// The default maximum number of open connections per client address
+    const DEFAULT_PER_CLIENT: u64 = 16;
+
+    /// Create a quota with the supplied limits
+    pub fn new(global: u64, per_client: u64) -> Self {
+        Self {
+            global: global.max(1),
+            per_client: per_client.max(1),
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create a quota from the `WEBSOCKET_MAX_CONNECTIONS` and
+    /// `WEBSOCKET_MAX_CONNECTIONS_PER_IP` secrets (if set)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        let limit = |name: &str, default: u64| {
+            utils::positive_secret(secrets, name).map_or(default, |limit| limit as u64)
+        };
+
+        Self::new(
+            limit("WEBSOCKET_MAX_CONNECTIONS", Self::DEFAULT_GLOBAL),
+            limit("WEBSOCKET_MAX_CONNECTIONS_PER_IP", Self::DEFAULT_PER_CLIENT),
+        )
+    }
+
+    /// Get the number of client addresses with open connections
+    pub fn clients(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// Count a connection opened by the supplied client, unless
+    /// doing so would exceed its limit
+    fn _admit(&self, client: IpAddr) -> Result<(), SocketRejection> {
+        let mut clients = self.clients.lock().unwrap();
+        let open = clients.entry(client).or_default();
+
+        if self.per_client <= *open {
+            return Err(SocketRejection::PerClient(self.per_client));
+        }
+
+        *open += 1;
+
+        Ok(())
+    }
+
+    /// Release a connection opened by the supplied client
+    fn _release(&self, client: IpAddr) {
+        let mut clients = self.clients.lock().unwrap();
+
+        if let Some(open) = clients.get_mut(&client) {
+            *open = open.saturating_sub(1);
+
+            if *open == 0 {
+                clients.remove(&client);
+            }
+        }
+    }
+}
+
+/// Why a websocket connection was refused
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SocketRejection {
+    /// the service's open connection limit was reached
+    Global(u64),
+    /// the client's open connection limit was reached
+    PerClient(u64),
+}
+
+impl From<SocketRejection> for ErrorEnvelope {
+    fn from(rejection: SocketRejection) -> Self {
+        let (scope, limit) = match rejection {
+            SocketRejection::Global(limit) => ("global", limit),
+            SocketRejection::PerClient(limit) => ("client", limit),
+        };
+
+        ErrorEnvelope::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "too many open websocket connections",
+        )
+        .with_details(Some(json!({ "scope": scope, "limit": limit })))
+    }
+}
+
+impl From<SocketRejection> for AppError {
+    fn from(rejection: SocketRejection) -> Self {
+        ErrorEnvelope::from(rejection).into()
+    }
+}
+
+impl IntoResponse for SocketRejection {
+    fn into_response(self) -> Response {
+        ErrorEnvelope::from(self).into_response()
+    }
+}
+
+/// A websocket connection, counted in [`ServiceMetrics::live_sockets`]
+/// (and against its client's [quota](SocketQuota)) for as long as the
+/// guard is held
+#[derive(Debug)]
+pub struct LiveSocket(Arc<ServiceMetrics>, Option<IpAddr>);
+
+impl LiveSocket {
+    /// Count a newly opened websocket connection
+    pub fn open(metrics: &Arc<ServiceMetrics>) -> Self {
+        metrics.live_sockets.fetch_add(1, Ordering::Relaxed);
+
+        Self(metrics.clone(), None)
+    }
+
+    /// Count a websocket connection about to be opened by the
+    /// supplied client (if known), unless doing so would exceed
+    /// the service's or the client's [quota](SocketQuota)
+    pub fn admit(
+        metrics: &Arc<ServiceMetrics>,
+        client: Option<IpAddr>,
+    ) -> Result<Self, SocketRejection> {
+        let admitted = Self::_admit(metrics, client);
+
+        if let Err(rejection) = admitted.as_ref() {
+            metrics.rejected_sockets.fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(client = ?client, "refused websocket connection: {rejection:?}");
+        }
+
+        admitted
+    }
+
+    /// Count the connection against the service's,
+    /// then (if known) the client's quota
+    fn _admit(
+        metrics: &Arc<ServiceMetrics>,
+        client: Option<IpAddr>,
+    ) -> Result<Self, SocketRejection> {
+        let global = metrics.sockets.global;
+
+        metrics
+            .live_sockets
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |live| {
+                (live < global).then_some(live + 1)
+            })
+            .map_err(|_| SocketRejection::Global(global))?;
+
+        // from here on, dropping the guard releases the global count
+        let mut live = Self(metrics.clone(), None);
+
+        if let Some(client) = client {
+            metrics.sockets._admit(client)?;
+            live.1 = Some(client);
+        }
+
+        Ok(live)
+    }
+}
+
+impl Drop for LiveSocket {
+    fn drop(&mut self) {
+        self.0.live_sockets.fetch_sub(1, Ordering::Relaxed);
+
+        if let Some(client) = self.1 {
+            self.0.sockets._release(client);
+        }
+    }
+}
+
+// </editor-fold desc="// LiveSocket ...">
+
+/// Middleware that counts every request
+/// in [`ServiceMetrics::requests`]
+pub async fn count_requests<B: Send + 'static>(
+    State(state): State<ShuttleAppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    state.metrics.requests.fetch_add(1, Ordering::Relaxed);
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use core::time::Duration;
+    use std::sync::Arc;
+
+    // Third-Party Imports
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use super::{LiveSocket, ServiceMetrics, SocketQuota, SocketRejection, TimedQuery};
+
+    /// Test that `time_query` records successful
+    /// and failed queries in the right histogram
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_time_query() {
+        let metrics = ServiceMetrics {
+            slow_query_threshold: Duration::ZERO,
+            ..ServiceMetrics::default()
+        };
+
+        let rows = metrics
+            .time_query(TimedQuery::RegionalTotals, async {
+                Ok::<_, String>(vec![1, 2, 3])
+            })
+            .await;
+
+        assert_eq!(Ok(vec![1, 2, 3]), rows);
+
+        let failure = metrics
+            .time_query(TimedQuery::RegionalTotals, async {
+                Err::<Vec<u8>, _>(String::from("connection reset"))
+            })
+            .await;
+
+        assert!(failure.is_err());
+
+        let snapshot = metrics.snapshot();
+
+        assert_eq!(Some(&2), snapshot.get("regional_totals.count"));
+        assert_eq!(Some(&1), snapshot.get("regional_totals.failures"));
+        assert_eq!(Some(&3), snapshot.get("regional_totals.rows"));
+        assert_eq!(Some(&0), snapshot.get("order_inserts.count"));
+        assert_eq!(Some(&0), snapshot.get("panics"));
+    }
+
+    /// Test that `LiveSocket` guards count
+    /// open connections until they're dropped
+    #[rstest]
+    #[test_log::test]
+    fn test_live_sockets() {
+        let metrics = Arc::new(ServiceMetrics::default());
+
+        let first = LiveSocket::open(&metrics);
+        let second = LiveSocket::open(&metrics);
+
+        assert_eq!(Some(&2), metrics.snapshot().get("live_sockets"));
+
+        drop(first);
+
+        assert_eq!(Some(&1), metrics.snapshot().get("live_sockets"));
+
+        drop(second);
+
+        assert_eq!(Some(&0), metrics.snapshot().get("live_sockets"));
+    }
+
+    /// Test that admitted sockets are capped globally
+    /// and per client, and released once dropped
+    #[rstest]
+    #[test_log::test]
+    fn test_socket_quota() {
+        let metrics = Arc::new(ServiceMetrics {
+            sockets: SocketQuota::new(2, 1),
+            ..ServiceMetrics::default()
+        });
+
+        let (first, second) = ("10.0.0.1".parse().ok(), "10.0.0.2".parse().ok());
+
+        let held = LiveSocket::admit(&metrics, first).expect("the quota is unspent");
+
+        assert_eq!(
+            Err(SocketRejection::PerClient(1)),
+            LiveSocket::admit(&metrics, first).map(drop)
+        );
+
+        let other = LiveSocket::admit(&metrics, second).expect("the quota is unspent");
+
+        assert_eq!(
+            Err(SocketRejection::Global(2)),
+            LiveSocket::admit(&metrics, None).map(drop)
+        );
+
+        let snapshot = metrics.snapshot();
+
+        assert_eq!(Some(&2), snapshot.get("live_sockets"));
+        assert_eq!(Some(&2), snapshot.get("rejected_sockets"));
+        assert_eq!(Some(&2), snapshot.get("socket_clients"));
+
+        drop((held, other));
+
+        assert_eq!(Some(&0), metrics.snapshot().get("live_sockets"));
+        assert_eq!(0, metrics.sockets.clients());
+        assert!(LiveSocket::admit(&metrics, first).is_ok());
+    }
+
+    /// Test that preflight latencies are reported
+    /// per provider, and failures in aggregate
+    #[rstest]
+    #[test_log::test]
+    fn test_preflight_stats() {
+        let metrics = ServiceMetrics::default();
+
+        metrics
+            .preflight
+            .record("pokeapi", Some(Duration::from_millis(120)));
+        metrics.preflight.record("geocoder", None);
+        metrics
+            .preflight
+            .record("pokeapi", Some(Duration::from_millis(40)));
+
+        let snapshot = metrics.snapshot();
+
+        assert_eq!(Some(&40), snapshot.get("preflight.pokeapi.latency_ms"));
+        assert_eq!(None, snapshot.get("preflight.geocoder.latency_ms"));
+        assert_eq!(Some(&1), snapshot.get("preflight.failures"));
+    }
+}
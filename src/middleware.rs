@@ -0,0 +1,503 @@
+//! ## Middleware
+//!
+
+// Standard Library Imports
+use core::{cell::RefCell, panic::AssertUnwindSafe, sync::atomic::Ordering};
+use std::{
+    backtrace::Backtrace,
+    panic::{set_hook, take_hook},
+    sync::Once,
+};
+
+// Third-Party Imports
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderName, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures::prelude::*;
+use shuttle_secrets::SecretStore;
+use tracing::Instrument;
+use uuid::Uuid;
+
+// Crate-Level Imports
+use crate::{
+    errors::ErrorEnvelope,
+    reporting::ErrorEvent,
+    state::ShuttleAppState,
+    telemetry::{fields, spans::REQUEST},
+};
+
+thread_local! {
+    /// The backtrace of the most recent panic on the current thread
+    static LAST_PANIC_BACKTRACE: RefCell<Option<Backtrace>> = const { RefCell::new(None) };
+}
+
+static PANIC_HOOK: Once = Once::new();
+
+/// The header identifying each request (and its response)
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The longest client-supplied request id that's honored
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// The default `Content-Security-Policy` of non-JSON responses
+const DEFAULT_CONTENT_SECURITY_POLICY: &str =
+    "default-src 'none'; style-src 'self' 'unsafe-inline'; \
+img-src 'self' data:; base-uri 'none'; form-action 'self'; frame-ancestors 'none'";
+
+/// The default `Referrer-Policy` of all responses
+const DEFAULT_REFERRER_POLICY: &str = "no-referrer";
+
+/// The default `X-Frame-Options` of non-JSON responses
+const DEFAULT_FRAME_OPTIONS: &str = "DENY";
+
+// <editor-fold desc="// SecurityHeaders ...">
+
+/// The security headers set on the service's responses
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecurityHeaders {
+    /// The `Content-Security-Policy` of non-JSON responses
+    pub content_security_policy: HeaderValue,
+    /// The `Referrer-Policy` of all responses
+    pub referrer_policy: HeaderValue,
+    /// The `X-Frame-Options` of non-JSON responses
+    pub frame_options: HeaderValue,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self {
+            content_security_policy: HeaderValue::from_static(DEFAULT_CONTENT_SECURITY_POLICY),
+            referrer_policy: HeaderValue::from_static(DEFAULT_REFERRER_POLICY),
+            frame_options: HeaderValue::from_static(DEFAULT_FRAME_OPTIONS),
+        }
+    }
+}
+
+impl SecurityHeaders {
+    /// Read the headers from the service's
+    /// secrets (falling back to the defaults)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        let defaults = Self::default();
+
+        Self {
+            content_security_policy: Self::_secret(secrets, "CONTENT_SECURITY_POLICY")
+                .unwrap_or(defaults.content_security_policy),
+            referrer_policy: Self::_secret(secrets, "REFERRER_POLICY")
+                .unwrap_or(defaults.referrer_policy),
+            frame_options: Self::_secret(secrets, "FRAME_OPTIONS")
+                .unwrap_or(defaults.frame_options),
+        }
+    }
+
+    fn _secret(secrets: &SecretStore, key: &str) -> Option<HeaderValue> {
+        secrets.get(key).and_then(|value| {
+            HeaderValue::from_str(value.trim())
+                .map_err(|error| tracing::error!("ignoring {key} secret: {error}"))
+                .ok()
+        })
+    }
+
+    /// Set any of the headers the supplied response lacks,
+    /// applying the strict (document) profile to anything
+    /// but JSON and the relaxed (API) profile otherwise
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        let is_json = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .is_some_and(|mime| {
+                let mime = mime.trim().to_ascii_lowercase();
+                mime == "application/json" || mime.ends_with("+json")
+            });
+
+        headers
+            .entry(header::X_CONTENT_TYPE_OPTIONS)
+            .or_insert(HeaderValue::from_static("nosniff"));
+        headers
+            .entry(header::REFERRER_POLICY)
+            .or_insert(self.referrer_policy.clone());
+
+        if !is_json {
+            headers
+                .entry(header::CONTENT_SECURITY_POLICY)
+                .or_insert(self.content_security_policy.clone());
+            headers
+                .entry(header::X_FRAME_OPTIONS)
+                .or_insert(self.frame_options.clone());
+        }
+    }
+}
+
+// </editor-fold desc="// SecurityHeaders ...">
+
+/// Install a panic hook that captures the backtrace of
+/// each panic for later retrieval by [`catch_panics`]
+/// (while still deferring to any previously installed hook)
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = take_hook();
+
+        set_hook(Box::new(move |info| {
+            LAST_PANIC_BACKTRACE.with(|last| last.replace(Some(Backtrace::force_capture())));
+            previous(info);
+        }));
+    });
+}
+
+/// Get the supplied request's id (if it has one)
+pub fn request_id_of(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
+
+/// Middleware that identifies each request by its `x-request-id`
+/// header (honoring a client-supplied id if it's short and printable,
+/// and generating one otherwise), handles the request within a
+/// [`REQUEST`] span recording that id, and echoes it on the response
+pub async fn assign_request_ids<B: Send + 'static>(
+    mut request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .filter(|value| {
+            (1..=MAX_REQUEST_ID_LENGTH).contains(&value.len())
+                && value.as_bytes().iter().all(u8::is_ascii_graphic)
+        })
+        .cloned()
+        .unwrap_or_else(|| {
+            HeaderValue::try_from(Uuid::now_v7().to_string())
+                .expect("uuids are valid header values")
+        });
+
+    let span = tracing::info_span!(
+        REQUEST,
+        { fields::REQUEST_ID } = request_id.to_str().unwrap_or_default(),
+        method = %request.method(),
+        path = request.uri().path(),
+    );
+
+    request
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER.clone(), request_id.clone());
+
+    let mut response = next.run(request).instrument(span).await;
+
+    response
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER.clone(), request_id);
+
+    response
+}
+
+/// Middleware that converts handler panics into
+/// structured `500 Internal Server Error` responses
+pub async fn catch_panics<B: Send + 'static>(
+    State(state): State<ShuttleAppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    install_panic_hook();
+
+    let request_id = request_id_of(request.headers());
+
+    match AssertUnwindSafe(next.run(request)).catch_unwind().await {
+        Ok(response) => response,
+        Err(payload) => {
+            state.metrics.panics.fetch_add(1u64, Ordering::Relaxed);
+
+            let event = ErrorEvent::from_panic(payload.as_ref());
+            let backtrace = LAST_PANIC_BACKTRACE
+                .with(|last| last.take())
+                .map_or_else(|| String::from("<unavailable>"), |trace| trace.to_string());
+
+            tracing::error!(
+                request_id = ?request_id,
+                "handler panicked: {}\n{backtrace}",
+                event.message,
+            );
+
+            ErrorEnvelope::new(StatusCode::INTERNAL_SERVER_ERROR, "internal server error")
+                .with_request_id(request_id)
+                .into_response()
+        }
+    }
+}
+
+/// Middleware that sets the service's [security headers](SecurityHeaders)
+pub async fn set_security_headers<B: Send + 'static>(
+    State(state): State<ShuttleAppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let mut response = next.run(request).await;
+
+    state.security.apply(response.headers_mut());
+
+    response
+}
+
+/// Middleware that hides the administrative endpoints unless an
+/// admin token is configured, and rejects requests lacking it
+pub async fn require_admin<B: Send + 'static>(
+    State(state): State<ShuttleAppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    if !state.admin.is_enabled() {
+        return ErrorEnvelope::new(StatusCode::NOT_FOUND, "admin endpoints are disabled")
+            .into_response();
+    }
+
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+
+    if state.admin.permits(authorization) {
+        return next.run(request).await;
+    }
+
+    (
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        ErrorEnvelope::new(StatusCode::UNAUTHORIZED, "missing or invalid admin token"),
+    )
+        .into_response()
+}
+
+/// Middleware that hides the static asset browser unless its
+/// credentials are configured, and rejects requests lacking them
+pub async fn require_asset_credentials<B: Send + 'static>(
+    State(state): State<ShuttleAppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    if !state.asset_browser.is_enabled() {
+        return ErrorEnvelope::new(StatusCode::NOT_FOUND, "asset browser is disabled")
+            .into_response();
+    }
+
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+
+    if state.asset_browser.permits(authorization) {
+        return next.run(request).await;
+    }
+
+    (
+        [(header::WWW_AUTHENTICATE, r#"Basic realm="assets""#)],
+        ErrorEnvelope::new(StatusCode::UNAUTHORIZED, "missing or invalid credentials"),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use core::{cmp::PartialEq, fmt::Debug, ops::BitOr, str::FromStr, sync::atomic::Ordering};
+
+    // Third-Party Imports
+    use axum::{
+        body::{Body, BoxBody, HttpBody},
+        http::{Request, StatusCode},
+        middleware::from_fn_with_state,
+        routing::{self, Router},
+    };
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use crate::admin::AdminAuth;
+    use crate::errors::ErrorEnvelope;
+    use crate::middleware::SecurityHeaders;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{state, TestService};
+
+    /// Test that each request's id is honored (if it's usable) or
+    /// generated, echoed on its response, and included in its errors
+    #[rstest]
+    #[case::honored(Some("abc123"), Some("abc123"))]
+    #[case::generated(None, None)]
+    #[case::unprintable(Some("abc 123"), None)]
+    #[case::oversized(Some(&*"a".repeat(crate::middleware::MAX_REQUEST_ID_LENGTH + 1)), None)]
+    #[test_log::test(tokio::test)]
+    async fn test_assign_request_ids(
+        state: ShuttleAppState,
+        #[case] supplied: Option<&str>,
+        #[case] expected: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let mut request = Request::get("/no/such/route");
+
+        if let Some(supplied) = supplied {
+            request = request.header(&super::REQUEST_ID_HEADER, supplied);
+        }
+
+        let response = TestService::from(crate::router(state))
+            .resolve(request.body(Body::empty())?)
+            .await?;
+
+        let echoed = super::request_id_of(response.headers()).expect("no request id echoed");
+
+        match expected {
+            Some(expected) => assert_eq!(expected, echoed),
+            None => assert!(uuid::Uuid::parse_str(&echoed).is_ok(), "{echoed}"),
+        }
+
+        let body: serde_json::Value =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(serde_json::json!(echoed), body["error"]["request_id"]);
+
+        Ok(())
+    }
+
+    /// Test that `catch_panics` converts handler panics into
+    /// structured 500 responses and counts them
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_catch_panics(state: ShuttleAppState) -> anyhow::Result<()> {
+        let metrics = state.metrics.clone();
+        let service = TestService::from(
+            Router::new()
+                .route("/panic", routing::get(|| async { panic!("oh no") as &str }))
+                .layer(from_fn_with_state(state.clone(), super::catch_panics))
+                .with_state(state),
+        );
+
+        let response = service
+            .resolve(
+                Request::get("/panic")
+                    .header("x-request-id", "abc123")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, response.status());
+
+        let envelope: ErrorEnvelope =
+            serde_json::from_slice(response.into_body().data().await.unwrap()?.as_ref())?;
+
+        assert_eq!(
+            ErrorEnvelope::new(StatusCode::INTERNAL_SERVER_ERROR, "internal server error")
+                .with_request_id(Some(String::from("abc123"))),
+            envelope,
+        );
+        assert_eq!(1u64, metrics.panics.load(Ordering::Relaxed));
+
+        Ok(())
+    }
+
+    /// Test that `require_admin` only admits requests bearing
+    /// the configured admin token, and hides the endpoints
+    /// altogether when none is configured
+    #[rstest]
+    #[case::no_token_configured(None, None, StatusCode::NOT_FOUND)]
+    #[case::no_token_configured_any_bearer(None, Some("Bearer "), StatusCode::NOT_FOUND)]
+    #[case::missing_credentials(Some("sleigh"), None, StatusCode::UNAUTHORIZED)]
+    #[case::wrong_token(Some("sleigh"), Some("Bearer sled"), StatusCode::UNAUTHORIZED)]
+    #[case::wrong_scheme(Some("sleigh"), Some("Basic sleigh"), StatusCode::UNAUTHORIZED)]
+    #[case::valid_token(Some("sleigh"), Some("Bearer sleigh"), StatusCode::OK)]
+    #[test_log::test(tokio::test)]
+    async fn test_require_admin(
+        state: ShuttleAppState,
+        #[case] token: Option<&str>,
+        #[case] authorization: Option<&str>,
+        #[case] expected: StatusCode,
+    ) -> anyhow::Result<()> {
+        let state = ShuttleAppState {
+            admin: AdminAuth::new(token),
+            ..state
+        };
+        let service = TestService::from(
+            Router::new()
+                .route("/admin", routing::get(|| async { "ok" }))
+                .route_layer(from_fn_with_state(state.clone(), super::require_admin))
+                .with_state(state),
+        );
+
+        let mut request = Request::get("/admin");
+
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+
+        let response = service.resolve(request.body(Body::empty())?).await?;
+
+        assert_eq!(expected, response.status());
+
+        Ok(())
+    }
+
+    /// Test that `set_security_headers` applies the strict profile
+    /// to documents, the relaxed one to JSON, and leaves headers
+    /// set by handlers alone
+    #[rstest]
+    #[case::document("/document", Some("default-src 'none'"), Some("DENY"))]
+    #[case::json("/json", None, None)]
+    #[case::handler_override("/framed", Some("default-src 'none'"), Some("SAMEORIGIN"))]
+    #[test_log::test(tokio::test)]
+    async fn test_set_security_headers(
+        state: ShuttleAppState,
+        #[case] url: &str,
+        #[case] expected_csp: Option<&str>,
+        #[case] expected_frame_options: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let state = ShuttleAppState {
+            security: SecurityHeaders {
+                content_security_policy: "default-src 'none'".parse()?,
+                ..SecurityHeaders::default()
+            },
+            ..state
+        };
+        let service = TestService::from(
+            Router::new()
+                .route("/document", routing::get(|| async { "<p>hi</p>" }))
+                .route("/json", routing::get(|| async { axum::Json([1, 2, 3]) }))
+                .route(
+                    "/framed",
+                    routing::get(|| async { ([("x-frame-options", "SAMEORIGIN")], "<p>hi</p>") }),
+                )
+                .layer(from_fn_with_state(
+                    state.clone(),
+                    super::set_security_headers,
+                ))
+                .with_state(state),
+        );
+
+        let response = service.resolve(url).await?;
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .map(|value| value.to_str().unwrap().to_string())
+        };
+
+        assert_eq!(
+            Some(String::from("nosniff")),
+            header("x-content-type-options")
+        );
+        assert_eq!(Some(String::from("no-referrer")), header("referrer-policy"));
+        assert_eq!(
+            expected_csp.map(String::from),
+            header("content-security-policy")
+        );
+        assert_eq!(
+            expected_frame_options.map(String::from),
+            header("x-frame-options")
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+//! ## OpenAPI Document
+//!
+//! An [OpenAPI 3](https://spec.openapis.org/oas/v3.0.3) description
+//! of the solutions modules' routes, generated from the metadata
+//! they [declare](crate::solutions::RouteDoc) alongside them
+
+// Standard Library Imports
+use std::collections::BTreeSet;
+
+// Third-Party Imports
+use axum::extract::Json;
+use serde_json::{json, Map, Value};
+
+// Crate-Level Imports
+use crate::{
+    solutions::{self, RouteDoc},
+    version::VERSION,
+};
+
+/// Convert a route pattern (e.g. `/8/weight/:pokedex_id`) to an
+/// OpenAPI path template (e.g. `/8/weight/{pokedex_id}`), along
+/// with the names of its parameters
+pub fn path_template(pattern: &str) -> (String, Vec<&str>) {
+    let mut parameters = Vec::new();
+
+    let template = pattern
+        .split('/')
+        .map(|segment| match segment.strip_prefix([':', '*']) {
+            Some(name) => {
+                parameters.push(name);
+                format!("{{{name}}}")
+            }
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    (template, parameters)
+}
+
+/// Describe the supplied routes as an OpenAPI document
+pub fn document(docs: &[RouteDoc]) -> Value {
+    let mut paths = Map::new();
+    let mut schemes = BTreeSet::new();
+
+    for doc in docs {
+        let (template, parameters) = path_template(doc.path);
+
+        let mut operation = json!({
+            "summary": doc.summary,
+            "tags": [format!("Day {}", doc.day)],
+            "parameters": parameters
+                .iter()
+                .map(|name| json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": {"type": "string"},
+                }))
+                .collect::<Vec<_>>(),
+            "responses": {"default": {"description": "The route's response"}},
+        });
+
+        if let Some(scheme) = doc.auth {
+            schemes.insert(scheme);
+            operation["security"] = json!([{ scheme: [] }]);
+        }
+
+        paths
+            .entry(template)
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .expect("path items are objects")
+            .insert(doc.method.to_lowercase(), operation);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Shuttle CCH 2023",
+            "version": VERSION,
+        },
+        "paths": paths,
+        "components": {
+            "securitySchemes": schemes
+                .into_iter()
+                .map(|scheme| (scheme.to_string(), json!({"type": "http", "scheme": scheme})))
+                .collect::<Map<_, _>>(),
+        },
+    })
+}
+
+/// Describe every registered solutions module's routes
+#[tracing::instrument]
+pub async fn get_openapi_document() -> Json<Value> {
+    Json(document(&solutions::route_docs()))
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Third-Party Imports
+    use axum::http::StatusCode;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+
+    // Crate-Level Imports
+    use super::{document, path_template};
+    use crate::routes;
+    use crate::solutions::{self, RouteDoc};
+    use crate::utils::{service, TestService};
+
+    /// Test that route patterns are converted to path templates
+    #[rstest]
+    #[case::static_path("/days", "/days", &[])]
+    #[case::parameter("/8/weight/:pokedex_id", "/8/weight/{pokedex_id}", &["pokedex_id"])]
+    #[case::wildcard("/1/*packets", "/1/{packets}", &["packets"])]
+    #[case::several("/a/:b/c/:d", "/a/{b}/c/{d}", &["b", "d"])]
+    #[test_log::test]
+    fn test_path_template(
+        #[case] pattern: &str,
+        #[case] expected_template: &str,
+        #[case] expected_parameters: &[&str],
+    ) {
+        assert_eq!(
+            (expected_template.to_string(), expected_parameters.to_vec()),
+            path_template(pattern)
+        );
+    }
+
+    /// Test that routes sharing a path are described as one path
+    /// item, and that authorized routes name their scheme
+    #[rstest]
+    #[test_log::test]
+    fn test_document() {
+        let doc = |method, auth| RouteDoc {
+            day: 7,
+            method,
+            path: "/7/things/:id",
+            summary: "Things",
+            auth,
+        };
+
+        let document = document(&[doc("GET", None), doc("POST", Some("basic"))]);
+        let item = &document["paths"]["/7/things/{id}"];
+
+        assert_eq!(json!("Things"), item["get"]["summary"]);
+        assert_eq!(json!(["Day 7"]), item["get"]["tags"]);
+        assert_eq!(json!("id"), item["get"]["parameters"][0]["name"]);
+        assert_eq!(Value::Null, item["get"]["security"]);
+        assert_eq!(json!([{"basic": []}]), item["post"]["security"]);
+        assert_eq!(
+            json!({"basic": {"type": "http", "scheme": "basic"}}),
+            document["components"]["securitySchemes"]
+        );
+    }
+
+    /// Test that the served document describes every declared route
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_get_openapi_document(service: TestService) -> anyhow::Result<()> {
+        let response = service.resolve(routes::openapi()).await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let document: Value =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        for doc in solutions::route_docs() {
+            let (template, _) = path_template(doc.path);
+
+            assert_eq!(
+                json!(doc.summary),
+                document["paths"][&template][doc.method.to_lowercase()]["summary"],
+                "{} {template} is undocumented",
+                doc.method,
+            );
+        }
+
+        Ok(())
+    }
+}
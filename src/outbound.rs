@@ -0,0 +1,309 @@
+//! ## Outbound HTTP Clients
+//!
+//! The clients the service calls its upstream providers
+//! with, routed through whichever proxy the service's
+//! secrets (or, failing that, its environment) specify,
+//! and kept warm by periodic [preflight](OutboundClients::preflight)
+//! calls
+
+// Standard Library Imports
+use core::time::Duration;
+
+// Third-Party Imports
+use futures::future::join_all;
+use reqwest::{Client, NoProxy, Proxy, Url};
+use shuttle_secrets::SecretStore;
+use tokio::time::Instant;
+
+// Crate-Level Imports
+use crate::metrics::ServiceMetrics;
+
+/// How long a preflight call may take before it's abandoned
+const PREFLIGHT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// <editor-fold desc="// ProxyRoute ...">
+
+/// How a provider's calls reach it
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum ProxyRoute {
+    /// Through whichever proxy the environment's `HTTPS_PROXY`
+    /// (and `NO_PROXY`) variables specify, if any
+    #[default]
+    System,
+    /// Directly, ignoring any proxy
+    Direct,
+    /// Through the specified proxy
+    Via(Url),
+}
+
+impl ProxyRoute {
+    /// Parse the named secret's value (if set) as either
+    /// a proxy url or `"none"` (for a direct connection)
+    fn from_secret(secrets: &SecretStore, name: &str) -> Option<Self> {
+        let value = secrets.get(name)?;
+
+        if value.eq_ignore_ascii_case("none") {
+            return Some(Self::Direct);
+        }
+
+        value
+            .parse::<Url>()
+            .map(Self::Via)
+            .map_err(|error| tracing::error!("ignoring {name} secret: {error}"))
+            .ok()
+    }
+}
+
+// </editor-fold desc="// ProxyRoute ...">
+
+// <editor-fold desc="// Provider ...">
+
+/// The upstream providers the service calls
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Provider {
+    /// [PokeAPI](https://pokeapi.co) (for Day 8)
+    PokeApi,
+    /// [geocode.maps.co](https://geocode.maps.co) (for Day 21)
+    Geocoder,
+}
+
+impl Provider {
+    /// Every upstream provider
+    pub const ALL: [Self; 2] = [Self::PokeApi, Self::Geocoder];
+
+    /// The provider's name (in metrics)
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PokeApi => "pokeapi",
+            Self::Geocoder => "geocoder",
+        }
+    }
+
+    /// The (cheap) url preflight calls are made to
+    fn preflight_url(self) -> &'static str {
+        match self {
+            Self::PokeApi => "https://pokeapi.co/api/v2/",
+            Self::Geocoder => "https://geocode.maps.co/",
+        }
+    }
+
+    /// The secret overriding the provider's proxy
+    fn secret(self) -> &'static str {
+        match self {
+            Self::PokeApi => "POKEAPI_PROXY",
+            Self::Geocoder => "GEOCODER_PROXY",
+        }
+    }
+}
+
+// </editor-fold desc="// Provider ...">
+
+// <editor-fold desc="// ProxySettings ...">
+
+/// The proxy (and proxy exclusions) outbound calls are routed
+/// through, along with any per-provider overrides thereof
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ProxySettings {
+    /// the route of calls to providers without an override
+    pub default: ProxyRoute,
+    /// the comma-separated hosts (or domains, or CIDR
+    /// blocks) that are always called directly
+    pub no_proxy: Option<String>,
+    /// the route of calls to PokeAPI (if overridden)
+    pub pokeapi: Option<ProxyRoute>,
+    /// the route of calls to the geocoder (if overridden)
+    pub geocoder: Option<ProxyRoute>,
+}
+
+impl ProxySettings {
+    /// Read the `HTTPS_PROXY` and `NO_PROXY` secrets (if set),
+    /// along with the `POKEAPI_PROXY` and `GEOCODER_PROXY`
+    /// overrides (each of which may be `"none"`, for a
+    /// direct connection)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        Self {
+            default: ProxyRoute::from_secret(secrets, "HTTPS_PROXY").unwrap_or_default(),
+            no_proxy: secrets.get("NO_PROXY"),
+            pokeapi: ProxyRoute::from_secret(secrets, Provider::PokeApi.secret()),
+            geocoder: ProxyRoute::from_secret(secrets, Provider::Geocoder.secret()),
+        }
+    }
+
+    /// The route of calls to the supplied provider
+    pub fn route(&self, provider: Provider) -> &ProxyRoute {
+        match provider {
+            Provider::PokeApi => self.pokeapi.as_ref(),
+            Provider::Geocoder => self.geocoder.as_ref(),
+        }
+        .unwrap_or(&self.default)
+    }
+
+    /// Build a client calling the supplied provider via its route
+    pub fn client(&self, provider: Provider) -> Client {
+        let builder = Client::builder();
+
+        let builder = match self.route(provider) {
+            ProxyRoute::System => builder,
+            ProxyRoute::Direct => builder.no_proxy(),
+            ProxyRoute::Via(url) => match Proxy::all(url.clone()) {
+                Ok(proxy) => builder
+                    .proxy(proxy.no_proxy(self.no_proxy.as_deref().and_then(NoProxy::from_string))),
+                Err(error) => {
+                    tracing::error!("ignoring {provider:?} proxy: {error}");
+                    builder
+                }
+            },
+        };
+
+        builder.build().unwrap_or_else(|error| {
+            tracing::error!("unable to build {provider:?} client: {error}");
+            Client::new()
+        })
+    }
+}
+
+// </editor-fold desc="// ProxySettings ...">
+
+// <editor-fold desc="// OutboundClients ...">
+
+/// The clients calls to each upstream provider are made with
+#[derive(Clone, Debug, Default)]
+pub struct OutboundClients {
+    /// the client PokeAPI is called with
+    pub pokeapi: Client,
+    /// the client the geocoder is called with
+    pub geocoder: Client,
+}
+
+impl OutboundClients {
+    /// Build each provider's client per the supplied settings
+    pub fn new(settings: &ProxySettings) -> Self {
+        Self {
+            pokeapi: settings.client(Provider::PokeApi),
+            geocoder: settings.client(Provider::Geocoder),
+        }
+    }
+
+    /// Build each provider's client per the service's secrets
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        Self::new(&ProxySettings::from_secrets(secrets))
+    }
+
+    /// The client the supplied provider is called with
+    pub fn client(&self, provider: Provider) -> &Client {
+        match provider {
+            Provider::PokeApi => &self.pokeapi,
+            Provider::Geocoder => &self.geocoder,
+        }
+    }
+
+    /// `HEAD` every provider (concurrently) to resolve its
+    /// address and establish a pooled (TLS) connection ahead
+    /// of any "real" call, recording each call's latency (or
+    /// failure) in the supplied metrics, and reporting the
+    /// number of providers reached
+    pub async fn preflight(&self, metrics: &ServiceMetrics) -> usize {
+        let outcomes = join_all(Provider::ALL.map(|provider| async move {
+            let started = Instant::now();
+
+            // any response at all means the connection is warm
+            let outcome = self
+                .client(provider)
+                .head(provider.preflight_url())
+                .timeout(PREFLIGHT_TIMEOUT)
+                .send()
+                .await
+                .map(|_| started.elapsed());
+
+            if let Err(error) = outcome.as_ref() {
+                tracing::warn!("preflight call to {} failed: {error}", provider.name());
+            }
+
+            metrics
+                .preflight
+                .record(provider.name(), outcome.as_ref().ok().copied());
+
+            outcome.is_ok()
+        }))
+        .await;
+
+        outcomes.into_iter().filter(|reached| *reached).count()
+    }
+}
+
+// </editor-fold desc="// OutboundClients ...">
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::collections::BTreeMap;
+
+    // Third-Party Imports
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use shuttle_secrets::SecretStore;
+
+    // Crate-Level Imports
+    use super::{OutboundClients, Provider, ProxyRoute, ProxySettings};
+    use crate::metrics::ServiceMetrics;
+
+    /// Test that each provider's route falls back from its
+    /// override to the `HTTPS_PROXY` secret to the environment
+    #[rstest]
+    #[case::unset(&[], ProxyRoute::System, ProxyRoute::System)]
+    #[case::global(
+        &[("HTTPS_PROXY", "http://proxy.corp:3128")],
+        ProxyRoute::Via("http://proxy.corp:3128".parse().unwrap()),
+        ProxyRoute::Via("http://proxy.corp:3128".parse().unwrap()),
+    )]
+    #[case::overridden(
+        &[("HTTPS_PROXY", "http://proxy.corp:3128"), ("POKEAPI_PROXY", "none"), ("GEOCODER_PROXY", "socks5://geo.corp:1080")],
+        ProxyRoute::Direct,
+        ProxyRoute::Via("socks5://geo.corp:1080".parse().unwrap()),
+    )]
+    #[case::invalid(&[("HTTPS_PROXY", "not a url"), ("GEOCODER_PROXY", "None")], ProxyRoute::System, ProxyRoute::Direct)]
+    #[test_log::test]
+    fn test_proxy_routes(
+        #[case] secrets: &[(&str, &str)],
+        #[case] expected_pokeapi: ProxyRoute,
+        #[case] expected_geocoder: ProxyRoute,
+    ) {
+        let settings = ProxySettings::from_secrets(&SecretStore::new(BTreeMap::from_iter(
+            secrets
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string().into())),
+        )));
+
+        assert_eq!(&expected_pokeapi, settings.route(Provider::PokeApi));
+        assert_eq!(&expected_geocoder, settings.route(Provider::Geocoder));
+
+        // every route should yield a usable client
+        settings.client(Provider::PokeApi);
+        settings.client(Provider::Geocoder);
+    }
+
+    /// Test that failed preflight calls are counted (here, by
+    /// routing them through a proxy that refuses connections)
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_failed_preflight() -> anyhow::Result<()> {
+        let metrics = ServiceMetrics::default();
+        let clients = OutboundClients::new(&ProxySettings {
+            default: ProxyRoute::Via("http://127.0.0.1:1".parse()?),
+            ..ProxySettings::default()
+        });
+
+        assert_eq!(0, clients.preflight(&metrics).await);
+
+        let snapshot = metrics.snapshot();
+
+        assert_eq!(Some(&2), snapshot.get("preflight.failures"));
+        assert_eq!(None, snapshot.get("preflight.pokeapi.latency_ms"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+//! ## Event Outbox
+//!
+//! Events are written to the `outbox` table by the same statement as
+//! the change they describe, then delivered (at least once) by the
+//! [`outbox-dispatch`](crate::scheduler::JOBS) job, so no event is lost
+//! if the process dies between committing a change and announcing it
+
+// Third-Party Imports
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use shuttle_secrets::SecretStore;
+use sqlx::{error::Error as DbError, postgres::PgQueryResult, FromRow};
+use tokio::sync::broadcast;
+use url::Url;
+
+/// The topic of the events recording newly inserted gift orders
+pub const ORDERS_CREATED: &str = "orders.created";
+
+/// The maximum number of events delivered per dispatch
+const DISPATCH_BATCH_SIZE: i64 = 100;
+
+/// The number of failed deliveries after
+/// which an event is no longer retried
+const MAX_DELIVERY_ATTEMPTS: i32 = 10;
+
+/// The number of delivered events retained
+/// for (slow) subscribers to the live feed
+const FEED_CAPACITY: usize = 256;
+
+// <editor-fold desc="// OutboxEvent ...">
+
+/// An event awaiting (or having had) delivery
+#[derive(Clone, Debug, FromRow, Serialize, Deserialize)]
+pub struct OutboxEvent {
+    /// the event's sequential id
+    pub id: i64,
+    /// what the event describes, e.g. [`ORDERS_CREATED`]
+    pub topic: String,
+    /// the event's content
+    pub payload: Value,
+    /// when the event was recorded
+    pub created_at: DateTime<Utc>,
+    /// the number of failed deliveries so far
+    pub attempts: i32,
+}
+
+// </editor-fold desc="// OutboxEvent ...">
+
+// <editor-fold desc="// Outbox ...">
+
+/// Delivers recorded events to the live feed
+/// and (if configured) the outbox webhook
+#[derive(Clone, Debug)]
+pub struct Outbox {
+    /// where events are `POST`ed (if anywhere)
+    webhook: Option<Url>,
+    /// the client making webhook requests
+    client: reqwest::Client,
+    /// the in-process live feed of delivered events
+    feed: broadcast::Sender<OutboxEvent>,
+}
+
+impl Default for Outbox {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Outbox {
+    /// Create an outbox delivering events to
+    /// the supplied webhook (if any)
+    pub fn new(webhook: Option<Url>) -> Self {
+        Self {
+            webhook,
+            client: reqwest::Client::new(),
+            feed: broadcast::channel(FEED_CAPACITY).0,
+        }
+    }
+
+    /// Create an outbox delivering events to the
+    /// `OUTBOX_WEBHOOK_URL` secret's url (if set)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        let webhook = secrets.get("OUTBOX_WEBHOOK_URL").and_then(|value| {
+            value
+                .parse::<Url>()
+                .map_err(|error| tracing::error!("ignoring OUTBOX_WEBHOOK_URL secret: {error}"))
+                .ok()
+        });
+
+        Self::new(webhook)
+    }
+
+    /// (Re)create the `outbox` table (if it doesn't already exist)
+    pub async fn ensure(db: &sqlx::PgPool) -> Result<PgQueryResult, DbError> {
+        sqlx::query(
+            r#"CREATE TABLE IF NOT EXISTS public.outbox (
+                 id BIGSERIAL PRIMARY KEY,
+                 topic TEXT NOT NULL,
+                 payload JSONB NOT NULL,
+                 created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                 attempts INT NOT NULL DEFAULT 0,
+                 last_error TEXT,
+                 delivered_at TIMESTAMPTZ
+               );
+            "#,
+        )
+        .execute(db)
+        .await?;
+
+        sqlx::query(
+            r#"CREATE INDEX IF NOT EXISTS outbox_undelivered
+               ON public.outbox (id)
+               WHERE delivered_at IS NULL;
+            "#,
+        )
+        .execute(db)
+        .await
+    }
+
+    /// Subscribe to the live feed of delivered events
+    pub fn subscribe(&self) -> broadcast::Receiver<OutboxEvent> {
+        self.feed.subscribe()
+    }
+
+    /// Deliver the supplied event to the
+    /// webhook (if any) and the live feed
+    pub async fn deliver(&self, event: &OutboxEvent) -> Result<(), String> {
+        if let Some(webhook) = self.webhook.as_ref() {
+            self.client
+                .post(webhook.clone())
+                .json(event)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(|error| format!("{error}"))?;
+        }
+
+        // having no live subscribers isn't a delivery failure
+        let _ = self.feed.send(event.clone());
+
+        Ok(())
+    }
+
+    /// Deliver the oldest undelivered events (in order), marking
+    /// each as delivered or recording why its delivery failed,
+    /// and returning the number of events delivered
+    pub async fn dispatch(&self, db: &sqlx::PgPool) -> Result<usize, DbError> {
+        let exists =
+            sqlx::query_scalar::<_, bool>("SELECT to_regclass('public.outbox') IS NOT NULL")
+                .fetch_one(db)
+                .await?;
+
+        if !exists {
+            return Ok(0);
+        }
+
+        let pending = sqlx::query_as::<_, OutboxEvent>(
+            r#"SELECT id, topic, payload, created_at, attempts
+               FROM public.outbox
+               WHERE delivered_at IS NULL AND attempts < $1
+               ORDER BY id ASC
+               LIMIT $2
+            "#,
+        )
+        .bind(MAX_DELIVERY_ATTEMPTS)
+        .bind(DISPATCH_BATCH_SIZE)
+        .fetch_all(db)
+        .await?;
+
+        let mut delivered = 0usize;
+
+        for event in pending {
+            match self.deliver(&event).await {
+                Ok(()) => {
+                    sqlx::query("UPDATE public.outbox SET delivered_at = NOW() WHERE id = $1")
+                        .bind(event.id)
+                        .execute(db)
+                        .await?;
+
+                    delivered += 1;
+                }
+                Err(error) => {
+                    tracing::warn!(
+                        event = event.id,
+                        topic = event.topic,
+                        "delivery failed: {error}"
+                    );
+
+                    sqlx::query(
+                        "UPDATE public.outbox SET attempts = attempts + 1, last_error = $2 WHERE id = $1",
+                    )
+                    .bind(event.id)
+                    .bind(error)
+                    .execute(db)
+                    .await?;
+
+                    // later events wait their turn, so
+                    // subscribers see events in order
+                    break;
+                }
+            }
+        }
+
+        Ok(delivered)
+    }
+}
+
+// </editor-fold desc="// Outbox ...">
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::collections::BTreeMap;
+
+    // Third-Party Imports
+    use axum::{body::Body, http::Request};
+    use chrono::Utc;
+    use pretty_assertions::{assert_eq, assert_str_eq};
+    use rstest::rstest;
+    use serde_json::json;
+    use shuttle_secrets::SecretStore;
+    use tower::ServiceExt;
+
+    // Crate-Level Imports
+    use super::{Outbox, OutboxEvent, ORDERS_CREATED};
+    use crate::router;
+    use crate::routes;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{isolated_state, IsolatedState};
+
+    /// Test that the webhook is read from the
+    /// `OUTBOX_WEBHOOK_URL` secret (if it's valid)
+    #[rstest]
+    #[case::unset(None, None)]
+    #[case::valid(Some("https://example.com/hooks"), Some("https://example.com/hooks"))]
+    #[case::invalid(Some("not a url"), None)]
+    #[test_log::test]
+    fn test_outbox_webhook(#[case] secret: Option<&str>, #[case] expected: Option<&str>) {
+        let secrets =
+            SecretStore::new(BTreeMap::from_iter(secret.map(|value| {
+                ("OUTBOX_WEBHOOK_URL".to_string(), value.to_string().into())
+            })));
+
+        assert_eq!(
+            expected,
+            Outbox::from_secrets(&secrets)
+                .webhook
+                .as_ref()
+                .map(url::Url::as_str)
+        );
+    }
+
+    /// Test that delivered events are published to the live feed
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_deliver_to_feed() {
+        let outbox = Outbox::default();
+        let mut feed = outbox.subscribe();
+        let event = OutboxEvent {
+            id: 7,
+            topic: ORDERS_CREATED.to_string(),
+            payload: json!({"orders": []}),
+            created_at: Utc::now(),
+            attempts: 0,
+        };
+
+        assert_eq!(Ok(()), outbox.deliver(&event).await);
+        assert_eq!(7, feed.recv().await.unwrap().id);
+    }
+
+    /// Test that inserting orders records an event in the
+    /// same statement, which is then dispatched exactly once
+    #[rstest]
+    #[cfg_attr(not(feature = "ci"), ignore = "requires a PostgreSQL instance")]
+    #[test_log::test(tokio::test)]
+    async fn test_order_events_are_dispatched(
+        #[future(awt)]
+        #[from(isolated_state)]
+        state: IsolatedState,
+    ) -> anyhow::Result<()> {
+        let mut feed = state.outbox.subscribe();
+
+        for request in [
+            Request::post(routes::day13::reset()).body(Body::empty())?,
+            Request::post(routes::day13::orders())
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"[{"id":1,"region_id":1,"gift_name":"Sled","quantity":3}]"#,
+                ))?,
+        ] {
+            router(state.clone()).oneshot(request).await?;
+        }
+
+        assert_eq!(1, state.outbox.dispatch(&state.db).await?);
+        assert_eq!(0, state.outbox.dispatch(&state.db).await?);
+
+        let event = feed.recv().await?;
+
+        assert_str_eq!(ORDERS_CREATED, event.topic);
+        assert_eq!(
+            json!({"schema": "cch13", "orders": [{"id": 1, "quantity": 3, "gift_name": "Sled", "region_id": 1}]}),
+            event.payload
+        );
+
+        Ok(())
+    }
+}
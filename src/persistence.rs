@@ -0,0 +1,441 @@
+//! ## Encrypted Persistence
+//!
+//! A wrapper around the service's persistent key-value store that
+//! (if the `PERSISTENCE_KEY` secret is set) seals every value it
+//! writes with AES-256-GCM, transparently unsealing them on load
+
+// Standard Library Imports
+use core::fmt::{Debug, Formatter, Result as FormatResult};
+use std::sync::Arc;
+
+// Third-Party Imports
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use b64::{engine::general_purpose::STANDARD as base64, Engine};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use shuttle_persist::{PersistError, PersistInstance};
+use shuttle_secrets::SecretStore;
+
+/// The marker distinguishing sealed values from
+/// (legacy) plaintext ones in the underlying store
+const SEALED_MAGIC: [u8; 8] = *b"cch23aes";
+
+/// The length (in bytes) of an encryption key
+const KEY_LENGTH: usize = 32;
+
+// <editor-fold desc="// EncryptedPersistError ...">
+
+/// An error saving or loading an encrypted value
+#[derive(Debug, thiserror::Error)]
+pub enum EncryptedPersistError {
+    /// the underlying store failed
+    #[error(transparent)]
+    Store(#[from] PersistError),
+    /// the value couldn't be (de)serialized
+    #[error("failed to (de)serialize value: {0}")]
+    Encoding(#[from] serde_json::Error),
+    /// the value was sealed with a key that isn't (or is no longer) known
+    #[error("value sealed with unknown key: {0}")]
+    UnknownKey(String),
+    /// the value couldn't be sealed or unsealed
+    #[error("failed to {0} value")]
+    Cipher(&'static str),
+}
+
+// </editor-fold desc="// EncryptedPersistError ...">
+
+// <editor-fold desc="// Keyring ...">
+
+/// An encryption key and its (public) id
+#[derive(Clone)]
+struct SealingKey {
+    /// the key's id, recorded alongside the values it seals
+    id: String,
+    /// the key's cipher
+    cipher: Aes256Gcm,
+}
+
+impl SealingKey {
+    /// Create a key from the supplied bytes
+    fn new(key: &[u8; KEY_LENGTH]) -> Self {
+        Self {
+            id: Sha256::digest(key)
+                .iter()
+                .take(4)
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        }
+    }
+
+    /// Decode a key from the supplied (base64-encoded) secret value
+    fn decode(encoded: &str) -> Result<Self, String> {
+        let bytes = base64
+            .decode(encoded.trim())
+            .map_err(|error| format!("{error}"))?;
+
+        <[u8; KEY_LENGTH]>::try_from(bytes.as_slice())
+            .map(|key| Self::new(&key))
+            .map_err(|_| format!("must decode to exactly {KEY_LENGTH} bytes"))
+    }
+}
+
+/// The key new values are sealed with, and
+/// any retired keys older values may be sealed with
+#[derive(Clone)]
+pub struct Keyring {
+    /// the key new values are sealed with
+    current: SealingKey,
+    /// keys older values may still be sealed with
+    retired: Vec<SealingKey>,
+}
+
+impl Debug for Keyring {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FormatResult {
+        f.debug_struct("Keyring")
+            .field("current", &self.current.id)
+            .field(
+                "retired",
+                &self.retired.iter().map(|key| &key.id).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Keyring {
+    /// Create a keyring from the supplied current and retired keys
+    pub fn new(current: &[u8; KEY_LENGTH], retired: &[[u8; KEY_LENGTH]]) -> Self {
+        Self {
+            current: SealingKey::new(current),
+            retired: retired.iter().map(SealingKey::new).collect(),
+        }
+    }
+
+    /// Read the keyring from the `PERSISTENCE_KEY` secret and the
+    /// (comma-separated) `PERSISTENCE_RETIRED_KEYS` secret, each
+    /// key being the base64 encoding of 32 random bytes
+    pub fn from_secrets(secrets: &SecretStore) -> Option<Self> {
+        let current = secrets.get("PERSISTENCE_KEY").and_then(|key| {
+            SealingKey::decode(&key)
+                .map_err(|error| tracing::error!("ignoring PERSISTENCE_KEY secret: {error}"))
+                .ok()
+        });
+
+        let Some(current) = current else {
+            tracing::warn!("PERSISTENCE_KEY secret unset, persisted values are unencrypted");
+            return None;
+        };
+
+        let retired = secrets
+            .get("PERSISTENCE_RETIRED_KEYS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|key| !key.trim().is_empty())
+            .filter_map(|key| {
+                SealingKey::decode(key)
+                    .map_err(|error| {
+                        tracing::error!("ignoring PERSISTENCE_RETIRED_KEYS entry: {error}");
+                    })
+                    .ok()
+            })
+            .collect();
+
+        Some(Self { current, retired })
+    }
+
+    /// Get the key with the supplied id (if known)
+    fn key(&self, id: &str) -> Option<&SealingKey> {
+        core::iter::once(&self.current)
+            .chain(self.retired.iter())
+            .find(|key| key.id == id)
+    }
+}
+
+// </editor-fold desc="// Keyring ...">
+
+// <editor-fold desc="// EncryptedPersistence ...">
+
+/// A value as sealed in the underlying store
+#[derive(Debug, Serialize, Deserialize)]
+struct SealedValue {
+    /// always [`SEALED_MAGIC`]
+    magic: [u8; 8],
+    /// the id of the key the value was sealed with
+    key: String,
+    /// the nonce the value was sealed with
+    nonce: Vec<u8>,
+    /// the sealed (JSON-encoded) value
+    ciphertext: Vec<u8>,
+}
+
+/// The service's persistent key-value store, sealing
+/// values at rest (if it has a [`Keyring`])
+#[derive(Clone, Debug)]
+pub struct EncryptedPersistence {
+    /// the underlying (plaintext) store
+    store: PersistInstance,
+    /// the keys values are sealed with (if any)
+    keys: Option<Arc<Keyring>>,
+}
+
+impl From<PersistInstance> for EncryptedPersistence {
+    fn from(store: PersistInstance) -> Self {
+        Self::new(store, None)
+    }
+}
+
+impl EncryptedPersistence {
+    /// Wrap the supplied store, sealing values
+    /// with the supplied keyring (if any)
+    pub fn new(store: PersistInstance, keys: Option<Keyring>) -> Self {
+        Self {
+            store,
+            keys: keys.map(Arc::new),
+        }
+    }
+
+    /// Whether values are sealed at rest
+    pub fn is_encrypted(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    /// Seal the supplied plaintext with the current key
+    fn _seal(
+        keys: &Keyring,
+        name: &str,
+        plaintext: &[u8],
+    ) -> Result<SealedValue, EncryptedPersistError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = keys
+            .current
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| EncryptedPersistError::Cipher("seal"))?;
+
+        Ok(SealedValue {
+            magic: SEALED_MAGIC,
+            key: keys.current.id.clone(),
+            nonce: nonce.to_vec(),
+            ciphertext,
+        })
+    }
+
+    /// Unseal the supplied value (which is bound to
+    /// the name it was saved under)
+    fn _unseal(
+        keys: &Keyring,
+        name: &str,
+        sealed: &SealedValue,
+    ) -> Result<Vec<u8>, EncryptedPersistError> {
+        let key = keys
+            .key(&sealed.key)
+            .ok_or_else(|| EncryptedPersistError::UnknownKey(sealed.key.clone()))?;
+
+        if sealed.nonce.len() != 12 {
+            return Err(EncryptedPersistError::Cipher("unseal"));
+        }
+
+        key.cipher
+            .decrypt(
+                Nonce::from_slice(&sealed.nonce),
+                Payload {
+                    msg: &sealed.ciphertext,
+                    aad: name.as_bytes(),
+                },
+            )
+            .map_err(|_| EncryptedPersistError::Cipher("unseal"))
+    }
+
+    /// Load the sealed value saved under the supplied name (if
+    /// the saved value is sealed, rather than legacy plaintext)
+    fn _load_sealed(&self, name: &str) -> Option<SealedValue> {
+        // check the marker before decoding the rest of the value, as
+        // decoding plaintext as a sealed value can read an arbitrary
+        // (and arbitrarily large) length prefix
+        self.store
+            .load::<[u8; 8]>(name)
+            .is_ok_and(|magic| magic == SEALED_MAGIC)
+            .then(|| self.store.load::<SealedValue>(name).ok())
+            .flatten()
+    }
+
+    /// Save the supplied value under the supplied name
+    pub fn save<T: Serialize>(&self, name: &str, value: T) -> Result<(), EncryptedPersistError> {
+        let Some(keys) = self.keys.as_deref() else {
+            return Ok(self.store.save(name, value)?);
+        };
+
+        let sealed = Self::_seal(keys, name, &serde_json::to_vec(&value)?)?;
+
+        Ok(self.store.save(name, sealed)?)
+    }
+
+    /// Load the value saved under the supplied name, unsealing
+    /// it if necessary (values saved before encryption was
+    /// enabled are still loaded as-is)
+    pub fn load<T: DeserializeOwned>(&self, name: &str) -> Result<T, EncryptedPersistError> {
+        match (self.keys.as_deref(), self._load_sealed(name)) {
+            (Some(keys), Some(sealed)) => Ok(serde_json::from_slice(&Self::_unseal(
+                keys, name, &sealed,
+            )?)?),
+            (None, Some(sealed)) => Err(EncryptedPersistError::UnknownKey(sealed.key)),
+            (_, None) => Ok(self.store.load(name)?),
+        }
+    }
+
+    /// List the names of every saved value
+    pub fn list(&self) -> Result<Vec<String>, EncryptedPersistError> {
+        Ok(self.store.list()?)
+    }
+
+    /// Remove the value saved under the supplied name
+    pub fn remove(&self, name: &str) -> Result<(), EncryptedPersistError> {
+        Ok(self.store.remove(name)?)
+    }
+
+    /// Check that values can be saved, loaded, and removed by
+    /// round-tripping a throwaway value under the supplied name
+    pub fn probe(&self, name: &str) -> Result<(), EncryptedPersistError> {
+        let written = crate::version::VERSION.to_string();
+
+        self.save(name, &written)?;
+        let read = self.load::<String>(name);
+        self.remove(name)?;
+
+        if read? == written {
+            Ok(())
+        } else {
+            Err(EncryptedPersistError::Cipher("probe"))
+        }
+    }
+
+    /// Re-seal every value sealed with a retired key with the
+    /// current one, returning the number of values re-sealed
+    /// (after which the retired keys can safely be dropped)
+    pub fn rotate(&self) -> Result<usize, EncryptedPersistError> {
+        let Some(keys) = self.keys.as_deref() else {
+            return Ok(0);
+        };
+
+        let mut resealed = 0usize;
+
+        for name in self.store.list()? {
+            let Some(sealed) = self
+                ._load_sealed(&name)
+                .filter(|sealed| sealed.key != keys.current.id)
+            else {
+                continue;
+            };
+
+            let plaintext = Self::_unseal(keys, &name, &sealed)?;
+
+            self.store
+                .save(&name, Self::_seal(keys, &name, &plaintext)?)?;
+            resealed += 1;
+        }
+
+        Ok(resealed)
+    }
+}
+
+// </editor-fold desc="// EncryptedPersistence ...">
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::collections::BTreeMap;
+
+    // Third-Party Imports
+    use b64::{engine::general_purpose::STANDARD as base64, Engine};
+    use chrono::{DateTime, Utc};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use shuttle_persist::PersistInstance;
+    use shuttle_secrets::SecretStore;
+
+    // Crate-Level Imports
+    use super::{EncryptedPersistError, EncryptedPersistence, Keyring, SEALED_MAGIC};
+
+    const OLD_KEY: [u8; 32] = [7u8; 32];
+    const NEW_KEY: [u8; 32] = [42u8; 32];
+
+    /// Test that the keyring is read from the
+    /// service's secrets, ignoring malformed keys
+    #[rstest]
+    #[case::unset(None, None, None)]
+    #[case::current(Some(base64.encode(NEW_KEY)), None, Some(0))]
+    #[case::rotated(Some(base64.encode(NEW_KEY)), Some(format!("{}, not-a-key", base64.encode(OLD_KEY))), Some(1))]
+    #[case::short(Some(base64.encode([1u8; 16])), None, None)]
+    #[test_log::test]
+    fn test_keyring_from_secrets(
+        #[case] current: Option<String>,
+        #[case] retired: Option<String>,
+        #[case] expected: Option<usize>,
+    ) {
+        let secrets = SecretStore::new(BTreeMap::from_iter(
+            [
+                ("PERSISTENCE_KEY", current),
+                ("PERSISTENCE_RETIRED_KEYS", retired),
+            ]
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.to_string(), value?.into()))),
+        ));
+
+        assert_eq!(
+            expected,
+            Keyring::from_secrets(&secrets).map(|keys| keys.retired.len())
+        );
+    }
+
+    /// Test that values are sealed at rest, that legacy plaintext
+    /// values still load, and that rotating keys re-seals values
+    /// sealed with retired keys
+    #[rstest]
+    #[test_log::test]
+    fn test_encrypted_persistence() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let store = PersistInstance::new(directory.path().to_path_buf())?;
+        let stamp = "2023-12-12T12:12:12Z".parse::<DateTime<Utc>>()?;
+
+        store.save("legacy", 12u64)?;
+
+        let old = EncryptedPersistence::new(store.clone(), Some(Keyring::new(&OLD_KEY, &[])));
+
+        old.save("packet", stamp)?;
+
+        assert_eq!(stamp, old.load::<DateTime<Utc>>("packet")?);
+        assert_eq!(12u64, old.load::<u64>("legacy")?);
+        assert_eq!(SEALED_MAGIC, store.load::<[u8; 8]>("packet")?);
+        assert!(matches!(
+            EncryptedPersistence::from(store.clone()).load::<DateTime<Utc>>("packet"),
+            Err(EncryptedPersistError::UnknownKey(_))
+        ));
+
+        let new =
+            EncryptedPersistence::new(store.clone(), Some(Keyring::new(&NEW_KEY, &[OLD_KEY])));
+
+        assert_eq!(stamp, new.load::<DateTime<Utc>>("packet")?);
+        assert_eq!(1, new.rotate()?);
+        assert_eq!(0, new.rotate()?);
+
+        let rotated = EncryptedPersistence::new(store, Some(Keyring::new(&NEW_KEY, &[])));
+
+        assert_eq!(stamp, rotated.load::<DateTime<Utc>>("packet")?);
+        assert!(old.load::<DateTime<Utc>>("packet").is_err());
+
+        Ok(())
+    }
+}
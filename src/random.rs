@@ -0,0 +1,163 @@
+//! ## Randomness Sources
+//!
+
+// Standard Library Imports
+use core::fmt::Debug;
+use std::sync::Mutex;
+
+// Third-Party Imports
+use chrono::{DateTime, Utc};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use shuttle_secrets::SecretStore;
+use ulid::Ulid;
+use uuid::Uuid;
+
+// <editor-fold desc="// RandomSource ...">
+
+/// A source of random values
+pub trait RandomSource: Debug + Send + Sync {
+    /// Get the next random `u64`
+    fn next_u64(&self) -> u64;
+
+    /// Fill the supplied buffer with random bytes
+    fn fill_bytes(&self, dest: &mut [u8]);
+
+    /// Get a random value in `0..upper` (or `0`
+    /// if `upper` is `0`)
+    fn below(&self, upper: u64) -> u64 {
+        self.next_u64().checked_rem(upper).unwrap_or(0)
+    }
+
+    /// Generate a random (v4) UUID
+    fn uuid(&self) -> Uuid {
+        let mut bytes = [0u8; 16];
+
+        self.fill_bytes(&mut bytes);
+
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    }
+
+    /// Generate a ULID for the supplied timestamp
+    fn ulid(&self, timestamp: DateTime<Utc>) -> Ulid {
+        let mut bytes = [0u8; 16];
+
+        self.fill_bytes(&mut bytes);
+
+        Ulid::from_parts(
+            u64::try_from(timestamp.timestamp_millis()).unwrap_or_default(),
+            u128::from_be_bytes(bytes),
+        )
+    }
+}
+
+// </editor-fold desc="// RandomSource ...">
+
+// <editor-fold desc="// StdRandom ...">
+
+/// A (optionally seeded) standard random number generator
+#[derive(Debug)]
+pub struct StdRandom(Mutex<StdRng>);
+
+impl Default for StdRandom {
+    fn default() -> Self {
+        Self(Mutex::new(StdRng::from_entropy()))
+    }
+}
+
+impl StdRandom {
+    /// Create a generator whose output is
+    /// fully determined by the supplied seed
+    pub fn seeded(seed: u64) -> Self {
+        Self(Mutex::new(StdRng::seed_from_u64(seed)))
+    }
+
+    /// Create a generator seeded by the `RNG_SEED` secret
+    /// (if set), or from system entropy otherwise
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        secrets
+            .get("RNG_SEED")
+            .and_then(|seed| {
+                seed.parse::<u64>()
+                    .map_err(|error| tracing::error!("ignoring RNG_SEED secret: {error}"))
+                    .ok()
+            })
+            .map_or_else(Self::default, Self::seeded)
+    }
+}
+
+impl RandomSource for StdRandom {
+    fn next_u64(&self) -> u64 {
+        self.0.lock().unwrap().next_u64()
+    }
+
+    fn fill_bytes(&self, dest: &mut [u8]) {
+        self.0.lock().unwrap().fill_bytes(dest);
+    }
+}
+
+// </editor-fold desc="// StdRandom ...">
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::collections::BTreeMap;
+
+    // Third-Party Imports
+    use pretty_assertions::{assert_eq, assert_ne};
+    use rstest::rstest;
+    use shuttle_secrets::SecretStore;
+
+    // Crate-Level Imports
+    use super::{RandomSource, StdRandom};
+
+    /// Test that identically seeded generators
+    /// produce identical sequences
+    #[rstest]
+    #[test_log::test]
+    fn test_seeded_determinism() {
+        let (left, right) = (StdRandom::seeded(25), StdRandom::seeded(25));
+
+        assert_eq!(left.uuid(), right.uuid());
+        assert_eq!(
+            left.ulid("2023-12-25T00:00:00Z".parse().unwrap()),
+            right.ulid("2023-12-25T00:00:00Z".parse().unwrap()),
+        );
+        assert_eq!(left.below(10), right.below(10));
+        assert_ne!(left.next_u64(), StdRandom::seeded(24).next_u64());
+    }
+
+    /// Test that the `RNG_SEED` secret seeds the generator
+    #[rstest]
+    #[test_log::test]
+    fn test_seed_from_secrets() {
+        let secrets = SecretStore::new(BTreeMap::from([(
+            String::from("RNG_SEED"),
+            String::from("1225").into(),
+        )]));
+
+        assert_eq!(
+            StdRandom::seeded(1225).next_u64(),
+            StdRandom::from_secrets(&secrets).next_u64()
+        );
+    }
+
+    /// Test that generated ids carry the expected
+    /// version and timestamp
+    #[rstest]
+    #[test_log::test]
+    fn test_generated_ids() {
+        let random = StdRandom::seeded(0);
+        let timestamp = "2023-12-25T00:00:00Z".parse().unwrap();
+
+        assert_eq!(Some(uuid::Version::Random), random.uuid().get_version());
+        assert_eq!(
+            timestamp,
+            chrono::DateTime::<chrono::Utc>::from(random.ulid(timestamp).datetime())
+        );
+        assert_eq!(0, random.below(0));
+    }
+}
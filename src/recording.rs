@@ -0,0 +1,532 @@
+//! ## Request Recording
+//!
+//! Records (bounded) request/response pairs in the service's
+//! persistent store, so exactly what a client sent (say, the
+//! grader, when a challenge fails) can be inspected and replayed
+
+// Standard Library Imports
+use core::fmt::Write as _;
+use std::time::Instant;
+
+// Third-Party Imports
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::{Json, Path, State},
+    http::{Method, Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+// Crate-Level Imports
+use crate::{
+    errors::ErrorEnvelope,
+    redaction::REDACTED,
+    responses::{ApiOk, ApiResult},
+    state::ShuttleAppState,
+    utils::MAX_MULTIPART_BODY_SIZE,
+};
+
+/// The prefix of the persisted recordings' names
+const RECORDING_PREFIX: &str = "recording-";
+
+/// The number of recordings retained (the
+/// oldest are forgotten beyond this many)
+const MAX_RECORDINGS: usize = 256;
+
+/// The largest (UTF-8) body recorded verbatim,
+/// rather than as its length and digest
+const MAX_VERBATIM_BODY: usize = 64 * 1024;
+
+/// The header marking a replayed request (which isn't re-recorded)
+pub const REPLAY_HEADER: &str = "x-cch23-replay";
+
+/// The paths whose requests aren't recorded
+const UNRECORDED_PREFIXES: [&str; 2] = ["/admin", "/metrics"];
+
+// <editor-fold desc="// Recording ...">
+
+/// A recorded request or response body
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedBody {
+    /// a (small enough) UTF-8 body, recorded verbatim
+    Text(String),
+    /// a binary (or too large) body's length and SHA-256 digest
+    Digest {
+        /// the body's length (in bytes)
+        length: usize,
+        /// the body's (hex-encoded) SHA-256 digest
+        sha256: String,
+    },
+    /// a body of unknown length (e.g. a stream), which isn't recorded
+    Unrecorded,
+}
+
+impl RecordedBody {
+    /// Record the supplied body
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        match core::str::from_utf8(bytes) {
+            Ok(text) if bytes.len() <= MAX_VERBATIM_BODY => Self::Text(text.to_string()),
+            _ => Self::Digest {
+                length: bytes.len(),
+                sha256: Sha256::digest(bytes)
+                    .iter()
+                    .fold(String::new(), |mut hex, byte| {
+                        let _ = write!(hex, "{byte:02x}");
+                        hex
+                    }),
+            },
+        }
+    }
+}
+
+/// A recorded request
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    /// the request's HTTP method
+    pub method: String,
+    /// the request's path (and query)
+    pub uri: String,
+    /// the request's (redacted) headers
+    pub headers: Vec<(String, String)>,
+    /// the request's body
+    pub body: RecordedBody,
+}
+
+/// A recorded response
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    /// the response's HTTP status code
+    pub status: u16,
+    /// the response's headers
+    pub headers: Vec<(String, String)>,
+    /// the response's body
+    pub body: RecordedBody,
+}
+
+/// A recorded request/response pair
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Recording {
+    /// the recording's (time-ordered) id
+    pub id: Uuid,
+    /// when the request was received
+    pub recorded_at: DateTime<Utc>,
+    /// how long the request took to handle (in milliseconds)
+    pub elapsed_ms: u64,
+    /// the recorded request
+    pub request: RecordedRequest,
+    /// the recorded response
+    pub response: RecordedResponse,
+}
+
+/// The gist of a [`Recording`]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordingSummary {
+    /// the recording's id
+    pub id: Uuid,
+    /// when the request was received
+    pub recorded_at: DateTime<Utc>,
+    /// the request's HTTP method
+    pub method: String,
+    /// the request's path (and query)
+    pub uri: String,
+    /// the response's HTTP status code
+    pub status: u16,
+}
+
+impl From<Recording> for RecordingSummary {
+    fn from(recording: Recording) -> Self {
+        Self {
+            id: recording.id,
+            recorded_at: recording.recorded_at,
+            method: recording.request.method,
+            uri: recording.request.uri,
+            status: recording.response.status,
+        }
+    }
+}
+
+impl Recording {
+    /// The name the recording is persisted under
+    fn name(id: &Uuid) -> String {
+        format!("{RECORDING_PREFIX}{id}")
+    }
+
+    /// Persist the recording, forgetting the
+    /// oldest recordings if there are too many
+    pub fn save(&self, state: &ShuttleAppState) {
+        if let Err(error) = state.persistence.save(&Self::name(&self.id), self) {
+            tracing::error!("error saving recording {}: {error}", self.id);
+            return;
+        }
+
+        let mut names = Self::_names(state);
+
+        names.sort();
+        names.reverse();
+
+        for name in names.iter().skip(MAX_RECORDINGS) {
+            if let Err(error) = state.persistence.remove(name) {
+                tracing::warn!("error forgetting {name}: {error}");
+            }
+        }
+    }
+
+    /// Load the specified recording (if it exists)
+    pub fn load(state: &ShuttleAppState, id: &Uuid) -> Option<Self> {
+        state.persistence.load(&Self::name(id)).ok()
+    }
+
+    /// Load every retained recording, most recent first
+    pub fn all(state: &ShuttleAppState) -> Vec<Self> {
+        let mut recordings = Self::_names(state)
+            .iter()
+            .filter_map(|name| state.persistence.load::<Self>(name).ok())
+            .collect::<Vec<Self>>();
+
+        recordings.sort_by_key(|recording| std::cmp::Reverse(recording.id));
+
+        recordings
+    }
+
+    /// The names of every persisted recording
+    fn _names(state: &ShuttleAppState) -> Vec<String> {
+        state
+            .persistence
+            .list()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|name| name.starts_with(RECORDING_PREFIX))
+            .collect()
+    }
+
+    /// Rebuild the recorded request (if its body was recorded verbatim),
+    /// less its redacted headers (redacted body fields are replayed as-is)
+    pub fn to_request(&self) -> Result<Request<Body>, String> {
+        let body = match &self.request.body {
+            RecordedBody::Text(text) => Body::from(text.clone()),
+            _ => return Err(String::from("the request's body wasn't recorded verbatim")),
+        };
+
+        let mut builder = Request::builder()
+            .method(
+                Method::from_bytes(self.request.method.as_bytes())
+                    .map_err(|error| format!("{error}"))?,
+            )
+            .uri(self.request.uri.as_str())
+            .header(REPLAY_HEADER, self.id.to_string());
+
+        for (name, value) in &self.request.headers {
+            if value != REDACTED {
+                builder = builder.header(name, value);
+            }
+        }
+
+        builder.body(body).map_err(|error| format!("{error}"))
+    }
+}
+
+/// The outcome of replaying a [`Recording`]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordingReplay {
+    /// the replayed recording's id
+    pub recording: Uuid,
+    /// whether the replayed response matched the recorded one
+    pub matched: bool,
+    /// the recorded response
+    pub recorded: RecordedResponse,
+    /// the replayed response
+    pub replayed: RecordedResponse,
+}
+
+// </editor-fold desc="// Recording ...">
+
+/// Buffer the supplied body (if its length is known
+/// and bounded), returning it along with its record
+async fn _buffer<B>(body: B, limit: usize) -> (Option<Bytes>, Option<B>)
+where
+    B: HttpBody<Data = Bytes>,
+    B::Error: core::fmt::Display,
+{
+    if body
+        .size_hint()
+        .exact()
+        .is_none_or(|length| limit < length as usize)
+    {
+        return (None, Some(body));
+    }
+
+    match hyper::body::to_bytes(body).await {
+        Ok(bytes) => (Some(bytes), None),
+        Err(error) => {
+            tracing::warn!("error buffering body: {error}");
+            (Some(Bytes::new()), None)
+        }
+    }
+}
+
+/// Middleware that records each (non-administrative)
+/// request, and the response to it
+pub async fn record_exchanges(
+    State(state): State<ShuttleAppState>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let path = request.uri().path();
+
+    if request.headers().contains_key(REPLAY_HEADER)
+        || UNRECORDED_PREFIXES
+            .iter()
+            .any(|prefix| path == *prefix || path.starts_with(&format!("{prefix}/")))
+    {
+        return next.run(request).await;
+    }
+
+    let (started, recorded_at) = (Instant::now(), state.clock.now());
+    let (parts, body) = request.into_parts();
+    let mut recorded = RecordedRequest {
+        method: parts.method.to_string(),
+        uri: parts.uri.to_string(),
+        headers: state.redaction.headers(&parts.headers),
+        body: RecordedBody::Unrecorded,
+    };
+
+    let body = match _buffer(body, MAX_MULTIPART_BODY_SIZE).await {
+        (Some(bytes), _) => {
+            recorded.body = RecordedBody::from_bytes(&state.redaction.body(&parts.headers, &bytes));
+            Body::from(bytes)
+        }
+        (None, Some(body)) => body,
+        (None, None) => Body::empty(),
+    };
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+    let (parts, body) = response.into_parts();
+
+    let (body, recorded_body) = match _buffer(body, MAX_MULTIPART_BODY_SIZE).await {
+        (Some(bytes), _) => {
+            let recorded = RecordedBody::from_bytes(&state.redaction.body(&parts.headers, &bytes));
+            (axum::body::boxed(Body::from(bytes)), recorded)
+        }
+        (None, Some(body)) => (body, RecordedBody::Unrecorded),
+        (None, None) => (axum::body::boxed(Body::empty()), RecordedBody::Unrecorded),
+    };
+
+    Recording {
+        id: Uuid::now_v7(),
+        recorded_at,
+        elapsed_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+        request: recorded,
+        response: RecordedResponse {
+            status: parts.status.as_u16(),
+            headers: state.redaction.headers(&parts.headers),
+            body: recorded_body,
+        },
+    }
+    .save(&state);
+
+    Response::from_parts(parts, body)
+}
+
+/// List the retained recordings, most recent first
+#[tracing::instrument(skip_all)]
+pub async fn list_recordings(State(state): State<ShuttleAppState>) -> Json<Vec<RecordingSummary>> {
+    Json(
+        Recording::all(&state)
+            .into_iter()
+            .map(RecordingSummary::from)
+            .collect(),
+    )
+}
+
+/// Get the specified recording
+#[tracing::instrument(skip(state))]
+pub async fn get_recording(
+    State(state): State<ShuttleAppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Recording> {
+    Recording::load(&state, &id).map(ApiOk).ok_or_else(|| {
+        ErrorEnvelope::new(StatusCode::NOT_FOUND, format!("no such recording: {id}")).into()
+    })
+}
+
+/// Replay the specified recording against the live
+/// router, comparing the response to the recorded one
+#[tracing::instrument(skip(state))]
+pub async fn replay_recording(
+    State(state): State<ShuttleAppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<RecordingReplay> {
+    let recording = Recording::load(&state, &id).ok_or_else(|| {
+        ErrorEnvelope::new(StatusCode::NOT_FOUND, format!("no such recording: {id}"))
+    })?;
+
+    let request = recording
+        .to_request()
+        .map_err(|error| ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, error))?;
+
+    let response = crate::router(state.clone())
+        .oneshot(request)
+        .await
+        .map_err(|error| ErrorEnvelope::new(StatusCode::INTERNAL_SERVER_ERROR, error))?;
+
+    let (parts, body) = response.into_parts();
+    let replayed = RecordedResponse {
+        status: parts.status.as_u16(),
+        headers: state.redaction.headers(&parts.headers),
+        body: match _buffer(body, MAX_MULTIPART_BODY_SIZE).await {
+            (Some(bytes), _) => {
+                RecordedBody::from_bytes(&state.redaction.body(&parts.headers, &bytes))
+            }
+            _ => RecordedBody::Unrecorded,
+        },
+    };
+
+    Ok(ApiOk(RecordingReplay {
+        recording: id,
+        matched: replayed.status == recording.response.status
+            && replayed.body == recording.response.body,
+        recorded: recording.response,
+        replayed,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Third-Party Imports
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use super::{RecordedBody, Recording, RecordingReplay, RecordingSummary};
+    use crate::router;
+    use crate::routes;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{admin_request, state, TestService};
+
+    /// Test that bodies are recorded verbatim only if
+    /// they're (small enough) UTF-8, and hashed otherwise
+    #[rstest]
+    #[case::text(b"elf on a shelf".to_vec(), true)]
+    #[case::binary(vec![0xff, 0xfe, 0x00], false)]
+    #[case::oversized(vec![b'a'; 64 * 1024 + 1], false)]
+    #[test_log::test]
+    fn test_recorded_bodies(#[case] bytes: Vec<u8>, #[case] verbatim: bool) {
+        match RecordedBody::from_bytes(&bytes) {
+            RecordedBody::Text(text) => {
+                assert!(verbatim);
+                assert_eq!(bytes, text.into_bytes());
+            }
+            RecordedBody::Digest { length, sha256 } => {
+                assert!(!verbatim);
+                assert_eq!(bytes.len(), length);
+                assert_eq!(64, sha256.len());
+            }
+            RecordedBody::Unrecorded => panic!("supplied bodies are always recorded"),
+        }
+    }
+
+    /// Test that requests are recorded (with their credentials
+    /// redacted), listed, and replayed without being re-recorded
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_record_and_replay(state: ShuttleAppState) -> anyhow::Result<()> {
+        let text = "recorded elf on a recorded shelf";
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(
+                Request::post(routes::day6::elves())
+                    .header(header::AUTHORIZATION, "Bearer hunter2")
+                    .body(Body::from(text))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let recording = Recording::all(&state)
+            .into_iter()
+            .find(|recording| recording.request.body == RecordedBody::Text(text.to_string()))
+            .expect("the request should've been recorded");
+
+        assert_eq!("POST", recording.request.method);
+        assert_eq!(200, recording.response.status);
+        assert!(recording
+            .request
+            .headers
+            .contains(&("authorization".to_string(), "[redacted]".to_string())));
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(admin_request(Method::GET, routes::admin::recordings()).body(Body::empty())?)
+            .await?;
+        let summaries: Vec<RecordingSummary> =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert!(summaries.iter().any(|summary| summary.id == recording.id));
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(
+                admin_request(Method::POST, routes::admin::replay_recording(recording.id))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let replay: RecordingReplay =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert!(replay.matched);
+        assert_eq!(
+            1,
+            Recording::all(&state)
+                .iter()
+                .filter(|recording| recording.request.body == RecordedBody::Text(text.to_string()))
+                .count()
+        );
+
+        Ok(())
+    }
+
+    /// Test that day 15's passwords are recorded redacted
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_redacted_passwords(state: ShuttleAppState) -> anyhow::Result<()> {
+        let password = "Recorded-Hunter2-12345";
+
+        TestService::from(router(state.clone()))
+            .resolve(
+                Request::post(routes::day15::game())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(format!(r#"{{"input": "{password}"}}"#)))?,
+            )
+            .await?;
+
+        let recordings = Recording::all(&state)
+            .into_iter()
+            .filter(|recording| recording.request.uri == routes::day15::game())
+            .collect::<Vec<Recording>>();
+
+        assert!(!recordings.is_empty());
+        assert!(recordings
+            .iter()
+            .all(|recording| !serde_json::to_string(recording)
+                .is_ok_and(|recorded| recorded.contains(password))));
+        assert!(recordings.iter().any(|recording| recording.request.body
+            == RecordedBody::Text(serde_json::json!({"input": "[redacted]"}).to_string())));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,345 @@
+//! ## Redaction Rules
+//!
+//! Rules for the headers and (JSON or form) body fields whose values
+//! are replaced before a request or response is recorded or reported,
+//! so that credentials (and day 15's passwords) never land anywhere
+//! verbatim. Rules beyond the defaults are read from the service's
+//! `REDACTED_HEADERS` and `REDACTED_FIELDS` secrets, each of which is
+//! a comma-separated list (of header names and of field paths like
+//! `$.password` or `$.user.token`, respectively).
+
+// Standard Library Imports
+use std::collections::BTreeSet;
+
+// Third-Party Imports
+use axum::{
+    body::Bytes,
+    http::{header, HeaderMap},
+};
+use serde_json::Value;
+use shuttle_secrets::SecretStore;
+use url::form_urlencoded;
+
+/// The value redacted headers and fields are replaced with
+pub const REDACTED: &str = "[redacted]";
+
+/// The headers redacted regardless of the service's secrets
+pub const DEFAULT_REDACTED_HEADERS: [&str; 4] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+];
+
+/// The fields redacted regardless of the service's secrets
+/// (day 15's passwords are submitted as `{"input": ...}`)
+pub const DEFAULT_REDACTED_FIELDS: [&str; 3] = ["$.password", "$.token", "$.input"];
+
+// <editor-fold desc="// FieldPath ...">
+
+/// The path of a (possibly nested) body field, written as
+/// `$.outer.inner` (arrays along the way are searched element-wise)
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct FieldPath(Vec<String>);
+
+impl core::str::FromStr for FieldPath {
+    type Err = String;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let segments = path
+            .trim()
+            .strip_prefix("$.")
+            .ok_or_else(|| format!("field paths must start with `$.`: {path}"))?
+            .split('.')
+            .map(String::from)
+            .collect::<Vec<String>>();
+
+        if segments.iter().any(String::is_empty) {
+            return Err(format!("field paths may not have empty segments: {path}"));
+        }
+
+        Ok(Self(segments))
+    }
+}
+
+impl core::fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "$.{}", self.0.join("."))
+    }
+}
+
+impl FieldPath {
+    /// Redact the field (if it's present) in the supplied
+    /// value, returning whether anything was redacted
+    fn redact(&self, value: &mut Value) -> bool {
+        Self::_redact(&self.0, value)
+    }
+
+    fn _redact(segments: &[String], value: &mut Value) -> bool {
+        let Some((first, rest)) = segments.split_first() else {
+            return false;
+        };
+
+        match value {
+            Value::Array(items) => items.iter_mut().fold(false, |redacted, item| {
+                Self::_redact(segments, item) | redacted
+            }),
+            Value::Object(fields) => match fields.get_mut(first) {
+                Some(field) if rest.is_empty() => {
+                    *field = Value::String(REDACTED.to_string());
+                    true
+                }
+                Some(field) => Self::_redact(rest, field),
+                None => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+// </editor-fold desc="// FieldPath ...">
+
+// <editor-fold desc="// RedactionRules ...">
+
+/// The headers and body fields redacted from recorded
+/// (or reported) requests and responses
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedactionRules {
+    /// the (lowercase) names of the redacted headers
+    pub headers: BTreeSet<String>,
+    /// the paths of the redacted body fields
+    pub fields: BTreeSet<FieldPath>,
+}
+
+impl Default for RedactionRules {
+    fn default() -> Self {
+        Self {
+            headers: DEFAULT_REDACTED_HEADERS.map(String::from).into(),
+            fields: DEFAULT_REDACTED_FIELDS
+                .iter()
+                .filter_map(|path| path.parse().ok())
+                .collect(),
+        }
+    }
+}
+
+impl RedactionRules {
+    /// Read the rules from the service's secrets
+    /// (in addition to the default rules)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        let mut rules = Self::default();
+
+        if let Some(headers) = secrets.get("REDACTED_HEADERS") {
+            rules
+                .headers
+                .extend(_entries(&headers).map(|name| name.to_ascii_lowercase()));
+        }
+
+        if let Some(fields) = secrets.get("REDACTED_FIELDS") {
+            rules.fields.extend(_entries(&fields).filter_map(|path| {
+                path.parse::<FieldPath>()
+                    .map_err(|error| tracing::error!("ignoring REDACTED_FIELDS secret: {error}"))
+                    .ok()
+            }));
+        }
+
+        rules
+    }
+
+    /// Check if the named header's value is redacted
+    pub fn redacts_header(&self, name: &str) -> bool {
+        self.headers.contains(&name.to_ascii_lowercase())
+    }
+
+    /// The supplied headers' names and (possibly redacted) values
+    pub fn headers(&self, headers: &HeaderMap) -> Vec<(String, String)> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if self.redacts_header(name.as_str()) {
+                    String::from(REDACTED)
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+
+                (name.to_string(), value)
+            })
+            .collect()
+    }
+
+    /// The supplied body, with its redacted fields replaced if
+    /// it's JSON or form data (per its `Content-Type` header)
+    pub fn body(&self, headers: &HeaderMap, body: &Bytes) -> Bytes {
+        let media = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|media| media.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        let redacted = if media == "application/json" || media.ends_with("+json") {
+            self._json(body)
+        } else if media == "application/x-www-form-urlencoded" {
+            self._form(body)
+        } else {
+            None
+        };
+
+        redacted.map_or_else(|| body.clone(), Bytes::from)
+    }
+
+    /// Redact the supplied JSON body's fields (if it's valid JSON and
+    /// any were redacted), preserving its formatting otherwise
+    fn _json(&self, body: &[u8]) -> Option<Vec<u8>> {
+        let mut value = serde_json::from_slice::<Value>(body).ok()?;
+
+        self.fields
+            .iter()
+            .fold(false, |redacted, path| path.redact(&mut value) | redacted)
+            .then(|| serde_json::to_vec(&value).ok())
+            .flatten()
+    }
+
+    /// Redact the supplied form body's (top-level) fields (if any were)
+    fn _form(&self, body: &[u8]) -> Option<Vec<u8>> {
+        let mut redacted = false;
+        let mut form = form_urlencoded::Serializer::new(String::new());
+
+        for (name, value) in form_urlencoded::parse(body) {
+            if self.fields.iter().any(|path| path.0 == [name.as_ref()]) {
+                redacted = true;
+                form.append_pair(&name, REDACTED);
+            } else {
+                form.append_pair(&name, &value);
+            }
+        }
+
+        redacted.then(|| form.finish().into_bytes())
+    }
+}
+
+// </editor-fold desc="// RedactionRules ...">
+
+/// The non-empty entries of a comma-separated secret
+fn _entries(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::collections::BTreeMap;
+
+    // Third-Party Imports
+    use axum::{
+        body::Bytes,
+        http::{header, HeaderMap, HeaderValue},
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use shuttle_secrets::SecretStore;
+
+    // Crate-Level Imports
+    use super::{FieldPath, RedactionRules, REDACTED};
+
+    /// Test that field paths are parsed (only) from `$.`-prefixed paths
+    #[rstest]
+    #[case::top_level("$.password", Ok(vec!["password"]))]
+    #[case::nested(" $.user.token ", Ok(vec!["user", "token"]))]
+    #[case::unrooted("password", Err(()))]
+    #[case::empty_segment("$.user..token", Err(()))]
+    #[case::root_only("$.", Err(()))]
+    #[test_log::test]
+    fn test_field_paths(#[case] path: &str, #[case] expected: Result<Vec<&str>, ()>) {
+        assert_eq!(
+            expected.map(|segments| FieldPath(segments.into_iter().map(String::from).collect())),
+            path.parse::<FieldPath>().map_err(|_| ())
+        );
+    }
+
+    /// Test that JSON and form bodies have their
+    /// (configured) fields redacted, and others don't
+    #[rstest]
+    #[case::day_15(
+        "application/json",
+        r#"{"input": "Password12345"}"#,
+        json!({"input": REDACTED}).to_string()
+    )]
+    #[case::nested(
+        "application/json; charset=utf-8",
+        r#"{"users": [{"name": "elf", "secret": "hunter2"}]}"#,
+        json!({"users": [{"name": "elf", "secret": REDACTED}]}).to_string()
+    )]
+    #[case::untouched_json(
+        "application/json",
+        r#"{"name":  "elf"}"#,
+        String::from(r#"{"name":  "elf"}"#)
+    )]
+    #[case::form(
+        "application/x-www-form-urlencoded",
+        "name=elf&password=hunter2",
+        format!("name=elf&password={}", "%5Bredacted%5D")
+    )]
+    #[case::plain_text(
+        "text/plain",
+        r#"{"input": "Password12345"}"#,
+        String::from(r#"{"input": "Password12345"}"#)
+    )]
+    #[test_log::test]
+    fn test_redacted_bodies(
+        #[case] content_type: &'static str,
+        #[case] body: &'static str,
+        #[case] expected: String,
+    ) {
+        let rules = RedactionRules::from_secrets(&SecretStore::new(BTreeMap::from([(
+            String::from("REDACTED_FIELDS"),
+            String::from("$.users.secret, not-a-path").into(),
+        )])));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+
+        assert_eq!(
+            Bytes::from(expected),
+            rules.body(&headers, &Bytes::from_static(body.as_bytes()))
+        );
+    }
+
+    /// Test that default and configured headers are redacted
+    #[rstest]
+    #[test_log::test]
+    fn test_redacted_headers() {
+        let rules = RedactionRules::from_secrets(&SecretStore::new(BTreeMap::from([(
+            String::from("REDACTED_HEADERS"),
+            String::from("X-Room-Token").into(),
+        )])));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer hunter2"),
+        );
+        headers.insert("x-room-token", HeaderValue::from_static("hunter3"));
+        headers.insert(header::ACCEPT, HeaderValue::from_static("text/plain"));
+
+        let mut recorded = rules.headers(&headers);
+        recorded.sort();
+
+        assert_eq!(
+            vec![
+                ("accept".to_string(), "text/plain".to_string()),
+                ("authorization".to_string(), REDACTED.to_string()),
+                ("x-room-token".to_string(), REDACTED.to_string()),
+            ],
+            recorded
+        );
+    }
+}
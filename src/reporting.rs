@@ -0,0 +1,431 @@
+//! ## Error Reporting
+//!
+
+// Standard Library Imports
+use core::{any::Any, fmt::Debug, panic::AssertUnwindSafe};
+use std::panic::resume_unwind;
+
+// Third-Party Imports
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::{MatchedPath, State},
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use bytes::BytesMut;
+use futures::{prelude::*, stream};
+use serde_json::{json, Value};
+use shuttle_secrets::SecretStore;
+
+// Crate-Level Imports
+use crate::{
+    errors::ErrorDetail, middleware::request_id_of, redaction::RedactionRules,
+    state::ShuttleAppState,
+};
+
+/// The maximum number of request body
+/// bytes included in a reported event
+const MAX_REPORTED_BODY_SIZE: usize = 1024;
+
+// <editor-fold desc="// ErrorEvent ...">
+
+/// An error (or panic) worth reporting, along
+/// with the context of the request (if any)
+/// that it occurred while handling
+#[derive(Clone, Debug, Default)]
+pub struct ErrorEvent {
+    /// A human-readable description of the error
+    pub message: String,
+    /// Whether the error was the result of a panic
+    pub panicked: bool,
+    /// The status code returned to the client
+    pub status: Option<u16>,
+    /// The matched route (e.g. `/19/ws/room/:room/user/:user`)
+    pub route: Option<String>,
+    /// The request's HTTP method
+    pub method: Option<String>,
+    /// The request's full URI
+    pub url: Option<String>,
+    /// The request's `x-request-id` header (if any)
+    pub request_id: Option<String>,
+    /// The request body (if short enough to report)
+    pub body: Option<String>,
+}
+
+impl ErrorEvent {
+    /// Create a new event with the supplied message
+    pub fn new<Message: ToString>(message: Message) -> Self {
+        Self {
+            message: message.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Create a new event from a caught panic's payload
+    pub fn from_panic(payload: &(dyn Any + Send)) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(ToString::to_string)
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("panic with non-string payload"));
+
+        Self {
+            panicked: true,
+            ..Self::new(message)
+        }
+    }
+
+    /// Attach the supplied request's context to the
+    /// event, less anything the supplied rules redact
+    pub fn with_request<B>(
+        mut self,
+        request: &Request<B>,
+        body: Option<&Bytes>,
+        rules: &RedactionRules,
+    ) -> Self {
+        self.method = Some(request.method().to_string());
+        self.url = Some(request.uri().to_string());
+        self.route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string());
+        self.request_id = request_id_of(request.headers());
+        self.body = body.filter(|data| !data.is_empty()).map(|data| {
+            let data = rules.body(request.headers(), data);
+
+            String::from_utf8_lossy(&data[..data.len().min(MAX_REPORTED_BODY_SIZE)]).to_string()
+        });
+
+        self
+    }
+
+    /// Render the event as a Sentry-compatible JSON payload
+    pub fn to_payload(&self) -> Value {
+        json!({
+            "event_id": uuid::Uuid::new_v4().simple().to_string(),
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "platform": "rust",
+            "level": if self.panicked { "fatal" } else { "error" },
+            "logger": env!("CARGO_PKG_NAME"),
+            "release": concat!(env!("CARGO_PKG_NAME"), "@", env!("CARGO_PKG_VERSION")),
+            "transaction": self.route,
+            "message": { "formatted": self.message },
+            "tags": {
+                "route": self.route,
+                "status": self.status,
+                "panic": self.panicked,
+                "request_id": self.request_id,
+            },
+            "request": {
+                "url": self.url,
+                "method": self.method,
+                "data": self.body,
+            },
+        })
+    }
+}
+
+// </editor-fold desc="// ErrorEvent ...">
+
+// <editor-fold desc="// SentryDsn ...">
+
+/// A parsed Sentry(-compatible) DSN
+#[cfg_attr(test, derive(Eq, PartialEq))]
+#[derive(Clone, Debug)]
+pub struct SentryDsn {
+    /// The project's public key
+    pub key: String,
+    /// The URL events should be sent to
+    pub store_url: String,
+}
+
+impl core::str::FromStr for SentryDsn {
+    type Err = String;
+
+    /// Parse a DSN of the form: `{scheme}://{key}@{host}[:{port}][/{path}]/{project_id}`
+    fn from_str(dsn: &str) -> Result<Self, Self::Err> {
+        let url = url::Url::parse(dsn).map_err(|error| format!("invalid DSN: {error}"))?;
+
+        let key = Some(url.username())
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| String::from("DSN missing public key"))?
+            .to_string();
+
+        let host = url
+            .host_str()
+            .ok_or_else(|| String::from("DSN missing host"))?;
+
+        let (prefix, project) = url
+            .path()
+            .trim_end_matches('/')
+            .rsplit_once('/')
+            .filter(|(_, project)| !project.is_empty())
+            .ok_or_else(|| String::from("DSN missing project id"))?;
+
+        let port = url
+            .port()
+            .map(|port| format!(":{port}"))
+            .unwrap_or_default();
+
+        Ok(Self {
+            key,
+            store_url: format!(
+                "{}://{host}{port}{prefix}/api/{project}/store/",
+                url.scheme()
+            ),
+        })
+    }
+}
+
+// </editor-fold desc="// SentryDsn ...">
+
+// <editor-fold desc="// ErrorReporter ...">
+
+/// An (optional) reporter of errors and panics
+/// to a Sentry-compatible error tracking service
+#[derive(Clone, Debug, Default)]
+pub struct ErrorReporter {
+    /// The DSN events should be shipped to (if any)
+    dsn: Option<SentryDsn>,
+    /// The client used to ship events
+    client: reqwest::Client,
+}
+
+impl ErrorReporter {
+    /// Create a reporter that ships events to the supplied DSN
+    pub fn new(dsn: Option<SentryDsn>) -> Self {
+        Self {
+            dsn,
+            ..Self::default()
+        }
+    }
+
+    /// Create a reporter from the `SENTRY_DSN` secret, if set
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        Self::new(
+            secrets
+                .get("SENTRY_DSN")
+                .filter(|dsn| !dsn.is_empty())
+                .and_then(|dsn| {
+                    dsn.parse::<SentryDsn>()
+                        .map_err(|error| tracing::error!("ignoring SENTRY_DSN secret: {error}"))
+                        .ok()
+                }),
+        )
+    }
+
+    /// Whether the reporter will actually ship events anywhere
+    pub fn is_enabled(&self) -> bool {
+        self.dsn.is_some()
+    }
+
+    /// Report the supplied event (in the background)
+    #[tracing::instrument(skip_all, fields(message = event.message, route = ?event.route))]
+    pub fn report(&self, event: ErrorEvent) {
+        tracing::error!("{event:?}");
+
+        let Some(dsn) = self.dsn.clone() else {
+            return;
+        };
+
+        let request = self
+            .client
+            .post(&dsn.store_url)
+            .header(
+                "x-sentry-auth",
+                format!(
+                    "Sentry sentry_version=7, sentry_key={}, sentry_client={}/{}",
+                    dsn.key,
+                    env!("CARGO_PKG_NAME"),
+                    env!("CARGO_PKG_VERSION"),
+                ),
+            )
+            .json(&event.to_payload());
+
+        tokio::spawn(async move {
+            match request.send().await {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => tracing::warn!("error report rejected: {response:?}"),
+                Err(error) => tracing::warn!("error report failed: {error:?}"),
+            }
+        });
+    }
+}
+
+// </editor-fold desc="// ErrorReporter ...">
+
+/// Read (at most) the first [`MAX_REPORTED_BODY_SIZE`] bytes of the
+/// supplied body, returning them (or `None` if the body is longer)
+/// along with a body replaying them before passing the rest through
+/// unread, so that reporting doesn't bypass the handlers' size limits
+///
+/// A longer body's prefix isn't returned, as a truncated (JSON or
+/// form) body can't be [redacted](RedactionRules::body) reliably.
+async fn _peek_body(mut body: Body) -> (Option<Bytes>, Body) {
+    let mut prefix = BytesMut::new();
+    let mut chunks = Vec::new();
+
+    while prefix.len() <= MAX_REPORTED_BODY_SIZE {
+        match body.data().await {
+            None => {
+                let body = prefix.freeze();
+
+                return (Some(body.clone()), Body::from(body));
+            }
+            Some(Ok(chunk)) => {
+                prefix.extend_from_slice(&chunk);
+                chunks.push(Ok(chunk));
+            }
+            Some(Err(error)) => {
+                tracing::warn!("unable to read request body: {error:?}");
+                chunks.push(Err(error));
+                break;
+            }
+        }
+    }
+
+    (None, Body::wrap_stream(stream::iter(chunks).chain(body)))
+}
+
+/// Middleware that reports handler panics, internal (5xx) errors, and
+/// responses produced from failed dependencies to the state's reporter
+pub async fn report_errors(
+    State(state): State<ShuttleAppState>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    if !state.reporter.is_enabled() {
+        return next.run(request).await;
+    }
+
+    let (parts, body) = request.into_parts();
+    let (reported, body) = _peek_body(body).await;
+
+    let mut context = Request::new(());
+
+    *context.method_mut() = parts.method.clone();
+    *context.uri_mut() = parts.uri.clone();
+    *context.headers_mut() = parts.headers.clone();
+
+    if let Some(path) = parts.extensions.get::<MatchedPath>().cloned() {
+        context.extensions_mut().insert(path);
+    }
+
+    let request = Request::from_parts(parts, body);
+
+    match AssertUnwindSafe(next.run(request)).catch_unwind().await {
+        Ok(response) => {
+            let detail = response.extensions().get::<ErrorDetail>();
+
+            if detail.is_some() || response.status().is_server_error() {
+                let message = detail.map_or_else(
+                    || {
+                        response
+                            .status()
+                            .canonical_reason()
+                            .unwrap_or("internal error")
+                    },
+                    |ErrorDetail(detail)| detail.as_str(),
+                );
+
+                state.reporter.report(ErrorEvent {
+                    status: Some(response.status().as_u16()),
+                    ..ErrorEvent::new(message).with_request(
+                        &context,
+                        reported.as_ref(),
+                        &state.redaction,
+                    )
+                });
+            }
+
+            response
+        }
+        Err(payload) => {
+            state.reporter.report(ErrorEvent {
+                status: Some(StatusCode::INTERNAL_SERVER_ERROR.as_u16()),
+                ..ErrorEvent::from_panic(payload.as_ref()).with_request(
+                    &context,
+                    reported.as_ref(),
+                    &state.redaction,
+                )
+            });
+
+            resume_unwind(payload)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use core::{cmp::PartialEq, fmt::Debug, ops::BitOr, str::FromStr};
+
+    // Third-Party Imports
+    use axum::body::Body;
+    use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use super::{SentryDsn, MAX_REPORTED_BODY_SIZE};
+
+    /// Test that `SentryDsn` correctly derives
+    /// the store endpoint for the supplied DSN
+    #[rstest]
+    #[case::hosted(
+        "https://abc123@o42.ingest.sentry.io/7",
+        Ok(("abc123", "https://o42.ingest.sentry.io/api/7/store/")),
+    )]
+    #[case::self_hosted(
+        "http://abc123@localhost:9000/sentry/7",
+        Ok(("abc123", "http://localhost:9000/sentry/api/7/store/")),
+    )]
+    #[case::missing_key("https://o42.ingest.sentry.io/7", Err(()))]
+    #[case::missing_project("https://abc123@o42.ingest.sentry.io/", Err(()))]
+    #[test_log::test]
+    fn test_sentry_dsn_parsing(#[case] dsn: &str, #[case] expected: Result<(&str, &str), ()>) {
+        let actual = dsn.parse::<SentryDsn>();
+
+        match expected {
+            Ok((key, store_url)) => assert_eq!(
+                SentryDsn {
+                    key: key.to_string(),
+                    store_url: store_url.to_string()
+                },
+                actual.unwrap(),
+            ),
+            Err(()) => assert!(actual.is_err(), "{actual:?}"),
+        }
+    }
+
+    /// Test that `_peek_body` returns only complete (short) bodies,
+    /// and passes every body through unchanged either way
+    #[rstest]
+    #[case::empty(0, true)]
+    #[case::short(MAX_REPORTED_BODY_SIZE, true)]
+    #[case::long(4 * MAX_REPORTED_BODY_SIZE, false)]
+    #[test_log::test(tokio::test)]
+    async fn test_peek_body(#[case] size: usize, #[case] reported: bool) -> anyhow::Result<()> {
+        let content = "x".repeat(size);
+        let chunks = content
+            .as_bytes()
+            .chunks(100)
+            .map(|chunk| Ok::<_, std::io::Error>(chunk.to_vec()))
+            .collect::<Vec<_>>();
+
+        let (prefix, body) =
+            super::_peek_body(Body::wrap_stream(futures::stream::iter(chunks))).await;
+
+        assert_eq!(reported.then_some(content.as_bytes()), prefix.as_deref());
+        assert_eq!(
+            content.as_bytes(),
+            hyper::body::to_bytes(body).await?.as_ref()
+        );
+
+        Ok(())
+    }
+}
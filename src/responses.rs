@@ -0,0 +1,752 @@
+//! ## Structured Handler Responses
+//!
+//! Responses follow one of two [profiles](ResponseProfile). By
+//! default (`v1`), handlers respond with their raw payload, so the
+//! bodies the challenge grader expects are untouched. Clients that
+//! send `Accept: application/json; profile=v2` (or the older
+//! `X-Envelope: true`) instead receive JSON and text payloads
+//! wrapped in a `{data, meta}` envelope carrying the request's id
+//! and timing, and every error as an [`ErrorEnvelope`]. Under `v1`,
+//! ad hoc plain-text (or empty) error bodies, such as those of axum's
+//! extractor rejections, are likewise typed as an [`ErrorEnvelope`],
+//! while JSON error payloads are left as the challenge grader expects.
+//! Browsers (i.e. clients that accept `text/html`) are instead shown
+//! every error as a page rendered from the [error template](ERROR_PAGE_TEMPLATE).
+
+// Standard Library Imports
+use std::time::Instant;
+
+// Third-Party Imports
+use axum::{
+    body::{Bytes, HttpBody},
+    extract::{Json, State},
+    http::{header, response::Parts, HeaderMap, HeaderName, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+// Crate-Level Imports
+use crate::{
+    errors::{AppError, ErrorEnvelope},
+    middleware::request_id_of,
+    state::ShuttleAppState,
+    templating,
+};
+
+/// The (legacy) header with which clients opt
+/// into the [`v2`](ResponseProfile::V2) profile
+pub static ENVELOPE_HEADER: HeaderName = HeaderName::from_static("x-envelope");
+
+/// The `Content-Type` of [`v2`](ResponseProfile::V2) responses
+pub const V2_CONTENT_TYPE: &str = "application/json; profile=v2";
+
+/// The `Content-Type` of [`html`](ResponseProfile::Html) error pages
+pub const HTML_CONTENT_TYPE: &str = "text/html; charset=utf-8";
+
+/// The template [`html`](ResponseProfile::Html) error pages are rendered from
+pub const ERROR_PAGE_TEMPLATE: &str = "errors/error";
+
+/// The result type of the service's structured handlers
+pub type ApiResult<T> = Result<ApiOk<T>, AppError>;
+
+// <editor-fold desc="// ApiOk ...">
+
+/// The success half of an [`ApiResult`]
+#[derive(Clone, Debug)]
+pub struct ApiOk<T>(pub T);
+
+/// The (serialized) payload of an [`ApiOk`] response,
+/// retained so that it may later be [enveloped](Enveloped)
+#[derive(Clone, Debug)]
+struct EnvelopePayload(Value);
+
+impl<T: Serialize> IntoResponse for ApiOk<T> {
+    fn into_response(self) -> Response {
+        match serde_json::to_value(self.0) {
+            Ok(payload) => {
+                let mut response = Json(&payload).into_response();
+                response.extensions_mut().insert(EnvelopePayload(payload));
+                response
+            }
+            Err(error) => {
+                tracing::error!("failed to serialize response payload: {error:?}");
+                ErrorEnvelope::new(StatusCode::INTERNAL_SERVER_ERROR, error).into_response()
+            }
+        }
+    }
+}
+
+// </editor-fold desc="// ApiOk ...">
+
+// <editor-fold desc="// Enveloped ...">
+
+/// Metadata describing the request that produced an [`Enveloped`] payload
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResponseMeta {
+    /// The request's `x-request-id` header (if any)
+    pub request_id: Option<String>,
+    /// The time taken to produce the response (in milliseconds)
+    pub elapsed_ms: f64,
+}
+
+/// An [`ApiOk`] payload wrapped with its [`ResponseMeta`]
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Enveloped {
+    /// The handler's (raw) payload
+    pub data: Value,
+    /// Metadata describing the request
+    pub meta: ResponseMeta,
+}
+
+// </editor-fold desc="// Enveloped ...">
+
+// <editor-fold desc="// ResponseProfile ...">
+
+/// The response schemas a client may negotiate
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ResponseProfile {
+    /// Raw payloads and JSON errors (exactly as the challenge
+    /// grader expects them), with [typed](ErrorEnvelope)
+    /// plain-text errors
+    #[default]
+    V1,
+    /// [Enveloped] payloads and [typed](ErrorEnvelope) errors
+    V2,
+    /// Raw payloads, with errors rendered as (browser-readable)
+    /// pages from the [error template](ERROR_PAGE_TEMPLATE)
+    Html,
+}
+
+impl ResponseProfile {
+    /// Negotiate the profile requested by the supplied headers
+    pub fn of(headers: &HeaderMap) -> Self {
+        let enveloped = headers
+            .get(&ENVELOPE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case("true"));
+
+        if enveloped
+            || headers
+                .get_all(header::ACCEPT)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .any(_accepts_v2)
+        {
+            Self::V2
+        } else if headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(_accepts_html)
+        {
+            Self::Html
+        } else {
+            Self::V1
+        }
+    }
+}
+
+/// Check if the supplied media range is
+/// `application/json` with the `v2` profile
+fn _accepts_v2(range: &str) -> bool {
+    let mut parts = range.split(';').map(str::trim);
+
+    parts
+        .next()
+        .is_some_and(|media| media.eq_ignore_ascii_case("application/json"))
+        && parts.any(|parameter| {
+            parameter.split_once('=').is_some_and(|(name, value)| {
+                name.trim().eq_ignore_ascii_case("profile")
+                    && value.trim().trim_matches('"').eq_ignore_ascii_case("v2")
+            })
+        })
+}
+
+/// Check if the supplied media range is `text/html`
+fn _accepts_html(range: &str) -> bool {
+    range
+        .split(';')
+        .next()
+        .is_some_and(|media| media.trim().eq_ignore_ascii_case("text/html"))
+}
+
+/// Check if the supplied headers describe a body of the supplied media type
+fn _has_media_type(headers: &HeaderMap, media: &str) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case(media))
+}
+
+/// Check if the supplied status is that of a failed response
+fn _is_failure(status: StatusCode) -> bool {
+    status.is_client_error() || status.is_server_error()
+}
+
+/// Type the supplied (failed) response's content as an [`ErrorEnvelope`],
+/// taking a JSON envelope as-is, other JSON as the error's details, and
+/// text as its message (or the status' reason, if there's no text)
+fn _as_error_envelope(status: StatusCode, content: &[u8], is_json: bool) -> ErrorEnvelope {
+    let reason = status.canonical_reason().unwrap_or_default();
+
+    if is_json {
+        if let Ok(envelope) = serde_json::from_slice::<ErrorEnvelope>(content) {
+            return envelope;
+        }
+
+        if let Ok(details) = serde_json::from_slice::<Value>(content) {
+            return ErrorEnvelope::new(status, reason).with_details(Some(details));
+        }
+    }
+
+    let message = String::from_utf8_lossy(content);
+    let message = message.trim();
+
+    ErrorEnvelope::new(status, if message.is_empty() { reason } else { message })
+}
+
+/// Type a failed response's (plain-text, empty, or if `typed_json`, JSON)
+/// body as an [`ErrorEnvelope`], handing back any other response untouched
+async fn _error_envelope_of(
+    response: Response,
+    typed_json: bool,
+    request_id: Option<String>,
+) -> Result<(Parts, ErrorEnvelope), Response> {
+    let (parts, body) = response.into_parts();
+
+    let is_json = typed_json && _has_media_type(&parts.headers, "application/json");
+
+    if !(is_json || _has_media_type(&parts.headers, "text/plain") || body.is_end_stream()) {
+        return Err(Response::from_parts(parts, body));
+    }
+
+    let content = match hyper::body::to_bytes(body).await {
+        Ok(content) => content,
+        Err(error) => {
+            tracing::error!("failed to buffer response body: {error:?}");
+            return Err(ErrorEnvelope::new(StatusCode::INTERNAL_SERVER_ERROR, error)
+                .with_request_id(request_id)
+                .into_response());
+        }
+    };
+
+    let envelope = _as_error_envelope(parts.status, &content, is_json);
+    let request_id = envelope.request_id.clone().or(request_id);
+
+    Ok((parts, envelope.with_request_id(request_id)))
+}
+
+/// Replace a response's body with the supplied (re-written) one
+fn _with_body(mut parts: Parts, content_type: &'static str, body: Response) -> Response {
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+
+    Response::from_parts(parts, body.into_body())
+}
+
+/// Re-type a failed [`v1`](ResponseProfile::V1) response's (plain-text
+/// or empty) body as an [`ErrorEnvelope`], leaving other bodies (e.g.
+/// the JSON payloads the challenge grader expects) untouched
+async fn _into_v1_error(response: Response, request_id: Option<String>) -> Response {
+    match _error_envelope_of(response, false, request_id).await {
+        Ok((parts, envelope)) => {
+            _with_body(parts, "application/json", Json(envelope).into_response())
+        }
+        Err(response) => response,
+    }
+}
+
+/// Render a failed response's (JSON, plain-text, or empty) body as
+/// an [`html`](ResponseProfile::Html) error page, falling back to
+/// its [`ErrorEnvelope`] should the page fail to render
+async fn _into_html_error(
+    state: &ShuttleAppState,
+    response: Response,
+    request_id: Option<String>,
+) -> Response {
+    let (parts, envelope) = match _error_envelope_of(response, true, request_id).await {
+        Ok(typed) => typed,
+        Err(response) => return response,
+    };
+
+    let page = templating::render(
+        &state.templates,
+        &state.template_limits,
+        ERROR_PAGE_TEMPLATE,
+        serde_json::json!({
+            "status": envelope.status,
+            "reason": parts.status.canonical_reason().unwrap_or_default(),
+            "message": envelope.error,
+            "details": envelope.details.as_ref().and_then(|details| serde_json::to_string_pretty(details).ok()),
+            "request_id": envelope.request_id,
+        }),
+    );
+
+    match page {
+        Ok(page) => _with_body(parts, HTML_CONTENT_TYPE, page.into_response()),
+        Err(failure) => {
+            tracing::error!("failed to render error page: {}", failure.error);
+            _with_body(parts, "application/json", Json(envelope).into_response())
+        }
+    }
+}
+
+/// Map a response to the [`v2`](ResponseProfile::V2) profile, wrapping
+/// (JSON or text) payloads in an [`Enveloped`] body and re-typing
+/// every error as an [`ErrorEnvelope`]
+async fn _into_v2(mut response: Response, meta: ResponseMeta) -> Response {
+    let payload = response.extensions_mut().remove::<EnvelopePayload>();
+    let (mut parts, body) = response.into_parts();
+    let failed = _is_failure(parts.status);
+
+    let (is_json, is_text) = (
+        _has_media_type(&parts.headers, "application/json"),
+        _has_media_type(&parts.headers, "text/plain") || body.is_end_stream(),
+    );
+
+    let bodiless = parts.status == StatusCode::NO_CONTENT || !(failed || parts.status.is_success());
+
+    if bodiless || (payload.is_none() && !is_json && !is_text) {
+        return Response::from_parts(parts, body);
+    }
+
+    let content = match payload {
+        Some(_) => Bytes::new(),
+        None => match hyper::body::to_bytes(body).await {
+            Ok(content) => content,
+            Err(error) => {
+                tracing::error!("failed to buffer response body: {error:?}");
+                return ErrorEnvelope::new(StatusCode::INTERNAL_SERVER_ERROR, error)
+                    .with_request_id(meta.request_id)
+                    .into_response();
+            }
+        },
+    };
+
+    let body = if failed {
+        let envelope = _as_error_envelope(parts.status, &content, is_json);
+
+        let request_id = envelope.request_id.clone().or(meta.request_id);
+
+        Json(envelope.with_request_id(request_id)).into_response()
+    } else {
+        let data = match payload {
+            Some(EnvelopePayload(data)) => data,
+            None if is_json => serde_json::from_slice(&content)
+                .unwrap_or_else(|_| Value::from(String::from_utf8_lossy(&content).into_owned())),
+            None => Value::from(String::from_utf8_lossy(&content).into_owned()),
+        };
+
+        Json(Enveloped { data, meta }).into_response()
+    };
+
+    _set_v2_headers(&mut parts);
+
+    Response::from_parts(parts, body.into_body())
+}
+
+/// Describe a [`v2`](ResponseProfile::V2) response's (re-written) body
+fn _set_v2_headers(parts: &mut Parts) {
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(V2_CONTENT_TYPE),
+    );
+}
+
+/// Middleware that maps responses to the [profile](ResponseProfile)
+/// negotiated by the request, so that handlers needn't know about it
+pub async fn envelope_responses<B: Send + 'static>(
+    State(state): State<ShuttleAppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let profile = ResponseProfile::of(request.headers());
+
+    let started = Instant::now();
+    let request_id = request_id_of(request.headers());
+
+    let mut response = next.run(request).await;
+
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept, x-envelope"));
+
+    match profile {
+        ResponseProfile::V1 if _is_failure(response.status()) => {
+            _into_v1_error(response, request_id).await
+        }
+        ResponseProfile::Html if _is_failure(response.status()) => {
+            _into_html_error(&state, response, request_id).await
+        }
+        ResponseProfile::V1 | ResponseProfile::Html => response,
+        ResponseProfile::V2 => {
+            _into_v2(
+                response,
+                ResponseMeta {
+                    request_id,
+                    elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+                },
+            )
+            .await
+        }
+    }
+}
+
+// </editor-fold desc="// ResponseProfile ...">
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Third-Party Imports
+    use axum::{
+        body::Body,
+        http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use serde_json::{json, Value};
+
+    // Crate-Level Imports
+    use super::{Enveloped, ResponseProfile, HTML_CONTENT_TYPE, V2_CONTENT_TYPE};
+    use crate::errors::ErrorEnvelope;
+    use crate::router;
+    use crate::routes;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{state, TestService};
+
+    /// Test that structured handlers respond with their raw
+    /// payload unless the request opts into an envelope
+    #[rstest]
+    #[case::raw(None, false)]
+    #[case::opted_out(Some("false"), false)]
+    #[case::enveloped(Some("true"), true)]
+    #[case::case_insensitive(Some("TRUE"), true)]
+    #[test_log::test(tokio::test)]
+    async fn test_envelope_responses(
+        state: ShuttleAppState,
+        #[case] header: Option<&str>,
+        #[case] enveloped: bool,
+    ) -> anyhow::Result<()> {
+        let mut request =
+            Request::get(routes::counters::counter("envelope")).header("x-request-id", "abc123");
+
+        if let Some(value) = header {
+            request = request.header("x-envelope", value);
+        }
+
+        let response = TestService::from(router(state))
+            .resolve(request.body(Body::empty())?)
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+
+        if enveloped {
+            let envelope: Enveloped = serde_json::from_slice(&body)?;
+
+            assert_eq!(json!(0), envelope.data);
+            assert_eq!(Some(String::from("abc123")), envelope.meta.request_id);
+        } else {
+            assert_eq!(json!(0), serde_json::from_slice::<Value>(&body)?);
+        }
+
+        Ok(())
+    }
+
+    /// Test that error responses are never enveloped
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_errors_are_not_enveloped(state: ShuttleAppState) -> anyhow::Result<()> {
+        let response = TestService::from(router(state))
+            .resolve(
+                Request::get(routes::counters::counter("not%20valid"))
+                    .header("x-envelope", "true")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+        let body: Value =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(json!(422), body["error"]["status"]);
+        assert_eq!(None, body.get("meta"));
+
+        Ok(())
+    }
+
+    /// Test that the response profile is negotiated
+    /// from the `Accept` (or `X-Envelope`) header
+    #[rstest]
+    #[case::none(None, None, ResponseProfile::V1)]
+    #[case::plain_json(Some("application/json"), None, ResponseProfile::V1)]
+    #[case::v1(Some("application/json; profile=v1"), None, ResponseProfile::V1)]
+    #[case::v2(Some("application/json; profile=v2"), None, ResponseProfile::V2)]
+    #[case::quoted(Some(r#"application/json;profile="v2""#), None, ResponseProfile::V2)]
+    #[case::listed(
+        Some("text/plain, application/json; q=0.9; profile=v2"),
+        None,
+        ResponseProfile::V2
+    )]
+    #[case::other_media(Some("text/plain; profile=v2"), None, ResponseProfile::V1)]
+    #[case::legacy_header(None, Some("true"), ResponseProfile::V2)]
+    #[case::html(Some("text/html"), None, ResponseProfile::Html)]
+    #[case::browser(
+        Some("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"),
+        None,
+        ResponseProfile::Html
+    )]
+    #[case::v2_over_html(
+        Some("text/html, application/json; profile=v2"),
+        None,
+        ResponseProfile::V2
+    )]
+    #[test_log::test]
+    fn test_response_profile(
+        #[case] accept: Option<&'static str>,
+        #[case] envelope: Option<&'static str>,
+        #[case] expected: ResponseProfile,
+    ) {
+        let mut headers = HeaderMap::new();
+
+        if let Some(accept) = accept {
+            headers.insert(header::ACCEPT, HeaderValue::from_static(accept));
+        }
+
+        if let Some(envelope) = envelope {
+            headers.insert("x-envelope", HeaderValue::from_static(envelope));
+        }
+
+        assert_eq!(expected, ResponseProfile::of(&headers));
+    }
+
+    /// Test that `v2` responses envelope raw (non-structured)
+    /// payloads, while `v1` responses are untouched
+    #[rstest]
+    #[case::v1(None, json!(1728))]
+    #[case::v2(Some("application/json; profile=v2"), json!({"data": 1728}))]
+    #[test_log::test(tokio::test)]
+    async fn test_v2_raw_payloads(
+        state: ShuttleAppState,
+        #[case] accept: Option<&str>,
+        #[case] expected: Value,
+    ) -> anyhow::Result<()> {
+        let mut request = Request::get(routes::day1::cube(4, 8));
+
+        if let Some(accept) = accept {
+            request = request.header(header::ACCEPT, accept);
+        }
+
+        let response = TestService::from(router(state))
+            .resolve(request.body(Body::empty())?)
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let mut body: Value =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        if let Some(envelope) = body.as_object_mut() {
+            assert!(envelope.remove("meta").is_some());
+        }
+
+        assert_eq!(expected, body);
+
+        Ok(())
+    }
+
+    /// Test that `v2` responses type ad hoc (plain-text) errors
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_v2_typed_errors(state: ShuttleAppState) -> anyhow::Result<()> {
+        let response = TestService::from(router(state))
+            .resolve(
+                Request::get(routes::day21::coords("0123"))
+                    .header(header::ACCEPT, "application/json; profile=v2")
+                    .header("x-request-id", "abc123")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+        assert_eq!(
+            Some(V2_CONTENT_TYPE),
+            response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+        );
+
+        let envelope: ErrorEnvelope =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(422, envelope.status);
+        assert_eq!(Some(String::from("abc123")), envelope.request_id);
+        assert!(!envelope.error.is_empty());
+
+        Ok(())
+    }
+
+    /// Test that ad hoc (plain-text or empty) errors, including
+    /// extractor rejections, are typed under the `v1` profile
+    #[rstest]
+    #[case::malformed_json(
+        Request::post(routes::day4::strength())
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from("[{"))
+            .unwrap(),
+        StatusCode::BAD_REQUEST,
+        "bad_request"
+    )]
+    #[case::invalid_path(
+        Request::get(routes::day18::top_list("many")).body(Body::empty()).unwrap(),
+        StatusCode::BAD_REQUEST,
+        "bad_request"
+    )]
+    #[case::unknown_route(
+        Request::get("/no/such/route").body(Body::empty()).unwrap(),
+        StatusCode::NOT_FOUND,
+        "not_found"
+    )]
+    #[case::deliberate(
+        Request::get(routes::day_minus_1::error()).body(Body::empty()).unwrap(),
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "internal_server_error"
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_v1_typed_errors(
+        state: ShuttleAppState,
+        #[case] request: Request<Body>,
+        #[case] expected_status: StatusCode,
+        #[case] expected_code: &str,
+    ) -> anyhow::Result<()> {
+        let response = TestService::from(router(state)).resolve(request).await?;
+
+        assert_eq!(expected_status, response.status());
+        assert_eq!(
+            Some("application/json"),
+            response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+        );
+
+        let body: Value =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(json!(expected_code), body["error"]["code"]);
+        assert_eq!(json!(expected_status.as_u16()), body["error"]["status"]);
+        assert!(body["error"]["message"]
+            .as_str()
+            .is_some_and(|message| !message.is_empty()));
+
+        Ok(())
+    }
+
+    /// Test that JSON error payloads (which the challenge grader
+    /// checks) are left untouched under the `v1` profile
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_v1_json_errors_untouched(state: ShuttleAppState) -> anyhow::Result<()> {
+        let response = TestService::from(router(state))
+            .resolve(
+                Request::post(routes::day15::nice())
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from(r#"{"input": "abcd"}"#))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::BAD_REQUEST, response.status());
+        assert_eq!(
+            json!({"result": "naughty"}),
+            serde_json::from_slice::<Value>(&hyper::body::to_bytes(response.into_body()).await?)?
+        );
+
+        Ok(())
+    }
+
+    /// Test that browser clients are shown errors (including JSON
+    /// error payloads) as pages rendered from the error template
+    #[rstest]
+    #[case::unknown_route(
+        Request::get("/no/such/route")
+            .header(header::ACCEPT, "text/html")
+            .body(Body::empty())
+            .unwrap(),
+        StatusCode::NOT_FOUND,
+        "404 Not Found"
+    )]
+    #[case::json_payload(
+        Request::post(routes::day15::nice())
+            .header(header::ACCEPT, "text/html")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"input": "abcd"}"#))
+            .unwrap(),
+        StatusCode::BAD_REQUEST,
+        "&quot;result&quot;: &quot;naughty&quot;"
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_html_error_pages(
+        state: ShuttleAppState,
+        #[case] request: Request<Body>,
+        #[case] expected_status: StatusCode,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let response = TestService::from(router(state)).resolve(request).await?;
+
+        assert_eq!(expected_status, response.status());
+        assert_eq!(
+            Some(HTML_CONTENT_TYPE),
+            response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+        );
+
+        let page = String::from_utf8(hyper::body::to_bytes(response.into_body()).await?.to_vec())?;
+
+        assert!(page.contains(expected_content), "{page}");
+
+        Ok(())
+    }
+
+    /// Test that browser clients are still
+    /// sent successful responses as-is
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_html_successes_untouched(state: ShuttleAppState) -> anyhow::Result<()> {
+        let response = TestService::from(router(state))
+            .resolve(
+                Request::get(routes::day_minus_1::root())
+                    .header(header::ACCEPT, "text/html")
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert_ne!(
+            Some(HTML_CONTENT_TYPE),
+            response
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+        );
+
+        Ok(())
+    }
+}
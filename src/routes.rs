@@ -0,0 +1,890 @@
+//! ## Route Paths
+//!
+//! Route patterns (as registered with the project's
+//! `Router`) and builders for concrete request paths
+
+// Standard Library Imports
+use core::fmt::Display;
+
+/// The route pattern for the solutions module registry
+pub const DAYS: &str = "/days";
+
+/// The path for the solutions module registry
+pub fn days() -> &'static str {
+    DAYS
+}
+
+/// The route pattern for the solutions modules' OpenAPI document
+pub const OPENAPI: &str = "/openapi.json";
+
+/// The path for the solutions modules' OpenAPI document
+pub fn openapi() -> &'static str {
+    OPENAPI
+}
+
+/// The route pattern for the deployment's version info
+pub const VERSION: &str = "/version";
+
+/// The path for the deployment's version info
+pub fn version() -> &'static str {
+    VERSION
+}
+
+/// The route pattern for the service's liveness report
+pub const HEALTHZ: &str = "/healthz";
+
+/// The path for the service's liveness report
+pub fn healthz() -> &'static str {
+    HEALTHZ
+}
+
+/// The route pattern for the service's readiness report
+pub const READYZ: &str = "/readyz";
+
+/// The path for the service's readiness report
+pub fn readyz() -> &'static str {
+    READYZ
+}
+
+/// The route pattern for a solutions module's request/response examples
+pub const DAY_EXAMPLES: &str = "/days/:day/examples";
+
+/// The path for a solutions module's request/response examples
+pub fn day_examples(day: impl Display) -> String {
+    format!("/days/{day}/examples")
+}
+
+/// The route pattern for replaying a solutions module's example
+pub const REPLAY_DAY_EXAMPLE: &str = "/days/:day/examples/:name/replay";
+
+/// The path for replaying a solutions module's example
+pub fn replay_day_example(day: impl Display, name: impl Display) -> String {
+    format!("/days/{day}/examples/{name}/replay")
+}
+
+/// The route pattern for self-validating a solutions module
+pub const VALIDATE_DAY: &str = "/validate/:day";
+
+/// The path for self-validating a solutions module
+pub fn validate(day: impl Display) -> String {
+    format!("/validate/{day}")
+}
+
+/// The route pattern for the static asset manifest
+pub const ASSET_MANIFEST: &str = "/assets/manifest.json";
+
+/// The path for the static asset manifest
+pub fn asset_manifest() -> &'static str {
+    ASSET_MANIFEST
+}
+
+/// Day -1 routes
+pub mod day_minus_1 {
+    /// The route pattern for the service's root
+    pub const ROOT: &str = "/";
+
+    /// The path for the service's root
+    pub fn root() -> &'static str {
+        ROOT
+    }
+
+    /// The route pattern for the intentionally failing endpoint
+    pub const ERROR: &str = "/-1/error";
+
+    /// The path for the intentionally failing endpoint
+    pub fn error() -> &'static str {
+        ERROR
+    }
+}
+
+/// Day 1 routes
+pub mod day1 {
+    use core::fmt::Display;
+
+    /// The path for two-packet sled id calculation
+    /// (served by the [`PACKETS`] route's fast path)
+    pub fn cube(num1: impl Display, num2: impl Display) -> String {
+        format!("/1/{num1}/{num2}")
+    }
+
+    /// The route pattern for sled id calculation
+    pub const PACKETS: &str = "/1/*packets";
+
+    /// The path for sled id calculation with the supplied packet ids
+    pub fn packets<Packet: Display>(packets: impl IntoIterator<Item = Packet>) -> String {
+        packets
+            .into_iter()
+            .fold(String::from("/1"), |path, packet| {
+                format!("{path}/{packet}")
+            })
+    }
+}
+
+/// Day 4 routes
+pub mod day4 {
+    /// The route pattern for reindeer strength calculation
+    pub const STRENGTH: &str = "/4/strength";
+
+    /// The path for reindeer strength calculation
+    pub fn strength() -> &'static str {
+        STRENGTH
+    }
+
+    /// The route pattern for reindeer contest summaries
+    pub const CONTEST: &str = "/4/contest";
+
+    /// The path for reindeer contest summaries
+    pub fn contest() -> &'static str {
+        CONTEST
+    }
+
+    /// The route pattern for reindeer contest persistence
+    pub const CONTEST_SAVE: &str = "/4/contest/save";
+
+    /// The path for reindeer contest persistence
+    pub fn contest_save() -> &'static str {
+        CONTEST_SAVE
+    }
+
+    /// The route pattern for the reindeer contest leaderboard
+    pub const LEADERBOARD: &str = "/4/leaderboard";
+
+    /// The path for the reindeer contest leaderboard
+    pub fn leaderboard() -> &'static str {
+        LEADERBOARD
+    }
+}
+
+/// Day 5 routes
+pub mod day5 {
+    /// The route pattern for name list pagination
+    pub const NAMES: &str = "/5";
+
+    /// The path for name list pagination
+    pub fn names() -> &'static str {
+        NAMES
+    }
+}
+
+/// Day 6 routes
+pub mod day6 {
+    /// The route pattern for elf counting
+    pub const ELVES: &str = "/6";
+
+    /// The path for elf counting
+    pub fn elves() -> &'static str {
+        ELVES
+    }
+
+    /// The route pattern for (live) elf counting over a websocket
+    pub const LIVE: &str = "/6/ws";
+
+    /// The path for (live) elf counting over a websocket
+    pub fn live() -> &'static str {
+        LIVE
+    }
+
+    /// The route pattern for (generic) pattern counting
+    pub const COUNT: &str = "/6/count";
+
+    /// The path for (generic) pattern counting
+    pub fn count() -> &'static str {
+        COUNT
+    }
+}
+
+/// Day 7 routes
+pub mod day7 {
+    /// The route pattern for cookie recipe decoding
+    pub const DECODE: &str = "/7/decode";
+
+    /// The path for cookie recipe decoding
+    pub fn decode() -> &'static str {
+        DECODE
+    }
+
+    /// The route pattern for cookie baking
+    pub const BAKE: &str = "/7/bake";
+
+    /// The path for cookie baking
+    pub fn bake() -> &'static str {
+        BAKE
+    }
+
+    /// The route pattern for the session's last decoded recipe
+    pub const LAST: &str = "/7/last";
+
+    /// The path for the session's last decoded recipe
+    pub fn last() -> &'static str {
+        LAST
+    }
+
+    /// The route pattern for ingredient aliases
+    pub const ALIASES: &str = "/7/aliases";
+
+    /// The path for ingredient aliases
+    pub fn aliases() -> &'static str {
+        ALIASES
+    }
+
+    /// The route pattern for a bake's cost
+    pub const COST: &str = "/7/cost";
+
+    /// The path for a bake's cost
+    pub fn cost() -> &'static str {
+        COST
+    }
+
+    /// The route pattern for scaling a recipe
+    pub const SCALE: &str = "/7/scale";
+
+    /// The path for scaling a recipe
+    pub fn scale() -> &'static str {
+        SCALE
+    }
+}
+
+/// Day 8 routes
+pub mod day8 {
+    use core::fmt::Display;
+
+    /// The route pattern for pokemon weight lookup
+    pub const WEIGHT: &str = "/8/weight/:pokedex_id";
+
+    /// The path for pokemon weight lookup
+    pub fn weight(pokedex_id: impl Display) -> String {
+        format!("/8/weight/{pokedex_id}")
+    }
+
+    /// The route pattern for pokemon impact momentum calculation
+    pub const DROP: &str = "/8/drop/:pokedex_id";
+
+    /// The path for pokemon impact momentum calculation
+    pub fn drop(pokedex_id: impl Display) -> String {
+        format!("/8/drop/{pokedex_id}")
+    }
+
+    /// The route for batch pokemon weight lookup
+    pub const WEIGHT_BATCH: &str = "/8/weight/batch";
+
+    /// The path for batch pokemon weight lookup
+    pub fn weight_batch() -> &'static str {
+        WEIGHT_BATCH
+    }
+
+    /// The route pattern for name-based pokemon weight lookup
+    pub const WEIGHT_BY_NAME: &str = "/8/weight/name/:name";
+
+    /// The path for name-based pokemon weight lookup
+    pub fn weight_by_name(name: impl Display) -> String {
+        format!("/8/weight/name/{name}")
+    }
+
+    /// The route pattern for name-based pokemon impact momentum calculation
+    pub const DROP_BY_NAME: &str = "/8/drop/name/:name";
+
+    /// The path for name-based pokemon impact momentum calculation
+    pub fn drop_by_name(name: impl Display) -> String {
+        format!("/8/drop/name/{name}")
+    }
+
+    /// The route for the pokedex cache's status
+    pub const CACHE_STATUS: &str = "/8/cache/status";
+
+    /// The path for the pokedex cache's status
+    pub fn cache_status() -> &'static str {
+        CACHE_STATUS
+    }
+}
+
+/// Day 11 routes
+pub mod day11 {
+    use core::fmt::Display;
+
+    /// The route pattern for static asset serving
+    pub const ASSETS: &str = "/11/assets/:asset";
+
+    /// The path for static asset serving
+    pub fn assets(asset: impl Display) -> String {
+        format!("/11/assets/{asset}")
+    }
+
+    /// The route pattern for static asset thumbnails
+    pub const THUMBNAIL: &str = "/11/assets/:asset/thumb";
+
+    /// The path for static asset thumbnails
+    pub fn thumbnail(asset: impl Display) -> String {
+        format!("/11/assets/{asset}/thumb")
+    }
+
+    /// The route pattern for the static asset listing
+    pub const LISTING: &str = "/11/assets/";
+
+    /// The path for the static asset listing
+    pub fn listing() -> &'static str {
+        LISTING
+    }
+
+    /// The route pattern for magical red pixel counting
+    pub const RED_PIXELS: &str = "/11/red_pixels";
+
+    /// The path for magical red pixel counting
+    pub fn red_pixels() -> &'static str {
+        RED_PIXELS
+    }
+
+    /// The route pattern for image format conversion
+    pub const CONVERT: &str = "/11/convert";
+
+    /// The path for image format conversion
+    pub fn convert() -> &'static str {
+        CONVERT
+    }
+}
+
+/// Day 12 routes
+pub mod day12 {
+    use core::fmt::Display;
+
+    /// The route pattern for packet timestamp storage
+    pub const SAVE: &str = "/12/save/:packet_it";
+
+    /// The path for packet timestamp storage
+    pub fn save(packet_id: impl Display) -> String {
+        format!("/12/save/{packet_id}")
+    }
+
+    /// The route pattern for packet timestamp retrieval
+    pub const LOAD: &str = "/12/load/:packet_it";
+
+    /// The path for packet timestamp retrieval
+    pub fn load(packet_id: impl Display) -> String {
+        format!("/12/load/{packet_id}")
+    }
+
+    /// The route pattern for ULID to UUID conversion
+    pub const ULIDS: &str = "/12/ulids";
+
+    /// The path for ULID to UUID conversion
+    pub fn ulids() -> &'static str {
+        ULIDS
+    }
+
+    /// The route pattern for ULID analysis
+    pub const ULIDS_WEEKDAY: &str = "/12/ulids/:weekday";
+
+    /// The path for ULID analysis
+    pub fn ulids_weekday(weekday: impl Display) -> String {
+        format!("/12/ulids/{weekday}")
+    }
+}
+
+/// Day 13 routes
+pub mod day13 {
+    /// The route pattern for the simple SQL select
+    pub const SQL: &str = "/13/sql";
+
+    /// The path for the simple SQL select
+    pub fn sql() -> &'static str {
+        SQL
+    }
+
+    /// The route pattern for schema resetting
+    pub const RESET: &str = "/13/reset";
+
+    /// The path for schema resetting
+    pub fn reset() -> &'static str {
+        RESET
+    }
+
+    /// The route pattern for order creation
+    pub const ORDERS: &str = "/13/orders";
+
+    /// The path for order creation
+    pub fn orders() -> &'static str {
+        ORDERS
+    }
+
+    /// The route pattern for order totals
+    pub const ORDERS_TOTAL: &str = "/13/orders/total";
+
+    /// The path for order totals
+    pub fn orders_total() -> &'static str {
+        ORDERS_TOTAL
+    }
+
+    /// The route pattern for the most popular gift
+    pub const ORDERS_POPULAR: &str = "/13/orders/popular";
+
+    /// The path for the most popular gift
+    pub fn orders_popular() -> &'static str {
+        ORDERS_POPULAR
+    }
+
+    /// The route pattern for recently recorded orders
+    pub const ORDERS_RECENT: &str = "/13/orders/recent";
+
+    /// The path for recently recorded orders
+    pub fn orders_recent() -> &'static str {
+        ORDERS_RECENT
+    }
+}
+
+/// Day 14 routes
+pub mod day14 {
+    /// The route pattern for unsafe HTML rendering
+    pub const UNSAFE: &str = "/14/unsafe";
+
+    /// The path for unsafe HTML rendering
+    pub fn unsafe_html() -> &'static str {
+        UNSAFE
+    }
+
+    /// The route pattern for safe HTML rendering
+    pub const SAFE: &str = "/14/safe";
+
+    /// The path for safe HTML rendering
+    pub fn safe_html() -> &'static str {
+        SAFE
+    }
+
+    /// The route pattern for the template helper listing
+    pub const HELPERS: &str = "/14/helpers";
+
+    /// The path for the template helper listing
+    pub fn helpers() -> &'static str {
+        HELPERS
+    }
+}
+
+/// Day 15 routes
+pub mod day15 {
+    /// The route pattern for naughty-or-nice assessment
+    pub const NICE: &str = "/15/nice";
+
+    /// The path for naughty-or-nice assessment
+    pub fn nice() -> &'static str {
+        NICE
+    }
+
+    /// The route pattern for the password game
+    pub const GAME: &str = "/15/game";
+
+    /// The path for the password game
+    pub fn game() -> &'static str {
+        GAME
+    }
+}
+
+/// Day 18 routes
+pub mod day18 {
+    use core::fmt::Display;
+
+    /// The route pattern for schema resetting
+    pub const RESET: &str = "/18/reset";
+
+    /// The path for schema resetting
+    pub fn reset() -> &'static str {
+        RESET
+    }
+
+    /// The route pattern for order creation
+    pub const ORDERS: &str = "/18/orders";
+
+    /// The path for order creation
+    pub fn orders() -> &'static str {
+        ORDERS
+    }
+
+    /// The route pattern for region creation
+    pub const REGIONS: &str = "/18/regions";
+
+    /// The path for region creation
+    pub fn regions() -> &'static str {
+        REGIONS
+    }
+
+    /// The route pattern for order totals by region
+    pub const REGIONS_TOTAL: &str = "/18/regions/total";
+
+    /// The path for order totals by region
+    pub fn regions_total() -> &'static str {
+        REGIONS_TOTAL
+    }
+
+    /// The route pattern for the top gifts by region
+    pub const TOP_LIST: &str = "/18/regions/top_list/:number";
+
+    /// The path for the top gifts by region
+    pub fn top_list(number: impl Display) -> String {
+        format!("/18/regions/top_list/{number}")
+    }
+}
+
+/// Day 19 routes
+pub mod day19 {
+    use core::fmt::Display;
+
+    /// The route pattern for socket ping pong
+    pub const PING: &str = "/19/ws/ping";
+
+    /// The path for socket ping pong
+    pub fn ping() -> &'static str {
+        PING
+    }
+
+    /// The route pattern for chat view counter resetting
+    pub const RESET: &str = "/19/reset";
+
+    /// The path for chat view counter resetting
+    pub fn reset() -> &'static str {
+        RESET
+    }
+
+    /// The route pattern for the chat view counter
+    pub const VIEWS: &str = "/19/views";
+
+    /// The path for the chat view counter
+    pub fn views() -> &'static str {
+        VIEWS
+    }
+
+    /// The route pattern for long-polling the chat view counter
+    pub const VIEWS_WAIT: &str = "/19/views/wait";
+
+    /// The path for long-polling the chat view counter
+    pub fn views_wait() -> &'static str {
+        VIEWS_WAIT
+    }
+
+    /// The route pattern for a chat room's recent messages
+    pub const ROOM_MESSAGES: &str = "/19/rooms/:room/messages";
+
+    /// The path for a chat room's recent messages
+    pub fn room_messages(room: impl Display) -> String {
+        format!("/19/rooms/{room}/messages")
+    }
+
+    /// The route pattern for a chat room's transcript
+    pub const ROOM_TRANSCRIPT: &str = "/19/rooms/:room/transcript";
+
+    /// The path for a chat room's transcript
+    pub fn room_transcript(room: impl Display) -> String {
+        format!("/19/rooms/{room}/transcript")
+    }
+
+    /// The route pattern for a chat room's connected users
+    pub const ROOM_PRESENCE: &str = "/19/rooms/:room/presence";
+
+    /// The path for a chat room's connected users
+    pub fn room_presence(room: impl Display) -> String {
+        format!("/19/rooms/{room}/presence")
+    }
+
+    /// The route pattern for chat room connections
+    pub const ROOM: &str = "/19/ws/room/:room/user/:user";
+
+    /// The path for chat room connections
+    pub fn room(room: impl Display, user: impl Display) -> String {
+        format!("/19/ws/room/{room}/user/{user}")
+    }
+}
+
+/// Day 20 routes
+pub mod day20 {
+    use core::fmt::Display;
+
+    /// The route pattern for archived file counting
+    pub const ARCHIVE_FILES: &str = "/20/archive_files";
+
+    /// The path for archived file counting
+    pub fn archive_files() -> &'static str {
+        ARCHIVE_FILES
+    }
+
+    /// The route pattern for archived file sizing
+    pub const ARCHIVE_FILES_SIZE: &str = "/20/archive_files_size";
+
+    /// The path for archived file sizing
+    pub fn archive_files_size() -> &'static str {
+        ARCHIVE_FILES_SIZE
+    }
+
+    /// The route pattern for the git blame cookie hunt
+    pub const COOKIE: &str = "/20/cookie";
+
+    /// The path for the git blame cookie hunt
+    pub fn cookie() -> &'static str {
+        COOKIE
+    }
+
+    /// The route pattern for (asynchronous) job status reports
+    pub const JOB: &str = "/20/jobs/:id";
+
+    /// The path for (asynchronous) job status reports
+    pub fn job(id: impl Display) -> String {
+        format!("/20/jobs/{id}")
+    }
+}
+
+/// Day 21 routes
+pub mod day21 {
+    use core::fmt::Display;
+
+    /// The route pattern for S2 cell center resolution
+    pub const COORDS: &str = "/21/coords/:cell_id";
+
+    /// The path for S2 cell center resolution
+    pub fn coords(cell_id: impl Display) -> String {
+        format!("/21/coords/{cell_id}")
+    }
+
+    /// The path for S2 cell center resolution as GeoJSON
+    pub fn coords_geojson(cell_id: impl Display) -> String {
+        format!("/21/coords/{cell_id}?format=geojson")
+    }
+
+    /// The route pattern for S2 cell country resolution
+    pub const COUNTRY: &str = "/21/country/:cell_id";
+
+    /// The path for S2 cell country resolution
+    pub fn country(cell_id: impl Display) -> String {
+        format!("/21/country/{cell_id}")
+    }
+
+    /// The route pattern for the geocoder's usage quota
+    pub const QUOTA: &str = "/21/quota";
+
+    /// The path for the geocoder's usage quota
+    pub fn quota() -> &'static str {
+        QUOTA
+    }
+}
+
+/// Day 22 routes
+pub mod day22 {
+    /// The route pattern for lonely integer location
+    pub const INTEGERS: &str = "/22/integers";
+
+    /// The path for lonely integer location
+    pub fn integers() -> &'static str {
+        INTEGERS
+    }
+
+    /// The route pattern for streamed integer dump verification
+    pub const INTEGERS_VERIFY: &str = "/22/integers/verify";
+
+    /// The path for streamed integer dump verification
+    pub fn integers_verify() -> &'static str {
+        INTEGERS_VERIFY
+    }
+
+    /// The route pattern for star chart analysis
+    pub const ROCKET: &str = "/22/rocket";
+
+    /// The path for star chart analysis
+    pub fn rocket() -> &'static str {
+        ROCKET
+    }
+
+    /// The route pattern for star chart paths as GeoJSON
+    pub const ROCKET_GEOJSON: &str = "/22/rocket/geojson";
+
+    /// The path for star chart paths as GeoJSON
+    pub fn rocket_geojson() -> &'static str {
+        ROCKET_GEOJSON
+    }
+}
+
+/// Named counter routes
+pub mod counters {
+    use core::fmt::Display;
+
+    /// The route pattern for a named counter's value
+    pub const COUNTER: &str = "/counters/:name";
+
+    /// The path for a named counter's value
+    pub fn counter(name: impl Display) -> String {
+        format!("/counters/{name}")
+    }
+
+    /// The route pattern for incrementing a named counter
+    pub const INCR: &str = "/counters/:name/incr";
+
+    /// The path for incrementing a named counter
+    pub fn incr(name: impl Display) -> String {
+        format!("/counters/{name}/incr")
+    }
+
+    /// The route pattern for zeroing a named counter
+    pub const RESET: &str = "/counters/:name/reset";
+
+    /// The path for zeroing a named counter
+    pub fn reset(name: impl Display) -> String {
+        format!("/counters/{name}/reset")
+    }
+}
+
+/// Debugging routes
+pub mod debug {
+    use core::fmt::Display;
+
+    /// The route pattern for echoing a request to the root path
+    pub const ECHO_ROOT: &str = "/debug/echo";
+
+    /// The route pattern for echoing a request to any other path
+    pub const ECHO: &str = "/debug/echo/*path";
+
+    /// The path for echoing a request to the supplied (absolute) path
+    pub fn echo(path: impl Display) -> String {
+        format!("{ECHO_ROOT}{path}")
+    }
+}
+
+/// Administrative routes
+pub mod admin {
+    use core::fmt::Display;
+
+    /// The route pattern for service metrics
+    pub const METRICS: &str = "/admin/metrics";
+
+    /// The path for service metrics
+    pub fn metrics() -> &'static str {
+        METRICS
+    }
+
+    /// The route pattern for the process and runtime report
+    pub const RUNTIME: &str = "/admin/runtime";
+
+    /// The path for the process and runtime report
+    pub fn runtime() -> &'static str {
+        RUNTIME
+    }
+
+    /// The route pattern for the state initialization report
+    pub const INIT: &str = "/admin/init";
+
+    /// The path for the state initialization report
+    pub fn init() -> &'static str {
+        INIT
+    }
+
+    /// The route pattern for the scheduled jobs report
+    pub const SCHEDULE: &str = "/admin/schedule";
+
+    /// The path for the scheduled jobs report
+    pub fn schedule() -> &'static str {
+        SCHEDULE
+    }
+
+    /// The route pattern for tracing settings
+    pub const TRACING: &str = "/admin/tracing";
+
+    /// The path for tracing settings
+    pub fn tracing() -> &'static str {
+        TRACING
+    }
+
+    /// The route pattern for the database overview
+    pub const DB_OVERVIEW: &str = "/admin/db/overview";
+
+    /// The path for the database overview
+    pub fn db_overview() -> &'static str {
+        DB_OVERVIEW
+    }
+
+    /// The route pattern for ad-hoc SQL queries
+    pub const SQL: &str = "/admin/sql";
+
+    /// The path for ad-hoc SQL queries
+    pub fn sql() -> &'static str {
+        SQL
+    }
+
+    /// The route pattern for address bans
+    pub const BANS: &str = "/admin/bans";
+
+    /// The path for address bans
+    pub fn bans() -> &'static str {
+        BANS
+    }
+
+    /// The route pattern for a specific address ban
+    pub const BAN: &str = "/admin/bans/:ip";
+
+    /// The path for a specific address ban
+    pub fn ban(ip: impl Display) -> String {
+        format!("/admin/bans/{ip}")
+    }
+
+    /// The route pattern for a chat room's protection
+    pub const ROOM_PROTECTION: &str = "/admin/rooms/:room/protection";
+
+    /// The path for a chat room's protection
+    pub fn room_protection(room: impl Display) -> String {
+        format!("/admin/rooms/{room}/protection")
+    }
+
+    /// The route pattern for issuing a chat room's access tokens
+    pub const ROOM_TOKEN: &str = "/admin/rooms/:room/token";
+
+    /// The path for issuing a chat room's access tokens
+    pub fn room_token(room: impl Display) -> String {
+        format!("/admin/rooms/{room}/token")
+    }
+
+    /// The route pattern for request recordings
+    pub const RECORDINGS: &str = "/admin/recordings";
+
+    /// The path for request recordings
+    pub fn recordings() -> &'static str {
+        RECORDINGS
+    }
+
+    /// The route pattern for a specific request recording
+    pub const RECORDING: &str = "/admin/recordings/:id";
+
+    /// The path for a specific request recording
+    pub fn recording(id: impl Display) -> String {
+        format!("/admin/recordings/{id}")
+    }
+
+    /// The route pattern for replaying a request recording
+    pub const REPLAY_RECORDING: &str = "/admin/recordings/:id/replay";
+
+    /// The path for replaying a request recording
+    pub fn replay_recording(id: impl Display) -> String {
+        format!("/admin/recordings/{id}/replay")
+    }
+}
+
+/// Grafana ("simple JSON") datasource routes
+pub mod datasource {
+    /// The route pattern for the datasource's connection test
+    pub const ROOT: &str = "/metrics";
+
+    /// The route pattern for the datasource's connection
+    /// test (as Grafana requests it, with a trailing slash)
+    pub const ROOT_SLASH: &str = "/metrics/";
+
+    /// The path for the datasource's connection test
+    pub fn root() -> &'static str {
+        ROOT
+    }
+
+    /// The route pattern for listing chartable targets
+    pub const SEARCH: &str = "/metrics/search";
+
+    /// The path for listing chartable targets
+    pub fn search() -> &'static str {
+        SEARCH
+    }
+
+    /// The route pattern for charting targets
+    pub const QUERY: &str = "/metrics/query";
+
+    /// The path for charting targets
+    pub fn query() -> &'static str {
+        QUERY
+    }
+}
@@ -0,0 +1,438 @@
+//! ## Scheduled Jobs
+//!
+//! Recurring maintenance jobs, run on (configurable) cron schedules
+
+// Standard Library Imports
+use core::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+// Third-Party Imports
+use axum::extract::{Json, State};
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use futures::future::{BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use shuttle_secrets::SecretStore;
+
+// Crate-Level Imports
+use crate::{outbound::Provider, state::ShuttleAppState};
+
+/// The value of a job's schedule secret that disables it
+const DISABLED: &str = "off";
+
+/// The outcome of a single run of a job: a
+/// summary of what it did, or why it failed
+type JobResult = Result<String, String>;
+
+// <editor-fold desc="// JobDefinition ...">
+
+/// A recurring job the [`Scheduler`] knows how to run
+#[derive(Debug)]
+pub struct JobDefinition {
+    /// the job's name (in reports and secrets)
+    pub name: &'static str,
+    /// the cron expression on which the job runs
+    /// (with a leading seconds field) unless
+    /// overridden by its schedule secret
+    pub default_schedule: &'static str,
+    /// the job itself
+    pub task: fn(ShuttleAppState) -> BoxFuture<'static, JobResult>,
+}
+
+impl JobDefinition {
+    /// The secret that overrides the job's schedule,
+    /// e.g. `SCHEDULE_COUNTER_FLUSH` for `counter-flush`
+    pub fn secret(&self) -> String {
+        format!("SCHEDULE_{}", self.name.to_uppercase().replace('-', "_"))
+    }
+}
+
+/// Every job the [`Scheduler`] knows how to run
+pub static JOBS: [JobDefinition; 8] = [
+    JobDefinition {
+        name: "counter-flush",
+        default_schedule: "*/5 * * * * *",
+        task: |state| {
+            async move {
+                state
+                    .counters
+                    .flush()
+                    .map(|flushed| format!("persisted {flushed} counter(s)"))
+                    .map_err(|error| format!("{error:?}"))
+            }
+            .boxed()
+        },
+    },
+    JobDefinition {
+        name: "outbox-dispatch",
+        default_schedule: "*/5 * * * * *",
+        task: |state| {
+            async move {
+                state
+                    .outbox
+                    .dispatch(&state.db)
+                    .await
+                    .map(|delivered| format!("delivered {delivered} event(s)"))
+                    .map_err(|error| format!("{error}"))
+            }
+            .boxed()
+        },
+    },
+    JobDefinition {
+        name: "persistence-compaction",
+        default_schedule: "0 0 * * * *",
+        task: |state| {
+            async move {
+                state
+                    .counters
+                    .compact()
+                    .map(|removed| format!("removed {removed} zeroed counter(s)"))
+                    .map_err(|error| format!("{error:?}"))
+            }
+            .boxed()
+        },
+    },
+    JobDefinition {
+        name: "persistence-rotation",
+        default_schedule: "0 30 * * * *",
+        task: |state| {
+            async move {
+                state
+                    .persistence
+                    .rotate()
+                    .map(|resealed| format!("re-sealed {resealed} value(s)"))
+                    .map_err(|error| format!("{error}"))
+            }
+            .boxed()
+        },
+    },
+    JobDefinition {
+        name: "star-chart-eviction",
+        default_schedule: "0 */15 * * * *",
+        task: |state| {
+            async move { Ok(format!("evicted {} analyses", state.charts.clear())) }.boxed()
+        },
+    },
+    JobDefinition {
+        name: "metrics-sampling",
+        default_schedule: "*/10 * * * * *",
+        task: |state| {
+            async move {
+                Ok(format!(
+                    "sampled {} metric(s)",
+                    state.history.sample(&state)
+                ))
+            }
+            .boxed()
+        },
+    },
+    JobDefinition {
+        name: "abuse-pruning",
+        default_schedule: "0 * * * * *",
+        task: |state| {
+            async move { Ok(format!("forgot {} address(es)", state.abuse.prune())) }.boxed()
+        },
+    },
+    JobDefinition {
+        name: "upstream-preflight",
+        default_schedule: "0 * * * * *",
+        task: |state| {
+            async move {
+                let reached = state.outbound.preflight(&state.metrics).await;
+
+                Ok(format!(
+                    "reached {reached} of {} upstream provider(s)",
+                    Provider::ALL.len()
+                ))
+            }
+            .boxed()
+        },
+    },
+];
+
+// </editor-fold desc="// JobDefinition ...">
+
+// <editor-fold desc="// ScheduledJob ...">
+
+/// The outcome of a job's most recent run
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JobOutcome {
+    /// when the run started
+    pub started: DateTime<Utc>,
+    /// how long the run took (in milliseconds)
+    pub elapsed_ms: u64,
+    /// whether the run succeeded
+    pub succeeded: bool,
+    /// a summary of what the run did (or why it failed)
+    pub message: String,
+}
+
+/// A job's (cumulative) run history
+#[derive(Debug, Default)]
+struct JobHistory {
+    /// the number of completed runs
+    runs: u64,
+    /// the number of failed runs
+    failures: u64,
+    /// the most recent run's outcome
+    last: Option<JobOutcome>,
+}
+
+/// A [`JobDefinition`] and the schedule on which it runs
+#[derive(Debug)]
+pub struct ScheduledJob {
+    /// the job being scheduled
+    definition: &'static JobDefinition,
+    /// the job's schedule (if it isn't disabled)
+    schedule: Option<Schedule>,
+    /// the job's run history
+    history: Mutex<JobHistory>,
+}
+
+impl ScheduledJob {
+    /// Schedule the supplied job per its schedule
+    /// secret (if set), or its default schedule
+    fn from_secrets(secrets: &SecretStore, definition: &'static JobDefinition) -> Self {
+        let key = definition.secret();
+        let configured = secrets.get(&key).and_then(|expression| {
+            if expression.trim().eq_ignore_ascii_case(DISABLED) {
+                return Some(None);
+            }
+
+            Schedule::from_str(expression.trim())
+                .map_err(|error| tracing::error!("ignoring {key} secret: {error}"))
+                .ok()
+                .map(Some)
+        });
+
+        Self {
+            definition,
+            schedule: configured
+                .unwrap_or_else(|| Schedule::from_str(definition.default_schedule).ok()),
+            history: Mutex::default(),
+        }
+    }
+
+    /// When the job will next run (if ever)
+    pub fn next_run(&self) -> Option<DateTime<Utc>> {
+        self.schedule.as_ref()?.upcoming(Utc).next()
+    }
+
+    /// Run the job once, recording its outcome
+    pub async fn run(&self, state: ShuttleAppState) -> JobOutcome {
+        let started = Utc::now();
+        let result = (self.definition.task)(state).await;
+        let elapsed_ms = (Utc::now() - started).num_milliseconds().max(0) as u64;
+
+        let outcome = JobOutcome {
+            started,
+            elapsed_ms,
+            succeeded: result.is_ok(),
+            message: result.unwrap_or_else(|error| error),
+        };
+
+        if outcome.succeeded {
+            tracing::debug!(job = self.definition.name, "{}", outcome.message);
+        } else {
+            tracing::error!(
+                job = self.definition.name,
+                "job failed: {}",
+                outcome.message
+            );
+        }
+
+        let mut history = self.history.lock().unwrap();
+
+        history.runs += 1;
+        history.failures += u64::from(!outcome.succeeded);
+        history.last = Some(outcome.clone());
+
+        outcome
+    }
+
+    /// Report the job's schedule and run history
+    pub fn report(&self) -> JobReport {
+        let history = self.history.lock().unwrap();
+
+        JobReport {
+            name: self.definition.name.to_string(),
+            schedule: self
+                .schedule
+                .as_ref()
+                .map_or_else(|| DISABLED.to_string(), ToString::to_string),
+            next_run: self.next_run(),
+            runs: history.runs,
+            failures: history.failures,
+            last_run: history.last.clone(),
+        }
+    }
+}
+
+/// A scheduled job's schedule and run history
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JobReport {
+    /// the job's name
+    pub name: String,
+    /// the job's cron expression (or `off`)
+    pub schedule: String,
+    /// when the job will next run (if ever)
+    pub next_run: Option<DateTime<Utc>>,
+    /// the number of completed runs
+    pub runs: u64,
+    /// the number of failed runs
+    pub failures: u64,
+    /// the most recent run's outcome
+    pub last_run: Option<JobOutcome>,
+}
+
+// </editor-fold desc="// ScheduledJob ...">
+
+// <editor-fold desc="// Scheduler ...">
+
+/// Runs every [known job](JOBS) on its schedule
+#[derive(Debug)]
+pub struct Scheduler {
+    /// the scheduled jobs
+    jobs: Vec<Arc<ScheduledJob>>,
+}
+
+impl Scheduler {
+    /// Schedule every [known job](JOBS), reading each job's
+    /// schedule from its schedule secret (if set)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        Self {
+            jobs: JOBS
+                .iter()
+                .map(|definition| Arc::new(ScheduledJob::from_secrets(secrets, definition)))
+                .collect(),
+        }
+    }
+
+    /// Get the named job (if any)
+    pub fn job(&self, name: &str) -> Option<&Arc<ScheduledJob>> {
+        self.jobs.iter().find(|job| job.definition.name == name)
+    }
+
+    /// Run every enabled job on its schedule (forever)
+    pub fn start(&self, state: &ShuttleAppState) {
+        for job in self.jobs.iter().filter(|job| job.schedule.is_some()) {
+            let (job, state) = (job.clone(), state.clone());
+
+            tokio::spawn(async move {
+                while let Some(next) = job.next_run() {
+                    let delay = (next - Utc::now()).to_std().unwrap_or_default();
+
+                    tokio::time::sleep(delay).await;
+                    job.run(state.clone()).await;
+                }
+            });
+        }
+    }
+
+    /// Report every job's schedule and run history
+    pub fn report(&self) -> Vec<JobReport> {
+        self.jobs.iter().map(|job| job.report()).collect()
+    }
+}
+
+// </editor-fold desc="// Scheduler ...">
+
+/// List every scheduled job's next run time and last result
+#[tracing::instrument(ret, skip(state))]
+pub async fn get_schedule(State(state): State<ShuttleAppState>) -> Json<Vec<JobReport>> {
+    Json(state.scheduler.report())
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::{collections::BTreeMap, sync::Arc};
+
+    // Third-Party Imports
+    use axum::{
+        body::Body,
+        http::{Method, StatusCode},
+    };
+    use pretty_assertions::{assert_eq, assert_str_eq};
+    use rstest::rstest;
+    use shuttle_secrets::SecretStore;
+
+    // Crate-Level Imports
+    use super::{JobReport, Scheduler, JOBS};
+    use crate::router;
+    use crate::routes;
+    use crate::solutions::day_22::ChartFormat;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{admin_request, state, TestService};
+
+    /// Test that job schedules are read from their
+    /// secrets, ignoring invalid cron expressions
+    #[rstest]
+    #[case::default(None, Some("0 */15 * * * *"))]
+    #[case::overridden(Some("0 0 3 * * *"), Some("0 0 3 * * *"))]
+    #[case::invalid(Some("every tuesday"), Some("0 */15 * * * *"))]
+    #[case::disabled(Some("OFF"), None)]
+    #[test_log::test]
+    fn test_job_schedules(#[case] secret: Option<&str>, #[case] expected: Option<&str>) {
+        let secrets = SecretStore::new(BTreeMap::from_iter(secret.map(|value| {
+            (
+                "SCHEDULE_STAR_CHART_EVICTION".to_string(),
+                value.to_string().into(),
+            )
+        })));
+        let scheduler = Scheduler::from_secrets(&secrets);
+        let report = scheduler.job("star-chart-eviction").unwrap().report();
+
+        assert_eq!(expected.unwrap_or("off"), report.schedule);
+        assert_eq!(expected.is_some(), report.next_run.is_some());
+        assert_eq!(JOBS.len(), scheduler.report().len());
+    }
+
+    /// Test that running a job records its outcome,
+    /// which `get_schedule` then reports
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_get_schedule(state: ShuttleAppState) -> anyhow::Result<()> {
+        state
+            .charts
+            .analyze("2\n0 0 0\n1 1 1\n1\n0 1\n", ChartFormat::Text, true)
+            .unwrap();
+
+        let outcome = state
+            .scheduler
+            .job("star-chart-eviction")
+            .unwrap()
+            .run(state.clone())
+            .await;
+
+        assert!(outcome.succeeded);
+        assert_str_eq!("evicted 1 analyses", outcome.message);
+
+        let response = TestService::from(router(state))
+            .resolve(admin_request(Method::GET, routes::admin::schedule()).body(Body::empty())?)
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let reports: Vec<JobReport> =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+        let eviction = reports
+            .iter()
+            .find(|report| report.name == "star-chart-eviction")
+            .unwrap();
+
+        assert_eq!(1, eviction.runs);
+        assert_eq!(0, eviction.failures);
+        assert_str_eq!(
+            "evicted 1 analyses",
+            eviction.last_run.as_ref().unwrap().message
+        );
+        assert!(reports.iter().all(|report| report.next_run.is_some()));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+//! ## Sessions
+//!
+//! Signed session cookies, and per-session scratch space
+//! (kept in the service's [shared cache](crate::shared))
+
+// Standard Library Imports
+use core::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+use std::sync::Arc;
+
+// Third-Party Imports
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, State},
+    http::{header, request::Parts, HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use b64::{engine::general_purpose::URL_SAFE_NO_PAD as base64, Engine};
+use hmac::{Hmac, Mac};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::Sha256;
+use shuttle_secrets::SecretStore;
+use uuid::Uuid;
+
+// Crate-Level Imports
+use crate::{shared::SharedCache, state::ShuttleAppState};
+
+/// The name of the session cookie
+pub const SESSION_COOKIE: &str = "cch23_session";
+
+/// The header with which a client without a session cookie opts
+/// in to starting a session (with the value [`SESSION_OPT_IN`])
+pub const SESSION_OPT_IN_HEADER: &str = "x-cch23-session";
+
+/// The [`SESSION_OPT_IN_HEADER`] value starting a new session
+pub const SESSION_OPT_IN: &str = "new";
+
+/// How long an idle session's data is retained
+const SESSION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The minimum length of the session signing key
+const MIN_KEY_LENGTH: usize = 32;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// <editor-fold desc="// SessionStore ...">
+
+/// Signs session cookies, and keeps each
+/// session's data in the shared cache
+#[derive(Clone)]
+pub struct SessionStore {
+    /// the key session cookies are signed with
+    key: Arc<[u8]>,
+    /// where session data is kept
+    cache: Arc<dyn SharedCache>,
+}
+
+impl core::fmt::Debug for SessionStore {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SessionStore")
+            .field("cache", &self.cache)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionStore {
+    /// Create a store signing cookies with the supplied
+    /// key and keeping data in the supplied cache
+    pub fn new(key: &[u8], cache: Arc<dyn SharedCache>) -> Self {
+        Self {
+            key: Arc::from(key),
+            cache,
+        }
+    }
+
+    /// Create a store signing cookies with the `SESSION_KEY` secret
+    /// (if set), or a random per-process key otherwise (in which
+    /// case sessions don't survive restarts, or span instances)
+    pub fn from_secrets(secrets: &SecretStore, cache: Arc<dyn SharedCache>) -> Self {
+        let key = secrets.get("SESSION_KEY").and_then(|key| {
+            if key.len() < MIN_KEY_LENGTH {
+                tracing::error!(
+                    "ignoring SESSION_KEY secret: must be at least {MIN_KEY_LENGTH} bytes long"
+                );
+                return None;
+            }
+
+            Some(key.into_bytes())
+        });
+
+        let key = key.unwrap_or_else(|| {
+            tracing::warn!("SESSION_KEY secret unset, using a random session key");
+            rand::random::<[u8; MIN_KEY_LENGTH]>().to_vec()
+        });
+
+        Self::new(&key, cache)
+    }
+
+    /// The signature of the supplied session id
+    fn _signature(&self, id: &Uuid) -> HmacSha256 {
+        let mut signature =
+            HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+
+        signature.update(id.as_bytes());
+
+        signature
+    }
+
+    /// Sign the supplied session id, producing the session cookie's value
+    pub fn sign(&self, id: &Uuid) -> String {
+        format!(
+            "{}.{}",
+            id.simple(),
+            base64.encode(self._signature(id).finalize().into_bytes())
+        )
+    }
+
+    /// Get the session id from the supplied cookie
+    /// value (if its signature is valid)
+    pub fn verify(&self, cookie: &str) -> Option<Uuid> {
+        let (id, signature) = cookie.split_once('.')?;
+        let id = Uuid::try_parse(id).ok()?;
+        let signature = base64.decode(signature).ok()?;
+
+        self._signature(&id)
+            .verify_slice(&signature)
+            .ok()
+            .map(|()| id)
+    }
+
+    /// Get the (signed) session id from the supplied request headers
+    pub fn session_id(&self, headers: &HeaderMap) -> Option<Uuid> {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .filter(|(name, _)| *name == SESSION_COOKIE)
+            .find_map(|(_, value)| self.verify(value))
+    }
+
+    /// The `Set-Cookie` header value establishing the supplied session
+    pub fn cookie(&self, id: &Uuid) -> Option<HeaderValue> {
+        HeaderValue::from_str(&format!(
+            "{SESSION_COOKIE}={}; Path=/; Max-Age={}; HttpOnly; Secure; SameSite=Lax",
+            self.sign(id),
+            SESSION_TTL.as_secs()
+        ))
+        .ok()
+    }
+}
+
+// </editor-fold desc="// SessionStore ...">
+
+// <editor-fold desc="// Session ...">
+
+/// The current request's session
+#[derive(Clone, Debug)]
+pub struct Session {
+    /// the session's id
+    pub id: Uuid,
+    /// whether the session was created by the current request
+    fresh: bool,
+    /// whether data may be stashed in the session (i.e. whether the
+    /// request carried a session cookie, or opted in to a new one)
+    stashable: bool,
+    /// whether the current request has stashed any data
+    stashed: Arc<AtomicBool>,
+    /// where the session's data is kept
+    store: SessionStore,
+}
+
+impl Session {
+    /// The cache key of the supplied datum
+    fn _key(&self, name: &str) -> String {
+        format!("session:{}:{name}", self.id.simple())
+    }
+
+    /// Get the named datum stashed in the session (if any)
+    pub async fn get<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        self.store
+            .cache
+            .get(&self._key(name))
+            .await
+            .and_then(|value| serde_json::from_str(&value).ok())
+    }
+
+    /// Stash the supplied datum in the session under the supplied
+    /// name (unless the session is neither established nor opted in
+    /// to, so that anonymous traffic can't fill the shared cache)
+    pub async fn insert(&self, name: &str, value: &impl Serialize) {
+        if !self.stashable {
+            tracing::debug!("not stashing session datum {name}: no session to stash it in");
+            return;
+        }
+
+        match serde_json::to_string(value) {
+            Ok(value) => {
+                self.store
+                    .cache
+                    .set(&self._key(name), value, SESSION_TTL)
+                    .await;
+                self.stashed.store(true, Ordering::SeqCst);
+            }
+            Err(error) => tracing::error!("error serializing session datum {name}: {error}"),
+        }
+    }
+}
+
+#[async_trait]
+impl<State: Send + Sync> FromRequestParts<State> for Session {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _: &State) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<Self>().cloned().ok_or((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "sessions aren't enabled on this route",
+        ))
+    }
+}
+
+// </editor-fold desc="// Session ...">
+
+/// Middleware that attaches the requesting client's [`Session`]
+/// to the request, only issuing a cookie for a new session if
+/// the request opted in to one and stashes something in it
+pub async fn attach_session<B: Send + 'static>(
+    State(state): State<ShuttleAppState>,
+    mut request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let existing = state.sessions.session_id(request.headers());
+    let opted_in = request
+        .headers()
+        .get(SESSION_OPT_IN_HEADER)
+        .is_some_and(|value| {
+            value
+                .as_bytes()
+                .eq_ignore_ascii_case(SESSION_OPT_IN.as_bytes())
+        });
+    let session = Session {
+        id: existing.unwrap_or_else(Uuid::new_v4),
+        fresh: existing.is_none(),
+        stashable: existing.is_some() || opted_in,
+        stashed: Arc::new(AtomicBool::new(false)),
+        store: state.sessions.clone(),
+    };
+
+    request.extensions_mut().insert(session.clone());
+
+    let mut response = next.run(request).await;
+
+    if session.fresh && session.stashed.load(Ordering::SeqCst) {
+        if let Some(cookie) = state.sessions.cookie(&session.id) {
+            response.headers_mut().append(header::SET_COOKIE, cookie);
+        }
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::{collections::BTreeMap, sync::Arc};
+
+    // Third-Party Imports
+    use axum::http::{header, HeaderMap, HeaderValue};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use shuttle_secrets::SecretStore;
+    use uuid::Uuid;
+
+    // Crate-Level Imports
+    use super::{SessionStore, SESSION_COOKIE};
+    use crate::shared::LocalCache;
+
+    fn store(key: &str) -> SessionStore {
+        SessionStore::new(key.as_bytes(), Arc::new(LocalCache::default()))
+    }
+
+    /// Test that only cookies signed with the
+    /// store's key identify a session
+    #[rstest]
+    #[test_log::test]
+    fn test_session_cookie_signatures() {
+        let (ours, theirs) = (
+            store("an adequately long session signing key"),
+            store("a different but equally long signing key"),
+        );
+        let id = Uuid::new_v4();
+        let cookie = ours.sign(&id);
+
+        assert_eq!(Some(id), ours.verify(&cookie));
+        assert_eq!(None, theirs.verify(&cookie));
+        assert_eq!(
+            None,
+            ours.verify(&cookie.replacen(
+                &id.simple().to_string(),
+                &Uuid::nil().simple().to_string(),
+                1
+            ))
+        );
+        assert_eq!(None, ours.verify("not-a-session"));
+
+        let mut headers = HeaderMap::new();
+
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_str(&format!("recipe=e30=; {SESSION_COOKIE}={cookie}")).unwrap(),
+        );
+
+        assert_eq!(Some(id), ours.session_id(&headers));
+        assert_eq!(None, theirs.session_id(&headers));
+    }
+
+    /// Test that the signing key is read from the
+    /// `SESSION_KEY` secret (if it's long enough)
+    #[rstest]
+    #[case::adequate("an adequately long session signing key", true)]
+    #[case::short("hunter2", false)]
+    #[test_log::test]
+    fn test_session_key_from_secrets(#[case] key: &str, #[case] expected: bool) {
+        let secrets = SecretStore::new(BTreeMap::from([(
+            "SESSION_KEY".to_string(),
+            key.to_string().into(),
+        )]));
+        let cookie = SessionStore::from_secrets(&secrets, Arc::new(LocalCache::default()))
+            .sign(&Uuid::nil());
+
+        assert_eq!(expected, store(key).verify(&cookie).is_some());
+    }
+}
@@ -0,0 +1,463 @@
+//! ## Shared State
+//!
+//! Caches, counters, and broadcasts shared by every instance of
+//! the service via Redis (if the `REDIS_URL` secret is set),
+//! falling back to in-process implementations otherwise
+
+// Standard Library Imports
+use core::{fmt::Debug, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+// Third-Party Imports
+use axum::async_trait;
+use futures::stream::{BoxStream, StreamExt};
+use redis::{aio::ConnectionManager, AsyncCommands, Client, RedisResult};
+use serde::Serialize;
+use shuttle_secrets::SecretStore;
+use tokio::{sync::OnceCell, time::Instant};
+use uuid::Uuid;
+
+/// The maximum number of entries (of each kind) kept by a [`LocalCache`]
+const MAX_LOCAL_ENTRIES: usize = 10_000;
+
+/// The number of entries a full [`LocalCache`] evicts at once (so
+/// that it needn't scan its entries again for as many insertions)
+const LOCAL_EVICTION_BATCH: usize = MAX_LOCAL_ENTRIES / 10;
+
+// <editor-fold desc="// SharedCache ...">
+
+/// A key-value cache (and set of windowed counters)
+/// shared by every instance of the service
+#[async_trait]
+pub trait SharedCache: Debug + Send + Sync {
+    /// Get the unexpired value cached under the supplied key (if any)
+    async fn get(&self, key: &str) -> Option<String>;
+
+    /// Cache the supplied value under the supplied key for `ttl`
+    async fn set(&self, key: &str, value: String, ttl: Duration);
+
+    /// Add `by` to the counter under the supplied key (which
+    /// is zeroed `window` after its first increment in any
+    /// given window), returning the counter's new total
+    async fn increment(&self, key: &str, by: u64, window: Duration) -> u64;
+}
+
+// </editor-fold desc="// SharedCache ...">
+
+// <editor-fold desc="// LocalCache ...">
+
+/// An in-process [`SharedCache`], i.e. one
+/// "shared" by a single instance of the service
+#[derive(Debug, Default)]
+pub struct LocalCache {
+    /// cached values (and their expiry), by key
+    values: Mutex<HashMap<String, (String, Instant)>>,
+    /// windowed counters (and their expiry), by key
+    counters: Mutex<HashMap<String, (u64, Instant)>>,
+}
+
+impl LocalCache {
+    /// Make room in the supplied map for the supplied (new) key if
+    /// it's full, evicting its expired entries and then (if it's
+    /// still nearly full) the entries due to expire soonest
+    fn _evict<T>(entries: &mut HashMap<String, (T, Instant)>, key: &str, now: Instant) {
+        if entries.len() < MAX_LOCAL_ENTRIES || entries.contains_key(key) {
+            return;
+        }
+
+        entries.retain(|_, (_, expiry)| now < *expiry);
+
+        let excess = entries
+            .len()
+            .saturating_sub(MAX_LOCAL_ENTRIES - LOCAL_EVICTION_BATCH);
+
+        if excess == 0 {
+            return;
+        }
+
+        let mut expiries = entries
+            .values()
+            .map(|(_, expiry)| *expiry)
+            .collect::<Vec<Instant>>();
+        let (_, cutoff, _) = expiries.select_nth_unstable(excess - 1);
+        let cutoff = *cutoff;
+
+        entries.retain(|_, (_, expiry)| cutoff < *expiry);
+    }
+}
+
+#[async_trait]
+impl SharedCache for LocalCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let now = Instant::now();
+
+        self.values
+            .lock()
+            .unwrap()
+            .get(key)
+            .filter(|(_, expiry)| now < *expiry)
+            .map(|(value, _)| value.clone())
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) {
+        let now = Instant::now();
+        let mut values = self.values.lock().unwrap();
+
+        Self::_evict(&mut values, key, now);
+        values.insert(key.to_string(), (value, now + ttl));
+    }
+
+    async fn increment(&self, key: &str, by: u64, window: Duration) -> u64 {
+        let now = Instant::now();
+        let mut counters = self.counters.lock().unwrap();
+
+        Self::_evict(&mut counters, key, now);
+
+        let (total, expiry) = counters
+            .entry(key.to_string())
+            .or_insert((0u64, now + window));
+
+        if *expiry <= now {
+            (*total, *expiry) = (0u64, now + window);
+        }
+
+        *total += by;
+        *total
+    }
+}
+
+// </editor-fold desc="// LocalCache ...">
+
+// <editor-fold desc="// RedisCache ...">
+
+/// A Redis-backed [`SharedCache`], which falls back to an in-process
+/// cache while Redis is unreachable (rather than failing requests)
+pub struct RedisCache {
+    /// the configured Redis server
+    client: Client,
+    /// the (lazily established, automatically
+    /// reconnecting) connection to the server
+    connection: OnceCell<ConnectionManager>,
+    /// the cache used while the server is unreachable
+    fallback: LocalCache,
+}
+
+impl Debug for RedisCache {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RedisCache")
+            .field("client", &self.client)
+            .field("connected", &self.connection.initialized())
+            .finish_non_exhaustive()
+    }
+}
+
+impl RedisCache {
+    /// Create a cache backed by the supplied Redis server
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            connection: OnceCell::new(),
+            fallback: LocalCache::default(),
+        }
+    }
+
+    /// Get a connection to the server (if it's reachable)
+    async fn _connection(&self) -> Option<ConnectionManager> {
+        self.connection
+            .get_or_try_init(|| ConnectionManager::new(self.client.clone()))
+            .await
+            .map_err(|error| tracing::error!("error connecting to redis: {error}"))
+            .ok()
+            .cloned()
+    }
+
+    /// Run the supplied command against the server,
+    /// logging (and discarding) any error it fails with
+    async fn _run<T, Command>(&self, command: Command) -> Option<T>
+    where
+        Command: FnOnce(ConnectionManager) -> futures::future::BoxFuture<'static, RedisResult<T>>,
+    {
+        command(self._connection().await?)
+            .await
+            .map_err(|error| tracing::error!("redis command failed: {error}"))
+            .ok()
+    }
+
+    /// Publish the supplied payload to the supplied channel
+    pub async fn publish(&self, channel: String, payload: String) {
+        self._run(|mut connection| {
+            Box::pin(async move { connection.publish::<_, _, ()>(channel, payload).await })
+        })
+        .await;
+    }
+
+    /// Subscribe to every channel matching the supplied
+    /// pattern, yielding each message's channel and payload
+    pub async fn subscribe(
+        &self,
+        pattern: &str,
+    ) -> RedisResult<BoxStream<'static, (String, String)>> {
+        let mut subscription = self.client.get_async_connection().await?.into_pubsub();
+
+        subscription.psubscribe(pattern).await?;
+
+        Ok(subscription
+            .into_on_message()
+            .filter_map(|message| async move {
+                Some((
+                    message.get_channel_name().to_string(),
+                    message.get_payload::<String>().ok()?,
+                ))
+            })
+            .boxed())
+    }
+}
+
+#[async_trait]
+impl SharedCache for RedisCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let owned = key.to_string();
+
+        match self
+            ._run(|mut connection| {
+                Box::pin(async move { connection.get::<_, Option<String>>(owned).await })
+            })
+            .await
+        {
+            Some(value) => value,
+            None => self.fallback.get(key).await,
+        }
+    }
+
+    async fn set(&self, key: &str, value: String, ttl: Duration) {
+        let (owned, fallback) = (key.to_string(), value.clone());
+
+        if self
+            ._run(|mut connection| {
+                Box::pin(async move {
+                    connection
+                        .set_ex::<_, _, ()>(owned, value, ttl.as_secs().max(1))
+                        .await
+                })
+            })
+            .await
+            .is_none()
+        {
+            self.fallback.set(key, fallback, ttl).await;
+        }
+    }
+
+    async fn increment(&self, key: &str, by: u64, window: Duration) -> u64 {
+        let owned = key.to_string();
+        let seconds = i64::try_from(window.as_secs().max(1)).unwrap_or(i64::MAX);
+
+        let total = self
+            ._run(|mut connection| {
+                Box::pin(async move {
+                    let total = connection.incr::<_, _, u64>(&owned, by).await?;
+
+                    // the first increment in a window starts the window
+                    if total == by {
+                        connection.expire::<_, ()>(&owned, seconds).await?;
+                    }
+
+                    Ok(total)
+                })
+            })
+            .await;
+
+        match total {
+            Some(total) => total,
+            None => self.fallback.increment(key, by, window).await,
+        }
+    }
+}
+
+// </editor-fold desc="// RedisCache ...">
+
+// <editor-fold desc="// SharedState ...">
+
+/// The state shared by every instance of the service
+#[derive(Clone, Debug)]
+pub struct SharedState {
+    /// this instance's (randomly generated) id
+    pub instance: Uuid,
+    /// the cache shared by every instance (or
+    /// an in-process one, if Redis isn't configured)
+    pub cache: Arc<dyn SharedCache>,
+    /// the configured Redis server (if any)
+    redis: Option<Arc<RedisCache>>,
+}
+
+impl Default for SharedState {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl SharedState {
+    /// Create the shared state, backed by
+    /// the supplied Redis server (if any)
+    pub fn new(redis: Option<Client>) -> Self {
+        let redis = redis.map(|client| Arc::new(RedisCache::new(client)));
+
+        Self {
+            instance: Uuid::new_v4(),
+            cache: redis.clone().map_or_else(
+                || Arc::new(LocalCache::default()) as Arc<dyn SharedCache>,
+                |redis| redis as Arc<dyn SharedCache>,
+            ),
+            redis,
+        }
+    }
+
+    /// Create the shared state, backed by the
+    /// `REDIS_URL` secret's server (if set)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        let redis = secrets.get("REDIS_URL").and_then(|url| {
+            Client::open(url)
+                .map_err(|error| tracing::error!("ignoring REDIS_URL secret: {error}"))
+                .ok()
+        });
+
+        Self::new(redis)
+    }
+
+    /// Whether the state is actually shared
+    /// with other instances of the service
+    pub fn is_distributed(&self) -> bool {
+        self.redis.is_some()
+    }
+
+    /// Publish the supplied payload to the other instances
+    /// of the service subscribed to the supplied channel
+    /// (which is a no-op if Redis isn't configured)
+    pub async fn publish(&self, channel: String, payload: &impl Serialize) {
+        let Some(redis) = self.redis.as_ref() else {
+            return;
+        };
+
+        match serde_json::to_string(payload) {
+            Ok(payload) => redis.publish(channel, payload).await,
+            Err(error) => tracing::error!("error serializing {channel} payload: {error}"),
+        }
+    }
+
+    /// Subscribe to every channel matching the supplied pattern,
+    /// yielding each message's channel and payload (or `None`
+    /// if Redis isn't configured or can't be subscribed to)
+    pub async fn subscribe(&self, pattern: &str) -> Option<BoxStream<'static, (String, String)>> {
+        self.redis
+            .as_ref()?
+            .subscribe(pattern)
+            .await
+            .map_err(|error| tracing::error!("error subscribing to {pattern}: {error}"))
+            .ok()
+    }
+}
+
+// </editor-fold desc="// SharedState ...">
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use core::time::Duration;
+    use std::collections::BTreeMap;
+
+    // Third-Party Imports
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use shuttle_secrets::SecretStore;
+
+    // Crate-Level Imports
+    use super::{LocalCache, SharedCache, SharedState, MAX_LOCAL_ENTRIES};
+
+    /// Test that the state is only distributed
+    /// given a valid `REDIS_URL` secret
+    #[rstest]
+    #[case::unset(None, false)]
+    #[case::valid(Some("redis://127.0.0.1:6379/0"), true)]
+    #[case::invalid(Some("not a url"), false)]
+    #[test_log::test]
+    fn test_shared_state_from_secrets(#[case] secret: Option<&str>, #[case] expected: bool) {
+        let secrets = SecretStore::new(BTreeMap::from_iter(
+            secret.map(|value| ("REDIS_URL".to_string(), value.to_string().into())),
+        ));
+
+        assert_eq!(
+            expected,
+            SharedState::from_secrets(&secrets).is_distributed()
+        );
+    }
+
+    /// Test that locally cached values and
+    /// counters expire when they should
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_local_cache_expiry() {
+        let cache = LocalCache::default();
+        let window = Duration::from_millis(50);
+
+        cache.set("pikachu", "6".to_string(), window).await;
+
+        assert_eq!(Some("6".to_string()), cache.get("pikachu").await);
+        assert_eq!(None, cache.get("raichu").await);
+        assert_eq!(1, cache.increment("strikes", 1, window).await);
+        assert_eq!(6, cache.increment("strikes", 5, window).await);
+
+        tokio::time::sleep(window).await;
+
+        assert_eq!(None, cache.get("pikachu").await);
+        assert_eq!(2, cache.increment("strikes", 2, window).await);
+    }
+
+    /// Test that a full cache evicts the entries due to expire
+    /// soonest (even if none have expired yet) to stay bounded
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_local_cache_capacity() {
+        let cache = LocalCache::default();
+
+        cache
+            .set("oldest", String::new(), Duration::from_secs(60))
+            .await;
+
+        for index in 0..(2 * MAX_LOCAL_ENTRIES) {
+            cache
+                .set(
+                    &format!("session:{index}"),
+                    String::new(),
+                    Duration::from_secs(3600),
+                )
+                .await;
+        }
+
+        assert!(cache.values.lock().unwrap().len() <= MAX_LOCAL_ENTRIES);
+        assert_eq!(None, cache.get("oldest").await);
+        assert_eq!(
+            Some(String::new()),
+            cache
+                .get(&format!("session:{}", 2 * MAX_LOCAL_ENTRIES - 1))
+                .await
+        );
+    }
+
+    /// Test that publishing and subscribing are
+    /// (harmless) no-ops without Redis
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_undistributed_pub_sub() {
+        let shared = SharedState::default();
+
+        shared.publish("cch:test".to_string(), &"ping").await;
+
+        assert!(shared.subscribe("cch:*").await.is_none());
+    }
+}
@@ -0,0 +1,1089 @@
+//! ## Load Shedding
+//!
+//! Sheds requests to the heavy endpoints while the service is
+//! overloaded, limits how many requests for each kind of heavy work
+//! run at once, and rate limits each client's requests (to the
+//! endpoints fanning out to upstream providers, or overall)
+
+// Standard Library Imports
+use core::{
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    time::Duration,
+};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+
+// Third-Party Imports
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use shuttle_secrets::SecretStore;
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::timeout,
+};
+
+// Crate-Level Imports
+use crate::{
+    clock::Clock, errors::ErrorEnvelope, random::RandomSource, state::ShuttleAppState, utils,
+};
+
+/// The path prefixes of the (low-priority) endpoints
+/// that are shed when the service is overloaded
+const HEAVY_ENDPOINTS: [&str; 2] = ["/20/", "/22/rocket"];
+
+/// The number of in-flight requests beyond which heavy requests
+/// are shed when the `SHED_MAX_IN_FLIGHT` secret is unset
+const DEFAULT_MAX_IN_FLIGHT: usize = 64;
+
+/// The fraction of the database pool's connections in use beyond
+/// which heavy requests are shed when the `SHED_MAX_POOL_SATURATION`
+/// secret is unset
+const DEFAULT_MAX_POOL_SATURATION: f64 = 0.9;
+
+/// The minimum `Retry-After` (in seconds) of a shed request
+const MIN_RETRY_AFTER_SECS: u64 = 1;
+
+/// The maximum jitter (in seconds) added to a shed
+/// request's `Retry-After`, so clients don't retry
+/// in lockstep (and overload the service all over again)
+const MAX_RETRY_JITTER_SECS: u64 = 4;
+
+/// How long requests for heavy work wait for their turn
+/// when the `CONCURRENCY_WAIT_MS` secret is unset
+const DEFAULT_CONCURRENCY_WAIT: Duration = Duration::from_secs(5);
+
+/// The number of (per-client) token buckets tracked
+/// before the full ones are forgotten
+const MAX_TRACKED_BUCKETS: usize = 4096;
+
+// <editor-fold desc="// LoadShedder ...">
+
+/// The service's current load, and the
+/// thresholds beyond which it's shedding
+#[derive(Debug)]
+pub struct LoadShedder {
+    /// the number of requests currently being handled
+    in_flight: Arc<AtomicUsize>,
+    /// the number of requests shed so far
+    shed: AtomicU64,
+    /// the number of in-flight requests beyond
+    /// which heavy requests are shed
+    pub max_in_flight: usize,
+    /// the fraction of the database pool's connections
+    /// in use beyond which heavy requests are shed
+    pub max_pool_saturation: f64,
+}
+
+impl Default for LoadShedder {
+    fn default() -> Self {
+        Self {
+            in_flight: Arc::default(),
+            shed: AtomicU64::default(),
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            max_pool_saturation: DEFAULT_MAX_POOL_SATURATION,
+        }
+    }
+}
+
+impl LoadShedder {
+    /// Create a load shedder using the `SHED_MAX_IN_FLIGHT`
+    /// and `SHED_MAX_POOL_SATURATION` secrets (if set)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        let max_in_flight = secrets.get("SHED_MAX_IN_FLIGHT").and_then(|value| {
+            value
+                .parse::<usize>()
+                .map_err(|error| tracing::error!("ignoring SHED_MAX_IN_FLIGHT secret: {error}"))
+                .ok()
+                .filter(|value| {
+                    if *value == 0 {
+                        tracing::error!("ignoring SHED_MAX_IN_FLIGHT secret: must be positive");
+                    }
+
+                    0 < *value
+                })
+        });
+
+        let max_pool_saturation = secrets.get("SHED_MAX_POOL_SATURATION").and_then(|value| {
+            value
+                .parse::<f64>()
+                .map_err(|error| {
+                    tracing::error!("ignoring SHED_MAX_POOL_SATURATION secret: {error}")
+                })
+                .ok()
+                .filter(|value| {
+                    let valid = 0.0 < *value && *value <= 1.0;
+
+                    if !valid {
+                        tracing::error!(
+                            "ignoring SHED_MAX_POOL_SATURATION secret: must be in (0, 1]"
+                        );
+                    }
+
+                    valid
+                })
+        });
+
+        Self {
+            max_in_flight: max_in_flight.unwrap_or(DEFAULT_MAX_IN_FLIGHT),
+            max_pool_saturation: max_pool_saturation.unwrap_or(DEFAULT_MAX_POOL_SATURATION),
+            ..Self::default()
+        }
+    }
+
+    /// Count a request as in flight until the returned guard is dropped
+    pub fn admit(&self) -> InFlight {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+
+        InFlight(self.in_flight.clone())
+    }
+
+    /// Get the number of requests currently in flight
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of requests shed so far
+    pub fn shed(&self) -> u64 {
+        self.shed.load(Ordering::Relaxed)
+    }
+
+    /// Check whether the request for the specified path should be
+    /// shed, given the database pool's current saturation
+    pub fn should_shed(&self, path: &str, pool_saturation: f64) -> bool {
+        let overloaded =
+            self.max_in_flight < self.in_flight() || self.max_pool_saturation <= pool_saturation;
+
+        overloaded && Self::is_heavy(path)
+    }
+
+    /// Check whether the specified path belongs to a heavy endpoint
+    pub fn is_heavy(path: &str) -> bool {
+        HEAVY_ENDPOINTS
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+    }
+
+    /// Reject a request, advising the client to
+    /// retry after a (randomly jittered) delay
+    pub fn reject(&self, rng: &dyn RandomSource) -> Response {
+        self.shed.fetch_add(1, Ordering::Relaxed);
+
+        let retry_after = MIN_RETRY_AFTER_SECS + rng.below(MAX_RETRY_JITTER_SECS + 1);
+
+        (
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            ErrorEnvelope::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "service overloaded, please retry later",
+            ),
+        )
+            .into_response()
+    }
+}
+
+/// A request counted as in flight by a [`LoadShedder`]
+#[derive(Debug)]
+pub struct InFlight(Arc<AtomicUsize>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// </editor-fold desc="// LoadShedder ...">
+
+// <editor-fold desc="// ConcurrencyLimits ...">
+
+/// The kinds of CPU-heavy work whose concurrency is limited
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum HeavyWork {
+    /// [Day 11](crate::solutions::day_11) image analysis and conversion
+    ImageAnalysis,
+    /// [Day 20](crate::solutions::day_20) git cookie hunting
+    CookieHunt,
+    /// [Day 22](crate::solutions::day_22) star chart pathfinding
+    StarChart,
+}
+
+impl HeavyWork {
+    /// Every kind of heavy work
+    pub const ALL: [Self; 3] = [Self::ImageAnalysis, Self::CookieHunt, Self::StarChart];
+
+    /// The path prefixes of the work's endpoints
+    fn prefixes(self) -> &'static [&'static str] {
+        match self {
+            Self::ImageAnalysis => &["/11/red_pixels", "/11/convert"],
+            Self::CookieHunt => &["/20/cookie"],
+            Self::StarChart => &["/22/rocket"],
+        }
+    }
+
+    /// The secret overriding the work's concurrency limit
+    fn secret(self) -> &'static str {
+        match self {
+            Self::ImageAnalysis => "CONCURRENCY_IMAGE_ANALYSIS",
+            Self::CookieHunt => "CONCURRENCY_COOKIE_HUNT",
+            Self::StarChart => "CONCURRENCY_STAR_CHART",
+        }
+    }
+
+    /// The work's concurrency limit when its secret is unset
+    fn default_limit(self) -> usize {
+        match self {
+            Self::ImageAnalysis | Self::StarChart => 2,
+            Self::CookieHunt => 1,
+        }
+    }
+
+    /// Get the kind of heavy work (if any) the specified path performs
+    pub fn of(path: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|work| {
+            work.prefixes()
+                .iter()
+                .any(|prefix| path.starts_with(prefix))
+        })
+    }
+}
+
+/// The number of concurrent requests allowed
+/// for each kind of [heavy work](HeavyWork)
+#[derive(Debug)]
+pub struct ConcurrencyLimits {
+    /// each kind of work's limit, and the permits enforcing it
+    semaphores: HashMap<HeavyWork, (usize, Arc<Semaphore>)>,
+    /// how long excess requests wait for a
+    /// permit before they're rejected
+    pub max_wait: Duration,
+}
+
+impl Default for ConcurrencyLimits {
+    fn default() -> Self {
+        HeavyWork::ALL.into_iter().fold(
+            Self {
+                semaphores: HashMap::new(),
+                max_wait: DEFAULT_CONCURRENCY_WAIT,
+            },
+            |limits, work| limits.with_limit(work, work.default_limit()),
+        )
+    }
+}
+
+impl ConcurrencyLimits {
+    /// Create concurrency limits using the `CONCURRENCY_*`
+    /// and `CONCURRENCY_WAIT_MS` secrets (if set)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        let limits = HeavyWork::ALL
+            .into_iter()
+            .fold(
+                Self::default(),
+                |limits, work| match utils::positive_secret(secrets, work.secret()) {
+                    Some(limit) => limits.with_limit(work, limit),
+                    None => limits,
+                },
+            );
+
+        match utils::positive_secret(secrets, "CONCURRENCY_WAIT_MS") {
+            Some(wait) => limits.with_max_wait(Duration::from_millis(wait as u64)),
+            None => limits,
+        }
+    }
+
+    /// Allow at most `limit` concurrent requests for the specified work
+    pub fn with_limit(mut self, work: HeavyWork, limit: usize) -> Self {
+        self.semaphores
+            .insert(work, (limit, Arc::new(Semaphore::new(limit))));
+        self
+    }
+
+    /// Wait at most `max_wait` for a permit before rejecting a request
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Get the concurrency limit of the specified work
+    pub fn limit(&self, work: HeavyWork) -> usize {
+        self.semaphores.get(&work).map_or(0, |(limit, _)| *limit)
+    }
+
+    /// Get the number of requests for the specified work that
+    /// may currently start without waiting
+    pub fn available(&self, work: HeavyWork) -> usize {
+        self.semaphores
+            .get(&work)
+            .map_or(0, |(_, semaphore)| semaphore.available_permits())
+    }
+
+    /// Wait (at most [`max_wait`](Self::max_wait)) for a permit
+    /// to perform the specified work, held until it's dropped
+    pub async fn acquire(&self, work: HeavyWork) -> Option<OwnedSemaphorePermit> {
+        let (_, semaphore) = self.semaphores.get(&work)?;
+
+        timeout(self.max_wait, semaphore.clone().acquire_owned())
+            .await
+            .ok()?
+            .ok()
+    }
+}
+
+// </editor-fold desc="// ConcurrencyLimits ...">
+
+// <editor-fold desc="// RateLimits ...">
+
+/// The kinds of (upstream-bound or otherwise
+/// expensive) work whose request rate is limited
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RateLimitedWork {
+    /// [Day 8](crate::solutions::day_8) PokeAPI lookups
+    Pokemon,
+    /// [Day 20](crate::solutions::day_20) git cookie hunting
+    CookieHunt,
+    /// [Day 21](crate::solutions::day_21) reverse geocoding
+    Geocoding,
+}
+
+impl RateLimitedWork {
+    /// Every kind of rate-limited work
+    pub const ALL: [Self; 3] = [Self::Pokemon, Self::CookieHunt, Self::Geocoding];
+
+    /// The path prefixes of the work's endpoints
+    fn prefixes(self) -> &'static [&'static str] {
+        match self {
+            Self::Pokemon => &["/8/"],
+            Self::CookieHunt => &["/20/cookie"],
+            Self::Geocoding => &["/21/country/"],
+        }
+    }
+
+    /// The secret overriding the work's rate limit (whose burst
+    /// is overridden by the same secret, suffixed with `_BURST`)
+    fn secret(self) -> &'static str {
+        match self {
+            Self::Pokemon => "RATE_LIMIT_POKEMON",
+            Self::CookieHunt => "RATE_LIMIT_COOKIE_HUNT",
+            Self::Geocoding => "RATE_LIMIT_GEOCODING",
+        }
+    }
+
+    /// Get the kind of rate-limited work (if any) the specified path performs
+    pub fn of(path: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|work| {
+            work.prefixes()
+                .iter()
+                .any(|prefix| path.starts_with(prefix))
+        })
+    }
+}
+
+/// The rate at which a client's requests are allowed (i.e. the
+/// capacity and refill rate of the client's token bucket)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RateLimit {
+    /// the sustained number of requests allowed per minute
+    pub per_minute: usize,
+    /// the number of requests allowed in a burst
+    pub burst: usize,
+}
+
+impl RateLimit {
+    /// Allow `per_minute` requests per minute, in bursts of at most `burst`
+    pub const fn new(per_minute: usize, burst: usize) -> Self {
+        Self { per_minute, burst }
+    }
+
+    /// Read a limit from the named secret (and its `_BURST`-suffixed
+    /// counterpart), falling back to the supplied limit's fields
+    fn from_secrets(secrets: &SecretStore, name: &str, fallback: Option<Self>) -> Option<Self> {
+        let per_minute = utils::positive_secret(secrets, name);
+        let burst = utils::positive_secret(secrets, &format!("{name}_BURST"));
+
+        match (per_minute, fallback) {
+            (Some(per_minute), _) => Some(Self::new(
+                per_minute,
+                burst
+                    .or(fallback.map(|limit| limit.burst))
+                    .unwrap_or(per_minute),
+            )),
+            (None, Some(fallback)) => Some(Self::new(
+                fallback.per_minute,
+                burst.unwrap_or(fallback.burst),
+            )),
+            (None, None) => None,
+        }
+    }
+
+    /// The number of tokens the limit's bucket regains per second
+    fn refill_rate(&self) -> f64 {
+        self.per_minute as f64 / 60.0
+    }
+}
+
+/// A client's token bucket
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    /// the tokens left in the bucket (as of `updated`)
+    tokens: f64,
+    /// when the bucket's tokens were last counted
+    updated: DateTime<Utc>,
+}
+
+impl Bucket {
+    /// Count the bucket's tokens as of the supplied time
+    fn refill(&mut self, limit: &RateLimit, now: DateTime<Utc>) {
+        let elapsed = (now - self.updated).num_milliseconds().max(0) as f64 / 1000.0;
+
+        self.tokens = (self.tokens + elapsed * limit.refill_rate()).min(limit.burst as f64);
+        self.updated = now;
+    }
+
+    /// How long until the bucket holds a whole token
+    fn retry_after(&self, limit: &RateLimit) -> Duration {
+        Duration::from_secs_f64((1.0 - self.tokens).max(0.0) / limit.refill_rate())
+    }
+}
+
+/// The scope of a client's token bucket
+type BucketKey = (Option<RateLimitedWork>, IpAddr);
+
+/// The rate at which each client's requests are allowed, overall
+/// and for each kind of [rate-limited work](RateLimitedWork)
+#[derive(Debug)]
+pub struct RateLimits {
+    /// the limit of every client's requests (if any)
+    pub global: Option<RateLimit>,
+    /// the limit of every client's requests for each kind of work
+    limits: HashMap<RateLimitedWork, RateLimit>,
+    /// each client's token buckets
+    buckets: Mutex<HashMap<BucketKey, Bucket>>,
+    /// the number of requests rejected so far
+    limited: AtomicU64,
+    /// the limits' source of the current time
+    clock: Arc<dyn Clock>,
+}
+
+impl RateLimits {
+    /// Create rate limits that (until configured otherwise)
+    /// don't limit the rate of any client's requests
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            global: None,
+            limits: HashMap::new(),
+            buckets: Mutex::default(),
+            limited: AtomicU64::default(),
+            clock,
+        }
+    }
+
+    /// Create rate limits using the `RATE_LIMIT_GLOBAL` and
+    /// `RATE_LIMIT_*` secrets (and their `_BURST` counterparts),
+    /// leaving any work whose secret is unset unlimited (as a
+    /// single client, e.g. the grader or a proxy without a usable
+    /// `X-Forwarded-For`, may legitimately send large bursts)
+    pub fn from_secrets(secrets: &SecretStore, clock: Arc<dyn Clock>) -> Self {
+        let limits = RateLimitedWork::ALL
+            .into_iter()
+            .fold(
+                Self::new(clock),
+                |limits, work| match RateLimit::from_secrets(secrets, work.secret(), None) {
+                    Some(limit) => limits.with_limit(work, limit),
+                    None => limits,
+                },
+            );
+
+        limits.with_global_limit(RateLimit::from_secrets(secrets, "RATE_LIMIT_GLOBAL", None))
+    }
+
+    /// Limit every client's requests for the specified work
+    pub fn with_limit(mut self, work: RateLimitedWork, limit: RateLimit) -> Self {
+        self.limits.insert(work, limit);
+        self
+    }
+
+    /// Limit (or stop limiting) every client's requests overall
+    pub fn with_global_limit(mut self, limit: Option<RateLimit>) -> Self {
+        self.global = limit;
+        self
+    }
+
+    /// Get the rate limit of the specified work
+    pub fn limit(&self, work: RateLimitedWork) -> Option<RateLimit> {
+        self.limits.get(&work).copied()
+    }
+
+    /// Get the number of requests rejected so far
+    pub fn limited(&self) -> u64 {
+        self.limited.load(Ordering::Relaxed)
+    }
+
+    /// Take a token from each of the supplied client's buckets
+    /// that the request for the specified path is subject to,
+    /// or (if any of them is empty) tell how long until it isn't
+    pub fn check(&self, ip: IpAddr, path: &str) -> Result<(), Duration> {
+        let now = self.clock.now();
+        let work = RateLimitedWork::of(path);
+
+        let scopes = [
+            self.global.map(|limit| ((None, ip), limit)),
+            work.and_then(|work| self.limit(work).map(|limit| ((Some(work), ip), limit))),
+        ];
+
+        let mut buckets = self.buckets.lock().expect("poisoned rate limit buckets");
+
+        if MAX_TRACKED_BUCKETS < buckets.len() {
+            self._forget_full(&mut buckets, now);
+        }
+
+        let mut retry_after = Duration::ZERO;
+
+        for (key, limit) in scopes.iter().flatten() {
+            let bucket = buckets.entry(*key).or_insert(Bucket {
+                tokens: limit.burst as f64,
+                updated: now,
+            });
+
+            bucket.refill(limit, now);
+
+            if bucket.tokens < 1.0 {
+                retry_after = retry_after.max(bucket.retry_after(limit));
+            }
+        }
+
+        if !retry_after.is_zero() {
+            self.limited.fetch_add(1, Ordering::Relaxed);
+            return Err(retry_after);
+        }
+
+        for (key, _) in scopes.iter().flatten() {
+            if let Some(bucket) = buckets.get_mut(key) {
+                bucket.tokens -= 1.0;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forget the buckets that have refilled (which
+    /// are indistinguishable from brand new ones)
+    fn _forget_full(&self, buckets: &mut HashMap<BucketKey, Bucket>, now: DateTime<Utc>) {
+        buckets.retain(|(work, _), bucket| {
+            let limit = match work {
+                Some(work) => self.limit(*work),
+                None => self.global,
+            };
+
+            limit.is_some_and(|limit| {
+                bucket.refill(&limit, now);
+                bucket.tokens < limit.burst as f64
+            })
+        });
+    }
+}
+
+// </editor-fold desc="// RateLimits ...">
+
+/// Get the fraction of the supplied pool's connections in use
+pub fn pool_saturation(db: &sqlx::PgPool) -> f64 {
+    let max_connections = db.options().get_max_connections();
+
+    if max_connections == 0 {
+        return 0.0;
+    }
+
+    let in_use = (db.size() as usize).saturating_sub(db.num_idle());
+
+    in_use as f64 / f64::from(max_connections)
+}
+
+/// Middleware that sheds requests to the heavy endpoints
+/// while the service is overloaded (rather than letting
+/// every request time out)
+pub async fn shed_load<B: Send + 'static>(
+    State(state): State<ShuttleAppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let _in_flight = state.shedder.admit();
+
+    if state
+        .shedder
+        .should_shed(request.uri().path(), pool_saturation(&state.db))
+    {
+        tracing::warn!(
+            path = request.uri().path(),
+            in_flight = state.shedder.in_flight(),
+            "shedding request"
+        );
+
+        return state.shedder.reject(state.rng.as_ref());
+    }
+
+    next.run(request).await
+}
+
+/// Middleware that limits how many requests for each
+/// kind of [heavy work](HeavyWork) run concurrently,
+/// rejecting those that wait too long for their turn
+pub async fn limit_concurrency<B: Send + 'static>(
+    State(state): State<ShuttleAppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let Some(work) = HeavyWork::of(request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let Some(permit) = state.concurrency.acquire(work).await else {
+        tracing::warn!(
+            path = request.uri().path(),
+            ?work,
+            "rejecting request: concurrency limit reached"
+        );
+
+        return state.shedder.reject(state.rng.as_ref());
+    };
+
+    let response = next.run(request).await;
+
+    drop(permit);
+
+    response
+}
+
+/// Middleware that rate limits each client's requests (per the
+/// service's [`RateLimits`]), rejecting those beyond its limits
+/// with a `Retry-After` of when the client may next try
+pub async fn limit_rates<B: Send + 'static>(
+    State(state): State<ShuttleAppState>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(peer)| peer);
+
+    let Some(ip) = state.proxies.client_ip(request.headers(), peer) else {
+        return next.run(request).await;
+    };
+
+    if let Err(retry_after) = state.rate_limits.check(ip, request.uri().path()) {
+        tracing::warn!(
+            path = request.uri().path(),
+            %ip,
+            ?retry_after,
+            "rejecting request: rate limit exceeded"
+        );
+
+        let retry_after = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+
+        return (
+            [(header::RETRY_AFTER, retry_after.to_string())],
+            ErrorEnvelope::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate limit exceeded, please retry later",
+            ),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use core::time::Duration;
+    use std::{collections::BTreeMap, net::SocketAddr, sync::Arc};
+
+    // Third-Party Imports
+    use axum::{
+        body::Body,
+        extract::ConnectInfo,
+        http::{header, Request, StatusCode},
+        middleware::from_fn_with_state,
+        routing::{self, Router},
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use shuttle_secrets::SecretStore;
+
+    // Crate-Level Imports
+    use super::{
+        ConcurrencyLimits, HeavyWork, LoadShedder, RateLimit, RateLimitedWork, RateLimits,
+    };
+    use crate::clock::FakeClock;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{state, TestService};
+    use crate::{router, routes};
+
+    /// Test that only heavy requests are shed,
+    /// and only while the service is overloaded
+    #[rstest]
+    #[case::idle("/22/rocket", 0, 0.0, false)]
+    #[case::busy_heavy("/22/rocket", 3, 0.0, true)]
+    #[case::busy_archive("/20/archive_files", 3, 0.0, true)]
+    #[case::busy_light("/22/integers", 3, 0.0, false)]
+    #[case::saturated_pool("/20/cookie", 0, 0.95, true)]
+    #[test_log::test]
+    fn test_should_shed(
+        #[case] path: &str,
+        #[case] in_flight: usize,
+        #[case] pool_saturation: f64,
+        #[case] expected: bool,
+    ) {
+        let shedder = LoadShedder {
+            max_in_flight: 2,
+            ..LoadShedder::default()
+        };
+        let _guards = (0..in_flight).map(|_| shedder.admit()).collect::<Vec<_>>();
+
+        assert_eq!(expected, shedder.should_shed(path, pool_saturation));
+    }
+
+    /// Test that `admit` counts requests until their guards are dropped
+    #[rstest]
+    #[test_log::test]
+    fn test_in_flight_guards() {
+        let shedder = LoadShedder::default();
+        let first = shedder.admit();
+        let second = shedder.admit();
+
+        assert_eq!(2, shedder.in_flight());
+
+        drop(first);
+        drop(second);
+
+        assert_eq!(0, shedder.in_flight());
+    }
+
+    /// Test that `shed_load` rejects heavy requests with a
+    /// jittered `Retry-After` while the service is overloaded
+    #[rstest]
+    #[case::heavy("/22/rocket", StatusCode::TOO_MANY_REQUESTS)]
+    #[case::light("/22/integers", StatusCode::OK)]
+    #[test_log::test(tokio::test)]
+    async fn test_shed_load(
+        state: ShuttleAppState,
+        #[case] path: &str,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let state = ShuttleAppState {
+            shedder: Arc::new(LoadShedder {
+                max_pool_saturation: f64::MIN_POSITIVE,
+                max_in_flight: 0,
+                ..LoadShedder::default()
+            }),
+            ..state
+        };
+        let service = TestService::from(
+            Router::new()
+                .route(path, routing::get(|| async { "ok" }))
+                .layer(from_fn_with_state(state.clone(), super::shed_load))
+                .with_state(state.clone()),
+        );
+
+        let response = service.resolve(path).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap();
+
+            assert!((1..=5).contains(&retry_after));
+            assert_eq!(1, state.shedder.shed());
+        }
+
+        assert_eq!(0, state.shedder.in_flight());
+
+        Ok(())
+    }
+
+    /// Test that paths are attributed to the right kind of heavy work
+    #[rstest]
+    #[case::red_pixels("/11/red_pixels", Some(HeavyWork::ImageAnalysis))]
+    #[case::convert("/11/convert", Some(HeavyWork::ImageAnalysis))]
+    #[case::cookie("/20/cookie", Some(HeavyWork::CookieHunt))]
+    #[case::rocket("/22/rocket", Some(HeavyWork::StarChart))]
+    #[case::geojson("/22/rocket/geojson", Some(HeavyWork::StarChart))]
+    #[case::light("/22/integers", None)]
+    #[test_log::test]
+    fn test_heavy_work_of(#[case] path: &str, #[case] expected: Option<HeavyWork>) {
+        assert_eq!(expected, HeavyWork::of(path));
+    }
+
+    /// Test that concurrency limits are read from
+    /// the service's secrets, ignoring invalid ones
+    #[rstest]
+    #[test_log::test]
+    fn test_concurrency_limits_from_secrets() {
+        let secrets = SecretStore::new(BTreeMap::from([
+            (
+                String::from("CONCURRENCY_STAR_CHART"),
+                String::from("4").into(),
+            ),
+            (
+                String::from("CONCURRENCY_COOKIE_HUNT"),
+                String::from("0").into(),
+            ),
+            (
+                String::from("CONCURRENCY_WAIT_MS"),
+                String::from("250").into(),
+            ),
+        ]));
+
+        let limits = ConcurrencyLimits::from_secrets(&secrets);
+
+        assert_eq!(4, limits.limit(HeavyWork::StarChart));
+        assert_eq!(1, limits.limit(HeavyWork::CookieHunt));
+        assert_eq!(2, limits.limit(HeavyWork::ImageAnalysis));
+        assert_eq!(Duration::from_millis(250), limits.max_wait);
+    }
+
+    /// Test that `limit_concurrency` rejects heavy requests that
+    /// can't get a permit in time, and releases permits when done
+    #[rstest]
+    #[case::saturated(true, StatusCode::TOO_MANY_REQUESTS)]
+    #[case::available(false, StatusCode::OK)]
+    #[test_log::test(tokio::test)]
+    async fn test_limit_concurrency(
+        state: ShuttleAppState,
+        #[case] saturated: bool,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let state = ShuttleAppState {
+            concurrency: Arc::new(
+                ConcurrencyLimits::default()
+                    .with_limit(HeavyWork::StarChart, 1)
+                    .with_max_wait(Duration::from_millis(10)),
+            ),
+            ..state
+        };
+        let service = TestService::from(
+            Router::new()
+                .route("/22/rocket", routing::get(|| async { "ok" }))
+                .layer(from_fn_with_state(state.clone(), super::limit_concurrency))
+                .with_state(state.clone()),
+        );
+
+        let held = if saturated {
+            state.concurrency.acquire(HeavyWork::StarChart).await
+        } else {
+            None
+        };
+
+        let response = service.resolve("/22/rocket").await?;
+
+        assert_eq!(expected_status, response.status());
+        assert_eq!(
+            usize::from(!saturated),
+            state.concurrency.available(HeavyWork::StarChart)
+        );
+
+        drop(held);
+
+        assert_eq!(1, state.concurrency.available(HeavyWork::StarChart));
+
+        Ok(())
+    }
+
+    /// Test that paths are attributed to the right kind of rate-limited work
+    #[rstest]
+    #[case::pokemon_weight("/8/weight/25", Some(RateLimitedWork::Pokemon))]
+    #[case::pokemon_drop("/8/drop/25", Some(RateLimitedWork::Pokemon))]
+    #[case::cookie("/20/cookie", Some(RateLimitedWork::CookieHunt))]
+    #[case::country("/21/country/0100111101001001", Some(RateLimitedWork::Geocoding))]
+    #[case::coords("/21/coords/0100111101001001", None)]
+    #[case::archive("/20/archive_files", None)]
+    #[test_log::test]
+    fn test_rate_limited_work_of(#[case] path: &str, #[case] expected: Option<RateLimitedWork>) {
+        assert_eq!(expected, RateLimitedWork::of(path));
+    }
+
+    /// Test that rate limits are read from the
+    /// service's secrets, ignoring invalid ones
+    #[rstest]
+    #[test_log::test]
+    fn test_rate_limits_from_secrets() {
+        let secrets = SecretStore::new(BTreeMap::from([
+            (
+                String::from("RATE_LIMIT_POKEMON"),
+                String::from("120").into(),
+            ),
+            (
+                String::from("RATE_LIMIT_GEOCODING_BURST"),
+                String::from("2").into(),
+            ),
+            (
+                String::from("RATE_LIMIT_COOKIE_HUNT"),
+                String::from("0").into(),
+            ),
+            (
+                String::from("RATE_LIMIT_GLOBAL"),
+                String::from("600").into(),
+            ),
+        ]));
+        let clock = Arc::new(FakeClock::new("2023-12-25T00:00:00Z".parse().unwrap()));
+
+        let limits = RateLimits::from_secrets(&secrets, clock);
+
+        assert_eq!(
+            Some(RateLimit::new(120, 120)),
+            limits.limit(RateLimitedWork::Pokemon)
+        );
+        assert_eq!(None, limits.limit(RateLimitedWork::CookieHunt));
+        assert_eq!(None, limits.limit(RateLimitedWork::Geocoding));
+        assert_eq!(Some(RateLimit::new(600, 600)), limits.global);
+    }
+
+    /// Test that a client's bucket empties after a burst, refills
+    /// over time, and doesn't affect other clients or routes
+    #[rstest]
+    #[test_log::test]
+    fn test_rate_limit_buckets() {
+        let clock = Arc::new(FakeClock::new("2023-12-25T00:00:00Z".parse().unwrap()));
+        let limits = RateLimits::new(clock.clone())
+            .with_limit(RateLimitedWork::CookieHunt, RateLimit::new(6, 2));
+        let (client, other) = (
+            "203.0.113.7".parse().unwrap(),
+            "203.0.113.8".parse().unwrap(),
+        );
+
+        assert_eq!(Ok(()), limits.check(client, "/20/cookie"));
+        assert_eq!(Ok(()), limits.check(client, "/20/cookie"));
+        assert_eq!(
+            Err(Duration::from_secs(10)),
+            limits.check(client, "/20/cookie")
+        );
+        assert_eq!(Ok(()), limits.check(other, "/20/cookie"));
+        assert_eq!(Ok(()), limits.check(client, "/20/archive_files"));
+
+        clock.advance(chrono::Duration::seconds(4));
+
+        assert_eq!(
+            Err(Duration::from_secs(6)),
+            limits.check(client, "/20/cookie")
+        );
+
+        clock.advance(chrono::Duration::seconds(6));
+
+        assert_eq!(Ok(()), limits.check(client, "/20/cookie"));
+        assert_eq!(2, limits.limited());
+    }
+
+    /// Test that `limit_rates` rejects a client's requests beyond
+    /// its limits with a `Retry-After`, leaving other clients be
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_limit_rates(state: ShuttleAppState) -> anyhow::Result<()> {
+        let state = ShuttleAppState {
+            rate_limits: Arc::new(
+                RateLimits::new(state.clock.clone())
+                    .with_limit(RateLimitedWork::Geocoding, RateLimit::new(2, 1)),
+            ),
+            ..state
+        };
+        let router = Router::new()
+            .route("/21/country/:binary", routing::get(|| async { "ok" }))
+            .layer(from_fn_with_state(state.clone(), super::limit_rates))
+            .with_state(state.clone());
+        let request = |client: &str| {
+            Request::get("/21/country/0100111101001001")
+                .header("x-forwarded-for", client)
+                .body(Body::empty())
+        };
+
+        let response = TestService::from(router.clone())
+            .resolve(request("203.0.113.7")?)
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let response = TestService::from(router.clone())
+            .resolve(request("203.0.113.7")?)
+            .await?;
+
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap();
+
+        assert!((1..=30).contains(&retry_after));
+
+        let response = TestService::from(router.clone())
+            .resolve(request("203.0.113.8")?)
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(1, state.rate_limits.limited());
+
+        Ok(())
+    }
+
+    /// Test that a single client's burst of day 8 requests (like the
+    /// grader's) passes the full router unless a limit is configured
+    #[rstest]
+    #[case::unconfigured(&[], 0)]
+    #[case::configured(&[("RATE_LIMIT_POKEMON", "60"), ("RATE_LIMIT_POKEMON_BURST", "10")], 40)]
+    #[test_log::test(tokio::test)]
+    async fn test_router_rate_limits(
+        state: ShuttleAppState,
+        #[case] secrets: &[(&str, &str)],
+        #[case] expected_limited: usize,
+    ) -> anyhow::Result<()> {
+        let secrets = SecretStore::new(
+            secrets
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string().into()))
+                .collect(),
+        );
+        let state = ShuttleAppState {
+            rate_limits: Arc::new(RateLimits::from_secrets(
+                &secrets,
+                Arc::new(FakeClock::new("2023-12-25T00:00:00Z".parse().unwrap())),
+            )),
+            ..state
+        };
+        let peer = SocketAddr::from(([203, 0, 113, 7], 40000));
+
+        let mut limited = 0;
+
+        for _ in 0..50 {
+            let mut request = Request::get(routes::day8::cache_status()).body(Body::empty())?;
+            request.extensions_mut().insert(ConnectInfo(peer));
+
+            let response = TestService::from(router(state.clone()))
+                .resolve(request)
+                .await?;
+
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                limited += 1;
+            }
+        }
+
+        assert_eq!(expected_limited, limited);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,373 @@
+//! ### Named Counters
+//!
+//! Persistent, atomically updated counters (of which
+//! the [Day 19](super::day_19) view counter is one)
+
+// Standard Library Imports
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+// Third-Party Imports
+use axum::extract::{Path, State};
+use tokio::{
+    sync::Notify,
+    time::{timeout_at, Instant},
+};
+
+// Crate-Level Imports
+use crate::{
+    persistence::{EncryptedPersistError as PersistenceError, EncryptedPersistence as Persistence},
+    responses::{ApiOk, ApiResult},
+    state::ShuttleAppState,
+    validation::ValidationErrors,
+};
+
+/// The name of the counter tracking "seen" Day 19 chat messages
+pub const VIEWS_COUNTER: &str = "views";
+
+/// The maximum length of a counter's name
+const MAX_NAME_LENGTH: usize = 64;
+
+// <editor-fold desc="// Counter ...">
+
+/// A single named counter
+#[derive(Debug, Default)]
+pub struct Counter {
+    /// the counter's current value
+    value: AtomicU64,
+    /// whether the value has changed since it was last persisted
+    dirty: AtomicBool,
+    /// notifier for changes to the value
+    changed: Notify,
+}
+
+impl Counter {
+    /// Create a counter starting from `value`
+    fn starting_at(value: u64) -> Self {
+        Self {
+            value: AtomicU64::new(value),
+            ..Self::default()
+        }
+    }
+
+    /// Get the counter's current value
+    pub fn value(&self) -> u64 {
+        self.value.load(Ordering::SeqCst)
+    }
+
+    /// Increment the counter, returning its new value
+    pub fn increment(&self) -> u64 {
+        let value = self.value.fetch_add(1u64, Ordering::SeqCst) + 1;
+
+        self.dirty.store(true, Ordering::SeqCst);
+        self.changed.notify_waiters();
+
+        value
+    }
+
+    /// Zero the counter, returning its value prior to being zeroed
+    pub fn reset(&self) -> u64 {
+        let previous = self.value.swap(0u64, Ordering::SeqCst);
+
+        self.dirty.store(true, Ordering::SeqCst);
+        self.changed.notify_waiters();
+
+        previous
+    }
+
+    /// Wait until the counter exceeds `since` or the
+    /// `deadline` passes (whichever comes first),
+    /// returning the then-current value
+    pub async fn wait_for_change(&self, since: u64, deadline: Instant) -> u64 {
+        loop {
+            let changed = self.changed.notified();
+            tokio::pin!(changed);
+            changed.as_mut().enable();
+
+            if since < self.value() || timeout_at(deadline, changed).await.is_err() {
+                return self.value();
+            }
+        }
+    }
+}
+
+// </editor-fold desc="// Counter ...">
+
+// <editor-fold desc="// Counters ...">
+
+/// The service's named counters, periodically flushed to its
+/// persistent key-value store (by the [scheduler](crate::scheduler))
+#[derive(Debug)]
+pub struct Counters {
+    /// the counters created so far, by name
+    counters: Mutex<BTreeMap<String, Arc<Counter>>>,
+    /// where counters are persisted (if anywhere)
+    persistence: Option<Persistence>,
+}
+
+impl Default for Counters {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl Counters {
+    /// Create an empty set of counters, persisted
+    /// to the supplied store (if any)
+    pub fn new(persistence: Option<Persistence>) -> Self {
+        Self {
+            counters: Mutex::new(BTreeMap::new()),
+            persistence,
+        }
+    }
+
+    /// The persistent store key of the named counter
+    fn key(name: &str) -> String {
+        format!("counter-{name}")
+    }
+
+    /// Check that the supplied name is usable as a counter's name
+    pub fn validate_name(name: &str) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if name.is_empty() || MAX_NAME_LENGTH < name.len() {
+            errors.add(
+                "name",
+                format!("must be between 1 and {MAX_NAME_LENGTH} characters long"),
+            );
+        }
+
+        if !name
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_')
+        {
+            errors.add(
+                "name",
+                "may only contain ASCII letters, digits, hyphens, and underscores",
+            );
+        }
+
+        errors.into_result()
+    }
+
+    /// Get the named counter, creating it (from
+    /// its persisted value, if any) if necessary
+    pub fn named(&self, name: &str) -> Arc<Counter> {
+        self.counters
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                let persisted = self
+                    .persistence
+                    .as_ref()
+                    .and_then(|store| store.load::<u64>(&Self::key(name)).ok())
+                    .unwrap_or_default();
+
+                Arc::new(Counter::starting_at(persisted))
+            })
+            .clone()
+    }
+
+    /// Persist every counter that's changed since it was
+    /// last persisted, returning the number persisted
+    pub fn flush(&self) -> Result<usize, PersistenceError> {
+        let Some(store) = self.persistence.as_ref() else {
+            return Ok(0);
+        };
+
+        let changed = self
+            .counters
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, counter)| counter.dirty.swap(false, Ordering::SeqCst))
+            .map(|(name, counter)| (name.clone(), counter.clone()))
+            .collect::<Vec<_>>();
+
+        for (name, counter) in &changed {
+            if let Err(error) = store.save(&Self::key(name), counter.value()) {
+                counter.dirty.store(true, Ordering::SeqCst);
+                return Err(error);
+            }
+        }
+
+        Ok(changed.len())
+    }
+
+    /// Remove the persisted values of counters that are
+    /// zero (which is what a missing value restores to),
+    /// returning the number of values removed
+    pub fn compact(&self) -> Result<usize, PersistenceError> {
+        let Some(store) = self.persistence.as_ref() else {
+            return Ok(0);
+        };
+
+        let mut removed = 0usize;
+
+        for key in store.list()? {
+            if key.starts_with(&Self::key(""))
+                && store.load::<u64>(&key).is_ok_and(|value| value == 0)
+            {
+                store.remove(&key)?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+// </editor-fold desc="// Counters ...">
+
+/// Get the named counter (if its name is valid)
+fn counter(state: &ShuttleAppState, name: &str) -> Result<Arc<Counter>, ValidationErrors> {
+    Counters::validate_name(name).map(|()| state.counters.named(name))
+}
+
+/// Get the named counter's current value
+#[tracing::instrument(ret, skip(state))]
+pub async fn get_counter(
+    Path(name): Path<String>,
+    State(state): State<ShuttleAppState>,
+) -> ApiResult<u64> {
+    Ok(ApiOk(counter(&state, &name)?.value()))
+}
+
+/// Increment the named counter, responding with its new value
+#[tracing::instrument(ret, skip(state))]
+pub async fn increment_counter(
+    Path(name): Path<String>,
+    State(state): State<ShuttleAppState>,
+) -> ApiResult<u64> {
+    Ok(ApiOk(counter(&state, &name)?.increment()))
+}
+
+/// Zero the named counter, responding with its value prior to being zeroed
+#[tracing::instrument(ret, skip(state))]
+pub async fn reset_counter(
+    Path(name): Path<String>,
+    State(state): State<ShuttleAppState>,
+) -> ApiResult<u64> {
+    Ok(ApiOk(counter(&state, &name)?.reset()))
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::sync::Arc;
+
+    // Third-Party Imports
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use shuttle_persist::PersistInstance as Persistence;
+
+    // Crate-Level Imports
+    use super::{Counters, VIEWS_COUNTER};
+    use crate::router;
+    use crate::routes;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{state, TestService};
+
+    /// Test that the counter endpoints increment,
+    /// report, and reset independent counters
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_counter_endpoints(state: ShuttleAppState) -> anyhow::Result<()> {
+        let state = ShuttleAppState {
+            counters: Arc::new(Counters::default()),
+            ..state
+        };
+
+        for (method, url, expected) in [
+            (Method::POST, routes::counters::incr("apples"), "1"),
+            (Method::POST, routes::counters::incr("apples"), "2"),
+            (Method::POST, routes::counters::incr("pears"), "1"),
+            (Method::GET, routes::counters::counter("apples"), "2"),
+            (Method::POST, routes::counters::reset("apples"), "2"),
+            (Method::GET, routes::counters::counter("apples"), "0"),
+            (Method::GET, routes::counters::counter("pears"), "1"),
+        ] {
+            let response = TestService::from(router(state.clone()))
+                .resolve(
+                    Request::builder()
+                        .method(method)
+                        .uri(url)
+                        .body(Body::empty())?,
+                )
+                .await?;
+
+            assert_eq!(StatusCode::OK, response.status());
+
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+
+            assert_eq!(expected, String::from_utf8_lossy(&content));
+        }
+
+        Ok(())
+    }
+
+    /// Test that counters with unusable names are rejected
+    #[rstest]
+    #[case::punctuation("not.a.name")]
+    #[case::overlong(&"x".repeat(65))]
+    #[test_log::test(tokio::test)]
+    async fn test_invalid_counter_names(
+        state: ShuttleAppState,
+        #[case] name: &str,
+    ) -> anyhow::Result<()> {
+        let response = TestService::from(router(state))
+            .resolve(routes::counters::counter(name).as_str())
+            .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+        Ok(())
+    }
+
+    /// Test that flushed counters are restored
+    /// from the persistent store, and that
+    /// unchanged counters aren't re-persisted
+    #[rstest]
+    #[test_log::test]
+    fn test_counter_persistence() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let counters = Counters::new(Some(
+            Persistence::new(directory.path().to_path_buf())?.into(),
+        ));
+
+        counters.named("apples").increment();
+        counters.named("apples").increment();
+        counters.named("pears");
+
+        assert_eq!(1, counters.flush()?);
+        assert_eq!(0, counters.flush()?);
+
+        let restored = Counters::new(Some(
+            Persistence::new(directory.path().to_path_buf())?.into(),
+        ));
+
+        assert_eq!(2, restored.named("apples").value());
+        assert_eq!(0, restored.named("pears").value());
+        assert_eq!(0, restored.named(VIEWS_COUNTER).value());
+
+        restored.named("apples").reset();
+
+        assert_eq!(1, restored.flush()?);
+        assert_eq!(1, restored.compact()?);
+        assert_eq!(0, restored.compact()?);
+
+        Ok(())
+    }
+}
@@ -7,54 +7,112 @@ use std::collections::HashMap;
 
 // Third-Party Imports
 use axum::{
-    async_trait,
-    extract::{rejection::PathRejection, FromRequestParts, Json, Path},
+    extract::{FromRequestParts, Json, Path},
     http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
 };
 use serde_json::Value;
+use tracing::field::display;
+
+// Crate-Level Imports
+use crate::{
+    record_fields, routes,
+    solutions::{DayModule, DayRoutes},
+    utils::VariadicPathValues,
+};
 
 type NonNumericPacketIdResponse = (StatusCode, Json<HashMap<String, Vec<Value>>>);
 
-// <editor-fold desc="// VariadicPathValues ...">
+/// XOR the supplied packet ids together,
+/// then cube the result (if it fits in an `i64`)
+fn _sled_id<Ids: IntoIterator<Item = i64>>(
+    packet_ids: Ids,
+) -> Result<Json<i64>, NonNumericPacketIdResponse> {
+    let xor = packet_ids.into_iter().fold(0i64, BitXor::bitxor);
 
-/// [`axum` extractor](axum::extract) for
-/// variadic path values (e.g. `/endpoint/*values`)
-#[derive(Debug)]
-pub struct VariadicPathValues(pub Vec<Value>);
-
-#[async_trait]
-impl<State: Send + Sync> FromRequestParts<State> for VariadicPathValues {
-    type Rejection = PathRejection;
-
-    async fn from_request_parts(
-        parts: &mut Parts,
-        state: &State,
-    ) -> anyhow::Result<Self, Self::Rejection> {
-        let values = <Path<String> as FromRequestParts<State>>::from_request_parts(parts, state)
-            .await?
-            .split('/')
-            .map(|part| {
-                serde_json::from_str::<Value>(part)
-                    .map_or_else(|_| Value::from(part), |value| value)
-            })
-            .collect::<Vec<Value>>();
-
-        Ok(Self(values))
-    }
+    xor.checked_pow(3u32).map(Json).ok_or_else(|| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(HashMap::from([(
+                String::from("overflowing sled id"),
+                vec![Value::from(xor)],
+            )])),
+        )
+    })
 }
 
-// </editor-fold desc="// VariadicPathValues ...">
+/// Parse the supplied path segment as a packet id, if it's
+/// one written exactly as [`VariadicPathValues`] would parse
+/// it (e.g. neither `+4` nor `04`, which it treats as text)
+fn _packet_id(segment: &str) -> Option<i64> {
+    segment
+        .parse::<i64>()
+        .ok()
+        .filter(|id| id.to_string() == segment)
+}
+
+/// Route requests carrying exactly two packet ids to
+/// [`cube_the_bits`], and all others to [`calculate_sled_id`]
+///
+/// Note: `axum`'s router can't register `/1/:num1/:num2`
+/// alongside `/1/*packets`, so the two-segment fast path is
+/// chosen here (by the number of captured segments, less
+/// any trailing slash's) rather than by the router itself,
+/// parsing both ids straight from the captured path before
+/// falling back to [`VariadicPathValues`] for anything else
+#[tracing::instrument(ret, skip_all)]
+pub async fn dispatch_sled_id(Path(path): Path<String>, mut parts: Parts) -> Response {
+    let trimmed = path.strip_suffix('/').unwrap_or(&path);
+
+    if let Some((left, right)) = trimmed
+        .split_once('/')
+        .and_then(|(left, right)| Some((_packet_id(left)?, _packet_id(right)?)))
+    {
+        return _sled_id([left, right]).into_response();
+    }
+
+    let mut packets =
+        match <VariadicPathValues as FromRequestParts<()>>::from_request_parts(&mut parts, &())
+            .await
+        {
+            Ok(VariadicPathValues(packets)) => packets,
+            Err(rejection) => return rejection.into_response(),
+        };
+
+    if packets.len() > 1 && packets.last().and_then(Value::as_str) == Some("") {
+        packets.pop();
+    }
+
+    match packets.as_slice() {
+        [left, right] => cube_the_bits(left, right).into_response(),
+        _ => calculate_sled_id(VariadicPathValues(packets))
+            .await
+            .into_response(),
+    }
+}
 
 /// Complete [Day 1: Challenge](https://console.shuttle.rs/cch/challenge/1#:~:text=⭐)
-#[allow(dead_code)]
-#[cfg_attr(tarpaulin, coverage(off))]
-#[cfg_attr(tarpaulin, tarpaulin::skip)]
-#[tracing::instrument(ret, skip(values), fields(left, right))]
-pub async fn cube_the_bits(Path(values): Path<(u32, u32)>) -> Json<u32> {
-    tracing::Span::current()
-        .record("left", values.0)
-        .record("right", values.1);
-    Json(BitXor::bitxor(values.0, values.1).pow(3u32))
+/// for exactly two packet ids, without partitioning them like [`calculate_sled_id`]
+#[tracing::instrument(ret, skip_all, fields(left, right))]
+pub fn cube_the_bits(left: &Value, right: &Value) -> Result<Json<i64>, NonNumericPacketIdResponse> {
+    record_fields!("left" => display(left), "right" => display(right));
+
+    match (left, right) {
+        (Value::Number(_), Value::Number(_)) => {
+            _sled_id([left, right].into_iter().filter_map(Value::as_i64))
+        }
+        _ => Err((
+            StatusCode::BAD_REQUEST,
+            Json(HashMap::from([(
+                String::from("non-numeric packet ids"),
+                [left, right]
+                    .into_iter()
+                    .filter(|value| !value.is_number())
+                    .cloned()
+                    .collect(),
+            )])),
+        )),
+    }
 }
 
 /// Complete [Day 1: Bonus](https://console.shuttle.rs/cch/challenge/1#:~:text=🎁)
@@ -73,13 +131,7 @@ pub async fn calculate_sled_id(
     }
 
     if invalid_packets.is_empty() {
-        Ok(Json(
-            packet_ids
-                .iter()
-                .filter_map(Value::as_i64)
-                .fold(0i64, BitXor::bitxor)
-                .pow(3u32),
-        ))
+        _sled_id(packet_ids.iter().filter_map(Value::as_i64))
     } else {
         Err((
             StatusCode::BAD_REQUEST,
@@ -91,6 +143,33 @@ pub async fn calculate_sled_id(
     }
 }
 
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 1](https://console.shuttle.rs/cch/challenge/1) solutions
+#[derive(Debug)]
+pub struct Day1;
+
+impl DayModule for Day1 {
+    fn day() -> i8 {
+        1
+    }
+
+    fn name() -> &'static str {
+        "Packet Cubes"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new().route(day_route!(
+            1,
+            GET routes::day1::PACKETS,
+            dispatch_sled_id,
+            "Calculate a sled id from packet ids"
+        ))
+    }
+}
+
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -119,17 +198,21 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
+    use crate::routes;
     use crate::utils::{service, TestService};
 
     /// Test that `calculate_sled_id`
     /// satisfies the conditions of [CCH 2023 Challenge 1](https://console.shuttle.rs/cch/challenge/1)
     #[rstest]
-    #[case::challenge_example("/1/4/8", StatusCode::OK, b"1728")]
-    #[case::bonus_example_one("/1/10", StatusCode::OK, b"1000")]
-    #[case::bonus_example_two("/1/4/5/8/10", StatusCode::OK, b"27")]
-    #[case::negative_packet_ids("/1/-12/45/-6", StatusCode::OK, b"42875")]
-    #[case::mixed_packet_ids("/1/95/7552/sixty-four", StatusCode::BAD_REQUEST, b"0")]
-    #[case::non_numeric_packet_ids("/1/fifty-five/fourteen", StatusCode::BAD_REQUEST, b"0")]
+    #[case::challenge_example(&routes::day1::packets([4, 8]), StatusCode::OK, b"1728")]
+    #[case::bonus_example_one(&routes::day1::packets([10]), StatusCode::OK, b"1000")]
+    #[case::bonus_example_two(&routes::day1::packets([4, 5, 8, 10]), StatusCode::OK, b"27")]
+    #[case::negative_packet_ids(&routes::day1::packets([-12, 45, -6]), StatusCode::OK, b"42875")]
+    #[case::mixed_packet_ids(&routes::day1::packets(["95", "7552", "sixty-four"]), StatusCode::BAD_REQUEST, b"0")]
+    #[case::non_numeric_packet_ids(&routes::day1::packets(["fifty-five", "fourteen"]), StatusCode::BAD_REQUEST, b"0")]
+    #[case::trailing_slash("/1/4/", StatusCode::OK, b"64")]
+    #[case::two_packets_trailing_slash("/1/4/8/", StatusCode::OK, b"1728")]
+    #[case::zero_padded_packet_id("/1/04/8", StatusCode::BAD_REQUEST, b"0")]
     #[test_log::test(tokio::test)]
     async fn test_challenge_one(
         service: TestService,
@@ -165,4 +248,63 @@ mod tests {
             String::from_utf8_lossy(content.as_ref()),
         ))
     }
+
+    /// Test that `cube_the_bits` handles the two-packet
+    /// fast path identically to `calculate_sled_id`
+    #[rstest]
+    #[case::challenge_example(routes::day1::cube(4, 8), StatusCode::OK, "1728")]
+    #[case::negative_packet_ids(routes::day1::cube(-3, 5), StatusCode::OK, "-512")]
+    #[case::wide_packet_ids(routes::day1::cube(1 << 20, 0), StatusCode::OK, "1152921504606846976")]
+    #[case::trailing_slash(routes::day1::cube(4, "8/"), StatusCode::OK, "1728")]
+    #[case::overflowing_sled_id(
+        routes::day1::cube(i64::MAX, 0),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        r#"{"overflowing sled id":[9223372036854775807]}"#
+    )]
+    #[case::one_non_numeric(
+        routes::day1::cube(4, "eight"),
+        StatusCode::BAD_REQUEST,
+        r#"{"non-numeric packet ids":["eight"]}"#
+    )]
+    #[case::both_non_numeric(
+        routes::day1::cube("four", "eight"),
+        StatusCode::BAD_REQUEST,
+        r#"{"non-numeric packet ids":["four","eight"]}"#
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_cube_the_bits(
+        service: TestService,
+        #[case] url: String,
+        #[case] expected_status: StatusCode,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let response = service.resolve(url.as_str()).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+
+        assert_eq!(expected_content, String::from_utf8_lossy(content.as_ref()));
+
+        Ok(())
+    }
+
+    /// Test that `calculate_sled_id` rejects crafted paths
+    /// before parsing any of their segments
+    #[rstest]
+    #[case::too_many_segments(routes::day1::packets(0..100), StatusCode::UNPROCESSABLE_ENTITY)]
+    #[case::too_long(routes::day1::packets(["1".repeat(4096)]), StatusCode::URI_TOO_LONG)]
+    #[case::within_limits(routes::day1::packets(0..64), StatusCode::OK)]
+    #[test_log::test(tokio::test)]
+    async fn test_path_limits(
+        service: TestService,
+        #[case] url: String,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let response = service.resolve(url.as_str()).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        Ok(())
+    }
 }
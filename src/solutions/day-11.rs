@@ -1,30 +1,243 @@
 //! ### CCH 2023 Day 11 Solutions
 //!
 
+// Standard Library Imports
+use std::path::{Component, Path as FilePath};
+
 // Third-Party Imports
 use axum::{
-    body::Body,
-    extract::{multipart::Multipart, Json, Path},
-    http::{Request, StatusCode},
-    response::IntoResponse,
+    body::{Body, Bytes},
+    extract::{multipart::Multipart, DefaultBodyLimit, Json, Path, State},
+    http::{header, HeaderValue, Request, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+use image_rs::{
+    codecs::{jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+    DynamicImage, GenericImageView, ImageResult,
 };
-use image_rs::GenericImageView;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use tower::ServiceExt;
 use tower_http::services::ServeFile;
 
 // Crate-Level Imports
-use crate::utils;
+use crate::{
+    abuse::Suspicious,
+    assets::AssetEntry,
+    errors::{AppError, ErrorEnvelope},
+    record_fields, routes,
+    solutions::{day_22::CACHE_STATUS_HEADER, DayModule, DayRoutes},
+    state::ShuttleAppState,
+    telemetry::fields,
+    templating, utils,
+    validation::{Validate, ValidatedQuery, ValidationErrors},
+};
+
+/// The quality of converted JPEG images when the client doesn't specify one
+const DEFAULT_JPEG_QUALITY: u8 = 80;
+
+/// The largest width or height of a generated thumbnail
+const MAX_THUMBNAIL_DIMENSION: u32 = 1024;
+
+/// How long (in seconds) clients may reuse a generated thumbnail
+const THUMBNAIL_MAX_AGE: u32 = 24 * 60 * 60;
+
+/// The characters percent-encoded in asset listing links
+const ASSET_NAME_ESCAPES: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.');
+
+// <editor-fold desc="// AssetLink ...">
+
+/// A single (linked) entry in the [static asset listing](list_static_assets)
+#[derive(Debug, Serialize)]
+pub struct AssetLink {
+    /// the asset's path, relative to the assets directory
+    pub name: String,
+    /// the asset's size (in bytes)
+    pub size: u64,
+    /// the path from which the asset is served
+    pub href: String,
+}
+
+impl From<&AssetEntry> for AssetLink {
+    fn from(entry: &AssetEntry) -> Self {
+        Self {
+            name: entry.name.clone(),
+            size: entry.size,
+            href: routes::day11::assets(utf8_percent_encode(&entry.name, ASSET_NAME_ESCAPES)),
+        }
+    }
+}
+
+// </editor-fold desc="// AssetLink ...">
+
+// <editor-fold desc="// ConversionOptions ...">
+
+/// The formats images may be [converted](convert_image) to
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversionFormat {
+    /// Portable Network Graphics
+    Png,
+    /// JPEG (at the requested quality)
+    Jpeg,
+    /// (Lossless) WebP
+    Webp,
+}
+
+impl ConversionFormat {
+    /// The format's media type
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::Webp => "image/webp",
+        }
+    }
+
+    /// Encode the supplied image in the format
+    pub fn encode(self, image: &DynamicImage, quality: Option<u8>) -> ImageResult<Vec<u8>> {
+        let mut encoded = Vec::new();
+
+        match self {
+            Self::Png => image.write_with_encoder(PngEncoder::new(&mut encoded)),
+            Self::Jpeg => DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(
+                JpegEncoder::new_with_quality(
+                    &mut encoded,
+                    quality.unwrap_or(DEFAULT_JPEG_QUALITY),
+                ),
+            ),
+            Self::Webp => DynamicImage::ImageRgba8(image.to_rgba8())
+                .write_with_encoder(WebPEncoder::new_lossless(&mut encoded)),
+        }?;
+
+        Ok(encoded)
+    }
+}
+
+/// Query parameters for [image conversion](convert_image)
+#[derive(Debug, Deserialize)]
+pub struct ConversionOptions {
+    /// The format to convert the image to
+    format: ConversionFormat,
+    /// The quality (1-100) of converted JPEG images
+    #[serde(default)]
+    quality: Option<u8>,
+}
+
+impl Validate for ConversionOptions {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if let Some(quality) = self.quality {
+            if self.format != ConversionFormat::Jpeg {
+                errors.add("quality", "is only supported for jpeg conversions");
+            }
+
+            if !(1..=100).contains(&quality) {
+                errors.add("quality", "must be between 1 and 100");
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+// </editor-fold desc="// ConversionOptions ...">
+
+// <editor-fold desc="// ThumbnailSize ...">
+
+/// Query parameters bounding a generated [thumbnail](serve_asset_thumbnail)
+#[derive(Debug, Deserialize)]
+pub struct ThumbnailSize {
+    /// The thumbnail's maximum width
+    #[serde(default)]
+    w: Option<u32>,
+    /// The thumbnail's maximum height
+    #[serde(default)]
+    h: Option<u32>,
+}
+
+impl ThumbnailSize {
+    /// The thumbnail's maximum width and height
+    /// (defaulting either one to the largest allowed)
+    pub fn bounds(&self) -> (u32, u32) {
+        (
+            self.w.unwrap_or(MAX_THUMBNAIL_DIMENSION),
+            self.h.unwrap_or(MAX_THUMBNAIL_DIMENSION),
+        )
+    }
+}
+
+impl Validate for ThumbnailSize {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if self.w.is_none() && self.h.is_none() {
+            errors.add("w", "either w or h (or both) must be supplied");
+        }
+
+        for (field, value) in [("w", self.w), ("h", self.h)] {
+            if value.is_some_and(|value| !(1..=MAX_THUMBNAIL_DIMENSION).contains(&value)) {
+                errors.add(
+                    field,
+                    format!("must be between 1 and {MAX_THUMBNAIL_DIMENSION}"),
+                );
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+// </editor-fold desc="// ThumbnailSize ...">
+
+/// Resolve the named asset's path, provided it
+/// stays within the assets directory
+fn _asset_path(asset: &str) -> Option<String> {
+    (!asset.is_empty()
+        && FilePath::new(asset)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_))))
+    .then(|| format!("assets/{asset}"))
+}
+
+/// Check if the request's `If-None-Match` header matches the supplied `ETag`
+fn _etag_matches(request: &Request<Body>, etag: &HeaderValue) -> bool {
+    request
+        .headers()
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || etag.to_str().is_ok_and(|etag| tag == etag))
+}
 
 /// Complete [Day 11: Challenge](https://console.shuttle.rs/cch/challenge/11#:~:text=⭐)
-#[tracing::instrument(skip_all, fields(error))]
+#[tracing::instrument(skip_all, fields({fields::ERROR}))]
 pub async fn serve_static_asset(
+    State(state): State<ShuttleAppState>,
     Path(asset): Path<String>,
     request: Request<Body>,
-) -> impl IntoResponse {
-    ServeFile::new(format!("assets/{asset}"))
+) -> Result<Response, AppError> {
+    let Some(path) = _asset_path(&asset) else {
+        tracing::warn!("rejected asset path: {asset}");
+        return Ok(Suspicious::flag(StatusCode::NOT_FOUND));
+    };
+
+    let etag = state.assets.get(&asset).map(AssetEntry::etag);
+
+    if let Some(etag) = etag.as_ref().filter(|etag| _etag_matches(&request, etag)) {
+        tracing::info!("asset unchanged for: {asset}");
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag.clone())]).into_response());
+    }
+
+    ServeFile::new(path)
         .oneshot(request)
         .await
-        .map(|response| {
+        .map(|mut response| {
             match response.status() {
                 StatusCode::OK => tracing::info!("resolved asset for: {asset}"),
                 StatusCode::NOT_FOUND => tracing::warn!("no asset found for: {asset}"),
@@ -33,54 +246,248 @@ pub async fn serve_static_asset(
                 ),
             };
 
+            if let Some(etag) = etag.filter(|_| response.status() == StatusCode::OK) {
+                response.headers_mut().insert(header::ETAG, etag);
+            }
+
             IntoResponse::into_response(response)
         })
         .map_err(|error| {
-            tracing::Span::current().record("error", &error.to_string());
-            StatusCode::UNPROCESSABLE_ENTITY
+            record_fields!(fields::ERROR => error.to_string());
+            ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, error).into()
         })
 }
 
+/// Serve a (cached) PNG thumbnail of the named asset,
+/// resized to fit within the requested bounds
+#[tracing::instrument(skip(state, size, request), fields({fields::CACHED}))]
+pub async fn serve_asset_thumbnail(
+    State(state): State<ShuttleAppState>,
+    Path(asset): Path<String>,
+    ValidatedQuery(size): ValidatedQuery<ThumbnailSize>,
+    request: Request<Body>,
+) -> Result<Response, AppError> {
+    let no_such_asset =
+        || ErrorEnvelope::new(StatusCode::NOT_FOUND, format!("no such asset: {asset}"));
+
+    let Some(path) = _asset_path(&asset) else {
+        tracing::warn!("rejected asset path: {asset}");
+        return Ok(Suspicious::flag(no_such_asset()));
+    };
+
+    let Some(entry) = state.assets.get(&asset) else {
+        return Err(no_such_asset().into());
+    };
+
+    let (width, height) = size.bounds();
+    let etag = format!("\"{}-{width}x{height}\"", entry.sha256);
+    let cache_control = format!("public, max-age={THUMBNAIL_MAX_AGE}");
+
+    if HeaderValue::from_str(&etag).is_ok_and(|etag| _etag_matches(&request, &etag)) {
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            [
+                (header::ETAG.as_str(), etag.as_str()),
+                (header::CACHE_CONTROL.as_str(), cache_control.as_str()),
+            ],
+        )
+            .into_response());
+    }
+
+    let key = format!("{asset}:{width}x{height}");
+    let cached = state.asset_cache.get(&key);
+
+    record_fields!(fields::CACHED => cached.is_some());
+
+    let (thumbnail, status) = match cached {
+        Some(thumbnail) => (thumbnail, "HIT"),
+        None => {
+            let thumbnail = utils::run_blocking(move || {
+                image_rs::open(path).and_then(|image| {
+                    ConversionFormat::Png
+                        .encode(&image.resize(width, height, FilterType::Lanczos3), None)
+                })
+            })
+            .await
+            .map(Bytes::from)
+            .map_err(|error| {
+                tracing::warn!("couldn't generate thumbnail for {asset}: {error:?}");
+                ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, error)
+            })?;
+
+            state.asset_cache.insert(key, thumbnail.clone());
+
+            (thumbnail, "MISS")
+        }
+    };
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE.as_str(),
+                ConversionFormat::Png.content_type(),
+            ),
+            (header::CACHE_CONTROL.as_str(), cache_control.as_str()),
+            (header::ETAG.as_str(), etag.as_str()),
+            (CACHE_STATUS_HEADER, status),
+        ],
+        thumbnail,
+    )
+        .into_response())
+}
+
+/// Render a (browsable) listing of the static assets
+#[tracing::instrument(skip_all)]
+pub async fn list_static_assets(
+    State(state): State<ShuttleAppState>,
+) -> Result<Html<String>, AppError> {
+    let assets = state
+        .assets
+        .entries()
+        .iter()
+        .filter(|entry| _asset_path(&entry.name).is_some())
+        .map(AssetLink::from)
+        .collect::<Vec<_>>();
+
+    templating::render(
+        &state.templates,
+        &state.template_limits,
+        "day-11/listing",
+        json!({ "assets": assets }),
+    )
+    .map(Html)
+    .map_err(AppError::from)
+}
+
 /// Complete [Day 11: Bonus](https://console.shuttle.rs/cch/challenge/11#:~:text=🎁)
 #[tracing::instrument(skip(request), fields(image.name, image.magic.red))]
 pub async fn calculate_magical_red_pixel_count(
     mut request: Multipart,
-) -> Result<Json<u64>, StatusCode> {
+) -> Result<Json<u64>, AppError> {
     let field = request
         .next_field()
         .await
         .map_err(|error| {
             tracing::error!("{error:?}");
-            StatusCode::UNPROCESSABLE_ENTITY
+            ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, error.body_text())
         })?
-        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+        .ok_or_else(|| ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, "no image supplied"))?;
 
-    tracing::Span::current().record("image.name", field.name().unwrap());
+    record_fields!("image.name" => field.name().unwrap_or_default());
 
-    let image = field
-        .bytes()
+    let data = utils::collect_multipart_field(field, utils::MAX_MULTIPART_FIELD_SIZE)
         .await
-        .map_err(|error| {
-            tracing::error!("{error:?}");
-        })
-        .and_then(|data| {
-            image_rs::load_from_memory(data.as_ref()).map_err(|error| {
-                tracing::error!("{error:?}");
-            })
-        })
-        .map_err(|()| StatusCode::UNPROCESSABLE_ENTITY)?;
+        .map_err(|(status, error)| {
+            tracing::error!("{error}");
+            AppError::from((status, error))
+        })?;
 
-    let magic_red_count = image
-        .pixels()
-        .map(utils::is_magic_red)
-        .map(u64::from)
-        .sum::<u64>();
+    let magic_red_count = utils::run_blocking(move || {
+        image_rs::load_from_memory(data.as_ref()).map(|image| {
+            image
+                .pixels()
+                .map(utils::is_magic_red)
+                .map(u64::from)
+                .sum::<u64>()
+        })
+    })
+    .await
+    .map_err(|error| {
+        tracing::error!("{error:?}");
+        ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, error)
+    })?;
 
-    tracing::Span::current().record("image.magic.red", magic_red_count);
+    record_fields!("image.magic.red" => magic_red_count);
 
     Ok(Json(magic_red_count))
 }
 
+/// Convert an uploaded image to the requested format
+#[tracing::instrument(skip(request), fields(image.name))]
+pub async fn convert_image(
+    ValidatedQuery(options): ValidatedQuery<ConversionOptions>,
+    mut request: Multipart,
+) -> Result<Response, AppError> {
+    let field = request
+        .next_field()
+        .await
+        .map_err(|error| ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, error.body_text()))?
+        .ok_or_else(|| ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, "no image supplied"))?;
+
+    if let Some(name) = field.name() {
+        record_fields!("image.name" => name);
+    }
+
+    let data = utils::collect_multipart_field(field, utils::MAX_MULTIPART_FIELD_SIZE)
+        .await
+        .map_err(AppError::from)?;
+
+    let ConversionOptions { format, quality } = options;
+
+    let converted = utils::run_blocking(move || {
+        image_rs::load_from_memory(&data).and_then(|image| format.encode(&image, quality))
+    })
+    .await
+    .map_err(|error| {
+        tracing::error!("{error:?}");
+        ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, error)
+    })?;
+
+    Ok(([(header::CONTENT_TYPE, format.content_type())], converted).into_response())
+}
+
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 11](https://console.shuttle.rs/cch/challenge/11) solutions
+#[derive(Debug)]
+pub struct Day11;
+
+impl DayModule for Day11 {
+    fn day() -> i8 {
+        11
+    }
+
+    fn name() -> &'static str {
+        "Decoration Imagery"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                11,
+                GET routes::day11::ASSETS,
+                serve_static_asset,
+                "Serve a static asset"
+            ))
+            .route(day_route!(
+                11,
+                GET routes::day11::THUMBNAIL,
+                serve_asset_thumbnail,
+                "Serve a static asset's thumbnail"
+            ))
+            .route(
+                day_route!(
+                    11,
+                    POST routes::day11::RED_PIXELS,
+                    calculate_magical_red_pixel_count,
+                    "Count an image's magical red pixels"
+                )
+                .map(|route| route.layer(DefaultBodyLimit::max(utils::MAX_MULTIPART_BODY_SIZE))),
+            )
+            .route(
+                day_route!(
+                    11,
+                    POST routes::day11::CONVERT,
+                    convert_image,
+                    "Convert an image's format"
+                )
+                .map(|route| route.layer(DefaultBodyLimit::max(utils::MAX_MULTIPART_BODY_SIZE))),
+            )
+    }
+}
+
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -89,7 +496,7 @@ mod tests {
 
     // Standard Library Imports
     use core::{cmp::PartialEq, fmt::Debug, ops::BitOr, str::FromStr};
-    use std::collections::HashMap;
+    use std::{collections::HashMap, io::Cursor};
 
     // Third-Party Imports
     use axum::{
@@ -101,6 +508,8 @@ mod tests {
         },
         routing::Router,
     };
+    use b64::{engine::general_purpose::STANDARD as base64, Engine};
+    use image_rs::{GenericImageView, ImageFormat, Rgba, RgbaImage};
     use once_cell::sync::Lazy;
     use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
     use rstest::{fixture, rstest};
@@ -109,5 +518,306 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
-    use crate::utils::{service, TestService};
+    use crate::abuse::Suspicious;
+    use crate::admin::BasicAuth;
+    use crate::router;
+    use crate::routes;
+    use crate::solutions::day_22::CACHE_STATUS_HEADER;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{service, state, TestService};
+
+    /// Build a `multipart/form-data` request (to the supplied
+    /// path) with a single field of the supplied content
+    fn multipart_request(path: &str, content: &[u8]) -> anyhow::Result<Request<Body>> {
+        let mut body = b"--boundary\r\n\
+            Content-Disposition: form-data; name=\"image\"; filename=\"image.png\"\r\n\
+            Content-Type: image/png\r\n\r\n"
+            .to_vec();
+
+        body.extend_from_slice(content);
+        body.extend_from_slice(b"\r\n--boundary--\r\n");
+
+        Ok(Request::post(path)
+            .header(
+                headers::CONTENT_TYPE,
+                "multipart/form-data; boundary=boundary",
+            )
+            .body(Body::from(body))?)
+    }
+
+    /// Test that `calculate_magical_red_pixel_count` satisfies the conditions
+    /// of [CCH 2023 Challenge 11](https://console.shuttle.rs/cch/challenge/11)
+    /// and enforces the multipart field size limit
+    #[rstest]
+    #[case::bonus_example(
+        std::fs::read(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/decoration.png")).unwrap(),
+        StatusCode::OK,
+        Some("73034")
+    )]
+    #[case::not_an_image(b"not an image".to_vec(), StatusCode::UNPROCESSABLE_ENTITY, None)]
+    #[case::field_too_large(
+        vec![0u8; crate::utils::MAX_MULTIPART_FIELD_SIZE + 1],
+        StatusCode::PAYLOAD_TOO_LARGE,
+        None
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_calculate_magical_red_pixel_count(
+        service: TestService,
+        #[case] content: Vec<u8>,
+        #[case] expected_status: StatusCode,
+        #[case] expected_content: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(multipart_request(routes::day11::red_pixels(), &content)?)
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if let Some(expected_content) = expected_content {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+
+            assert_eq!(expected_content, String::from_utf8_lossy(content.as_ref()));
+        }
+
+        Ok(())
+    }
+
+    /// Test that static assets are served with their
+    /// manifest `ETag`, and revalidated against it
+    #[rstest]
+    #[case::unconditional(None, StatusCode::OK)]
+    #[case::unchanged(Some(true), StatusCode::NOT_MODIFIED)]
+    #[case::changed(Some(false), StatusCode::OK)]
+    #[test_log::test(tokio::test)]
+    async fn test_serve_static_asset(
+        service: TestService,
+        #[case] revalidate: Option<bool>,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let manifest = crate::assets::AssetManifest::build(crate::assets::ASSETS_DIR)?;
+        let etag = manifest.get("decoration.png").unwrap().etag();
+
+        let mut request = Request::get(routes::day11::assets("decoration.png"));
+
+        if let Some(unchanged) = revalidate {
+            request = request.header(
+                headers::IF_NONE_MATCH,
+                if unchanged {
+                    etag.to_str()?
+                } else {
+                    "\"stale\""
+                },
+            );
+        }
+
+        let response = service.resolve(request.body(Body::empty())?).await?;
+
+        assert_eq!(expected_status, response.status());
+        assert_eq!(Some(&etag), response.headers().get(headers::ETAG));
+
+        Ok(())
+    }
+
+    /// Test that asset paths can't escape the assets directory,
+    /// and that attempts to are flagged as suspicious
+    #[rstest]
+    #[case::nested("day-4%2Fconsumer.tpl", StatusCode::OK)]
+    #[case::parent("..%2FCargo.toml", StatusCode::NOT_FOUND)]
+    #[case::nested_parent("day-4%2F..%2F..%2FCargo.toml", StatusCode::NOT_FOUND)]
+    #[case::absolute("%2Fetc%2Fhostname", StatusCode::NOT_FOUND)]
+    #[test_log::test(tokio::test)]
+    async fn test_static_asset_traversal(
+        service: TestService,
+        #[case] asset: &str,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(routes::day11::assets(asset).as_str())
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+        assert_eq!(
+            expected_status == StatusCode::NOT_FOUND,
+            response.extensions().get::<Suspicious>().is_some()
+        );
+
+        Ok(())
+    }
+
+    /// Test that the asset listing is only served, when
+    /// enabled, to requests bearing its credentials
+    #[rstest]
+    #[case::disabled(None, None, StatusCode::NOT_FOUND)]
+    #[case::missing_credentials(Some("elf:shelf"), None, StatusCode::UNAUTHORIZED)]
+    #[case::wrong_credentials(Some("elf:shelf"), Some("elf:sled"), StatusCode::UNAUTHORIZED)]
+    #[case::valid_credentials(Some("elf:shelf"), Some("elf:shelf"), StatusCode::OK)]
+    #[test_log::test(tokio::test)]
+    async fn test_list_static_assets(
+        state: ShuttleAppState,
+        #[case] configured: Option<&str>,
+        #[case] supplied: Option<&str>,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let state = ShuttleAppState {
+            asset_browser: BasicAuth::new(configured),
+            ..state
+        };
+
+        let mut request = Request::get(routes::day11::listing());
+
+        if let Some(credentials) = supplied {
+            request = request.header(
+                headers::AUTHORIZATION,
+                format!("Basic {}", base64.encode(credentials)),
+            );
+        }
+
+        let response = TestService::from(router(state))
+            .resolve(request.body(Body::empty())?)
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status == StatusCode::UNAUTHORIZED {
+            assert!(response.headers().contains_key(headers::WWW_AUTHENTICATE));
+        }
+
+        if expected_status == StatusCode::OK {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+            let content = String::from_utf8_lossy(content.as_ref());
+
+            assert!(content.contains(r#"<a href="/11/assets/decoration.png">decoration.png</a>"#));
+            assert!(content.contains(r#"<a href="/11/assets/day-4%2Fconsumer.tpl">"#));
+        }
+
+        Ok(())
+    }
+
+    /// Test that `convert_image` re-encodes uploaded
+    /// images in (only) the requested, valid format
+    #[rstest]
+    #[case::png("format=png", StatusCode::OK, Some(ImageFormat::Png))]
+    #[case::jpeg("format=jpeg&quality=50", StatusCode::OK, Some(ImageFormat::Jpeg))]
+    #[case::webp("format=webp", StatusCode::OK, Some(ImageFormat::WebP))]
+    #[case::unknown_format("format=gif", StatusCode::UNPROCESSABLE_ENTITY, None)]
+    #[case::lossless_quality("format=png&quality=50", StatusCode::UNPROCESSABLE_ENTITY, None)]
+    #[case::zero_quality("format=jpeg&quality=0", StatusCode::UNPROCESSABLE_ENTITY, None)]
+    #[test_log::test(tokio::test)]
+    async fn test_convert_image(
+        service: TestService,
+        #[case] query: &str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_format: Option<ImageFormat>,
+    ) -> anyhow::Result<()> {
+        let mut png = Cursor::new(Vec::new());
+        RgbaImage::from_pixel(4, 4, Rgba([0xff, 0x00, 0x00, 0x80]))
+            .write_to(&mut png, ImageFormat::Png)?;
+
+        let path = format!("{}?{query}", routes::day11::convert());
+        let response = service
+            .resolve(multipart_request(&path, png.get_ref())?)
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if let Some(expected_format) = expected_format {
+            assert_eq!(
+                Some(expected_format.to_mime_type()),
+                response
+                    .headers()
+                    .get(headers::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+            );
+
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+            let converted = image_rs::load_from_memory_with_format(&content, expected_format)?;
+
+            assert_eq!((4, 4), converted.dimensions());
+        }
+
+        Ok(())
+    }
+
+    /// Test that `convert_image` rejects content that isn't an image
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_convert_non_image(service: TestService) -> anyhow::Result<()> {
+        let path = format!("{}?format=png", routes::day11::convert());
+        let response = service
+            .resolve(multipart_request(&path, b"not an image")?)
+            .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+        Ok(())
+    }
+
+    /// Test that `serve_asset_thumbnail` resizes assets to fit
+    /// the requested bounds, caches them, and revalidates them
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_serve_asset_thumbnail(state: ShuttleAppState) -> anyhow::Result<()> {
+        let path = format!("{}?w=16&h=32", routes::day11::thumbnail("decoration.png"));
+
+        let mut etag = None;
+
+        for expected_cache_status in ["MISS", "HIT"] {
+            let response = TestService::from(router(state.clone()))
+                .resolve(path.as_str())
+                .await?;
+
+            assert_eq!(StatusCode::OK, response.status());
+            assert_eq!(
+                expected_cache_status,
+                response.headers()[CACHE_STATUS_HEADER]
+            );
+            assert_eq!("image/png", response.headers()[headers::CONTENT_TYPE]);
+            assert!(response.headers().contains_key(headers::CACHE_CONTROL));
+
+            etag = response.headers().get(headers::ETAG).cloned();
+
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+            let (width, height) =
+                image_rs::load_from_memory_with_format(&content, ImageFormat::Png)?.dimensions();
+
+            assert_eq!(16, width);
+            assert!(height <= 32);
+        }
+
+        let response = TestService::from(router(state))
+            .resolve(
+                Request::get(path.as_str())
+                    .header(headers::IF_NONE_MATCH, etag.unwrap())
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::NOT_MODIFIED, response.status());
+
+        Ok(())
+    }
+
+    /// Test that `serve_asset_thumbnail` rejects invalid bounds
+    /// and assets that don't exist or aren't images
+    #[rstest]
+    #[case::no_bounds("decoration.png", "", StatusCode::UNPROCESSABLE_ENTITY)]
+    #[case::zero_width("decoration.png", "w=0", StatusCode::UNPROCESSABLE_ENTITY)]
+    #[case::oversized_height("decoration.png", "h=4096", StatusCode::UNPROCESSABLE_ENTITY)]
+    #[case::unknown_asset("missing.png", "w=16", StatusCode::NOT_FOUND)]
+    #[case::traversal("..%2FCargo.toml", "w=16", StatusCode::NOT_FOUND)]
+    #[case::not_an_image("day-4%2Fconsumer.tpl", "w=16", StatusCode::UNPROCESSABLE_ENTITY)]
+    #[test_log::test(tokio::test)]
+    async fn test_invalid_asset_thumbnails(
+        service: TestService,
+        #[case] asset: &str,
+        #[case] query: &str,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let path = format!("{}?{query}", routes::day11::thumbnail(asset));
+        let response = service.resolve(path.as_str()).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        Ok(())
+    }
 }
@@ -3,29 +3,181 @@
 
 // Standard Library Imports
 use core::ops::{BitAnd, Sub};
+use std::collections::{BTreeMap, BTreeSet};
 
 // Third-Party Imports
 use axum::{
-    extract::{Json, Path, State},
-    http::StatusCode,
+    async_trait,
+    extract::{FromRequestParts, Json, Path, State},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
 };
-use chrono::{DateTime, Datelike, Utc};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::Deserialize;
 use serde_json::{Map as JsonObject, Value};
 
 // Crate-Level Imports
-use crate::state::ShuttleAppState;
+use crate::{
+    errors::AppError,
+    routes,
+    solutions::{DayModule, DayRoutes},
+    state::ShuttleAppState,
+    validation::{Validate, ValidatedQuery, ValidationErrors},
+};
+
+// <editor-fold desc="// Weekday ...">
+
+/// [`axum` extractor](axum::extract) for a weekday path parameter,
+/// given as either a number between 0 (Monday) and 6 (Sunday) or
+/// a day's (case-insensitive) English name
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Weekday(pub chrono::Weekday);
+
+impl Weekday {
+    /// Parse the supplied path parameter
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+
+        value
+            .parse::<u8>()
+            .ok()
+            .map_or_else(
+                || value.parse::<chrono::Weekday>().ok(),
+                |number| chrono::Weekday::try_from(number).ok(),
+            )
+            .map(Self)
+    }
+}
+
+#[async_trait]
+impl<State> FromRequestParts<State> for Weekday
+where
+    State: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let Path(value) =
+            <Path<String> as FromRequestParts<State>>::from_request_parts(parts, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+
+        Self::parse(&value).ok_or_else(|| {
+            let mut errors = ValidationErrors::default();
+
+            errors.add(
+                "weekday",
+                format!(
+                    "must be a number between 0 (Monday) and 6 (Sunday) \
+or an English day name, got: {value}"
+                ),
+            );
+
+            errors.into_response()
+        })
+    }
+}
+
+// </editor-fold desc="// Weekday ...">
+
+// <editor-fold desc="// UlidAnalysis ...">
+
+/// A calendar histogram [`analyze_ulids`] may additionally compute
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExtraBucket {
+    /// creation times by month (1 through 12)
+    Month,
+    /// creation times by (calendar) year
+    Year,
+    /// creation times by hour of the day (0 through 23, UTC)
+    Hour,
+}
+
+impl ExtraBucket {
+    /// Every available bucket
+    pub const ALL: [Self; 3] = [Self::Month, Self::Year, Self::Hour];
+
+    /// The bucket's name (in query parameters and responses)
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Month => "month",
+            Self::Year => "year",
+            Self::Hour => "hour",
+        }
+    }
+
+    /// The bucket's key for the supplied creation time
+    pub fn key(self, created_at: &DateTime<Utc>) -> i64 {
+        match self {
+            Self::Month => i64::from(created_at.month()),
+            Self::Year => i64::from(created_at.year()),
+            Self::Hour => i64::from(created_at.hour()),
+        }
+    }
+
+    /// Get the bucket with the supplied name (if any)
+    pub fn named(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|bucket| bucket.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Query parameters for [`analyze_ulids`]
+#[derive(Debug, Default, Deserialize)]
+pub struct UlidAnalysis {
+    /// A comma-separated list of the [extra buckets](ExtraBucket)
+    /// to compute (in addition to the challenge's counts)
+    #[serde(default)]
+    extra: Option<String>,
+}
+
+impl Validate for UlidAnalysis {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        for name in self.names() {
+            if ExtraBucket::named(name).is_none() {
+                errors.add(
+                    "extra",
+                    format!(
+                        "unknown bucket {name:?}, expected one of: {}",
+                        ExtraBucket::ALL.map(ExtraBucket::name).join(", ")
+                    ),
+                );
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+impl UlidAnalysis {
+    /// The requested buckets' (raw) names
+    fn names(&self) -> impl Iterator<Item = &str> {
+        self.extra
+            .iter()
+            .flat_map(|extra| extra.split(','))
+            .filter(|name| !name.trim().is_empty())
+    }
+
+    /// The (validated) extra buckets to compute
+    pub fn extras(&self) -> BTreeSet<ExtraBucket> {
+        self.names().filter_map(ExtraBucket::named).collect()
+    }
+}
+
+// </editor-fold desc="// UlidAnalysis ...">
 
 /// Endpoint 1/2 for [Day 12: Challenge](https://console.shuttle.rs/cch/challenge/12#:~:text=⭐)
 #[tracing::instrument(ret, skip(state), fields(new, old))]
 pub async fn store_packet_id_timestamp(
     Path(packet_id): Path<String>,
     State(state): State<ShuttleAppState>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    state
-        .persistence
-        .save(&packet_id, Utc::now())
-        .map(|()| StatusCode::OK)
-        .map_err(|error| (StatusCode::FAILED_DEPENDENCY, format!("{error}")))
+) -> Result<StatusCode, AppError> {
+    state.persistence.save(&packet_id, state.clock.now())?;
+
+    Ok(StatusCode::OK)
 }
 
 /// Endpoint 2/2 for [Day 12: Challenge](https://console.shuttle.rs/cch/challenge/12#:~:text=⭐)
@@ -33,15 +185,15 @@ pub async fn store_packet_id_timestamp(
 pub async fn retrieve_packet_id_timestamp(
     Path(packet_id): Path<String>,
     State(state): State<ShuttleAppState>,
-) -> Result<Json<u64>, (StatusCode, String)> {
-    let now = Utc::now();
+) -> Result<Json<u64>, AppError> {
+    let now = state.clock.now();
 
-    state
+    let stamp = state
         .persistence
         .load::<DateTime<Utc>>(&packet_id)
-        .or_else(|_| state.persistence.save(&packet_id, now).map(|()| now))
-        .map(|stamp| Json(now.sub(stamp).num_seconds().unsigned_abs()))
-        .map_err(|error| (StatusCode::FAILED_DEPENDENCY, format!("{error}")))
+        .or_else(|_| state.persistence.save(&packet_id, now).map(|()| now))?;
+
+    Ok(Json(now.sub(stamp).num_seconds().unsigned_abs()))
 }
 
 /// Complete [Day 12: Bonus 1](https://console.shuttle.rs/cch/challenge/12#:~:text=🎁)
@@ -61,15 +213,26 @@ pub async fn santas_ulid_hug_box(Json(ulids): Json<Vec<ulid::Ulid>>) -> Json<Vec
 /// For the set of provided ULIDs, returns a cumulative count of:
 ///   - How many have entropy bits where the Least Significant Bit (LSB) is 1?
 ///   - How many of the ULIDs were generated on a Christmas Eve? (day == 24) (?)
-///   - How many were generated on a <weekday>? (A number in the path between 0 (Monday) and 6 (Sunday))
+///   - How many were generated on a <weekday>? (A number in the path between 0 (Monday) and 6 (Sunday),
+///     or the [weekday's name](Weekday))
 ///   - How many were generated in the future? (has a date later than the current time)
-#[tracing::instrument(ret)]
+///
+/// The [extra buckets](UlidAnalysis) (if any) are reported under an
+/// `extra` key, so the default response is exactly the challenge's.
+#[tracing::instrument(ret, skip(state))]
 pub async fn analyze_ulids(
-    Path(weekday): Path<u32>,
+    Weekday(weekday): Weekday,
+    State(state): State<ShuttleAppState>,
+    ValidatedQuery(analysis): ValidatedQuery<UlidAnalysis>,
     Json(ulids): Json<Vec<ulid::Ulid>>,
 ) -> Json<JsonObject<String, Value>> {
-    let now = Utc::now();
+    let now = state.clock.now();
     let (mut chaotic, mut xmas_eve, mut in_future, mut on_weekday) = (0u64, 0u64, 0u64, 0u64);
+    let mut extra = analysis
+        .extras()
+        .into_iter()
+        .map(|bucket| (bucket, BTreeMap::<i64, u64>::new()))
+        .collect::<BTreeMap<_, _>>();
 
     for id in ulids {
         let created_at: DateTime<Utc> = id.datetime().into();
@@ -86,12 +249,16 @@ pub async fn analyze_ulids(
             xmas_eve += 1;
         }
 
-        if created_at.weekday().num_days_from_monday() == weekday {
+        if created_at.weekday() == weekday {
             on_weekday += 1;
         }
+
+        for (bucket, histogram) in extra.iter_mut() {
+            *histogram.entry(bucket.key(&created_at)).or_default() += 1;
+        }
     }
 
-    Json(JsonObject::<String, Value>::from_iter(
+    let mut analysis = JsonObject::<String, Value>::from_iter(
         [
             ("LSB is 1".to_string(), Value::from(chaotic)),
             ("weekday".to_string(), Value::from(on_weekday)),
@@ -99,9 +266,79 @@ pub async fn analyze_ulids(
             ("in the future".to_string(), Value::from(in_future)),
         ]
         .into_iter(),
-    ))
+    );
+
+    if !extra.is_empty() {
+        analysis.insert(
+            "extra".to_string(),
+            Value::Object(
+                extra
+                    .into_iter()
+                    .map(|(bucket, histogram)| {
+                        (
+                            bucket.name().to_string(),
+                            Value::Object(
+                                histogram
+                                    .into_iter()
+                                    .map(|(key, count)| (key.to_string(), Value::from(count)))
+                                    .collect(),
+                            ),
+                        )
+                    })
+                    .collect(),
+            ),
+        );
+    }
+
+    Json(analysis)
+}
+
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 12](https://console.shuttle.rs/cch/challenge/12) solutions
+#[derive(Debug)]
+pub struct Day12;
+
+impl DayModule for Day12 {
+    fn day() -> i8 {
+        12
+    }
+
+    fn name() -> &'static str {
+        "ULID Timekeeping"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                12,
+                POST routes::day12::SAVE,
+                store_packet_id_timestamp,
+                "Store a packet's timestamp"
+            ))
+            .route(day_route!(
+                12,
+                GET routes::day12::LOAD,
+                retrieve_packet_id_timestamp,
+                "Get the seconds since a packet was stored"
+            ))
+            .route(day_route!(
+                12,
+                POST routes::day12::ULIDS,
+                santas_ulid_hug_box,
+                "Convert ULIDs to UUIDs"
+            ))
+            .route(day_route!(
+                12,
+                POST routes::day12::ULIDS_WEEKDAY,
+                analyze_ulids,
+                "Analyze ULIDs' dates"
+            ))
+    }
 }
 
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -130,5 +367,266 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
+    use std::sync::Arc;
+
+    use crate::clock::{Clock, FakeClock};
+    use crate::random::StdRandom;
+    use crate::routes;
+    use crate::state::ShuttleAppState;
     use crate::utils::{service, TestService};
+    use crate::{router, utils::state};
+
+    use super::{UlidAnalysis, Weekday};
+
+    /// Test that `Weekday` accepts numbers and
+    /// (case-insensitive) names, and nothing else
+    #[rstest]
+    #[case::first("0", Some(chrono::Weekday::Mon))]
+    #[case::last("6", Some(chrono::Weekday::Sun))]
+    #[case::name("friday", Some(chrono::Weekday::Fri))]
+    #[case::shouting("WEDNESDAY", Some(chrono::Weekday::Wed))]
+    #[case::out_of_range("7", None)]
+    #[case::negative("-1", None)]
+    #[case::huge("4294967295", None)]
+    #[case::made_up("funday", None)]
+    #[test_log::test]
+    fn test_parse_weekday(#[case] value: &str, #[case] expected: Option<chrono::Weekday>) {
+        assert_eq!(expected.map(Weekday), Weekday::parse(value));
+    }
+
+    /// Test that `store_packet_id_timestamp` and `retrieve_packet_id_timestamp`
+    /// satisfy the conditions of [CCH 2023 Challenge 12](https://console.shuttle.rs/cch/challenge/12)
+    #[rstest]
+    #[case::immediately(0, "0")]
+    #[case::after_two_seconds(2, "2")]
+    #[case::after_a_day(86_400, "86400")]
+    #[test_log::test(tokio::test)]
+    async fn test_challenge_twelve(
+        mut state: ShuttleAppState,
+        #[case] elapsed: i64,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let clock = Arc::new(FakeClock::new("2023-12-12T12:00:00Z".parse()?));
+        state.clock = clock.clone();
+        state.rng = Arc::new(StdRandom::seeded(12));
+
+        let packet_id = state.rng.ulid(clock.now()).to_string();
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(Request::post(routes::day12::save(&packet_id)).body(Body::empty())?)
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        clock.advance(chrono::Duration::seconds(elapsed));
+
+        let response = TestService::from(router(state))
+            .resolve(routes::day12::load(&packet_id).as_str())
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+
+        assert_eq!(expected_content, String::from_utf8_lossy(content.as_ref()));
+
+        Ok(())
+    }
+
+    /// Test that `analyze_ulids` judges "the future"
+    /// relative to the service's clock
+    #[rstest]
+    #[case::before_creation("2016-07-30T00:00:00Z", "1")]
+    #[case::after_creation("2024-01-01T00:00:00Z", "0")]
+    #[test_log::test(tokio::test)]
+    async fn test_analyze_ulids_uses_clock(
+        mut state: ShuttleAppState,
+        #[case] now: &str,
+        #[case] expected_in_future: &str,
+    ) -> anyhow::Result<()> {
+        state.clock = Arc::new(FakeClock::new(now.parse()?));
+
+        let response = TestService::from(router(state))
+            .resolve(
+                Request::post(routes::day12::ulids_weekday(0))
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(r#"["01BJQ0E1C3Z56ABCD0E11HYX4M"]"#))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+        let analysis = serde_json::from_slice::<Value>(content.as_ref())?;
+
+        assert_eq!(
+            expected_in_future.parse::<u64>()?,
+            analysis["in the future"].as_u64().unwrap()
+        );
+
+        Ok(())
+    }
+
+    /// Test that `analyze_ulids` reports the requested extra
+    /// buckets (and only those) under the `extra` key
+    #[rstest]
+    #[case::none("", None)]
+    #[case::month_and_year(
+        "?extra=month,year",
+        Some(r#"{"month": {"2": 1, "12": 2}, "year": {"2020": 1, "2023": 2}}"#)
+    )]
+    #[case::hour("?extra=HOUR", Some(r#"{"hour": {"0": 1, "12": 1, "23": 1}}"#))]
+    #[test_log::test(tokio::test)]
+    async fn test_analyze_ulids_extras(
+        service: TestService,
+        #[case] query: &str,
+        #[case] expected_extra: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let ulids = [
+            "2020-02-29T12:00:00Z",
+            "2023-12-24T23:59:59Z",
+            "2023-12-25T00:00:00Z",
+        ]
+        .map(|stamp| {
+            let stamp = stamp.parse::<chrono::DateTime<chrono::Utc>>().unwrap();
+
+            ulid::Ulid::from_parts(stamp.timestamp_millis() as u64, 0).to_string()
+        });
+
+        let response = service
+            .resolve(
+                Request::post(format!("{}{query}", routes::day12::ulids_weekday(0)))
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::to_string(&ulids)?))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+        let analysis = serde_json::from_slice::<Value>(content.as_ref())?;
+
+        assert_eq!(Some(1), analysis["christmas eve"].as_u64());
+        assert_eq!(
+            expected_extra
+                .map(serde_json::from_str::<Value>)
+                .transpose()?,
+            analysis.get("extra").cloned(),
+        );
+
+        Ok(())
+    }
+
+    /// Test that unknown extra buckets are rejected
+    #[rstest]
+    #[case::unknown("extra=week")]
+    #[case::partly_unknown("extra=month,fortnight")]
+    #[test_log::test(tokio::test)]
+    async fn test_analyze_ulids_rejects_unknown_extras(
+        service: TestService,
+        #[case] query: &str,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                Request::post(format!("{}?{query}", routes::day12::ulids_weekday(0)))
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from("[]"))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+        Ok(())
+    }
+
+    /// Test that `santas_ulid_hug_box` and `analyze_ulids` satisfy the
+    /// conditions of [CCH 2023 Challenge 12](https://console.shuttle.rs/cch/challenge/12)
+    #[rstest]
+    #[case::bonus_one_example(
+        routes::day12::ulids().to_string(),
+        r#"[
+          "01BJQ0E1C3Z56ABCD0E11HYX4M",
+          "01BJQ0E1C3Z56ABCD0E11HYX5N",
+          "01BJQ0E1C3Z56ABCD0E11HYX6Q",
+          "01BJQ0E1C3Z56ABCD0E11HYX7R",
+          "01BJQ0E1C3Z56ABCD0E11HYX8P"
+        ]"#,
+        StatusCode::OK,
+        r#"[
+          "015cae07-0583-f94c-a5b1-a070431f7516",
+          "015cae07-0583-f94c-a5b1-a070431f74f8",
+          "015cae07-0583-f94c-a5b1-a070431f74d7",
+          "015cae07-0583-f94c-a5b1-a070431f74b5",
+          "015cae07-0583-f94c-a5b1-a070431f7494"
+        ]"#
+    )]
+    #[case::bonus_two_analysis(
+        routes::day12::ulids_weekday(5),
+        r#"[
+          "00WEGGF0G0J5HEYXS3D7RWZGV8",
+          "76EP4G39R8JD1N8AQNYDVJBRCF",
+          "018CJ7KMG0051CDCS3B7BFJ3AK",
+          "00Y986KPG0AMGB78RD45E9109K",
+          "010451HTG0NQ7H2AP28T3K1VW5",
+          "01HH9SJEG0KY16H81S3N1BMXM4",
+          "01HH9SJEG0P9M22Z9VGHH9C8CX",
+          "017F8YY0G0NQA16HHC2QT5JD6X",
+          "03QCPC7P003V1NND3B3QJW72QJ"
+        ]"#,
+        StatusCode::OK,
+        r#"{"christmas eve": 3, "weekday": 1, "in the future": 2, "LSB is 1": 6}"#
+    )]
+    #[case::invalid_ulid(
+        routes::day12::ulids().to_string(),
+        r#"["not-a-ulid"]"#,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ""
+    )]
+    #[case::named_weekday(
+        routes::day12::ulids_weekday("Sunday"),
+        r#"["01HH9SJEG0KY16H81S3N1BMXM4"]"#,
+        StatusCode::OK,
+        r#"{"christmas eve": 0, "weekday": 1, "in the future": 0, "LSB is 1": 0}"#
+    )]
+    #[case::weekday_out_of_range(
+        routes::day12::ulids_weekday(7),
+        "[]",
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ""
+    )]
+    #[case::invalid_weekday(
+        routes::day12::ulids_weekday("funday"),
+        "[]",
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ""
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_challenge_twelve_bonus(
+        service: TestService,
+        #[case] url: String,
+        #[case] ulids: &str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                Request::post(url)
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(ulids.to_string()))?,
+            )
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status.is_success() {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+
+            assert_eq!(
+                serde_json::from_str::<Value>(expected_content)?,
+                serde_json::from_slice::<Value>(content.as_ref())?,
+            );
+        }
+
+        Ok(())
+    }
 }
@@ -1,24 +1,106 @@
 //! ### CCH 2023 Day 13 Solutions
 //!
 
+// Standard Library Imports
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    str::FromStr,
+    time::Duration,
+};
+
 // Third-Party Imports
 use axum::{
-    extract::{Json, State},
+    extract::{Json, Query, State},
     http::StatusCode,
+    response::{IntoResponse, Response},
 };
-use futures::prelude::*;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::{Map as JsonObject, Value};
-use sqlx::{error::Error as DbError, postgres::PgQueryResult};
+use serde_json::{json, Map as JsonObject, Value};
+use sqlx::{error::Error as DbError, postgres::PgQueryResult, Column, FromRow, Row, TypeInfo};
 
 // Crate-Level Imports
-use crate::state::ShuttleAppState;
+use crate::{
+    errors::{AppError, ErrorEnvelope},
+    metrics::TimedQuery,
+    outbox::{Outbox, ORDERS_CREATED},
+    record_db, routes,
+    solutions::{DayModule, DayRoutes},
+    state::ShuttleAppState,
+    telemetry::{fields, spans::DB_QUERY},
+    utils::pg_json_value,
+    validation::{Validate, ValidatedQuery, ValidationErrors},
+};
+
+/// The longest text argument a [`ScalarExpression`] may carry
+const MAX_SCALAR_TEXT_LENGTH: usize = 256;
+
+// <editor-fold desc="// OrderSchema ...">
+
+/// The database schemas isolating each
+/// day's (otherwise identically named) tables
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OrderSchema {
+    /// the `cch13` schema, used by day 13
+    Day13,
+    /// the `cch18` schema, used by day 18 (and gRPC)
+    Day18,
+}
+
+impl Display for OrderSchema {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(match self {
+            Self::Day13 => "cch13",
+            Self::Day18 => "cch18",
+        })
+    }
+}
+
+impl OrderSchema {
+    /// Create the schema (if it doesn't already exist),
+    /// along with its `updated_at`-maintaining trigger function
+    pub async fn ensure(self, db: &sqlx::PgPool) -> Result<PgQueryResult, DbError> {
+        sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS {self};"))
+            .execute(db)
+            .await?;
+
+        sqlx::query(&format!(
+            r#"CREATE OR REPLACE FUNCTION {self}.touch_updated_at() RETURNS TRIGGER AS $$
+               BEGIN
+                 NEW.updated_at = NOW();
+                 RETURN NEW;
+               END;
+               $$ LANGUAGE plpgsql;
+            "#
+        ))
+        .execute(db)
+        .await
+    }
+
+    /// Keep the specified table's `updated_at` column current
+    pub async fn track_updates(
+        self,
+        table: &str,
+        db: &sqlx::PgPool,
+    ) -> Result<PgQueryResult, DbError> {
+        sqlx::query(&format!(
+            r#"CREATE TRIGGER {table}_touch_updated_at
+               BEFORE UPDATE ON {self}.{table}
+               FOR EACH ROW EXECUTE FUNCTION {self}.touch_updated_at();
+            "#
+        ))
+        .execute(db)
+        .await
+    }
+}
+
+// </editor-fold desc="// OrderSchema ...">
 
 // <editor-fold desc="// GiftOrder ...">
 
 /// A gift order
 #[cfg_attr(test, derive(Eq, PartialEq))]
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, FromRow, Serialize, Deserialize)]
 pub struct GiftOrder {
     /// the order's sequential id
     pub id: i64,
@@ -29,99 +111,406 @@ pub struct GiftOrder {
     /// the region to which the
     /// gift must be delivered
     pub region_id: i64,
+    /// when the order was recorded (set by the database)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// when the order was last modified (set by the database)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl GiftOrder {
-    /// ...
-    pub async fn insert(&self, db: &sqlx::PgPool) -> Result<PgQueryResult, DbError> {
-        Self::insert_many([self].into_iter(), db).await
+    /// (Re)create the (empty) `orders` table in the specified schema
+    pub async fn reset_schema(
+        schema: OrderSchema,
+        db: &sqlx::PgPool,
+    ) -> Result<PgQueryResult, DbError> {
+        schema.ensure(db).await?;
+        Outbox::ensure(db).await?;
+
+        sqlx::query(&format!("DROP TABLE IF EXISTS {schema}.orders;"))
+            .execute(db)
+            .await?;
+
+        sqlx::query(&format!(
+            r#"CREATE TABLE IF NOT EXISTS {schema}.orders (
+                 id INT PRIMARY KEY,
+                 gift_name VARCHAR(50),
+                 quantity INT,
+                 region_id INT,
+                 created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                 updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+               );
+            "#
+        ))
+        .execute(db)
+        .await?;
+
+        schema.track_updates("orders", db).await
     }
 
     /// ...
+    pub async fn insert(
+        &self,
+        schema: OrderSchema,
+        db: &sqlx::PgPool,
+    ) -> Result<PgQueryResult, DbError> {
+        Self::insert_many([self].into_iter(), schema, db).await
+    }
+
+    /// Insert the supplied orders, recording an [`ORDERS_CREATED`]
+    /// event in the [outbox](Outbox) in the same statement
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
     pub async fn insert_many<'orders, Orders: Iterator<Item = &'orders Self>>(
         orders: Orders,
+        schema: OrderSchema,
         db: &sqlx::PgPool,
     ) -> Result<PgQueryResult, DbError> {
-        sqlx::QueryBuilder::<sqlx::Postgres>::new(
-            "INSERT INTO ORDERS (id, quantity, gift_name, region_id) ",
-        )
-        .push_values(orders, |mut builder, order| {
-            builder
-                .push_bind(order.id)
-                .push_bind(order.quantity)
-                .push_bind(order.gift_name.clone())
-                .push_bind(order.region_id);
-        })
-        .build()
-        .execute(db)
-        .await
+        let mut query = sqlx::QueryBuilder::<sqlx::Postgres>::new(format!(
+            "WITH inserted AS (INSERT INTO {schema}.orders (id, quantity, gift_name, region_id) "
+        ));
+
+        query
+            .push_values(orders, |mut builder, order| {
+                builder
+                    .push_bind(order.id)
+                    .push_bind(order.quantity)
+                    .push_bind(order.gift_name.clone())
+                    .push_bind(order.region_id);
+            })
+            .push(
+                r#" RETURNING id, quantity, gift_name, region_id
+                ), event AS (
+                  INSERT INTO public.outbox (topic, payload)
+                  SELECT "#,
+            )
+            .push_bind(ORDERS_CREATED)
+            .push(", jsonb_build_object('schema', ")
+            .push_bind(schema.to_string())
+            .push(
+                r#", 'orders', jsonb_agg(to_jsonb(inserted)))
+                  FROM inserted
+                )
+                SELECT 1 FROM inserted"#,
+            );
+
+        record_db!("insert orders", query.build().execute(db).await)
+    }
+
+    /// Get the orders recorded after `since` (or
+    /// every order), least recently recorded first
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
+    pub async fn recorded_since(
+        since: Option<DateTime<Utc>>,
+        schema: OrderSchema,
+        db: &sqlx::PgPool,
+    ) -> Result<Vec<Self>, DbError> {
+        let orders = sqlx::query_as::<_, Self>(&format!(
+            r#"SELECT
+                 id::INT8 AS id,
+                 quantity::INT8 AS quantity,
+                 gift_name,
+                 region_id::INT8 AS region_id,
+                 created_at,
+                 updated_at
+               FROM
+                 {schema}.orders
+               WHERE
+                 $1::TIMESTAMPTZ IS NULL OR $1 < created_at
+               ORDER BY
+                 created_at ASC,
+                 id ASC
+            "#
+        ))
+        .bind(since)
+        .fetch_all(db)
+        .await;
+
+        record_db!("select orders", orders)
     }
 
     /// ...
-    pub async fn total_ordered(db: &sqlx::PgPool) -> Result<i64, DbError> {
-        sqlx::query_scalar::<_, i64>("SELECT SUM(quantity) FROM orders")
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
+    pub async fn total_ordered(schema: OrderSchema, db: &sqlx::PgPool) -> Result<i64, DbError> {
+        record_db!("sum orders");
+
+        sqlx::query_scalar::<_, i64>(&format!("SELECT SUM(quantity) FROM {schema}.orders"))
             .fetch_one(db)
             .await
     }
 
+    /// The quantity of gifts ordered in each `bucket`-long interval
+    /// between `from` and `to` (omitting intervals without orders)
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
+    pub async fn quantity_over_time(
+        schema: OrderSchema,
+        db: &sqlx::PgPool,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        bucket: Duration,
+    ) -> Result<Vec<(DateTime<Utc>, i64)>, DbError> {
+        let buckets = sqlx::query_as::<_, (DateTime<Utc>, i64)>(&format!(
+            r#"SELECT
+                 to_timestamp(
+                   floor(extract(epoch FROM created_at)::FLOAT8 / $3) * $3
+                 ) AS bucket,
+                 SUM(quantity)::INT8 AS quantity
+               FROM
+                 {schema}.orders
+               WHERE
+                 created_at BETWEEN $1 AND $2
+               GROUP BY
+                 bucket
+               ORDER BY
+                 bucket ASC
+            "#
+        ))
+        .bind(from)
+        .bind(to)
+        .bind(bucket.as_secs_f64().max(1.0))
+        .fetch_all(db)
+        .await;
+
+        record_db!("bucket orders", buckets)
+    }
+
     /// ...
-    pub async fn most_popular(db: &sqlx::PgPool) -> Result<Option<(String, i64)>, DbError> {
-        sqlx::query_as(
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
+    pub async fn most_popular(
+        schema: OrderSchema,
+        db: &sqlx::PgPool,
+    ) -> Result<Option<(String, i64)>, DbError> {
+        let gift = sqlx::query_as(&format!(
             r#"
             SELECT
                 gift_name,
                 SUM(quantity) as popularity
             FROM
-                orders
+                {schema}.orders
             GROUP BY
                 gift_name
             ORDER BY
                 popularity
             DESC
             LIMIT 1
-        "#,
-        )
+        "#
+        ))
         .fetch_optional(db)
-        .await
+        .await;
+
+        record_db!("select most popular gift", gift)
     }
 }
 
 // </editor-fold desc="// GiftOrder ...">
 
+// <editor-fold desc="// ScalarExpression ...">
+
+/// An allowlisted scalar SQL expression, whose
+/// argument (if any) is bound as a parameter
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScalarExpression {
+    /// an integer literal
+    Integer(i64),
+    /// `now()`
+    Now,
+    /// `pi()`
+    Pi,
+    /// `gen_random_uuid()`
+    RandomUuid,
+    /// `sqrt(<number>)`
+    Sqrt(f64),
+    /// `upper('<text>')`
+    Upper(String),
+    /// `lower('<text>')`
+    Lower(String),
+    /// `length('<text>')`
+    Length(String),
+    /// `md5('<text>')`
+    Md5(String),
+}
+
+impl FromStr for ScalarExpression {
+    type Err = (StatusCode, String);
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        if let Ok(integer) = text.parse::<i64>() {
+            return Ok(Self::Integer(integer));
+        }
+
+        let invalid = |message: &str| {
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("{message}: {text}"),
+            )
+        };
+
+        let (name, argument) = text
+            .strip_suffix(')')
+            .and_then(|call| call.split_once('('))
+            .map(|(name, argument)| (name.trim().to_lowercase(), argument.trim()))
+            .ok_or_else(|| invalid("expected an integer or a function call"))?;
+
+        let number = || {
+            argument
+                .parse::<f64>()
+                .map_err(|_| invalid("expected a numeric argument"))
+        };
+
+        let string = || {
+            argument
+                .strip_prefix('\'')
+                .and_then(|argument| argument.strip_suffix('\''))
+                .map(|argument| argument.replace("''", "'"))
+                .ok_or_else(|| invalid("expected a single-quoted text argument"))
+        };
+
+        let nullary = |expression: Self| {
+            argument
+                .is_empty()
+                .then_some(expression)
+                .ok_or_else(|| invalid("expected no arguments"))
+        };
+
+        match name.as_str() {
+            "now" => nullary(Self::Now),
+            "pi" => nullary(Self::Pi),
+            "gen_random_uuid" => nullary(Self::RandomUuid),
+            "sqrt" => number().map(Self::Sqrt),
+            "upper" => string().map(Self::Upper),
+            "lower" => string().map(Self::Lower),
+            "length" => string().map(Self::Length),
+            "md5" => string().map(Self::Md5),
+            _ => Err(invalid("unsupported function")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ScalarExpression {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(|(_, message): (StatusCode, String)| serde::de::Error::custom(message))
+    }
+}
+
+impl ScalarExpression {
+    /// The (parameterized) SQL evaluating the expression
+    fn sql(&self) -> &'static str {
+        match self {
+            Self::Integer(_) => "SELECT $1::INT8",
+            Self::Now => "SELECT NOW()",
+            Self::Pi => "SELECT PI()",
+            Self::RandomUuid => "SELECT GEN_RANDOM_UUID()",
+            Self::Sqrt(_) => "SELECT SQRT($1::FLOAT8)",
+            Self::Upper(_) => "SELECT UPPER($1::TEXT)",
+            Self::Lower(_) => "SELECT LOWER($1::TEXT)",
+            Self::Length(_) => "SELECT LENGTH($1::TEXT)",
+            Self::Md5(_) => "SELECT MD5($1::TEXT)",
+        }
+    }
+
+    /// Evaluate the expression
+    pub async fn evaluate(&self, db: &sqlx::PgPool) -> Result<ScalarValue, DbError> {
+        let query = sqlx::query(self.sql());
+
+        let query = match self {
+            Self::Integer(integer) => query.bind(*integer),
+            Self::Sqrt(number) => query.bind(*number),
+            Self::Upper(text) | Self::Lower(text) | Self::Length(text) | Self::Md5(text) => {
+                query.bind(text.clone())
+            }
+            _ => query,
+        };
+
+        let row = query.fetch_one(db).await?;
+        let column = row.try_column(0)?;
+
+        Ok(ScalarValue {
+            value: pg_json_value(&row, column),
+            pg_type: column.type_info().name().to_string(),
+        })
+    }
+}
+
+/// The query parameters of [`simple_sql_select`]
+#[derive(Debug, Default, Deserialize)]
+pub struct ScalarQuery {
+    /// the expression to evaluate (if any)
+    #[serde(default)]
+    expr: Option<ScalarExpression>,
+}
+
+impl Validate for ScalarQuery {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        match &self.expr {
+            Some(ScalarExpression::Sqrt(number)) if !number.is_finite() || *number < 0.0 => {
+                errors.add("expr", "sqrt requires a finite, non-negative argument");
+            }
+            Some(
+                ScalarExpression::Upper(text)
+                | ScalarExpression::Lower(text)
+                | ScalarExpression::Length(text)
+                | ScalarExpression::Md5(text),
+            ) if MAX_SCALAR_TEXT_LENGTH < text.len() => {
+                errors.add(
+                    "expr",
+                    format!("text arguments may be at most {MAX_SCALAR_TEXT_LENGTH} bytes"),
+                );
+            }
+            _ => {}
+        }
+
+        errors.into_result()
+    }
+}
+
+/// The evaluated value of a [`ScalarExpression`]
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScalarValue {
+    /// the expression's value
+    pub value: Value,
+    /// the name of the value's PostgreSQL type
+    pub pg_type: String,
+}
+
+// </editor-fold desc="// ScalarExpression ...">
+
 /// Complete [Day 13: Task 1](https://console.shuttle.rs/cch/challenge/13#:~:text=⭐)
+/// (or evaluate the supplied allowlisted scalar expression)
 #[tracing::instrument(ret, skip(state))]
 pub async fn simple_sql_select(
     State(state): State<ShuttleAppState>,
-) -> Result<Json<i32>, (StatusCode, String)> {
-    sqlx::query_scalar::<_, i32>("SELECT 20231213")
-        .fetch_one(&state.db)
+    ValidatedQuery(query): ValidatedQuery<ScalarQuery>,
+) -> Result<Response, AppError> {
+    let Some(expression) = query.expr else {
+        return sqlx::query_scalar::<_, i32>("SELECT 20231213")
+            .fetch_one(&state.db)
+            .await
+            .map_err(|error| ErrorEnvelope::new(StatusCode::EXPECTATION_FAILED, error).into())
+            .map(|value| Json(value).into_response());
+    };
+
+    expression
+        .evaluate(&state.db)
         .await
-        .map_err(|error| (StatusCode::EXPECTATION_FAILED, format!("{error}")))
-        .map(Json)
+        .map_err(|error| ErrorEnvelope::new(StatusCode::EXPECTATION_FAILED, error).into())
+        .map(|value| Json(value).into_response())
 }
 
 /// Endpoint 1/3 for [Day 13: Task 2](https://console.shuttle.rs/cch/challenge/13#:~:text=⭐)
 #[tracing::instrument(ret, err(Debug), skip(state))]
 pub async fn reset_day_13_schema(
     State(state): State<ShuttleAppState>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    sqlx::query("DROP TABLE IF EXISTS orders;")
-        .execute(&state.db)
-        .and_then(|_| {
-            sqlx::query(
-                r#"CREATE TABLE IF NOT EXISTS orders (
-                 id INT PRIMARY KEY,
-                 gift_name VARCHAR(50),
-                 quantity INT,
-                 region_id INT
-               );
-            "#,
-            )
-            .execute(&state.db)
-        })
-        .await
-        .map(|_| StatusCode::OK)
-        .map_err(|error| (StatusCode::FAILED_DEPENDENCY, format!("{error}")))
+) -> Result<StatusCode, AppError> {
+    GiftOrder::reset_schema(OrderSchema::Day13, &state.db).await?;
+
+    Ok(StatusCode::OK)
 }
 
 /// Endpoint 2/3 for [Day 13: Task 2](https://console.shuttle.rs/cch/challenge/13#:~:text=⭐)
@@ -129,60 +518,133 @@ pub async fn reset_day_13_schema(
 pub async fn create_orders(
     State(state): State<ShuttleAppState>,
     Json(orders): Json<Vec<GiftOrder>>,
-) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+) -> Result<StatusCode, AppError> {
+    insert_orders(&state, orders, OrderSchema::Day13).await
+}
+
+/// Insert the supplied orders into the specified schema
+pub(crate) async fn insert_orders(
+    state: &ShuttleAppState,
+    orders: Vec<GiftOrder>,
+    schema: OrderSchema,
+) -> Result<StatusCode, AppError> {
     if !orders.is_empty() {
-        GiftOrder::insert_many(orders.iter(), &state.db)
+        state
+            .metrics
+            .time_query(
+                TimedQuery::OrderInserts,
+                GiftOrder::insert_many(orders.iter(), schema, &state.db),
+            )
             .await
-            .map(|_| StatusCode::OK)
-            .map_err(|error| {
-                (
-                    StatusCode::FAILED_DEPENDENCY,
-                    Json(Value::Object(JsonObject::<String, Value>::from_iter([
-                        ("error".to_string(), Value::String(format!("{error}"))),
-                        ("request".to_string(), serde_json::to_value(orders).unwrap()),
-                    ]))),
-                )
-            })
-    } else {
-        Ok(StatusCode::OK)
+            .map_err(|error| AppError::from(error).with_details(json!({ "request": orders })))?;
     }
+
+    Ok(StatusCode::OK)
 }
 
 /// Endpoint 3/3 for [Day 13: Task 2](https://console.shuttle.rs/cch/challenge/13#:~:text=⭐)
 #[tracing::instrument(ret, err(Debug), skip(state))]
 pub async fn total_order_count(
     State(state): State<ShuttleAppState>,
-) -> Result<Json<Value>, (StatusCode, String)> {
-    GiftOrder::total_ordered(&state.db)
-        .await
-        .map(|count| {
-            Json(Value::Object(JsonObject::from_iter([(
-                "total".to_string(),
-                Value::from(count),
-            )])))
-        })
-        .map_err(|error| (StatusCode::FAILED_DEPENDENCY, format!("{error}")))
+) -> Result<Json<Value>, AppError> {
+    let count = GiftOrder::total_ordered(OrderSchema::Day13, &state.db).await?;
+
+    Ok(Json(Value::Object(JsonObject::from_iter([(
+        "total".to_string(),
+        Value::from(count),
+    )]))))
+}
+
+/// The query parameters of [`get_recent_orders`]
+#[derive(Debug, Default, Deserialize)]
+pub struct RecentOrders {
+    /// the (exclusive) time after which
+    /// returned orders must've been recorded
+    #[serde(default)]
+    since: Option<DateTime<Utc>>,
+}
+
+/// Retrieve the orders recorded since the specified time,
+/// for consumers incrementally following new orders
+#[tracing::instrument(ret, err(Debug), skip(state))]
+pub async fn get_recent_orders(
+    State(state): State<ShuttleAppState>,
+    Query(query): Query<RecentOrders>,
+) -> Result<Json<Vec<GiftOrder>>, AppError> {
+    Ok(Json(
+        GiftOrder::recorded_since(query.since, OrderSchema::Day13, &state.db).await?,
+    ))
 }
 
 /// Complete [Day 13: Bonus](https://console.shuttle.rs/cch/challenge/13#:~:text=🎁)
 #[tracing::instrument(ret, err(Debug), skip(state))]
 pub async fn most_popular_gift(
     State(state): State<ShuttleAppState>,
-) -> Result<Json<Value>, (StatusCode, String)> {
-    GiftOrder::most_popular(&state.db)
-        .await
-        .map(|count| {
-            Json(Value::Object(JsonObject::from_iter([(
-                "popular".to_string(),
-                match count {
-                    None => Value::Null,
-                    Some((toy, _)) => Value::String(toy),
-                },
-            )])))
-        })
-        .map_err(|error| (StatusCode::FAILED_DEPENDENCY, format!("{error}")))
+) -> Result<Json<Value>, AppError> {
+    let popular = GiftOrder::most_popular(OrderSchema::Day13, &state.db).await?;
+
+    Ok(Json(Value::Object(JsonObject::from_iter([(
+        "popular".to_string(),
+        match popular {
+            None => Value::Null,
+            Some((toy, _)) => Value::String(toy),
+        },
+    )]))))
 }
 
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 13](https://console.shuttle.rs/cch/challenge/13) solutions
+#[derive(Debug)]
+pub struct Day13;
+
+impl DayModule for Day13 {
+    fn day() -> i8 {
+        13
+    }
+
+    fn name() -> &'static str {
+        "Gift Orders"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                13,
+                GET routes::day13::SQL,
+                simple_sql_select,
+                "Run a trivial SQL query"
+            ))
+            .route(day_route!(
+                13,
+                POST routes::day13::RESET,
+                reset_day_13_schema,
+                "Reset the orders schema"
+            ))
+            .route(day_route!(13, POST routes::day13::ORDERS, create_orders, "Insert gift orders"))
+            .route(day_route!(
+                13,
+                GET routes::day13::ORDERS_TOTAL,
+                total_order_count,
+                "Count the ordered gifts"
+            ))
+            .route(day_route!(
+                13,
+                GET routes::day13::ORDERS_RECENT,
+                get_recent_orders,
+                "List recent orders"
+            ))
+            .route(day_route!(
+                13,
+                GET routes::day13::ORDERS_POPULAR,
+                most_popular_gift,
+                "Get the most popular gift"
+            ))
+    }
+}
+
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -211,5 +673,271 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
+    use super::{GiftOrder, ScalarExpression};
+    use crate::routes;
+    use crate::state::ShuttleAppState;
     use crate::utils::{service, TestService};
+    use crate::{
+        router,
+        utils::{isolated_state, state, IsolatedState},
+    };
+
+    /// Resolve the supplied request against a fresh service
+    /// sharing `state`, returning the response's status and
+    /// (JSON-decoded, if possible) content
+    async fn call(
+        state: &ShuttleAppState,
+        request: Request<Body>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let response = TestService::from(router(state.clone()))
+            .resolve(request)
+            .await?;
+        let status = response.status();
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+
+        Ok((
+            status,
+            serde_json::from_slice(content.as_ref())
+                .unwrap_or_else(|_| Value::from(String::from_utf8_lossy(content.as_ref()))),
+        ))
+    }
+
+    fn json_post(url: &str, data: &str) -> anyhow::Result<Request<Body>> {
+        Ok(Request::post(url)
+            .header(headers::CONTENT_TYPE, "application/json")
+            .body(Body::from(data.to_string()))?)
+    }
+
+    /// Test that `ScalarExpression` only parses
+    /// allowlisted expressions
+    #[rstest]
+    #[case::integer("20231213", Some(ScalarExpression::Integer(20231213)))]
+    #[case::nullary(" NOW() ", Some(ScalarExpression::Now))]
+    #[case::numeric("sqrt(16)", Some(ScalarExpression::Sqrt(16.0)))]
+    #[case::text(
+        "upper('o''tannenbaum')",
+        Some(ScalarExpression::Upper(String::from("o'tannenbaum")))
+    )]
+    #[case::unquoted_text("upper(tannenbaum)", None)]
+    #[case::unexpected_argument("pi(3)", None)]
+    #[case::unsupported("pg_sleep(10)", None)]
+    #[case::server_version("version()", None)]
+    #[case::database_name("current_database()", None)]
+    #[case::injection("1; DROP TABLE orders", None)]
+    #[test_log::test]
+    fn test_scalar_expression_parsing(
+        #[case] text: &str,
+        #[case] expected: Option<ScalarExpression>,
+    ) {
+        assert_eq!(expected, text.parse::<ScalarExpression>().ok());
+    }
+
+    /// Test that `simple_sql_select` rejects unsupported
+    /// expressions without touching the database
+    #[rstest]
+    #[case::unsupported("pg_sleep(10)")]
+    #[case::negative_sqrt("sqrt(-1)")]
+    #[test_log::test(tokio::test)]
+    async fn test_simple_sql_select_rejects_invalid_expressions(
+        state: ShuttleAppState,
+        #[case] expression: &str,
+    ) -> anyhow::Result<()> {
+        let (status, _) = call(
+            &state,
+            Request::get(format!("{}?expr={expression}", routes::day13::sql()))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, status);
+
+        Ok(())
+    }
+
+    /// Test that `simple_sql_select` evaluates allowlisted
+    /// expressions, reporting their PostgreSQL type
+    #[rstest]
+    #[case::integer("42", serde_json::json!({"value": 42, "pg_type": "INT8"}))]
+    #[case::sqrt("sqrt(2.25)", serde_json::json!({"value": 1.5, "pg_type": "FLOAT8"}))]
+    #[case::length("length('sleigh')", serde_json::json!({"value": 6, "pg_type": "INT4"}))]
+    #[case::upper("upper('ho%20ho')", serde_json::json!({"value": "HO HO", "pg_type": "TEXT"}))]
+    #[cfg_attr(not(feature = "ci"), ignore = "requires a PostgreSQL instance")]
+    #[test_log::test(tokio::test)]
+    async fn test_simple_sql_select_expressions(
+        state: ShuttleAppState,
+        #[case] expression: &str,
+        #[case] expected: Value,
+    ) -> anyhow::Result<()> {
+        let (status, content) = call(
+            &state,
+            Request::get(format!("{}?expr={expression}", routes::day13::sql()))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+        assert_eq!((StatusCode::OK, expected), (status, content));
+
+        Ok(())
+    }
+
+    /// Test that `get_recent_orders` returns the orders recorded
+    /// after `since`, with their database-assigned timestamps
+    #[rstest]
+    #[cfg_attr(not(feature = "ci"), ignore = "requires a PostgreSQL instance")]
+    #[test_log::test(tokio::test)]
+    async fn test_get_recent_orders(
+        #[future(awt)]
+        #[from(isolated_state)]
+        state: IsolatedState,
+    ) -> anyhow::Result<()> {
+        call(
+            &state,
+            Request::post(routes::day13::reset()).body(Body::empty())?,
+        )
+        .await?;
+        call(
+            &state,
+            json_post(
+                routes::day13::orders(),
+                r#"[
+                  {"id":2,"region_id":1,"gift_name":"Doll","quantity":2},
+                  {"id":1,"region_id":1,"gift_name":"Sled","quantity":3}
+                ]"#,
+            )?,
+        )
+        .await?;
+
+        for (since, expected_ids) in [
+            ("", vec![1, 2]),
+            ("?since=2000-01-01T00:00:00Z", vec![1, 2]),
+            ("?since=2999-01-01T00:00:00Z", vec![]),
+        ] {
+            let (status, content) = call(
+                &state,
+                Request::get(format!("{}{since}", routes::day13::orders_recent()))
+                    .body(Body::empty())?,
+            )
+            .await?;
+
+            assert_eq!(StatusCode::OK, status);
+
+            let orders: Vec<GiftOrder> = serde_json::from_value(content)?;
+
+            assert_eq!(
+                expected_ids,
+                orders.iter().map(|order| order.id).collect::<Vec<i64>>()
+            );
+            assert!(orders
+                .iter()
+                .all(|order| order.created_at.is_some() && order.updated_at.is_some()));
+        }
+
+        Ok(())
+    }
+
+    /// Test that `create_orders` rejects malformed orders
+    /// without ever touching the database
+    #[rstest]
+    #[case::not_a_list(r#"{"id": 1}"#)]
+    #[case::missing_fields(r#"[{"id": 1, "quantity": 5}]"#)]
+    #[case::wrong_types(r#"[{"id": "one", "quantity": 5, "gift_name": "Doll", "region_id": 1}]"#)]
+    #[test_log::test(tokio::test)]
+    async fn test_create_orders_rejects_malformed_orders(
+        state: ShuttleAppState,
+        #[case] orders: &str,
+    ) -> anyhow::Result<()> {
+        let (status, _) = call(&state, json_post(routes::day13::orders(), orders)?).await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, status);
+
+        Ok(())
+    }
+
+    /// Test that `simple_sql_select`, `reset_day_13_schema`, `create_orders`,
+    /// `total_order_count`, and `most_popular_gift` satisfy the conditions
+    /// of [CCH 2023 Challenge 13](https://console.shuttle.rs/cch/challenge/13)
+    #[rstest]
+    #[cfg_attr(not(feature = "ci"), ignore = "requires a PostgreSQL instance")]
+    #[test_log::test(tokio::test)]
+    async fn test_challenge_thirteen(
+        #[future(awt)]
+        #[from(isolated_state)]
+        state: IsolatedState,
+    ) -> anyhow::Result<()> {
+        let (status, content) = call(
+            &state,
+            Request::get(routes::day13::sql()).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!((StatusCode::OK, Value::from(20231213)), (status, content));
+
+        let (status, _) = call(
+            &state,
+            Request::post(routes::day13::reset()).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(StatusCode::OK, status);
+
+        let (status, content) = call(
+            &state,
+            Request::get(routes::day13::orders_popular()).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(
+            (StatusCode::OK, serde_json::json!({"popular": null})),
+            (status, content)
+        );
+
+        let (status, _) = call(
+            &state,
+            json_post(
+                routes::day13::orders(),
+                r#"[
+                  {"id":1,"region_id":2,"gift_name":"Toy Train","quantity":5},
+                  {"id":2,"region_id":2,"gift_name":"Doll","quantity":8},
+                  {"id":3,"region_id":3,"gift_name":"Action Figure","quantity":12},
+                  {"id":4,"region_id":4,"gift_name":"Board Game","quantity":10},
+                  {"id":5,"region_id":2,"gift_name":"Teddy Bear","quantity":6},
+                  {"id":6,"region_id":3,"gift_name":"Toy Train","quantity":3}
+                ]"#,
+            )?,
+        )
+        .await?;
+        assert_eq!(StatusCode::OK, status);
+
+        let (status, content) = call(
+            &state,
+            Request::get(routes::day13::orders_total()).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(
+            (StatusCode::OK, serde_json::json!({"total": 44})),
+            (status, content)
+        );
+
+        let (status, content) = call(
+            &state,
+            Request::get(routes::day13::orders_popular()).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(
+            (
+                StatusCode::OK,
+                serde_json::json!({"popular": "Action Figure"})
+            ),
+            (status, content)
+        );
+
+        let (status, _) = call(
+            &state,
+            json_post(
+                routes::day13::orders(),
+                r#"[{"id":1,"region_id":2,"gift_name":"Duplicate","quantity":1}]"#,
+            )?,
+        )
+        .await?;
+        assert_eq!(StatusCode::FAILED_DEPENDENCY, status);
+
+        Ok(())
+    }
 }
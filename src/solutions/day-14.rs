@@ -8,18 +8,25 @@ use axum::{
     extract::{Json, State},
     http::StatusCode,
 };
-use axum_template::TemplateEngine;
 
 // Crate-Level Imports
-use crate::state::ShuttleAppState;
+use crate::{
+    errors::{AppError, ErrorEnvelope},
+    routes,
+    solutions::{DayModule, DayRoutes},
+    state::ShuttleAppState,
+    templating::{self, TemplateHelper, HELPERS},
+};
 
 /// Complete [Day 14: Task](https://console.shuttle.rs/cch/challenge/14#:~:text=⭐)
 #[tracing::instrument(ret)]
 pub async fn render_html_unsafe(
     Json(data): Json<HashMap<String, String>>,
-) -> Result<String, StatusCode> {
+) -> Result<String, AppError> {
     data.get("content")
-        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)
+        .ok_or_else(|| {
+            ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, "no content supplied").into()
+        })
         .map(|content| {
             format!(
                 include_str!(concat!(
@@ -36,13 +43,62 @@ pub async fn render_html_unsafe(
 pub async fn render_html_safe(
     State(state): State<ShuttleAppState>,
     Json(data): Json<HashMap<String, String>>,
-) -> Result<String, (StatusCode, String)> {
-    state
-        .templates
-        .render("day-14/safe", data)
-        .map_err(|error| (StatusCode::UNPROCESSABLE_ENTITY, format!("{error}")))
+) -> Result<String, AppError> {
+    templating::render(
+        &state.templates,
+        &state.template_limits,
+        "day-14/safe",
+        data,
+    )
+    .map_err(AppError::from)
+}
+
+/// List the custom helpers available to templates
+#[tracing::instrument(ret)]
+pub async fn list_template_helpers() -> Json<&'static [TemplateHelper]> {
+    Json(&HELPERS)
+}
+
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 14](https://console.shuttle.rs/cch/challenge/14) solutions
+#[derive(Debug)]
+pub struct Day14;
+
+impl DayModule for Day14 {
+    fn day() -> i8 {
+        14
+    }
+
+    fn name() -> &'static str {
+        "HTML Rendering"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                14,
+                POST routes::day14::SAFE,
+                render_html_safe,
+                "Render escaped HTML"
+            ))
+            .route(day_route!(
+                14,
+                POST routes::day14::UNSAFE,
+                render_html_unsafe,
+                "Render unescaped HTML"
+            ))
+            .route(day_route!(
+                14,
+                GET routes::day14::HELPERS,
+                list_template_helpers,
+                "List the template helpers"
+            ))
+    }
 }
 
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -71,5 +127,87 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
+    use crate::routes;
     use crate::utils::{service, TestService};
+
+    /// Test that `render_html_unsafe` and `render_html_safe` satisfy the
+    /// conditions of [CCH 2023 Challenge 14](https://console.shuttle.rs/cch/challenge/14)
+    #[rstest]
+    #[case::challenge_example(
+        routes::day14::unsafe_html(),
+        r#"{"content": "<h1>Welcome to the North Pole!</h1>"}"#,
+        StatusCode::OK,
+        "<h1>Welcome to the North Pole!</h1>"
+    )]
+    #[case::bonus_example(
+        routes::day14::safe_html(),
+        r#"{"content": "<script>alert(\"XSS Attack!\")</script>"}"#,
+        StatusCode::OK,
+        "&lt;script&gt;alert(&quot;XSS Attack!&quot;)&lt;/script&gt;"
+    )]
+    #[case::missing_content(
+        routes::day14::unsafe_html(),
+        r#"{"contents": "oops"}"#,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ""
+    )]
+    #[case::malformed_body(
+        routes::day14::safe_html(),
+        r#"{"content": 42}"#,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ""
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_challenge_fourteen(
+        service: TestService,
+        #[case] url: &str,
+        #[case] data: &str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                Request::post(url)
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(data.to_string()))?,
+            )
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status.is_success() {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+            let content = String::from_utf8_lossy(content.as_ref());
+
+            assert!(content.starts_with("<html>"), "{content}");
+            assert!(
+                content.contains(&format!("<body>\n    {expected_content}\n  </body>")),
+                "{content}"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Test that `list_template_helpers` lists every custom helper
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_list_template_helpers(service: TestService) -> anyhow::Result<()> {
+        let response = service.resolve(routes::day14::helpers()).await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+        let helpers = serde_json::from_slice::<Vec<Value>>(content.as_ref())?
+            .into_iter()
+            .filter_map(|helper| helper["name"].as_str().map(String::from))
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            vec!["format_date", "pluralize", "json", "uppercase"],
+            helpers
+        );
+
+        Ok(())
+    }
 }
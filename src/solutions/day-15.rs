@@ -3,20 +3,27 @@
 //!
 
 // Standard Library Imports
+use core::cmp::Ordering;
 use std::iter::Iterator;
 use std::{
     collections::HashMap,
-    ops::{BitAnd, BitOr, Not},
+    ops::{BitAnd, BitOr, Not, RangeInclusive},
     string::ToString,
 };
 
 // Third-Party Imports
 use axum::{extract::Json, http::StatusCode};
 use itertools::Itertools;
-use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use unicode_normalization::UnicodeNormalization;
 
+// Crate-Level Imports
+use crate::{
+    record_fields, routes,
+    solutions::{DayModule, DayRoutes},
+    telemetry::fields,
+};
+
 // <editor-fold desc="// Type Aliases ...">
 
 type EvaluationResponse = (StatusCode, Json<HashMap<String, String>>);
@@ -27,45 +34,66 @@ type NaughtyNiceEvaluationResponse = Result<EvaluationResponse, EvaluationRespon
 
 // <editor-fold desc="// Static Values ...">
 
-// (0x2980..=0x2BFF)
-static NICE_RANGE: Lazy<Vec<char>> = Lazy::new(|| {
-    (0x2980..=0x2BFF)
-        .filter_map(char::from_u32)
-        .collect::<Vec<char>>()
-});
-static EMOJIS: Lazy<Vec<char>> = Lazy::new(|| {
-    itertools::chain![
-        0x0080..=0x02AF,
-        0x0300..=0x03FF,
-        0x0600..=0x06FF,
-        0x0C00..=0x0C7F,
-        0x1DC0..=0x1DFF,
-        0x1E00..=0x1EFF,
-        0x2000..=0x209F,
-        0x20D0..=0x214F,
-        0x2190..=0x23FF,
-        0x2460..=0x25FF,
-        0x2600..=0x27EF,
-        0x2900..=0x29FF,
-        0x2B00..=0x2BFF,
-        0x2C60..=0x2C7F,
-        0x2E00..=0x2E7F,
-        0x3000..=0x303F,
-        0xA490..=0xA4CF,
-        0xE000..=0xF8FF,
-        0xFE00..=0xFE0F,
-        0xFE30..=0xFE4F,
-        0x1F000..=0x1F02F,
-        0x1F0A0..=0x1F0FF,
-        0x1F100..=0x1F64F,
-        0x1F680..=0x1F6FF,
-        0x1F910..=0x1F96B,
-        0x1F980..=0x1F9E0,
-    ]
-    .filter_map(char::from_u32)
-    .filter(|c| NICE_RANGE.contains(c).not())
-    .collect::<Vec<char>>()
-});
+/// The unicode block(s) a "nice" password's
+/// special character must be drawn from
+const NICE_RANGE: RangeInclusive<char> = '\u{2980}'..='\u{2BFF}';
+
+/// Sorted, non-overlapping (inclusive) code point
+/// ranges considered to be "emoji" when evaluating
+/// passwords (less anything within `NICE_RANGE`)
+const EMOJI_RANGES: [(u32, u32); 26] = [
+    (0x0080, 0x02AF),
+    (0x0300, 0x03FF),
+    (0x0600, 0x06FF),
+    (0x0C00, 0x0C7F),
+    (0x1DC0, 0x1DFF),
+    (0x1E00, 0x1EFF),
+    (0x2000, 0x209F),
+    (0x20D0, 0x214F),
+    (0x2190, 0x23FF),
+    (0x2460, 0x25FF),
+    (0x2600, 0x27EF),
+    (0x2900, 0x29FF),
+    (0x2B00, 0x2BFF),
+    (0x2C60, 0x2C7F),
+    (0x2E00, 0x2E7F),
+    (0x3000, 0x303F),
+    (0xA490, 0xA4CF),
+    (0xE000, 0xF8FF),
+    (0xFE00, 0xFE0F),
+    (0xFE30, 0xFE4F),
+    (0x1F000, 0x1F02F),
+    (0x1F0A0, 0x1F0FF),
+    (0x1F100, 0x1F64F),
+    (0x1F680, 0x1F6FF),
+    (0x1F910, 0x1F96B),
+    (0x1F980, 0x1F9E0),
+];
+
+/// Check whether the supplied character is drawn
+/// from the "nice" special character block(s)
+fn is_nice_char(c: char) -> bool {
+    NICE_RANGE.contains(&c)
+}
+
+/// Check whether the supplied character is an
+/// "emoji" (per `EMOJI_RANGES`, less `NICE_RANGE`)
+fn is_emoji(c: char) -> bool {
+    let code = c as u32;
+
+    is_nice_char(c).not()
+        && EMOJI_RANGES
+            .binary_search_by(|&(start, end)| {
+                if end < code {
+                    Ordering::Less
+                } else if code < start {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .is_ok()
+}
 
 const NICE: fn() -> Result<EvaluationResponse, EvaluationResponse> = || {
     Ok((
@@ -125,8 +153,7 @@ impl NaughtyNiceEvaluation {
 
         for pair in password.nfkd().tuple_windows::<(char, char)>() {
             if blacklist.contains(&pair) {
-                tracing::Span::current()
-                    .record("error", format!("blacklisted character pair: {pair:?}"));
+                record_fields!(fields::ERROR => format!("blacklisted character pair: {pair:?}"));
 
                 return NAUGHTY();
             }
@@ -135,7 +162,7 @@ impl NaughtyNiceEvaluation {
             has_repeat = has_repeat.bitor(valid_pair(pair).bitand(pair.0 == pair.1));
         }
 
-        tracing::Span::current().record("vowels", vowel_count);
+        record_fields!("vowels" => vowel_count);
 
         let meets_vowel_count = 2 < vowel_count;
 
@@ -157,7 +184,7 @@ impl NaughtyNiceEvaluation {
             error.push_str("below required vowel count");
         };
 
-        tracing::Span::current().record("error", error);
+        record_fields!(fields::ERROR => error);
 
         NAUGHTY()
     }
@@ -346,7 +373,7 @@ impl NaughtyNiceEvaluation {
     fn _has_at_least_one_unicode_char_between_2980_and_2bff<'input>(
         password: &'input str,
     ) -> ComplexEvaluationResult<'input> {
-        if password.chars().any(|c| NICE_RANGE.contains(&c)) {
+        if password.chars().any(is_nice_char) {
             Ok(password)
         } else {
             Err((StatusCode::RANGE_NOT_SATISFIABLE, "outranged"))
@@ -358,7 +385,7 @@ impl NaughtyNiceEvaluation {
         password: &'input str,
     ) -> ComplexEvaluationResult<'input> {
         for (idx, chr) in password.char_indices() {
-            if EMOJIS.contains(&chr) {
+            if is_emoji(chr) {
                 tracing::warn!(
                     "emoji '{chr}' ({:#x}) detected at position {idx} in password '{password}'",
                     chr as u32
@@ -369,7 +396,7 @@ impl NaughtyNiceEvaluation {
 
         Err((StatusCode::UPGRADE_REQUIRED, "\u{1F633}"))
 
-        // if password.chars().any(|c| EMOJIS.contains(&c)) {
+        // if password.chars().any(is_emoji) {
         //     Ok(password)
         // } else {
         //     Err((StatusCode::UPGRADE_REQUIRED, "\u{1F633}"))
@@ -396,7 +423,7 @@ impl NaughtyNiceEvaluation {
 // </editor-fold desc="// NaughtyNiceEvaluation ...">
 
 /// Complete [Day 15: Task](https://console.shuttle.rs/cch/challenge/15#:~:text=⭐)
-#[tracing::instrument(ret, skip(request) fields(error, vowels, input = request.input))]
+#[tracing::instrument(ret, skip(request) fields({fields::ERROR}, vowels))]
 pub async fn assess_naughty_or_nice(
     Json(request): Json<NaughtyNiceEvaluation>,
 ) -> NaughtyNiceEvaluationResponse {
@@ -405,13 +432,47 @@ pub async fn assess_naughty_or_nice(
 
 /// Complete [Day 15: Bonus](https://console.shuttle.rs/cch/challenge/15#:~:text=🎁)
 #[allow(unused_variables)]
-#[tracing::instrument(ret, skip(request) fields(input = request.input))]
+#[tracing::instrument(ret, skip(request))]
 pub async fn game_of_the_year(
     Json(request): Json<NaughtyNiceEvaluation>,
 ) -> NaughtyNiceEvaluationResponse {
     request.evaluate_complex()
 }
 
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 15](https://console.shuttle.rs/cch/challenge/15) solutions
+#[derive(Debug)]
+pub struct Day15;
+
+impl DayModule for Day15 {
+    fn day() -> i8 {
+        15
+    }
+
+    fn name() -> &'static str {
+        "Naughty or Nice Passwords"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                15,
+                POST routes::day15::NICE,
+                assess_naughty_or_nice,
+                "Judge a password naughty or nice"
+            ))
+            .route(day_route!(
+                15,
+                POST routes::day15::GAME,
+                game_of_the_year,
+                "Judge a password by the game's rules"
+            ))
+    }
+}
+
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -441,4 +502,84 @@ mod tests {
 
     // Crate-Level Imports
     use crate::utils::{service, TestService};
+
+    // <editor-fold desc="// Helper Values ...">
+
+    extern crate test;
+
+    use test::Bencher;
+
+    use super::{is_emoji, is_nice_char, EMOJI_RANGES, NICE_RANGE};
+
+    /// The previous (linear scan) "nice" character table
+    static LINEAR_NICE: Lazy<Vec<char>> = Lazy::new(|| NICE_RANGE.collect::<Vec<char>>());
+
+    /// The previous (linear scan) emoji character table
+    static LINEAR_EMOJIS: Lazy<Vec<char>> = Lazy::new(|| {
+        EMOJI_RANGES
+            .iter()
+            .flat_map(|&(start, end)| start..=end)
+            .filter_map(char::from_u32)
+            .filter(|c| !LINEAR_NICE.contains(c))
+            .collect::<Vec<char>>()
+    });
+
+    /// A multi-kilobyte password containing neither
+    /// "nice" characters nor emoji (i.e. the worst
+    /// case for both lookups)
+    static LONG_INPUT: Lazy<String> =
+        Lazy::new(|| "2000 and 23 reasons to be jOyful xyx ".repeat(256));
+
+    // </editor-fold desc="// Helper Values ...">
+
+    /// Test that the range-table lookups classify each range's
+    /// boundaries (and their neighbours), each range's midpoint,
+    /// and a spread of other code points identically to the
+    /// linear-scan tables
+    #[test]
+    fn test_char_classes_match_linear_tables() {
+        let nice = (*NICE_RANGE.start() as u32, *NICE_RANGE.end() as u32);
+
+        let boundaries = EMOJI_RANGES
+            .iter()
+            .chain([&nice])
+            .flat_map(|&(start, end)| {
+                [
+                    start.saturating_sub(1),
+                    start,
+                    start + 1,
+                    start + (end - start) / 2,
+                    end - 1,
+                    end,
+                    end + 1,
+                ]
+            });
+
+        let samples = (0u32..=0x1FFFF).step_by(0x101);
+
+        for c in boundaries.chain(samples).filter_map(char::from_u32) {
+            assert_eq!(LINEAR_NICE.contains(&c), is_nice_char(c), "nice: {c:?}");
+            assert_eq!(LINEAR_EMOJIS.contains(&c), is_emoji(c), "emoji: {c:?}");
+        }
+    }
+
+    #[bench]
+    fn bench_linear_char_classes(bencher: &mut Bencher) {
+        Lazy::force(&LINEAR_EMOJIS);
+
+        bencher.iter(|| {
+            test::black_box(&*LONG_INPUT)
+                .chars()
+                .any(|c| LINEAR_NICE.contains(&c) || LINEAR_EMOJIS.contains(&c))
+        });
+    }
+
+    #[bench]
+    fn bench_range_table_char_classes(bencher: &mut Bencher) {
+        bencher.iter(|| {
+            test::black_box(&*LONG_INPUT)
+                .chars()
+                .any(|c| is_nice_char(c) || is_emoji(c))
+        });
+    }
 }
@@ -6,13 +6,24 @@ use axum::{
     extract::{Json, Path, State},
     http::StatusCode,
 };
+use chrono::{DateTime, Utc};
 use futures::prelude::*;
 use serde::{Deserialize, Serialize};
-use serde_json::{Map as JsonObject, Value};
+use serde_json::json;
 use sqlx::{error::Error as DbError, postgres::PgQueryResult, FromRow};
 
 // Crate-Level Imports
-use crate::state::ShuttleAppState;
+use crate::{
+    errors::AppError,
+    metrics::TimedQuery,
+    record_db, routes,
+    solutions::{
+        day_13::{self, GiftOrder, OrderSchema},
+        DayModule, DayRoutes,
+    },
+    state::ShuttleAppState,
+    telemetry::{fields, spans::DB_QUERY},
+};
 
 // <editor-fold desc="// RegionalTopGifts ...">
 
@@ -59,9 +70,38 @@ pub struct GiftOrderRegion {
     pub id: i64,
     /// the region's elf-readable name
     pub name: String,
+    /// when the region was recorded (set by the database)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// when the region was last modified (set by the database)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl GiftOrderRegion {
+    /// (Re)create the (empty) `regions` table
+    pub async fn reset_schema(db: &sqlx::PgPool) -> Result<PgQueryResult, DbError> {
+        OrderSchema::Day18.ensure(db).await?;
+
+        sqlx::query("DROP TABLE IF EXISTS cch18.regions;")
+            .execute(db)
+            .and_then(|_| {
+                sqlx::query(
+                    r#"CREATE TABLE cch18.regions (
+                      id INT PRIMARY KEY,
+                      name VARCHAR(50),
+                      created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                      updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+                    );
+                "#,
+                )
+                .execute(db)
+            })
+            .await?;
+
+        OrderSchema::Day18.track_updates("regions", db).await
+    }
+
     /// ...
     pub async fn insert(&self, db: &sqlx::PgPool) -> Result<PgQueryResult, DbError> {
         Self::insert_many([self].into_iter(), db).await
@@ -72,7 +112,7 @@ impl GiftOrderRegion {
         orders: Orders,
         db: &sqlx::PgPool,
     ) -> Result<PgQueryResult, DbError> {
-        sqlx::QueryBuilder::<sqlx::Postgres>::new("INSERT INTO regions (id, name) ")
+        sqlx::QueryBuilder::<sqlx::Postgres>::new("INSERT INTO cch18.regions (id, name) ")
             .push_values(orders, |mut builder, region| {
                 builder.push_bind(region.id).push_bind(region.name.clone());
             })
@@ -82,32 +122,36 @@ impl GiftOrderRegion {
     }
 
     /// ...
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
     pub async fn total_orders_by_region(
         db: &sqlx::PgPool,
     ) -> Result<Vec<RegionalOrderTotal>, DbError> {
-        sqlx::query_as::<_, RegionalOrderTotal>(
+        let totals = sqlx::query_as::<_, RegionalOrderTotal>(
             r#"SELECT
               regions.name,
               SUM(orders.quantity) AS total_orders
             FROM
-              regions
+              cch18.regions AS regions
             INNER JOIN
-              orders ON regions.id = orders.region_id
+              cch18.orders AS orders ON regions.id = orders.region_id
             GROUP BY
               regions.name
             ORDER BY
               regions.name ASC"#,
         )
         .fetch_all(db)
-        .await
+        .await;
+
+        record_db!("total regional orders", totals)
     }
 
     /// ...
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
     pub async fn top_n_most_popular(
         number: u64,
         db: &sqlx::PgPool,
     ) -> Result<Vec<RegionalTopGifts>, DbError> {
-        sqlx::query_as::<sqlx::Postgres, RegionalTopGifts>(
+        let ranked = sqlx::query_as::<sqlx::Postgres, RegionalTopGifts>(
             r#"
             WITH ranked_gifts AS (
               SELECT
@@ -120,8 +164,8 @@ impl GiftOrderRegion {
                     orders.gift_name ASC
                 ) AS row_number
               FROM
-                regions
-                LEFT JOIN orders ON regions.id = orders.region_id
+                cch18.regions AS regions
+                LEFT JOIN cch18.orders AS orders ON regions.id = orders.region_id
               GROUP BY
                 regions.name,
                 orders.gift_name
@@ -146,7 +190,9 @@ impl GiftOrderRegion {
         )
         .bind(number as i64)
         .fetch_all(db)
-        .await
+        .await;
+
+        record_db!("rank regional gifts", ranked)
     }
 }
 
@@ -156,35 +202,21 @@ impl GiftOrderRegion {
 #[tracing::instrument(ret, err(Debug), skip(state))]
 pub async fn reset_day_18_schema(
     State(state): State<ShuttleAppState>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    sqlx::query("DROP TABLE IF EXISTS orders;")
-        .execute(&state.db)
-        .and_then(|_| sqlx::query("DROP TABLE IF EXISTS regions;").execute(&state.db))
-        .and_then(|_| {
-            sqlx::query(
-                r#"CREATE TABLE regions (
-                  id INT PRIMARY KEY,
-                  name VARCHAR(50)
-                );
-            "#,
-            )
-            .execute(&state.db)
-        })
-        .and_then(|_| {
-            sqlx::query(
-                r#"CREATE TABLE IF NOT EXISTS orders (
-                 id INT PRIMARY KEY,
-                 gift_name VARCHAR(50),
-                 quantity INT,
-                 region_id INT
-               );
-            "#,
-            )
-            .execute(&state.db)
-        })
-        .await
-        .map(|_| StatusCode::OK)
-        .map_err(|error| (StatusCode::FAILED_DEPENDENCY, format!("{error}")))
+) -> Result<StatusCode, AppError> {
+    GiftOrderRegion::reset_schema(&state.db)
+        .and_then(|_| GiftOrder::reset_schema(OrderSchema::Day18, &state.db))
+        .await?;
+
+    Ok(StatusCode::OK)
+}
+
+/// Insert gift orders for [Day 18: Task 1](https://console.shuttle.rs/cch/challenge/18#:~:text=⭐)
+#[tracing::instrument(ret, err(Debug), skip_all, fields(orders.count = orders.len()))]
+pub async fn create_regional_orders(
+    State(state): State<ShuttleAppState>,
+    Json(orders): Json<Vec<GiftOrder>>,
+) -> Result<StatusCode, AppError> {
+    day_13::insert_orders(&state, orders, OrderSchema::Day18).await
 }
 
 /// Endpoint 2/3 for [Day 18: Task 1](https://console.shuttle.rs/cch/challenge/18#:~:text=⭐)
@@ -192,37 +224,30 @@ pub async fn reset_day_18_schema(
 pub async fn create_regions(
     State(state): State<ShuttleAppState>,
     Json(regions): Json<Vec<GiftOrderRegion>>,
-) -> Result<StatusCode, (StatusCode, Json<Value>)> {
+) -> Result<StatusCode, AppError> {
     if !regions.is_empty() {
         GiftOrderRegion::insert_many(regions.iter(), &state.db)
             .await
-            .map(|_| StatusCode::OK)
-            .map_err(|error| {
-                (
-                    StatusCode::FAILED_DEPENDENCY,
-                    Json(Value::Object(JsonObject::<String, Value>::from_iter([
-                        ("error".to_string(), Value::String(format!("{error}"))),
-                        (
-                            "request".to_string(),
-                            serde_json::to_value(regions).unwrap(),
-                        ),
-                    ]))),
-                )
-            })
-    } else {
-        Ok(StatusCode::OK)
+            .map_err(|error| AppError::from(error).with_details(json!({ "request": regions })))?;
     }
+
+    Ok(StatusCode::OK)
 }
 
 /// Endpoint 3/3 for [Day 18: Task 1](https://console.shuttle.rs/cch/challenge/18#:~:text=⭐)
 #[tracing::instrument(ret, err(Debug), skip(state))]
 pub async fn get_order_count_by_region(
     State(state): State<ShuttleAppState>,
-) -> Result<Json<Vec<RegionalOrderTotal>>, (StatusCode, String)> {
-    GiftOrderRegion::total_orders_by_region(&state.db)
-        .await
-        .map(Json)
-        .map_err(|error| (StatusCode::FAILED_DEPENDENCY, format!("{error}")))
+) -> Result<Json<Vec<RegionalOrderTotal>>, AppError> {
+    Ok(Json(
+        state
+            .metrics
+            .time_query(
+                TimedQuery::RegionalTotals,
+                GiftOrderRegion::total_orders_by_region(&state.db),
+            )
+            .await?,
+    ))
 }
 
 /// Complete [Day 18: Bonus](https://console.shuttle.rs/cch/challenge/18#:~:text=🎁)
@@ -230,13 +255,65 @@ pub async fn get_order_count_by_region(
 pub async fn get_top_n_gifts_by_region(
     State(state): State<ShuttleAppState>,
     Path(number): Path<u64>,
-) -> Result<Json<Vec<RegionalTopGifts>>, (StatusCode, String)> {
-    GiftOrderRegion::top_n_most_popular(number, &state.db)
-        .await
-        .map(Json)
-        .map_err(|error| (StatusCode::FAILED_DEPENDENCY, format!("{error}")))
+) -> Result<Json<Vec<RegionalTopGifts>>, AppError> {
+    Ok(Json(
+        state
+            .metrics
+            .time_query(
+                TimedQuery::RegionalTopGifts,
+                GiftOrderRegion::top_n_most_popular(number, &state.db),
+            )
+            .await?,
+    ))
+}
+
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 18](https://console.shuttle.rs/cch/challenge/18) solutions
+#[derive(Debug)]
+pub struct Day18;
+
+impl DayModule for Day18 {
+    fn day() -> i8 {
+        18
+    }
+
+    fn name() -> &'static str {
+        "Regional Gift Orders"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                18,
+                POST routes::day18::RESET,
+                reset_day_18_schema,
+                "Reset the regional orders schema"
+            ))
+            .route(day_route!(
+                18,
+                POST routes::day18::ORDERS,
+                create_regional_orders,
+                "Insert regional gift orders"
+            ))
+            .route(day_route!(18, POST routes::day18::REGIONS, create_regions, "Insert regions"))
+            .route(day_route!(
+                18,
+                GET routes::day18::REGIONS_TOTAL,
+                get_order_count_by_region,
+                "Count the orders in each region"
+            ))
+            .route(day_route!(
+                18,
+                GET routes::day18::TOP_LIST,
+                get_top_n_gifts_by_region,
+                "List each region's top gifts"
+            ))
+    }
 }
 
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -265,5 +342,236 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
+    use crate::routes;
+    use crate::state::ShuttleAppState;
     use crate::utils::{service, TestService};
+    use crate::{
+        router,
+        utils::{isolated_state, state, IsolatedState},
+    };
+
+    /// Resolve the supplied request against a fresh service
+    /// sharing `state`, returning the response's status and
+    /// (JSON-decoded, if possible) content
+    async fn call(
+        state: &ShuttleAppState,
+        request: Request<Body>,
+    ) -> anyhow::Result<(StatusCode, Value)> {
+        let response = TestService::from(router(state.clone()))
+            .resolve(request)
+            .await?;
+        let status = response.status();
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+
+        Ok((
+            status,
+            serde_json::from_slice(content.as_ref())
+                .unwrap_or_else(|_| Value::from(String::from_utf8_lossy(content.as_ref()))),
+        ))
+    }
+
+    fn json_post(url: &str, data: &str) -> anyhow::Result<Request<Body>> {
+        Ok(Request::post(url)
+            .header(headers::CONTENT_TYPE, "application/json")
+            .body(Body::from(data.to_string()))?)
+    }
+
+    /// Test that `get_top_n_gifts_by_region` rejects
+    /// non-numeric limits without touching the database
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_top_n_gifts_rejects_malformed_number(
+        state: ShuttleAppState,
+    ) -> anyhow::Result<()> {
+        let (status, _) = call(
+            &state,
+            Request::get(routes::day18::top_list("two")).body(Body::empty())?,
+        )
+        .await?;
+
+        assert_eq!(StatusCode::BAD_REQUEST, status);
+
+        Ok(())
+    }
+
+    /// Test that `reset_day_18_schema`, `create_regions`, `get_order_count_by_region`,
+    /// and `get_top_n_gifts_by_region` satisfy the conditions of
+    /// [CCH 2023 Challenge 18](https://console.shuttle.rs/cch/challenge/18)
+    #[rstest]
+    #[cfg_attr(not(feature = "ci"), ignore = "requires a PostgreSQL instance")]
+    #[test_log::test(tokio::test)]
+    async fn test_challenge_eighteen(
+        #[future(awt)]
+        #[from(isolated_state)]
+        state: IsolatedState,
+    ) -> anyhow::Result<()> {
+        let (status, _) = call(
+            &state,
+            Request::post(routes::day18::reset()).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(StatusCode::OK, status);
+
+        let (status, _) = call(
+            &state,
+            json_post(
+                routes::day18::regions(),
+                r#"[
+                  {"id":1,"name":"North Pole"},
+                  {"id":2,"name":"Europe"},
+                  {"id":3,"name":"North America"},
+                  {"id":4,"name":"South America"},
+                  {"id":5,"name":"Africa"},
+                  {"id":6,"name":"Asia"},
+                  {"id":7,"name":"Oceania"}
+                ]"#,
+            )?,
+        )
+        .await?;
+        assert_eq!(StatusCode::OK, status);
+
+        let (status, _) = call(
+            &state,
+            json_post(
+                routes::day18::orders(),
+                r#"[
+                  {"id":1,"region_id":2,"gift_name":"Board Game","quantity":5},
+                  {"id":2,"region_id":2,"gift_name":"Origami Set","quantity":8},
+                  {"id":3,"region_id":3,"gift_name":"Action Figure","quantity":12},
+                  {"id":4,"region_id":4,"gift_name":"Teddy Bear","quantity":10},
+                  {"id":5,"region_id":2,"gift_name":"Yarn Ball","quantity":6},
+                  {"id":6,"region_id":3,"gift_name":"Art Set","quantity":3},
+                  {"id":7,"region_id":5,"gift_name":"Robot Lego Kit","quantity":5},
+                  {"id":8,"region_id":6,"gift_name":"Drone","quantity":9}
+                ]"#,
+            )?,
+        )
+        .await?;
+        assert_eq!(StatusCode::OK, status);
+
+        let (status, content) = call(
+            &state,
+            Request::get(routes::day18::regions_total()).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(
+            (
+                StatusCode::OK,
+                serde_json::json!([
+                  {"region":"Africa","total":5},
+                  {"region":"Asia","total":9},
+                  {"region":"Europe","total":19},
+                  {"region":"North America","total":15},
+                  {"region":"South America","total":10}
+                ])
+            ),
+            (status, content)
+        );
+
+        let (status, content) = call(
+            &state,
+            Request::get(routes::day18::top_list(2)).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(
+            (
+                StatusCode::OK,
+                serde_json::json!([
+                  {"region":"Africa","top_gifts":["Robot Lego Kit"]},
+                  {"region":"Asia","top_gifts":["Drone"]},
+                  {"region":"Europe","top_gifts":["Origami Set","Yarn Ball"]},
+                  {"region":"North America","top_gifts":["Action Figure","Art Set"]},
+                  {"region":"North Pole","top_gifts":[]},
+                  {"region":"Oceania","top_gifts":[]},
+                  {"region":"South America","top_gifts":["Teddy Bear"]}
+                ])
+            ),
+            (status, content)
+        );
+
+        Ok(())
+    }
+
+    /// Test that resetting day 13's schema leaves
+    /// day 18's orders (and vice versa) untouched
+    #[rstest]
+    #[cfg_attr(not(feature = "ci"), ignore = "requires a PostgreSQL instance")]
+    #[test_log::test(tokio::test)]
+    async fn test_schemas_are_isolated(
+        #[future(awt)]
+        #[from(isolated_state)]
+        state: IsolatedState,
+    ) -> anyhow::Result<()> {
+        for (reset, orders) in [
+            (routes::day13::reset(), routes::day13::orders()),
+            (routes::day18::reset(), routes::day18::orders()),
+        ] {
+            call(&state, Request::post(reset).body(Body::empty())?).await?;
+            call(
+                &state,
+                json_post(
+                    orders,
+                    r#"[{"id":1,"region_id":1,"gift_name":"Sled","quantity":3}]"#,
+                )?,
+            )
+            .await?;
+        }
+
+        call(
+            &state,
+            json_post(
+                routes::day18::regions(),
+                r#"[{"id":1,"name":"North Pole"}]"#,
+            )?,
+        )
+        .await?;
+
+        let (status, _) = call(
+            &state,
+            Request::post(routes::day13::reset()).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(StatusCode::OK, status);
+
+        let (status, content) = call(
+            &state,
+            Request::get(routes::day18::regions_total()).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(
+            (
+                StatusCode::OK,
+                serde_json::json!([{"region":"North Pole","total":3}])
+            ),
+            (status, content)
+        );
+
+        call(
+            &state,
+            json_post(
+                routes::day13::orders(),
+                r#"[{"id":1,"region_id":1,"gift_name":"Doll","quantity":2}]"#,
+            )?,
+        )
+        .await?;
+
+        let (status, _) = call(
+            &state,
+            Request::post(routes::day18::reset()).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(StatusCode::OK, status);
+
+        let (status, content) = call(
+            &state,
+            Request::get(routes::day13::orders_total()).body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(
+            (StatusCode::OK, serde_json::json!({"total": 2})),
+            (status, content)
+        );
+
+        Ok(())
+    }
 }
@@ -4,31 +4,58 @@
 // Standard Library Imports
 use core::fmt::Debug;
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    net::SocketAddr,
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc,
+        Arc, RwLock,
     },
+    time::Duration,
 };
 
 // Third-Party Imports
 use axum::{
+    body::StreamBody,
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        FromRef, Json, Path, State,
+        ConnectInfo, FromRef, Json, Path, Query, State,
     },
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     response::IntoResponse,
 };
+use b64::{engine::general_purpose::URL_SAFE_NO_PAD as base64, Engine};
+use chrono::{DateTime, Utc};
 use futures_util::{
     sink::SinkExt,
-    stream::{SplitSink, SplitStream, StreamExt},
+    stream::{self, SplitSink, SplitStream, StreamExt},
 };
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
-use tokio::sync::{broadcast, Mutex};
+use sha2::Sha256;
+use shuttle_secrets::SecretStore;
+use sqlx::error::Error as DbError;
+use tokio::{
+    sync::{broadcast, Mutex, OnceCell},
+    time::Instant,
+};
+use tracing::Instrument;
+use uuid::Uuid;
 
 // Crate-Level Imports
-use crate::state::ShuttleAppState;
+use crate::{
+    clock::{Clock, SystemClock},
+    errors::{AppError, ErrorEnvelope},
+    jobs::JobQueue,
+    metrics::LiveSocket,
+    record_db, record_fields,
+    reporting::{ErrorEvent, ErrorReporter},
+    routes,
+    shared::SharedState,
+    solutions::{counters::Counter, DayModule, DayRoutes},
+    state::ShuttleAppState,
+    telemetry::{fields, spans::DB_QUERY},
+    validation::{Validate, ValidatedQuery, ValidationErrors},
+};
 
 // <editor-fold desc="// SocketPongSession ...">
 
@@ -41,9 +68,9 @@ impl SocketPongSession {
         Self::default()
     }
 
-    #[tracing::instrument(skip_all, fields(socket))]
+    #[tracing::instrument(skip_all, fields({fields::SOCKET}))]
     async fn play(mut self, mut socket: WebSocket) {
-        tracing::Span::current().record("socket", format!("{:p}", &socket));
+        record_fields!(fields::SOCKET => format!("{:p}", &socket));
 
         while let Some(Ok(message)) = socket.recv().await {
             match (self.0, message.to_text()) {
@@ -82,9 +109,9 @@ impl SocketPongSession {
 
 // <editor-fold desc="// ChatMessage ...">
 
-/// A message from a specific user
+/// A plain text message from a specific user
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct ChatMessage {
+pub struct ChatText {
     /// the connected user's name
     #[serde(default)]
     user: String,
@@ -92,8 +119,240 @@ pub struct ChatMessage {
     message: String,
 }
 
+/// The `type` tag of a [`ChatReaction`]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReactionTag {
+    #[default]
+    Reaction,
+}
+
+/// The `type` tag of a [`ChatTyping`] indicator
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TypingTag {
+    #[default]
+    Typing,
+}
+
+/// An emoji reaction to an earlier message
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChatReaction {
+    /// the frame's type tag
+    #[serde(rename = "type")]
+    tag: ReactionTag,
+    /// the connected user's name
+    #[serde(default)]
+    user: String,
+    /// when the message being reacted to was sent
+    target_ts: DateTime<Utc>,
+    /// the reaction itself
+    emoji: String,
+}
+
+impl ChatReaction {
+    /// The emoji users may react with
+    const ALLOWED_EMOJI: [&'static str; 8] = ["👍", "👎", "❤️", "😂", "🎉", "🎄", "🎁", "⭐"];
+}
+
+/// An (ephemeral) indicator that a user is typing
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChatTyping {
+    /// the frame's type tag
+    #[serde(rename = "type")]
+    tag: TypingTag,
+    /// the connected user's name
+    #[serde(default)]
+    user: String,
+}
+
+/// Debounces a connection's (and so its
+/// user's) typing indicators
+#[derive(Debug, Default)]
+struct TypingDebounce(Option<DateTime<Utc>>);
+
+impl TypingDebounce {
+    /// The minimum interval between propagated indicators
+    const INTERVAL: chrono::Duration = chrono::Duration::seconds(3);
+
+    /// Whether an indicator received at `now` should be propagated
+    fn admit(&mut self, now: DateTime<Utc>) -> bool {
+        if self
+            .0
+            .is_some_and(|last| now.signed_duration_since(last) < Self::INTERVAL)
+        {
+            return false;
+        }
+
+        self.0 = Some(now);
+
+        true
+    }
+}
+
+/// A frame sent by a specific user, either a plain text
+/// message (which carries no `type` tag, for the sake of
+/// existing clients) or a tagged reaction or indicator
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ChatMessage {
+    /// an emoji reaction to an earlier message
+    Reaction(ChatReaction),
+    /// an indicator that the user is typing
+    Typing(ChatTyping),
+    /// a plain text message
+    Text(ChatText),
+}
+
+impl ChatMessage {
+    /// The maximum length of a propagated message
+    const MAX_LENGTH: usize = 128;
+
+    /// Attribute the message to the supplied user
+    fn set_user(&mut self, user: &str) {
+        match self {
+            Self::Reaction(reaction) => user.clone_into(&mut reaction.user),
+            Self::Typing(typing) => user.clone_into(&mut typing.user),
+            Self::Text(text) => user.clone_into(&mut text.user),
+        }
+    }
+
+    /// Whether the message is fit for propagation
+    fn is_deliverable(&self) -> bool {
+        match self {
+            Self::Reaction(reaction) => {
+                ChatReaction::ALLOWED_EMOJI.contains(&reaction.emoji.as_str())
+            }
+            Self::Typing(_) => true,
+            Self::Text(text) => !text.message.is_empty() && text.message.len() <= Self::MAX_LENGTH,
+        }
+    }
+
+    /// Whether delivering the message counts toward the running
+    /// total of "seen" messages (reactions and typing indicators don't)
+    fn is_viewable(&self) -> bool {
+        matches!(self, Self::Text(_))
+    }
+
+    /// The record of the message (if it's a
+    /// deliverable text message) sent at `timestamp`
+    fn record(&self, timestamp: DateTime<Utc>) -> Option<ChatRecord> {
+        match self {
+            Self::Text(text) if self.is_deliverable() => {
+                Some(ChatRecord::new(text.clone(), timestamp))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A message relayed to the service's other
+/// instances (via the shared state's broadcasts)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct RelayedMessage {
+    /// the id of the instance the message was sent to
+    origin: Uuid,
+    /// the room the message was sent to
+    room: u64,
+    /// the message itself
+    message: ChatMessage,
+    /// when the message was sent
+    timestamp: DateTime<Utc>,
+}
+
+impl RelayedMessage {
+    /// The prefix of the shared channels
+    /// messages are relayed over (by room)
+    const CHANNEL_PREFIX: &'static str = "cch:chat:";
+
+    /// The shared channel the message is relayed over
+    fn channel(&self) -> String {
+        format!("{}{}", Self::CHANNEL_PREFIX, self.room)
+    }
+}
+
 // </editor-fold desc="// ChatMessage ...">
 
+// <editor-fold desc="// ChatRecord ...">
+
+/// A timestamped record of a message
+/// sent to a specific chat room
+#[cfg_attr(test, derive(Eq, PartialEq))]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChatRecord {
+    /// the sending user's name
+    user: String,
+    /// the message's content
+    message: String,
+    /// when the message was sent
+    timestamp: DateTime<Utc>,
+}
+
+impl From<(String, String, DateTime<Utc>)> for ChatRecord {
+    fn from((user, message, timestamp): (String, String, DateTime<Utc>)) -> Self {
+        Self {
+            user,
+            message,
+            timestamp,
+        }
+    }
+}
+
+impl ChatRecord {
+    /// Record the supplied message as sent at `timestamp`
+    fn new(message: ChatText, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            user: message.user,
+            message: message.message,
+            timestamp,
+        }
+    }
+}
+
+// </editor-fold desc="// ChatRecord ...">
+
+// <editor-fold desc="// SystemNotice ...">
+
+/// A notice sent to a single connection by
+/// the service itself (rather than a user)
+#[cfg_attr(test, derive(Eq, PartialEq))]
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "notice", rename_all = "snake_case")]
+pub enum SystemNotice {
+    /// The connection fell behind the room and
+    /// `dropped` messages were never delivered to it
+    Lagged {
+        /// the number of undelivered messages
+        dropped: u64,
+    },
+}
+
+// </editor-fold desc="// SystemNotice ...">
+
+// <editor-fold desc="// ChatPresence ...">
+
+/// A user currently connected to a chat room
+#[derive(Clone, Debug, Serialize)]
+pub struct ChatPresence {
+    /// the connected user's name
+    user: String,
+    /// the number of messages the connection
+    /// has dropped by falling behind the room
+    lagged: u64,
+}
+
+/// A single live connection to a chat room
+#[derive(Debug)]
+struct ConnectedUser {
+    /// the connected user's name
+    user: String,
+    /// running total of messages dropped
+    /// by falling behind the room
+    lagged: Arc<AtomicU64>,
+}
+
+// </editor-fold desc="// ChatPresence ...">
+
 // <editor-fold desc="// WsComPair ...">
 
 #[derive(Clone, Debug)]
@@ -148,14 +407,512 @@ impl ChatRoomConnection {
 
 // </editor-fold desc="// ChatRoomConnection ...">
 
+// <editor-fold desc="// ViewCountWait ...">
+
+/// Query parameters for long-polling
+/// the chat view counter
+#[derive(Debug, Deserialize)]
+pub struct ViewCountWait {
+    /// The last-known view count
+    #[serde(default)]
+    since: u64,
+    /// The maximum number of seconds to wait
+    #[serde(default = "ViewCountWait::default_timeout")]
+    timeout: u64,
+}
+
+impl ViewCountWait {
+    /// The maximum permissible wait (in seconds)
+    const MAX_TIMEOUT: u64 = 300;
+
+    fn default_timeout() -> u64 {
+        30
+    }
+}
+
+impl Validate for ViewCountWait {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if Self::MAX_TIMEOUT < self.timeout {
+            errors.add(
+                "timeout",
+                format!("must not exceed {} seconds", Self::MAX_TIMEOUT),
+            );
+        }
+
+        errors.into_result()
+    }
+}
+
+// </editor-fold desc="// ViewCountWait ...">
+
+// <editor-fold desc="// TranscriptRange ...">
+
+/// Query parameters for filtering
+/// a chat room's transcript
+#[derive(Debug, Deserialize)]
+pub struct TranscriptRange {
+    /// The earliest timestamp to include
+    from: Option<DateTime<Utc>>,
+    /// The latest timestamp to include
+    to: Option<DateTime<Utc>>,
+}
+
+impl TranscriptRange {
+    /// Whether the supplied record falls within the range
+    fn contains(&self, record: &ChatRecord) -> bool {
+        self.from.is_none_or(|from| from <= record.timestamp)
+            && self.to.is_none_or(|to| record.timestamp <= to)
+    }
+}
+
+impl Validate for TranscriptRange {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if to < from {
+                errors.add("to", "must not precede `from`");
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+// </editor-fold desc="// TranscriptRange ...">
+
+// <editor-fold desc="// RecentMessages ...">
+
+/// Query parameters for retrieving a
+/// chat room's most recent messages
+#[derive(Debug, Deserialize)]
+pub struct RecentMessages {
+    /// The maximum number of messages to retrieve
+    #[serde(default = "RecentMessages::default_limit")]
+    limit: u64,
+}
+
+impl RecentMessages {
+    /// The maximum permissible limit
+    const MAX_LIMIT: u64 = 1000;
+
+    fn default_limit() -> u64 {
+        50
+    }
+}
+
+impl Validate for RecentMessages {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if Self::MAX_LIMIT < self.limit {
+            errors.add("limit", format!("must not exceed {}", Self::MAX_LIMIT));
+        }
+
+        errors.into_result()
+    }
+}
+
+// </editor-fold desc="// RecentMessages ...">
+
+// <editor-fold desc="// ChatArchive ...">
+
+/// A Postgres-backed archive of chat messages
+#[derive(Clone, Debug)]
+pub struct ChatArchive {
+    /// A pool of connections to the
+    /// service's PostgreSQL database
+    db: sqlx::PgPool,
+    /// How long archived messages are retained
+    /// (indefinitely, if unspecified)
+    retention: Option<chrono::Duration>,
+    /// Whether the archive's schema has been ensured
+    schema: Arc<OnceCell<()>>,
+}
+
+impl ChatArchive {
+    /// Create an archive backed by the supplied database
+    pub fn new(db: sqlx::PgPool, retention: Option<chrono::Duration>) -> Self {
+        Self {
+            db,
+            retention,
+            schema: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// Create an archive if the `CHAT_ARCHIVE` secret is
+    /// `"true"`, retaining messages for the number of hours
+    /// specified by the `CHAT_RETENTION_HOURS` secret (if set)
+    pub fn from_secrets(db: &sqlx::PgPool, secrets: &SecretStore) -> Option<Self> {
+        if !secrets
+            .get("CHAT_ARCHIVE")
+            .is_some_and(|enabled| enabled.eq_ignore_ascii_case("true"))
+        {
+            return None;
+        }
+
+        let retention = secrets.get("CHAT_RETENTION_HOURS").and_then(|hours| {
+            hours
+                .parse::<i64>()
+                .map(chrono::Duration::hours)
+                .map_err(|error| tracing::error!("ignoring CHAT_RETENTION_HOURS secret: {error}"))
+                .ok()
+        });
+
+        Some(Self::new(db.clone(), retention))
+    }
+
+    /// Create the archive's table (if it doesn't already exist)
+    async fn ensure_schema(&self) -> Result<(), DbError> {
+        self.schema
+            .get_or_try_init(|| async {
+                sqlx::query(
+                    r#"CREATE TABLE IF NOT EXISTS chat_messages (
+                     id BIGSERIAL PRIMARY KEY,
+                     room BIGINT NOT NULL,
+                     username VARCHAR(128) NOT NULL,
+                     body TEXT NOT NULL,
+                     timestamp TIMESTAMPTZ NOT NULL
+                   );
+                "#,
+                )
+                .execute(&self.db)
+                .await?;
+
+                sqlx::query(
+                    r#"CREATE INDEX IF NOT EXISTS chat_messages_room_timestamp
+                     ON chat_messages (room, timestamp);
+                "#,
+                )
+                .execute(&self.db)
+                .await
+                .map(|_| ())
+            })
+            .await
+            .copied()
+    }
+
+    /// Archive the supplied record, pruning any of the room's
+    /// records outside retention (relative to the record's time)
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
+    pub async fn insert(&self, room: u64, record: ChatRecord) -> Result<(), DbError> {
+        self.ensure_schema().await?;
+
+        let record_timestamp = record.timestamp;
+
+        let inserted = sqlx::query(
+            "INSERT INTO chat_messages (room, username, body, timestamp) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(room as i64)
+        .bind(record.user)
+        .bind(record.message)
+        .bind(record_timestamp)
+        .execute(&self.db)
+        .await;
+
+        record_db!("insert chat message", inserted)?;
+
+        if let Some(retention) = self.retention {
+            sqlx::query("DELETE FROM chat_messages WHERE room = $1 AND timestamp < $2")
+                .bind(room as i64)
+                .bind(record_timestamp - retention)
+                .execute(&self.db)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the room's `limit` most recent
+    /// archived records (oldest first)
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
+    pub async fn recent(&self, room: u64, limit: u64) -> Result<Vec<ChatRecord>, DbError> {
+        self.ensure_schema().await?;
+
+        let rows = sqlx::query_as::<_, (String, String, DateTime<Utc>)>(
+            r#"SELECT username, body, timestamp
+            FROM chat_messages
+            WHERE room = $1
+            ORDER BY timestamp DESC, id DESC
+            LIMIT $2"#,
+        )
+        .bind(room as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.db)
+        .await;
+
+        let mut records = record_db!("select recent chat messages", rows)?
+            .into_iter()
+            .map(ChatRecord::from)
+            .collect::<Vec<ChatRecord>>();
+
+        records.reverse();
+
+        Ok(records)
+    }
+
+    /// Get the room's archived records (oldest first)
+    /// within the supplied range and preceding `before`
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
+    pub async fn range(
+        &self,
+        room: u64,
+        range: &TranscriptRange,
+        before: Option<DateTime<Utc>>,
+    ) -> Result<Vec<ChatRecord>, DbError> {
+        self.ensure_schema().await?;
+
+        let rows = sqlx::query_as::<_, (String, String, DateTime<Utc>)>(
+            r#"SELECT username, body, timestamp
+            FROM chat_messages
+            WHERE room = $1
+              AND ($2::TIMESTAMPTZ IS NULL OR $2 <= timestamp)
+              AND ($3::TIMESTAMPTZ IS NULL OR timestamp <= $3)
+              AND ($4::TIMESTAMPTZ IS NULL OR timestamp < $4)
+            ORDER BY timestamp ASC, id ASC"#,
+        )
+        .bind(room as i64)
+        .bind(range.from)
+        .bind(range.to)
+        .bind(before)
+        .fetch_all(&self.db)
+        .await;
+
+        record_db!("select chat transcript", rows)
+            .map(|rows| rows.into_iter().map(ChatRecord::from).collect())
+    }
+}
+
+// </editor-fold desc="// ChatArchive ...">
+
+// <editor-fold desc="// RoomTokens ...">
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A signed token granting access to a protected chat room
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct RoomToken {
+    /// the room the token grants access to
+    pub room: u64,
+    /// the token itself
+    pub token: String,
+    /// when the token expires
+    pub expires: DateTime<Utc>,
+}
+
+/// Why a connection to a protected room was refused
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RoomAccessRejection {
+    /// The connection didn't supply a token
+    Missing(u64),
+    /// The supplied token is malformed, expired,
+    /// or was issued for some other room
+    Invalid(u64),
+}
+
+impl From<RoomAccessRejection> for ErrorEnvelope {
+    fn from(rejection: RoomAccessRejection) -> Self {
+        match rejection {
+            RoomAccessRejection::Missing(room) => ErrorEnvelope::new(
+                StatusCode::UNAUTHORIZED,
+                format!("room {room} requires an access token"),
+            ),
+            RoomAccessRejection::Invalid(room) => ErrorEnvelope::new(
+                StatusCode::FORBIDDEN,
+                format!("invalid access token for room {room}"),
+            ),
+        }
+    }
+}
+
+impl From<RoomAccessRejection> for AppError {
+    fn from(rejection: RoomAccessRejection) -> Self {
+        ErrorEnvelope::from(rejection).into()
+    }
+}
+
+/// The access token (if any) supplied to a chat room connection
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct RoomAccess {
+    /// the connection's access token
+    pub token: Option<String>,
+}
+
+/// Which chat rooms are protected, and the
+/// key their access tokens are signed with
+#[derive(Clone)]
+pub struct RoomTokens {
+    /// the key access tokens are signed with
+    key: Arc<[u8]>,
+    /// the rooms requiring an access token
+    protected: Arc<RwLock<BTreeSet<u64>>>,
+}
+
+impl Debug for RoomTokens {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RoomTokens")
+            .field("protected", &self.protected)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for RoomTokens {
+    fn default() -> Self {
+        Self::new(&rand::random::<[u8; Self::MIN_KEY_LENGTH]>())
+    }
+}
+
+impl RoomTokens {
+    /// The minimum length of the token signing key
+    const MIN_KEY_LENGTH: usize = 32;
+
+    /// How long issued tokens remain valid
+    const TOKEN_TTL: chrono::Duration = chrono::Duration::hours(1);
+
+    /// Create a (protection-free) token issuer
+    /// signing tokens with the supplied key
+    pub fn new(key: &[u8]) -> Self {
+        Self {
+            key: Arc::from(key),
+            protected: Arc::new(RwLock::new(BTreeSet::new())),
+        }
+    }
+
+    /// Create a token issuer signing tokens with the `CHAT_TOKEN_KEY`
+    /// secret (if set), or a random per-process key otherwise (in
+    /// which case tokens don't survive restarts, or span instances)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        secrets
+            .get("CHAT_TOKEN_KEY")
+            .and_then(|key| {
+                if key.len() < Self::MIN_KEY_LENGTH {
+                    tracing::error!(
+                        "ignoring CHAT_TOKEN_KEY secret: must be at least {} bytes long",
+                        Self::MIN_KEY_LENGTH
+                    );
+                    return None;
+                }
+
+                Some(Self::new(key.as_bytes()))
+            })
+            .unwrap_or_else(|| {
+                tracing::warn!("CHAT_TOKEN_KEY secret unset, using a random room token key");
+                Self::default()
+            })
+    }
+
+    /// The signature of the supplied room and expiry
+    fn _signature(&self, room: u64, expires: i64) -> HmacSha256 {
+        let mut signature =
+            HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+
+        signature.update(format!("{room}:{expires}").as_bytes());
+
+        signature
+    }
+
+    /// Issue a token granting access to the room until `now` plus
+    /// the token TTL (whether or not the room is protected yet)
+    pub fn issue(&self, room: u64, now: DateTime<Utc>) -> RoomToken {
+        let expires = now + Self::TOKEN_TTL;
+        let signature = self._signature(room, expires.timestamp());
+
+        RoomToken {
+            room,
+            token: format!(
+                "{}.{}",
+                expires.timestamp(),
+                base64.encode(signature.finalize().into_bytes())
+            ),
+            expires,
+        }
+    }
+
+    /// Whether the supplied token grants access to the room at `now`
+    pub fn verify(&self, room: u64, token: &str, now: DateTime<Utc>) -> bool {
+        let Some((expires, signature)) = token.split_once('.') else {
+            return false;
+        };
+
+        let (Ok(expires), Ok(signature)) = (expires.parse::<i64>(), base64.decode(signature))
+        else {
+            return false;
+        };
+
+        now.timestamp() < expires
+            && self
+                ._signature(room, expires)
+                .verify_slice(&signature)
+                .is_ok()
+    }
+
+    /// Whether the room requires an access token
+    pub fn is_protected(&self, room: u64) -> bool {
+        self.protected.read().unwrap().contains(&room)
+    }
+
+    /// Require (or stop requiring) an access token for the room
+    pub fn protect(&self, room: u64, protected: bool) {
+        let mut rooms = self.protected.write().unwrap();
+
+        if protected {
+            rooms.insert(room);
+        } else {
+            rooms.remove(&room);
+        }
+    }
+
+    /// Check the supplied token (if any) against the room's protection
+    pub fn authorize(
+        &self,
+        room: u64,
+        token: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<(), RoomAccessRejection> {
+        if !self.is_protected(room) {
+            return Ok(());
+        }
+
+        match token {
+            None => Err(RoomAccessRejection::Missing(room)),
+            Some(token) if self.verify(room, token, now) => Ok(()),
+            Some(_) => Err(RoomAccessRejection::Invalid(room)),
+        }
+    }
+}
+
+/// A chat room's (requested or current) protection
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct RoomProtection {
+    /// whether the room requires an access token
+    pub protected: bool,
+}
+
+// </editor-fold desc="// RoomTokens ...">
+
 // <editor-fold desc="// ChatRoomState ...">
 
 #[derive(Clone, Debug, FromRef)]
 pub struct ChatRoomState {
     // running total of "seen" messages
-    views: Arc<AtomicU64>,
+    views: Arc<Counter>,
     // Channel-per-room map for all connected clients
     rooms: Arc<Mutex<BTreeMap<u64, Arc<broadcast::Sender<ChatMessage>>>>>,
+    // Bounded per-room history of propagated messages
+    history: Arc<Mutex<BTreeMap<u64, VecDeque<ChatRecord>>>>,
+    // Persistent archive of propagated messages (if enabled)
+    archive: Option<ChatArchive>,
+    // The source of message timestamps
+    clock: Arc<dyn Clock>,
+    // Per-room live connections, keyed by connection id
+    presence: Arc<Mutex<BTreeMap<u64, BTreeMap<u64, ConnectedUser>>>>,
+    // The rooms requiring an access token, and their token issuer
+    tokens: RoomTokens,
+    // The id of the next connection to join any room
+    #[from_ref(skip)]
+    next_connection: Arc<AtomicU64>,
 }
 
 impl Default for ChatRoomState {
@@ -164,12 +921,238 @@ impl Default for ChatRoomState {
 
         Self {
             rooms: Arc::new(Mutex::new(rooms)),
-            views: Arc::new(AtomicU64::new(0u64)),
+            views: Arc::new(Counter::default()),
+            history: Arc::new(Mutex::new(BTreeMap::new())),
+            archive: None,
+            clock: Arc::new(SystemClock),
+            presence: Arc::new(Mutex::new(BTreeMap::new())),
+            tokens: RoomTokens::default(),
+            next_connection: Arc::new(AtomicU64::new(0u64)),
         }
     }
 }
 
 impl ChatRoomState {
+    /// The maximum number of messages retained per room
+    const HISTORY_CAPACITY: usize = 256;
+
+    /// Retain the supplied record in the room's
+    /// history, evicting the oldest record(s) as
+    /// necessary to stay within capacity
+    async fn remember(&self, room: u64, record: ChatRecord) {
+        let mut history = self.history.lock().await;
+        let records = history.entry(room).or_default();
+
+        while Self::HISTORY_CAPACITY <= records.len() {
+            records.pop_front();
+        }
+
+        records.push_back(record);
+    }
+
+    /// Create a chat state that timestamps messages with
+    /// the supplied clock, archives them to the supplied
+    /// archive (if any), and counts "seen" messages with
+    /// the supplied counter
+    pub fn new(archive: Option<ChatArchive>, clock: Arc<dyn Clock>, views: Arc<Counter>) -> Self {
+        Self {
+            archive,
+            clock,
+            views,
+            ..Self::default()
+        }
+    }
+
+    /// Guard the state's rooms with the supplied token issuer
+    pub fn with_tokens(mut self, tokens: RoomTokens) -> Self {
+        self.tokens = tokens;
+        self
+    }
+
+    /// Get the room's retained history (oldest first)
+    /// filtered to the supplied range, preceded by any
+    /// older records held in the archive (if enabled)
+    async fn transcript(
+        &self,
+        room: u64,
+        range: &TranscriptRange,
+    ) -> Result<Vec<ChatRecord>, DbError> {
+        let (earliest, mut records) = self
+            .history
+            .lock()
+            .await
+            .get(&room)
+            .map(|records| {
+                (
+                    records.front().map(|record| record.timestamp),
+                    records
+                        .iter()
+                        .filter(|record| range.contains(record))
+                        .cloned()
+                        .collect::<Vec<ChatRecord>>(),
+                )
+            })
+            .unwrap_or_default();
+
+        if let Some(archive) = self.archive.as_ref() {
+            let mut archived = archive.range(room, range, earliest).await?;
+            archived.append(&mut records);
+            records = archived;
+        }
+
+        Ok(records)
+    }
+
+    /// Get the room's `limit` most recent records (oldest
+    /// first) from the archive if enabled, otherwise from
+    /// the room's retained history
+    async fn recent(&self, room: u64, limit: u64) -> Result<Vec<ChatRecord>, DbError> {
+        if let Some(archive) = self.archive.as_ref() {
+            return archive.recent(room, limit).await;
+        }
+
+        Ok(self
+            .history
+            .lock()
+            .await
+            .get(&room)
+            .map(|records| {
+                records
+                    .iter()
+                    .skip(records.len().saturating_sub(limit as usize))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Increment the running total of "seen" messages
+    fn record_view(&self) {
+        self.views.increment();
+    }
+
+    /// Zero the running total of "seen" messages,
+    /// returning the total prior to being zeroed
+    fn reset_views(&self) -> u64 {
+        self.views.reset()
+    }
+
+    /// Wait until the running total of "seen" messages
+    /// exceeds `since` or the `deadline` passes (whichever
+    /// comes first), returning the then-current total
+    async fn wait_for_views(&self, since: u64, deadline: Instant) -> u64 {
+        self.views.wait_for_change(since, deadline).await
+    }
+
+    /// Register a live connection to the room, returning
+    /// the connection's id and its (shared) lag counter
+    async fn join(&self, room: u64, user: &str) -> (u64, Arc<AtomicU64>) {
+        let connection = self.next_connection.fetch_add(1u64, Ordering::Relaxed);
+        let lagged = Arc::new(AtomicU64::new(0u64));
+
+        self.presence.lock().await.entry(room).or_default().insert(
+            connection,
+            ConnectedUser {
+                user: user.to_string(),
+                lagged: lagged.clone(),
+            },
+        );
+
+        (connection, lagged)
+    }
+
+    /// Deregister a live connection from the room
+    async fn leave(&self, room: u64, connection: u64) {
+        let mut presence = self.presence.lock().await;
+
+        if let Some(connections) = presence.get_mut(&room) {
+            connections.remove(&connection);
+
+            if connections.is_empty() {
+                presence.remove(&room);
+            }
+        }
+    }
+
+    /// Get the users currently connected to the room
+    async fn present(&self, room: u64) -> Vec<ChatPresence> {
+        self.presence
+            .lock()
+            .await
+            .get(&room)
+            .map(|connections| {
+                connections
+                    .values()
+                    .map(|connected| ChatPresence {
+                        user: connected.user.clone(),
+                        lagged: connected.lagged.load(Ordering::Relaxed),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Wait for the next message broadcast to the room, or for
+    /// a notice of how many messages were dropped if the receiver
+    /// fell behind (adding them to `lagged`), returning `None`
+    /// once the room's channel is closed
+    async fn next_delivery(
+        incoming: &mut broadcast::Receiver<ChatMessage>,
+        lagged: &AtomicU64,
+    ) -> Option<Result<ChatMessage, SystemNotice>> {
+        match incoming.recv().await {
+            Ok(message) => Some(Ok(message)),
+            Err(broadcast::error::RecvError::Lagged(dropped)) => {
+                lagged.fetch_add(dropped, Ordering::Relaxed);
+                tracing::warn!("connection lagged, dropping {dropped} message(s)");
+                Some(Err(SystemNotice::Lagged { dropped }))
+            }
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    }
+
+    /// Deliver the supplied (encoded) relayed message to this
+    /// instance's clients, unless it originated here, returning
+    /// whether the message was delivered
+    async fn accept_relayed(&self, instance: Uuid, payload: &str) -> bool {
+        let relayed = match serde_json::from_str::<RelayedMessage>(payload) {
+            Ok(relayed) if relayed.origin != instance => relayed,
+            Ok(_) => return false,
+            Err(error) => {
+                tracing::error!("error deserializing relayed message: {error:?}");
+                return false;
+            }
+        };
+
+        if let Some(record) = relayed.message.record(relayed.timestamp) {
+            self.remember(relayed.room, record).await;
+        }
+
+        // having no local clients in the room isn't an error
+        let _ = self.room_channel(relayed.room).await.send(relayed.message);
+
+        true
+    }
+
+    /// Deliver messages sent to the service's other instances
+    /// to this instance's clients (if the service's state is
+    /// distributed), for as long as the subscription lasts
+    pub async fn bridge(self: Arc<Self>, shared: SharedState) {
+        let pattern = format!("{}*", RelayedMessage::CHANNEL_PREFIX);
+
+        let Some(mut messages) = shared.subscribe(&pattern).await else {
+            return;
+        };
+
+        tokio::spawn(async move {
+            while let Some((_, payload)) = messages.next().await {
+                self.accept_relayed(shared.instance, &payload).await;
+            }
+
+            tracing::warn!("chat bridge subscription ended");
+        });
+    }
+
     async fn room_channel(&self, room: u64) -> Arc<broadcast::Sender<ChatMessage>> {
         self.rooms
             .lock()
@@ -183,76 +1166,186 @@ impl ChatRoomState {
     }
 
     #[allow(unused_parens)]
-    #[tracing::instrument(skip(state, socket))]
-    async fn connect_and_chat(state: Arc<Self>, socket: WebSocket, room: u64, user: String) {
+    #[tracing::instrument(skip(state, reporter, jobs, shared, socket))]
+    async fn connect_and_chat(
+        state: Arc<Self>,
+        reporter: ErrorReporter,
+        jobs: JobQueue,
+        shared: SharedState,
+        socket: WebSocket,
+        room: u64,
+        user: String,
+    ) {
         let broadcaster = state.room_channel(room).await;
         let chat = ChatRoomConnection::new(room, &user, socket, broadcaster.clone());
+        let (connection, lagged) = state.join(room, &user).await;
+        let (history, presence) = (state.clone(), state.clone());
 
         // Spawn the first task that will receive broadcast messages
         // and send chat messages over the websocket to our client.
-        let mut send_task = tokio::spawn(async move {
-            while let Ok(message) = chat.incoming.lock().await.recv().await {
-                if message.message.is_empty() {
-                    tracing::warn!("declining to propagate empty message");
-                } else if 128 < message.message.len() {
-                    tracing::warn!(
-                        r#"declining to propagate {} character message: "{} ...""#,
-                        message.message.len(),
-                        &message.message[0..=15]
-                    );
-                } else {
-                    let message = match serde_json::to_string(&message) {
-                        Ok(encoded) => encoded,
-                        Err(error) => {
-                            tracing::error!("error serializing message: {error:?}");
-                            break;
+        let mut send_task = tokio::spawn(
+            async move {
+                loop {
+                    let delivery = {
+                        let mut incoming = chat.incoming.lock().await;
+                        Self::next_delivery(&mut incoming, &lagged).await
+                    };
+
+                    let message = match delivery {
+                        None => break,
+                        Some(Ok(message)) => message,
+                        Some(Err(notice)) => {
+                            let notice = match serde_json::to_string(&notice) {
+                                Ok(encoded) => encoded,
+                                Err(error) => {
+                                    tracing::error!("error serializing notice: {error:?}");
+                                    break;
+                                }
+                            };
+
+                            if let Err(error) = chat
+                                .socket
+                                .sender
+                                .lock()
+                                .await
+                                .send(Message::Text(notice))
+                                .await
+                            {
+                                tracing::error!("error sending notice to user: {error:?}");
+                                break;
+                            }
+
+                            continue;
                         }
                     };
 
-                    if let Err(error) = chat
-                        .socket
-                        .sender
-                        .lock()
-                        .await
-                        .send(Message::Text(message))
-                        .await
-                    {
-                        tracing::error!("error propagating message to user: {error:?}");
-                        break;
-                    }
+                    if !message.is_deliverable() {
+                        match &message {
+                            ChatMessage::Text(text) if text.message.is_empty() => {
+                                tracing::warn!("declining to propagate empty message");
+                            }
+                            ChatMessage::Text(text) => tracing::warn!(
+                                r#"declining to propagate {} character message: "{} ...""#,
+                                text.message.len(),
+                                &text.message[0..=15]
+                            ),
+                            ChatMessage::Reaction(reaction) => {
+                                tracing::warn!(
+                                    r#"declining to propagate "{}" reaction"#,
+                                    reaction.emoji
+                                );
+                            }
+                            // typing indicators are always deliverable
+                            ChatMessage::Typing(_) => {}
+                        }
+                    } else {
+                        let viewable = message.is_viewable();
+                        let message = match serde_json::to_string(&message) {
+                            Ok(encoded) => encoded,
+                            Err(error) => {
+                                tracing::error!("error serializing message: {error:?}");
+                                break;
+                            }
+                        };
+
+                        if let Err(error) = chat
+                            .socket
+                            .sender
+                            .lock()
+                            .await
+                            .send(Message::Text(message))
+                            .await
+                        {
+                            tracing::error!("error propagating message to user: {error:?}");
+                            break;
+                        }
 
-                    state.views.fetch_add(1u64, Ordering::SeqCst);
+                        if viewable {
+                            state.record_view();
+                        }
+                    }
                 }
             }
-        });
+            .in_current_span(),
+        );
 
         // Spawn a task that takes messages from the websocket, ensures they're
         // properly formatted, and broadcasts them to everyone in the chat room.
-        let mut recv_task = tokio::spawn(async move {
-            while let Some(Ok(Message::Text(received))) =
-                chat.socket.receiver.lock().await.next().await
-            {
-                match serde_json::from_str::<ChatMessage>(&received) {
-                    Err(error) => {
-                        tracing::error!("error deserializing message: {error:?}");
-                        break;
-                    }
-                    Ok(mut message) => {
-                        message.user = user.clone();
+        let mut recv_task = tokio::spawn(
+            async move {
+                let mut typing = TypingDebounce::default();
 
-                        if let Err(error) = broadcaster.send(message) {
-                            tracing::error!("error propagating message to room: {error:?}");
+                while let Some(Ok(Message::Text(received))) =
+                    chat.socket.receiver.lock().await.next().await
+                {
+                    match serde_json::from_str::<ChatMessage>(&received) {
+                        Err(error) => {
+                            tracing::error!("error deserializing message: {error:?}");
                             break;
                         }
+                        Ok(mut message) => {
+                            message.set_user(&user);
+
+                            let timestamp = history.clock.now();
+
+                            if matches!(message, ChatMessage::Typing(_)) && !typing.admit(timestamp)
+                            {
+                                continue;
+                            }
+
+                            if let Some(record) = message.record(timestamp) {
+                                if let Some(archive) = history.archive.clone() {
+                                    let record = record.clone();
+                                    let _ = jobs.enqueue("archive chat message", async move {
+                                        archive.insert(room, record).await
+                                    });
+                                }
+
+                                history.remember(room, record).await;
+                            }
+
+                            let relayed = RelayedMessage {
+                                origin: shared.instance,
+                                room,
+                                message: message.clone(),
+                                timestamp,
+                            };
+
+                            if let Err(error) = broadcaster.send(message) {
+                                tracing::error!("error propagating message to room: {error:?}");
+                                break;
+                            }
+
+                            shared.publish(relayed.channel(), &relayed).await;
+                        }
                     }
                 }
             }
-        });
+            .in_current_span(),
+        );
 
         // If any one of the tasks run to completion, we abort the other.
-        tokio::select! {
-            _ = (&mut send_task) => recv_task.abort(),
-            _ = (&mut recv_task) => send_task.abort(),
+        let outcome = tokio::select! {
+            outcome = (&mut send_task) => {
+                recv_task.abort();
+                outcome
+            },
+            outcome = (&mut recv_task) => {
+                send_task.abort();
+                outcome
+            },
+        };
+
+        presence.leave(room, connection).await;
+
+        // Make sure panics in either task don't just vanish
+        if let Err(error) = outcome {
+            if error.is_panic() {
+                reporter.report(ErrorEvent {
+                    route: Some(String::from(routes::day19::ROOM)),
+                    ..ErrorEvent::from_panic(error.into_panic().as_ref())
+                });
+            }
         }
 
         tracing::debug!("disconnection");
@@ -262,18 +1355,38 @@ impl ChatRoomState {
 // </editor-fold desc="// ChatRoomState ...">
 
 /// Complete [Day 19: Task](https://console.shuttle.rs/cch/challenge/19#:~:text=⭐)
+///
+/// Connections are refused once the service's (or the
+/// client's) [socket quota](crate::metrics::SocketQuota) is spent
 #[tracing::instrument(skip_all)]
-pub async fn play_socket_ping_pong(ws: WebSocketUpgrade) -> impl IntoResponse {
-    ws.on_upgrade(|socket| SocketPongSession::new().play(socket))
+pub async fn play_socket_ping_pong(
+    State(state): State<ShuttleAppState>,
+    headers: HeaderMap,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    ws: WebSocketUpgrade,
+) -> Result<impl IntoResponse, AppError> {
+    let live = LiveSocket::admit(
+        &state.metrics,
+        state
+            .proxies
+            .client_ip(&headers, peer.as_ref().map(|ConnectInfo(peer)| peer)),
+    )?;
+
+    // (the upgraded socket is served by its own task, which
+    // must carry the request's span for its logs to be correlated)
+    Ok(ws.on_upgrade(|socket| {
+        async move {
+            SocketPongSession::new().play(socket).await;
+            drop(live);
+        }
+        .in_current_span()
+    }))
 }
 
 /// Endpoint 1/3 for [Day 19: Bonus](https://console.shuttle.rs/cch/challenge/19#:~:text=🎁)
 #[tracing::instrument(ret, skip_all, fields(zeroed_from))]
 pub async fn reset_chat_count(State(state): State<ShuttleAppState>) -> StatusCode {
-    tracing::Span::current().record(
-        "zeroed_from",
-        state.chat.views.as_ref().swap(0u64, Ordering::SeqCst),
-    );
+    record_fields!("zeroed_from" => state.chat.reset_views());
 
     StatusCode::OK
 }
@@ -281,19 +1394,206 @@ pub async fn reset_chat_count(State(state): State<ShuttleAppState>) -> StatusCod
 /// Endpoint 2/3 for [Day 19: Bonus](https://console.shuttle.rs/cch/challenge/19#:~:text=🎁)
 #[tracing::instrument(ret, skip_all)]
 pub async fn get_current_chat_count(State(state): State<ShuttleAppState>) -> Json<u64> {
-    Json(state.chat.views.load(Ordering::Relaxed))
+    Json(state.chat.views.value())
+}
+
+/// Wait for the running total of "seen" chat messages
+/// to exceed `since` (or for `timeout` seconds to pass)
+/// before responding with the then-current total
+#[tracing::instrument(ret, skip_all, fields(since = query.since, timeout = query.timeout))]
+pub async fn wait_for_chat_count_change(
+    ValidatedQuery(query): ValidatedQuery<ViewCountWait>,
+    State(state): State<ShuttleAppState>,
+) -> Json<u64> {
+    let deadline = Instant::now() + Duration::from_secs(query.timeout);
+
+    Json(state.chat.wait_for_views(query.since, deadline).await)
+}
+
+/// Stream the room's retained message history
+/// as newline-delimited JSON (oldest first)
+#[tracing::instrument(skip_all, fields(room, from = ?range.from, to = ?range.to))]
+pub async fn stream_chat_transcript(
+    Path(room): Path<u64>,
+    ValidatedQuery(range): ValidatedQuery<TranscriptRange>,
+    State(state): State<ShuttleAppState>,
+) -> impl IntoResponse {
+    let records = state
+        .chat
+        .transcript(room, &range)
+        .await
+        .map_err(|error| (StatusCode::FAILED_DEPENDENCY, format!("{error}")))?;
+
+    let lines = stream::iter(records).map(|record| {
+        serde_json::to_string(&record).map(|mut line| {
+            line.push('\n');
+            line
+        })
+    });
+
+    Ok::<_, (StatusCode, String)>((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        StreamBody::new(lines),
+    ))
+}
+
+/// Retrieve the room's most recent messages (oldest first)
+#[tracing::instrument(ret, err(Debug), skip_all, fields(room, limit = query.limit))]
+pub async fn get_recent_chat_messages(
+    Path(room): Path<u64>,
+    ValidatedQuery(query): ValidatedQuery<RecentMessages>,
+    State(state): State<ShuttleAppState>,
+) -> Result<Json<Vec<ChatRecord>>, AppError> {
+    Ok(Json(state.chat.recent(room, query.limit).await?))
+}
+
+/// Retrieve the users currently connected to the room,
+/// along with how many messages each has dropped
+#[tracing::instrument(ret, skip_all, fields(room))]
+pub async fn get_chat_room_presence(
+    Path(room): Path<u64>,
+    State(state): State<ShuttleAppState>,
+) -> Json<Vec<ChatPresence>> {
+    Json(state.chat.present(room).await)
+}
+
+/// Issue a token granting access to the room (should it
+/// be, or become, protected), served only to admins so that
+/// protection actually keeps anonymous clients out
+#[tracing::instrument(skip_all, fields(room))]
+pub async fn issue_chat_room_token(
+    Path(room): Path<u64>,
+    State(state): State<ShuttleAppState>,
+) -> Json<RoomToken> {
+    Json(state.chat.tokens.issue(room, state.chat.clock.now()))
+}
+
+/// Require (or stop requiring) an access token to join the room
+#[tracing::instrument(ret, skip(state))]
+pub async fn update_chat_room_protection(
+    Path(room): Path<u64>,
+    State(state): State<ShuttleAppState>,
+    Json(protection): Json<RoomProtection>,
+) -> Json<RoomProtection> {
+    state.chat.tokens.protect(room, protection.protected);
+
+    Json(protection)
 }
 
 /// Endpoint 3/3 for [Day 19: Bonus](https://console.shuttle.rs/cch/challenge/19#:~:text=🎁)
+///
+/// Connections to [protected](RoomTokens) rooms must supply a valid
+/// `token` query parameter, and are refused once the service's (or
+/// the client's) [socket quota](crate::metrics::SocketQuota) is spent
 #[tracing::instrument(skip_all)]
 pub async fn connect_to_chat_room(
     Path((room, user)): Path<(u64, String)>,
+    Query(access): Query<RoomAccess>,
     State(state): State<ShuttleAppState>,
+    headers: HeaderMap,
+    peer: Option<ConnectInfo<SocketAddr>>,
     socket: WebSocketUpgrade,
-) -> impl IntoResponse {
-    socket.on_upgrade(move |socket| ChatRoomState::connect_and_chat(state.chat, socket, room, user))
+) -> Result<impl IntoResponse, AppError> {
+    state
+        .chat
+        .tokens
+        .authorize(room, access.token.as_deref(), state.chat.clock.now())?;
+
+    let live = LiveSocket::admit(
+        &state.metrics,
+        state
+            .proxies
+            .client_ip(&headers, peer.as_ref().map(|ConnectInfo(peer)| peer)),
+    )?;
+
+    Ok(socket.on_upgrade(move |socket| {
+        async move {
+            ChatRoomState::connect_and_chat(
+                state.chat,
+                state.reporter,
+                state.jobs,
+                state.shared,
+                socket,
+                room,
+                user,
+            )
+            .await;
+            drop(live);
+        }
+        .in_current_span()
+    }))
+}
+
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 19](https://console.shuttle.rs/cch/challenge/19) solutions
+#[derive(Debug)]
+pub struct Day19;
+
+impl DayModule for Day19 {
+    fn day() -> i8 {
+        19
+    }
+
+    fn name() -> &'static str {
+        "Chat Rooms"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                19,
+                GET routes::day19::PING,
+                play_socket_ping_pong,
+                "Play websocket ping-pong"
+            ))
+            .route(day_route!(
+                19,
+                POST routes::day19::RESET,
+                reset_chat_count,
+                "Reset the seen-message count"
+            ))
+            .route(day_route!(
+                19,
+                GET routes::day19::VIEWS,
+                get_current_chat_count,
+                "Get the seen-message count"
+            ))
+            .route(day_route!(
+                19,
+                GET routes::day19::VIEWS_WAIT,
+                wait_for_chat_count_change,
+                "Wait for the seen-message count to change"
+            ))
+            .route(day_route!(
+                19,
+                GET routes::day19::ROOM_MESSAGES,
+                get_recent_chat_messages,
+                "List a room's recent messages"
+            ))
+            .route(day_route!(
+                19,
+                GET routes::day19::ROOM_TRANSCRIPT,
+                stream_chat_transcript,
+                "Stream a room's transcript"
+            ))
+            .route(day_route!(
+                19,
+                GET routes::day19::ROOM_PRESENCE,
+                get_chat_room_presence,
+                "List a room's connected users"
+            ))
+            .route(day_route!(
+                19,
+                GET routes::day19::ROOM,
+                connect_to_chat_room,
+                "Join a chat room"
+            ))
+    }
 }
 
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -322,5 +1622,531 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
+    use crate::routes;
     use crate::utils::{service, TestService};
+
+    use super::{
+        ChatMessage, ChatRecord, ChatRoomState, ChatText, RelayedMessage, RoomAccessRejection,
+        RoomToken, RoomTokens, SystemNotice, TypingDebounce,
+    };
+    use crate::router;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{admin_request, state};
+
+    /// The text (or emoji, or typing user) of the supplied message
+    fn _content(message: ChatMessage) -> String {
+        match message {
+            ChatMessage::Reaction(reaction) => reaction.emoji,
+            ChatMessage::Typing(typing) => typing.user,
+            ChatMessage::Text(text) => text.message,
+        }
+    }
+
+    /// Test that `wait_for_chat_count_change` responds once the
+    /// counter exceeds `since` or the timeout elapses
+    #[rstest]
+    #[case::timeout_elapses(&format!("{}?since=5&timeout=0", routes::day19::views_wait()), false, StatusCode::OK, "0")]
+    #[case::counter_changes(&format!("{}?since=0&timeout=5", routes::day19::views_wait()), true, StatusCode::OK, "1")]
+    #[case::timeout_too_long(
+        &format!("{}?timeout=301", routes::day19::views_wait()),
+        false,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        r#"{"error":{"code":"unprocessable_entity","status":422,"message":"invalid request parameters","details":{"timeout":["must not exceed 300 seconds"]}}}"#
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_wait_for_chat_count_change(
+        state: ShuttleAppState,
+        #[case] url: &str,
+        #[case] view: bool,
+        #[case] expected_status: StatusCode,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let chat = state.chat.clone();
+        let service = TestService::from(router(state));
+
+        if view {
+            tokio::spawn(async move {
+                tokio::time::sleep(core::time::Duration::from_millis(50)).await;
+                chat.record_view();
+            });
+        }
+
+        let response = service.resolve(url).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        let content = response.into_body().data().await.unwrap()?;
+
+        assert_eq!(
+            serde_json::from_str::<Value>(expected_content)?,
+            serde_json::from_slice::<Value>(content.as_ref())?,
+        );
+
+        Ok(())
+    }
+
+    /// Test that `stream_chat_transcript` streams the room's
+    /// retained history as NDJSON, filtered by timestamp
+    #[rstest]
+    #[case::everything(&routes::day19::room_transcript(1), StatusCode::OK, &["one", "two", "three"])]
+    #[case::from(&format!("{}?from=2023-12-19T12:01:00Z", routes::day19::room_transcript(1)), StatusCode::OK, &["two", "three"])]
+    #[case::to(&format!("{}?to=2023-12-19T12:01:00Z", routes::day19::room_transcript(1)), StatusCode::OK, &["one", "two"])]
+    #[case::other_room(&routes::day19::room_transcript(2), StatusCode::OK, &[])]
+    #[case::inverted_range(
+        &format!("{}?from=2023-12-19T12:02:00Z&to=2023-12-19T12:00:00Z", routes::day19::room_transcript(1)),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        &[]
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_stream_chat_transcript(
+        state: ShuttleAppState,
+        #[case] url: &str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_messages: &[&str],
+    ) -> anyhow::Result<()> {
+        for (minute, message) in ["one", "two", "three"].into_iter().enumerate() {
+            state
+                .chat
+                .remember(
+                    1,
+                    ChatRecord {
+                        user: String::from("santa"),
+                        message: message.to_string(),
+                        timestamp: format!("2023-12-19T12:0{minute}:00Z").parse()?,
+                    },
+                )
+                .await;
+        }
+
+        let response = TestService::from(router(state)).resolve(url).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status.is_success() {
+            assert_eq!(
+                "application/x-ndjson",
+                response.headers()[headers::CONTENT_TYPE]
+            );
+
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+
+            let actual = String::from_utf8(content.to_vec())?
+                .lines()
+                .map(serde_json::from_str::<ChatRecord>)
+                .map(|record| record.map(|record| record.message))
+                .collect::<Result<Vec<String>, _>>()?;
+
+            assert_eq!(expected_messages, actual.as_slice());
+        }
+
+        Ok(())
+    }
+
+    /// Test that `get_recent_chat_messages` returns the room's
+    /// most recent messages (oldest first) from its history
+    #[rstest]
+    #[case::default_limit(&routes::day19::room_messages(1), StatusCode::OK, &["one", "two", "three"])]
+    #[case::limited(&format!("{}?limit=2", routes::day19::room_messages(1)), StatusCode::OK, &["two", "three"])]
+    #[case::limit_too_large(&format!("{}?limit=1001", routes::day19::room_messages(1)), StatusCode::UNPROCESSABLE_ENTITY, &[])]
+    #[test_log::test(tokio::test)]
+    async fn test_get_recent_chat_messages(
+        state: ShuttleAppState,
+        #[case] url: &str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_messages: &[&str],
+    ) -> anyhow::Result<()> {
+        for message in ["one", "two", "three"] {
+            state
+                .chat
+                .remember(
+                    1,
+                    ChatRecord {
+                        user: String::from("santa"),
+                        message: message.to_string(),
+                        timestamp: chrono::Utc::now(),
+                    },
+                )
+                .await;
+        }
+
+        let response = TestService::from(router(state)).resolve(url).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status.is_success() {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+
+            let actual = serde_json::from_slice::<Vec<ChatRecord>>(content.as_ref())?
+                .into_iter()
+                .map(|record| record.message)
+                .collect::<Vec<String>>();
+
+            assert_eq!(expected_messages, actual.as_slice());
+        }
+
+        Ok(())
+    }
+
+    /// Test that messages relayed from other instances are delivered
+    /// to (and remembered by) this one, unless they originated here
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_accept_relayed() -> anyhow::Result<()> {
+        let state = ChatRoomState::default();
+        let (here, elsewhere) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+        let mut incoming = state.room_channel(7).await.subscribe();
+        let relayed = |origin| {
+            serde_json::to_string(&RelayedMessage {
+                origin,
+                room: 7,
+                message: ChatMessage::Text(ChatText {
+                    user: String::from("rudolph"),
+                    message: String::from("hello from the other side"),
+                }),
+                timestamp: "2023-12-19T00:00:00Z".parse().unwrap(),
+            })
+        };
+
+        assert!(!state.accept_relayed(here, &relayed(here)?).await);
+        assert!(!state.accept_relayed(here, "not json").await);
+        assert!(state.accept_relayed(here, &relayed(elsewhere)?).await);
+
+        assert_str_eq!(
+            "hello from the other side",
+            _content(incoming.recv().await?)
+        );
+        assert!(incoming.try_recv().is_err());
+        assert_eq!(1, state.recent(7, 10).await?.len());
+
+        Ok(())
+    }
+
+    /// Test that `next_delivery` notifies lagging receivers
+    /// of the messages they dropped, then carries on
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_next_delivery_after_lag() -> anyhow::Result<()> {
+        let (sender, mut incoming) = tokio::sync::broadcast::channel::<ChatMessage>(2);
+        let lagged = std::sync::atomic::AtomicU64::new(0);
+
+        for message in ["one", "two", "three", "four", "five"] {
+            sender.send(ChatMessage::Text(ChatText {
+                user: String::from("santa"),
+                message: message.to_string(),
+            }))?;
+        }
+
+        assert_eq!(
+            Some(Err(SystemNotice::Lagged { dropped: 3 })),
+            ChatRoomState::next_delivery(&mut incoming, &lagged)
+                .await
+                .map(|delivery| delivery.map(_content))
+        );
+        assert_eq!(3, lagged.load(std::sync::atomic::Ordering::Relaxed));
+
+        for expected in ["four", "five"] {
+            assert_eq!(
+                Some(Ok(expected.to_string())),
+                ChatRoomState::next_delivery(&mut incoming, &lagged)
+                    .await
+                    .map(|delivery| delivery.map(_content))
+            );
+        }
+
+        drop(sender);
+
+        assert!(ChatRoomState::next_delivery(&mut incoming, &lagged)
+            .await
+            .is_none());
+
+        assert_eq!(
+            r#"{"notice":"lagged","dropped":3}"#,
+            serde_json::to_string(&SystemNotice::Lagged { dropped: 3 })?
+        );
+
+        Ok(())
+    }
+
+    /// Test that `get_chat_room_presence` lists the room's
+    /// connected users along with their lag counters
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_get_chat_room_presence(state: ShuttleAppState) -> anyhow::Result<()> {
+        let (_, lagged) = state.chat.join(1, "santa").await;
+        let (elf, _) = state.chat.join(1, "elf").await;
+        state.chat.join(2, "grinch").await;
+
+        lagged.fetch_add(7, std::sync::atomic::Ordering::Relaxed);
+        state.chat.leave(1, elf).await;
+
+        let response = TestService::from(router(state))
+            .resolve(routes::day19::room_presence(1).as_str())
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+
+        assert_eq!(
+            serde_json::json!([{"user": "santa", "lagged": 7}]),
+            serde_json::from_slice::<Value>(content.as_ref())?
+        );
+
+        Ok(())
+    }
+
+    /// Test that websocket upgrades are refused (with a `503`)
+    /// once the requesting client's socket quota is spent
+    #[rstest]
+    #[case::quota_spent("10.0.0.1", StatusCode::SERVICE_UNAVAILABLE)]
+    #[case::quota_available("10.0.0.2", StatusCode::SWITCHING_PROTOCOLS)]
+    #[test_log::test(tokio::test)]
+    async fn test_socket_quota(
+        state: ShuttleAppState,
+        #[case] client: &str,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let metrics = std::sync::Arc::new(crate::metrics::ServiceMetrics {
+            sockets: crate::metrics::SocketQuota::new(8, 1),
+            ..Default::default()
+        });
+        let state = ShuttleAppState {
+            metrics: metrics.clone(),
+            ..state
+        };
+
+        let _held = crate::metrics::LiveSocket::admit(&metrics, "10.0.0.1".parse().ok())
+            .expect("the quota is unspent");
+
+        let mut request = Request::get(routes::day19::ping())
+            .header("x-forwarded-for", client)
+            .header(headers::CONNECTION, "upgrade")
+            .header(headers::UPGRADE, "websocket")
+            .header(headers::SEC_WEBSOCKET_VERSION, "13")
+            .header(headers::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ==")
+            .body(Body::empty())?;
+
+        let upgrade = hyper::upgrade::on(&mut request);
+        request.extensions_mut().insert(upgrade);
+
+        let response = TestService::from(router(state)).resolve(request).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status == StatusCode::SERVICE_UNAVAILABLE {
+            let body: Value =
+                serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+            assert_eq!(
+                serde_json::json!({"scope": "client", "limit": 1}),
+                body["error"]["details"]
+            );
+            assert_eq!(Some(&1), metrics.snapshot().get("rejected_sockets"));
+        }
+
+        Ok(())
+    }
+
+    /// Test that room tokens only grant access to the
+    /// room they were issued for, and only until they expire
+    #[rstest]
+    #[test_log::test]
+    fn test_room_tokens() {
+        let tokens = RoomTokens::new(&[7u8; 32]);
+        let now = chrono::Utc::now();
+        let issued = tokens.issue(1, now);
+
+        assert!(tokens.verify(1, &issued.token, now));
+        assert!(!tokens.verify(2, &issued.token, now));
+        assert!(!tokens.verify(1, &issued.token, issued.expires));
+        assert!(!RoomTokens::new(&[8u8; 32]).verify(1, &issued.token, now));
+        assert!(!tokens.verify(1, "not-a-token", now));
+
+        assert_eq!(Ok(()), tokens.authorize(1, None, now));
+
+        tokens.protect(1, true);
+
+        assert_eq!(
+            Err(RoomAccessRejection::Missing(1)),
+            tokens.authorize(1, None, now)
+        );
+        assert_eq!(
+            Err(RoomAccessRejection::Invalid(1)),
+            tokens.authorize(1, Some("0.AAAA"), now)
+        );
+        assert_eq!(Ok(()), tokens.authorize(1, Some(&issued.token), now));
+
+        tokens.protect(1, false);
+
+        assert!(!tokens.is_protected(1));
+    }
+
+    /// Test that upgrades to a protected room are refused
+    /// unless they supply a token issued for that room
+    #[rstest]
+    #[case::unprotected(false, None, StatusCode::SWITCHING_PROTOCOLS)]
+    #[case::missing_token(true, None, StatusCode::UNAUTHORIZED)]
+    #[case::invalid_token(true, Some("0.AAAA"), StatusCode::FORBIDDEN)]
+    #[case::issued_token(true, Some(""), StatusCode::SWITCHING_PROTOCOLS)]
+    #[test_log::test(tokio::test)]
+    async fn test_protected_room(
+        state: ShuttleAppState,
+        #[case] protected: bool,
+        #[case] token: Option<&str>,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let response = TestService::from(router(state.clone()))
+            .resolve(
+                admin_request(Method::PUT, routes::admin::room_protection(1))
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        serde_json::json!({ "protected": protected }).to_string(),
+                    ))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(admin_request(Method::POST, routes::admin::room_token(1)).body(Body::empty())?)
+            .await?;
+        let issued: RoomToken =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        // an empty case token stands in for the one just issued
+        let query = match token {
+            None => String::new(),
+            Some("") => format!("?token={}", issued.token),
+            Some(token) => format!("?token={token}"),
+        };
+
+        let mut request = Request::get(format!("{}{query}", routes::day19::room(1, "elf")))
+            .header(headers::CONNECTION, "upgrade")
+            .header(headers::UPGRADE, "websocket")
+            .header(headers::SEC_WEBSOCKET_VERSION, "13")
+            .header(headers::SEC_WEBSOCKET_KEY, "dGhlIHNhbXBsZSBub25jZQ==")
+            .body(Body::empty())?;
+
+        let upgrade = hyper::upgrade::on(&mut request);
+        request.extensions_mut().insert(upgrade);
+
+        let response = TestService::from(router(state)).resolve(request).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        Ok(())
+    }
+
+    /// Test that anonymous clients can't mint a token for a
+    /// protected room (i.e. that token issuance is admin-only)
+    #[rstest]
+    #[case::without_credentials(
+        Request::post(routes::admin::room_token(1)),
+        StatusCode::UNAUTHORIZED
+    )]
+    #[case::former_public_route(Request::post("/19/rooms/1/token"), StatusCode::NOT_FOUND)]
+    #[test_log::test(tokio::test)]
+    async fn test_anonymous_token_issuance(
+        state: ShuttleAppState,
+        #[case] request: Builder,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        state.chat.tokens.protect(1, true);
+
+        let response = TestService::from(router(state))
+            .resolve(request.body(Body::empty())?)
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+
+        assert!(serde_json::from_slice::<RoomToken>(&content).is_err());
+
+        Ok(())
+    }
+
+    /// Test that untagged frames are still read (and written) as
+    /// text messages, and that reactions are only deliverable with
+    /// allowed emoji (and never count as "seen" messages)
+    #[rstest]
+    #[case::text(
+        r#"{"message":"hi"}"#,
+        Some(true),
+        true,
+        r#"{"user":"elf","message":"hi"}"#
+    )]
+    #[case::tagged_text(
+        r#"{"type":"message","message":"hi"}"#,
+        Some(true),
+        true,
+        r#"{"user":"elf","message":"hi"}"#
+    )]
+    #[case::reaction(
+        r#"{"type":"reaction","target_ts":"2023-12-19T12:00:00Z","emoji":"🎄"}"#,
+        Some(false),
+        true,
+        r#"{"type":"reaction","user":"elf","target_ts":"2023-12-19T12:00:00Z","emoji":"🎄"}"#
+    )]
+    #[case::disallowed_reaction(
+        r#"{"type":"reaction","target_ts":"2023-12-19T12:00:00Z","emoji":"🐍"}"#,
+        Some(false),
+        false,
+        r#"{"type":"reaction","user":"elf","target_ts":"2023-12-19T12:00:00Z","emoji":"🐍"}"#
+    )]
+    #[case::typing(
+        r#"{"type":"typing"}"#,
+        Some(false),
+        true,
+        r#"{"type":"typing","user":"elf"}"#
+    )]
+    #[case::untargeted_reaction(r#"{"type":"reaction","emoji":"🎄"}"#, None, false, "")]
+    #[test_log::test]
+    fn test_chat_message_frames(
+        #[case] frame: &str,
+        #[case] expected_viewable: Option<bool>,
+        #[case] expected_deliverable: bool,
+        #[case] expected_encoding: &str,
+    ) -> anyhow::Result<()> {
+        let message = serde_json::from_str::<ChatMessage>(frame);
+
+        let Some(expected_viewable) = expected_viewable else {
+            assert!(message.is_err());
+            return Ok(());
+        };
+
+        let mut message = message?;
+
+        message.set_user("elf");
+
+        assert_eq!(expected_viewable, message.is_viewable());
+        assert_eq!(expected_deliverable, message.is_deliverable());
+        assert_eq!(
+            expected_viewable && expected_deliverable,
+            message.record(chrono::Utc::now()).is_some()
+        );
+        assert_eq!(
+            serde_json::from_str::<Value>(expected_encoding)?,
+            serde_json::to_value(&message)?
+        );
+
+        Ok(())
+    }
+
+    /// Test that typing indicators are propagated at
+    /// most once per debounce interval
+    #[rstest]
+    #[test_log::test]
+    fn test_typing_debounce() {
+        let mut typing = TypingDebounce::default();
+        let start = chrono::Utc::now();
+        let after = |seconds| start + chrono::Duration::seconds(seconds);
+
+        assert!(typing.admit(start));
+        assert!(!typing.admit(after(1)));
+        assert!(!typing.admit(after(2)));
+        assert!(typing.admit(after(3)));
+        assert!(!typing.admit(after(5)));
+        assert!(typing.admit(after(6)));
+    }
 }
@@ -7,26 +7,42 @@ use core::{
     fmt::{Debug, Formatter, Result as FormatResult},
     ops::{BitOr, Deref, DerefMut, Not},
 };
+use std::{
+    collections::BTreeSet,
+    path::{Component, Path as FilePath, PathBuf as FilePathBuf},
+};
 
 // Third-Party Imports
 use axum::{
     async_trait,
     body::Bytes,
-    extract::{FromRequest, FromRequestParts, Json, TypedHeader},
+    extract::{FromRequest, FromRequestParts, Json, Path, Query, State, TypedHeader},
     headers::ContentType,
-    http::{Request, StatusCode},
+    http::{header, Request, StatusCode},
     response::{IntoResponse, Response},
 };
 use bytes::{buf::Reader as ByteReader, Buf};
 use git2::Repository as GitRepo;
 use num_traits::FromPrimitive;
 use once_cell::sync::Lazy;
+use serde::Deserialize;
+use serde_json::Value;
+
+// Crate-Level Imports
+use crate::{
+    errors::{AppError, ErrorEnvelope},
+    jobs::{JobProgress, JobStatus},
+    routes,
+    solutions::{DayModule, DayRoutes},
+    state::ShuttleAppState,
+    utils,
+};
 
 // <editor-fold desc="// Utilities ...">
 
-fn as_412_response<E: GenericError>(error: E) -> Response {
+fn as_412<E: GenericError>(error: E) -> AppError {
     tracing::error!("{:?}", &error);
-    (StatusCode::PRECONDITION_FAILED, error.to_string()).into_response()
+    ErrorEnvelope::new(StatusCode::PRECONDITION_FAILED, error.to_string()).into()
 }
 
 // </editor-fold desc="// Utilities ...">
@@ -111,55 +127,291 @@ where
 
 // </editor-fold desc="// UploadedTarArchive ...">
 
+// <editor-fold desc="// Archive Safety ...">
+
+/// The default maximum number of entries in an uploaded archive
+const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+
+/// The default maximum total (unpacked) size
+/// of an uploaded archive's entries
+const MAX_UNPACKED_SIZE: u64 = 256 * 1024 * 1024;
+
+/// The default maximum ratio of an uploaded archive's
+/// total (unpacked) size to its (uploaded) size
+const MAX_EXPANSION_RATIO: u64 = 100;
+
+/// Why an uploaded archive was refused
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveRejection {
+    /// An entry's path is absolute or climbs out of the archive
+    #[error("archive entry {0:?} escapes the archive")]
+    Traversal(String),
+    /// An entry's path passes through a (symbolic) link
+    #[error("archive entry {0:?} is written through a link")]
+    ThroughLink(String),
+    /// A link's target is outside the archive
+    #[error("archive entry {0:?} links to {1:?}, outside the archive")]
+    EscapingLink(String, String),
+    /// An entry is neither a file, a directory, nor a link
+    #[error("archive entry {0:?} is of an unsupported type ({1:?})")]
+    UnsupportedType(String, tar::EntryType),
+    /// An entry exceeds the archive's entry count limit
+    #[error("archive entry {0:?} exceeds the limit of {1} entries")]
+    TooManyEntries(String, usize),
+    /// An entry exceeds the archive's unpacked size limit
+    #[error("archive entry {0:?} exceeds the unpacked size limit of {1} bytes")]
+    TooLarge(String, u64),
+    /// An entry couldn't be read (or unpacked)
+    #[error("archive entry #{0} is unreadable: {1}")]
+    Unreadable(usize, String),
+}
+
+impl From<ArchiveRejection> for ErrorEnvelope {
+    fn from(rejection: ArchiveRejection) -> Self {
+        tracing::warn!("{rejection}");
+        ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, &rejection)
+    }
+}
+
+impl ArchiveRejection {
+    /// Check whether the rejected entry tried to
+    /// reach outside the archive's directory
+    pub fn is_traversal(&self) -> bool {
+        matches!(
+            self,
+            Self::Traversal(_) | Self::ThroughLink(_) | Self::EscapingLink(..)
+        )
+    }
+}
+
+impl From<ArchiveRejection> for AppError {
+    fn from(rejection: ArchiveRejection) -> Self {
+        let traversal = rejection.is_traversal();
+        let error = AppError::from(ErrorEnvelope::from(rejection));
+
+        if traversal {
+            error.suspicious()
+        } else {
+            error
+        }
+    }
+}
+
+/// The limits an uploaded archive's entries are vetted against
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ArchiveLimits {
+    /// the maximum number of entries
+    pub max_entries: usize,
+    /// the maximum total (unpacked) size of the entries
+    pub max_unpacked_size: u64,
+    /// the maximum ratio of the entries' total (unpacked)
+    /// size to the archive's (uploaded) size
+    pub max_expansion_ratio: u64,
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: MAX_ARCHIVE_ENTRIES,
+            max_unpacked_size: MAX_UNPACKED_SIZE,
+            max_expansion_ratio: MAX_EXPANSION_RATIO,
+        }
+    }
+}
+
+/// The supplied entry path, relative to the archive's root,
+/// or `None` if it's absolute or climbs out of the root
+fn _sanitized_path(path: &FilePath) -> Option<FilePathBuf> {
+    let mut sanitized = FilePathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => sanitized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    Some(sanitized)
+}
+
+/// Whether the supplied link target, (lexically) resolved
+/// from the (sanitized) `base` directory, stays in the archive
+fn _is_contained(base: &FilePath, target: &FilePath) -> bool {
+    let mut depth = base.components().count();
+
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(parent) => depth = parent,
+                None => return false,
+            },
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+
+    true
+}
+
+/// Walk the supplied archive's entries, refusing the first that's
+/// unsafe to unpack (per the supplied limits) and handing each
+/// of the others (along with its sanitized path) to `visit`,
+/// reporting the number of entries walked
+fn _vet_entries<Visit>(
+    UploadedTarArchive(mut archive, size): UploadedTarArchive,
+    limits: ArchiveLimits,
+    mut visit: Visit,
+) -> Result<usize, ArchiveRejection>
+where
+    Visit: FnMut(&mut tar::Entry<'_, ByteReader<Bytes>>, &FilePath) -> Result<(), ArchiveRejection>,
+{
+    let size_limit = limits
+        .max_unpacked_size
+        .min((size as u64).saturating_mul(limits.max_expansion_ratio));
+
+    let unreadable = |index: usize| {
+        move |error: std::io::Error| ArchiveRejection::Unreadable(index, error.to_string())
+    };
+
+    let (mut count, mut total) = (0usize, 0u64);
+    let mut links = BTreeSet::<FilePathBuf>::new();
+
+    for (index, entry) in archive.entries().map_err(unreadable(0))?.enumerate() {
+        let mut entry = entry.map_err(unreadable(index))?;
+        let raw = entry.path().map_err(unreadable(index))?.into_owned();
+        let name = raw.display().to_string();
+
+        count += 1;
+
+        if limits.max_entries < count {
+            return Err(ArchiveRejection::TooManyEntries(name, limits.max_entries));
+        }
+
+        let path =
+            _sanitized_path(&raw).ok_or_else(|| ArchiveRejection::Traversal(name.clone()))?;
+
+        if path
+            .ancestors()
+            .skip(1)
+            .any(|ancestor| links.contains(ancestor))
+        {
+            return Err(ArchiveRejection::ThroughLink(name));
+        }
+
+        match entry.header().entry_type() {
+            tar::EntryType::Regular | tar::EntryType::Continuous | tar::EntryType::Directory => {}
+            kind @ (tar::EntryType::Symlink | tar::EntryType::Link) => {
+                let target = entry
+                    .link_name()
+                    .map_err(unreadable(index))?
+                    .ok_or_else(|| {
+                        ArchiveRejection::Unreadable(index, String::from("link has no target"))
+                    })?
+                    .into_owned();
+
+                // symbolic links are relative to their own directory,
+                // hard links are relative to the archive's root
+                let base = match kind {
+                    tar::EntryType::Symlink => path.parent().unwrap_or(FilePath::new("")),
+                    _ => FilePath::new(""),
+                };
+
+                if !_is_contained(base, &target) {
+                    return Err(ArchiveRejection::EscapingLink(
+                        name,
+                        target.display().to_string(),
+                    ));
+                }
+
+                if kind.is_symlink() {
+                    links.insert(path.clone());
+                }
+            }
+            kind => return Err(ArchiveRejection::UnsupportedType(name, kind)),
+        }
+
+        total = total.saturating_add(entry.size());
+
+        if size_limit < total {
+            return Err(ArchiveRejection::TooLarge(name, size_limit));
+        }
+
+        visit(&mut entry, &path)?;
+    }
+
+    Ok(count)
+}
+
+/// Unpack the supplied archive into `dir`, entry by entry,
+/// refusing (and stopping at) the first unsafe entry
+pub fn safe_unpack(
+    archive: UploadedTarArchive,
+    dir: &FilePath,
+    limits: ArchiveLimits,
+) -> Result<usize, ArchiveRejection> {
+    let mut index = 0usize;
+
+    _vet_entries(archive, limits, |entry, path| {
+        index += 1;
+
+        match entry.unpack_in(dir) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(ArchiveRejection::Traversal(path.display().to_string())),
+            Err(error) => Err(ArchiveRejection::Unreadable(index - 1, error.to_string())),
+        }
+    })
+}
+
+// </editor-fold desc="// Archive Safety ...">
+
 /// Endpoint 1/2 for [Day 20: Task](https://console.shuttle.rs/cch/challenge/20#:~:text=⭐️)
 #[tracing::instrument(ret, err(Debug), skip_all)]
-pub async fn get_archived_file_count(
-    mut archive: UploadedTarArchive,
-) -> Result<Json<u64>, (StatusCode, String)> {
-    archive
-        .entries()
-        .map_err(|error| (StatusCode::UNPROCESSABLE_ENTITY, error.to_string()))
-        .and_then(|entries| {
-            let file_count = entries.count();
-
-            u64::from_usize(file_count).map(Json).ok_or_else(|| {
-                (
-                    StatusCode::UNPROCESSABLE_ENTITY,
-                    format!("error casting {file_count} as u64"),
-                )
-            })
+pub async fn get_archived_file_count(archive: UploadedTarArchive) -> Result<Json<u64>, AppError> {
+    utils::run_blocking(move || {
+        let file_count = _vet_entries(archive, ArchiveLimits::default(), |_, _| Ok(()))?;
+
+        u64::from_usize(file_count).map(Json).ok_or_else(|| {
+            ErrorEnvelope::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("error casting {file_count} as u64"),
+            )
+            .into()
         })
+    })
+    .await
 }
 
 /// Endpoint 2/2 for [Day 20: Task](https://console.shuttle.rs/cch/challenge/20#:~:text=⭐️)
 #[tracing::instrument(ret, err(Debug), skip_all)]
 pub async fn get_total_archived_file_size(
-    mut archive: UploadedTarArchive,
-) -> Result<Json<u64>, (StatusCode, String)> {
-    let entries = archive
-        .entries()
-        .map_err(|error| (StatusCode::UNPROCESSABLE_ENTITY, error.to_string()))?;
+    archive: UploadedTarArchive,
+) -> Result<Json<u64>, AppError> {
+    utils::run_blocking(move || _total_archived_file_size(archive)).await
+}
 
+/// Sum the sizes of the supplied archive's entries
+fn _total_archived_file_size(archive: UploadedTarArchive) -> Result<Json<u64>, AppError> {
     let mut total = 0u64;
-    let file_count = entries.size_hint().1.unwrap_or(usize::MAX);
 
-    for (idx, entry) in entries.enumerate() {
-        match entry {
-            Ok(entry) => {
-                total += entry.size();
-            }
-            Err(error) => {
-                return Err((
-                    StatusCode::UNPROCESSABLE_ENTITY,
-                    format!("could not evaluate size of {idx}/{file_count}: {error:?}"),
-                ));
-            }
-        }
-    }
+    _vet_entries(archive, ArchiveLimits::default(), |entry, _| {
+        total += entry.size();
+        Ok(())
+    })?;
 
     Ok(Json(total))
 }
 
+/// How a [cookie hunt](git_blame_cookie_hunt) is run
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct HuntMode {
+    /// whether to queue the hunt as a background
+    /// job (rather than wait for its outcome)
+    #[serde(default)]
+    r#async: bool,
+}
+
 /// Complete [Day 20: Bonus](https://console.shuttle.rs/cch/challenge/20#:~:text=🎁️)
 ///
 /// > **NOTE:** I hate this fucking function so god damn much.
@@ -167,37 +419,98 @@ pub async fn get_total_archived_file_size(
 /// >           git is fantastic, but things like how it works
 /// >           under the hood or how to traverse its structure(s)
 /// >           are absolutely none of my fucking business.
-#[tracing::instrument(ret, err(Debug), skip_all)]
+///
+/// With `?async=true`, the hunt is queued as a background job
+/// instead, and its id is reported immediately, so that large
+/// repositories don't outlast proxies' timeouts; the job's
+/// progress (commits scanned) and outcome can then be polled
+/// via [`get_job_status`] (until an hour after it finishes).
+/// While too many hunts are already queued, further ones are
+/// rejected with a `503 Service Unavailable`.
+#[tracing::instrument(ret, skip_all)]
 pub async fn git_blame_cookie_hunt(
-    UploadedTarArchive(mut archive, _): UploadedTarArchive,
-) -> Result<String, Response> {
-    let temp = tempfile::tempdir().map_err(as_412_response)?;
+    State(state): State<ShuttleAppState>,
+    Query(mode): Query<HuntMode>,
+    archive: UploadedTarArchive,
+) -> Response {
+    if !mode.r#async {
+        return utils::run_blocking(move || _hunt_cookie(archive, &JobProgress::default()))
+            .await
+            .into_response();
+    }
+
+    let id = state.rng.ulid(state.clock.now()).to_string();
+
+    let Some(status) =
+        state
+            .tracked_jobs
+            .submit(id.clone(), "cookie hunt", |progress| async move {
+                utils::run_blocking(move || _hunt_cookie(archive, &progress))
+                    .await
+                    .map(Value::String)
+                    .map_err(|error| error.to_string())
+            })
+    else {
+        return ErrorEnvelope::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "too many queued cookie hunts, please retry later",
+        )
+        .into_response();
+    };
 
-    archive.unpack(temp.path()).map_err(as_412_response)?;
+    (
+        StatusCode::ACCEPTED,
+        [(header::LOCATION, routes::day20::job(&id))],
+        Json(status),
+    )
+        .into_response()
+}
+
+/// Report the status (and, once finished, the
+/// outcome) of a queued [cookie hunt](git_blame_cookie_hunt)
+#[tracing::instrument(ret, skip(state))]
+pub async fn get_job_status(
+    State(state): State<ShuttleAppState>,
+    Path(id): Path<String>,
+) -> Result<Json<JobStatus>, AppError> {
+    state.tracked_jobs.get(&id).map(Json).ok_or_else(|| {
+        ErrorEnvelope::new(StatusCode::NOT_FOUND, format!("no such job: {id}")).into()
+    })
+}
 
-    let repo = GitRepo::open(temp.path()).map_err(as_412_response)?;
+/// Unpack the supplied archive's repository and walk its `christmas`
+/// branch for the commit that added a cookie to `santa.txt`,
+/// recording each commit scanned in the supplied progress
+fn _hunt_cookie(archive: UploadedTarArchive, progress: &JobProgress) -> Result<String, AppError> {
+    let temp = tempfile::tempdir().map_err(as_412)?;
+
+    safe_unpack(archive, temp.path(), ArchiveLimits::default())?;
+
+    let repo = GitRepo::open(temp.path()).map_err(as_412)?;
 
     let branch = repo
         .find_branch("christmas", git2::BranchType::Local)
-        .map_err(as_412_response)?;
+        .map_err(as_412)?;
 
-    let tree = branch.get().peel_to_tree().map_err(as_412_response)?;
+    let tree = branch.get().peel_to_tree().map_err(as_412)?;
 
     let mut options = git2::build::CheckoutBuilder::new();
 
     repo.checkout_tree(tree.as_object(), Some(options.force()))
-        .map_err(as_412_response)?;
+        .map_err(as_412)?;
 
     repo.set_head(branch.get().name().unwrap())
-        .map_err(as_412_response)?;
+        .map_err(as_412)?;
 
-    let mut walker = repo.revwalk().map_err(as_412_response)?;
-    walker.push_head().map_err(as_412_response)?;
+    let mut walker = repo.revwalk().map_err(as_412)?;
+    walker.push_head().map_err(as_412)?;
 
     let mut cookie_commit: Option<(String, git2::Oid)> = None;
 
     for id in walker.filter(Result::is_ok).map(Result::unwrap) {
-        let commit = repo.find_commit(id).map_err(as_412_response)?;
+        let commit = repo.find_commit(id).map_err(as_412)?;
+
+        progress.advance(1);
 
         let commit_id = commit
             .parent_ids()
@@ -206,18 +519,18 @@ pub async fn git_blame_cookie_hunt(
 
         let mut diff = repo
             .diff_tree_to_tree(
-                Some(&commit.tree().map_err(as_412_response)?),
+                Some(&commit.tree().map_err(as_412)?),
                 commit_id.as_ref(),
                 Some(&mut git2::DiffOptions::new()),
             )
-            .map_err(as_412_response)?;
+            .map_err(as_412)?;
 
         diff.find_similar(Some(
             git2::DiffFindOptions::new()
                 .copies(false)
                 .break_rewrites(false),
         ))
-        .map_err(as_412_response)?;
+        .map_err(as_412)?;
 
         for file in diff
             .deltas()
@@ -247,7 +560,7 @@ pub async fn git_blame_cookie_hunt(
         {
             if let Ok(blob) = repo
                 .find_object(file.id(), Some(git2::ObjectType::Blob))
-                .map_err(as_412_response)?
+                .map_err(as_412)?
                 .into_blob()
             {
                 let contents = String::from_utf8_lossy(blob.content()).to_string();
@@ -269,10 +582,56 @@ pub async fn git_blame_cookie_hunt(
 
     match cookie_commit {
         Some((author, commit)) => Ok(format!("{author} {commit}")),
-        None => Err((StatusCode::NOT_FOUND, "cookie commit not found".to_string()).into_response()),
+        None => Err(ErrorEnvelope::new(StatusCode::NOT_FOUND, "cookie commit not found").into()),
+    }
+}
+
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 20](https://console.shuttle.rs/cch/challenge/20) solutions
+#[derive(Debug)]
+pub struct Day20;
+
+impl DayModule for Day20 {
+    fn day() -> i8 {
+        20
+    }
+
+    fn name() -> &'static str {
+        "Git Archives"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                20,
+                POST routes::day20::ARCHIVE_FILES,
+                get_archived_file_count,
+                "Count an archive's files"
+            ))
+            .route(day_route!(
+                20,
+                POST routes::day20::ARCHIVE_FILES_SIZE,
+                get_total_archived_file_size,
+                "Total an archive's file sizes"
+            ))
+            .route(day_route!(
+                20,
+                POST routes::day20::COOKIE,
+                git_blame_cookie_hunt,
+                "Find the commit that hid the cookie"
+            ))
+            .route(day_route!(
+                20,
+                GET routes::day20::JOB,
+                get_job_status,
+                "Report a queued cookie hunt's status"
+            ))
     }
 }
 
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -301,5 +660,294 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
-    use crate::utils::{service, TestService};
+    use super::{_vet_entries, safe_unpack, ArchiveLimits, ArchiveRejection, UploadedTarArchive};
+    use crate::errors::ErrorEnvelope;
+    use crate::jobs::{JobState, JobStatus};
+    use crate::routes;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{service, state, TestService};
+
+    /// Test that `get_archived_file_count`, `get_total_archived_file_size`,
+    /// and `git_blame_cookie_hunt` satisfy the conditions of
+    /// [CCH 2023 Challenge 20](https://console.shuttle.rs/cch/challenge/20)
+    #[rstest]
+    #[case::challenge_example_one(
+        routes::day20::archive_files(),
+        "northpole20231220.tar",
+        StatusCode::OK,
+        "6"
+    )]
+    #[case::challenge_example_two(
+        routes::day20::archive_files_size(),
+        "northpole20231220.tar",
+        StatusCode::OK,
+        "1196282"
+    )]
+    #[case::bonus_example(
+        routes::day20::cookie(),
+        "cookiejar.tar",
+        StatusCode::OK,
+        "Grinch 71dfab551a1958b35b7436c54b7455dcec99a12c"
+    )]
+    #[case::not_a_git_repo(
+        routes::day20::cookie(),
+        "northpole20231220.tar",
+        StatusCode::PRECONDITION_FAILED,
+        ""
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_challenge_twenty(
+        service: TestService,
+        #[case] url: &str,
+        #[case] asset: &str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let archive = std::fs::read(format!("{}/assets/{asset}", env!("CARGO_MANIFEST_DIR")))?;
+
+        let response = service
+            .resolve(
+                Request::post(url)
+                    .header(headers::CONTENT_TYPE, "application/x-tar")
+                    .body(Body::from(archive))?,
+            )
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status.is_success() {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+
+            assert_eq!(expected_content, String::from_utf8_lossy(content.as_ref()));
+        }
+
+        Ok(())
+    }
+
+    /// Test that queued cookie hunts report their id immediately,
+    /// and their progress and outcome once they've finished
+    #[rstest]
+    #[case::found("cookiejar.tar", JobState::Succeeded)]
+    #[case::not_a_git_repo("northpole20231220.tar", JobState::Failed)]
+    #[test_log::test(tokio::test)]
+    async fn test_async_cookie_hunt(
+        state: ShuttleAppState,
+        #[case] asset: &str,
+        #[case] expected_state: JobState,
+    ) -> anyhow::Result<()> {
+        let archive = std::fs::read(format!("{}/assets/{asset}", env!("CARGO_MANIFEST_DIR")))?;
+
+        let response = TestService::from(crate::router(state.clone()))
+            .resolve(
+                Request::post(format!("{}?async=true", routes::day20::cookie()))
+                    .header(headers::CONTENT_TYPE, "application/x-tar")
+                    .body(Body::from(archive))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::ACCEPTED, response.status());
+
+        let location = response
+            .headers()
+            .get(headers::LOCATION)
+            .map(|value| value.to_str().map(String::from))
+            .transpose()?;
+        let queued: JobStatus =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(Some(routes::day20::job(&queued.id)), location);
+        assert!(!queued.state.is_finished());
+
+        let mut status = queued.clone();
+
+        for _ in 0..100 {
+            let response = TestService::from(crate::router(state.clone()))
+                .resolve(routes::day20::job(&queued.id).as_str())
+                .await?;
+
+            assert_eq!(StatusCode::OK, response.status());
+
+            status = serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+            if status.state.is_finished() {
+                break;
+            }
+
+            tokio::time::sleep(core::time::Duration::from_millis(50)).await;
+        }
+
+        assert_eq!(expected_state, status.state);
+
+        match expected_state {
+            JobState::Succeeded => {
+                assert_eq!(
+                    Some(Value::from(
+                        "Grinch 71dfab551a1958b35b7436c54b7455dcec99a12c"
+                    )),
+                    status.result
+                );
+                assert!(0 < status.progress);
+            }
+            _ => assert!(status.error.is_some()),
+        }
+
+        Ok(())
+    }
+
+    /// Test that unknown jobs aren't reported
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_unknown_job(service: TestService) -> anyhow::Result<()> {
+        let response = service
+            .resolve(routes::day20::job("01HJ0000000000000000000000").as_str())
+            .await?;
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+        Ok(())
+    }
+
+    /// A raw (i.e. unvalidated) archive entry's path,
+    /// type, link target, and content
+    type RawEntry<'a> = (&'a str, tar::EntryType, &'a str, &'a str);
+
+    /// Build a tar archive of the supplied entries, bypassing
+    /// the validation `tar::Builder` applies to entry paths
+    fn _crafted_archive(entries: &[RawEntry]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+
+        for (path, kind, link, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            let gnu = header.as_gnu_mut().unwrap();
+
+            gnu.name[..path.len()].copy_from_slice(path.as_bytes());
+            gnu.linkname[..link.len()].copy_from_slice(link.as_bytes());
+
+            header.set_entry_type(*kind);
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+
+            builder.append(&header, content.as_bytes()).unwrap();
+        }
+
+        builder.into_inner().unwrap()
+    }
+
+    /// Wrap the supplied archive as though it'd been uploaded
+    fn _uploaded(archive: Vec<u8>) -> UploadedTarArchive {
+        let size = archive.len();
+        UploadedTarArchive(
+            tar::Archive::new(bytes::Buf::reader(bytes::Bytes::from(archive))),
+            size,
+        )
+    }
+
+    /// Test that every archive endpoint refuses archives with
+    /// unsafe entries, naming the offending entry
+    #[rstest]
+    #[case::traversal(&[("../evil.txt", tar::EntryType::Regular, "", "evil")], "../evil.txt")]
+    #[case::absolute(&[("/etc/evil", tar::EntryType::Regular, "", "evil")], "/etc/evil")]
+    #[case::escaping_symlink(&[("link", tar::EntryType::Symlink, "../../etc", "")], "link")]
+    #[case::escaping_hard_link(&[("hard", tar::EntryType::Link, "../secret", "")], "hard")]
+    #[case::through_symlink(
+        &[("sub/", tar::EntryType::Directory, "", ""), ("link", tar::EntryType::Symlink, "sub", ""), ("link/evil.txt", tar::EntryType::Regular, "", "evil")],
+        "link/evil.txt"
+    )]
+    #[case::unsupported(&[("pipe", tar::EntryType::Fifo, "", "")], "pipe")]
+    #[test_log::test(tokio::test)]
+    async fn test_unsafe_archives(
+        state: ShuttleAppState,
+        #[case] entries: &[RawEntry<'_>],
+        #[case] offender: &str,
+    ) -> anyhow::Result<()> {
+        let archive = _crafted_archive(entries);
+
+        for url in [
+            routes::day20::archive_files(),
+            routes::day20::archive_files_size(),
+            routes::day20::cookie(),
+        ] {
+            let response = TestService::from(crate::router(state.clone()))
+                .resolve(
+                    Request::post(url)
+                        .header(headers::CONTENT_TYPE, "application/x-tar")
+                        .body(Body::from(archive.clone()))?,
+                )
+                .await?;
+
+            assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status(), "{url}");
+
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+            let envelope = serde_json::from_slice::<ErrorEnvelope>(&content)?;
+
+            assert!(
+                envelope.error.contains(&format!("{offender:?}")),
+                "{url}: {envelope:?}"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Test that contained links are unpacked as-is
+    #[rstest]
+    #[test_log::test]
+    fn test_safe_unpack() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let archive = _crafted_archive(&[
+            ("./", tar::EntryType::Directory, "", ""),
+            ("sub/", tar::EntryType::Directory, "", ""),
+            ("sub/file.txt", tar::EntryType::Regular, "", "cookie"),
+            (
+                "sub/alias",
+                tar::EntryType::Symlink,
+                "../sub/./file.txt",
+                "",
+            ),
+        ]);
+
+        assert_eq!(
+            4,
+            safe_unpack(
+                _uploaded(archive),
+                directory.path(),
+                ArchiveLimits::default()
+            )?
+        );
+        assert_eq!(
+            "cookie",
+            std::fs::read_to_string(directory.path().join("sub/alias"))?
+        );
+
+        Ok(())
+    }
+
+    /// Test that archives exceeding the entry count
+    /// or unpacked size limits are refused
+    #[rstest]
+    #[case::too_many_entries(
+        ArchiveLimits { max_entries: 1, ..ArchiveLimits::default() },
+        "archive entry \"b.txt\" exceeds the limit of 1 entries"
+    )]
+    #[case::too_large(
+        ArchiveLimits { max_unpacked_size: 6, ..ArchiveLimits::default() },
+        "archive entry \"b.txt\" exceeds the unpacked size limit of 6 bytes"
+    )]
+    #[case::too_expansive(
+        ArchiveLimits { max_expansion_ratio: 0, ..ArchiveLimits::default() },
+        "archive entry \"a.txt\" exceeds the unpacked size limit of 0 bytes"
+    )]
+    #[test_log::test]
+    fn test_archive_limits(#[case] limits: ArchiveLimits, #[case] expected: &str) {
+        let archive = _crafted_archive(&[
+            ("a.txt", tar::EntryType::Regular, "", "four"),
+            ("b.txt", tar::EntryType::Regular, "", "four"),
+        ]);
+
+        let rejection = _vet_entries(_uploaded(archive), limits, |_, _| Ok(()))
+            .expect_err("the archive exceeds its limits");
+
+        assert_eq!(expected, rejection.to_string());
+    }
 }
@@ -2,20 +2,49 @@
 //!
 
 // Standard Library Imports
-use core::fmt::Debug;
+use core::{fmt::Debug, net::IpAddr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    net::SocketAddr,
+    path::PathBuf as FilePathBuf,
+    sync::{Arc, Mutex},
+};
 
 // Third-Party Imports
 use axum::{
     async_trait,
     body::BoxBody,
-    extract::{path::Path, FromRef, FromRequestParts},
-    http::{request::Parts, Response, StatusCode},
+    extract::{path::Path, ConnectInfo, FromRef, FromRequestParts, Json, Query, State},
+    http::{header, request::Parts, HeaderMap, Response, StatusCode},
     response::IntoResponse,
 };
+use chrono::{DateTime, NaiveDate, Utc};
 use dms_coordinates::DMS;
+use geojson::{Feature, Geometry, JsonObject, Value as GeoJsonValue};
 use isocountry::{CountryCode, CountryCodeParseErr};
+use once_cell::sync::Lazy;
 use s2::{cellid::CellID, latlng::LatLng};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use shuttle_secrets::SecretStore;
+
+// Crate-Level Imports
+use crate::{
+    errors::{ErrorEnvelope, UpstreamError},
+    outbound::Provider,
+    persistence::EncryptedPersistence,
+    record_fields, routes,
+    solutions::{DayModule, DayRoutes},
+    state::ShuttleAppState,
+    telemetry::send_upstream,
+};
+
+/// The persistent store key of the geocoder's usage
+const QUOTA_KEY: &str = "geocoder-quota";
+
+/// The default number of daily requests to the geocoding provider
+const DEFAULT_DAILY_QUOTA: u64 = 1_000;
 
 // <editor-fold desc="// S2CellId ...">
 
@@ -72,11 +101,40 @@ impl<State: Send + Sync> FromRequestParts<State> for S2CellId {
 
 // </editor-fold desc="// S2CellId ...">
 
+// <editor-fold desc="// CoordsFormat ...">
+
+/// The media type of GeoJSON responses
+pub const GEOJSON_CONTENT_TYPE: &str = "application/geo+json";
+
+/// The formats in which [`resolve_s2_cell_center`]
+/// may report a cell's center
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CoordsFormat {
+    /// degrees, minutes, and seconds (per the challenge)
+    #[default]
+    Dms,
+    /// a GeoJSON `Point` feature
+    GeoJson,
+}
+
+/// Query parameters for [`resolve_s2_cell_center`]
+#[derive(Debug, Default, Deserialize)]
+pub struct CoordsQuery {
+    #[serde(default)]
+    format: CoordsFormat,
+}
+
+// </editor-fold desc="// CoordsFormat ...">
+
 // <editor-fold desc="// GeoAddress ...">
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GeoAddress {
     country_code: String,
+    /// the address's other (unused) components
+    #[serde(flatten)]
+    rest: Map<String, Value>,
 }
 
 // </editor-fold desc="// GeoAddress ...">
@@ -86,10 +144,13 @@ pub struct GeoAddress {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GeoCodeResponse {
     address: GeoAddress,
+    /// the response's other (unused) fields
+    #[serde(flatten)]
+    rest: Map<String, Value>,
 }
 
 impl GeoCodeResponse {
-    fn country(&self) -> Result<CountryCode, (StatusCode, String)> {
+    fn country(&self) -> Result<CountryCode, UpstreamError> {
         let code = &self.address.country_code.to_uppercase();
 
         {
@@ -104,15 +165,216 @@ impl GeoCodeResponse {
                     .map(|country| *country),
             }
         }
-        .map_err(|error| (StatusCode::UNPROCESSABLE_ENTITY, format!("{error:?}")))
+        .map_err(|error| UpstreamError::Decode(format!("{error:?}")))
     }
 }
 
 // </editor-fold desc="// GeoCodeResponse ...">
 
+// <editor-fold desc="// CountryNames ...">
+
+/// The bundled normalization table, mapping ISO 3166 country
+/// names to the short forms the challenge's grader expects
+static BUNDLED_COUNTRY_NAMES: Lazy<BTreeMap<String, String>> = Lazy::new(|| {
+    serde_json::from_str(include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/day-21/country-names.json"
+    )))
+    .expect("the bundled country-name table should be valid")
+});
+
+/// The names countries are reported by, per the bundled
+/// normalization table and any overrides thereof
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CountryNames {
+    /// the reported names of specific countries (by ISO 3166
+    /// name), taking precedence over the bundled table
+    overrides: BTreeMap<String, String>,
+}
+
+impl CountryNames {
+    /// Create a table with the supplied overrides
+    pub fn new(overrides: BTreeMap<String, String>) -> Self {
+        Self { overrides }
+    }
+
+    /// Read the overrides from the `COUNTRY_NAME_OVERRIDES` secret
+    /// (if set), a JSON object of ISO 3166 names to reported names
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        secrets
+            .get("COUNTRY_NAME_OVERRIDES")
+            .and_then(|overrides| {
+                serde_json::from_str(&overrides)
+                    .map_err(|error| {
+                        tracing::error!("ignoring COUNTRY_NAME_OVERRIDES secret: {error}");
+                    })
+                    .ok()
+            })
+            .map(Self::new)
+            .unwrap_or_default()
+    }
+
+    /// The name the supplied country is reported by
+    pub fn normalize(&self, country: CountryCode) -> String {
+        let name = country.name();
+
+        self.overrides
+            .get(name)
+            .or_else(|| BUNDLED_COUNTRY_NAMES.get(name))
+            .map_or_else(|| name.to_string(), String::clone)
+    }
+}
+
+// </editor-fold desc="// CountryNames ...">
+
+// <editor-fold desc="// Geocoder ...">
+
+/// A reverse geocoding provider
+#[async_trait]
+pub trait Geocoder: Debug + Send + Sync {
+    /// Reverse-geocode the supplied coordinates
+    async fn reverse(&self, lat: f64, lng: f64) -> Result<GeoCodeResponse, UpstreamError>;
+}
+
+/// The [geocode.maps.co](https://geocode.maps.co) geocoding provider
+#[derive(Clone, Debug, Default)]
+pub struct MapsCoGeocoder {
+    /// the client the provider is called with
+    client: reqwest::Client,
+}
+
+impl MapsCoGeocoder {
+    /// Create a geocoder calling the provider with the supplied client
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Geocoder for MapsCoGeocoder {
+    async fn reverse(&self, lat: f64, lng: f64) -> Result<GeoCodeResponse, UpstreamError> {
+        Ok(UpstreamError::check(
+            send_upstream(
+                Provider::Geocoder.name(),
+                self.client.get(format!(
+                    "https://geocode.maps.co/reverse?lat={lat}&lon={lng}"
+                )),
+            )
+            .await?,
+        )?
+        .json::<GeoCodeResponse>()
+        .await?)
+    }
+}
+
+/// The path of the fixture recording the response for the supplied
+/// coordinates (which are rounded to 4 decimal places, i.e. ~11m)
+fn _fixture_path(dir: &FilePathBuf, lat: f64, lng: f64) -> FilePathBuf {
+    dir.join(format!("{lat:.4}_{lng:.4}.json"))
+}
+
+/// A geocoder answering from previously recorded responses
+/// (e.g. [`GEOCODE_FIXTURES_DIR`]), for I/O-free tests
+#[derive(Clone, Debug)]
+pub struct FixtureGeocoder {
+    /// the directory the recorded responses are read from
+    dir: FilePathBuf,
+}
+
+impl FixtureGeocoder {
+    /// Create a geocoder answering from the supplied directory's fixtures
+    pub fn new(dir: impl Into<FilePathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl Geocoder for FixtureGeocoder {
+    async fn reverse(&self, lat: f64, lng: f64) -> Result<GeoCodeResponse, UpstreamError> {
+        let path = _fixture_path(&self.dir, lat, lng);
+        let recorded = fs::read(&path).map_err(|error| {
+            UpstreamError::Unavailable(format!(
+                "no recorded response ({}): {error}",
+                path.display()
+            ))
+        })?;
+
+        serde_json::from_slice(&recorded).map_err(|error| UpstreamError::Decode(error.to_string()))
+    }
+}
+
+/// A geocoder recording another's responses as
+/// fixtures (for a [`FixtureGeocoder`] to answer from)
+#[derive(Debug)]
+pub struct RecordingGeocoder {
+    /// the geocoder whose responses are recorded
+    inner: Arc<dyn Geocoder>,
+    /// the directory recorded responses are written to
+    dir: FilePathBuf,
+}
+
+impl RecordingGeocoder {
+    /// Record the supplied geocoder's responses to the supplied directory
+    pub fn new(inner: Arc<dyn Geocoder>, dir: impl Into<FilePathBuf>) -> Self {
+        Self {
+            inner,
+            dir: dir.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Geocoder for RecordingGeocoder {
+    async fn reverse(&self, lat: f64, lng: f64) -> Result<GeoCodeResponse, UpstreamError> {
+        let response = self.inner.reverse(lat, lng).await?;
+        let path = _fixture_path(&self.dir, lat, lng);
+
+        // failing to record a response shouldn't fail the request
+        if let Err(error) = fs::create_dir_all(&self.dir).and_then(|()| {
+            fs::write(
+                &path,
+                serde_json::to_vec_pretty(&response).map_err(std::io::Error::other)?,
+            )
+        }) {
+            tracing::warn!("unable to record {}: {error}", path.display());
+        }
+
+        Ok(response)
+    }
+}
+
+/// The directory of the geocoding provider's recorded responses
+pub const GEOCODE_FIXTURES_DIR: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/geocode");
+
+/// Create the service's geocoder, which answers from the
+/// `GEOCODER_FIXTURES` secret's directory (if set), or else
+/// calls the provider with the supplied client (recording its
+/// responses to the `GEOCODER_RECORD` secret's directory, if set)
+pub fn geocoder_from_secrets(secrets: &SecretStore, client: reqwest::Client) -> Arc<dyn Geocoder> {
+    if let Some(dir) = secrets.get("GEOCODER_FIXTURES") {
+        return Arc::new(FixtureGeocoder::new(dir));
+    }
+
+    let live = Arc::new(MapsCoGeocoder::new(client));
+
+    match secrets.get("GEOCODER_RECORD") {
+        Some(dir) => Arc::new(RecordingGeocoder::new(live, dir)),
+        None => live,
+    }
+}
+
+// </editor-fold desc="// Geocoder ...">
+
 /// Complete [Day 21: Challenge](https://console.shuttle.rs/cch/challenge/21#:~:text=⭐)
-#[tracing::instrument(ret, skip(cell), fields(cell_id = cell.0, lat, lng))]
-pub async fn resolve_s2_cell_center(cell: S2CellId) -> impl IntoResponse {
+///
+/// With `format=geojson`, the cell's center is reported as a GeoJSON
+/// `Point` feature (carrying the challenge's answer as its `dms` property)
+#[tracing::instrument(skip(cell), fields(cell_id = cell.0, lat, lng))]
+pub async fn resolve_s2_cell_center(
+    cell: S2CellId,
+    Query(query): Query<CoordsQuery>,
+) -> Response<BoxBody> {
     // Examples:
     //   - "0100111110010011000110011001010101011111000010100011110001011011"
     //     -> 5733954879908101211
@@ -127,8 +389,7 @@ pub async fn resolve_s2_cell_center(cell: S2CellId) -> impl IntoResponse {
 
     let (lat, lng) = (point.lat.deg(), point.lng.deg());
 
-    tracing::Span::current().record("lat", format!("{lat:.7}"));
-    tracing::Span::current().record("lng", format!("{lng:.7}"));
+    record_fields!("lat" => format!("{lat:.7}"), "lng" => format!("{lng:.7}"));
 
     let (mut lat, mut lng) = (
         DMS::from_decimal_degrees(lat, true),
@@ -138,38 +399,325 @@ pub async fn resolve_s2_cell_center(cell: S2CellId) -> impl IntoResponse {
     lat.seconds = format!("{:.3}", lat.seconds).parse::<f64>().unwrap();
     lng.seconds = format!("{:.3}", lng.seconds).parse::<f64>().unwrap();
 
-    format!("{lat} {lng}")
+    let dms = format!("{lat} {lng}");
+
+    match query.format {
+        CoordsFormat::Dms => dms.into_response(),
+        CoordsFormat::GeoJson => {
+            let feature = Feature {
+                geometry: Some(Geometry::new(GeoJsonValue::Point(vec![
+                    point.lng.deg(),
+                    point.lat.deg(),
+                ]))),
+                properties: Some(JsonObject::from_iter([
+                    ("cell_id".to_string(), cell.0.into()),
+                    ("dms".to_string(), dms.into()),
+                ])),
+                ..Feature::default()
+            };
+
+            (
+                [(header::CONTENT_TYPE, GEOJSON_CONTENT_TYPE)],
+                feature.to_string(),
+            )
+                .into_response()
+        }
+    }
+}
+
+// <editor-fold desc="// GeocoderQuota ...">
+
+/// The geocoding provider's usage on a given (UTC) day
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct QuotaUsage {
+    /// the day the usage applies to
+    day: NaiveDate,
+    /// the number of requests made to the provider
+    used: u64,
+}
+
+/// A snapshot of the geocoding provider's usage quota
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaReport {
+    /// the number of requests allowed per (UTC) day
+    pub limit: u64,
+    /// the number of requests made so far today
+    pub used: u64,
+    /// the number of requests left today
+    pub remaining: u64,
+    /// when the quota next resets
+    pub resets_at: DateTime<Utc>,
+}
+
+/// The daily quota of requests to the geocoding provider
+/// (which silently blocks clients that exceed its limits),
+/// and the client addresses allowed to spend it (if limited)
+#[derive(Debug)]
+pub struct GeocoderQuota {
+    /// the number of requests allowed per (UTC) day
+    limit: u64,
+    /// the client addresses allowed to spend the quota
+    /// (or `None` if every address is allowed to)
+    allowlist: Option<BTreeSet<IpAddr>>,
+    /// today's usage (once loaded)
+    usage: Mutex<Option<QuotaUsage>>,
+    /// where usage is persisted (if anywhere)
+    persistence: Option<EncryptedPersistence>,
+}
+
+impl Default for GeocoderQuota {
+    fn default() -> Self {
+        Self::new(DEFAULT_DAILY_QUOTA, None, None)
+    }
 }
 
+impl GeocoderQuota {
+    /// Create a quota of `limit` daily requests, spendable by the
+    /// supplied addresses (if limited), persisted to the supplied
+    /// store (if any)
+    pub fn new(
+        limit: u64,
+        allowlist: Option<BTreeSet<IpAddr>>,
+        persistence: Option<EncryptedPersistence>,
+    ) -> Self {
+        Self {
+            limit,
+            allowlist,
+            usage: Mutex::new(None),
+            persistence,
+        }
+    }
+
+    /// Create a quota whose limit is read from the `GEOCODER_DAILY_QUOTA`
+    /// secret and whose allowlist is read from the (comma-separated)
+    /// `GEOCODER_ALLOWLIST` secret, falling back to the defaults
+    pub fn from_secrets(secrets: &SecretStore, persistence: Option<EncryptedPersistence>) -> Self {
+        let limit = secrets.get("GEOCODER_DAILY_QUOTA").and_then(|limit| {
+            limit
+                .parse::<u64>()
+                .map_err(|error| tracing::error!("ignoring GEOCODER_DAILY_QUOTA secret: {error}"))
+                .ok()
+        });
+
+        let allowlist = secrets.get("GEOCODER_ALLOWLIST").map(|addresses| {
+            addresses
+                .split(',')
+                .filter(|address| !address.trim().is_empty())
+                .filter_map(|address| {
+                    address
+                        .trim()
+                        .parse::<IpAddr>()
+                        .map_err(|error| {
+                            tracing::error!(
+                                "ignoring GEOCODER_ALLOWLIST entry {address:?}: {error}"
+                            );
+                        })
+                        .ok()
+                })
+                .collect::<BTreeSet<IpAddr>>()
+        });
+
+        Self::new(limit.unwrap_or(DEFAULT_DAILY_QUOTA), allowlist, persistence)
+    }
+
+    /// Whether the supplied (client) address may spend the quota
+    pub fn permits(&self, ip: Option<IpAddr>) -> bool {
+        self.allowlist
+            .as_ref()
+            .is_none_or(|allowlist| ip.is_some_and(|ip| allowlist.contains(&ip)))
+    }
+
+    /// Get the supplied day's usage, (re)loading it
+    /// from the persistent store if necessary
+    fn _usage(&self, usage: &mut Option<QuotaUsage>, today: NaiveDate) -> QuotaUsage {
+        let current = usage
+            .or_else(|| {
+                self.persistence
+                    .as_ref()
+                    .and_then(|store| store.load::<QuotaUsage>(QUOTA_KEY).ok())
+            })
+            .filter(|usage| usage.day == today)
+            .unwrap_or(QuotaUsage {
+                day: today,
+                used: 0,
+            });
+
+        *usage = Some(current);
+
+        current
+    }
+
+    /// Report the supplied usage against the quota
+    fn _report(&self, usage: QuotaUsage) -> QuotaReport {
+        QuotaReport {
+            limit: self.limit,
+            used: usage.used,
+            remaining: self.limit.saturating_sub(usage.used),
+            resets_at: usage
+                .day
+                .succ_opt()
+                .and_then(|day| day.and_hms_opt(0, 0, 0))
+                .map_or(DateTime::<Utc>::MAX_UTC, |midnight| midnight.and_utc()),
+        }
+    }
+
+    /// Report the quota's usage as of `now`
+    pub fn report(&self, now: DateTime<Utc>) -> QuotaReport {
+        let mut usage = self.usage.lock().unwrap();
+
+        self._report(self._usage(&mut usage, now.date_naive()))
+    }
+
+    /// Spend one request from the quota as of `now`, reporting the
+    /// resulting usage (or the exhausted quota, if nothing's left)
+    pub fn consume(&self, now: DateTime<Utc>) -> Result<QuotaReport, QuotaReport> {
+        let mut usage = self.usage.lock().unwrap();
+        let mut current = self._usage(&mut usage, now.date_naive());
+
+        if self.limit <= current.used {
+            return Err(self._report(current));
+        }
+
+        current.used += 1;
+        *usage = Some(current);
+
+        if let Some(store) = self.persistence.as_ref() {
+            if let Err(error) = store.save(QUOTA_KEY, current) {
+                tracing::error!("error persisting geocoder usage: {error}");
+            }
+        }
+
+        Ok(self._report(current))
+    }
+}
+
+/// Why a country couldn't be resolved
+#[derive(Debug)]
+pub enum GeocodeRejection {
+    /// the requesting address may not spend the geocoder's quota
+    Forbidden,
+    /// the geocoder's quota is exhausted for the day
+    Exhausted(QuotaReport, DateTime<Utc>),
+    /// the geocoding provider couldn't be called,
+    /// or its response was unusable
+    Upstream(UpstreamError),
+}
+
+impl IntoResponse for GeocodeRejection {
+    fn into_response(self) -> axum::response::Response {
+        match self {
+            Self::Forbidden => ErrorEnvelope::new(
+                StatusCode::FORBIDDEN,
+                "address not permitted to use the geocoder",
+            )
+            .into_response(),
+            Self::Exhausted(report, now) => {
+                let retry_after = (report.resets_at - now).num_seconds().max(1);
+
+                (
+                    [(header::RETRY_AFTER, retry_after.to_string())],
+                    ErrorEnvelope::new(
+                        StatusCode::TOO_MANY_REQUESTS,
+                        "daily geocoder quota exhausted",
+                    )
+                    .with_details(Some(json!(report))),
+                )
+                    .into_response()
+            }
+            Self::Upstream(error) => error.into_response(),
+        }
+    }
+}
+
+// </editor-fold desc="// GeocoderQuota ...">
+
 /// Complete [Day 21: Challenge](https://console.shuttle.rs/cch/challenge/21#:~:text=⭐)
-#[tracing::instrument(ret, skip(cell), fields(cell_id = cell.0, lat, lng))]
-pub async fn resolve_country_from_s2_cell(cell: S2CellId) -> Result<String, (StatusCode, String)> {
-    //
+///
+/// Requests to the geocoding provider are limited to the
+/// [daily quota](GeocoderQuota) (and its allowlist, if any)
+#[tracing::instrument(ret, skip(cell, state, headers, peer), fields(cell_id = cell.0, lat, lng))]
+pub async fn resolve_country_from_s2_cell(
+    State(state): State<ShuttleAppState>,
+    headers: HeaderMap,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    cell: S2CellId,
+) -> Result<String, GeocodeRejection> {
+    if !state.geocoder.permits(
+        state
+            .proxies
+            .client_ip(&headers, peer.as_ref().map(|ConnectInfo(peer)| peer)),
+    ) {
+        return Err(GeocodeRejection::Forbidden);
+    }
+
+    let now = state.clock.now();
+
+    state
+        .geocoder
+        .consume(now)
+        .map_err(|report| GeocodeRejection::Exhausted(report, now))?;
 
     let point: LatLng = cell.into();
 
     let (lat, lng) = (point.lat.deg(), point.lng.deg());
 
-    tracing::Span::current().record("lat", format!("{lat:.7}"));
-    tracing::Span::current().record("lng", format!("{lng:.7}"));
-
-    reqwest::get(format!(
-        "https://geocode.maps.co/reverse?lat={lat}&lon={lng}"
-    ))
-    .await
-    .map_err(|error| {
-        (
-            error.status().unwrap_or(StatusCode::UNPROCESSABLE_ENTITY),
-            format!("{error:?}"),
-        )
-    })?
-    .json::<GeoCodeResponse>()
-    .await
-    .map_err(|error| (StatusCode::UNPROCESSABLE_ENTITY, format!("{error:?}")))?
-    .country()
-    .map(|country| country.name().replace(" Darussalam", ""))
+    record_fields!("lat" => format!("{lat:.7}"), "lng" => format!("{lng:.7}"));
+
+    state
+        .geocoding
+        .reverse(lat, lng)
+        .await
+        .and_then(|response| response.country())
+        .map(|country| state.country_names.normalize(country))
+        .map_err(GeocodeRejection::Upstream)
+}
+
+/// Report the geocoding provider's usage against today's quota
+#[tracing::instrument(ret, skip(state))]
+pub async fn get_geocoder_quota(State(state): State<ShuttleAppState>) -> Json<QuotaReport> {
+    Json(state.geocoder.report(state.clock.now()))
+}
+
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 21](https://console.shuttle.rs/cch/challenge/21) solutions
+#[derive(Debug)]
+pub struct Day21;
+
+impl DayModule for Day21 {
+    fn day() -> i8 {
+        21
+    }
+
+    fn name() -> &'static str {
+        "S2 Geocoding"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                21,
+                GET routes::day21::COORDS,
+                resolve_s2_cell_center,
+                "Locate an S2 cell's center"
+            ))
+            .route(day_route!(
+                21,
+                GET routes::day21::COUNTRY,
+                resolve_country_from_s2_cell,
+                "Locate an S2 cell's country"
+            ))
+            .route(day_route!(
+                21,
+                GET routes::day21::QUOTA,
+                get_geocoder_quota,
+                "Report the geocoder's quota usage"
+            ))
+    }
 }
 
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -178,7 +726,7 @@ mod tests {
 
     // Standard Library Imports
     use core::{cmp::PartialEq, fmt::Debug, ops::BitOr, str::FromStr};
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
     // Third-Party Imports
     use axum::{
@@ -190,13 +738,318 @@ mod tests {
         },
         routing::Router,
     };
+    use isocountry::CountryCode;
     use once_cell::sync::Lazy;
     use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
     use rstest::{fixture, rstest};
     use serde_json::{error::Error as SerdeJsonError, Value};
+    use shuttle_secrets::SecretStore;
     use shuttle_shared_db::Postgres as ShuttleDB;
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
-    use crate::utils::{service, TestService};
+    use super::{
+        CountryNames, FixtureGeocoder, Geocoder, GeocoderQuota, QuotaReport, RecordingGeocoder,
+        BUNDLED_COUNTRY_NAMES, GEOCODE_FIXTURES_DIR,
+    };
+    use crate::clock::FakeClock;
+    use crate::router;
+    use crate::routes;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{service, state, TestService};
+
+    /// Test that `resolve_s2_cell_center` satisfies the conditions of
+    /// [CCH 2023 Challenge 21](https://console.shuttle.rs/cch/challenge/21)
+    /// and that both endpoints reject malformed cell ids
+    #[rstest]
+    #[case::challenge_example_one(
+        routes::day21::coords("0100111110010011000110011001010101011111000010100011110001011011"),
+        StatusCode::OK,
+        "83°39'54.324''N 30°37'40.584''W"
+    )]
+    #[case::challenge_example_two(
+        routes::day21::coords("0010000111110000011111100000111010111100000100111101111011000101"),
+        StatusCode::OK,
+        "18°54'55.944''S 47°31'17.976''E"
+    )]
+    #[case::non_binary_coords(routes::day21::coords("0123"), StatusCode::UNPROCESSABLE_ENTITY, "")]
+    #[case::non_binary_country(
+        routes::day21::country("not-binary"),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ""
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_challenge_twenty_one(
+        service: TestService,
+        #[case] url: String,
+        #[case] expected_status: StatusCode,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let response = service.resolve(url.as_str()).await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status.is_success() {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+
+            assert_eq!(expected_content, String::from_utf8_lossy(content.as_ref()));
+        }
+
+        Ok(())
+    }
+
+    /// Test that `resolve_s2_cell_center` reports the cell's
+    /// center as a GeoJSON `Point` when asked to
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_coords_geojson(service: TestService) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                routes::day21::coords_geojson(
+                    "0100111110010011000110011001010101011111000010100011110001011011",
+                )
+                .as_str(),
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            Some("application/geo+json"),
+            response
+                .headers()
+                .get(headers::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+        );
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+        let feature = serde_json::from_slice::<Value>(&content)?;
+        let coordinates = feature["geometry"]["coordinates"].as_array().unwrap();
+
+        assert_eq!("Feature", feature["type"]);
+        assert_eq!("Point", feature["geometry"]["type"]);
+        assert_eq!(
+            ("-30.6279399", "83.6650900"),
+            (
+                format!("{:.7}", coordinates[0].as_f64().unwrap()).as_str(),
+                format!("{:.7}", coordinates[1].as_f64().unwrap()).as_str(),
+            )
+        );
+        assert_eq!(
+            "83°39'54.324''N 30°37'40.584''W",
+            feature["properties"]["dms"]
+        );
+        assert_eq!(5733954879908101211u64, feature["properties"]["cell_id"]);
+
+        Ok(())
+    }
+
+    /// Test that the geocoder's quota is spent, exhausted,
+    /// reset daily, and restored from the persistent store
+    #[rstest]
+    #[test_log::test]
+    fn test_geocoder_quota() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let store = shuttle_persist::PersistInstance::new(directory.path().to_path_buf())?;
+        let quota = GeocoderQuota::new(2, None, Some(store.clone().into()));
+        let now = "2023-12-21T23:00:00Z".parse::<chrono::DateTime<chrono::Utc>>()?;
+
+        assert_eq!(1, quota.consume(now).unwrap().remaining);
+        assert_eq!(0, quota.consume(now).unwrap().remaining);
+
+        let exhausted = quota.consume(now).unwrap_err();
+
+        assert_eq!((2, 0), (exhausted.used, exhausted.remaining));
+        assert_eq!(
+            "2023-12-22T00:00:00Z".parse::<chrono::DateTime<chrono::Utc>>()?,
+            exhausted.resets_at
+        );
+
+        let restored = GeocoderQuota::new(2, None, Some(store.into()));
+
+        assert_eq!(2, restored.report(now).used);
+        assert_eq!(0, restored.report(now + chrono::Duration::hours(1)).used);
+        assert!(restored.consume(now + chrono::Duration::hours(1)).is_ok());
+
+        Ok(())
+    }
+
+    /// Test that the geocoder's allowlist is read from the
+    /// `GEOCODER_ALLOWLIST` secret (ignoring malformed entries)
+    #[rstest]
+    #[case::unset(None, "203.0.113.7", true)]
+    #[case::listed(Some("203.0.113.7, sleigh, 2001:db8::1"), "2001:db8::1", true)]
+    #[case::unlisted(Some("203.0.113.7"), "198.51.100.2", false)]
+    #[test_log::test]
+    fn test_geocoder_allowlist(
+        #[case] allowlist: Option<&str>,
+        #[case] ip: &str,
+        #[case] expected: bool,
+    ) {
+        let secrets = shuttle_secrets::SecretStore::new(std::collections::BTreeMap::from_iter(
+            allowlist.map(|value| ("GEOCODER_ALLOWLIST".to_string(), value.to_string().into())),
+        ));
+        let quota = GeocoderQuota::from_secrets(&secrets, None);
+
+        assert_eq!(expected, quota.permits(ip.parse().ok()));
+        assert_eq!(allowlist.is_none(), quota.permits(None));
+    }
+
+    /// Test that country resolution is refused (without calling
+    /// the geocoding provider) once the quota is exhausted
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_geocoder_quota_exhausted(state: ShuttleAppState) -> anyhow::Result<()> {
+        let clock = std::sync::Arc::new(FakeClock::new("2023-12-21T23:59:30Z".parse()?));
+        let state = ShuttleAppState {
+            geocoder: std::sync::Arc::new(GeocoderQuota::new(1, None, None)),
+            clock: clock.clone(),
+            ..state
+        };
+
+        assert!(state.geocoder.consume(state.clock.now()).is_ok());
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(
+                routes::day21::country(
+                    "0100111110010011000110011001010101011111000010100011110001011011",
+                )
+                .as_str(),
+            )
+            .await?;
+
+        assert_eq!(StatusCode::TOO_MANY_REQUESTS, response.status());
+        assert_eq!("30", response.headers()[headers::RETRY_AFTER]);
+
+        let response = TestService::from(router(state))
+            .resolve(routes::day21::quota())
+            .await?;
+        let report: QuotaReport =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!((1, 1, 0), (report.limit, report.used, report.remaining));
+
+        Ok(())
+    }
+
+    /// Test that `resolve_country_from_s2_cell` resolves countries
+    /// from recorded responses carrying alpha-2 codes, alpha-3
+    /// codes, or (failing those) country names
+    #[rstest]
+    #[case::alpha_2(
+        "0010000111110000011111100000111010111100000100111101111011000101",
+        StatusCode::OK,
+        "Madagascar"
+    )]
+    #[case::alpha_3(
+        "0100111110010011000110011001010101011111000010100011110001011011",
+        StatusCode::OK,
+        "Greenland"
+    )]
+    #[case::name(
+        "0011001000100010010111111111100100110100111110100000010010000101",
+        StatusCode::OK,
+        "Brunei"
+    )]
+    #[case::unrecorded(
+        "0101001000100010010111111111100100110100111110100000010010000101",
+        StatusCode::BAD_GATEWAY,
+        ""
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_resolve_country_from_fixtures(
+        state: ShuttleAppState,
+        #[case] cell: &str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_country: &str,
+    ) -> anyhow::Result<()> {
+        let state = ShuttleAppState {
+            geocoding: std::sync::Arc::new(FixtureGeocoder::new(GEOCODE_FIXTURES_DIR)),
+            ..state
+        };
+
+        let response = TestService::from(router(state))
+            .resolve(routes::day21::country(cell).as_str())
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status.is_success() {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+
+            assert_eq!(expected_country, String::from_utf8_lossy(content.as_ref()));
+        }
+
+        Ok(())
+    }
+
+    /// Test that recorded responses can be answered from as fixtures
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_recording_geocoder() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let recorder = RecordingGeocoder::new(
+            std::sync::Arc::new(FixtureGeocoder::new(GEOCODE_FIXTURES_DIR)),
+            directory.path(),
+        );
+
+        let recorded = recorder.reverse(-18.91554, 47.52166).await;
+        let replayed = FixtureGeocoder::new(directory.path())
+            .reverse(-18.91554, 47.52166)
+            .await;
+
+        assert_eq!(
+            serde_json::to_value(recorded.expect("the response is recorded"))?,
+            serde_json::to_value(replayed.expect("the recording is replayed"))?
+        );
+        assert!(FixtureGeocoder::new(directory.path())
+            .reverse(0.0, 0.0)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    /// Test that country names are normalized per the
+    /// bundled table, unless overridden by secret
+    #[rstest]
+    #[case::unlisted(None, "MDG", "Madagascar")]
+    #[case::bundled(None, "BRN", "Brunei")]
+    #[case::bundled_comma(None, "TWN", "Taiwan")]
+    #[case::overridden(
+        Some(r#"{"Brunei Darussalam": "Negara Brunei"}"#),
+        "BRN",
+        "Negara Brunei"
+    )]
+    #[case::invalid(Some("Brunei Darussalam=Brunei"), "BRN", "Brunei")]
+    #[test_log::test]
+    fn test_country_names(
+        #[case] overrides: Option<&str>,
+        #[case] alpha3: &str,
+        #[case] expected: &str,
+    ) -> anyhow::Result<()> {
+        let names = CountryNames::from_secrets(&SecretStore::new(BTreeMap::from_iter(
+            overrides.map(|value| {
+                (
+                    String::from("COUNTRY_NAME_OVERRIDES"),
+                    value.to_string().into(),
+                )
+            }),
+        )));
+
+        assert_eq!(expected, names.normalize(CountryCode::for_alpha3(alpha3)?));
+
+        Ok(())
+    }
+
+    /// Test that every entry in the bundled table names a known country
+    #[rstest]
+    #[test_log::test]
+    fn test_bundled_country_names_are_known() {
+        for name in BUNDLED_COUNTRY_NAMES.keys() {
+            assert!(
+                CountryCode::iter().any(|country| country.name() == name),
+                "unknown country: {name}"
+            );
+        }
+    }
 }
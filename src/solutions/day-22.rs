@@ -8,22 +8,151 @@ use core::{
     marker::PhantomData,
     ops::{Add, AddAssign, BitXor, Div, Mul, Sub},
 };
-use std::{collections::VecDeque, str::FromStr};
+use std::{
+    collections::{HashMap, VecDeque},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
 
 // Third-Party Imports
 use axum::{
-    extract::{FromRef, FromRequest},
-    http::StatusCode,
+    extract::{BodyStream, FromRef, FromRequest, Json, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{self, Router},
 };
+use futures_util::StreamExt;
+use geojson::{Feature, FeatureCollection, Geometry, JsonObject, Value as GeoJsonValue};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
+// Crate-Level Imports
+use crate::{
+    errors::{AppError, ErrorEnvelope},
+    record_fields, routes,
+    solutions::{day_21::GEOJSON_CONTENT_TYPE, DayModule, DayRoutes},
+    state::ShuttleAppState,
+    telemetry::fields,
+    utils::{self, TextBody},
+    validation::{Validate, ValidationErrors},
+};
+
+/// The name of the response header reporting whether
+/// a star chart's analysis was served from the cache
+pub const CACHE_STATUS_HEADER: &str = "x-cache";
+
 // <editor-fold desc="// Portal ...">
 
 type Portal = (usize, usize);
 
 // </editor-fold desc="// Portal ...">
 
+// <editor-fold desc="// IntegerChecksum ...">
+
+/// The size (in bytes) of the chunks in which
+/// streamed integer dumps are processed
+pub const CHECKSUM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A token that couldn't be parsed as an integer
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ParseFailure {
+    /// the token's byte offset in the dump
+    offset: u64,
+    /// the token itself (truncated, if overlong)
+    token: String,
+}
+
+/// The running xor-fold of a (streamed) dump of
+/// whitespace-separated integers
+#[derive(Debug, Default, Serialize)]
+pub struct IntegerChecksum {
+    /// the xor-fold of every parsed integer
+    xor: u64,
+    /// the number of parsed integers
+    count: u64,
+    /// the number of unparseable tokens
+    error_count: u64,
+    /// the first [`IntegerChecksum::MAX_REPORTED_ERRORS`]
+    /// unparseable tokens
+    errors: Vec<ParseFailure>,
+    /// the (possibly incomplete) token straddling chunks
+    #[serde(skip)]
+    partial: Vec<u8>,
+    /// the byte offset at which `partial` started
+    #[serde(skip)]
+    partial_offset: u64,
+    /// whether `partial` was truncated
+    #[serde(skip)]
+    overlong: bool,
+    /// the number of bytes processed so far
+    #[serde(skip)]
+    position: u64,
+}
+
+impl IntegerChecksum {
+    /// The maximum number of reported unparseable tokens
+    const MAX_REPORTED_ERRORS: usize = 100;
+
+    /// The maximum retained length of a single token
+    /// (comfortably longer than any `u64`)
+    const MAX_TOKEN_LENGTH: usize = 32;
+
+    /// Process the supplied chunk of the dump, retaining
+    /// any token that may continue in the next chunk
+    pub fn feed(&mut self, chunk: &[u8]) {
+        for byte in chunk {
+            if byte.is_ascii_whitespace() {
+                self._consume_partial();
+            } else if self.partial.is_empty() && !self.overlong {
+                self.partial_offset = self.position;
+                self.partial.push(*byte);
+            } else if self.partial.len() < Self::MAX_TOKEN_LENGTH {
+                self.partial.push(*byte);
+            } else {
+                self.overlong = true;
+            }
+
+            self.position += 1;
+        }
+    }
+
+    /// Process any token left over at the end of the dump
+    pub fn finish(mut self) -> Self {
+        self._consume_partial();
+        self
+    }
+
+    fn _consume_partial(&mut self) {
+        if self.partial.is_empty() {
+            return;
+        }
+
+        let parsed = core::str::from_utf8(&self.partial)
+            .ok()
+            .filter(|_| !self.overlong)
+            .and_then(|token| token.parse::<u64>().ok());
+
+        if let Some(value) = parsed {
+            self.xor ^= value;
+            self.count += 1;
+        } else {
+            self.error_count += 1;
+
+            if self.errors.len() < Self::MAX_REPORTED_ERRORS {
+                self.errors.push(ParseFailure {
+                    offset: self.partial_offset,
+                    token: String::from_utf8_lossy(&self.partial).into_owned(),
+                });
+            }
+        }
+
+        self.partial.clear();
+        self.overlong = false;
+    }
+}
+
+// </editor-fold desc="// IntegerChecksum ...">
+
 // <editor-fold desc="// Star ...">
 
 #[derive(Eq, Ord, Copy, Hash, Clone, Debug, PartialEq, PartialOrd)]
@@ -144,7 +273,116 @@ impl FromStr for StarPortalChart {
 }
 
 impl StarPortalChart {
-    fn shortest_path(&self) -> Result<Vec<Star>, (StatusCode, String)> {
+    /// Parse a chart from "typed" CSV records, each of
+    /// which is either a star (`star,x,y,z`) or a portal
+    /// (`portal,origin,destination`), e.g.:
+    ///
+    /// ```text
+    /// # stars are numbered in the order they appear
+    /// star,0,1,0
+    /// star,-2,2,3
+    /// portal,0,1
+    /// ```
+    pub fn from_csv(text: &str) -> Result<Self, (StatusCode, String)> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .comment(Some(b'#'))
+            .trim(csv::Trim::All)
+            .from_reader(text.as_bytes());
+
+        let (mut stars, mut portals) = (Vec::<Star>::new(), Vec::<Portal>::new());
+
+        for record in reader.records() {
+            let record =
+                record.map_err(|error| (StatusCode::UNPROCESSABLE_ENTITY, error.to_string()))?;
+            let line = record.position().map_or(0, csv::Position::line);
+            let malformed = |expected: &str| {
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!(
+                        "line {line}: expected {expected}, got: {:?}",
+                        record.iter().join(",")
+                    ),
+                )
+            };
+
+            match record.get(0).map(str::to_ascii_lowercase).as_deref() {
+                Some("star") => {
+                    let point = |index: usize| record.get(index)?.parse::<i32>().ok();
+
+                    match (record.len(), point(1), point(2), point(3)) {
+                        (4, Some(x), Some(y), Some(z)) => stars.push(Star(x, y, z)),
+                        _ => return Err(malformed("star,<x>,<y>,<z>")),
+                    }
+                }
+                Some("portal") => {
+                    let id = |index: usize| record.get(index)?.parse::<usize>().ok();
+
+                    match (record.len(), id(1), id(2)) {
+                        (3, Some(origin), Some(destination)) => portals.push((origin, destination)),
+                        _ => return Err(malformed("portal,<origin>,<destination>")),
+                    }
+                }
+                _ => return Err(malformed(r#"a "star" or "portal" record"#)),
+            }
+        }
+
+        Ok(Self { stars, portals })
+    }
+}
+
+impl Validate for StarPortalChart {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        for (index, (origin, destination)) in self.portals.iter().enumerate() {
+            for star in [origin, destination] {
+                if self.stars.len() <= *star {
+                    errors.add(
+                        format!("portals[{index}]"),
+                        format!(
+                            "star {star} is out of range (the chart has {} stars)",
+                            self.stars.len()
+                        ),
+                    );
+                }
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+impl StarPortalChart {
+    /// Build the chart's adjacency list, treating portals
+    /// as two-way unless `directed`, and skipping any
+    /// parallel (i.e. duplicate) portals
+    fn adjacency(&self, directed: bool) -> Vec<Vec<usize>> {
+        let mut adjacent = vec![Vec::<usize>::new(); self.stars.len()];
+
+        let edges = self.portals.iter().flat_map(|(origin, destination)| {
+            [
+                Some((*origin, *destination)),
+                (!directed).then_some((*destination, *origin)),
+            ]
+            .into_iter()
+            .flatten()
+        });
+
+        for (origin, destination) in edges {
+            if !adjacent[origin].contains(&destination) {
+                adjacent[origin].push(destination);
+            }
+        }
+
+        adjacent
+    }
+
+    /// Find the shortest path (by portal count) from the
+    /// chart's first star to its last, treating portals
+    /// as two-way unless `directed`
+    fn shortest_path(&self, directed: bool) -> Result<Vec<Star>, (StatusCode, String)> {
         if self.stars.is_empty() || self.portals.is_empty() {
             return Err((
                 StatusCode::EXPECTATION_FAILED,
@@ -153,44 +391,231 @@ impl StarPortalChart {
         }
 
         let (start, end) = (0usize, self.stars.len() - 1);
+        let adjacent = self.adjacency(directed);
 
+        let mut previous = vec![None::<usize>; end + 1];
         let mut visited = vec![false; end + 1];
-        let mut routes = vec![vec![]; end + 1];
-        let mut unexplored = VecDeque::new();
+        let mut unexplored = VecDeque::from([start]);
 
         visited[start] = true;
-        routes[start].push(start);
-        unexplored.push_back(start);
 
         while let Some(current) = unexplored.pop_front() {
-            for portal in &self.portals {
-                let (origin, destination) = *portal;
-
-                if origin == current && !visited[destination] {
-                    visited[destination] = true;
-
-                    let mut route_b = routes[origin].clone();
-
-                    route_b.push(destination);
-                    routes[destination] = route_b;
-                    unexplored.push_back(destination);
+            for destination in &adjacent[current] {
+                if !visited[*destination] {
+                    visited[*destination] = true;
+                    previous[*destination] = Some(current);
+                    unexplored.push_back(*destination);
                 }
             }
         }
 
         if !visited[end] {
-            Err((StatusCode::NOT_FOUND, "".to_string()))
-        } else {
-            Ok(routes[end].iter().map(|idx| self.stars[*idx]).collect_vec())
+            return Err((StatusCode::NOT_FOUND, "".to_string()));
         }
+
+        let mut route = vec![end];
+
+        while let Some(star) = route.last().and_then(|star| previous[*star]) {
+            route.push(star);
+        }
+
+        Ok(route.iter().rev().map(|idx| self.stars[*idx]).collect_vec())
     }
 }
 
 // </editor-fold desc="// StarPortalChart ...">
 
+// <editor-fold desc="// StarChartCache ...">
+
+/// A parsed star chart along with its
+/// (possibly non-existent) shortest path
+#[derive(Debug)]
+pub struct StarChartAnalysis {
+    /// the parsed chart
+    chart: StarPortalChart,
+    /// the chart's shortest path from
+    /// its first star to its last
+    path: Result<Vec<Star>, (StatusCode, String)>,
+}
+
+/// The reasons a star chart can't be analyzed
+#[derive(Debug)]
+pub enum StarChartRejection {
+    /// the chart couldn't be parsed
+    Malformed((StatusCode, String)),
+    /// the chart's portals reference non-existent stars
+    Invalid(ValidationErrors),
+    /// the chart has no path from its first star to its last
+    Unroutable((StatusCode, String)),
+}
+
+impl IntoResponse for StarChartRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Malformed(rejection) => rejection.into_response(),
+            Self::Invalid(errors) => errors.into_response(),
+            Self::Unroutable(rejection) => rejection.into_response(),
+        }
+    }
+}
+
+impl StarChartAnalysis {
+    /// Parse, validate, and analyze the supplied star chart
+    fn new(text: &str, format: ChartFormat, directed: bool) -> Result<Self, StarChartRejection> {
+        let chart = match format {
+            ChartFormat::Text => text.parse::<StarPortalChart>(),
+            ChartFormat::Csv => StarPortalChart::from_csv(text),
+        }
+        .map_err(StarChartRejection::Malformed)?;
+
+        chart.validate().map_err(StarChartRejection::Invalid)?;
+
+        let path = chart.shortest_path(directed);
+
+        Ok(Self { chart, path })
+    }
+}
+
+/// The formats in which star charts are accepted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChartFormat {
+    /// the challenge's whitespace-separated format
+    Text,
+    /// "typed" CSV records (see [`StarPortalChart::from_csv`])
+    Csv,
+}
+
+impl ChartFormat {
+    /// Determine a chart's format from its request's `Content-Type`
+    pub fn of(headers: &HeaderMap) -> Self {
+        let is_csv = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<mime::Mime>().ok())
+            .is_some_and(|mime| mime.essence_str() == mime::TEXT_CSV.essence_str());
+
+        if is_csv {
+            Self::Csv
+        } else {
+            Self::Text
+        }
+    }
+}
+
+/// Query parameters specifying how a
+/// star chart's portals may be traversed
+#[derive(Debug, Deserialize)]
+pub struct ChartTraversal {
+    /// whether portals are one-way
+    #[serde(default = "ChartTraversal::default_directed")]
+    directed: bool,
+}
+
+impl ChartTraversal {
+    fn default_directed() -> bool {
+        true
+    }
+}
+
+/// Retained analyses (by key), and their
+/// keys' recency order (least recent first)
+type CachedAnalyses = (HashMap<String, Arc<StarChartAnalysis>>, VecDeque<String>);
+
+/// A response carrying its [`CACHE_STATUS_HEADER`]
+type CacheAwareResponse = ([(&'static str, &'static str); 1], String);
+
+/// A bounded, least-recently-used cache of star chart
+/// analyses, keyed by the SHA-256 digest of the chart
+#[derive(Debug)]
+pub struct StarChartCache {
+    /// the maximum number of retained analyses
+    capacity: usize,
+    /// the retained analyses
+    entries: Mutex<CachedAnalyses>,
+}
+
+impl Default for StarChartCache {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+impl StarChartCache {
+    /// The default maximum number of retained analyses
+    const DEFAULT_CAPACITY: usize = 32;
+
+    /// Create an empty cache retaining at
+    /// most `capacity` analyses
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new((HashMap::new(), VecDeque::new())),
+        }
+    }
+
+    /// Get the supplied chart's analysis, and whether it was
+    /// served from the cache, analyzing (and retaining) it
+    /// if it isn't already cached
+    pub fn analyze(
+        &self,
+        text: &str,
+        format: ChartFormat,
+        directed: bool,
+    ) -> Result<(Arc<StarChartAnalysis>, bool), StarChartRejection> {
+        let key = format!("{}:{format:?}:{directed}", sha256::digest(text));
+
+        if let Some(analysis) = self.get(&key) {
+            return Ok((analysis, true));
+        }
+
+        let analysis = Arc::new(StarChartAnalysis::new(text, format, directed)?);
+        let (analyses, recency) = &mut *self.entries.lock().unwrap();
+
+        if analyses.insert(key.clone(), analysis.clone()).is_none() {
+            recency.push_back(key);
+        }
+
+        while self.capacity < recency.len() {
+            if let Some(evicted) = recency.pop_front() {
+                analyses.remove(&evicted);
+            }
+        }
+
+        Ok((analysis, false))
+    }
+
+    /// Evict every retained analysis, returning the number evicted
+    pub fn clear(&self) -> usize {
+        let (analyses, recency) = &mut *self.entries.lock().unwrap();
+        let evicted = analyses.len();
+
+        analyses.clear();
+        recency.clear();
+
+        evicted
+    }
+
+    /// Get the analysis cached under `key` (if any),
+    /// marking it as the most recently used
+    fn get(&self, key: &str) -> Option<Arc<StarChartAnalysis>> {
+        let (analyses, recency) = &mut *self.entries.lock().unwrap();
+        let analysis = analyses.get(key)?.clone();
+
+        if let Some(position) = recency.iter().position(|cached| cached == key) {
+            if let Some(key) = recency.remove(position) {
+                recency.push_back(key);
+            }
+        }
+
+        Some(analysis)
+    }
+}
+
+// </editor-fold desc="// StarChartCache ...">
+
 /// Complete [Day 22: Task](https://console.shuttle.rs/cch/challenge/22#:~:text=⭐️)
 #[tracing::instrument(skip_all, fields(int.count, loner))]
-pub async fn locate_lonely_int(text: String) -> Result<String, (StatusCode, String)> {
+pub async fn locate_lonely_int(TextBody(text): TextBody) -> Result<String, AppError> {
     let mut ints = 0u64;
 
     let loner: usize = text
@@ -199,25 +624,87 @@ pub async fn locate_lonely_int(text: String) -> Result<String, (StatusCode, Stri
         .filter_map(|line| line.parse::<u64>().ok())
         .fold(0u64, BitXor::bitxor)
         .try_into()
-        .map_err(|error| (StatusCode::UNPROCESSABLE_ENTITY, format!("{error:?}")))?;
+        .map_err(|error| {
+            ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, format!("{error:?}"))
+        })?;
 
-    tracing::Span::current().record("int.count", ints);
-    tracing::Span::current().record("loner", loner);
+    record_fields!("int.count" => ints, "loner" => loner);
 
     tracing::info!("gift id located");
 
     Ok("🎁".repeat(loner))
 }
 
-/// Complete [Day 22: Task](https://console.shuttle.rs/cch/challenge/22#:~:text=⭐️)
-#[tracing::instrument(ret, skip_all, fields(stars, portals, distance))]
-pub async fn analyze_star_chart(text: String) -> Result<String, (StatusCode, String)> {
-    let chart = text.parse::<StarPortalChart>()?;
+/// Incrementally xor-fold a streamed dump of whitespace-separated
+/// integers, reporting the fold, the number of integers, and the
+/// position of any unparseable tokens without buffering the dump
+#[tracing::instrument(ret, err(Debug), skip_all)]
+pub async fn verify_integer_dump(mut body: BodyStream) -> Result<Json<IntegerChecksum>, AppError> {
+    let mut checksum = IntegerChecksum::default();
+
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|error| ErrorEnvelope::new(StatusCode::BAD_REQUEST, error))?;
+
+        for piece in chunk.chunks(CHECKSUM_CHUNK_SIZE) {
+            checksum.feed(piece);
+        }
+    }
+
+    Ok(Json(checksum.finish()))
+}
+
+/// Get the supplied chart's (cached) shortest path,
+/// recording the analysis on the current span
+async fn chart_path(
+    state: &ShuttleAppState,
+    traversal: &ChartTraversal,
+    headers: &HeaderMap,
+    text: String,
+) -> Result<(Vec<Star>, bool), StarChartRejection> {
+    let (charts, format, directed) = (
+        state.charts.clone(),
+        ChartFormat::of(headers),
+        traversal.directed,
+    );
 
-    tracing::Span::current().record("stars", chart.stars.len());
-    tracing::Span::current().record("portals", chart.portals.len());
+    let (analysis, cached) =
+        utils::run_blocking(move || charts.analyze(&text, format, directed)).await?;
 
-    let path = chart.shortest_path()?;
+    record_fields!(
+        fields::CACHED => cached,
+        "stars" => analysis.chart.stars.len(),
+        "portals" => analysis.chart.portals.len(),
+    );
+
+    let path = analysis
+        .path
+        .clone()
+        .map_err(StarChartRejection::Unroutable)?;
+
+    Ok((path, cached))
+}
+
+/// The cache status header value for a (possibly) cached analysis
+fn cache_status(cached: bool) -> [(&'static str, &'static str); 1] {
+    [(CACHE_STATUS_HEADER, if cached { "HIT" } else { "MISS" })]
+}
+
+/// Complete [Day 22: Task](https://console.shuttle.rs/cch/challenge/22#:~:text=⭐️)
+///
+/// Portals are one-way unless `directed=false` is supplied.
+/// Charts sent as `text/csv` are parsed as [typed CSV
+/// records](StarPortalChart::from_csv) rather than the
+/// challenge's format. Repeated charts are served from the service's
+/// [`StarChartCache`], as reported by the
+/// [`CACHE_STATUS_HEADER`] header
+#[tracing::instrument(ret, skip_all, fields(directed = traversal.directed, {fields::CACHED}, stars, portals, distance))]
+pub async fn analyze_star_chart(
+    State(state): State<ShuttleAppState>,
+    Query(traversal): Query<ChartTraversal>,
+    headers: HeaderMap,
+    TextBody(text): TextBody,
+) -> Result<CacheAwareResponse, StarChartRejection> {
+    let (path, cached) = chart_path(&state, &traversal, &headers, text).await?;
 
     let real_distance = path
         .iter()
@@ -226,11 +713,119 @@ pub async fn analyze_star_chart(text: String) -> Result<String, (StatusCode, Str
             distance + origin.distance(destination)
         });
 
-    tracing::Span::current().record("distance", real_distance);
+    record_fields!("distance" => real_distance);
+
+    Ok((
+        cache_status(cached),
+        format!("{} {:.3}", path.len() - 1, real_distance),
+    ))
+}
+
+/// Report a star chart's shortest path (per [`analyze_star_chart`])
+/// as a GeoJSON `FeatureCollection` holding the path as a
+/// `LineString`, followed by a `Point` for each star along it
+/// (with its step, leg distance, and cumulative distance)
+#[tracing::instrument(skip_all, fields(directed = traversal.directed, {fields::CACHED}, stars, portals))]
+pub async fn star_chart_geojson(
+    State(state): State<ShuttleAppState>,
+    Query(traversal): Query<ChartTraversal>,
+    headers: HeaderMap,
+    TextBody(text): TextBody,
+) -> Result<Response, StarChartRejection> {
+    let (path, cached) = chart_path(&state, &traversal, &headers, text).await?;
+
+    let position = |star: &Star| vec![f64::from(star.0), f64::from(star.1), f64::from(star.2)];
+
+    let mut travelled = 0.0f64;
+    let stops = path
+        .iter()
+        .enumerate()
+        .map(|(step, star)| {
+            let leg = step
+                .checked_sub(1)
+                .map_or(0.0, |previous| path[previous].distance(star));
+
+            travelled += leg;
 
-    Ok(format!("{} {:.3}", path.len() - 1, real_distance))
+            Feature {
+                geometry: Some(Geometry::new(GeoJsonValue::Point(position(star)))),
+                properties: Some(JsonObject::from_iter([
+                    ("step".to_string(), step.into()),
+                    ("leg_distance".to_string(), leg.into()),
+                    ("distance".to_string(), travelled.into()),
+                ])),
+                ..Feature::default()
+            }
+        })
+        .collect_vec();
+
+    let route = Feature {
+        geometry: Some(Geometry::new(GeoJsonValue::LineString(
+            path.iter().map(position).collect(),
+        ))),
+        properties: Some(JsonObject::from_iter([
+            ("portals".to_string(), (path.len() - 1).into()),
+            ("distance".to_string(), travelled.into()),
+        ])),
+        ..Feature::default()
+    };
+
+    let features = [route].into_iter().chain(stops);
+
+    Ok((
+        cache_status(cached),
+        [(header::CONTENT_TYPE, GEOJSON_CONTENT_TYPE)],
+        features.collect::<FeatureCollection>().to_string(),
+    )
+        .into_response())
 }
 
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 22](https://console.shuttle.rs/cch/challenge/22) solutions
+#[derive(Debug)]
+pub struct Day22;
+
+impl DayModule for Day22 {
+    fn day() -> i8 {
+        22
+    }
+
+    fn name() -> &'static str {
+        "Star Charts"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                22,
+                POST routes::day22::INTEGERS,
+                locate_lonely_int,
+                "Find the unpaired integer"
+            ))
+            .route(day_route!(
+                22,
+                POST routes::day22::INTEGERS_VERIFY,
+                verify_integer_dump,
+                "Checksum a streamed integer dump"
+            ))
+            .route(day_route!(
+                22,
+                POST routes::day22::ROCKET,
+                analyze_star_chart,
+                "Route a rocket through a star chart"
+            ))
+            .route(day_route!(
+                22,
+                POST routes::day22::ROCKET_GEOJSON,
+                star_chart_geojson,
+                "Route a rocket as GeoJSON"
+            ))
+    }
+}
+
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -259,5 +854,314 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
-    use crate::utils::{service, TestService};
+    use super::{ChartFormat, IntegerChecksum, StarChartCache, CACHE_STATUS_HEADER};
+    use crate::router;
+    use crate::routes;
+    use crate::state::ShuttleAppState;
+    use crate::utils::{service, state, TestService};
+
+    /// Test that `locate_lonely_int` and `analyze_star_chart` satisfy the
+    /// conditions of [CCH 2023 Challenge 22](https://console.shuttle.rs/cch/challenge/22)
+    #[rstest]
+    #[case::challenge_example(
+        routes::day22::integers(),
+        "888\n77\n888\n22\n77\n",
+        StatusCode::OK,
+        "🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁🎁"
+    )]
+    #[case::bonus_example(
+        routes::day22::rocket(),
+        "5\n0 1 0\n-2 2 3\n3 -3 -5\n1 1 5\n4 3 5\n4\n0 1\n2 4\n3 4\n1 2\n",
+        StatusCode::OK,
+        "3 26.123"
+    )]
+    #[case::unreachable_destination(
+        routes::day22::rocket(),
+        "3\n0 0 0\n1 1 1\n2 2 2\n1\n0 1\n",
+        StatusCode::NOT_FOUND,
+        ""
+    )]
+    #[case::portal_count_mismatch(
+        routes::day22::rocket(),
+        "2\n0 0 0\n1 1 1\n3\n0 1\n",
+        StatusCode::EXPECTATION_FAILED,
+        ""
+    )]
+    #[case::one_way_portals(
+        routes::day22::rocket(),
+        "3\n0 0 0\n1 1 1\n2 2 2\n2\n0 1\n2 1\n",
+        StatusCode::NOT_FOUND,
+        ""
+    )]
+    #[case::two_way_portals(
+        &format!("{}?directed=false", routes::day22::rocket()),
+        "3\n0 0 0\n1 1 1\n2 2 2\n2\n0 1\n2 1\n",
+        StatusCode::OK,
+        "2 3.464"
+    )]
+    #[case::parallel_portals(
+        &format!("{}?directed=false", routes::day22::rocket()),
+        "2\n0 0 0\n3 4 0\n3\n0 1\n1 0\n0 1\n",
+        StatusCode::OK,
+        "1 5.000"
+    )]
+    #[case::out_of_range_portals(
+        routes::day22::rocket(),
+        "2\n0 0 0\n1 1 1\n3\n0 1\n1 5\n7 0\n",
+        StatusCode::UNPROCESSABLE_ENTITY,
+        r#"{"error":{"code":"unprocessable_entity","status":422,"message":"invalid request parameters","details":{"portals[1]":["star 5 is out of range (the chart has 2 stars)"],"portals[2]":["star 7 is out of range (the chart has 2 stars)"]}}}"#
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_challenge_twenty_two(
+        service: TestService,
+        #[case] url: &str,
+        #[case] text: &str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(Request::post(url).body(Body::from(text.to_string()))?)
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status.is_success() || !expected_content.is_empty() {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+
+            assert_eq!(expected_content, String::from_utf8_lossy(content.as_ref()));
+        }
+
+        Ok(())
+    }
+
+    /// Test that `star_chart_geojson` reports the shortest path as
+    /// a `LineString` followed by a `Point` for each star along it
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_star_chart_geojson(service: TestService) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                Request::post(routes::day22::rocket_geojson()).body(Body::from(
+                    "5\n0 1 0\n-2 2 3\n3 -3 -5\n1 1 5\n4 3 5\n4\n0 1\n2 4\n3 4\n1 2\n",
+                ))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            Some("application/geo+json"),
+            response
+                .headers()
+                .get(headers::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+        );
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+        let collection = serde_json::from_slice::<Value>(&content)?;
+        let features = collection["features"].as_array().unwrap();
+
+        assert_eq!("FeatureCollection", collection["type"]);
+        assert_eq!(5, features.len());
+        assert_eq!("LineString", features[0]["geometry"]["type"]);
+        assert_eq!(
+            serde_json::json!([
+                [0.0, 1.0, 0.0],
+                [-2.0, 2.0, 3.0],
+                [3.0, -3.0, -5.0],
+                [4.0, 3.0, 5.0]
+            ]),
+            features[0]["geometry"]["coordinates"]
+        );
+        assert_eq!(3, features[0]["properties"]["portals"]);
+        assert_eq!(
+            "26.123",
+            format!(
+                "{:.3}",
+                features[0]["properties"]["distance"].as_f64().unwrap()
+            )
+        );
+
+        for (step, stop) in features[1..].iter().enumerate() {
+            assert_eq!("Point", stop["geometry"]["type"]);
+            assert_eq!(step, stop["properties"]["step"].as_u64().unwrap() as usize);
+        }
+
+        assert_eq!(0.0, features[1]["properties"]["leg_distance"]);
+        assert_eq!(
+            features[0]["properties"]["distance"],
+            features[4]["properties"]["distance"]
+        );
+
+        Ok(())
+    }
+
+    /// Test that `analyze_star_chart` accepts (and validates)
+    /// charts sent as typed CSV records
+    #[rstest]
+    #[case::bonus_example(
+        "star,0,1,0\nstar,-2,2,3\nstar,3,-3,-5\nstar,1,1,5\nstar,4,3,5\n\
+portal,0,1\nportal,2,4\nportal,3,4\nportal,1,2\n",
+        StatusCode::OK,
+        "3 26.123"
+    )]
+    #[case::interleaved_and_commented(
+        "# the bonus example, out of order\nstar, 0, 1, 0\nportal,0,1\nSTAR,-2,2,3\n\
+portal,1,2\nstar,3,-3,-5\nportal,2,4\nstar,1,1,5\nportal,3,4\nstar,4,3,5\n",
+        StatusCode::OK,
+        "3 26.123"
+    )]
+    #[case::unknown_record("star,0,0,0\nplanet,1,1,1\n", StatusCode::UNPROCESSABLE_ENTITY, "")]
+    #[case::short_star("star,0,0\n", StatusCode::UNPROCESSABLE_ENTITY, "")]
+    #[case::fractional_portal(
+        "star,0,0,0\nstar,1,1,1\nportal,0,0.5\n",
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ""
+    )]
+    #[case::out_of_range_portal(
+        "star,0,0,0\nstar,1,1,1\nportal,0,2\n",
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ""
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_csv_star_charts(
+        service: TestService,
+        #[case] chart: &str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                Request::post(routes::day22::rocket())
+                    .header(headers::CONTENT_TYPE, "text/csv; charset=utf-8")
+                    .body(Body::from(chart.to_string()))?,
+            )
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if expected_status.is_success() {
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+
+            assert_eq!(expected_content, String::from_utf8_lossy(content.as_ref()));
+        }
+
+        Ok(())
+    }
+
+    /// Test that `analyze_star_chart` serves repeated
+    /// charts from the service's cache
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_star_chart_cache_hits(state: ShuttleAppState) -> anyhow::Result<()> {
+        let chart = "5\n0 1 0\n-2 2 3\n3 -3 -5\n1 1 5\n4 3 5\n4\n0 1\n2 4\n3 4\n1 2\n";
+
+        for expected_status in ["MISS", "HIT", "HIT"] {
+            let response = TestService::from(router(state.clone()))
+                .resolve(Request::post(routes::day22::rocket()).body(Body::from(chart))?)
+                .await?;
+
+            assert_eq!(StatusCode::OK, response.status());
+            assert_eq!(expected_status, response.headers()[CACHE_STATUS_HEADER]);
+
+            let content = hyper::body::to_bytes(response.into_body()).await?;
+
+            assert_eq!("3 26.123", String::from_utf8_lossy(content.as_ref()));
+        }
+
+        Ok(())
+    }
+
+    /// Test that `StarChartCache` evicts its least
+    /// recently used analyses once full
+    #[rstest]
+    #[test_log::test]
+    fn test_star_chart_cache_eviction() -> anyhow::Result<()> {
+        let cache = StarChartCache::new(2);
+        let charts = [
+            "2\n0 0 0\n1 1 1\n1\n0 1\n",
+            "2\n0 0 0\n2 2 2\n1\n0 1\n",
+            "2\n0 0 0\n3 3 3\n1\n0 1\n",
+        ];
+
+        let cached = |chart: &str| {
+            cache
+                .analyze(chart, ChartFormat::Text, true)
+                .map(|(_, cached)| cached)
+                .map_err(|rejection| anyhow::anyhow!("{rejection:?}"))
+        };
+
+        assert!(!cached(charts[0])?);
+        assert!(!cached(charts[1])?);
+        assert!(cached(charts[0])?);
+        assert!(!cached(charts[2])?);
+        assert!(cached(charts[0])?);
+        assert!(!cached(charts[1])?);
+
+        Ok(())
+    }
+
+    /// Test that `verify_integer_dump` xor-folds streamed
+    /// integer dumps and reports unparseable tokens
+    #[rstest]
+    #[case::challenge_example(
+        "888\n77\n888\n22\n77\n",
+        r#"{"xor":22,"count":5,"error_count":0,"errors":[]}"#
+    )]
+    #[case::no_trailing_newline("1 2 3", r#"{"xor":0,"count":3,"error_count":0,"errors":[]}"#)]
+    #[case::unparseable_tokens(
+        "5\nfive\n-1\n7",
+        r#"{"xor":2,"count":2,"error_count":2,"errors":[{"offset":2,"token":"five"},{"offset":7,"token":"-1"}]}"#
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_verify_integer_dump(
+        service: TestService,
+        #[case] text: &str,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                Request::post(routes::day22::integers_verify())
+                    .body(Body::from(text.to_string()))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+
+        assert_eq!(
+            serde_json::from_str::<Value>(expected_content)?,
+            serde_json::from_slice::<Value>(content.as_ref())?
+        );
+
+        Ok(())
+    }
+
+    /// Test that `IntegerChecksum` handles tokens
+    /// straddling chunk boundaries
+    #[rstest]
+    #[test_log::test]
+    fn test_integer_checksum_chunking() {
+        let text = "123 4567 89 12345678901234567890123456789012345678 10";
+        let expected = {
+            let mut whole = IntegerChecksum::default();
+            whole.feed(text.as_bytes());
+            whole.finish()
+        };
+
+        for size in 1..=7 {
+            let mut chunked = IntegerChecksum::default();
+
+            for chunk in text.as_bytes().chunks(size) {
+                chunked.feed(chunk);
+            }
+
+            let chunked = chunked.finish();
+
+            assert_eq!(123 ^ 4567 ^ 89 ^ 10, chunked.xor);
+            assert_eq!((expected.xor, expected.count), (chunked.xor, chunked.count));
+            assert_eq!(expected.errors, chunked.errors);
+            assert_eq!(1, chunked.error_count);
+        }
+    }
 }
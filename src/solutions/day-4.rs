@@ -4,17 +4,286 @@
 // Standard Library Imports
 use core::{
     fmt::{Debug, Formatter, Result as FormatResult},
-    ops::Add,
+    str::FromStr,
 };
 use std::collections::HashMap;
 
 // Third-Party Imports
-use axum::extract::Json;
+use axum::{
+    extract::{Json, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use axum_template::{engine::HandlebarsError, TemplateEngine as _};
+use itertools::Itertools;
 use serde::ser::Error;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::{error::Error as DbError, FromRow};
+use tokio::sync::OnceCell;
+use uuid::Uuid;
 
 // Crate-Level Imports
-use crate::utils::is_zero;
+use crate::{
+    errors::{AppError, ErrorEnvelope},
+    record_db, routes,
+    solutions::{DayModule, DayRoutes},
+    state::{ShuttleAppState, TemplateEngine},
+    telemetry::{fields, spans::DB_QUERY},
+    utils::is_zero,
+};
+
+/// Whether the contest schema has been ensured
+static CONTEST_SCHEMA: OnceCell<()> = OnceCell::const_new();
+
+// <editor-fold desc="// Units ...">
+
+/// The system of units a client's reindeer
+/// stats are expressed (and summarized) in
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    /// lengths in centimetres, masses in kilograms
+    #[default]
+    #[serde(alias = "cm", alias = "kg")]
+    Metric,
+    /// lengths in inches, masses in pounds
+    #[serde(alias = "in", alias = "lbs")]
+    Imperial,
+}
+
+impl Units {
+    /// The number of centimetres in an inch
+    const CENTIMETRES_PER_INCH: f64 = 2.54;
+
+    /// The number of kilograms in a pound
+    const KILOGRAMS_PER_POUND: f64 = 0.453_592_37;
+
+    /// Convert the supplied length (in these units)
+    /// to canonical centimetres
+    pub fn to_centimetres(self, length: f64) -> f64 {
+        match self {
+            Self::Metric => length,
+            Self::Imperial => length * Self::CENTIMETRES_PER_INCH,
+        }
+    }
+
+    /// Convert the supplied length (in canonical
+    /// centimetres) to these units
+    pub fn from_centimetres(self, length: f64) -> f64 {
+        match self {
+            Self::Metric => length,
+            Self::Imperial => length / Self::CENTIMETRES_PER_INCH,
+        }
+    }
+
+    /// Render the supplied length (in canonical centimetres)
+    /// in these units, rounded to a single decimal place
+    pub fn render_length(self, length: f64) -> String {
+        let length = (self.from_centimetres(length) * 10.0).round() / 10.0;
+
+        match self {
+            Self::Metric => format!("{length} cm"),
+            Self::Imperial => format!("{length} in"),
+        }
+    }
+
+    /// Convert the supplied mass (in these units)
+    /// to canonical kilograms
+    pub fn to_kilograms(self, mass: f64) -> f64 {
+        match self {
+            Self::Metric => mass,
+            Self::Imperial => mass * Self::KILOGRAMS_PER_POUND,
+        }
+    }
+
+    /// Convert the supplied mass (in canonical
+    /// kilograms) to these units
+    pub fn from_kilograms(self, mass: f64) -> f64 {
+        match self {
+            Self::Metric => mass,
+            Self::Imperial => mass / Self::KILOGRAMS_PER_POUND,
+        }
+    }
+
+    /// Render the supplied mass (in canonical kilograms)
+    /// in these units, rounded to a single decimal place
+    pub fn render_mass(self, mass: f64) -> String {
+        let mass = (self.from_kilograms(mass) * 10.0).round() / 10.0;
+
+        match self {
+            Self::Metric => format!("{mass} kg"),
+            Self::Imperial => format!("{mass} lbs"),
+        }
+    }
+}
+
+/// Query parameters specifying the system
+/// of units used by a day 4 endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct UnitsQuery {
+    /// the client's system of units
+    #[serde(default)]
+    pub units: Units,
+}
+
+// </editor-fold desc="// Units ...">
+
+// <editor-fold desc="// StrengthWeights ...">
+
+/// The name of the response header carrying the
+/// formula used to calculate a weighted strength
+pub const STRENGTH_FORMULA_HEADER: &str = "x-strength-formula";
+
+/// A (numeric) reindeer stat that may
+/// contribute to a weighted strength
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum WeightedStat {
+    /// [`ReindeerStats::strength`]
+    Strength,
+    /// [`ReindeerStats::speed`]
+    Speed,
+    /// [`ReindeerStats::height`]
+    Height,
+    /// [`ReindeerStats::antler_width`]
+    AntlerWidth,
+    /// [`ReindeerStats::snow_magic_power`]
+    SnowMagicPower,
+    /// [`ReindeerStats::candies_eaten_yesterday`]
+    CandiesEatenYesterday,
+}
+
+impl FromStr for WeightedStat {
+    type Err = (StatusCode, String);
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "strength" => Ok(Self::Strength),
+            "speed" => Ok(Self::Speed),
+            "height" => Ok(Self::Height),
+            "antler_width" => Ok(Self::AntlerWidth),
+            "snow_magic_power" => Ok(Self::SnowMagicPower),
+            "candies" | "candies_eaten_yesterday" => Ok(Self::CandiesEatenYesterday),
+            other => Err((
+                StatusCode::BAD_REQUEST,
+                format!("unknown reindeer stat: {other:?}"),
+            )),
+        }
+    }
+}
+
+impl WeightedStat {
+    /// The stat's canonical name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Strength => "strength",
+            Self::Speed => "speed",
+            Self::Height => "height",
+            Self::AntlerWidth => "antler_width",
+            Self::SnowMagicPower => "snow_magic_power",
+            Self::CandiesEatenYesterday => "candies_eaten_yesterday",
+        }
+    }
+
+    /// The supplied reindeer's value for this stat
+    pub fn of(self, reindeer: &ReindeerStats) -> f64 {
+        match self {
+            Self::Strength => reindeer.strength as f64,
+            Self::Speed => reindeer.speed,
+            Self::Height => reindeer.height,
+            Self::AntlerWidth => reindeer.antler_width,
+            Self::SnowMagicPower => reindeer.snow_magic_power as f64,
+            Self::CandiesEatenYesterday => reindeer.candies_eaten_yesterday as f64,
+        }
+    }
+}
+
+/// The per-stat weights of a composite strength
+/// score (e.g. `speed:0.5,strength:2`)
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrengthWeights(pub Vec<(WeightedStat, f64)>);
+
+impl FromStr for StrengthWeights {
+    type Err = (StatusCode, String);
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = |message: String| (StatusCode::BAD_REQUEST, message);
+        let mut weights = Vec::<(WeightedStat, f64)>::new();
+
+        for term in text
+            .split(',')
+            .map(str::trim)
+            .filter(|term| !term.is_empty())
+        {
+            let (name, weight) = term
+                .split_once(':')
+                .ok_or_else(|| invalid(format!("expected `stat:weight`, got: {term:?}")))?;
+
+            let stat = name.trim().parse::<WeightedStat>()?;
+            let weight = weight
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|weight| weight.is_finite())
+                .ok_or_else(|| {
+                    invalid(format!("invalid weight for {}: {weight:?}", stat.name()))
+                })?;
+
+            if weights.iter().any(|(existing, _)| *existing == stat) {
+                return Err(invalid(format!("duplicate weight for {}", stat.name())));
+            }
+
+            weights.push((stat, weight));
+        }
+
+        if weights.is_empty() {
+            return Err(invalid(String::from("no weights supplied")));
+        }
+
+        Ok(Self(weights))
+    }
+}
+
+impl<'de> Deserialize<'de> for StrengthWeights {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(|(_, message): (StatusCode, String)| serde::de::Error::custom(message))
+    }
+}
+
+impl StrengthWeights {
+    /// Calculate the weighted strength of the supplied reindeer
+    pub fn score(&self, stats: &[ReindeerStats]) -> f64 {
+        stats
+            .iter()
+            .flat_map(|reindeer| {
+                self.0
+                    .iter()
+                    .map(move |(stat, weight)| weight * stat.of(reindeer))
+            })
+            .sum()
+    }
+
+    /// The human-readable formula used by [`StrengthWeights::score`]
+    pub fn formula(&self) -> String {
+        self.0
+            .iter()
+            .map(|(stat, weight)| format!("{weight}*{}", stat.name()))
+            .join(" + ")
+    }
+}
+
+/// Query parameters optionally specifying the
+/// weights of a composite strength score
+#[derive(Debug, Default, Deserialize)]
+pub struct StrengthQuery {
+    /// the per-stat weights (if any)
+    #[serde(default)]
+    pub weights: Option<StrengthWeights>,
+}
+
+// </editor-fold desc="// StrengthWeights ...">
 
 // <editor-fold desc="// ReindeerStats ...">
 
@@ -30,14 +299,21 @@ pub struct ReindeerStats {
     #[serde(default)]
     #[serde(skip_serializing_if = "is_zero")]
     pub speed: f64,
-    /// The reindeer's height in {units}
+    /// The reindeer's height (in canonical centimetres,
+    /// once normalized from the client's units)
     #[serde(default)]
     #[serde(skip_serializing_if = "is_zero")]
-    pub height: i64,
-    /// The absolute width of the reindeer's antler's
+    pub height: f64,
+    /// The width of the reindeer's antler's (in canonical
+    /// centimetres, once normalized from the client's units)
     #[serde(default)]
     #[serde(skip_serializing_if = "is_zero")]
-    pub antler_width: i64,
+    pub antler_width: f64,
+    /// The reindeer's weight (in canonical kilograms,
+    /// once normalized from the client's units)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_zero")]
+    pub weight: f64,
     /// The reindeer's absolute "snow magic" power rating
     #[serde(default)]
     #[serde(skip_serializing_if = "is_zero")]
@@ -65,118 +341,379 @@ impl Debug for ReindeerStats {
 }
 
 impl ReindeerStats {
-    /// Summarize the supplied reindeer stats
+    /// Convert the reindeer's lengths and weight from the supplied
+    /// units to canonical centimetres and kilograms (respectively)
     #[must_use]
-    pub fn summarize(stats: &[Self]) -> HashMap<String, String> {
-        let (mut fastest, mut tallest, mut consumer, mut magician) = (
-            Option::<&Self>::None,
-            Option::<&Self>::None,
-            Option::<&Self>::None,
-            Option::<&Self>::None,
-        );
+    pub fn normalized(mut self, units: Units) -> Self {
+        self.height = units.to_centimetres(self.height);
+        self.antler_width = units.to_centimetres(self.antler_width);
+        self.weight = units.to_kilograms(self.weight);
+        self
+    }
+
+    /// Select the winners of each contest
+    /// category from the supplied reindeer stats
+    #[must_use]
+    pub fn summarize(stats: &[Self]) -> ContestWinners<'_> {
+        let mut winners = ContestWinners::default();
 
         for reindeer in stats {
-            if fastest
-                .map(|deer| deer.speed < reindeer.speed)
-                .unwrap_or(true)
+            if winners
+                .fastest
+                .is_none_or(|deer| deer.speed < reindeer.speed)
             {
-                fastest = Some(reindeer);
+                winners.fastest = Some(reindeer);
             }
 
-            if tallest
-                .map(|deer| deer.height < reindeer.height)
-                .unwrap_or(true)
+            if winners
+                .tallest
+                .is_none_or(|deer| deer.height < reindeer.height)
             {
-                tallest = Some(reindeer);
+                winners.tallest = Some(reindeer);
             }
 
-            if consumer
-                .map(|deer| deer.candies_eaten_yesterday < reindeer.candies_eaten_yesterday)
-                .unwrap_or(true)
+            if winners
+                .consumer
+                .is_none_or(|deer| deer.candies_eaten_yesterday < reindeer.candies_eaten_yesterday)
             {
-                consumer = Some(reindeer);
+                winners.consumer = Some(reindeer);
             }
 
-            if magician
-                .map(|deer| deer.snow_magic_power < reindeer.snow_magic_power)
-                .unwrap_or(true)
+            if winners
+                .magician
+                .is_none_or(|deer| deer.snow_magic_power < reindeer.snow_magic_power)
             {
-                magician = Some(reindeer);
+                winners.magician = Some(reindeer);
+            }
+
+            // weights are optional, so only reindeer
+            // that were actually weighed can win
+            if 0.0 < reindeer.weight
+                && winners
+                    .heaviest
+                    .is_none_or(|deer| deer.weight < reindeer.weight)
+            {
+                winners.heaviest = Some(reindeer);
             }
         }
 
-        let summary = [
-            ("fastest", fastest),
-            ("tallest", tallest),
-            ("consumer", consumer),
-            ("magician", magician),
+        winners
+    }
+}
+
+impl ReindeerStats {
+    /// Create the contest's table (if it doesn't already exist)
+    async fn ensure_schema(db: &sqlx::PgPool) -> Result<(), DbError> {
+        CONTEST_SCHEMA
+            .get_or_try_init(|| async {
+                sqlx::query(
+                    r#"CREATE TABLE IF NOT EXISTS reindeer_stats (
+                     id BIGSERIAL PRIMARY KEY,
+                     run_id UUID NOT NULL,
+                     name VARCHAR(128) NOT NULL,
+                     strength BIGINT NOT NULL,
+                     speed DOUBLE PRECISION NOT NULL,
+                     height DOUBLE PRECISION NOT NULL,
+                     antler_width DOUBLE PRECISION NOT NULL,
+                     snow_magic_power BIGINT NOT NULL,
+                     favorite_food VARCHAR(128) NOT NULL,
+                     candies_eaten_yesterday BIGINT NOT NULL
+                   );
+                "#,
+                )
+                .execute(db)
+                .await
+                .map(|_| ())
+            })
+            .await
+            .copied()
+    }
+
+    /// Store the supplied stats as a single contest
+    /// run identified by the supplied id
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
+    pub async fn save_run<'stats, Stats: Iterator<Item = &'stats Self>>(
+        run_id: Uuid,
+        stats: Stats,
+        db: &sqlx::PgPool,
+    ) -> Result<Uuid, DbError> {
+        Self::ensure_schema(db).await?;
+
+        let inserted = sqlx::QueryBuilder::<sqlx::Postgres>::new(
+            "INSERT INTO reindeer_stats (run_id, name, strength, speed, height, \
+             antler_width, snow_magic_power, favorite_food, candies_eaten_yesterday) ",
+        )
+        .push_values(stats, |mut builder, reindeer| {
+            builder
+                .push_bind(run_id)
+                .push_bind(reindeer.name.clone())
+                .push_bind(reindeer.strength)
+                .push_bind(reindeer.speed)
+                .push_bind(reindeer.height)
+                .push_bind(reindeer.antler_width)
+                .push_bind(reindeer.snow_magic_power)
+                .push_bind(reindeer.favorite_food.clone())
+                .push_bind(reindeer.candies_eaten_yesterday);
+        })
+        .build()
+        .execute(db)
+        .await;
+
+        record_db!("insert reindeer stats", inserted).map(|_| run_id)
+    }
+
+    /// Get the best-ever entry in each contest
+    /// category across all stored runs
+    #[tracing::instrument(name = DB_QUERY, skip_all, fields({fields::DB_OPERATION}, {fields::DB_ROWS}, {fields::ERROR}))]
+    pub async fn leaderboard(db: &sqlx::PgPool) -> Result<Vec<LeaderboardEntry>, DbError> {
+        Self::ensure_schema(db).await?;
+
+        let leaders = sqlx::query_as::<_, LeaderboardEntry>(
+            r#"
+            (SELECT 'fastest' AS category, name, speed AS value, run_id
+              FROM reindeer_stats ORDER BY speed DESC, id ASC LIMIT 1)
+            UNION ALL
+            (SELECT 'strongest', name, strength::DOUBLE PRECISION, run_id
+              FROM reindeer_stats ORDER BY strength DESC, id ASC LIMIT 1)
+            UNION ALL
+            (SELECT 'tallest', name, height, run_id
+              FROM reindeer_stats ORDER BY height DESC, id ASC LIMIT 1)
+            UNION ALL
+            (SELECT 'widest', name, antler_width, run_id
+              FROM reindeer_stats ORDER BY antler_width DESC, id ASC LIMIT 1)
+            UNION ALL
+            (SELECT 'magician', name, snow_magic_power::DOUBLE PRECISION, run_id
+              FROM reindeer_stats ORDER BY snow_magic_power DESC, id ASC LIMIT 1)
+            UNION ALL
+            (SELECT 'consumer', name, candies_eaten_yesterday::DOUBLE PRECISION, run_id
+              FROM reindeer_stats ORDER BY candies_eaten_yesterday DESC, id ASC LIMIT 1)
+            "#,
+        )
+        .fetch_all(db)
+        .await;
+
+        record_db!("select leaderboard", leaders)
+    }
+}
+
+// </editor-fold desc="// ReindeerStats ...">
+
+// <editor-fold desc="// ContestWinners ...">
+
+/// The winning reindeer in each contest category
+#[derive(Debug, Default)]
+pub struct ContestWinners<'stats> {
+    /// the reindeer with the highest speed rating
+    pub fastest: Option<&'stats ReindeerStats>,
+    /// the tallest reindeer
+    pub tallest: Option<&'stats ReindeerStats>,
+    /// the reindeer that ate the most candies yesterday
+    pub consumer: Option<&'stats ReindeerStats>,
+    /// the reindeer with the most snow magic power
+    pub magician: Option<&'stats ReindeerStats>,
+    /// the heaviest (weighed) reindeer
+    pub heaviest: Option<&'stats ReindeerStats>,
+}
+
+impl ContestWinners<'_> {
+    /// Render a sentence for each category's winner from the
+    /// `day-4/{category}` templates, with lengths
+    /// and weights in `units`
+    pub fn render_sentences(
+        &self,
+        templates: &TemplateEngine,
+        units: Units,
+    ) -> Result<HashMap<String, String>, HandlebarsError> {
+        [
+            ("fastest", self.fastest),
+            ("tallest", self.tallest),
+            ("consumer", self.consumer),
+            ("magician", self.magician),
+            ("heaviest", self.heaviest),
         ]
         .into_iter()
-        .filter_map(|(key, reindeer)| {
-            if let Some(deer) = reindeer {
-                let key = key.to_string();
-                match key.as_str() {
-                    "consumer" => Some((
-                        key,
-                        format!(
-                            "{} ate lots of candies, but also some {}",
-                            deer.name, deer.favorite_food
-                        ),
-                    )),
-                    "tallest" => Some((
-                        key,
-                        format!(
-                            "{} is standing tall with his {} cm wide antlers",
-                            deer.name, deer.antler_width
-                        ),
-                    )),
-                    "fastest" => Some((
-                        key,
-                        format!(
-                            "Speeding past the finish line with a strength of {} is {}",
-                            deer.strength, deer.name
-                        ),
-                    )),
-                    "magician" => Some((
-                        key,
-                        format!(
-                            "{} could blast you away with a snow magic power of {}",
-                            deer.name, deer.snow_magic_power
-                        ),
-                    )),
-                    _ => None,
-                }
-            } else {
-                None
-            }
+        .filter_map(|(category, winner)| winner.map(|deer| (category, deer)))
+        .map(|(category, deer)| {
+            templates
+                .render(
+                    &format!("day-4/{category}"),
+                    json!({
+                        "name": deer.name,
+                        "strength": deer.strength,
+                        "speed": deer.speed,
+                        "height": units.render_length(deer.height),
+                        "antler_width": units.render_length(deer.antler_width),
+                        "weight": units.render_mass(deer.weight),
+                        "snow_magic_power": deer.snow_magic_power,
+                        "favorite_food": deer.favorite_food,
+                        "candies_eaten_yesterday": deer.candies_eaten_yesterday,
+                    }),
+                )
+                .map(|sentence| (category.to_string(), sentence))
         })
-        .collect::<HashMap<String, String>>();
+        .collect()
+    }
+}
+
+// </editor-fold desc="// ContestWinners ...">
+
+// <editor-fold desc="// LeaderboardEntry ...">
+
+/// The best-ever reindeer in a
+/// given contest category
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    /// the contest category (e.g. "fastest")
+    #[serde(skip_serializing)]
+    pub category: String,
+    /// the reindeer's human-readable name
+    pub name: String,
+    /// the reindeer's rating in the category
+    /// (lengths in canonical centimetres)
+    pub value: f64,
+    /// the id of the run the entry was submitted in
+    pub run_id: Uuid,
+}
 
-        summary
+impl LeaderboardEntry {
+    /// Express the entry's value in the supplied units
+    fn in_units(mut self, units: Units) -> Self {
+        if matches!(self.category.as_str(), "tallest" | "widest") {
+            self.value = units.from_centimetres(self.value);
+        }
+
+        self
     }
 }
 
-// </editor-fold desc="// ReindeerStats ...">
+// </editor-fold desc="// LeaderboardEntry ...">
 
 /// Complete [Day 4: Challenge](https://console.shuttle.rs/cch/challenge/4#:~:text=⭐)
-#[tracing::instrument(ret)]
-pub async fn calculate_reindeer_strength(Json(stats): Json<Vec<ReindeerStats>>) -> Json<i64> {
-    Json(
-        stats
-            .iter()
-            .map(|reindeer| reindeer.strength)
-            .fold(0i64, i64::add),
+///
+/// If `weights` are supplied, a weighted composite score across
+/// the specified stats is returned instead, along with the formula
+/// used to calculate it in the [`STRENGTH_FORMULA_HEADER`] header
+#[tracing::instrument(skip(stats), fields(stats.count = stats.len()))]
+pub async fn calculate_reindeer_strength(
+    Query(StrengthQuery { weights }): Query<StrengthQuery>,
+    Json(stats): Json<Vec<ReindeerStats>>,
+) -> Response {
+    let Some(weights) = weights else {
+        return Json(stats.iter().map(|reindeer| reindeer.strength).sum::<i64>()).into_response();
+    };
+
+    (
+        [(STRENGTH_FORMULA_HEADER, weights.formula())],
+        Json(weights.score(&stats)),
     )
+        .into_response()
 }
 
 /// Complete [Day 4: Bonus](https://console.shuttle.rs/cch/challenge/4#:~:text=🎁)
-#[tracing::instrument(ret)]
+#[tracing::instrument(ret, err(Debug), skip(state))]
 pub async fn summarize_reindeer_contest(
+    State(state): State<ShuttleAppState>,
+    Query(UnitsQuery { units }): Query<UnitsQuery>,
+    Json(stats): Json<Vec<ReindeerStats>>,
+) -> Result<Json<HashMap<String, String>>, AppError> {
+    let stats = stats
+        .into_iter()
+        .map(|reindeer| reindeer.normalized(units))
+        .collect::<Vec<ReindeerStats>>();
+
+    ReindeerStats::summarize(&stats)
+        .render_sentences(&state.templates, units)
+        .map(Json)
+        .map_err(|error| ErrorEnvelope::new(StatusCode::INTERNAL_SERVER_ERROR, error).into())
+}
+
+/// Store a reindeer contest run for later comparison
+#[tracing::instrument(ret, err(Debug), skip_all, fields(stats.count = stats.len()))]
+pub async fn save_reindeer_contest(
+    State(state): State<ShuttleAppState>,
+    Query(UnitsQuery { units }): Query<UnitsQuery>,
     Json(stats): Json<Vec<ReindeerStats>>,
-) -> Json<HashMap<String, String>> {
-    Json(ReindeerStats::summarize(&stats))
+) -> Result<(StatusCode, Json<Value>), AppError> {
+    if stats.is_empty() {
+        return Err(ErrorEnvelope::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "cannot save an empty contest run",
+        )
+        .into());
+    }
+
+    let stats = stats
+        .into_iter()
+        .map(|reindeer| reindeer.normalized(units))
+        .collect::<Vec<ReindeerStats>>();
+
+    let run_id = ReindeerStats::save_run(state.rng.uuid(), stats.iter(), &state.db).await?;
+
+    Ok((StatusCode::CREATED, Json(json!({ "run": run_id }))))
+}
+
+/// Retrieve the best-ever reindeer in each
+/// contest category across all stored runs
+#[tracing::instrument(ret, err(Debug), skip(state))]
+pub async fn get_reindeer_leaderboard(
+    State(state): State<ShuttleAppState>,
+    Query(UnitsQuery { units }): Query<UnitsQuery>,
+) -> Result<Json<HashMap<String, LeaderboardEntry>>, AppError> {
+    Ok(Json(
+        ReindeerStats::leaderboard(&state.db)
+            .await?
+            .into_iter()
+            .map(|entry| (entry.category.clone(), entry.in_units(units)))
+            .collect(),
+    ))
 }
 
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 4](https://console.shuttle.rs/cch/challenge/4) solutions
+#[derive(Debug)]
+pub struct Day4;
+
+impl DayModule for Day4 {
+    fn day() -> i8 {
+        4
+    }
+
+    fn name() -> &'static str {
+        "Reindeer Strength"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                4,
+                POST routes::day4::CONTEST,
+                summarize_reindeer_contest,
+                "Summarize a reindeer contest"
+            ))
+            .route(day_route!(
+                4,
+                POST routes::day4::CONTEST_SAVE,
+                save_reindeer_contest,
+                "Store a reindeer contest run"
+            ))
+            .route(day_route!(
+                4,
+                GET routes::day4::LEADERBOARD,
+                get_reindeer_leaderboard,
+                "Rank the best-ever reindeer"
+            ))
+            .route(day_route!(
+                4,
+                POST routes::day4::STRENGTH,
+                calculate_reindeer_strength,
+                "Sum the reindeer team's strength"
+            ))
+    }
+}
+
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -205,6 +742,8 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
+    use super::{StrengthWeights, Units, WeightedStat, STRENGTH_FORMULA_HEADER};
+    use crate::routes;
     use crate::utils::{service, TestService};
 
     #[derive(Debug)]
@@ -232,7 +771,7 @@ mod tests {
     /// satisfy the conditions of [CCH 2023 Challenge 4](https://console.shuttle.rs/cch/challenge/4)
     #[rstest]
     #[case::challenge_example(
-        "/4/strength",
+        routes::day4::strength(),
         Body::from(
             r#"[
               {"name":"Dasher","strength":5},
@@ -246,7 +785,7 @@ mod tests {
     )]
     #[case::bonus_example(
         // <editor-fold desc="// ...">
-        "/4/contest",
+        routes::day4::contest(),
         Body::from(
             r#"[
               {
@@ -344,16 +883,10 @@ mod tests {
         ),
         StatusCode::OK,
         "{\
-          \"fastest\": \"Dasher absolutely guzzles Rust-Eze\u{2122} \
-          to maintain his speed rating of 19.16\",
-          \"consumer\": \"Dasher is an absolute slut for candy \
-          and consumed 179 pieces of it yesterday\",
-          \"strongest\": \"Dasher is the strongest reindeer around \
-          with an impressive strength rating of 183\",
-          \"tallest\": \"Dasher is standing tall at 154 cm\",
-          \"widest\": \"Dasher is the thiccest boi at 181 cm\",
-          \"magician\": \"Dasher could blast you away with a snow \
-          magic power of 19.16\"\
+          \"fastest\": \"Speeding past the finish line with a strength of 14 is Prancer\",
+          \"tallest\": \"Dancer is standing tall with his 34 cm wide antlers\",
+          \"magician\": \"Prancer could blast you away with a snow magic power of 200\",
+          \"consumer\": \"Dasher ate lots of candies, but also some bring\"\
         }",
         // </editor-fold desc="// ...">
     )]
@@ -407,4 +940,229 @@ mod tests {
 
         Ok(())
     }
+
+    /// Test that `save_reindeer_contest` rejects empty runs
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_save_empty_reindeer_contest(service: TestService) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                Request::post(routes::day4::contest_save())
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from("[]"))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+        Ok(())
+    }
+
+    /// Test that `summarize_reindeer_contest` interprets and
+    /// renders lengths in the client's system of units
+    #[rstest]
+    #[case::default_units(
+        routes::day4::contest(),
+        181.0,
+        "Vixen is standing tall with his 181 cm wide antlers"
+    )]
+    #[case::metric(
+        &format!("{}?units=metric", routes::day4::contest()),
+        181.0,
+        "Vixen is standing tall with his 181 cm wide antlers"
+    )]
+    #[case::imperial(
+        &format!("{}?units=imperial", routes::day4::contest()),
+        71.5,
+        "Vixen is standing tall with his 71.5 in wide antlers"
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_reindeer_contest_units(
+        service: TestService,
+        #[case] url: &str,
+        #[case] antler_width: f64,
+        #[case] expected_tallest: &str,
+    ) -> anyhow::Result<()> {
+        let reindeer = serde_json::json!([
+            {"name": "Vixen", "strength": 5, "height": 60, "antler_width": antler_width},
+            {"name": "Comet", "strength": 6, "height": 40, "antler_width": 12},
+        ]);
+
+        let response = service
+            .resolve(
+                Request::post(url)
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(reindeer.to_string()))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let content = response.into_body().data().await.unwrap()?;
+        let summary = serde_json::from_slice::<HashMap<String, String>>(content.as_ref())?;
+
+        assert_eq!(
+            Some(expected_tallest),
+            summary.get("tallest").map(String::as_str)
+        );
+
+        Ok(())
+    }
+
+    /// Test that `summarize_reindeer_contest` interprets and renders
+    /// weights in the client's system of units, and only names a
+    /// heaviest reindeer when at least one was weighed
+    #[rstest]
+    #[case::unweighed("", None, None)]
+    #[case::metric(
+        "?units=kg",
+        Some(120.0),
+        Some("Vixen is the heaviest of the herd, tipping the scales at 120 kg")
+    )]
+    #[case::imperial(
+        "?units=lbs",
+        Some(264.5),
+        Some("Vixen is the heaviest of the herd, tipping the scales at 264.5 lbs")
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_reindeer_contest_weights(
+        service: TestService,
+        #[case] query: &str,
+        #[case] weight: Option<f64>,
+        #[case] expected_heaviest: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let mut vixen = serde_json::json!({"name": "Vixen", "strength": 5});
+
+        if let Some(weight) = weight {
+            vixen["weight"] = Value::from(weight);
+        }
+
+        let reindeer = serde_json::json!([vixen, {"name": "Comet", "strength": 6}]);
+
+        let response = service
+            .resolve(
+                Request::post(format!("{}{query}", routes::day4::contest()))
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(reindeer.to_string()))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let content = response.into_body().data().await.unwrap()?;
+        let summary = serde_json::from_slice::<HashMap<String, String>>(content.as_ref())?;
+
+        assert_eq!(
+            expected_heaviest,
+            summary.get("heaviest").map(String::as_str)
+        );
+
+        Ok(())
+    }
+
+    /// Test that `Units` round-trips lengths
+    /// through canonical centimetres
+    #[rstest]
+    #[case::metric(Units::Metric, 181.0, 181.0)]
+    #[case::imperial(Units::Imperial, 10.0, 25.4)]
+    #[test_log::test]
+    fn test_unit_conversions(
+        #[case] units: Units,
+        #[case] length: f64,
+        #[case] expected_centimetres: f64,
+    ) {
+        let centimetres = units.to_centimetres(length);
+
+        assert!((expected_centimetres - centimetres).abs() < 1e-9);
+        assert!((length - units.from_centimetres(centimetres)).abs() < 1e-9);
+    }
+
+    /// Test that `Units` round-trips masses
+    /// through canonical kilograms
+    #[rstest]
+    #[case::metric(Units::Metric, 120.0, 120.0)]
+    #[case::imperial(Units::Imperial, 100.0, 45.359237)]
+    #[test_log::test]
+    fn test_mass_conversions(
+        #[case] units: Units,
+        #[case] mass: f64,
+        #[case] expected_kilograms: f64,
+    ) {
+        let kilograms = units.to_kilograms(mass);
+
+        assert!((expected_kilograms - kilograms).abs() < 1e-9);
+        assert!((mass - units.from_kilograms(kilograms)).abs() < 1e-9);
+    }
+
+    /// Test that `calculate_reindeer_strength` calculates
+    /// weighted composite scores when asked to
+    #[rstest]
+    #[case::unweighted(routes::day4::strength(), "11", None)]
+    #[case::weighted(
+        &format!("{}?weights=speed:0.5,strength:2", routes::day4::strength()),
+        "29.5",
+        Some("0.5*speed + 2*strength")
+    )]
+    #[case::candies(
+        &format!("{}?weights=candies:1", routes::day4::strength()),
+        "3.0",
+        Some("1*candies_eaten_yesterday")
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_weighted_reindeer_strength(
+        service: TestService,
+        #[case] url: &str,
+        #[case] expected_content: &str,
+        #[case] expected_formula: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let reindeer = serde_json::json!([
+            {"name": "Vixen", "strength": 5, "speed": 10.0, "cAnD13s_3ATeN-yesT3rdAy": 1},
+            {"name": "Comet", "strength": 6, "speed": 5.0, "cAnD13s_3ATeN-yesT3rdAy": 2},
+        ]);
+
+        let response = service
+            .resolve(
+                Request::post(url)
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(reindeer.to_string()))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            expected_formula,
+            response
+                .headers()
+                .get(STRENGTH_FORMULA_HEADER)
+                .and_then(|value| value.to_str().ok())
+        );
+
+        let content = response.into_body().data().await.unwrap()?;
+
+        assert_eq!(expected_content, String::from_utf8_lossy(content.as_ref()));
+
+        Ok(())
+    }
+
+    /// Test that `StrengthWeights` parses valid
+    /// weights and rejects invalid ones
+    #[rstest]
+    #[case::single("speed:0.5", Some(vec![(WeightedStat::Speed, 0.5)]))]
+    #[case::spaced(" speed : 0.5 , height:-1 ", Some(vec![(WeightedStat::Speed, 0.5), (WeightedStat::Height, -1.0)]))]
+    #[case::empty("", None)]
+    #[case::unknown_stat("agility:1", None)]
+    #[case::missing_weight("speed", None)]
+    #[case::invalid_weight("speed:fast", None)]
+    #[case::infinite_weight("speed:inf", None)]
+    #[case::duplicate_stat("speed:1,speed:2", None)]
+    #[test_log::test]
+    fn test_strength_weights_parsing(
+        #[case] text: &str,
+        #[case] expected: Option<Vec<(WeightedStat, f64)>>,
+    ) {
+        assert_eq!(
+            expected.map(StrengthWeights),
+            text.parse::<StrengthWeights>().ok()
+        );
+    }
 }
@@ -5,23 +5,77 @@
 use core::cmp;
 
 // Third-Party Imports
-use axum::{
-    extract::{Json, Query},
-    http::StatusCode,
-};
+use axum::{extract::Json, http::StatusCode};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+// Crate-Level Imports
+use crate::{
+    errors::{AppError, ErrorEnvelope},
+    routes,
+    solutions::{DayModule, DayRoutes},
+};
+
+// Crate-Level Imports
+use crate::validation::{Validate, ValidatedQuery, ValidationErrors};
+
+// <editor-fold desc="// Pagination ...">
+
+/// Query parameters for paginating (and
+/// optionally splitting) a list of names
+#[derive(Debug, Default, Deserialize)]
 pub struct Pagination {
+    /// The number of names to skip
     #[serde(default)]
-    offset: usize,
+    offset: Option<i64>,
+    /// The maximum number of names to return
     #[serde(default)]
-    limit: Option<usize>,
+    limit: Option<i64>,
+    /// The size of the chunks the returned
+    /// names should be split into (if any)
     #[serde(default)]
-    split: Option<usize>,
+    split: Option<i64>,
+}
+
+impl Validate for Pagination {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if self.offset.is_some_and(|offset| offset < 0) {
+            errors.add("offset", "must not be negative");
+        }
+
+        if self.limit.is_some_and(|limit| limit < 0) {
+            errors.add("limit", "must not be negative");
+        }
+
+        if self.split.is_some_and(|split| split < 1) {
+            errors.add("split", "must be greater than zero (or omitted)");
+        }
+
+        errors.into_result()
+    }
+}
+
+impl Pagination {
+    /// The (validated) number of names to skip
+    pub fn offset(&self) -> usize {
+        self.offset.map_or(0usize, |offset| offset as usize)
+    }
+
+    /// The (validated) maximum number of names to return
+    pub fn limit(&self) -> Option<usize> {
+        self.limit.map(|limit| limit as usize)
+    }
+
+    /// The (validated) chunk size to split names into
+    pub fn split(&self) -> Option<usize> {
+        self.split.map(|split| split as usize)
+    }
 }
 
+// </editor-fold desc="// Pagination ...">
+
 #[derive(Debug, Serialize)]
 #[serde(untagged)]
 pub enum NameList {
@@ -35,42 +89,66 @@ pub enum NameList {
     skip_all,
     fields(
       names = names.len(),
-      offset = pagination.offset,
-      limit = pagination.limit.unwrap_or(names.len()),
-      split = pagination.split.unwrap_or(0),
+      offset = pagination.offset(),
+      limit = pagination.limit().unwrap_or(names.len()),
+      split = pagination.split().unwrap_or(0),
     )
 )]
 pub async fn slice_the_loop(
-    Query(pagination): Query<Pagination>,
+    ValidatedQuery(pagination): ValidatedQuery<Pagination>,
     Json(names): Json<Vec<String>>,
-) -> Result<Json<NameList>, StatusCode> {
-    let limit = pagination.limit.unwrap_or(names.len());
-    let (start, end) = (
-        pagination.offset,
-        cmp::min(names.len(), pagination.offset + limit),
-    );
-
-    if names.len() < start || names.len() < end {
-        Err(StatusCode::UNPROCESSABLE_ENTITY)
+) -> Result<Json<NameList>, AppError> {
+    let limit = pagination.limit().unwrap_or(names.len());
+    let start = pagination.offset();
+    let end = cmp::min(names.len(), start.saturating_add(limit));
+
+    if names.len() < start {
+        Err(ErrorEnvelope::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("offset {start} exceeds the {} supplied names", names.len()),
+        )
+        .into())
     } else {
-        let split = pagination.split.unwrap_or(0usize);
         let names = &names[start..end];
 
-        Ok(Json(if 0 < split {
-            NameList::Split(
+        Ok(Json(match pagination.split() {
+            Some(split) => NameList::Split(
                 names
                     .iter()
                     .chunks(split)
                     .into_iter()
                     .map(|chunk| chunk.map(String::from).collect_vec())
                     .collect_vec(),
-            )
-        } else {
-            NameList::Unsplit(names.to_vec())
+            ),
+            None => NameList::Unsplit(names.to_vec()),
         }))
     }
 }
 
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 5](https://console.shuttle.rs/cch/challenge/5) solutions
+#[derive(Debug)]
+pub struct Day5;
+
+impl DayModule for Day5 {
+    fn day() -> i8 {
+        5
+    }
+
+    fn name() -> &'static str {
+        "Paginated Names"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new().route(
+            day_route!(5, POST routes::day5::NAMES, slice_the_loop, "Paginate the list of names"),
+        )
+    }
+}
+
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -99,5 +177,79 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
+    use crate::routes;
     use crate::utils::{service, TestService};
+
+    /// Test that `slice_the_loop` satisfies the conditions of
+    /// [CCH 2023 Challenge 5](https://console.shuttle.rs/cch/challenge/5)
+    /// and rejects invalid pagination parameters
+    #[rstest]
+    #[case::challenge_example(
+        &format!("{}?offset=3&limit=5", routes::day5::names()),
+        StatusCode::OK,
+        r#"["David","Eve","Frank","Grace","Hank"]"#
+    )]
+    #[case::bonus_example_one(
+        &format!("{}?offset=10&limit=4&split=4", routes::day5::names()),
+        StatusCode::OK,
+        r#"[["Kyle","Leo","Michael","Nathan"]]"#
+    )]
+    #[case::bonus_example_two(
+        &format!("{}?split=8", routes::day5::names()),
+        StatusCode::OK,
+        r#"[
+          ["Alice","Bob","Charlie","David","Eve","Frank","Grace","Hank"],
+          ["Ingrid","Jack","Kyle","Leo","Michael","Nathan","Olivia"]
+        ]"#
+    )]
+    #[case::negative_offset(
+        &format!("{}?offset=-1", routes::day5::names()),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        r#"{"error":{"code":"unprocessable_entity","status":422,"message":"invalid request parameters","details":{"offset":["must not be negative"]}}}"#,
+    )]
+    #[case::zero_split(
+        &format!("{}?split=0&limit=-2", routes::day5::names()),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        r#"{"error":{"code":"unprocessable_entity","status":422,"message":"invalid request parameters","details":{
+          "limit":["must not be negative"],
+          "split":["must be greater than zero (or omitted)"]
+        }}}"#
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_challenge_five(
+        service: TestService,
+        #[case] url: &str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_content: &str,
+    ) -> anyhow::Result<()> {
+        let names = r#"[
+          "Alice", "Bob", "Charlie", "David", "Eve", "Frank", "Grace", "Hank",
+          "Ingrid", "Jack", "Kyle", "Leo", "Michael", "Nathan", "Olivia"
+        ]"#;
+
+        let response = service
+            .resolve(
+                Request::post(url)
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(names))?,
+            )
+            .await?;
+
+        assert_eq!(
+            expected_status,
+            response.status(),
+            "status[expected: {}, actual: {}]",
+            expected_status,
+            response.status(),
+        );
+
+        let content = response.into_body().data().await.unwrap()?;
+
+        assert_eq!(
+            serde_json::from_str::<Value>(expected_content)?,
+            serde_json::from_slice::<Value>(content.as_ref())?,
+        );
+
+        Ok(())
+    }
 }
@@ -3,22 +3,150 @@
 
 // Standard Library Imports
 use core::{convert::AsRef, fmt::Debug};
+use std::net::SocketAddr;
 
 // Third-Party Imports
-use axum::Json;
+use aho_corasick::{AhoCorasick, BuildError};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, State,
+    },
+    http::HeaderMap,
+    response::IntoResponse,
+    Json,
+};
 #[allow(unused_imports)]
 use axum_template::{
     engine::{Engine as HandlebarsEngine, HandlebarsError},
     Key, RenderHtml,
 };
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
+// Crate-Level Imports
+use crate::{
+    errors::AppError,
+    metrics::LiveSocket,
+    record_fields,
+    responses::{ApiOk, ApiResult},
+    routes,
+    solutions::{DayModule, DayRoutes},
+    state::ShuttleAppState,
+    telemetry::fields,
+    utils::TextBody,
+    validation::{Validate, ValidationErrors},
+};
+
+/// The longest pattern counted, i.e. the most text
+/// needed to decide what starts at any given position
+const LONGEST_PATTERN: usize = "elf on a shelf".len();
+
+/// The maximum number of patterns counted per request
+const MAX_PATTERNS: usize = 256;
+
+/// The maximum length of a counted pattern
+const MAX_PATTERN_LENGTH: usize = 256;
+
+/// The counter for the patterns summarized by an [`ElfShelfCountSummary`]
+static ELF_PATTERNS: Lazy<PatternCounter> = Lazy::new(|| {
+    PatternCounter::new(&["elf", "elf on a shelf", "shelf"])
+        .expect("the elf patterns should build an automaton")
+});
+
+// <editor-fold desc="// PatternCounter ...">
+
+/// Counts (overlapping) occurrences of any number
+/// of patterns in a single pass over some text
+#[derive(Clone, Debug)]
+pub struct PatternCounter {
+    /// the automaton matching every pattern at once
+    automaton: AhoCorasick,
+}
+
+impl PatternCounter {
+    /// Create a counter for the supplied patterns
+    pub fn new<Pattern: AsRef<str>>(patterns: &[Pattern]) -> Result<Self, BuildError> {
+        Ok(Self {
+            automaton: AhoCorasick::new(patterns.iter().map(AsRef::as_ref))?,
+        })
+    }
+
+    /// Count each pattern's (overlapping) occurrences in the supplied text
+    pub fn count(&self, text: &str) -> Vec<u64> {
+        self.count_before(text, text.len())
+    }
+
+    /// Count each pattern's (overlapping) occurrences in the
+    /// supplied text that start before the supplied offset
+    pub fn count_before(&self, text: &str, before: usize) -> Vec<u64> {
+        let mut counts = vec![0u64; self.automaton.patterns_len()];
+
+        for found in self
+            .automaton
+            .find_overlapping_iter(text)
+            .filter(|found| found.start() < before)
+        {
+            counts[found.pattern().as_usize()] += 1;
+        }
+
+        counts
+    }
+}
+
+/// Some text in which to count occurrences of some patterns
+#[derive(Debug, Deserialize)]
+pub struct PatternCountRequest {
+    /// the patterns to count
+    pub patterns: Vec<String>,
+    /// the text to count them in
+    pub text: String,
+}
+
+impl Validate for PatternCountRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if self.patterns.is_empty() || MAX_PATTERNS < self.patterns.len() {
+            errors.add(
+                "patterns",
+                format!("must contain between 1 and {MAX_PATTERNS} patterns"),
+            );
+        }
+
+        if self
+            .patterns
+            .iter()
+            .any(|pattern| pattern.is_empty() || MAX_PATTERN_LENGTH < pattern.len())
+        {
+            errors.add(
+                "patterns",
+                format!("patterns must be between 1 and {MAX_PATTERN_LENGTH} bytes long"),
+            );
+        }
+
+        errors.into_result()
+    }
+}
+
+/// The number of (overlapping) occurrences of a pattern
+#[cfg_attr(test, derive(Eq, PartialEq))]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PatternCount {
+    /// the pattern counted
+    pub pattern: String,
+    /// the number of times it occurs
+    pub count: u64,
+}
+
+// </editor-fold desc="// PatternCounter ...">
+
 // <editor-fold desc="// ElfShelfCountSummary ...">
 
 /// Custom struct for responding to elf/shelf count
 /// requests for [Day 6](https://console.shuttle.rs/cch/challenge/6)
 #[cfg_attr(test, derive(Eq, PartialEq))]
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ElfShelfCountSummary {
     /// The count of how many times the literal
     /// string "elf" appears in the source text
@@ -54,39 +182,205 @@ impl<T: AsRef<str>> From<T> for ElfShelfCountSummary {
 
         let mut summary = Self::default();
 
-        for idx in 0..text.len() {
-            match &text[idx..] {
-                segment if segment.starts_with("elf on a shelf") => {
-                    // that's one loose elf
-                    summary.loose_elves += 1;
-                    // and one shelved elf
-                    summary.shelved_elves += 1;
-                }
-                segment if segment.starts_with("elf") => {
-                    summary.loose_elves += 1;
-                }
-                segment if segment.starts_with("shelf") => {
-                    summary.bare_shelves += 1;
-                }
-                _ => (),
-            }
-        }
+        summary._tally(text, text.len());
+
+        summary._adjusted()
+    }
+}
 
-        // Adjust the count of shelves to exclude shelves with an elf
-        summary.bare_shelves = u64::saturating_sub(summary.bare_shelves, summary.shelved_elves);
+impl ElfShelfCountSummary {
+    /// Count the elves (and shelves) starting before the supplied
+    /// offset in the supplied text (counting every shelf as bare,
+    /// for the time being)
+    fn _tally(&mut self, text: &str, before: usize) {
+        let [elves, shelved, shelves] = ELF_PATTERNS
+            .count_before(text, before)
+            .try_into()
+            .unwrap_or_default();
 
-        summary
+        self.loose_elves += elves;
+        self.shelved_elves += shelved;
+        self.bare_shelves += shelves;
+    }
+
+    /// The summary with its count of shelves
+    /// adjusted to exclude shelves with an elf
+    fn _adjusted(mut self) -> Self {
+        self.bare_shelves = u64::saturating_sub(self.bare_shelves, self.shelved_elves);
+        self
     }
 }
 
 // </editor-fold desc="// ElfShelfCountSummary ...">
 
+// <editor-fold desc="// ElfShelfCounter ...">
+
+/// Counts elves (and shelves) in text supplied in chunks,
+/// e.g. from a live log stream, carrying over just enough
+/// of each chunk to count patterns spanning chunk boundaries
+#[derive(Debug, Default)]
+pub struct ElfShelfCounter {
+    /// the (unadjusted) counts for every position
+    /// followed by enough text to count definitively
+    settled: ElfShelfCountSummary,
+    /// the text after the last settled position
+    carry: String,
+}
+
+impl ElfShelfCounter {
+    /// Count the supplied chunk, returning the summary
+    /// of every chunk counted so far
+    pub fn feed(&mut self, chunk: &str) -> ElfShelfCountSummary {
+        self.carry.push_str(chunk);
+
+        // positions followed by at least the longest
+        // pattern's worth of text can be settled now
+        let unsettled = self
+            .carry
+            .char_indices()
+            .map(|(idx, _)| idx)
+            .find(|idx| self.carry.len() - idx < LONGEST_PATTERN)
+            .unwrap_or(self.carry.len());
+
+        self.settled._tally(&self.carry, unsettled);
+
+        self.carry.drain(..unsettled);
+
+        self.summary()
+    }
+
+    /// The summary of every chunk counted so far
+    pub fn summary(&self) -> ElfShelfCountSummary {
+        let mut summary = self.settled.clone();
+
+        summary._tally(&self.carry, self.carry.len());
+
+        summary._adjusted()
+    }
+}
+
+// </editor-fold desc="// ElfShelfCounter ...">
+
 /// Complete [Day 6: Task + Bonus](https://console.shuttle.rs/cch/challenge/6#:~:text=🎄)
 #[tracing::instrument(ret)]
-pub async fn count_elves(text: String) -> Json<ElfShelfCountSummary> {
+pub async fn count_elves(TextBody(text): TextBody) -> Json<ElfShelfCountSummary> {
     Json(ElfShelfCountSummary::from(text))
 }
 
+/// Count the (overlapping) occurrences of each of the supplied patterns
+#[tracing::instrument(skip_all, fields(patterns = request.patterns.len()))]
+pub async fn count_patterns(
+    Json(request): Json<PatternCountRequest>,
+) -> ApiResult<Vec<PatternCount>> {
+    request.validate()?;
+
+    let counter = PatternCounter::new(&request.patterns).map_err(|error| {
+        let mut errors = ValidationErrors::default();
+        errors.add("patterns", error);
+        errors
+    })?;
+
+    Ok(ApiOk(
+        request
+            .patterns
+            .into_iter()
+            .zip(counter.count(&request.text))
+            .map(|(pattern, count)| PatternCount { pattern, count })
+            .collect(),
+    ))
+}
+
+/// Count elves in text streamed over a websocket, responding
+/// to each (text) chunk with the running [`ElfShelfCountSummary`]
+#[tracing::instrument(skip_all)]
+pub async fn count_elves_live(
+    State(state): State<ShuttleAppState>,
+    headers: HeaderMap,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    ws: WebSocketUpgrade,
+) -> Result<impl IntoResponse, AppError> {
+    let live = LiveSocket::admit(
+        &state.metrics,
+        state
+            .proxies
+            .client_ip(&headers, peer.as_ref().map(|ConnectInfo(peer)| peer)),
+    )?;
+
+    Ok(ws.on_upgrade(|socket| async move {
+        stream_elf_counts(socket).await;
+        drop(live);
+    }))
+}
+
+/// Count each chunk received over the supplied socket,
+/// replying with the running summary, until it closes
+#[tracing::instrument(skip_all, fields({fields::SOCKET}))]
+async fn stream_elf_counts(mut socket: WebSocket) {
+    record_fields!(fields::SOCKET => format!("{:p}", &socket));
+
+    let mut counter = ElfShelfCounter::default();
+
+    while let Some(Ok(message)) = socket.recv().await {
+        let chunk = match message {
+            Message::Text(chunk) => chunk,
+            Message::Close(_) => break,
+            Message::Ping(_) | Message::Pong(_) => continue,
+            Message::Binary(_) => {
+                tracing::warn!("ignoring binary message");
+                continue;
+            }
+        };
+
+        let summary = match serde_json::to_string(&counter.feed(&chunk)) {
+            Ok(summary) => summary,
+            Err(error) => {
+                tracing::error!("error serializing elf count: {error}");
+                break;
+            }
+        };
+
+        if let Err(error) = socket.send(Message::Text(summary)).await {
+            tracing::error!("{error:?}");
+            break;
+        }
+    }
+}
+
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 6](https://console.shuttle.rs/cch/challenge/6) solutions
+#[derive(Debug)]
+pub struct Day6;
+
+impl DayModule for Day6 {
+    fn day() -> i8 {
+        6
+    }
+
+    fn name() -> &'static str {
+        "Elf Counting"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(6, POST routes::day6::ELVES, count_elves, "Count elves on shelves"))
+            .route(day_route!(
+                6,
+                GET routes::day6::LIVE,
+                count_elves_live,
+                "Count elves in streamed text"
+            ))
+            .route(day_route!(
+                6,
+                POST routes::day6::COUNT,
+                count_patterns,
+                "Count occurrences of arbitrary patterns"
+            ))
+    }
+}
+
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -115,7 +409,8 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
-    use super::ElfShelfCountSummary;
+    use super::{ElfShelfCountSummary, ElfShelfCounter, PatternCount};
+    use crate::routes;
     use crate::utils::{service, TestService};
 
     /// Test that `count_elves` satisfies the conditions of
@@ -213,7 +508,9 @@ mod tests {
         #[case] expected_summary: ElfShelfCountSummary,
     ) -> anyhow::Result<()> {
         let response = service
-            .resolve(Request::post("/6").body(Body::from(text.as_bytes().to_vec()))?)
+            .resolve(
+                Request::post(routes::day6::elves()).body(Body::from(text.as_bytes().to_vec()))?,
+            )
             .await?;
 
         assert_eq!(
@@ -233,6 +530,153 @@ mod tests {
             summary, expected_summary
         );
 
+        Ok(())
+    }
+    /// Test that `count_elves` accepts non-UTF-8 request bodies
+    #[rstest]
+    #[case::latin1_charset(
+        Some("text/plain; charset=ISO-8859-1"),
+        b"caf\xe9 elf on a shelf".to_vec(),
+    )]
+    #[case::latin1_sniffed(None, b"caf\xe9 elf on a shelf".to_vec())]
+    #[case::utf16le_bom(
+        None,
+        [0xFFu8, 0xFE]
+            .into_iter()
+            .chain("caf\u{e9} elf on a shelf".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect::<Vec<u8>>(),
+    )]
+    #[case::utf16be_charset(
+        Some("text/plain; charset=utf-16be"),
+        "caf\u{e9} elf on a shelf"
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect::<Vec<u8>>(),
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_count_elves_transcodes_body(
+        service: TestService,
+        #[case] content_type: Option<&str>,
+        #[case] body: Vec<u8>,
+    ) -> anyhow::Result<()> {
+        let mut request = Request::post(routes::day6::elves());
+
+        if let Some(content_type) = content_type {
+            request = request.header(headers::CONTENT_TYPE, content_type);
+        }
+
+        let response = service.resolve(request.body(Body::from(body))?).await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let summary: ElfShelfCountSummary =
+            serde_json::from_slice(response.into_body().data().await.unwrap()?.as_ref())?;
+
+        assert_eq!(
+            ElfShelfCountSummary {
+                loose_elves: 2u64,
+                bare_shelves: 0u64,
+                shelved_elves: 1u64,
+            },
+            summary,
+        );
+
+        Ok(())
+    }
+
+    /// Test that counting text in chunks matches counting it whole,
+    /// however the text is split (including mid-pattern)
+    #[rstest]
+    #[case::bonus_example(
+        "there is an elf on a shelf on an elf. there is also another shelf in Belfast."
+    )]
+    #[case::overlapping("elf on a shelf on a shelf elf on a shelfelf on a shel")]
+    #[case::multibyte("caf\u{e9} \u{1f9dd} elf on a \u{1f9dd} shelf elf on a shelf")]
+    #[test_log::test]
+    fn test_elf_shelf_counter(#[case] text: &str) {
+        let expected = ElfShelfCountSummary::from(text);
+
+        for (split, _) in text.char_indices() {
+            let mut counter = ElfShelfCounter::default();
+
+            assert_eq!(
+                ElfShelfCountSummary::from(&text[..split]),
+                counter.feed(&text[..split])
+            );
+            assert_eq!(expected, counter.feed(&text[split..]), "split at {split}");
+        }
+
+        let mut counter = ElfShelfCounter::default();
+
+        for (idx, char) in text.char_indices() {
+            assert_eq!(
+                ElfShelfCountSummary::from(&text[..idx + char.len_utf8()]),
+                counter.feed(&char.to_string())
+            );
+        }
+    }
+
+    /// Test that `count_patterns` counts each pattern's
+    /// overlapping occurrences, in the order requested
+    #[rstest]
+    #[case::overlapping(
+        r#"{"patterns": ["aa", "a"], "text": "aaaa"}"#,
+        StatusCode::OK,
+        Some(vec![("aa", 3u64), ("a", 4u64)]),
+    )]
+    #[case::elves(
+        r#"{"patterns": ["shelf", "elf", "elf on a shelf", "Belfast"], "text": "there is an elf on a shelf on an elf. there is also another shelf in Belfast."}"#,
+        StatusCode::OK,
+        Some(vec![("shelf", 2u64), ("elf", 5u64), ("elf on a shelf", 1u64), ("Belfast", 1u64)]),
+    )]
+    #[case::missing(
+        r#"{"patterns": ["grinch"], "text": "elf elf elf"}"#,
+        StatusCode::OK,
+        Some(vec![("grinch", 0u64)]),
+    )]
+    #[case::no_patterns(
+        r#"{"patterns": [], "text": "elf"}"#,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        None
+    )]
+    #[case::empty_pattern(
+        r#"{"patterns": [""], "text": "elf"}"#,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        None
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_count_patterns(
+        service: TestService,
+        #[case] body: &'static str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_counts: Option<Vec<(&str, u64)>>,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                Request::post(routes::day6::count())
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))?,
+            )
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if let Some(expected_counts) = expected_counts {
+            let counts: Vec<PatternCount> =
+                serde_json::from_slice(response.into_body().data().await.unwrap()?.as_ref())?;
+
+            assert_eq!(
+                expected_counts
+                    .into_iter()
+                    .map(|(pattern, count)| PatternCount {
+                        pattern: pattern.to_string(),
+                        count
+                    })
+                    .collect::<Vec<PatternCount>>(),
+                counts
+            );
+        }
+
         Ok(())
     }
 }
@@ -2,25 +2,79 @@
 //!
 
 // Standard Library Imports
+use std::{collections::BTreeMap, sync::RwLock};
+
 use core::{
     cmp::PartialOrd,
     convert::{AsMut, AsRef},
     fmt::{Debug, Display, Formatter, Result as FormatResult},
     mem::discriminant as enum_variant,
     ops::{Deref, DerefMut, Not, Sub, SubAssign},
+    str::FromStr,
 };
 
 // Third-Party Imports
 use axum::{
     async_trait,
-    extract::{FromRequestParts, Json},
+    extract::{FromRequestParts, Json, State},
     http::{header::COOKIE, request::Parts, StatusCode},
 };
 use b64::{engine::general_purpose as base64, Engine};
 use itertools::Itertools;
+use once_cell::sync::Lazy;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{map::Map as JsonObject, Value};
 
+// Crate-Level Imports
+use crate::{
+    errors::ErrorEnvelope,
+    record_fields,
+    responses::{ApiOk, ApiResult},
+    routes,
+    sessions::Session,
+    solutions::{DayModule, DayRoutes},
+    state::ShuttleAppState,
+    validation::{Validate, ValidationErrors},
+};
+
+/// The session datum holding the session's last decoded recipe
+const LAST_RECIPE: &str = "day7:last";
+
+/// The bundled ingredient aliases, as (normalized) alias
+/// and (normalized) canonical ingredient name pairs
+const BUNDLED_ALIASES: [(&str, &str); 8] = [
+    ("choc chip", "chocolate chip"),
+    ("plain flour", "flour"),
+    ("all purpose flour", "flour"),
+    ("caster sugar", "sugar"),
+    ("granulated sugar", "sugar"),
+    ("unsalted butter", "butter"),
+    ("bicarb", "baking soda"),
+    ("bicarbonate of soda", "baking soda"),
+];
+
+/// The maximum length of an (added) alias or ingredient name
+const MAX_ALIAS_LENGTH: usize = 64;
+
+/// The maximum number of aliases added per request
+const MAX_ALIASES_PER_REQUEST: usize = 64;
+
+/// The maximum number of ingredients in a supplied catalog
+const MAX_CATALOG_ENTRIES: usize = 256;
+
+/// The number of decimal places costs and nutritional content are reported to
+const REPORTED_PRECISION: i32 = 6;
+
+/// The bundled ingredient catalog, used by cost
+/// requests that don't supply their own
+static BUNDLED_CATALOG: Lazy<IngredientCatalog> = Lazy::new(|| {
+    serde_json::from_str(include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/assets/day-7/catalog.json"
+    )))
+    .expect("the bundled ingredient catalog should be valid")
+});
+
 // <editor-fold desc="// Types ...">
 
 /// A recipe detailing the required
@@ -268,10 +322,177 @@ impl CookieData {
             self[&key] = computed_value;
         }
     }
+
+    /// Multiply every numeric value by the supplied fraction, keeping
+    /// integer values integral where the fraction divides them evenly,
+    /// or producing the names of the ingredients whose amounts overflow
+    pub fn scaled(&self, factor: Fraction) -> Result<Self, Vec<String>> {
+        let (numerator, denominator) = (factor.numerator, factor.denominator);
+
+        let mut instance = JsonObject::<String, Value>::new();
+        let mut overflowed = Vec::<String>::new();
+
+        for (key, value) in self.iter() {
+            let scaled = if let Some(value) = value.as_u64() {
+                value.checked_mul(numerator).map(|product| {
+                    if product % denominator == 0 {
+                        Value::from(product / denominator)
+                    } else {
+                        Value::from(product as f64 / denominator as f64)
+                    }
+                })
+            } else if let Some(value) = value.as_i64() {
+                i64::try_from(numerator)
+                    .ok()
+                    .zip(i64::try_from(denominator).ok())
+                    .and_then(|(numerator, denominator)| {
+                        value.checked_mul(numerator).map(|product| {
+                            if product % denominator == 0 {
+                                Value::from(product / denominator)
+                            } else {
+                                Value::from(product as f64 / denominator as f64)
+                            }
+                        })
+                    })
+            } else if let Some(value) = value.as_f64() {
+                Some(value * numerator as f64 / denominator as f64)
+                    .filter(|product| product.is_finite())
+                    .map(Value::from)
+            } else {
+                tracing::warn!(
+                    "Unsupported value type for scaling: {:?}",
+                    enum_variant(value),
+                );
+                Some(value.clone())
+            };
+
+            match scaled {
+                Some(scaled) => {
+                    instance.insert(key.clone(), scaled);
+                }
+                None => overflowed.push(key.clone()),
+            }
+        }
+
+        if overflowed.is_empty() {
+            Ok(Self(instance))
+        } else {
+            Err(overflowed)
+        }
+    }
 }
 
 // </editor-fold desc="// CookieData ...">
 
+// <editor-fold desc="// Fraction ...">
+
+/// A (non-negative) rational number, e.g. the factor a recipe
+/// is scaled by, accepted as an integer, a decimal (`1.5`),
+/// or a fraction (`"3/2"`), and reported in lowest terms
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Value", into = "String")]
+pub struct Fraction {
+    /// the fraction's numerator
+    pub numerator: u64,
+    /// the fraction's (non-zero) denominator
+    pub denominator: u64,
+}
+
+impl Fraction {
+    /// Create the supplied fraction (in lowest terms)
+    pub fn new(numerator: u64, denominator: u64) -> Result<Self, String> {
+        if denominator == 0 {
+            return Err(String::from("a fraction's denominator can't be zero"));
+        }
+
+        let divisor = Self::_gcd(numerator, denominator).max(1);
+
+        Ok(Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        })
+    }
+
+    /// The greatest common divisor of the supplied numbers
+    fn _gcd(left: u64, right: u64) -> u64 {
+        match right {
+            0 => left,
+            _ => Self::_gcd(right, left % right),
+        }
+    }
+
+    /// Parse the supplied (plain, non-negative) decimal
+    fn _from_decimal(value: &str) -> Option<Self> {
+        let (whole, fractional) = value.split_once('.').unwrap_or((value, ""));
+
+        if whole.is_empty() && fractional.is_empty()
+            || !whole
+                .chars()
+                .chain(fractional.chars())
+                .all(|char| char.is_ascii_digit())
+        {
+            return None;
+        }
+
+        let denominator = 10u64.checked_pow(u32::try_from(fractional.len()).ok()?)?;
+        let numerator = format!("{whole}{fractional}").parse::<u64>().ok()?;
+
+        Self::new(numerator, denominator).ok()
+    }
+}
+
+impl Display for Fraction {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match self.denominator {
+            1 => write!(formatter, "{}", self.numerator),
+            denominator => write!(formatter, "{}/{denominator}", self.numerator),
+        }
+    }
+}
+
+impl From<Fraction> for String {
+    fn from(fraction: Fraction) -> Self {
+        fraction.to_string()
+    }
+}
+
+impl FromStr for Fraction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+
+        match value.split_once('/') {
+            Some((numerator, denominator)) => {
+                let (Ok(numerator), Ok(denominator)) = (
+                    numerator.trim().parse::<u64>(),
+                    denominator.trim().parse::<u64>(),
+                ) else {
+                    return Err(format!("{value:?} isn't a fraction of whole numbers"));
+                };
+
+                Self::new(numerator, denominator)
+            }
+            None => Self::_from_decimal(value)
+                .ok_or_else(|| format!("{value:?} isn't a non-negative decimal or fraction")),
+        }
+    }
+}
+
+impl TryFrom<Value> for Fraction {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Number(number) => number.to_string().parse(),
+            Value::String(text) => text.parse(),
+            other => Err(format!("{other} isn't a number or fraction")),
+        }
+    }
+}
+
+// </editor-fold desc="// Fraction ...">
+
 // <editor-fold desc="// CookieRecipeInventory ...">
 
 /// A cookie recipe detailing the required
@@ -354,7 +575,7 @@ impl CookieRecipeInventory {
     #[tracing::instrument(skip(self), fields(after, before))]
     pub fn bake(mut self) -> Self {
         // Record the pre-bake state as part of the current span.
-        tracing::Span::current().record("before", format!("{}", &self).as_str());
+        record_fields!("before" => format!("{}", self).as_str());
 
         if self.recipe.is_empty() {
             tracing::warn!(r#"Declining to "re-bake" previously recipe/pantry"#);
@@ -375,7 +596,7 @@ impl CookieRecipeInventory {
         self.recipe.clear();
 
         // Record the post-bake state as part of the current span.
-        tracing::Span::current().record("after", format!("{}", &self).as_str());
+        record_fields!("after" => format!("{}", self).as_str());
 
         self
     }
@@ -383,6 +604,381 @@ impl CookieRecipeInventory {
 
 // </editor-fold desc="// CookieRecipeInventory ...">
 
+// <editor-fold desc="// IngredientAliases ...">
+
+/// Alternative names for recipe ingredients (e.g. "choc chips"
+/// for "chocolate chips"), by which recipes are matched to
+/// the pantry's ingredients before baking
+#[derive(Debug)]
+pub struct IngredientAliases(RwLock<BTreeMap<String, String>>);
+
+impl Default for IngredientAliases {
+    fn default() -> Self {
+        Self(RwLock::new(
+            BUNDLED_ALIASES
+                .iter()
+                .map(|(alias, canonical)| (alias.to_string(), canonical.to_string()))
+                .collect(),
+        ))
+    }
+}
+
+impl IngredientAliases {
+    /// Normalize the supplied ingredient name (lowercase, trimmed,
+    /// single-spaced, and with its last word singularized)
+    pub fn normalize(name: &str) -> String {
+        let mut words = name
+            .to_lowercase()
+            .replace(['-', '_'], " ")
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Vec<String>>();
+
+        if let Some(last) = words.last_mut() {
+            *last = Self::_singularize(last);
+        }
+
+        words.join(" ")
+    }
+
+    /// Naively singularize the supplied (lowercase) word
+    fn _singularize(word: &str) -> String {
+        if word.len() <= 3 || word.ends_with("ss") {
+            return word.to_string();
+        }
+
+        if let Some(stem) = word.strip_suffix("ies") {
+            return format!("{stem}y");
+        }
+
+        for suffix in ["sses", "ches", "shes", "xes"] {
+            if word.ends_with(suffix) {
+                return word[..word.len() - 2].to_string();
+            }
+        }
+
+        word.strip_suffix('s').unwrap_or(word).to_string()
+    }
+
+    /// The canonical (normalized) name of the supplied ingredient
+    pub fn canonical(&self, name: &str) -> String {
+        let name = Self::normalize(name);
+
+        self.0.read().unwrap().get(&name).cloned().unwrap_or(name)
+    }
+
+    /// Add the supplied aliases, returning every known alias
+    pub fn extend(&self, aliases: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+        let mut known = self.0.write().unwrap();
+
+        for (alias, canonical) in aliases {
+            let (alias, canonical) = (Self::normalize(alias), Self::normalize(canonical));
+
+            if alias != canonical {
+                known.insert(alias, canonical);
+            }
+        }
+
+        known.clone()
+    }
+
+    /// Get every known alias
+    pub fn all(&self) -> BTreeMap<String, String> {
+        self.0.read().unwrap().clone()
+    }
+
+    /// Rename the recipe's ingredients to the names the pantry uses
+    /// for the same (canonical) ingredients, so that the bake math
+    /// matches them (and the pantry's names are kept in its output)
+    pub fn reconcile(&self, data: &mut CookieRecipeInventory) {
+        let pantry = data
+            .pantry
+            .keys()
+            .map(|name| (self.canonical(name), name.clone()))
+            .collect::<BTreeMap<String, String>>();
+
+        let renames = data
+            .recipe
+            .keys()
+            .filter(|name| !data.pantry.contains_key(*name))
+            .filter_map(|name| {
+                pantry
+                    .get(&self.canonical(name))
+                    .filter(|target| !data.recipe.contains_key(*target))
+                    .map(|target| (name.clone(), target.clone()))
+            })
+            .collect::<BTreeMap<String, String>>();
+
+        for (name, target) in renames {
+            if let Some(amount) = data.recipe.remove(&name) {
+                tracing::debug!("matched recipe's {name:?} to pantry's {target:?}");
+                data.recipe.insert(target, amount);
+            }
+        }
+    }
+}
+
+/// Aliases to add to the known [`IngredientAliases`],
+/// as alias and ingredient name pairs
+#[derive(Debug, Deserialize)]
+pub struct AliasRequest(pub BTreeMap<String, String>);
+
+impl Validate for AliasRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if self.0.is_empty() || MAX_ALIASES_PER_REQUEST < self.0.len() {
+            errors.add(
+                "aliases",
+                format!("must contain between 1 and {MAX_ALIASES_PER_REQUEST} aliases"),
+            );
+        }
+
+        if self.0.iter().any(|(alias, canonical)| {
+            [alias, canonical].iter().any(|name| {
+                IngredientAliases::normalize(name).is_empty() || MAX_ALIAS_LENGTH < name.len()
+            })
+        }) {
+            errors.add(
+                "aliases",
+                format!("names must be between 1 and {MAX_ALIAS_LENGTH} characters long"),
+            );
+        }
+
+        errors.into_result()
+    }
+}
+
+// </editor-fold desc="// IngredientAliases ...">
+
+// <editor-fold desc="// IngredientCatalog ...">
+
+/// The price and nutritional content of one unit
+/// (i.e. whatever unit recipes measure it in) of an ingredient
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IngredientFacts {
+    /// the price of one unit of the ingredient
+    pub price: f64,
+    /// the nutritional content of one unit of
+    /// the ingredient, e.g. `{"calories": 3.64}`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub nutrition: BTreeMap<String, f64>,
+}
+
+/// Per-ingredient prices and nutritional content
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct IngredientCatalog(pub BTreeMap<String, IngredientFacts>);
+
+impl IngredientCatalog {
+    /// The catalog bundled with the service
+    pub fn bundled() -> &'static Self {
+        &BUNDLED_CATALOG
+    }
+
+    /// Annotate the supplied recipe (and the number of cookies
+    /// baked from it) with its cost and nutritional content,
+    /// matching ingredients to the catalog's by their canonical names
+    pub fn annotate(
+        &self,
+        recipe: &CookieRecipe,
+        cookies: u64,
+        aliases: &IngredientAliases,
+    ) -> CostReport {
+        let catalog = self
+            .0
+            .iter()
+            .map(|(name, facts)| (aliases.canonical(name), facts))
+            .collect::<BTreeMap<String, &IngredientFacts>>();
+
+        let mut report = CostReport {
+            cookies,
+            ..CostReport::default()
+        };
+
+        for (name, amount) in recipe.iter() {
+            match catalog.get(&aliases.canonical(name)) {
+                Some(facts) => report
+                    .per_cookie
+                    .add(facts, amount.as_f64().unwrap_or_default()),
+                None => report.unpriced.push(name.clone()),
+            }
+        }
+
+        report.total = report.per_cookie.scaled(cookies as f64);
+        report.per_cookie = report.per_cookie.scaled(1.0);
+
+        report
+    }
+}
+
+/// The (summed) cost and nutritional content of some cookies
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostTotals {
+    /// the cookies' cost
+    pub cost: f64,
+    /// the cookies' nutritional content
+    #[serde(default)]
+    pub nutrition: BTreeMap<String, f64>,
+}
+
+impl CostTotals {
+    /// Add the supplied amount of the supplied ingredient
+    fn add(&mut self, facts: &IngredientFacts, amount: f64) {
+        self.cost += facts.price * amount;
+
+        for (nutrient, content) in &facts.nutrition {
+            *self.nutrition.entry(nutrient.clone()).or_default() += content * amount;
+        }
+    }
+
+    /// The totals multiplied by the supplied factor
+    /// (and rounded to the reported precision)
+    fn scaled(&self, factor: f64) -> Self {
+        let scale = 10f64.powi(REPORTED_PRECISION);
+        let round = |value: f64| (value * factor * scale).round() / scale;
+
+        Self {
+            cost: round(self.cost),
+            nutrition: self
+                .nutrition
+                .iter()
+                .map(|(nutrient, content)| (nutrient.clone(), round(*content)))
+                .collect(),
+        }
+    }
+}
+
+/// The cost and nutritional content of a bake
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostReport {
+    /// the number of cookies baked
+    pub cookies: u64,
+    /// the cost and nutritional content of one cookie
+    pub per_cookie: CostTotals,
+    /// the cost and nutritional content of every cookie baked
+    pub total: CostTotals,
+    /// the recipe's ingredients missing from the catalog
+    /// (which are excluded from the reported totals)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unpriced: Vec<String>,
+}
+
+/// A bake (or a recipe and pantry to bake) to annotate
+/// with its cost, priced per the supplied catalog (if
+/// any) or the [bundled](IngredientCatalog::bundled) one
+#[derive(Debug, Deserialize)]
+pub struct CostRequest {
+    /// the bake's recipe, along with either the number of
+    /// cookies baked or the pantry to bake them from
+    #[serde(flatten)]
+    pub bake: CookieRecipeInventory,
+    /// the ingredient prices and nutritional content to use
+    #[serde(default)]
+    pub catalog: Option<IngredientCatalog>,
+}
+
+impl Validate for CostRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if self.bake.recipe.is_empty() {
+            errors.add("recipe", "must contain at least one ingredient");
+        }
+
+        if self
+            .bake
+            .recipe
+            .values()
+            .any(|amount| amount.as_f64().is_none_or(f64::is_sign_negative))
+        {
+            errors.add("recipe", "ingredient amounts must be non-negative numbers");
+        }
+
+        let catalog = self.catalog.as_ref().map(|catalog| &catalog.0);
+
+        if catalog.is_some_and(|catalog| MAX_CATALOG_ENTRIES < catalog.len()) {
+            errors.add(
+                "catalog",
+                format!("must contain at most {MAX_CATALOG_ENTRIES} ingredients"),
+            );
+        }
+
+        for (name, facts) in catalog.into_iter().flatten() {
+            if [facts.price]
+                .iter()
+                .chain(facts.nutrition.values())
+                .any(|value| !value.is_finite() || value.is_sign_negative())
+            {
+                errors.add(
+                    format!("catalog.{name}"),
+                    "prices and nutritional content must be non-negative numbers",
+                );
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+// </editor-fold desc="// IngredientCatalog ...">
+
+// <editor-fold desc="// ScaleRequest ...">
+
+/// A recipe to scale, either by a factor or to yield a number of cookies
+#[derive(Debug, Deserialize)]
+pub struct ScaleRequest {
+    /// the recipe to scale
+    pub recipe: CookieRecipe,
+    /// the factor to scale the recipe by
+    #[serde(default)]
+    pub factor: Option<Fraction>,
+    /// the number of cookies the scaled recipe should yield
+    #[serde(default)]
+    pub cookies: Option<u64>,
+    /// the number of cookies the (unscaled) recipe yields
+    #[serde(default = "ScaleRequest::_default_yield")]
+    pub yields: u64,
+}
+
+impl ScaleRequest {
+    /// Recipes make one cookie unless otherwise stated
+    fn _default_yield() -> u64 {
+        1
+    }
+
+    /// The factor the recipe should be scaled by
+    pub fn factor(&self) -> Option<Fraction> {
+        match (self.factor, self.cookies) {
+            (Some(factor), None) => Some(factor),
+            (None, Some(cookies)) => Fraction::new(cookies, self.yields).ok(),
+            _ => None,
+        }
+    }
+}
+
+impl Validate for ScaleRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if self.recipe.is_empty() {
+            errors.add("recipe", "must contain at least one ingredient");
+        }
+
+        if self.factor.is_some() == self.cookies.is_some() {
+            errors.add("factor", "exactly one of `factor` or `cookies` is required");
+        }
+
+        if self.yields == 0 {
+            errors.add("yields", "must be at least 1");
+        }
+
+        errors.into_result()
+    }
+}
+
+// </editor-fold desc="// ScaleRequest ...">
+
 // <editor-fold desc="// CookieRecipeHeader ...">
 
 /// [`axum` extractor](axum::extract) for
@@ -411,7 +1007,7 @@ where
                 String::from(r#""cookie" header missing"#),
             ))
             .and_then(|header| {
-                tracing::Span::current().record("cookie", format!("{header:?}").as_str());
+                record_fields!("cookie" => format!("{header:?}").as_str());
                 let header = header.as_bytes();
 
                 match (
@@ -459,27 +1055,206 @@ where
 /// Complete [Day 7: Challenge](https://console.shuttle.rs/cch/challenge/7#:~:text=⭐)
 #[tracing::instrument(skip_all)]
 pub async fn decode_cookie_recipe(
+    session: Option<Session>,
     CookieRecipeHeader(recipe): CookieRecipeHeader<Value>,
 ) -> Json<Value> {
+    if let Some(session) = session {
+        session.insert(LAST_RECIPE, &recipe).await;
+    }
+
     Json(recipe)
 }
 
 /// Complete [Day 7: Bonus](https://console.shuttle.rs/cch/challenge/7#:~:text=🎁)
 #[tracing::instrument(skip_all, fields(request, response))]
 pub async fn bake_cookies_from_recipe_and_pantry(
-    CookieRecipeHeader(data): CookieRecipeHeader<CookieRecipeInventory>,
+    State(state): State<ShuttleAppState>,
+    session: Option<Session>,
+    CookieRecipeHeader(mut data): CookieRecipeHeader<CookieRecipeInventory>,
 ) -> RecipeAnalysisResponse {
-    tracing::Span::current().record("request", format!("{}", &data).as_str());
+    record_fields!("request" => format!("{}", data).as_str());
+
+    if let Some(session) = session {
+        session.insert(LAST_RECIPE, &data).await;
+    }
+
+    state.aliases.reconcile(&mut data);
 
     let data = data.bake();
 
-    tracing::Span::current().record("response", format!("{}", &data).as_str());
+    record_fields!("response" => format!("{}", data).as_str());
 
     tracing::info!("\u{1F6CE}\u{FE0F}\u{3000}\u{1F36A}");
 
     (StatusCode::OK, Json(data))
 }
 
+/// List the known ingredient aliases
+#[tracing::instrument(skip_all)]
+pub async fn list_ingredient_aliases(
+    State(state): State<ShuttleAppState>,
+) -> Json<BTreeMap<String, String>> {
+    Json(state.aliases.all())
+}
+
+/// Add ingredient aliases, responding with every known alias
+#[tracing::instrument(skip(state))]
+pub async fn add_ingredient_aliases(
+    State(state): State<ShuttleAppState>,
+    Json(request): Json<AliasRequest>,
+) -> ApiResult<BTreeMap<String, String>> {
+    request.validate()?;
+
+    Ok(ApiOk(state.aliases.extend(&request.0)))
+}
+
+/// Annotate a bake with its cost and nutritional content, baking
+/// the supplied pantry first if no number of cookies is supplied
+#[tracing::instrument(skip_all)]
+pub async fn estimate_cookie_cost(
+    State(state): State<ShuttleAppState>,
+    Json(request): Json<CostRequest>,
+) -> ApiResult<CostReport> {
+    request.validate()?;
+
+    let CostRequest {
+        bake: mut data,
+        catalog,
+    } = request;
+
+    state.aliases.reconcile(&mut data);
+
+    let recipe = data.recipe.clone();
+    let cookies = match data.cookies {
+        0 => data.bake().cookies,
+        cookies => cookies,
+    };
+
+    let report = catalog
+        .as_ref()
+        .unwrap_or_else(|| IngredientCatalog::bundled())
+        .annotate(&recipe, cookies, &state.aliases);
+
+    Ok(ApiOk(report))
+}
+
+/// Scale a recipe by a factor, or to yield a number of cookies
+#[tracing::instrument(skip_all, fields(factor))]
+pub async fn scale_recipe(Json(request): Json<ScaleRequest>) -> ApiResult<CookieRecipe> {
+    request.validate()?;
+
+    let factor = request.factor().unwrap_or(Fraction {
+        numerator: 1,
+        denominator: 1,
+    });
+
+    record_fields!("factor" => factor.to_string().as_str());
+
+    request
+        .recipe
+        .scaled(factor)
+        .map(ApiOk)
+        .map_err(|overflowed| {
+            let mut errors = ValidationErrors::default();
+
+            for ingredient in overflowed {
+                errors.add(
+                    format!("recipe.{ingredient}"),
+                    format!("scaling by {factor} overflows"),
+                );
+            }
+
+            errors.into()
+        })
+}
+
+/// Get the last recipe decoded in the requesting client's session
+#[tracing::instrument(skip_all)]
+pub async fn get_last_recipe(session: Session) -> ApiResult<Value> {
+    session
+        .get::<Value>(LAST_RECIPE)
+        .await
+        .map(ApiOk)
+        .ok_or_else(|| {
+            ErrorEnvelope::new(
+                StatusCode::NOT_FOUND,
+                "no recipe has been decoded in this session",
+            )
+            .into()
+        })
+}
+
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 7](https://console.shuttle.rs/cch/challenge/7) solutions
+#[derive(Debug)]
+pub struct Day7;
+
+impl DayModule for Day7 {
+    fn day() -> i8 {
+        7
+    }
+
+    fn name() -> &'static str {
+        "Cookie Recipes"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                7,
+                GET routes::day7::BAKE,
+                bake_cookies_from_recipe_and_pantry,
+                "Bake cookies from a recipe and pantry"
+            ))
+            .route(day_route!(
+                7,
+                POST routes::day7::BAKE,
+                bake_cookies_from_recipe_and_pantry,
+                "Bake cookies from a recipe and pantry"
+            ))
+            .route(day_route!(
+                7,
+                GET routes::day7::DECODE,
+                decode_cookie_recipe,
+                "Decode a cookie recipe"
+            ))
+            .route(day_route!(
+                7,
+                POST routes::day7::DECODE,
+                decode_cookie_recipe,
+                "Decode a cookie recipe"
+            ))
+            .route(day_route!(
+                7,
+                GET routes::day7::LAST,
+                get_last_recipe,
+                "Get the session's last decoded recipe"
+            ))
+            .route(day_route!(
+                7,
+                POST routes::day7::COST,
+                estimate_cookie_cost,
+                "Estimate a bake's cost and nutrition"
+            ))
+            .route(day_route!(7, POST routes::day7::SCALE, scale_recipe, "Scale a recipe"))
+            .route(day_route!(
+                7,
+                GET routes::day7::ALIASES,
+                list_ingredient_aliases,
+                "List the known ingredient aliases"
+            ))
+            .route(day_route!(
+                7,
+                POST routes::day7::ALIASES,
+                add_ingredient_aliases,
+                "Add ingredient aliases"
+            ))
+    }
+}
+
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -508,8 +1283,12 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
-    use super::{CookieRecipe, CookieRecipeInventory};
-    use crate::utils::{service, TestService};
+    use super::{CookieRecipe, CookieRecipeInventory, CostReport, Fraction, IngredientAliases};
+    use crate::router;
+    use crate::routes;
+    use crate::sessions::{SESSION_OPT_IN, SESSION_OPT_IN_HEADER};
+    use crate::state::ShuttleAppState;
+    use crate::utils::{service, state, TestService};
 
     #[derive(Debug)]
     enum RecipeOrBakeResult {
@@ -538,13 +1317,13 @@ mod tests {
     /// [CCH 2023 Challenge 7](https://console.shuttle.rs/cch/challenge/7)
     #[rstest]
     #[case::challenge_example(
-        "/7/decode",
+        routes::day7::decode(),
         "eyJmbG91ciI6MTAwLCJjaG9jb2xhdGUgY2hpcHMiOjIwfQ==",
         StatusCode::OK,
         "{\"flour\":100,\"chocolate chips\":20}"
     )]
     #[case::bonus_example(
-        "/7/bake",
+        routes::day7::bake(),
         "eyJyZWNpcGUiOnsiZmxvdXIiOjk1LCJzdWdhciI6NTAsImJ1\
          dHRlciI6MzAsImJha2luZyBwb3dkZXIiOjEwLCJjaG9jb2xh\
          dGUgY2hpcHMiOjUwfSwicGFudHJ5Ijp7ImZsb3VyIjozODUs\
@@ -566,7 +1345,7 @@ mod tests {
     )]
     #[ignore = "not implemented yet"]
     #[case::second_bonus_example(
-        "/7/bake",
+        routes::day7::bake(),
         "eyJyZWNpcGUiOnsic2xpbWUiOjl9LCJwYW50cnkiO\
         nsiY29iYmxlc3RvbmUiOjY0LCJzdGljayI6IDR9fQ==",
         StatusCode::OK,
@@ -625,4 +1404,298 @@ mod tests {
 
         Ok(())
     }
+
+    /// Test that the last recipe decoded in a session is
+    /// available to (only) that session's later requests,
+    /// and that only clients opting in get a new session
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_get_last_recipe(state: ShuttleAppState) -> anyhow::Result<()> {
+        let response = TestService::from(router(state.clone()))
+            .resolve(routes::day7::last())
+            .await?;
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(Request::get(routes::day7::decode()).header(
+                headers::COOKIE,
+                "recipe=eyJmbG91ciI6MTAwLCJjaG9jb2xhdGUgY2hpcHMiOjIwfQ==",
+            ))
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert!(!response.headers().contains_key(headers::SET_COOKIE));
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(
+                Request::get(routes::day7::decode())
+                    .header(
+                        headers::COOKIE,
+                        "recipe=eyJmbG91ciI6MTAwLCJjaG9jb2xhdGUgY2hpcHMiOjIwfQ==",
+                    )
+                    .header(SESSION_OPT_IN_HEADER, SESSION_OPT_IN),
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let session = response.headers()[headers::SET_COOKIE]
+            .to_str()?
+            .split(';')
+            .next()
+            .unwrap()
+            .to_string();
+
+        let response = TestService::from(router(state.clone()))
+            .resolve(Request::get(routes::day7::last()).header(headers::COOKIE, &session))
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert!(!response.headers().contains_key(headers::SET_COOKIE));
+        assert_eq!(
+            serde_json::json!({"flour": 100, "chocolate chips": 20}),
+            serde_json::from_slice::<Value>(&hyper::body::to_bytes(response.into_body()).await?)?
+        );
+
+        let response = TestService::from(router(state))
+            .resolve(routes::day7::last())
+            .await?;
+
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+        Ok(())
+    }
+
+    /// Test that ingredient names are normalized (and aliased) as expected
+    #[rstest]
+    #[case::plural("Chocolate Chips", "chocolate chip")]
+    #[case::padded("  flour ", "flour")]
+    #[case::separated("baking_powder", "baking powder")]
+    #[case::ies("Cranberries", "cranberry")]
+    #[case::ches("peaches", "peach")]
+    #[case::sses("Glasses", "glass")]
+    #[case::kept("glass", "glass")]
+    #[case::aliased("choc-chips", "chocolate chip")]
+    #[case::aliased_phrase("Bicarbonate of Soda", "baking soda")]
+    #[test_log::test]
+    fn test_canonical_ingredients(#[case] name: &str, #[case] expected: &str) {
+        assert_str_eq!(expected, IngredientAliases::default().canonical(name));
+    }
+
+    /// Test that aliased recipe ingredients are baked from the
+    /// matching pantry ingredients, keeping the pantry's names
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_bake_with_aliases(state: ShuttleAppState) -> anyhow::Result<()> {
+        let cookie = b64::Engine::encode(
+            &b64::engine::general_purpose::STANDARD,
+            r#"{"recipe":{"choc chips":10,"Plain Flour":20},"pantry":{"chocolate chips":25,"flour":45}}"#,
+        );
+        let response = TestService::from(router(state))
+            .resolve(
+                Request::get(routes::day7::bake())
+                    .header(headers::COOKIE, format!("recipe={cookie}")),
+            )
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            serde_json::json!({"cookies": 2, "pantry": {"chocolate chips": 5, "flour": 5}}),
+            serde_json::from_slice::<Value>(&hyper::body::to_bytes(response.into_body()).await?)?
+        );
+
+        Ok(())
+    }
+
+    /// Test that aliases can be added (and are then listed)
+    #[rstest]
+    #[case::added(r#"{"Semi-Sweet Morsels":"chocolate chips"}"#, StatusCode::OK)]
+    #[case::empty("{}", StatusCode::UNPROCESSABLE_ENTITY)]
+    #[case::blank(r#"{" ":"flour"}"#, StatusCode::UNPROCESSABLE_ENTITY)]
+    #[test_log::test(tokio::test)]
+    async fn test_add_ingredient_aliases(
+        state: ShuttleAppState,
+        #[case] body: &'static str,
+        #[case] expected: StatusCode,
+    ) -> anyhow::Result<()> {
+        let response = TestService::from(router(state.clone()))
+            .resolve(
+                Request::post(routes::day7::aliases())
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))?,
+            )
+            .await?;
+
+        assert_eq!(expected, response.status());
+        assert_eq!(
+            expected.is_success(),
+            state.aliases.canonical("semi sweet morsel") == "chocolate chip"
+        );
+
+        let response = TestService::from(router(state))
+            .resolve(routes::day7::aliases())
+            .await?;
+        let aliases = serde_json::from_slice::<HashMap<String, String>>(
+            &hyper::body::to_bytes(response.into_body()).await?,
+        )?;
+
+        assert_eq!(
+            Some("flour"),
+            aliases.get("plain flour").map(String::as_str)
+        );
+
+        Ok(())
+    }
+
+    /// Test that `estimate_cookie_cost` prices (and, if need be, bakes)
+    /// the supplied recipe per the supplied or bundled catalog
+    #[rstest]
+    #[case::supplied_catalog(
+        r#"{
+          "recipe": {"flour": 2, "choc chips": 1, "glitter": 1},
+          "pantry": {"flour": 10, "chocolate chips": 4, "glitter": 5},
+          "catalog": {
+            "flour": {"price": 0.5, "nutrition": {"calories": 4}},
+            "chocolate chips": {"price": 0.25, "nutrition": {"calories": 5, "sugar": 0.5}}
+          }
+        }"#,
+        StatusCode::OK,
+        Some(serde_json::json!({
+          "cookies": 4,
+          "per_cookie": {"cost": 1.25, "nutrition": {"calories": 13.0, "sugar": 0.5}},
+          "total": {"cost": 5.0, "nutrition": {"calories": 52.0, "sugar": 2.0}},
+          "unpriced": ["glitter"]
+        }))
+    )]
+    #[case::bundled_catalog(
+        r#"{"cookies": 3, "recipe": {"flour": 100, "butter": 50}}"#,
+        StatusCode::OK,
+        Some(serde_json::json!({
+          "cookies": 3,
+          "per_cookie": {"cost": 0.8, "nutrition": {"calories": 722.5, "carbohydrates": 76.0, "fat": 41.5}},
+          "total": {"cost": 2.4, "nutrition": {"calories": 2167.5, "carbohydrates": 228.0, "fat": 124.5}}
+        }))
+    )]
+    #[case::no_recipe(r#"{"cookies": 3}"#, StatusCode::UNPROCESSABLE_ENTITY, None)]
+    #[case::negative_amount(
+        r#"{"recipe": {"flour": -1}, "pantry": {"flour": 10}}"#,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        None
+    )]
+    #[case::negative_price(
+        r#"{"cookies": 1, "recipe": {"flour": 1}, "catalog": {"flour": {"price": -0.5}}}"#,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        None
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_estimate_cookie_cost(
+        service: TestService,
+        #[case] body: &'static str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_report: Option<Value>,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                Request::post(routes::day7::cost())
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))?,
+            )
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if let Some(expected_report) = expected_report {
+            assert_eq!(
+                serde_json::from_value::<CostReport>(expected_report)?,
+                serde_json::from_slice::<CostReport>(
+                    &hyper::body::to_bytes(response.into_body()).await?
+                )?
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Test that fractions are parsed (in lowest terms) as expected
+    #[rstest]
+    #[case::integer(serde_json::json!(2), Ok("2"))]
+    #[case::decimal(serde_json::json!(1.5), Ok("3/2"))]
+    #[case::decimal_string(serde_json::json!("0.25"), Ok("1/4"))]
+    #[case::fraction(serde_json::json!("6/4"), Ok("3/2"))]
+    #[case::zero_denominator(serde_json::json!("1/0"), Err(()))]
+    #[case::negative(serde_json::json!(-2), Err(()))]
+    #[case::exponent(serde_json::json!(1e-9), Err(()))]
+    #[case::garbage(serde_json::json!("half"), Err(()))]
+    #[test_log::test]
+    fn test_fractions(#[case] value: Value, #[case] expected: Result<&str, ()>) {
+        assert_eq!(
+            expected.map(String::from),
+            serde_json::from_value::<Fraction>(value)
+                .map(String::from)
+                .map_err(|_| ())
+        );
+    }
+
+    /// Test that `scale_recipe` scales recipes by a factor
+    /// or to a cookie count, rejecting overflowing amounts
+    #[rstest]
+    #[case::factor(
+        r#"{"recipe": {"flour": 95, "sugar": 50}, "factor": 2}"#,
+        StatusCode::OK,
+        Some(r#"{"flour": 190, "sugar": 100}"#)
+    )]
+    #[case::fraction(
+        r#"{"recipe": {"flour": 95, "sugar": 50, "vanilla": 0.5}, "factor": "3/2"}"#,
+        StatusCode::OK,
+        Some(r#"{"flour": 142.5, "sugar": 75, "vanilla": 0.75}"#)
+    )]
+    #[case::cookies(
+        r#"{"recipe": {"flour": 300, "eggs": 2}, "yields": 12, "cookies": 30}"#,
+        StatusCode::OK,
+        Some(r#"{"flour": 750, "eggs": 5}"#)
+    )]
+    #[case::overflow(
+        r#"{"recipe": {"flour": 18446744073709551615}, "factor": 2}"#,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        None
+    )]
+    #[case::ambiguous(
+        r#"{"recipe": {"flour": 1}, "factor": 2, "cookies": 2}"#,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        None
+    )]
+    #[case::no_yield(
+        r#"{"recipe": {"flour": 1}, "cookies": 2, "yields": 0}"#,
+        StatusCode::UNPROCESSABLE_ENTITY,
+        None
+    )]
+    #[test_log::test(tokio::test)]
+    async fn test_scale_recipe(
+        service: TestService,
+        #[case] body: &'static str,
+        #[case] expected_status: StatusCode,
+        #[case] expected_recipe: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                Request::post(routes::day7::scale())
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))?,
+            )
+            .await?;
+
+        assert_eq!(expected_status, response.status());
+
+        if let Some(expected_recipe) = expected_recipe {
+            assert_eq!(
+                serde_json::from_str::<Value>(expected_recipe)?,
+                serde_json::from_slice::<Value>(
+                    &hyper::body::to_bytes(response.into_body()).await?
+                )?
+            );
+        }
+
+        Ok(())
+    }
 }
@@ -1,45 +1,883 @@
 //! ### CCH 2023 Day 8 Solutions
 //!
 
+// Standard Library Imports
+use core::time::Duration;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+};
+
 // Third-Party Imports
 use axum::{
-    extract::{Json, Path},
+    extract::{Json, Path, State},
     http::StatusCode,
+    response::{IntoResponse, Response},
 };
+use chrono::{DateTime, Utc};
+use futures::stream::{FuturesUnordered, StreamExt};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use shuttle_secrets::SecretStore;
+use tokio::sync::OnceCell;
+use unicode_normalization::UnicodeNormalization;
 
 // Crate-Level Imports
-use crate::utils;
+use crate::{
+    clock::{Clock, SystemClock},
+    coalesce::SingleFlight,
+    errors::{AppError, ErrorEnvelope, UpstreamError},
+    metrics::ServiceMetrics,
+    outbound::Provider,
+    routes,
+    shared::SharedCache,
+    solutions::{DayModule, DayRoutes},
+    state::ShuttleAppState,
+    telemetry::send_upstream,
+    validation::{Validate, ValidatedQuery, ValidationErrors},
+};
+
+/// The PokeAPI endpoint describing individual pokemon
+const POKEMON_API: &str = "https://pokeapi.co/api/v2/pokemon";
+
+/// The PokeAPI endpoint describing pokemon species
+const SPECIES_API: &str = "https://pokeapi.co/api/v2/pokemon-species";
+
+/// PokeAPI's GraphQL endpoint (used to fetch the full pokedex at once)
+const GRAPHQL_API: &str = "https://beta.pokeapi.co/graphql/v1beta";
+
+/// The GraphQL query fetching every pokemon's id, name, and weight
+const POKEDEX_QUERY: &str = "query { pokemon_v2_pokemon { id name weight } }";
+
+/// The maximum number of names suggested for an unknown pokemon
+const MAX_SUGGESTIONS: usize = 3;
+
+/// The maximum edit distance between an
+/// unknown pokemon's name and a suggestion
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// The maximum number of pokedex ids in a single batch lookup
+const MAX_BATCH_SIZE: usize = 64;
+
+/// The maximum number of concurrent upstream weight fetches
+const MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// How long fetched weights are retained in the shared cache
+const SHARED_WEIGHT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// <editor-fold desc="// PokedexTable ...">
+
+/// When (if ever) the full pokedex is loaded into memory
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreloadMode {
+    /// Never (every pokemon is fetched individually)
+    #[default]
+    Off,
+    /// In the background, on the first pokemon lookup
+    Lazy,
+    /// In the background, as the service starts
+    Startup,
+}
+
+impl PreloadMode {
+    /// Read the mode from the `POKEDEX_PRELOAD`
+    /// secret (`"off"`, `"lazy"`, or `"startup"`)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        match secrets.get("POKEDEX_PRELOAD").as_deref() {
+            None | Some("off") => Self::Off,
+            Some("lazy") => Self::Lazy,
+            Some("startup") => Self::Startup,
+            Some(other) => {
+                tracing::error!(r#"ignoring POKEDEX_PRELOAD secret: unknown mode "{other}""#);
+                Self::Off
+            }
+        }
+    }
+}
+
+/// Every pokemon's pokedex id (by name) and
+/// weight in kilograms (by pokedex id)
+#[derive(Debug)]
+pub struct PokedexTable {
+    /// pokedex ids, by (normalized) name
+    ids: HashMap<String, u16>,
+    /// weights (in kilograms), by pokedex id
+    weights: HashMap<u16, f64>,
+    /// when the table was loaded
+    loaded_at: DateTime<Utc>,
+}
+
+impl PokedexTable {
+    /// Build a table from the supplied response
+    /// to the [full pokedex query](POKEDEX_QUERY)
+    fn from_graphql(response: &Value, loaded_at: DateTime<Utc>) -> Result<Self, UpstreamError> {
+        let pokemon = response["data"]["pokemon_v2_pokemon"]
+            .as_array()
+            .ok_or_else(|| UpstreamError::Decode(String::from("malformed pokedex response")))?;
+
+        let mut table = Self {
+            ids: HashMap::with_capacity(pokemon.len()),
+            weights: HashMap::with_capacity(pokemon.len()),
+            loaded_at,
+        };
+
+        for entry in pokemon {
+            let (Some(id), Some(name), Some(weight)) = (
+                entry["id"].as_u64().and_then(|id| u16::try_from(id).ok()),
+                entry["name"].as_str(),
+                entry["weight"].as_f64(),
+            ) else {
+                return Err(UpstreamError::Decode(format!(
+                    "malformed pokedex entry: {entry}"
+                )));
+            };
+
+            table.ids.insert(Pokedex::normalize(name), id);
+            table.weights.insert(id, weight / 10f64);
+        }
+
+        Ok(table)
+    }
+}
+
+/// How much of the pokedex is known, and how fresh it is
+#[derive(Debug, Serialize)]
+pub struct PokedexStatus {
+    /// when (if ever) the full pokedex is preloaded
+    pub mode: PreloadMode,
+    /// whether the full pokedex is currently being loaded
+    pub loading: bool,
+    /// the number of pokemon in the preloaded table
+    pub preloaded: usize,
+    /// the number of weights fetched individually
+    pub fetched: usize,
+    /// the number of names resolved individually
+    pub resolved: usize,
+    /// when the preloaded table was loaded (if it has been)
+    pub loaded_at: Option<DateTime<Utc>>,
+    /// the age of the preloaded table (in seconds)
+    pub age_secs: Option<i64>,
+}
+
+// </editor-fold desc="// PokedexTable ...">
+
+// <editor-fold desc="// Pokedex ...">
+
+/// A cache of pokemon names' pokedex ids and weights
+#[derive(Debug)]
+pub struct Pokedex {
+    /// resolved pokedex ids, by normalized name
+    ids: RwLock<HashMap<String, u16>>,
+    /// fetched weights (in kilograms), by pokedex id
+    weights: RwLock<HashMap<u16, f64>>,
+    /// every known species' name (fetched
+    /// on the first failed resolution)
+    species: OnceCell<Vec<String>>,
+    /// in-flight PokeAPI calls, by url
+    upstream: SingleFlight<UpstreamResult>,
+    /// the service's metrics (to which
+    /// coalesced calls are reported)
+    metrics: Arc<ServiceMetrics>,
+    /// the cache shared with the service's other
+    /// instances (if the service is distributed)
+    shared: Option<Arc<dyn SharedCache>>,
+    /// the client PokeAPI is called with
+    client: reqwest::Client,
+    /// when (if ever) the full pokedex is preloaded
+    preload: PreloadMode,
+    /// the preloaded pokedex (once loaded)
+    table: RwLock<Option<Arc<PokedexTable>>>,
+    /// whether the full pokedex is currently being loaded
+    loading: AtomicBool,
+    /// the source of the preloaded table's timestamp
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for Pokedex {
+    fn default() -> Self {
+        Self {
+            ids: RwLock::default(),
+            weights: RwLock::default(),
+            species: OnceCell::default(),
+            upstream: SingleFlight::default(),
+            metrics: Arc::default(),
+            shared: None,
+            client: reqwest::Client::default(),
+            preload: PreloadMode::default(),
+            table: RwLock::default(),
+            loading: AtomicBool::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
+
+/// The (shareable) outcome of a PokeAPI call, which
+/// is `None` if the requested resource doesn't exist
+type UpstreamResult = Result<Option<Value>, UpstreamError>;
+
+impl Pokedex {
+    /// Create an (empty) pokedex, reporting coalesced calls to
+    /// the supplied metrics, sharing fetched weights via the
+    /// supplied cache (if any), and timestamping preloaded
+    /// tables with the supplied clock
+    pub fn new(
+        metrics: Arc<ServiceMetrics>,
+        shared: Option<Arc<dyn SharedCache>>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            metrics,
+            shared,
+            clock,
+            ..Self::default()
+        }
+    }
+
+    /// Call PokeAPI with the supplied client (e.g.
+    /// one routed through an outbound proxy)
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Preload the full pokedex per the supplied mode
+    pub fn with_preload(mut self, preload: PreloadMode) -> Self {
+        self.preload = preload;
+        self
+    }
+
+    /// The preloaded pokedex (if loaded)
+    fn _table(&self) -> Option<Arc<PokedexTable>> {
+        self.table.read().unwrap().clone()
+    }
+
+    /// Fetch the full pokedex in a single call, replacing
+    /// any previously preloaded table, and reporting the
+    /// number of pokemon loaded
+    pub async fn preload(&self) -> Result<usize, UpstreamError> {
+        let response = UpstreamError::check(
+            send_upstream(
+                Provider::PokeApi.name(),
+                self.client
+                    .post(GRAPHQL_API)
+                    .json(&json!({ "query": POKEDEX_QUERY })),
+            )
+            .await?,
+        )?
+        .json::<Value>()
+        .await?;
+
+        self._load(&response)
+    }
+
+    /// Replace any previously preloaded table with the
+    /// one described by the supplied (full pokedex query)
+    /// response, reporting the number of pokemon loaded
+    fn _load(&self, response: &Value) -> Result<usize, UpstreamError> {
+        let table = PokedexTable::from_graphql(response, self.clock.now())?;
+        let loaded = table.weights.len();
+
+        *self.table.write().unwrap() = Some(Arc::new(table));
+
+        Ok(loaded)
+    }
+
+    /// Preload the full pokedex in the background, unless
+    /// it's already loaded (or already being loaded)
+    pub fn spawn_preload(self: &Arc<Self>) {
+        if self._table().is_some() || self.loading.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let pokedex = self.clone();
+
+        tokio::spawn(async move {
+            match pokedex.preload().await {
+                Ok(loaded) => tracing::info!("preloaded {loaded} pokemon"),
+                Err(error) => tracing::warn!("unable to preload the pokedex: {error}"),
+            }
+
+            pokedex.loading.store(false, Ordering::Release);
+        });
+    }
+
+    /// Start preloading the full pokedex (if configured
+    /// to do so as the service starts)
+    pub fn on_startup(self: &Arc<Self>) {
+        if self.preload == PreloadMode::Startup {
+            self.spawn_preload();
+        }
+    }
+
+    /// Start preloading the full pokedex (if configured
+    /// to do so lazily, and not already loaded)
+    pub fn on_lookup(self: &Arc<Self>) {
+        if self.preload == PreloadMode::Lazy {
+            self.spawn_preload();
+        }
+    }
+
+    /// Report how much of the pokedex is known (as of `now`)
+    pub fn status(&self, now: DateTime<Utc>) -> PokedexStatus {
+        let table = self._table();
+
+        PokedexStatus {
+            mode: self.preload,
+            loading: self.loading.load(Ordering::Acquire),
+            preloaded: table.as_ref().map_or(0, |table| table.weights.len()),
+            fetched: self.weights.read().unwrap().len(),
+            resolved: self.ids.read().unwrap().len(),
+            loaded_at: table.as_ref().map(|table| table.loaded_at),
+            age_secs: table
+                .as_ref()
+                .map(|table| now.signed_duration_since(table.loaded_at).num_seconds()),
+        }
+    }
+
+    /// Normalize the supplied name to PokeAPI's naming
+    /// convention (lowercase ASCII, hyphen-separated)
+    pub fn normalize(name: &str) -> String {
+        name.nfd()
+            .filter_map(|char| match char.to_ascii_lowercase() {
+                char @ ('a'..='z' | '0'..='9') => Some(char),
+                ' ' | '_' | '-' => Some('-'),
+                _ => None,
+            })
+            .collect::<String>()
+            .split('-')
+            .filter(|part| !part.is_empty())
+            .join("-")
+    }
+
+    /// Suggest the known names closest to the supplied (unknown) one
+    pub fn suggest<'names>(
+        name: &str,
+        known: impl IntoIterator<Item = &'names str>,
+    ) -> Vec<String> {
+        known
+            .into_iter()
+            .map(|candidate| (strsim::levenshtein(name, candidate), candidate))
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .sorted()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, candidate)| candidate.to_string())
+            .collect()
+    }
+
+    /// Resolve the supplied name's pokedex id, responding with
+    /// `404 Not Found` (and suggestions) for unknown names
+    pub async fn resolve(&self, name: &str) -> Result<u16, AppError> {
+        let name = Self::normalize(name);
+
+        if let Some(id) = self
+            ._table()
+            .and_then(|table| table.ids.get(&name).copied())
+        {
+            return Ok(id);
+        }
+
+        if let Some(id) = self.ids.read().unwrap().get(&name) {
+            return Ok(*id);
+        }
+
+        if let Some(id) = self._fetch_species_id(&name).await? {
+            self.ids.write().unwrap().insert(name, id);
+            return Ok(id);
+        }
+
+        let suggestions = Self::suggest(&name, self._species().await.iter().map(String::as_str));
+
+        Err(
+            ErrorEnvelope::new(StatusCode::NOT_FOUND, format!("unknown pokemon: {name}"))
+                .with_details(Some(json!({ "suggestions": suggestions })))
+                .into(),
+        )
+    }
+
+    /// Get the specified pokemon's weight (in kilograms),
+    /// fetching (and caching) it if not already known
+    pub async fn weight(&self, pokedex_id: u16) -> Result<f64, AppError> {
+        if let Some(weight) = self
+            ._table()
+            .and_then(|table| table.weights.get(&pokedex_id).copied())
+        {
+            return Ok(weight);
+        }
+
+        if let Some(weight) = self.weights.read().unwrap().get(&pokedex_id) {
+            return Ok(*weight);
+        }
+
+        let weight = self._fetch_weight(pokedex_id).await.1?;
+
+        self.weights.write().unwrap().insert(pokedex_id, weight);
+
+        Ok(weight)
+    }
+
+    /// Get the specified pokemons' weights, fetching any
+    /// not already known concurrently (but boundedly)
+    pub async fn weights(&self, pokedex_ids: &[u16]) -> WeightBatchReport {
+        let mut report = WeightBatchReport::default();
+        let mut uncached = Vec::<u16>::new();
+
+        {
+            let table = self._table();
+            let weights = self.weights.read().unwrap();
+
+            for pokedex_id in pokedex_ids.iter().copied().unique() {
+                if let Some(weight) = table
+                    .as_ref()
+                    .and_then(|table| table.weights.get(&pokedex_id))
+                    .or_else(|| weights.get(&pokedex_id))
+                {
+                    report.weights.insert(pokedex_id, *weight);
+                } else {
+                    uncached.push(pokedex_id);
+                }
+            }
+        }
+
+        let mut uncached = uncached.into_iter();
+        let mut pending = FuturesUnordered::new();
+
+        pending.extend(
+            uncached
+                .by_ref()
+                .take(MAX_CONCURRENT_FETCHES)
+                .map(|pokedex_id| self._fetch_weight(pokedex_id)),
+        );
+
+        while let Some((pokedex_id, result)) = pending.next().await {
+            match result {
+                Ok(weight) => {
+                    self.weights.write().unwrap().insert(pokedex_id, weight);
+                    report.weights.insert(pokedex_id, weight);
+                }
+                Err(error) => {
+                    report.errors.insert(pokedex_id, error.to_string());
+                }
+            }
+
+            pending.extend(
+                uncached
+                    .next()
+                    .map(|pokedex_id| self._fetch_weight(pokedex_id)),
+            );
+        }
+
+        report
+    }
+
+    /// GET the supplied PokeAPI url, sharing the outcome
+    /// of any identical call that's already in flight
+    async fn _get(&self, url: String) -> UpstreamResult {
+        let client = self.client.clone();
+
+        self.upstream
+            .run(url.clone(), &self.metrics.upstream, || async move {
+                let response = send_upstream(Provider::PokeApi.name(), client.get(url)).await?;
+
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+
+                Ok(Some(UpstreamError::check(response)?.json::<Value>().await?))
+            })
+            .await
+    }
+
+    /// Fetch the specified pokemon's weight (in kilograms) from
+    /// the shared cache (if any) or PokeAPI, keeping track of
+    /// which pokemon the result belongs to
+    async fn _fetch_weight(&self, pokedex_id: u16) -> (u16, Result<f64, AppError>) {
+        let key = format!("pokedex:weight:{pokedex_id}");
+
+        if let Some(shared) = self.shared.as_ref() {
+            if let Some(weight) = shared
+                .get(&key)
+                .await
+                .and_then(|weight| weight.parse::<f64>().ok())
+            {
+                return (pokedex_id, Ok(weight));
+            }
+        }
+
+        let weight = self
+            ._get(format!("{POKEMON_API}/{pokedex_id}"))
+            .await
+            .map_err(AppError::from)
+            .and_then(|pokemon| {
+                pokemon.ok_or_else(|| {
+                    ErrorEnvelope::new(
+                        StatusCode::NOT_FOUND,
+                        format!("no such pokemon: {pokedex_id}"),
+                    )
+                    .into()
+                })
+            })
+            .and_then(|pokemon| {
+                pokemon
+                    .get("weight")
+                    .and_then(Value::as_f64)
+                    .ok_or_else(|| {
+                        UpstreamError::Decode(format!(r#"missing "weight" key from: {pokemon}"#))
+                            .into()
+                    })
+            })
+            .map(|weight| weight / 10f64);
+
+        if let (Some(shared), Ok(weight)) = (self.shared.as_ref(), weight.as_ref()) {
+            shared
+                .set(&key, weight.to_string(), SHARED_WEIGHT_TTL)
+                .await;
+        }
+
+        (pokedex_id, weight)
+    }
+
+    /// Fetch the pokedex id of the named species
+    /// (or `None` if no such species exists)
+    async fn _fetch_species_id(&self, name: &str) -> Result<Option<u16>, AppError> {
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        let Some(species) = self._get(format!("{SPECIES_API}/{name}")).await? else {
+            return Ok(None);
+        };
+
+        species
+            .get("id")
+            .and_then(Value::as_u64)
+            .and_then(|id| u16::try_from(id).ok())
+            .map(Some)
+            .ok_or_else(|| {
+                UpstreamError::Decode(format!("malformed species data for: {name}")).into()
+            })
+    }
+
+    /// Get every known species' name (or none,
+    /// if they can't currently be fetched)
+    async fn _species(&self) -> &[String] {
+        let species = self
+            .species
+            .get_or_try_init(|| async {
+                let listing = UpstreamError::check(
+                    send_upstream(
+                        Provider::PokeApi.name(),
+                        self.client.get(format!("{SPECIES_API}?limit=10000")),
+                    )
+                    .await?,
+                )?
+                .json::<Value>()
+                .await?;
+
+                Ok::<_, UpstreamError>(
+                    listing["results"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|species| species["name"].as_str().map(String::from))
+                        .collect(),
+                )
+            })
+            .await;
+
+        match species {
+            Ok(species) => species.as_slice(),
+            Err(error) => {
+                tracing::warn!("unable to fetch pokemon species names: {error}");
+                &[]
+            }
+        }
+    }
+}
+
+// </editor-fold desc="// Pokedex ...">
+
+// <editor-fold desc="// WeightBatch ...">
+
+/// A request for several pokemons' weights at once
+#[derive(Debug, Deserialize)]
+pub struct WeightBatch {
+    /// the pokedex ids to look up
+    pub ids: Vec<u16>,
+}
+
+impl Validate for WeightBatch {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if !(1..=MAX_BATCH_SIZE).contains(&self.ids.len()) {
+            errors.add("ids", format!("must contain 1..={MAX_BATCH_SIZE} ids"));
+        }
+
+        errors.into_result()
+    }
+}
+
+/// The outcome of a [batch weight lookup](WeightBatch)
+#[derive(Debug, Default, Serialize)]
+pub struct WeightBatchReport {
+    /// weights (in kilograms), by pokedex id
+    pub weights: BTreeMap<u16, f64>,
+    /// lookup failures, by pokedex id
+    pub errors: BTreeMap<u16, String>,
+}
+
+// </editor-fold desc="// WeightBatch ...">
 
 /// Complete [Day 8: Challenge](https://console.shuttle.rs/cch/challenge/8#:~:text=⭐)
-#[tracing::instrument(ret)]
+#[tracing::instrument(ret, skip(state))]
 pub async fn fetch_pokemon_weight(
+    State(state): State<ShuttleAppState>,
     Path(pokedex_id): Path<u16>,
-) -> Result<Json<f64>, (StatusCode, String)> {
-    Ok(Json(utils::fetch_pokemon_weight(pokedex_id).await?))
+) -> Result<Json<f64>, AppError> {
+    state.pokedex.on_lookup();
+
+    Ok(Json(state.pokedex.weight(pokedex_id).await?))
 }
 
+/// Report how much of the pokedex is cached, and how fresh it is
+#[tracing::instrument(ret, skip(state))]
+pub async fn get_pokedex_cache_status(State(state): State<ShuttleAppState>) -> Json<PokedexStatus> {
+    Json(state.pokedex.status(state.clock.now()))
+}
+
+// <editor-fold desc="// DropParameters ...">
+
+/// The parameters of a (simulated) chimney drop
+#[derive(Debug, Deserialize)]
+pub struct DropParameters {
+    /// Chimney height in meters
+    #[serde(default = "DropParameters::default_height")]
+    pub height: f64,
+    /// Gravitational acceleration in m/s²
+    #[serde(default = "DropParameters::default_gravity")]
+    pub gravity: f64,
+    /// Whether to respond with an [`ImpactReport`]
+    /// instead of the bare impact momentum
+    #[serde(default)]
+    pub extended: bool,
+}
+
+impl Default for DropParameters {
+    fn default() -> Self {
+        Self {
+            height: Self::default_height(),
+            gravity: Self::default_gravity(),
+            extended: false,
+        }
+    }
+}
+
+impl Validate for DropParameters {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::default();
+
+        if !(self.height > 0.0 && self.height <= Self::MAX_HEIGHT) {
+            errors.add("height", format!("must be in (0, {}]", Self::MAX_HEIGHT));
+        }
+
+        if !(self.gravity > 0.0 && self.gravity <= Self::MAX_GRAVITY) {
+            errors.add("gravity", format!("must be in (0, {}]", Self::MAX_GRAVITY));
+        }
+
+        errors.into_result()
+    }
+}
+
+impl DropParameters {
+    /// The tallest supported chimney (in meters)
+    const MAX_HEIGHT: f64 = 10_000.0;
+
+    /// The strongest supported gravity (in m/s², roughly the sun's surface gravity)
+    const MAX_GRAVITY: f64 = 275.0;
+
+    fn default_height() -> f64 {
+        10.0
+    }
+
+    fn default_gravity() -> f64 {
+        9.825
+    }
+
+    /// Calculate the final speed with kinematic equation
+    pub fn final_speed(&self) -> f64 {
+        (2.0 * self.gravity * self.height).sqrt()
+    }
+}
+
+/// The inputs, intermediate values, and result of
+/// a (simulated) chimney drop's momentum calculation
+#[cfg_attr(test, derive(PartialEq, Deserialize))]
+#[derive(Debug, Serialize)]
+pub struct ImpactReport {
+    /// the dropped pokemon's pokedex id
+    pub pokedex_id: u16,
+    /// the dropped pokemon's weight in kilograms
+    pub weight: f64,
+    /// the chimney's height in meters
+    pub height: f64,
+    /// the gravitational acceleration in m/s²
+    pub gravity: f64,
+    /// the pokemon's speed (in m/s) at impact
+    pub final_speed: f64,
+    /// the pokemon's momentum (in N·s) at impact
+    pub momentum: f64,
+}
+
+impl ImpactReport {
+    /// Simulate dropping a pokemon of
+    /// the specified weight down a chimney
+    pub fn new(pokedex_id: u16, weight: f64, parameters: &DropParameters) -> Self {
+        let final_speed = parameters.final_speed();
+
+        Self {
+            pokedex_id,
+            weight,
+            height: parameters.height,
+            gravity: parameters.gravity,
+            final_speed,
+            momentum: weight * final_speed,
+        }
+    }
+}
+
+// </editor-fold desc="// DropParameters ...">
+
 /// Complete [Day 8: Bonus](https://console.shuttle.rs/cch/challenge/8#:~:text=🎁)
-#[allow(non_upper_case_globals)]
-#[tracing::instrument(ret)]
+#[tracing::instrument(ret, skip(state))]
 pub async fn calculate_pokemon_impact_momentum(
+    State(state): State<ShuttleAppState>,
     Path(pokedex_id): Path<u16>,
-) -> Result<Json<f64>, (StatusCode, String)> {
-    /// Gravitational acceleration in m/s²
-    const gravity: f64 = 9.825;
-    /// Chimney height in meters
-    const drop_height: f64 = 10.0;
+    ValidatedQuery(parameters): ValidatedQuery<DropParameters>,
+) -> Result<Response, AppError> {
+    state.pokedex.on_lookup();
 
-    let poke_weight = utils::fetch_pokemon_weight(pokedex_id).await?;
+    _impact_momentum(&state.pokedex, pokedex_id, &parameters).await
+}
 
-    // Calculate the final speed with kinematic equation
-    let final_speed = (2.0 * gravity * drop_height).sqrt();
+/// Look up several pokemons' weights at once
+#[tracing::instrument(ret, skip(state))]
+pub async fn fetch_pokemon_weights(
+    State(state): State<ShuttleAppState>,
+    Json(batch): Json<WeightBatch>,
+) -> Result<Json<WeightBatchReport>, AppError> {
+    batch.validate()?;
 
-    // Calculate the impact momentum
-    let momentum = poke_weight * final_speed;
+    state.pokedex.on_lookup();
 
-    Ok(Json(momentum))
+    Ok(Json(state.pokedex.weights(&batch.ids).await))
 }
 
+/// Look up a pokemon's weight by name (rather than pokedex id)
+#[tracing::instrument(ret, skip(state))]
+pub async fn fetch_pokemon_weight_by_name(
+    State(state): State<ShuttleAppState>,
+    Path(name): Path<String>,
+) -> Result<Json<f64>, AppError> {
+    state.pokedex.on_lookup();
+
+    let pokedex_id = state.pokedex.resolve(&name).await?;
+
+    state.pokedex.weight(pokedex_id).await.map(Json)
+}
+
+/// Calculate a pokemon's impact momentum by name (rather than pokedex id)
+#[tracing::instrument(ret, skip(state))]
+pub async fn calculate_pokemon_impact_momentum_by_name(
+    State(state): State<ShuttleAppState>,
+    Path(name): Path<String>,
+    ValidatedQuery(parameters): ValidatedQuery<DropParameters>,
+) -> Result<Response, AppError> {
+    state.pokedex.on_lookup();
+
+    let pokedex_id = state.pokedex.resolve(&name).await?;
+
+    _impact_momentum(&state.pokedex, pokedex_id, &parameters).await
+}
+
+async fn _impact_momentum(
+    pokedex: &Pokedex,
+    pokedex_id: u16,
+    parameters: &DropParameters,
+) -> Result<Response, AppError> {
+    let poke_weight = pokedex.weight(pokedex_id).await?;
+
+    let report = ImpactReport::new(pokedex_id, poke_weight, parameters);
+
+    if parameters.extended {
+        Ok(Json(report).into_response())
+    } else {
+        Ok(Json(report.momentum).into_response())
+    }
+}
+
+// <editor-fold desc="// DayModule ...">
+
+/// The [Day 8](https://console.shuttle.rs/cch/challenge/8) solutions
+#[derive(Debug)]
+pub struct Day8;
+
+impl DayModule for Day8 {
+    fn day() -> i8 {
+        8
+    }
+
+    fn name() -> &'static str {
+        "Pokémon Physics"
+    }
+
+    fn routes() -> DayRoutes {
+        DayRoutes::new()
+            .route(day_route!(
+                8,
+                GET routes::day8::WEIGHT,
+                fetch_pokemon_weight,
+                "Look up a pokemon's weight"
+            ))
+            .route(day_route!(
+                8,
+                GET routes::day8::DROP,
+                calculate_pokemon_impact_momentum,
+                "Drop a pokemon down a chimney"
+            ))
+            .route(day_route!(
+                8,
+                POST routes::day8::WEIGHT_BATCH,
+                fetch_pokemon_weights,
+                "Look up several pokemons' weights"
+            ))
+            .route(day_route!(
+                8,
+                GET routes::day8::WEIGHT_BY_NAME,
+                fetch_pokemon_weight_by_name,
+                "Look up a pokemon's weight by name"
+            ))
+            .route(day_route!(
+                8,
+                GET routes::day8::DROP_BY_NAME,
+                calculate_pokemon_impact_momentum_by_name,
+                "Drop a pokemon down a chimney by name"
+            ))
+            .route(day_route!(
+                8,
+                GET routes::day8::CACHE_STATUS,
+                get_pokedex_cache_status,
+                "Report the pokedex cache's coverage and age"
+            ))
+    }
+}
+
+// </editor-fold desc="// DayModule ...">
+
 #[cfg(test)]
 mod tests {
     //! ## I/O-free Unit Tests
@@ -48,7 +886,7 @@ mod tests {
 
     // Standard Library Imports
     use core::{cmp::PartialEq, fmt::Debug, ops::BitOr, str::FromStr};
-    use std::collections::HashMap;
+    use std::{collections::HashMap, sync::Arc};
 
     // Third-Party Imports
     use axum::{
@@ -68,6 +906,14 @@ mod tests {
     use tower::{MakeService, ServiceExt};
 
     // Crate-Level Imports
+    use super::{
+        DropParameters, ImpactReport, Pokedex, PokedexTable, PreloadMode, WeightBatch,
+        MAX_BATCH_SIZE,
+    };
+    use crate::clock::{Clock, FakeClock};
+    use crate::routes;
+    use crate::state::ShuttleAppState;
+    use crate::utils::state;
     use crate::utils::{service, TestService};
 
     // <editor-fold desc="// Helper Types ...">
@@ -121,8 +967,8 @@ mod tests {
     /// Test that `fetch_pokemon_weight` and `calculate_pokemon_impact_momentum`
     /// satisfy the conditions of [CCH 2023 Challenge 6](https://console.shuttle.rs/cch/challenge/8)
     #[rstest]
-    #[case::challenge_example("/8/weight/25", StatusCode::OK, "6")]
-    #[case::bonus_example("/8/drop/25", StatusCode::OK, "84.10707461325713")]
+    #[case::challenge_example(&routes::day8::weight(25), StatusCode::OK, "6")]
+    #[case::bonus_example(&routes::day8::drop(25), StatusCode::OK, "84.10707461325713")]
     #[test_log::test(tokio::test)]
     async fn test_challenge_eight(
         service: TestService,
@@ -158,4 +1004,268 @@ mod tests {
 
         Ok(())
     }
+
+    /// Test that `ImpactReport` reproduces the challenge's
+    /// momentum by default and honors custom parameters
+    #[rstest]
+    #[case::defaults(DropParameters::default(), 84.10707461325713)]
+    #[case::moon(
+        DropParameters {
+            height: 10.0,
+            gravity: 1.62,
+            extended: true,
+        },
+        6.0 * 32.4f64.sqrt(),
+    )]
+    #[test_log::test]
+    fn test_impact_report(#[case] parameters: DropParameters, #[case] expected_momentum: f64) {
+        let report = ImpactReport::new(25, 6.0, &parameters);
+
+        assert_eq!(expected_momentum, report.momentum);
+        assert_eq!(report.weight * report.final_speed, report.momentum);
+        assert_eq!(
+            (parameters.height, parameters.gravity),
+            (report.height, report.gravity)
+        );
+    }
+
+    /// Test that `calculate_pokemon_impact_momentum` rejects
+    /// out-of-bounds parameters without fetching the pokemon
+    #[rstest]
+    #[case::negative_height("height=-1")]
+    #[case::zero_gravity("gravity=0")]
+    #[case::absurd_gravity("gravity=1e9")]
+    #[case::nan_height("height=NaN")]
+    #[case::not_a_number("height=tall")]
+    #[test_log::test(tokio::test)]
+    async fn test_drop_rejects_invalid_parameters(
+        service: TestService,
+        #[case] query: &str,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(format!("{}?{query}", routes::day8::drop(25)).as_str())
+            .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+        Ok(())
+    }
+
+    /// Test that `Pokedex::normalize` maps user-supplied
+    /// names onto PokeAPI's naming convention
+    #[rstest]
+    #[case::lowercase("pikachu", "pikachu")]
+    #[case::uppercase("PIKACHU", "pikachu")]
+    #[case::spaces(" Mr. Mime ", "mr-mime")]
+    #[case::underscores("tapu_koko", "tapu-koko")]
+    #[case::repeated_separators("ho--oh", "ho-oh")]
+    #[case::accents("Flabébé", "flabebe")]
+    #[case::apostrophes("Farfetch'd", "farfetchd")]
+    #[test_log::test]
+    fn test_normalize_pokemon_name(#[case] name: &str, #[case] expected: &str) {
+        assert_str_eq!(expected, Pokedex::normalize(name));
+    }
+
+    /// Test that `Pokedex::suggest` offers only
+    /// the nearest known names, closest first
+    #[rstest]
+    #[case::typo("pikachi", &["pikachu"])]
+    #[case::tied("pichu", &["pikachu", "raichu"])]
+    #[case::nothing_close("missingno", &[])]
+    #[test_log::test]
+    fn test_suggest_pokemon_names(#[case] name: &str, #[case] expected: &[&str]) {
+        let known = ["bulbasaur", "ivysaur", "venusaur", "pikachu", "raichu"];
+
+        assert_eq!(expected, Pokedex::suggest(name, known).as_slice());
+    }
+
+    /// Test that `Pokedex::resolve` answers
+    /// previously resolved names from its cache
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_resolve_cached_pokemon_name() {
+        let names = Pokedex::default();
+
+        names.ids.write().unwrap().insert("mr-mime".into(), 122);
+
+        assert_eq!(Some(122), names.resolve("Mr. Mime").await.ok());
+    }
+
+    /// Test that the name-based drop route validates
+    /// its parameters before resolving the pokemon
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_drop_by_name_rejects_invalid_parameters(
+        service: TestService,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(format!("{}?gravity=0", routes::day8::drop_by_name("pikachu")).as_str())
+            .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+        Ok(())
+    }
+
+    /// Test that `Pokedex::weights` answers previously fetched
+    /// weights from its cache, reporting each id only once
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_cached_pokemon_weights() {
+        let pokedex = Pokedex::default();
+
+        pokedex
+            .weights
+            .write()
+            .unwrap()
+            .extend([(1, 6.9), (4, 8.5), (7, 9.0)]);
+
+        let report = pokedex.weights(&[7, 1, 7, 4]).await;
+
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            vec![(1, 6.9), (4, 8.5), (7, 9.0)],
+            report.weights.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    /// Test that `fetch_pokemon_weights` rejects empty and
+    /// oversized batches without fetching any pokemon
+    #[rstest]
+    #[case::empty(Vec::new())]
+    #[case::oversized((1..=MAX_BATCH_SIZE as u16 + 1).collect())]
+    #[test_log::test(tokio::test)]
+    async fn test_weight_batch_rejects_invalid_sizes(
+        service: TestService,
+        #[case] ids: Vec<u16>,
+    ) -> anyhow::Result<()> {
+        let response = service
+            .resolve(
+                Request::post(routes::day8::weight_batch())
+                    .header(headers::CONTENT_TYPE, "application/json")
+                    .body(Body::from(serde_json::json!({ "ids": ids }).to_string()))?,
+            )
+            .await?;
+
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+        Ok(())
+    }
+
+    /// Test that the full pokedex query's response is
+    /// loaded into a table keyed by normalized name and id
+    #[rstest]
+    #[case::valid(
+        serde_json::json!({"data": {"pokemon_v2_pokemon": [
+            {"id": 25, "name": "pikachu", "weight": 60},
+            {"id": 122, "name": "mr-mime", "weight": 545},
+        ]}}),
+        Some(2)
+    )]
+    #[case::missing_data(serde_json::json!({"errors": []}), None)]
+    #[case::malformed_entry(
+        serde_json::json!({"data": {"pokemon_v2_pokemon": [{"id": 25, "name": "pikachu"}]}}),
+        None
+    )]
+    #[test_log::test]
+    fn test_pokedex_table_from_graphql(#[case] response: Value, #[case] expected: Option<usize>) {
+        let table = PokedexTable::from_graphql(&response, chrono::Utc::now());
+
+        assert_eq!(
+            expected,
+            table.as_ref().ok().map(|table| table.weights.len())
+        );
+
+        if let Ok(table) = table {
+            assert_eq!(Some(&122), table.ids.get("mr-mime"));
+            assert_eq!(Some(&6.0), table.weights.get(&25));
+        }
+    }
+
+    /// Test that preloaded tables are timestamped by the pokedex's clock
+    #[rstest]
+    #[test_log::test]
+    fn test_pokedex_preload_timestamp() -> anyhow::Result<()> {
+        let clock = Arc::new(FakeClock::new("2023-12-08T00:00:00Z".parse()?));
+        let pokedex = Pokedex::new(Arc::default(), None, clock.clone());
+
+        let loaded = pokedex
+            ._load(&serde_json::json!({"data": {"pokemon_v2_pokemon": [
+                {"id": 25, "name": "pikachu", "weight": 60},
+            ]}}))
+            .expect("the response is well-formed");
+
+        clock.advance(chrono::Duration::seconds(30));
+
+        let status = pokedex.status(clock.now());
+
+        assert_eq!(1, loaded);
+        assert_eq!(Some("2023-12-08T00:00:00Z".parse()?), status.loaded_at);
+        assert_eq!(Some(30), status.age_secs);
+
+        Ok(())
+    }
+
+    /// Test that the preload mode is read from its secret
+    #[rstest]
+    #[case::unset(None, PreloadMode::Off)]
+    #[case::lazy(Some("lazy"), PreloadMode::Lazy)]
+    #[case::startup(Some("startup"), PreloadMode::Startup)]
+    #[case::unknown(Some("eventually"), PreloadMode::Off)]
+    #[test_log::test]
+    fn test_preload_mode_from_secrets(#[case] secret: Option<&str>, #[case] expected: PreloadMode) {
+        let secrets = shuttle_secrets::SecretStore::new(std::collections::BTreeMap::from_iter(
+            secret.map(|mode| (String::from("POKEDEX_PRELOAD"), mode.to_string().into())),
+        ));
+
+        assert_eq!(expected, PreloadMode::from_secrets(&secrets));
+    }
+
+    /// Test that a preloaded pokedex answers weight lookups (by
+    /// id and name) without calling PokeAPI, and that its
+    /// coverage and age are reported
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_preloaded_pokedex(state: ShuttleAppState) -> anyhow::Result<()> {
+        let loaded_at = state.clock.now() - chrono::Duration::seconds(90);
+        let table = PokedexTable::from_graphql(
+            &serde_json::json!({"data": {"pokemon_v2_pokemon": [
+                {"id": 25, "name": "pikachu", "weight": 60},
+            ]}}),
+            loaded_at,
+        )
+        .expect("the response is well-formed");
+
+        *state.pokedex.table.write().unwrap() = Some(std::sync::Arc::new(table));
+
+        for url in [
+            routes::day8::weight(25),
+            routes::day8::weight_by_name("Pikachu"),
+        ] {
+            let response = TestService::from(crate::router(state.clone()))
+                .resolve(url.as_str())
+                .await?;
+
+            assert_eq!(StatusCode::OK, response.status());
+            assert_eq!(
+                serde_json::json!(6.0),
+                serde_json::from_slice::<Value>(
+                    &hyper::body::to_bytes(response.into_body()).await?
+                )?
+            );
+        }
+
+        let response = TestService::from(crate::router(state))
+            .resolve(routes::day8::cache_status())
+            .await?;
+        let status: Value =
+            serde_json::from_slice(&hyper::body::to_bytes(response.into_body()).await?)?;
+
+        assert_eq!(serde_json::json!("off"), status["mode"]);
+        assert_eq!(serde_json::json!(1), status["preloaded"]);
+        assert_eq!(serde_json::json!(0), status["fetched"]);
+        assert!(status["age_secs"].as_i64().is_some_and(|age| 90 <= age));
+
+        Ok(())
+    }
 }
@@ -1,7 +1,54 @@
 //! ## Solutions
 //!
 
+// Third-Party Imports
+use axum::{
+    extract::Json,
+    routing::{MethodRouter, Router},
+};
+use serde::Serialize;
+
+// Crate-Level Imports
+use crate::state::ShuttleAppState;
+
+/// Declare a [`DayRoute`] (i.e. one of a solutions module's
+/// routes, along with its [metadata](RouteDoc)), as in:
+///
+/// ```ignore
+/// day_route!(13, POST routes::day13::ORDERS, create_orders, "Insert gift orders")
+/// ```
+///
+/// Routes requiring authorization name their scheme
+/// with a trailing `auth = "<scheme>"` argument
+macro_rules! day_route {
+    ($day:literal, $method:ident $path:expr, $handler:expr, $summary:literal $(,)?) => {
+        day_route!(@declare $day, $method $path, $handler, $summary, None)
+    };
+    (
+        $day:literal,
+        $method:ident $path:expr,
+        $handler:expr,
+        $summary:literal,
+        auth = $auth:literal $(,)?
+    ) => {
+        day_route!(@declare $day, $method $path, $handler, $summary, Some($auth))
+    };
+    (@declare $day:literal, $method:ident $path:expr, $handler:expr, $summary:literal, $auth:expr) => {
+        $crate::solutions::DayRoute::new(
+            $crate::solutions::RouteDoc {
+                day: $day,
+                method: stringify!($method),
+                path: $path,
+                summary: $summary,
+                auth: $auth,
+            },
+            ::axum::routing::on(::axum::routing::MethodFilter::$method, $handler),
+        )
+    };
+}
+
 // Module Declarations
+pub mod counters;
 #[path = "day-1.rs"]
 pub mod day_1;
 #[path = "day-11.rs"]
@@ -37,7 +84,7 @@ pub mod day_8;
 
 #[allow(unused_imports)]
 pub use self::{
-    day_1::{calculate_sled_id, cube_the_bits},
+    day_1::{calculate_sled_id, cube_the_bits, dispatch_sled_id},
     day_11::{calculate_magical_red_pixel_count, serve_static_asset},
     day_12::{
         analyze_ulids, retrieve_packet_id_timestamp, santas_ulid_hug_box, store_packet_id_timestamp,
@@ -48,25 +95,254 @@ pub use self::{
     day_14::{render_html_safe, render_html_unsafe},
     day_15::{assess_naughty_or_nice, game_of_the_year},
     day_18::{
-        create_regions, get_order_count_by_region, get_top_n_gifts_by_region, reset_day_18_schema,
+        create_regional_orders, create_regions, get_order_count_by_region,
+        get_top_n_gifts_by_region, reset_day_18_schema,
     },
     day_19::{
-        connect_to_chat_room, get_current_chat_count, play_socket_ping_pong, reset_chat_count,
-        ChatRoomState,
+        connect_to_chat_room, get_chat_room_presence, get_current_chat_count,
+        get_recent_chat_messages, play_socket_ping_pong, reset_chat_count, stream_chat_transcript,
+        wait_for_chat_count_change, ChatRoomState,
     },
     day_20::{get_archived_file_count, get_total_archived_file_size, git_blame_cookie_hunt},
     day_21::{resolve_country_from_s2_cell, resolve_s2_cell_center},
-    day_22::{analyze_star_chart, locate_lonely_int},
-    day_4::{calculate_reindeer_strength, summarize_reindeer_contest},
+    day_22::{analyze_star_chart, locate_lonely_int, verify_integer_dump},
+    day_4::{
+        calculate_reindeer_strength, get_reindeer_leaderboard, save_reindeer_contest,
+        summarize_reindeer_contest,
+    },
     day_5::slice_the_loop,
     day_6::count_elves,
     day_7::{bake_cookies_from_recipe_and_pantry, decode_cookie_recipe},
     day_8::{calculate_pokemon_impact_momentum, fetch_pokemon_weight},
     day_minus_1::{hello_world, throw_error},
 };
+// <editor-fold desc="// DayModule ...">
+
+/// A solutions module, able to describe and route itself
+pub trait DayModule {
+    /// The challenge day the module solves
+    fn day() -> i8;
+
+    /// The module's elf-readable name
+    fn name() -> &'static str;
+
+    /// The module's (declared) routes
+    fn routes() -> DayRoutes;
+}
+
+/// A [`DayModule`], as listed in the [`DAYS`] registry
+#[derive(Clone, Copy, Debug)]
+pub struct DayRegistration {
+    /// see [`DayModule::day`]
+    pub day: fn() -> i8,
+    /// see [`DayModule::name`]
+    pub name: fn() -> &'static str,
+    /// see [`DayModule::routes`]
+    pub routes: fn() -> DayRoutes,
+}
+
+impl DayRegistration {
+    /// Register the specified module
+    pub const fn of<Module: DayModule>() -> Self {
+        Self {
+            day: Module::day,
+            name: Module::name,
+            routes: Module::routes,
+        }
+    }
+}
+
+/// Every registered solutions module (in challenge order)
+pub static DAYS: [DayRegistration; 17] = [
+    DayRegistration::of::<day_minus_1::DayMinus1>(),
+    DayRegistration::of::<day_1::Day1>(),
+    DayRegistration::of::<day_4::Day4>(),
+    DayRegistration::of::<day_5::Day5>(),
+    DayRegistration::of::<day_6::Day6>(),
+    DayRegistration::of::<day_7::Day7>(),
+    DayRegistration::of::<day_8::Day8>(),
+    DayRegistration::of::<day_11::Day11>(),
+    DayRegistration::of::<day_12::Day12>(),
+    DayRegistration::of::<day_13::Day13>(),
+    DayRegistration::of::<day_14::Day14>(),
+    DayRegistration::of::<day_15::Day15>(),
+    DayRegistration::of::<day_18::Day18>(),
+    DayRegistration::of::<day_19::Day19>(),
+    DayRegistration::of::<day_20::Day20>(),
+    DayRegistration::of::<day_21::Day21>(),
+    DayRegistration::of::<day_22::Day22>(),
+];
+
+/// A registered solutions module's route, as listed by [`list_days`]
+#[cfg_attr(test, derive(serde::Deserialize, Eq, PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct RouteSummary {
+    /// see [`RouteDoc::method`]
+    pub method: String,
+    /// see [`RouteDoc::path`]
+    pub path: String,
+    /// see [`RouteDoc::summary`]
+    pub summary: String,
+    /// see [`RouteDoc::auth`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<String>,
+}
+
+impl From<&RouteDoc> for RouteSummary {
+    fn from(doc: &RouteDoc) -> Self {
+        Self {
+            method: doc.method.to_string(),
+            path: doc.path.to_string(),
+            summary: doc.summary.to_string(),
+            auth: doc.auth.map(String::from),
+        }
+    }
+}
+
+/// A registered solutions module's metadata
+#[cfg_attr(test, derive(serde::Deserialize, Eq, PartialEq))]
+#[derive(Debug, Serialize)]
+pub struct DaySummary {
+    /// the challenge day the module solves
+    pub day: i8,
+    /// the module's elf-readable name
+    pub name: String,
+    /// the module's routes
+    pub routes: Vec<RouteSummary>,
+}
+
+impl From<&DayRegistration> for DaySummary {
+    fn from(registration: &DayRegistration) -> Self {
+        Self {
+            day: (registration.day)(),
+            name: (registration.name)().to_string(),
+            routes: (registration.routes)()
+                .docs()
+                .iter()
+                .map(RouteSummary::from)
+                .collect(),
+        }
+    }
+}
+
+/// Create a `Router` serving every registered solutions module
+pub fn routes() -> Router<ShuttleAppState> {
+    DAYS.iter().fold(Router::new(), |router, registration| {
+        router.merge((registration.routes)().into_router())
+    })
+}
+
+/// Get the metadata of every registered solutions module's routes
+pub fn route_docs() -> Vec<RouteDoc> {
+    DAYS.iter()
+        .flat_map(|registration| (registration.routes)().docs().to_vec())
+        .collect()
+}
+
+/// List every registered solutions module
+#[tracing::instrument(ret)]
+pub async fn list_days() -> Json<Vec<DaySummary>> {
+    Json(DAYS.iter().map(DaySummary::from).collect())
+}
+
+// </editor-fold desc="// DayModule ...">
+
+// <editor-fold desc="// DayRoutes ...">
+
+/// A solutions module's route's metadata, as
+/// declared (once) via [`day_route!`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RouteDoc {
+    /// the challenge day the route belongs to
+    pub day: i8,
+    /// the route's (upper-case) HTTP method
+    pub method: &'static str,
+    /// the route's pattern (see [`crate::routes`])
+    pub path: &'static str,
+    /// a short, elf-readable description of the route
+    pub summary: &'static str,
+    /// the authorization scheme the route requires (if any)
+    pub auth: Option<&'static str>,
+}
+
+/// A solutions module's route, and its [metadata](RouteDoc)
+#[derive(Debug)]
+pub struct DayRoute {
+    /// the route's metadata
+    pub doc: RouteDoc,
+    /// the route's handler
+    router: MethodRouter<ShuttleAppState>,
+}
+
+impl DayRoute {
+    /// Create a route from its metadata and handler
+    pub fn new(doc: RouteDoc, router: MethodRouter<ShuttleAppState>) -> Self {
+        Self { doc, router }
+    }
+
+    /// Transform the route's handler (e.g. to layer it)
+    pub fn map(
+        mut self,
+        transform: impl FnOnce(MethodRouter<ShuttleAppState>) -> MethodRouter<ShuttleAppState>,
+    ) -> Self {
+        self.router = transform(self.router);
+        self
+    }
+}
+
+/// A solutions module's (declared) [routes](DayRoute)
+#[derive(Debug)]
+pub struct DayRoutes {
+    /// every route's metadata (in declaration order)
+    docs: Vec<RouteDoc>,
+    /// the router serving every route
+    router: Router<ShuttleAppState>,
+}
+
+impl Default for DayRoutes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DayRoutes {
+    /// Create an empty set of routes
+    pub fn new() -> Self {
+        Self {
+            docs: Vec::new(),
+            router: Router::new(),
+        }
+    }
+
+    /// Add the supplied route (merging it with any
+    /// other methods declared for the same path)
+    pub fn route(mut self, route: DayRoute) -> Self {
+        self.router = self.router.route(route.doc.path, route.router);
+        self.docs.push(route.doc);
+        self
+    }
+
+    /// Get every route's metadata
+    pub fn docs(&self) -> &[RouteDoc] {
+        &self.docs
+    }
+
+    /// Get the router serving every route
+    pub fn into_router(self) -> Router<ShuttleAppState> {
+        self.router
+    }
+}
+
+// </editor-fold desc="// DayRoutes ...">
+
 pub mod day_minus_1 {
     use axum::{http::StatusCode, response::IntoResponse};
 
+    use crate::{
+        routes,
+        solutions::{DayModule, DayRoutes},
+    };
+
     /// Complete [Day -1: Challenge](https://console.shuttle.rs/cch/challenge/-1#:~:text=⭐)
     #[tracing::instrument(ret)]
     pub async fn hello_world() -> &'static str {
@@ -79,6 +355,40 @@ pub mod day_minus_1 {
         (StatusCode::INTERNAL_SERVER_ERROR, "Gimme them bonus points")
     }
 
+    // <editor-fold desc="// DayModule ...">
+
+    /// The [Day -1](https://console.shuttle.rs/cch/challenge/-1) solutions
+    #[derive(Debug)]
+    pub struct DayMinus1;
+
+    impl DayModule for DayMinus1 {
+        fn day() -> i8 {
+            -1
+        }
+
+        fn name() -> &'static str {
+            "Warmup"
+        }
+
+        fn routes() -> DayRoutes {
+            DayRoutes::new()
+                .route(day_route!(
+                    -1,
+                    GET routes::day_minus_1::ROOT,
+                    hello_world,
+                    "Greet the grader"
+                ))
+                .route(day_route!(
+                    -1,
+                    GET routes::day_minus_1::ERROR,
+                    throw_error,
+                    "Fail on purpose"
+                ))
+        }
+    }
+
+    // </editor-fold desc="// DayModule ...">
+
     #[cfg(test)]
     mod tests {
         //! ## I/O-free Unit Tests
@@ -107,20 +417,21 @@ pub mod day_minus_1 {
         use tower::{MakeService, ServiceExt};
 
         // Crate-Level Imports
+        use crate::routes;
         use crate::utils::{service, TestService};
 
         /// Test that `hello_world` and `throw_error`
         /// satisfy the conditions of [CCH 2023 Challenge -1](https://console.shuttle.rs/cch/challenge/-1)
         #[rstest]
         #[case::hello_world(
-            "/",
+            routes::day_minus_1::root(),
             StatusCode::OK,  // no-reformat
             b"Hello Shuttle CCH 2023!",
         )]
         #[case::throw_error(
-            "/-1/error",
+            routes::day_minus_1::error(),
             StatusCode::INTERNAL_SERVER_ERROR,
-            b"Gimme them bonus points"
+            br#"{"error":{"code":"internal_server_error","status":500,"message":"Gimme them bonus points","details":null,"request_id":"abc123"}}"#
         )]
         #[test_log::test(tokio::test)]
         async fn test_challenge_minus_one(
@@ -129,7 +440,13 @@ pub mod day_minus_1 {
             #[case] expected_status: StatusCode,
             #[case] expected_content: &[u8],
         ) -> anyhow::Result<()> {
-            let response = service.resolve(url).await?;
+            let response = service
+                .resolve(
+                    Request::get(url)
+                        .header("x-request-id", "abc123")
+                        .body(Body::empty())?,
+                )
+                .await?;
 
             assert_eq!(
                 expected_status,
@@ -150,3 +467,74 @@ pub mod day_minus_1 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::collections::HashSet;
+
+    // Third-Party Imports
+    use axum::{
+        body::{Body, HttpBody},
+        http::{Request, StatusCode},
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use super::{route_docs, DaySummary, DAYS};
+    use crate::routes;
+    use crate::utils::{service, TestService};
+
+    /// Test that `list_days` lists every registered
+    /// solutions module exactly once, in challenge order
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_list_days(service: TestService) -> anyhow::Result<()> {
+        let response = service
+            .resolve(Request::get(routes::days()).body(Body::empty())?)
+            .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+
+        let days: Vec<DaySummary> =
+            serde_json::from_slice(response.into_body().data().await.unwrap()?.as_ref())?;
+
+        assert_eq!(DAYS.len(), days.len());
+        assert_eq!(
+            vec![-1, 1, 4, 5, 6, 7, 8, 11, 12, 13, 14, 15, 18, 19, 20, 21, 22],
+            days.iter().map(|day| day.day).collect::<Vec<i8>>(),
+        );
+        assert!(days.iter().all(|day| !day.name.is_empty()));
+        assert!(days.iter().all(|day| !day.routes.is_empty()));
+
+        Ok(())
+    }
+
+    /// Test that every module's declared routes belong to it,
+    /// and that no method/path pair is declared more than once
+    #[rstest]
+    #[test_log::test]
+    fn test_declared_routes() {
+        let mut declared = HashSet::new();
+
+        for registration in DAYS.iter() {
+            for doc in (registration.routes)().docs() {
+                assert_eq!((registration.day)(), doc.day, "{} {}", doc.method, doc.path);
+                assert!(!doc.summary.is_empty(), "{} {}", doc.method, doc.path);
+                assert!(
+                    declared.insert((doc.method, doc.path)),
+                    "{} {} is declared twice",
+                    doc.method,
+                    doc.path
+                );
+            }
+        }
+
+        assert_eq!(declared.len(), route_docs().len());
+    }
+}
@@ -12,6 +12,7 @@ use std::{
 };
 
 // Third-Party Imports
+use anyhow::Context as _;
 use axum::extract::FromRef;
 #[allow(unused_imports)]
 use axum_template::{
@@ -19,15 +20,173 @@ use axum_template::{
     Key, RenderHtml,
 };
 use handlebars::{Handlebars, TemplateError};
+use serde::{Deserialize, Serialize};
 
-use shuttle_persist::{PersistError as PersistenceError, PersistInstance as Persistence};
+use shuttle_persist::PersistInstance as Persistence;
 use shuttle_secrets::SecretStore;
 
 // Crate-Level Imports
-use crate::solutions::day_19::ChatRoomState;
+use crate::{
+    abuse::{AbuseGuard, TrustedProxies},
+    admin::{AdminAuth, BasicAuth},
+    assets::{AssetCache, AssetManifest},
+    clock::{Clock, SystemClock},
+    datasource::MetricsHistory,
+    examples::{ExampleCatalog, EXAMPLES_DIR},
+    jobs::{JobQueue, JobTracker},
+    metrics::ServiceMetrics,
+    middleware::SecurityHeaders,
+    outbound::OutboundClients,
+    outbox::Outbox,
+    persistence::{EncryptedPersistence, Keyring},
+    random::{RandomSource, StdRandom},
+    redaction::RedactionRules,
+    reporting::ErrorReporter,
+    scheduler::Scheduler,
+    sessions::SessionStore,
+    shared::SharedState,
+    shedding::{ConcurrencyLimits, LoadShedder, RateLimits},
+    solutions::{
+        counters::{Counters, VIEWS_COUNTER},
+        day_19::{ChatArchive, ChatRoomState, RoomTokens},
+        day_21::{geocoder_from_secrets, CountryNames, Geocoder, GeocoderQuota},
+        day_22::StarChartCache,
+        day_7::IngredientAliases,
+        day_8::{Pokedex, PreloadMode},
+    },
+    telemetry::TracingControl,
+    templating::{self, TemplateLimits},
+};
 
 pub(super) type TemplateEngine = HandlebarsEngine<Handlebars<'static>>;
 
+/// The secrets whose absence leaves a subsystem on its
+/// defaults (or disabled), by the subsystem they configure
+const NOTABLE_SECRETS: [(&str, &str); 7] = [
+    ("admin", "ADMIN_TOKEN"),
+    ("asset_browser", "ASSETS_CREDENTIALS"),
+    ("outbox", "OUTBOX_WEBHOOK_URL"),
+    ("persistence", "PERSISTENCE_KEY"),
+    ("reporter", "SENTRY_DSN"),
+    ("sessions", "SESSION_KEY"),
+    ("shared", "REDIS_URL"),
+];
+
+// <editor-fold desc="// InitReport ...">
+
+/// What became of the service's subsystems
+/// as its [state](ShuttleAppState) was initialized
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct InitReport {
+    /// whether (non-critical) subsystems were allowed to
+    /// fail, per the `ALLOW_DEGRADED_START` secret
+    pub allow_degraded: bool,
+    /// the subsystems left on their defaults (or disabled)
+    pub defaulted: Vec<String>,
+    /// the notable secrets that weren't set
+    pub missing_secrets: Vec<String>,
+    /// the directory values are persisted to (if
+    /// chosen by the service, rather than supplied)
+    pub persistence_path: Option<String>,
+    /// the number of compiled templates (if the
+    /// engine was built by the service, rather than supplied)
+    pub template_count: Option<usize>,
+    /// why each (non-critical) subsystem that
+    /// failed to initialize did so, by subsystem
+    pub failures: BTreeMap<String, String>,
+}
+
+impl InitReport {
+    /// Start a report on initializing the
+    /// service's state with the supplied secrets
+    fn new(secrets: &SecretStore) -> Self {
+        let allow_degraded = secrets
+            .get("ALLOW_DEGRADED_START")
+            .and_then(|value| {
+                value
+                    .parse::<bool>()
+                    .map_err(|error| {
+                        tracing::error!("ignoring ALLOW_DEGRADED_START secret: {error}")
+                    })
+                    .ok()
+            })
+            .unwrap_or(false);
+
+        let (defaulted, missing_secrets) = NOTABLE_SECRETS
+            .into_iter()
+            .filter(|(_, secret)| secrets.get(secret).is_none())
+            .map(|(subsystem, secret)| (subsystem.to_string(), secret.to_string()))
+            .unzip();
+
+        Self {
+            allow_degraded,
+            defaulted,
+            missing_secrets,
+            ..Self::default()
+        }
+    }
+
+    /// Whether any (non-critical) subsystem failed to initialize
+    pub fn is_degraded(&self) -> bool {
+        !self.failures.is_empty()
+    }
+
+    /// Record that the named subsystem was left on its defaults
+    fn defaulted(&mut self, subsystem: &str) {
+        self.defaulted.push(subsystem.to_string());
+    }
+
+    /// Unwrap the named (non-critical) subsystem's initialization
+    /// outcome, recording its failure and falling back to the
+    /// supplied default instead if degraded starts are allowed
+    fn recover<T, E: Into<anyhow::Error>>(
+        &mut self,
+        subsystem: &str,
+        outcome: Result<T, E>,
+        fallback: impl FnOnce() -> T,
+    ) -> anyhow::Result<T> {
+        match outcome.map_err(Into::into) {
+            Ok(value) => Ok(value),
+            Err(error) if self.allow_degraded => {
+                tracing::error!(
+                    "{subsystem} failed to initialize (continuing degraded): {error:?}"
+                );
+                self.failures
+                    .insert(subsystem.to_string(), format!("{error:#}"));
+                self.defaulted(subsystem);
+                Ok(fallback())
+            }
+            Err(error) => Err(error.context(format!("initializing {subsystem}"))),
+        }
+    }
+
+    /// Log the report's findings
+    fn log(&self) {
+        if !self.missing_secrets.is_empty() {
+            tracing::warn!(
+                "unset secrets (using defaults): {}",
+                self.missing_secrets.join(", ")
+            );
+        }
+
+        if self.is_degraded() {
+            tracing::warn!(
+                "started degraded, without: {}",
+                self.failures.keys().cloned().collect::<Vec<_>>().join(", ")
+            );
+        }
+
+        tracing::info!(
+            persistence_path = self.persistence_path.as_deref(),
+            template_count = self.template_count,
+            defaulted = self.defaulted.join(", "),
+            "service state initialized"
+        );
+    }
+}
+
+// </editor-fold desc="// InitReport ...">
+
 // <editor-fold desc="// ShuttleAppState ...">
 
 /// The service's "shared" state
@@ -43,12 +202,106 @@ pub struct ShuttleAppState {
     pub templates: TemplateEngine,
     /// The service's instance-independent
     /// persistent key-value store
-    pub persistence: Persistence,
+    pub persistence: EncryptedPersistence,
+    /// A runtime-adjustable handle to the
+    /// service's tracing subscriber
+    pub tracing: TracingControl,
+    /// The service's (optional) reporter
+    /// of internal errors and panics
+    pub reporter: ErrorReporter,
+    /// The headers and body fields redacted
+    /// from recorded and reported requests
+    pub redaction: Arc<RedactionRules>,
+    /// Running totals of noteworthy
+    /// service events
+    pub metrics: Arc<ServiceMetrics>,
+    /// Periodic samples of the service's metrics
+    pub history: Arc<MetricsHistory>,
+    /// A bounded queue of fire-and-forget
+    /// background jobs
+    pub jobs: JobQueue,
+    /// Background jobs whose status
+    /// (and outcome) can be polled
+    pub tracked_jobs: JobTracker,
+    /// The service's source of the current time
+    pub clock: Arc<dyn Clock>,
+    /// The service's source of randomness
+    pub rng: Arc<dyn RandomSource>,
+    /// Recently analyzed star charts
+    pub charts: Arc<StarChartCache>,
+    /// The credentials guarding the
+    /// service's administrative endpoints
+    pub admin: AdminAuth,
+    /// The (optional) credentials guarding
+    /// the static asset browser
+    pub asset_browser: BasicAuth,
+    /// Known alternative names for recipe ingredients
+    pub aliases: Arc<IngredientAliases>,
+    /// Previously resolved pokemon names and weights
+    pub pokedex: Arc<Pokedex>,
+    /// The clients the service's upstream
+    /// providers are called with
+    pub outbound: OutboundClients,
+    /// The daily quota of requests
+    /// to the geocoding provider
+    pub geocoder: Arc<GeocoderQuota>,
+    /// The (reverse) geocoding provider
+    pub geocoding: Arc<dyn Geocoder>,
+    /// The names countries are reported by
+    pub country_names: Arc<CountryNames>,
+    /// The execution limits applied
+    /// to rendered templates
+    pub template_limits: TemplateLimits,
+    /// The security headers set
+    /// on the service's responses
+    pub security: SecurityHeaders,
+    /// Per-address abuse signals
+    /// and temporary bans
+    pub abuse: Arc<AbuseGuard>,
+    /// The reverse proxies trusted to report
+    /// clients' addresses (in `X-Forwarded-For`)
+    pub proxies: TrustedProxies,
+    /// Curated request/response
+    /// examples, by day
+    pub examples: ExampleCatalog,
+    /// The name, size, and digest
+    /// of every static asset
+    pub assets: Arc<AssetManifest>,
+    /// Recently derived (e.g. resized)
+    /// static asset content
+    pub asset_cache: Arc<AssetCache>,
+    /// The service's in-flight request count
+    /// and load shedding thresholds
+    pub shedder: Arc<LoadShedder>,
+    /// The number of concurrent requests
+    /// allowed for each kind of heavy work
+    pub concurrency: Arc<ConcurrencyLimits>,
+    /// The rate at which each client's
+    /// (expensive) requests are allowed
+    pub rate_limits: Arc<RateLimits>,
+    /// Persistent named counters
+    pub counters: Arc<Counters>,
+    /// The service's recurring maintenance jobs
+    pub scheduler: Arc<Scheduler>,
+    /// The deliverer of recorded events
+    pub outbox: Outbox,
+    /// The caches, counters, and broadcasts
+    /// shared with the service's other instances
+    pub shared: SharedState,
+    /// The signer of session cookies
+    /// and keeper of session data
+    pub sessions: SessionStore,
+    /// What became of the service's
+    /// subsystems as it was initialized
+    pub init: Arc<InitReport>,
 }
 
 //noinspection RsReplaceMatchExpr
 impl ShuttleAppState {
-    /// Initialize the service's state
+    /// Initialize the service's state, failing if a critical subsystem
+    /// can't be initialized (or a non-critical one can't, unless the
+    /// `ALLOW_DEGRADED_START` secret is `true`), and reporting what
+    /// became of each subsystem in its [`init`](Self::init) report
     #[tracing::instrument(skip_all)]
     pub fn initialize(
         db: sqlx::PgPool,
@@ -56,25 +309,134 @@ impl ShuttleAppState {
         templates: Option<TemplateEngine>,
         persistence: Option<Persistence>,
     ) -> anyhow::Result<Self> {
-        Self::_initialize_secrets(secrets);
+        let secrets = Self::_initialize_secrets(secrets);
+
+        let mut init = InitReport::new(&secrets);
+
+        let reporter = ErrorReporter::from_secrets(&secrets);
+
+        let clock: Arc<dyn Clock> = Arc::new(SystemClock);
+
+        let abuse = Arc::new(AbuseGuard::from_secrets(&secrets, clock.clone()));
+
+        let metrics = Arc::new(ServiceMetrics::from_secrets(&secrets));
+
+        let rng: Arc<dyn RandomSource> = Arc::new(StdRandom::from_secrets(&secrets));
+
+        let template_limits = TemplateLimits::from_secrets(&secrets);
+
+        let templates = match templates {
+            Some(templates) => templates,
+            None => {
+                let (templates, count) = init.recover(
+                    "templates",
+                    Self::_default_template_engine(&template_limits),
+                    || (TemplateEngine::from(Handlebars::new()), 0),
+                )?;
+
+                init.template_count = Some(count);
 
-        let chat = Arc::new(ChatRoomState::default());
+                templates
+            }
+        };
 
-        let templates = templates.map_or_else(
-            Self::_default_template_engine,
-            Result::<TemplateEngine, Box<TemplateError>>::Ok,
-        )?;
+        let persistence = match persistence {
+            Some(persistence) => persistence,
+            None => {
+                let path = Self::_default_persistence_path();
 
-        let persistence = persistence.map_or_else(
-            Self::_default_persistence,
-            Result::<Persistence, PersistenceError>::Ok,
-        )?;
+                if get_env_var("CCH23_PERSISTENCE_DIR").is_err() {
+                    init.defaulted("persistence_dir");
+                }
+                init.persistence_path = Some(path.display().to_string());
+
+                Persistence::new(path.clone())
+                    .with_context(|| format!("initializing persistence in {}", path.display()))?
+            }
+        };
+
+        let persistence = EncryptedPersistence::new(persistence, Keyring::from_secrets(&secrets));
+
+        let counters = Arc::new(Counters::new(Some(persistence.clone())));
+
+        let geocoder = Arc::new(GeocoderQuota::from_secrets(
+            &secrets,
+            Some(persistence.clone()),
+        ));
+
+        let shared = SharedState::from_secrets(&secrets);
+
+        let outbound = OutboundClients::from_secrets(&secrets);
+
+        let geocoding = geocoder_from_secrets(&secrets, outbound.geocoder.clone());
+
+        let jobs = JobQueue::default();
+
+        let tracked_jobs = JobTracker::new(jobs.clone(), clock.clone(), Some(persistence.clone()));
+
+        let chat = Arc::new(
+            ChatRoomState::new(
+                ChatArchive::from_secrets(&db, &secrets),
+                clock.clone(),
+                counters.named(VIEWS_COUNTER),
+            )
+            .with_tokens(RoomTokens::from_secrets(&secrets)),
+        );
 
         Ok(Self {
             db,
             chat,
             templates,
             persistence,
+            tracing: TracingControl::default(),
+            reporter,
+            aliases: Arc::new(IngredientAliases::default()),
+            pokedex: Arc::new(
+                Pokedex::new(
+                    metrics.clone(),
+                    shared.is_distributed().then(|| shared.cache.clone()),
+                    clock.clone(),
+                )
+                .with_client(outbound.pokeapi.clone())
+                .with_preload(PreloadMode::from_secrets(&secrets)),
+            ),
+            outbound,
+            metrics,
+            history: Arc::new(MetricsHistory::default()),
+            geocoder,
+            geocoding,
+            country_names: Arc::new(CountryNames::from_secrets(&secrets)),
+            jobs,
+            tracked_jobs,
+            rate_limits: Arc::new(RateLimits::from_secrets(&secrets, clock.clone())),
+            clock,
+            rng,
+            charts: Arc::new(StarChartCache::default()),
+            admin: AdminAuth::from_secrets(&secrets),
+            asset_browser: BasicAuth::from_secrets(&secrets),
+            template_limits,
+            security: SecurityHeaders::from_secrets(&secrets),
+            redaction: Arc::new(RedactionRules::from_secrets(&secrets)),
+            abuse,
+            proxies: TrustedProxies::from_secrets(&secrets, TrustedProxies::default()),
+            examples: init.recover(
+                "examples",
+                ExampleCatalog::load(EXAMPLES_DIR),
+                ExampleCatalog::default,
+            )?,
+            assets: init.recover("assets", AssetManifest::startup(), Arc::default)?,
+            asset_cache: Arc::new(AssetCache::default()),
+            shedder: Arc::new(LoadShedder::from_secrets(&secrets)),
+            concurrency: Arc::new(ConcurrencyLimits::from_secrets(&secrets)),
+            counters,
+            scheduler: Arc::new(Scheduler::from_secrets(&secrets)),
+            outbox: Outbox::from_secrets(&secrets),
+            sessions: SessionStore::from_secrets(&secrets, shared.cache.clone()),
+            shared,
+            init: {
+                init.log();
+                Arc::new(init)
+            },
         })
     }
 
@@ -86,9 +448,14 @@ impl ShuttleAppState {
 
     #[cfg_attr(tarpaulin, coverage(off))]
     #[cfg_attr(tarpaulin, tarpaulin::skip)]
-    fn _default_template_engine() -> Result<TemplateEngine, Box<TemplateError>> {
+    fn _default_template_engine(
+        limits: &TemplateLimits,
+    ) -> Result<(TemplateEngine, usize), Box<TemplateError>> {
         let mut engine = Handlebars::new();
 
+        templating::register_helpers(&mut engine);
+        limits.apply(&mut engine);
+
         if get_env_var("SHUTTLE").is_ok_and(|value| &value == "true") {
             engine.set_dev_mode(true);
         }
@@ -98,8 +465,11 @@ impl ShuttleAppState {
                 ".tpl",
                 FilePathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/assets")),
             )
-            .map_err(Box::from)
-            .map(|_| TemplateEngine::from(engine))
+            .map_err(Box::from)?;
+
+        let count = engine.get_templates().len();
+
+        Ok((TemplateEngine::from(engine), count))
     }
 
     fn _initialize_secrets(secrets: Option<SecretStore>) -> SecretStore {
@@ -117,17 +487,92 @@ impl ShuttleAppState {
 
     #[cfg_attr(tarpaulin, coverage(off))]
     #[cfg_attr(tarpaulin, tarpaulin::skip)]
-    fn _default_persistence() -> anyhow::Result<Persistence, PersistenceError> {
-        let path = get_env_var("CCH23_PERSISTENCE_DIR")
+    fn _default_persistence_path() -> FilePathBuf {
+        get_env_var("CCH23_PERSISTENCE_DIR")
             .map(FilePathBuf::from)
             .unwrap_or(
                 FilePathBuf::from(env!("CARGO_MANIFEST_DIR"))
                     .join(".shuttle-storage")
                     .join("shuttle-persist"),
-            );
-
-        Persistence::new(path)
+            )
     }
 }
 
 // </editor-fold desc="// ShuttleAppState ...">
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::collections::BTreeMap;
+
+    // Third-Party Imports
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use shuttle_secrets::SecretStore;
+
+    // Crate-Level Imports
+    use super::InitReport;
+
+    /// Test that failed (non-critical) subsystems fall
+    /// back to their defaults only if degraded starts
+    /// are allowed, and are reported either way
+    #[rstest]
+    #[case::allowed(Some("true"), true)]
+    #[case::disallowed(Some("false"), false)]
+    #[case::unset(None, false)]
+    #[case::invalid(Some("sure"), false)]
+    #[test_log::test]
+    fn test_degraded_start(#[case] secret: Option<&str>, #[case] allowed: bool) {
+        let secrets =
+            SecretStore::new(BTreeMap::from_iter(secret.map(|value| {
+                ("ALLOW_DEGRADED_START".to_string(), value.to_string().into())
+            })));
+
+        let mut report = InitReport::new(&secrets);
+
+        assert_eq!(allowed, report.allow_degraded);
+        assert_eq!(
+            Some(1),
+            report
+                .recover("examples", Ok::<_, anyhow::Error>(1), || 0)
+                .ok()
+        );
+
+        let outcome = report.recover("examples", Err(anyhow::anyhow!("no examples")), || 0);
+
+        assert_eq!(allowed, report.is_degraded());
+
+        if allowed {
+            assert_eq!(0, outcome.unwrap());
+            assert_eq!(
+                Some("no examples"),
+                report.failures.get("examples").map(String::as_str)
+            );
+            assert!(report.defaulted.contains(&"examples".to_string()));
+        } else {
+            assert_eq!("initializing examples", outcome.unwrap_err().to_string());
+        }
+    }
+
+    /// Test that every unset notable secret (and
+    /// only those) is reported as missing
+    #[rstest]
+    #[test_log::test]
+    fn test_missing_secrets() {
+        let secrets = SecretStore::new(BTreeMap::from([(
+            "ADMIN_TOKEN".to_string(),
+            "hunter2".to_string().into(),
+        )]));
+
+        let report = InitReport::new(&secrets);
+
+        assert!(!report.missing_secrets.contains(&"ADMIN_TOKEN".to_string()));
+        assert!(!report.defaulted.contains(&"admin".to_string()));
+        assert!(report.missing_secrets.contains(&"SESSION_KEY".to_string()));
+        assert!(report.defaulted.contains(&"sessions".to_string()));
+    }
+}
@@ -0,0 +1,352 @@
+//! ## Telemetry
+//!
+//! The service's tracing subscriber, along with the names (and
+//! recording helpers) of the spans and fields shared across days,
+//! so that dashboards can rely on them being stable
+
+// Standard Library Imports
+use core::ops::Not;
+use std::{
+    env::var as get_env_var,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+// Third-Party Imports
+use axum::http::StatusCode;
+use reqwest::{RequestBuilder, Response as UpstreamResponse};
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::{
+    filter::{self, EnvFilter},
+    fmt,
+    layer::SubscriberExt,
+    reload,
+    util::SubscriberInitExt,
+    Layer, Registry,
+};
+
+// Crate-Level Imports
+use self::{
+    fields::{ERROR, UPSTREAM_LATENCY_MS, UPSTREAM_PROVIDER, UPSTREAM_STATUS},
+    spans::UPSTREAM_CALL,
+};
+
+type FilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// The filter directives used when
+/// `RUST_LOG` is unset (or invalid)
+const DEFAULT_DIRECTIVES: &str = "info,shuttle=trace";
+
+// <editor-fold desc="// TracingSettings ...">
+
+/// The service's adjustable tracing settings
+#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TracingSettings {
+    /// `EnvFilter`-style filter directives
+    /// (e.g. `info,cch23_thewondersmith=debug`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    /// The fraction of spans (0.0 - 1.0,
+    /// inclusive) that should be recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampling_rate: Option<f64>,
+}
+
+// </editor-fold desc="// TracingSettings ...">
+
+// <editor-fold desc="// TracingControl ...">
+
+/// A runtime-adjustable handle to the
+/// service's tracing subscriber
+#[derive(Clone, Debug)]
+pub struct TracingControl {
+    /// A handle to the subscriber's (reloadable)
+    /// filter layer, if one has been installed
+    filter: Option<FilterHandle>,
+    /// The currently active filter directives
+    directives: Arc<RwLock<String>>,
+    /// The current span sampling rate (as `f64` bits)
+    sampling_rate: Arc<AtomicU64>,
+    /// The running total of sampling decisions made
+    sampled: Arc<AtomicU64>,
+}
+
+impl Default for TracingControl {
+    fn default() -> Self {
+        Self {
+            filter: None,
+            directives: Arc::new(RwLock::new(Self::_default_directives())),
+            sampling_rate: Arc::new(AtomicU64::new(1.0f64.to_bits())),
+            sampled: Arc::new(AtomicU64::new(0u64)),
+        }
+    }
+}
+
+impl TracingControl {
+    /// Install the service's global tracing subscriber,
+    /// returning a handle that can be used to adjust its
+    /// filter and sampling rate at runtime
+    #[cfg_attr(tarpaulin, coverage(off))]
+    #[cfg_attr(tarpaulin, tarpaulin::skip)]
+    pub fn install() -> anyhow::Result<Self> {
+        let mut control = Self::default();
+
+        let directives = control.directives.read().unwrap().clone();
+        let (filter, handle) = reload::Layer::new(
+            EnvFilter::try_new(&directives).unwrap_or_else(|_| EnvFilter::new(DEFAULT_DIRECTIVES)),
+        );
+
+        let sampler = control.clone();
+
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(
+                fmt::layer()
+                    .without_time()
+                    .with_filter(filter::dynamic_filter_fn(move |metadata, _| {
+                        metadata.is_span().not() || sampler.sample()
+                    })),
+            )
+            .try_init()?;
+
+        control.filter = Some(handle);
+
+        Ok(control)
+    }
+
+    /// Get the currently active tracing settings
+    pub fn settings(&self) -> TracingSettings {
+        TracingSettings {
+            filter: Some(self.directives.read().unwrap().clone()),
+            sampling_rate: Some(f64::from_bits(self.sampling_rate.load(Ordering::Relaxed))),
+        }
+    }
+
+    /// Apply the supplied tracing settings, returning
+    /// the (complete) set of resulting settings
+    pub fn apply(
+        &self,
+        settings: TracingSettings,
+    ) -> Result<TracingSettings, (StatusCode, String)> {
+        if let Some(rate) = settings.sampling_rate {
+            if (0.0f64..=1.0f64).contains(&rate).not() {
+                return Err((
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("sampling rate must be between 0.0 and 1.0, got: {rate}"),
+                ));
+            }
+        }
+
+        if let Some(directives) = settings.filter {
+            let filter = EnvFilter::try_new(&directives)
+                .map_err(|error| (StatusCode::UNPROCESSABLE_ENTITY, error.to_string()))?;
+
+            self.filter
+                .as_ref()
+                .ok_or((
+                    StatusCode::FAILED_DEPENDENCY,
+                    String::from("no reloadable tracing subscriber installed"),
+                ))?
+                .reload(filter)
+                .map_err(|error| (StatusCode::FAILED_DEPENDENCY, error.to_string()))?;
+
+            tracing::info!("tracing filter set to: {directives:?}");
+
+            *self.directives.write().unwrap() = directives;
+        }
+
+        if let Some(rate) = settings.sampling_rate {
+            tracing::info!("span sampling rate set to: {rate}");
+
+            self.sampling_rate.store(rate.to_bits(), Ordering::Relaxed);
+        }
+
+        Ok(self.settings())
+    }
+
+    /// Determine whether the next span should be
+    /// recorded according to the current sampling
+    /// rate (evenly spacing recorded spans rather
+    /// than selecting them at random)
+    pub fn sample(&self) -> bool {
+        let rate = f64::from_bits(self.sampling_rate.load(Ordering::Relaxed));
+        let count = self.sampled.fetch_add(1u64, Ordering::Relaxed) as f64;
+
+        ((count + 1.0) * rate).floor() > (count * rate).floor()
+    }
+
+    fn _default_directives() -> String {
+        get_env_var("RUST_LOG")
+            .ok()
+            .filter(|directives| EnvFilter::try_new(directives).is_ok())
+            .unwrap_or_else(|| DEFAULT_DIRECTIVES.to_string())
+    }
+}
+
+// </editor-fold desc="// TracingControl ...">
+
+// <editor-fold desc="// Span Conventions ...">
+
+/// The names of the spans shared across days
+pub mod spans {
+    /// The handling of a single (HTTP) request, within which every
+    /// other span (and any task the request spawns) is recorded
+    pub const REQUEST: &str = "request";
+
+    /// A query (or statement) against the service's database
+    pub const DB_QUERY: &str = "db.query";
+
+    /// A call to one of the service's upstream providers
+    pub const UPSTREAM_CALL: &str = "upstream.call";
+}
+
+/// The names of the span fields shared across days
+pub mod fields {
+    /// The [`x-request-id`](crate::middleware::REQUEST_ID_HEADER)
+    /// of the request a [`REQUEST`](super::spans::REQUEST) span handles
+    pub const REQUEST_ID: &str = "request_id";
+
+    /// Why the span's work failed (or was refused)
+    pub const ERROR: &str = "error";
+
+    /// Whether the span's result was served from a cache
+    pub const CACHED: &str = "cached";
+
+    /// The (address of the) websocket the span serves
+    pub const SOCKET: &str = "socket";
+
+    /// The database operation a [`DB_QUERY`](super::spans::DB_QUERY)
+    /// span performed (e.g. `"select orders"`)
+    pub const DB_OPERATION: &str = "db.operation";
+
+    /// The number of rows a [`DB_QUERY`](super::spans::DB_QUERY)
+    /// span's operation returned (or affected)
+    pub const DB_ROWS: &str = "db.rows";
+
+    /// The provider an [`UPSTREAM_CALL`](super::spans::UPSTREAM_CALL)
+    /// span called (per [`Provider::name`](crate::outbound::Provider::name))
+    pub const UPSTREAM_PROVIDER: &str = "upstream.provider";
+
+    /// The status of an [`UPSTREAM_CALL`](super::spans::UPSTREAM_CALL)
+    /// span's response
+    pub const UPSTREAM_STATUS: &str = "upstream.status";
+
+    /// How long an [`UPSTREAM_CALL`](super::spans::UPSTREAM_CALL)
+    /// span's call took (in milliseconds)
+    pub const UPSTREAM_LATENCY_MS: &str = "upstream.latency_ms";
+}
+
+/// The number of rows a database operation returned (or affected)
+pub trait RowCount {
+    /// Count the operation's rows
+    fn row_count(&self) -> u64;
+}
+
+impl RowCount for sqlx::postgres::PgQueryResult {
+    fn row_count(&self) -> u64 {
+        self.rows_affected()
+    }
+}
+
+impl<Row> RowCount for Vec<Row> {
+    fn row_count(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+impl<Row> RowCount for Option<Row> {
+    fn row_count(&self) -> u64 {
+        u64::from(self.is_some())
+    }
+}
+
+/// Record the supplied values in the current span's (declared) fields
+/// (preferably named per [`fields`](crate::telemetry::fields)), as in:
+///
+/// ```ignore
+/// record_fields!(fields::CACHED => true, "stars" => stars.len());
+/// ```
+#[macro_export]
+macro_rules! record_fields {
+    ($($field:expr => $value:expr),+ $(,)?) => {{
+        let span = ::tracing::Span::current();
+        $(span.record($field, $value);)+
+    }};
+}
+
+/// Record a database operation (and, once it's succeeded, the number
+/// of rows it returned or affected, per [`RowCount`]) in the current
+/// [`DB_QUERY`](crate::telemetry::spans::DB_QUERY) span, evaluating
+/// to the operation's outcome, as in:
+///
+/// ```ignore
+/// record_db!("select orders", query.fetch_all(db).await)
+/// ```
+#[macro_export]
+macro_rules! record_db {
+    ($operation:expr) => {
+        $crate::record_fields!($crate::telemetry::fields::DB_OPERATION => $operation)
+    };
+    ($operation:expr, $outcome:expr $(,)?) => {{
+        let outcome = $outcome;
+
+        $crate::record_db!($operation);
+
+        match outcome.as_ref() {
+            Ok(rows) => $crate::record_fields!(
+                $crate::telemetry::fields::DB_ROWS => $crate::telemetry::RowCount::row_count(rows)
+            ),
+            Err(error) => $crate::record_fields!(
+                $crate::telemetry::fields::ERROR => ::std::string::ToString::to_string(error)
+            ),
+        }
+
+        outcome
+    }};
+}
+
+/// Record an upstream call's status (or error) and latency (since
+/// `started`) in the current [`UPSTREAM_CALL`](crate::telemetry::spans::UPSTREAM_CALL)
+/// span, evaluating to the call's outcome
+#[macro_export]
+macro_rules! record_upstream {
+    ($started:expr, $outcome:expr $(,)?) => {{
+        let outcome = $outcome;
+
+        $crate::record_fields!(
+            $crate::telemetry::fields::UPSTREAM_LATENCY_MS => $started.elapsed().as_millis() as u64
+        );
+
+        match outcome.as_ref() {
+            Ok(response) => $crate::record_fields!(
+                $crate::telemetry::fields::UPSTREAM_STATUS => response.status().as_u16()
+            ),
+            Err(error) => $crate::record_fields!(
+                $crate::telemetry::fields::ERROR => ::std::string::ToString::to_string(error)
+            ),
+        }
+
+        outcome
+    }};
+}
+
+/// Send the supplied request to the named upstream provider
+/// in an [`UPSTREAM_CALL`] span recording its outcome
+#[tracing::instrument(
+    name = UPSTREAM_CALL,
+    skip_all,
+    fields({UPSTREAM_PROVIDER} = provider, {UPSTREAM_STATUS}, {UPSTREAM_LATENCY_MS}, {ERROR})
+)]
+pub async fn send_upstream(
+    provider: &'static str,
+    request: RequestBuilder,
+) -> reqwest::Result<UpstreamResponse> {
+    let started = tokio::time::Instant::now();
+
+    record_upstream!(started, request.send().await)
+}
+
+// </editor-fold desc="// Span Conventions ...">
@@ -0,0 +1,477 @@
+//! ## Handlebars Template Helpers
+//!
+
+// Standard Library Imports
+use core::str::FromStr;
+
+// Third-Party Imports
+use axum::http::StatusCode;
+use axum_template::{engine::HandlebarsError, TemplateEngine as _};
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Utc,
+};
+use handlebars::{
+    handlebars_helper, Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext,
+    RenderError, ScopedJson,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use shuttle_secrets::SecretStore;
+
+// Crate-Level Imports
+use crate::{errors::ErrorEnvelope, state::TemplateEngine};
+
+/// The format used by `format_date` when none is specified
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The default maximum nesting depth of data rendered by a template
+const DEFAULT_MAX_DEPTH: usize = 32;
+
+// <editor-fold desc="// TemplateLimits ...">
+
+/// How templates render variables missing from their data
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissingVariables {
+    /// render nothing (Handlebars' default)
+    #[default]
+    Empty,
+    /// render the missing variable's name, e.g. `{{name}}`
+    Placeholder,
+}
+
+impl FromStr for MissingVariables {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "empty" => Ok(Self::Empty),
+            "placeholder" => Ok(Self::Placeholder),
+            other => Err(format!("unknown policy: {other}")),
+        }
+    }
+}
+
+/// Execution limits applied to the service's template engine
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TemplateLimits {
+    /// The maximum nesting depth of rendered data (Handlebars
+    /// has no recursion limit of its own, but templates can
+    /// only recurse as deeply as the data they're given)
+    pub max_depth: usize,
+    /// Whether missing variables (and helper
+    /// parameters) are render errors
+    pub strict_mode: bool,
+    /// How missing variables render when
+    /// [strict mode](Self::strict_mode) is off
+    pub missing_variables: MissingVariables,
+}
+
+impl Default for TemplateLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            strict_mode: false,
+            missing_variables: MissingVariables::default(),
+        }
+    }
+}
+
+impl TemplateLimits {
+    /// Read the limits from the service's
+    /// secrets (falling back to the defaults)
+    pub fn from_secrets(secrets: &SecretStore) -> Self {
+        let defaults = Self::default();
+
+        Self {
+            max_depth: Self::_secret(secrets, "TEMPLATE_MAX_DEPTH").unwrap_or(defaults.max_depth),
+            strict_mode: Self::_secret(secrets, "TEMPLATE_STRICT_MODE")
+                .unwrap_or(defaults.strict_mode),
+            missing_variables: Self::_secret(secrets, "TEMPLATE_MISSING_VARIABLES")
+                .unwrap_or(defaults.missing_variables),
+        }
+    }
+
+    fn _secret<T: FromStr>(secrets: &SecretStore, key: &str) -> Option<T>
+    where
+        T::Err: core::fmt::Display,
+    {
+        secrets.get(key).and_then(|value| {
+            value
+                .parse::<T>()
+                .map_err(|error| tracing::error!("ignoring {key} secret: {error}"))
+                .ok()
+        })
+    }
+
+    /// Configure the supplied engine to honor the limits
+    pub fn apply(&self, engine: &mut Handlebars) {
+        engine.set_strict_mode(self.strict_mode);
+
+        if self.missing_variables == MissingVariables::Placeholder {
+            engine.register_helper("helperMissing", Box::new(placeholder));
+        }
+    }
+
+    /// Verify that the supplied data is no
+    /// more deeply nested than allowed
+    pub fn check_depth(&self, data: &Value) -> Result<(), ErrorEnvelope> {
+        let depth = Self::_depth(data);
+
+        if depth > self.max_depth {
+            Err(ErrorEnvelope::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "template data nested too deeply: {depth} > {}",
+                    self.max_depth
+                ),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn _depth(data: &Value) -> usize {
+        match data {
+            Value::Array(items) => 1 + items.iter().map(Self::_depth).max().unwrap_or_default(),
+            Value::Object(fields) => {
+                1 + fields.values().map(Self::_depth).max().unwrap_or_default()
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// Render the named template with the supplied data, within the supplied limits
+pub fn render<Data: Serialize>(
+    engine: &TemplateEngine,
+    limits: &TemplateLimits,
+    name: &str,
+    data: Data,
+) -> Result<String, ErrorEnvelope> {
+    let data = serde_json::to_value(data)
+        .map_err(|error| ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, error))?;
+
+    limits.check_depth(&data)?;
+
+    engine
+        .render(name, data)
+        .map_err(|error| render_failure(&error))
+}
+
+/// Describe the supplied render failure, including
+/// where in the template it occurred (if known)
+pub fn render_failure(error: &HandlebarsError) -> ErrorEnvelope {
+    let HandlebarsError::RenderError(error) = error;
+
+    ErrorEnvelope::new(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "template rendering failed",
+    )
+    .with_details(Some(json!({
+        "template": error.template_name,
+        "line": error.line_no,
+        "column": error.column_no,
+        "reason": error.desc,
+    })))
+}
+
+/// Renders missing variables as (the name of) themselves
+fn placeholder(
+    helper: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    out.write(&format!("{{{{{}}}}}", helper.name()))?;
+
+    Ok(())
+}
+
+// </editor-fold desc="// TemplateLimits ...">
+
+// <editor-fold desc="// TemplateHelper ...">
+
+/// A description of a custom template helper
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct TemplateHelper {
+    /// the helper's name (as used in templates)
+    pub name: &'static str,
+    /// an example of the helper's invocation
+    pub usage: &'static str,
+    /// what the helper does
+    pub description: &'static str,
+}
+
+/// The custom helpers available to every template
+pub const HELPERS: [TemplateHelper; 4] = [
+    TemplateHelper {
+        name: "format_date",
+        usage: r#"{{format_date value format="%Y-%m-%d"}}"#,
+        description: "formats an RFC 3339 timestamp or unix epoch seconds (strftime syntax)",
+    },
+    TemplateHelper {
+        name: "pluralize",
+        usage: r#"{{pluralize count "gift" "gifts"}}"#,
+        description:
+            "picks the singular or plural form of a noun for a count (default plural appends \"s\")",
+    },
+    TemplateHelper {
+        name: "json",
+        usage: "{{{json value pretty=true}}}",
+        description: "serializes a value as JSON (use triple braces to skip HTML escaping)",
+    },
+    TemplateHelper {
+        name: "uppercase",
+        usage: "{{uppercase value}}",
+        description: "converts a string to uppercase",
+    },
+];
+
+/// Register every [custom helper](HELPERS) with the supplied engine
+pub fn register_helpers(engine: &mut Handlebars) {
+    engine.register_helper("format_date", Box::new(FormatDate));
+    engine.register_helper("pluralize", Box::new(pluralize));
+    engine.register_helper("json", Box::new(json));
+    engine.register_helper("uppercase", Box::new(uppercase));
+}
+
+// </editor-fold desc="// TemplateHelper ...">
+
+// <editor-fold desc="// Helpers ...">
+
+/// Formats a timestamp (see [`HELPERS`])
+#[derive(Clone, Copy, Debug)]
+pub struct FormatDate;
+
+impl HelperDef for FormatDate {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        helper: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let timestamp = match helper.param(0).map(|param| param.value()) {
+            Some(Value::String(value)) => DateTime::parse_from_rfc3339(value)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .ok(),
+            Some(Value::Number(value)) => value
+                .as_i64()
+                .and_then(|seconds| DateTime::<Utc>::from_timestamp(seconds, 0)),
+            _ => None,
+        }
+        .ok_or_else(|| RenderError::new("`format_date` helper: unparsable timestamp"))?;
+
+        let format = match helper.hash_get("format").map(|param| param.value()) {
+            Some(Value::String(format)) => format.as_str(),
+            Some(_) => {
+                return Err(RenderError::new(
+                    "`format_date` helper: format must be a string",
+                ))
+            }
+            None => DEFAULT_DATE_FORMAT,
+        };
+
+        let items = StrftimeItems::new(format).collect::<Vec<Item>>();
+
+        if items.contains(&Item::Error) {
+            return Err(RenderError::new(format!(
+                "`format_date` helper: invalid format: {format}"
+            )));
+        }
+
+        Ok(ScopedJson::Derived(Value::String(
+            timestamp.format_with_items(items.into_iter()).to_string(),
+        )))
+    }
+}
+
+handlebars_helper!(pluralize: |count: i64, singular: str, *args| {
+    match (count, args.get(2).and_then(|plural| plural.as_str())) {
+        (1, _) => singular.to_string(),
+        (_, Some(plural)) => plural.to_string(),
+        (_, None) => format!("{singular}s"),
+    }
+});
+
+handlebars_helper!(json: |value: Json, { pretty: bool = false }| {
+    if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+    .unwrap_or_default()
+});
+
+handlebars_helper!(uppercase: |value: str| value.to_uppercase());
+
+// </editor-fold desc="// Helpers ...">
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Standard Library Imports
+    use std::collections::BTreeMap;
+
+    // Third-Party Imports
+    use handlebars::Handlebars;
+    use pretty_assertions::{assert_eq, assert_str_eq};
+    use rstest::rstest;
+    use serde_json::{json, Value};
+    use shuttle_secrets::SecretStore;
+
+    // Crate-Level Imports
+    use super::{register_helpers, render, MissingVariables, TemplateLimits};
+    use crate::state::TemplateEngine;
+
+    /// Build an engine with the supplied
+    /// limits and a single template
+    fn engine(limits: &TemplateLimits, template: &str) -> anyhow::Result<TemplateEngine> {
+        let mut engine = Handlebars::new();
+
+        limits.apply(&mut engine);
+        engine.register_template_string("test", template)?;
+
+        Ok(TemplateEngine::from(engine))
+    }
+
+    /// Test that each custom helper renders as documented
+    #[rstest]
+    #[case::format_date_default(r#"{{format_date "2023-12-14T10:30:00Z"}}"#, "2023-12-14")]
+    #[case::format_date_custom(
+        r#"{{format_date "2023-12-14T10:30:00+02:00" format="%H:%M"}}"#,
+        "08:30"
+    )]
+    #[case::format_date_epoch(r#"{{format_date 1702549800 format="%d/%m/%Y"}}"#, "14/12/2023")]
+    #[case::pluralize_one(r#"{{pluralize 1 "gift"}}"#, "gift")]
+    #[case::pluralize_many(r#"{{pluralize 3 "gift"}}"#, "gifts")]
+    #[case::pluralize_irregular(r#"{{pluralize 0 "elf" "elves"}}"#, "elves")]
+    #[case::json(r#"{{{json gifts}}}"#, r#"["sled","ribbon"]"#)]
+    #[case::json_escaped(r#"{{json gifts}}"#, "[&quot;sled&quot;,&quot;ribbon&quot;]")]
+    #[case::uppercase(r#"{{uppercase "ho ho ho"}}"#, "HO HO HO")]
+    #[test_log::test]
+    fn test_template_helpers(#[case] template: &str, #[case] expected: &str) -> anyhow::Result<()> {
+        let mut engine = Handlebars::new();
+
+        register_helpers(&mut engine);
+
+        let rendered = engine.render_template(template, &json!({"gifts": ["sled", "ribbon"]}))?;
+
+        assert_str_eq!(expected, rendered);
+
+        Ok(())
+    }
+
+    /// Test that `format_date` rejects unusable input
+    #[rstest]
+    #[case::bad_timestamp(r#"{{format_date "yesterday"}}"#)]
+    #[case::bad_format(r#"{{format_date "2023-12-14T10:30:00Z" format="%Q"}}"#)]
+    #[test_log::test]
+    fn test_format_date_rejects_invalid_input(#[case] template: &str) {
+        let mut engine = Handlebars::new();
+
+        register_helpers(&mut engine);
+
+        assert!(engine.render_template(template, &json!({})).is_err());
+    }
+
+    /// Test that `TemplateLimits::from_secrets` reads valid
+    /// secrets and ignores invalid ones
+    #[rstest]
+    #[test_log::test]
+    fn test_limits_from_secrets() {
+        let secrets = SecretStore::new(BTreeMap::from([
+            (String::from("TEMPLATE_MAX_DEPTH"), String::from("4").into()),
+            (
+                String::from("TEMPLATE_STRICT_MODE"),
+                String::from("yes").into(),
+            ),
+            (
+                String::from("TEMPLATE_MISSING_VARIABLES"),
+                String::from("Placeholder").into(),
+            ),
+        ]));
+
+        assert_eq!(
+            TemplateLimits {
+                max_depth: 4,
+                strict_mode: false,
+                missing_variables: MissingVariables::Placeholder,
+            },
+            TemplateLimits::from_secrets(&secrets)
+        );
+    }
+
+    /// Test that each missing-variable policy
+    /// renders (or rejects) missing variables
+    #[rstest]
+    #[case::empty(TemplateLimits::default(), Some("Hello, !"))]
+    #[case::placeholder(
+        TemplateLimits { missing_variables: MissingVariables::Placeholder, ..TemplateLimits::default() },
+        Some("Hello, {{name}}!"),
+    )]
+    #[case::strict(TemplateLimits { strict_mode: true, ..TemplateLimits::default() }, None)]
+    #[test_log::test]
+    fn test_missing_variables(
+        #[case] limits: TemplateLimits,
+        #[case] expected: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let engine = engine(&limits, "Hello, {{name}}!")?;
+
+        assert_eq!(
+            expected.map(String::from),
+            render(&engine, &limits, "test", json!({})).ok()
+        );
+
+        Ok(())
+    }
+
+    /// Test that `render` rejects overly nested data
+    #[rstest]
+    #[case::within_limit(json!({"a": {"b": "c"}}), true)]
+    #[case::beyond_limit(json!({"a": {"b": {"c": "d"}}}), false)]
+    #[case::nested_arrays(json!([[[1]]]), false)]
+    #[test_log::test]
+    fn test_render_depth_limit(#[case] data: Value, #[case] expected: bool) -> anyhow::Result<()> {
+        let limits = TemplateLimits {
+            max_depth: 2,
+            ..TemplateLimits::default()
+        };
+        let engine = engine(&limits, "ok")?;
+
+        assert_eq!(expected, render(&engine, &limits, "test", data).is_ok());
+
+        Ok(())
+    }
+
+    /// Test that render failures report where they occurred
+    #[rstest]
+    #[test_log::test]
+    fn test_render_failure_location() -> anyhow::Result<()> {
+        let limits = TemplateLimits {
+            strict_mode: true,
+            ..TemplateLimits::default()
+        };
+        let engine = engine(&limits, "<p>\n  {{missing}}\n</p>")?;
+
+        let failure = render(&engine, &limits, "test", json!({})).unwrap_err();
+
+        assert_eq!(422, failure.status);
+        assert_eq!(
+            Some(json!({
+                "template": "test",
+                "line": 2,
+                "column": 3,
+                "reason": "Variable \"missing\" not found in strict mode.",
+            })),
+            failure.details
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+//! ## Request Validation
+//!
+
+// Standard Library Imports
+use core::fmt::Debug;
+use std::collections::BTreeMap;
+
+// Third-Party Imports
+use axum::{
+    async_trait,
+    extract::{FromRequestParts, Query},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+// Crate-Level Imports
+use crate::errors::ErrorEnvelope;
+
+// <editor-fold desc="// ValidationErrors ...">
+
+/// Per-field validation failure messages
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ValidationErrors(BTreeMap<String, Vec<String>>);
+
+impl ValidationErrors {
+    /// Record a validation failure for the specified field
+    pub fn add<Field: ToString, Message: ToString>(&mut self, field: Field, message: Message) {
+        self.0
+            .entry(field.to_string())
+            .or_default()
+            .push(message.to_string());
+    }
+
+    /// Check if any validation failures have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Convert the recorded failures (if any) into a `Result`
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl IntoResponse for ValidationErrors {
+    fn into_response(self) -> Response {
+        ErrorEnvelope::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid request parameters",
+        )
+        .with_details(serde_json::to_value(self).ok())
+        .into_response()
+    }
+}
+
+// </editor-fold desc="// ValidationErrors ...">
+
+// <editor-fold desc="// Validate ...">
+
+/// A type whose (deserialized) values
+/// may be semantically invalid
+pub trait Validate {
+    /// Verify that the value is semantically valid,
+    /// returning any per-field failures if not
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+// </editor-fold desc="// Validate ...">
+
+// <editor-fold desc="// ValidatedQuery ...">
+
+/// [`axum` extractor](axum::extract) for query
+/// parameters that must be [validated](Validate)
+/// before they can be used
+#[derive(Debug)]
+pub struct ValidatedQuery<T>(pub T);
+
+#[async_trait]
+impl<State, T> FromRequestParts<State> for ValidatedQuery<T>
+where
+    State: Send + Sync,
+    T: Debug + DeserializeOwned + Validate,
+{
+    type Rejection = Response;
+
+    #[tracing::instrument(err(Debug), skip_all)]
+    async fn from_request_parts(parts: &mut Parts, state: &State) -> Result<Self, Self::Rejection> {
+        let Query(value) = <Query<T> as FromRequestParts<State>>::from_request_parts(parts, state)
+            .await
+            .map_err(|rejection| {
+                ErrorEnvelope::new(StatusCode::UNPROCESSABLE_ENTITY, rejection.body_text())
+                    .into_response()
+            })?;
+
+        value
+            .validate()
+            .map(|()| Self(value))
+            .map_err(IntoResponse::into_response)
+    }
+}
+
+// </editor-fold desc="// ValidatedQuery ...">
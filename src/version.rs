@@ -0,0 +1,163 @@
+//! ## Build & Version Info
+//!
+
+// Third-Party Imports
+use axum::{
+    extract::Json,
+    http::{HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+
+// Crate-Level Imports
+use crate::solutions::DAYS;
+
+/// The service's version, as the crate's version
+/// plus the (short) sha of the commit it was built from
+pub const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "+", env!("VERGEN_GIT_SHA"));
+
+/// The header reporting the [`VERSION`] that served a response
+pub static VERSION_HEADER: HeaderName = HeaderName::from_static("x-cch-version");
+
+/// The header reporting the number of registered solutions modules
+pub static DAY_COUNT_HEADER: HeaderName = HeaderName::from_static("x-cch-day-count");
+
+// <editor-fold desc="// BuildInfo ...">
+
+/// The service's build provenance (as emitted by the build script)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildInfo {
+    /// the crate's version
+    pub version: String,
+    /// the commit the service was built from
+    pub git_sha: String,
+    /// the version of the compiler that built the service
+    pub rustc_version: String,
+    /// when the service was built
+    pub timestamp: String,
+}
+
+impl Default for BuildInfo {
+    fn default() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_sha: env!("VERGEN_GIT_SHA").to_string(),
+            rustc_version: env!("VERGEN_RUSTC_SEMVER").to_string(),
+            timestamp: env!("VERGEN_BUILD_TIMESTAMP").to_string(),
+        }
+    }
+}
+
+// </editor-fold desc="// BuildInfo ...">
+
+// <editor-fold desc="// VersionInfo ...">
+
+/// The deployment serving requests, as reported
+/// by the [version headers](set_version_headers)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VersionInfo {
+    /// see [`VERSION`]
+    pub version: String,
+    /// the number of registered solutions modules
+    pub day_count: usize,
+}
+
+impl Default for VersionInfo {
+    fn default() -> Self {
+        Self {
+            version: VERSION.to_string(),
+            day_count: DAYS.len(),
+        }
+    }
+}
+
+// </editor-fold desc="// VersionInfo ...">
+
+/// Middleware that reports the deployment
+/// serving each request in its response's headers
+pub async fn set_version_headers<B: Send + 'static>(
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+
+    headers.insert(VERSION_HEADER.clone(), HeaderValue::from_static(VERSION));
+    headers.insert(DAY_COUNT_HEADER.clone(), HeaderValue::from(DAYS.len()));
+
+    response
+}
+
+/// Report the deployment serving requests
+#[tracing::instrument(ret)]
+pub async fn get_version() -> Json<VersionInfo> {
+    Json(VersionInfo::default())
+}
+
+#[cfg(test)]
+mod tests {
+    //! ## I/O-free Unit Tests
+
+    #![allow(unused_imports, clippy::unit_arg)]
+
+    // Third-Party Imports
+    use axum::http::StatusCode;
+    use pretty_assertions::{assert_eq, assert_str_eq};
+    use rstest::rstest;
+
+    // Crate-Level Imports
+    use super::{VersionInfo, DAY_COUNT_HEADER, VERSION, VERSION_HEADER};
+    use crate::routes;
+    use crate::solutions::DAYS;
+    use crate::utils::{service, TestService};
+
+    /// Test that every response (including
+    /// errors) carries the version headers
+    #[rstest]
+    #[case::version(routes::version(), StatusCode::OK)]
+    #[case::solution(routes::day_minus_1::root(), StatusCode::OK)]
+    #[case::not_found("/no/such/route", StatusCode::NOT_FOUND)]
+    #[test_log::test(tokio::test)]
+    async fn test_version_headers(
+        service: TestService,
+        #[case] url: &str,
+        #[case] expected_status: StatusCode,
+    ) -> anyhow::Result<()> {
+        let response = service.resolve(url).await?;
+
+        assert_eq!(expected_status, response.status());
+        assert_eq!(
+            Some(VERSION),
+            response
+                .headers()
+                .get(&VERSION_HEADER)
+                .and_then(|value| value.to_str().ok())
+        );
+        assert_eq!(
+            Some(DAYS.len().to_string().as_str()),
+            response
+                .headers()
+                .get(&DAY_COUNT_HEADER)
+                .and_then(|value| value.to_str().ok())
+        );
+
+        Ok(())
+    }
+
+    /// Test that `get_version` reports the
+    /// same version as the version headers
+    #[rstest]
+    #[test_log::test(tokio::test)]
+    async fn test_get_version(service: TestService) -> anyhow::Result<()> {
+        let response = service.resolve(routes::version()).await?;
+        let content = hyper::body::to_bytes(response.into_body()).await?;
+        let info = serde_json::from_slice::<VersionInfo>(&content)?;
+
+        assert_str_eq!(VERSION, info.version);
+        assert_eq!(DAYS.len(), info.day_count);
+        assert!(VERSION.starts_with(env!("CARGO_PKG_VERSION")));
+
+        Ok(())
+    }
+}